@@ -0,0 +1,52 @@
+//! Prints out the RPC messages in a capture file (i.e. one recorded by
+//! `lc3-device-server --capture`).
+//!
+//! Usage: `lc3-capture [--crc] <file>`; `--crc` is for captures of sessions
+//! that use the [CRC trailer](lc3_device_support::rpc::encoding::Crc).
+//!
+//! Captures are expected to use the COBS framed postcard encoding that the
+//! board and the socket transport use. See the [`capture`] module for the
+//! format and for how to replay a capture.
+//!
+//! [`capture`]: lc3_device_support::rpc::transport::capture
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    use lc3_device_support::rpc::encoding::{postcard_crc_decode, Cobs, Crc16, PostcardDecode};
+    use lc3_device_support::rpc::transport::capture::Capture;
+    use lc3_device_support::util::Fifo;
+    use lc3_traits::control::rpc::{RequestEnvelope, ResponseEnvelope};
+
+    use std::fs::File;
+    use std::io::{self, BufReader};
+
+    let mut args = std::env::args().skip(1).peekable();
+    let crc = args.peek().map(String::as_str) == Some("--crc");
+    if crc {
+        args.next();
+    }
+
+    let path = args.next().expect("usage: lc3-capture [--crc] <file>");
+    let file = File::open(&path).unwrap_or_else(|e| panic!("couldn't open `{}`: {}", path, e));
+    let capture = Capture::read(BufReader::new(file))
+        .unwrap_or_else(|e| panic!("couldn't read `{}`: {}", path, e));
+
+    let out = io::stdout().lock();
+    let res = if crc {
+        capture.print(out, postcard_crc_decode::<RequestEnvelope, Crc16>(), postcard_crc_decode::<ResponseEnvelope, Crc16>())
+    } else {
+        capture.print(
+            out,
+            PostcardDecode::<RequestEnvelope, Cobs<Fifo<u8>>>::new(),
+            PostcardDecode::<ResponseEnvelope, Cobs<Fifo<u8>>>::new(),
+        )
+    };
+
+    if let Err(e) = res {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,71 @@
+//! Serves a simulator to remote controllers over TCP or a Unix domain socket
+//! (i.e. so that the TUI can attach to a simulator running on a lab server).
+//!
+//! Usage: `lc3-device-server [--capture <file>] [address]` where the address
+//! is either a TCP socket address (`0.0.0.0:9002`) or `unix:<path>`; it
+//! defaults to `127.0.0.1:9002`.
+//!
+//! With `--capture`, all the RPC traffic is recorded to the given file; see
+//! `lc3-capture` for what to do with it.
+//!
+//! Clients connect with [`SocketDevice`]. The simulator's console is relayed to
+//! and from the connected client.
+//!
+//! [`SocketDevice`]: lc3_application_support::init::SocketDevice
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    use lc3_application_support::event_loop::Backoff;
+    use lc3_application_support::init::{
+        serve_over_socket, BlackBox, Init, SimDevice, DEFAULT_SOCKET_PORT,
+    };
+    use lc3_application_support::io_peripherals::{InputSink, OutputSource};
+    use lc3_device_support::rpc::transport::{
+        capture::{RecordingTransport, Side},
+        socket_host::{HostSocketTransport, SocketAddress},
+    };
+
+    use std::fs::File;
+    use std::io::BufWriter;
+
+    let mut args = std::env::args().skip(1).peekable();
+
+    let capture = if args.peek().map(String::as_str) == Some("--capture") {
+        let path = args.nth(1).expect("`--capture` needs a file");
+        let file = File::create(&path)
+            .unwrap_or_else(|e| panic!("couldn't create `{}`: {}", path, e));
+
+        eprintln!("recording to {}", path);
+        Some(BufWriter::new(file))
+    } else {
+        None
+    };
+
+    let addr = args
+        .next()
+        .unwrap_or_else(|| format!("127.0.0.1:{}", DEFAULT_SOCKET_PORT));
+    let addr: SocketAddress = addr
+        .parse()
+        .unwrap_or_else(|e| panic!("invalid address `{}`: {}", addr, e));
+
+    let transport = HostSocketTransport::listen(addr.clone())
+        .unwrap_or_else(|e| panic!("couldn't listen on `{}`: {}", addr, e));
+    eprintln!("listening on {}", addr);
+
+    let mut b = BlackBox::new();
+    let (sim, _, input, output) = SimDevice::init(&mut b);
+
+    let input = input.map(|i| i as &dyn InputSink);
+    let output = output.map(|o| o as &dyn OutputSource);
+
+    match capture {
+        Some(file) => {
+            let transport = RecordingTransport::new(transport, Side::Device, file);
+            serve_over_socket(sim, transport, input, output, &Backoff::default())
+        }
+        None => serve_over_socket(sim, transport, input, output, &Backoff::default()),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,42 @@
+//! Exposes a simulator over a WebSocket so that a front-end running elsewhere
+//! (i.e. in a browser) can drive it.
+//!
+//! Usage: `lc3-ws-server [address]`; the address defaults to
+//! `127.0.0.1:9001`.
+//!
+//! Clients connect with [`WebSocketDevice`] (or anything else that speaks the
+//! protocol described in [`lc3_application_support::init::websocket`]).
+//! The simulator's console is relayed to the connected client.
+//!
+//! [`WebSocketDevice`]: lc3_application_support::init::WebSocketDevice
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    use lc3_application_support::event_loop::Backoff;
+    use lc3_application_support::init::{
+        BlackBox, Init, SimDevice, WebSocketServer, DEFAULT_PORT,
+    };
+    use lc3_application_support::io_peripherals::{InputSink, OutputSource};
+
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| format!("127.0.0.1:{}", DEFAULT_PORT));
+
+    let server = WebSocketServer::bind(&*addr)
+        .unwrap_or_else(|e| panic!("couldn't bind to `{}`: {}", addr, e));
+    eprintln!("listening on ws://{}", server.local_addr().unwrap());
+
+    let mut b = BlackBox::new();
+    let (sim, _, input, output) = SimDevice::init(&mut b);
+
+    let input = input.map(|i| i as &dyn InputSink);
+    let output = output.map(|o| o as &dyn OutputSource);
+
+    match server.serve(sim, input, output, &Backoff::default()) {
+        Ok(never) => match never {},
+        Err(e) => panic!("failed to accept a connection: {}", e),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
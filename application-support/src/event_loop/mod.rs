@@ -94,8 +94,19 @@
 //! in the main loop, yield to the event loop until a timeout triggers or a
 //! message arrives.
 //!
+//! #### Async Controller
+//!
+//! Applications that are already `async` can skip all of the above on the
+//! controller side and use an [`AsyncController`] instead. Every operation
+//! returns a future that makes progress when it's polled, so there's no tick
+//! function, no backoff, and no thread to block. On `wasm`, give it a
+//! [`Wait`] strategy that wakes futures from a timer (i.e. `setTimeout`) so
+//! that waiting on the device yields to the browser's event loop.
+//!
 //! [`Control`]: `lc3_traits::control::Control`
 //! [`Controller`]: `lc3_traits::control::rpc::Controller`
+//! [`AsyncController`]: `lc3_traits::control::rpc::AsyncController`
+//! [`Wait`]: `lc3_traits::control::rpc::Wait`
 //! [`Control::tick`]: `lc3_traits::control::Control::tick`
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
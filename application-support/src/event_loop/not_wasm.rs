@@ -7,7 +7,7 @@
 use super::Backoff;
 
 use lc3_traits::control::Control;
-use lc3_traits::control::rpc::{RequestMessage, ResponseMessage, Decode, Encode, Transport, Device};
+use lc3_traits::control::rpc::{RequestMessage, ResponseMessage, Envelope, Decode, Encode, Transport, Device};
 
 use std::fmt::Debug;
 use std::sync::mpsc::{Receiver, RecvTimeoutError, TryRecvError};
@@ -125,19 +125,46 @@ impl Backoff {
         Resp: Debug,
         Req: Into<RequestMessage>,
         ResponseMessage: Into<Resp>,
-        D: Decode<Req>,
-        E: Encode<Resp>,
-        T: Transport<<E as Encode<Resp>>::Encoded, <D as Decode<Req>>::Encoded>,
+        D: Decode<Envelope<Req>>,
+        E: Encode<Envelope<Resp>>,
+        T: Transport<<E as Encode<Envelope<Resp>>>::Encoded, <D as Decode<Envelope<Req>>>::Encoded>,
         C: Control,
         <C as Control>::EventFuture: Unpin, // TODO: use `pin_utils::pin_mut!` and relax this requirement. (see rpc::device)
-   {
+    {
+        self.run_step_until(sim, &mut device, |_| false);
+
+        unreachable!()
+    }
+
+    /// Like [`run_step`](Backoff::run_step) but returns once `done` (which is
+    /// checked before every step) says to.
+    ///
+    /// This is useful for devices whose controllers can go away (i.e. a
+    /// WebSocket server whose client disconnected). `done` also gets to use
+    /// the device in between steps (i.e. to push console output).
+    pub fn run_step_until<C, Req, Resp, D, E, T, F>(&self, sim: &mut C, device: &mut Device<T, C, Req, Resp, D, E>, mut done: F)
+    where
+        Req: Debug,
+        Resp: Debug,
+        Req: Into<RequestMessage>,
+        ResponseMessage: Into<Resp>,
+        D: Decode<Envelope<Req>>,
+        E: Encode<Envelope<Resp>>,
+        T: Transport<<E as Encode<Envelope<Resp>>>::Encoded, <D as Decode<Envelope<Req>>>::Encoded>,
+        C: Control,
+        <C as Control>::EventFuture: Unpin,
+        F: FnMut(&mut Device<T, C, Req, Resp, D, E>) -> bool,
+    {
         let mut idle_count = 0;
 
         loop {
-            let count: usize = (0..self.num_iters).map(|_| {
+            let mut count = 0;
+            for _ in 0..self.num_iters {
+                if done(device) { return; }
+
                 let (msgs, insns) = device.step(sim);
-                msgs + insns
-            }).sum();
+                count += msgs + insns;
+            }
 
             if count == 0 { idle_count += 1; } else { idle_count = 0; }
 
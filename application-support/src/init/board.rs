@@ -6,7 +6,8 @@ use crate::shim_support::Shims;
 use lc3_shims::peripherals::SourceShim;
 use lc3_traits::control::rpc::{
     futures::SyncEventFutureSharedState,
-    Controller, RequestMessage, ResponseMessage,
+    Controller, HandshakeError, RequestEnvelope, RequestMessage,
+    ResponseEnvelope, ResponseMessage,
 };
 use lc3_device_support::{
     rpc::{
@@ -18,6 +19,8 @@ use lc3_device_support::{
 
 use std::{
     borrow::Cow,
+    fmt::{self, Display},
+    io::{Error as IoError, Result as IoResult},
     sync::Mutex,
     default::Default,
 };
@@ -37,8 +40,8 @@ type Cont<'ss, EncFunc: FnMut() -> Cobs<Fifo<u8>>> = Controller<
     SyncEventFutureSharedState,
     RequestMessage,
     ResponseMessage,
-    PostcardEncode<RequestMessage, Cobs<Fifo<u8>>, EncFunc>,
-    PostcardDecode<ResponseMessage, Cobs<Fifo<u8>>>,
+    PostcardEncode<RequestEnvelope, Cobs<Fifo<u8>>, EncFunc>,
+    PostcardDecode<ResponseEnvelope, Cobs<Fifo<u8>>>,
 >;
 
 // #[derive(Debug)]
@@ -116,21 +119,83 @@ impl BoardConfig {
 }
 
 impl BoardConfig {
-    fn new_transport(self) -> HostUartTransport {
-        // Note: we unwrap here! This is probably not great!
-        // (TODO)
-        match self.settings {
+    fn new_transport(self) -> IoResult<HostUartTransport> {
+        let transport = match self.settings {
             SerialSettings::DefaultsWithBaudRate { path, baud_rate } => {
-                HostUartTransport::new(path, baud_rate).unwrap()
+                HostUartTransport::new(path, baud_rate)
             },
 
             SerialSettings::Custom(config) => {
-                HostUartTransport::new_with_config(config).unwrap()
+                HostUartTransport::new_with_config(config)
             },
+        };
+
+        transport.map_err(Into::into)
+    }
+}
+
+/// Why [`BoardDevice::try_init_with_config`] failed.
+#[derive(Debug)]
+pub enum BoardInitError {
+    /// Couldn't open the serial port.
+    Open(IoError),
+    /// The board's firmware doesn't speak our version of the protocol.
+    Handshake(HandshakeError<IoError, IoError>),
+}
+
+impl Display for BoardInitError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use BoardInitError::*;
+
+        match self {
+            Open(e) => write!(fmt, "couldn't open the serial port: {}", e),
+            Handshake(e) => write!(fmt, "{}", e),
         }
     }
 }
 
+impl std::error::Error for BoardInitError {}
+
+impl BoardDevice<'static, Box<dyn FnMut() -> Cobs<Fifo<u8>>>> {
+    /// Like [`Init::init_with_config`] but returns errors instead of panicking.
+    pub fn try_init_with_config<'s>(
+        b: &'s mut BlackBox,
+        config: BoardConfig,
+    ) -> Result<
+        (
+            &'s mut Cont<'static, Box<dyn FnMut() -> Cobs<Fifo<u8>>>>,
+            Option<Shims<'static>>,
+            Option<&'s SourceShim>,
+            Option<&'s Mutex<Vec<u8>>>,
+        ),
+        BoardInitError,
+    > {
+        let func: Box<dyn FnMut() -> Cobs<Fifo<u8>>> = Box::new(|| Cobs::try_new(Fifo::new()).unwrap());
+
+        let transport = config.new_transport().map_err(BoardInitError::Open)?;
+
+        let mut controller = Controller::new(
+            PostcardEncode::new(func),
+            PostcardDecode::new(),
+            transport,
+            &*EVENT_FUTURE_SHARED_STATE_CONT
+        );
+
+        // Make sure the board's firmware speaks our protocol before we
+        // hand out the controller:
+        controller.handshake().map_err(BoardInitError::Handshake)?;
+
+        let storage: &'s mut _ = b.put(BoardDevice::<_> { controller });
+
+        Ok((
+            &mut storage.controller,
+            None,
+            None, // TODO
+            None, // TODO
+        ))
+    }
+}
+
 impl<'s> Init<'s> for BoardDevice<'static, Box<dyn FnMut() -> Cobs<Fifo<u8>>>>
 where
     BoardConfig: Default,
@@ -156,22 +221,9 @@ where
         Option<&'s Self::Input>,
         Option<&'s Self::Output>,
     ) {
-        let func: Box<dyn FnMut() -> Cobs<Fifo<u8>>> = Box::new(|| Cobs::try_new(Fifo::new()).unwrap());
-
-        let controller = Controller::new(
-            PostcardEncode::new(func),
-            PostcardDecode::new(),
-            config.new_transport(),
-            &*EVENT_FUTURE_SHARED_STATE_CONT
-        );
-
-        let storage: &'s mut _ = b.put(BoardDevice::<_> { controller });
-
-        (
-            &mut storage.controller,
-            None,
-            None, // TODO
-            None, // TODO
-        )
+        match Self::try_init_with_config(b, config) {
+            Ok(res) => res,
+            Err(e) => panic!("{}", e),
+        }
     }
 }
@@ -45,9 +45,11 @@ pub use websocket::*;
 not_wasm! {
     pub mod board;
     pub mod sim_rpc;
+    pub mod socket;
 
     pub use board::*;
     pub use sim_rpc::*;
+    pub use socket::*;
 }
 
 #[derive(Debug)]
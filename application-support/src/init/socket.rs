@@ -0,0 +1,277 @@
+//! RPC over TCP or Unix domain sockets.
+//!
+//! [`SocketDevice`] is an [`Init`] impl that produces a [`Controller`] that
+//! talks to a simulator served with [`serve_over_socket`] (i.e. by the
+//! `lc3-device-server` binary), possibly on another machine.
+//!
+//! Messages use the same COBS framed postcard encoding that the board uses; see
+//! [`HostSocketTransport`] for the details of the transport (and how it copes
+//! with losing its connection).
+//!
+//! [`Init`]: `Init`
+//! [`Controller`]: `lc3_traits::control::rpc::Controller`
+
+use super::{BlackBox, Init};
+use crate::io_peripherals::{relay_console, InputSink, OutputSource};
+use crate::{event_loop::Backoff, shim_support::Shims};
+
+use lc3_shims::peripherals::{Source, SourceShim};
+use lc3_traits::control::rpc::{
+    futures::SyncEventFutureSharedState, handshake::Features, Controller,
+    Device, HandshakeError, RequestEnvelope, RequestMessage, ResponseEnvelope,
+    ResponseMessage, RpcError, Topics, Transport,
+};
+use lc3_traits::control::Control;
+use lc3_device_support::{
+    rpc::{
+        transport::{
+            capture::RecordingTransport,
+            socket_host::{HostSocketTransport, SocketAddress},
+        },
+        encoding::{PostcardEncode, PostcardDecode, Cobs},
+    },
+    util::Fifo,
+};
+
+use std::fmt::{self, Display};
+use std::io::{Error as IoError, Write};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Mutex;
+
+/// The port [`SocketConfig::default`] and the `lc3-device-server` binary use.
+pub const DEFAULT_SOCKET_PORT: u16 = 9002;
+
+// Static data that we need:
+// TODO: note that, like sim and sim_rpc, this will cause problems if more than
+// 1 instance of this controller is instantiated.
+lazy_static::lazy_static! {
+    pub static ref EVENT_FUTURE_SHARED_STATE_CONT: SyncEventFutureSharedState =
+        SyncEventFutureSharedState::new();
+}
+
+type EncFunc = Box<dyn FnMut() -> Cobs<Fifo<u8>>>;
+
+type Cont<'ss> = Controller<
+    'ss,
+    HostSocketTransport,
+    SyncEventFutureSharedState,
+    RequestMessage,
+    ResponseMessage,
+    PostcardEncode<RequestEnvelope, Cobs<Fifo<u8>>, EncFunc>,
+    PostcardDecode<ResponseEnvelope, Cobs<Fifo<u8>>>,
+>;
+
+fn new_cobs_fifo() -> Cobs<Fifo<u8>> {
+    Cobs::try_new(Fifo::new()).unwrap()
+}
+
+/// Transports that [`serve_over_socket`] can tell have lost their controller.
+pub trait SocketConnection {
+    /// See [`HostSocketTransport::take_lost_connection`].
+    fn take_lost_connection(&self) -> bool;
+}
+
+impl SocketConnection for HostSocketTransport {
+    fn take_lost_connection(&self) -> bool {
+        HostSocketTransport::take_lost_connection(self)
+    }
+}
+
+impl<T: SocketConnection, W: Write> SocketConnection for RecordingTransport<T, W> {
+    fn take_lost_connection(&self) -> bool {
+        self.inner().take_lost_connection()
+    }
+}
+
+/// Serves `control` to [`SocketDevice`]s that connect to `transport` (which
+/// should be [listening](HostSocketTransport::listen)).
+///
+/// One controller is served at a time; when a controller disconnects, `control`
+/// is paused (so that the next controller finds it in a known state) and the
+/// next one to connect takes over.
+///
+/// The console is relayed to and from the controller (see
+/// [`relay_console`]) if `input` and `output` are given.
+///
+/// `transport` is usually a [`HostSocketTransport`] but can be anything that
+/// carries the same frames (i.e. one wrapped in a [`RecordingTransport`]).
+pub fn serve_over_socket<C, T>(
+    control: &mut C,
+    transport: T,
+    input: Option<&dyn InputSink>,
+    output: Option<&dyn OutputSource>,
+    backoff: &Backoff,
+) -> !
+where
+    C: Control,
+    <C as Control>::EventFuture: Unpin,
+    T: Transport<Fifo<u8>, Fifo<u8>> + SocketConnection,
+{
+    let func: EncFunc = Box::new(new_cobs_fifo);
+
+    let mut device = Device::<_, C, RequestMessage, ResponseMessage, _, _>::new(
+        PostcardEncode::<ResponseEnvelope, _, _>::new(func),
+        PostcardDecode::<RequestEnvelope, Cobs<Fifo<u8>>>::new(),
+        transport,
+    );
+
+    loop {
+        backoff.run_step_until(control, &mut device, |d| {
+            relay_console(d, input, output);
+            d.transport.take_lost_connection()
+        });
+
+        // Resolves any `run_until_event` future the controller left behind.
+        control.pause();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketConfig {
+    /// Where the simulator is being served.
+    pub addr: SocketAddress,
+}
+
+impl SocketConfig {
+    pub fn new(addr: SocketAddress) -> Self {
+        Self { addr }
+    }
+}
+
+impl Default for SocketConfig {
+    fn default() -> Self {
+        Self::new(SocketAddress::Tcp(SocketAddr::from((
+            Ipv4Addr::LOCALHOST,
+            DEFAULT_SOCKET_PORT,
+        ))))
+    }
+}
+
+/// Why [`SocketDevice::try_init_with_config`] failed.
+#[derive(Debug)]
+pub enum SocketInitError {
+    /// Couldn't connect to the simulator.
+    Connect(IoError),
+    /// The simulator doesn't speak our version of the protocol.
+    Handshake(HandshakeError<IoError, IoError>),
+    /// Couldn't subscribe to the simulator's console output.
+    Subscribe(RpcError<IoError, IoError>),
+}
+
+impl Display for SocketInitError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SocketInitError::*;
+
+        match self {
+            Connect(e) => write!(fmt, "couldn't connect: {}", e),
+            Handshake(e) => write!(fmt, "{}", e),
+            Subscribe(e) => write!(fmt, "couldn't subscribe to console output: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SocketInitError {}
+
+// The console: what the user types goes to `CONSOLE_INPUT` and is sent to the
+// simulator on `tick`; output the simulator pushes to us ends up in
+// `CONSOLE_OUTPUT`.
+//
+// Like the event future state above, these are shared by every instance.
+lazy_static::lazy_static! {
+    static ref CONSOLE_INPUT: SourceShim = SourceShim::new();
+    static ref CONSOLE_OUTPUT: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+}
+
+fn console_source(buf: &mut [u8]) -> usize {
+    match (buf.first_mut(), CONSOLE_INPUT.get_char()) {
+        (Some(b), Some(c)) => {
+            *b = c;
+            1
+        }
+        _ => 0,
+    }
+}
+
+fn console_sink(bytes: &[u8]) {
+    CONSOLE_OUTPUT.lock().unwrap().extend_from_slice(bytes)
+}
+
+pub struct SocketDevice<'ss> {
+    controller: Cont<'ss>,
+}
+
+impl SocketDevice<'static> {
+    /// Like [`Init::init_with_config`] but returns errors instead of panicking.
+    pub fn try_init_with_config<'s>(
+        b: &'s mut BlackBox,
+        config: SocketConfig,
+    ) -> Result<
+        (
+            &'s mut Cont<'static>,
+            Option<Shims<'static>>,
+            Option<&'s SourceShim>,
+            Option<&'s Mutex<Vec<u8>>>,
+        ),
+        SocketInitError,
+    > {
+        let func: EncFunc = Box::new(new_cobs_fifo);
+
+        let transport = HostSocketTransport::connect(config.addr)
+            .map_err(SocketInitError::Connect)?;
+
+        let mut controller = Controller::new(
+            PostcardEncode::new(func),
+            PostcardDecode::new(),
+            transport,
+            &*EVENT_FUTURE_SHARED_STATE_CONT,
+        )
+        .with_console_sink(&console_sink)
+        .with_console_source(&console_source);
+
+        let features = controller
+            .handshake()
+            .map_err(SocketInitError::Handshake)?;
+
+        // Without notifications the console just doesn't get relayed:
+        if features.contains(Features::NOTIFICATIONS) {
+            controller
+                .subscribe(Topics::CONSOLE)
+                .map_err(SocketInitError::Subscribe)?;
+        }
+
+        let storage: &'s mut _ = b.put(SocketDevice { controller });
+
+        Ok((
+            &mut storage.controller,
+            None,
+            Some(&*CONSOLE_INPUT),
+            Some(&*CONSOLE_OUTPUT),
+        ))
+    }
+}
+
+impl<'s> Init<'s> for SocketDevice<'static> {
+    type Config = SocketConfig;
+
+    type ControlImpl = Cont<'static>;
+
+    // The simulator's I/O happens on the other end of the connection; these
+    // are relayed to it.
+    type Input = SourceShim;
+    type Output = Mutex<Vec<u8>>;
+
+    fn init_with_config(
+        b: &'s mut BlackBox,
+        config: SocketConfig,
+    ) -> (
+        &'s mut Self::ControlImpl,
+        Option<Shims<'static>>,
+        Option<&'s Self::Input>,
+        Option<&'s Self::Output>,
+    ) {
+        match Self::try_init_with_config(b, config) {
+            Ok(res) => res,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
@@ -1,25 +0,0 @@
-//! TODO!
-
-#[derive(Debug)]
-pub struct WebSocketDevice {}
-
-// TODO!
-
-// impl<'s> Init<'s> for WebSocketDevice {
-//     type Config = ();
-
-//     type ControlImpl = !;
-//     type Input = !;
-//     type Output = !;
-
-//     fn init(
-//         b: &'s mut BlackBox,
-//     ) -> (
-//         &'s mut Self::ControlImpl,
-//         Option<Shims<'static>>,
-//         Option<&'s Self::Input>,
-//         Option<&'s Self::Output>,
-//     ) {
-//         unimplemented!();
-//     }
-// }
@@ -0,0 +1,44 @@
+//! RPC over WebSockets.
+//!
+//! Off of WebAssembly this module has both halves of the connection:
+//!   - [`WebSocketDevice`] is an [`Init`] impl that produces a [`Controller`]
+//!     that talks to a simulator (or board) running elsewhere.
+//!   - [`WebSocketServer`] exposes any [`Control`] impl to such controllers.
+//!
+//! Both use a `WebSocketTransport` which carries one RPC message (in an
+//! [`Envelope`]) per WebSocket frame. Messages are [JSON encoded] and sent as
+//! text frames so that front-ends that aren't written in Rust (or that are, but
+//! that run in a browser) can speak the protocol without much fuss.
+//!
+//! On WebAssembly only the client half is available: a `WebSocketTransport`
+//! built on the browser's `WebSocket`. Browsers don't let us block while we
+//! wait for responses so it's meant to be used with an [`AsyncController`]
+//! rather than a [`Controller`].
+//!
+//! Console I/O goes over the connection too: the server pushes what its
+//! output peripheral produces to the client (as [console notifications]) and
+//! feeds the client's console input to its input peripheral.
+//!
+//! Only plain `ws://` URLs are supported (no TLS).
+//!
+//! [`Init`]: `super::Init`
+//! [`Envelope`]: `lc3_traits::control::rpc::Envelope`
+//! [`Control`]: `lc3_traits::control::Control`
+//! [`Controller`]: `lc3_traits::control::rpc::Controller`
+//! [`AsyncController`]: `lc3_traits::control::rpc::AsyncController`
+//! [JSON encoded]: `lc3_traits::control::rpc::encoding::JsonEncoding`
+//! [console notifications]: `lc3_traits::control::rpc::notifications`
+
+/// The port `WebSocketConfig::default` and the server binary use.
+pub const DEFAULT_PORT: u16 = 9001;
+
+specialize! {
+    wasm: {
+        mod wasm;
+        pub use wasm::*;
+    }
+    not: {
+        mod not_wasm;
+        pub use not_wasm::*;
+    }
+}
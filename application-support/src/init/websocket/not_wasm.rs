@@ -0,0 +1,475 @@
+//! The WebSocket client and server for platforms with threads and sockets (not
+//! WebAssembly).
+
+use super::DEFAULT_PORT;
+use crate::init::{BlackBox, Init};
+use crate::io_peripherals::{relay_console, InputSink, OutputSource};
+use crate::{event_loop::Backoff, shim_support::Shims};
+
+use lc3_shims::peripherals::{Source, SourceShim};
+use lc3_traits::control::rpc::{
+    encoding::JsonEncoding, futures::SyncEventFutureSharedState,
+    handshake::Features, Controller, Device,
+    HandshakeError as RpcHandshakeError, RequestMessage, ResponseMessage,
+    RpcError, Topics, Transport,
+};
+use lc3_traits::control::{version_from_crate, Control, Identifier, Version};
+
+use tungstenite::client::IntoClientRequest;
+use tungstenite::error::{ProtocolError, UrlError};
+use tungstenite::handshake::{HandshakeError, HandshakeRole};
+use tungstenite::{Message, WebSocket};
+
+pub use tungstenite::Error as WebSocketError;
+
+use std::cell::{Cell, RefCell};
+use std::convert::Infallible;
+use std::fmt::{self, Display};
+use std::io::{ErrorKind, Result as IoResult};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+
+/// A [`Transport`] that sends and receives (JSON encoded) messages over a
+/// WebSocket.
+///
+/// The same type is used on both ends of the connection: controllers
+/// [`connect`](WebSocketTransport::connect) and servers
+/// [`accept`](WebSocketTransport::accept).
+///
+/// Once the other end of the connection goes away, [`get`](Transport::get)
+/// returns errors and [`send`](Transport::send) quietly discards messages;
+/// [`Device`] panics on send errors and the server needs its `Device` to
+/// outlive its client long enough to notice that the client is gone (see
+/// [`is_closed`](WebSocketTransport::is_closed)).
+#[derive(Debug)]
+pub struct WebSocketTransport {
+    socket: RefCell<WebSocket<TcpStream>>,
+    closed: Cell<bool>,
+    num_get_errors: Cell<u64>,
+}
+
+fn handshake_err<R: HandshakeRole>(err: HandshakeError<R>) -> WebSocketError {
+    match err {
+        HandshakeError::Failure(err) => err,
+        // We only make sockets non-blocking once the handshake is done.
+        HandshakeError::Interrupted(_) => unreachable!(),
+    }
+}
+
+fn is_disconnect(err: &WebSocketError) -> bool {
+    use WebSocketError::*;
+
+    match err {
+        ConnectionClosed | AlreadyClosed => true,
+        Protocol(ProtocolError::ResetWithoutClosingHandshake) => true,
+        Io(err) => matches!(
+            err.kind(),
+            ErrorKind::BrokenPipe
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::UnexpectedEof
+        ),
+        _ => false,
+    }
+}
+
+impl WebSocketTransport {
+    /// Connects to the WebSocket server at `url` (i.e. `ws://127.0.0.1:9001`).
+    ///
+    /// This blocks until the handshake is done.
+    pub fn connect(url: &str) -> Result<Self, WebSocketError> {
+        let request = url.into_client_request()?;
+        let uri = request.uri();
+
+        if uri.scheme_str() != Some("ws") {
+            return Err(WebSocketError::Url(UrlError::UnsupportedUrlScheme));
+        }
+
+        let host = uri
+            .host()
+            .ok_or(WebSocketError::Url(UrlError::NoHostName))?
+            .to_string();
+        let port = uri.port_u16().unwrap_or(80);
+
+        let stream = TcpStream::connect((host.as_str(), port))?;
+        let (socket, _) =
+            tungstenite::client(request, stream).map_err(handshake_err)?;
+
+        Self::new(socket)
+    }
+
+    /// Does the server side of the handshake on a freshly accepted connection.
+    ///
+    /// This blocks until the handshake is done.
+    pub fn accept(stream: TcpStream) -> Result<Self, WebSocketError> {
+        let socket = tungstenite::accept(stream).map_err(handshake_err)?;
+
+        Self::new(socket)
+    }
+
+    fn new(socket: WebSocket<TcpStream>) -> Result<Self, WebSocketError> {
+        // `Transport::get` must not block:
+        socket.get_ref().set_nonblocking(true)?;
+        socket.get_ref().set_nodelay(true)?;
+
+        Ok(Self {
+            socket: RefCell::new(socket),
+            closed: Cell::new(false),
+            num_get_errors: Cell::new(0),
+        })
+    }
+
+    /// Whether the other end of the connection has gone away.
+    pub fn is_closed(&self) -> bool {
+        self.closed.get()
+    }
+}
+
+impl Transport<String, String> for WebSocketTransport {
+    type RecvErr = WebSocketError;
+    type SendErr = WebSocketError;
+
+    const ID: Identifier =
+        Identifier::new_from_str_that_crashes_on_invalid_inputs("WSKT");
+    const VER: Version = {
+        let ver = version_from_crate!();
+
+        let id =
+            Identifier::new_from_str_that_crashes_on_invalid_inputs("host");
+
+        Version::new(ver.major, ver.minor, ver.patch, Some(id))
+    };
+
+    fn send(&self, message: String) -> Result<(), WebSocketError> {
+        if self.closed.get() {
+            return Ok(());
+        }
+
+        let mut socket = self.socket.borrow_mut();
+
+        // The message is queued even if writing it out would block; when that
+        // happens we switch the socket to blocking mode until it's flushed out
+        // (`get` is the only thing that has to not block).
+        let res = match socket.write_message(Message::Text(message)) {
+            Err(WebSocketError::Io(ref err)) if err.kind() == ErrorKind::WouldBlock => {
+                socket.get_ref().set_nonblocking(false)?;
+                let res = socket.write_pending();
+                socket.get_ref().set_nonblocking(true)?;
+
+                res
+            }
+            res => res,
+        };
+
+        match res {
+            Err(ref err) if is_disconnect(err) => {
+                self.closed.set(true);
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    fn get(&self) -> Result<String, Option<WebSocketError>> {
+        if self.closed.get() {
+            return Err(Some(WebSocketError::AlreadyClosed));
+        }
+
+        let mut socket = self.socket.borrow_mut();
+
+        loop {
+            match socket.read_message() {
+                Ok(Message::Text(message)) => break Ok(message),
+
+                // We only speak text; count anything else as a bad message
+                // and move on:
+                Ok(Message::Binary(_)) => {
+                    self.num_get_errors.set(self.num_get_errors.get() + 1);
+                }
+
+                // Pings are answered (and closes acknowledged) by `tungstenite`
+                // for us.
+                Ok(Message::Ping(_))
+                | Ok(Message::Pong(_))
+                | Ok(Message::Close(_))
+                | Ok(Message::Frame(_)) => {}
+
+                Err(WebSocketError::Io(ref err))
+                    if err.kind() == ErrorKind::WouldBlock =>
+                {
+                    break Err(None)
+                }
+
+                Err(err) => {
+                    if is_disconnect(&err) {
+                        self.closed.set(true);
+                    }
+
+                    break Err(Some(err));
+                }
+            }
+        }
+    }
+
+    fn num_get_errors(&self) -> u64 {
+        self.num_get_errors.get()
+    }
+
+    fn record_get_error(&self) {
+        self.num_get_errors.set(self.num_get_errors.get() + 1);
+    }
+}
+
+impl Drop for WebSocketTransport {
+    fn drop(&mut self) {
+        if !self.closed.get() {
+            // Let the other end know we're going away. If this fails (or
+            // would block) the other end will find out when the connection
+            // is reset instead.
+            let _ = self.socket.get_mut().close(None);
+        }
+    }
+}
+
+/// Exposes a [`Control`] impl to [`WebSocketTransport`] clients (i.e.
+/// [`WebSocketDevice`]).
+///
+/// Clients are served one at a time, in the order they connect in. When a
+/// client disconnects the [`Control`] impl is paused so that the next client
+/// finds it in a known state.
+///
+/// If given the [`Control`] impl's console (the [`InputSink`] and
+/// [`OutputSource`] that [`Init`] impls hand out), the server pushes console
+/// output to clients that subscribe to it and feeds the input clients send to
+/// the input peripheral, one character at a time.
+///
+/// [`Control`]: `lc3_traits::control::Control`
+#[derive(Debug)]
+pub struct WebSocketServer {
+    listener: TcpListener,
+}
+
+impl WebSocketServer {
+    pub fn bind(addr: impl ToSocketAddrs) -> IoResult<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+        })
+    }
+
+    pub fn local_addr(&self) -> IoResult<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves clients, one after another, forever.
+    ///
+    /// Clients that fail the WebSocket handshake are ignored; this only returns
+    /// if accepting connections fails.
+    pub fn serve<C: Control>(
+        &self,
+        control: &mut C,
+        input: Option<&dyn InputSink>,
+        output: Option<&dyn OutputSource>,
+        backoff: &Backoff,
+    ) -> IoResult<Infallible>
+    where
+        <C as Control>::EventFuture: Unpin,
+    {
+        loop {
+            let (stream, _) = self.listener.accept()?;
+
+            if let Ok(transport) = WebSocketTransport::accept(stream) {
+                Self::serve_client(control, transport, input, output, backoff);
+            }
+        }
+    }
+
+    /// Serves one client until it disconnects.
+    pub fn serve_client<C: Control>(
+        control: &mut C,
+        transport: WebSocketTransport,
+        input: Option<&dyn InputSink>,
+        output: Option<&dyn OutputSource>,
+        backoff: &Backoff,
+    ) where
+        <C as Control>::EventFuture: Unpin,
+    {
+        let mut device = Device::<_, C, RequestMessage, ResponseMessage, _, _>::new(
+            JsonEncoding,
+            JsonEncoding,
+            transport,
+        );
+
+        backoff.run_step_until(control, &mut device, |device| {
+            relay_console(device, input, output);
+            device.transport.is_closed()
+        });
+
+        // Resolves any `run_until_event` future the client left behind; the
+        // device has to collect it (the response goes nowhere) before it goes
+        // away or the next client can't start a new one.
+        control.pause();
+        device.step(control);
+    }
+}
+
+// Static data that we need:
+// TODO: note that this will cause problems if more than 1 instance of this
+// controller is instantiated.
+lazy_static::lazy_static! {
+    pub static ref EVENT_FUTURE_SHARED_STATE_CONT: SyncEventFutureSharedState =
+        SyncEventFutureSharedState::new();
+}
+
+type Cont<'ss> = Controller<
+    'ss,
+    WebSocketTransport,
+    SyncEventFutureSharedState,
+    RequestMessage,
+    ResponseMessage,
+    JsonEncoding,
+    JsonEncoding,
+>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSocketConfig {
+    /// The URL of the [`WebSocketServer`] to connect to.
+    pub url: String,
+}
+
+impl WebSocketConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self::new(format!("ws://127.0.0.1:{}", DEFAULT_PORT))
+    }
+}
+
+/// Why [`WebSocketDevice::try_init_with_config`] failed.
+#[derive(Debug)]
+pub enum WebSocketInitError {
+    /// Couldn't connect to the server (or the WebSocket handshake failed).
+    Connect(WebSocketError),
+    /// The server doesn't speak our version of the protocol.
+    Handshake(RpcHandshakeError<WebSocketError, WebSocketError>),
+    /// Couldn't subscribe to the server's console output.
+    Subscribe(RpcError<WebSocketError, WebSocketError>),
+}
+
+impl Display for WebSocketInitError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use WebSocketInitError::*;
+
+        match self {
+            Connect(e) => write!(fmt, "couldn't connect: {}", e),
+            Handshake(e) => write!(fmt, "{}", e),
+            Subscribe(e) => write!(fmt, "couldn't subscribe to console output: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WebSocketInitError {}
+
+// The console: what the user types goes to `CONSOLE_INPUT` and is sent to the
+// server on `tick`; output the server pushes to us ends up in `CONSOLE_OUTPUT`.
+//
+// Like the event future state above, these are shared by every instance.
+lazy_static::lazy_static! {
+    static ref CONSOLE_INPUT: SourceShim = SourceShim::new();
+    static ref CONSOLE_OUTPUT: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+}
+
+fn console_source(buf: &mut [u8]) -> usize {
+    match (buf.first_mut(), CONSOLE_INPUT.get_char()) {
+        (Some(b), Some(c)) => {
+            *b = c;
+            1
+        }
+        _ => 0,
+    }
+}
+
+fn console_sink(bytes: &[u8]) {
+    CONSOLE_OUTPUT.lock().unwrap().extend_from_slice(bytes)
+}
+
+pub struct WebSocketDevice<'ss> {
+    controller: Cont<'ss>,
+}
+
+impl WebSocketDevice<'static> {
+    /// Like [`Init::init_with_config`] but returns errors instead of panicking.
+    pub fn try_init_with_config<'s>(
+        b: &'s mut BlackBox,
+        config: WebSocketConfig,
+    ) -> Result<
+        (
+            &'s mut Cont<'static>,
+            Option<Shims<'static>>,
+            Option<&'s SourceShim>,
+            Option<&'s Mutex<Vec<u8>>>,
+        ),
+        WebSocketInitError,
+    > {
+        let transport = WebSocketTransport::connect(&config.url)
+            .map_err(WebSocketInitError::Connect)?;
+
+        let mut controller = Controller::new(
+            JsonEncoding,
+            JsonEncoding,
+            transport,
+            &*EVENT_FUTURE_SHARED_STATE_CONT,
+        )
+        .with_console_sink(&console_sink)
+        .with_console_source(&console_source);
+
+        let features = controller
+            .handshake()
+            .map_err(WebSocketInitError::Handshake)?;
+
+        // Without notifications the console just doesn't get relayed:
+        if features.contains(Features::NOTIFICATIONS) {
+            controller
+                .subscribe(Topics::CONSOLE)
+                .map_err(WebSocketInitError::Subscribe)?;
+        }
+
+        let storage: &'s mut _ = b.put(WebSocketDevice { controller });
+
+        Ok((
+            &mut storage.controller,
+            None,
+            Some(&*CONSOLE_INPUT),
+            Some(&*CONSOLE_OUTPUT),
+        ))
+    }
+}
+
+impl<'s> Init<'s> for WebSocketDevice<'static> {
+    type Config = WebSocketConfig;
+
+    type ControlImpl = Cont<'static>;
+
+    // The simulator's I/O happens on the other end of the connection; these
+    // are relayed to it.
+    type Input = SourceShim;
+    type Output = Mutex<Vec<u8>>;
+
+    fn init_with_config(
+        b: &'s mut BlackBox,
+        config: WebSocketConfig,
+    ) -> (
+        &'s mut Self::ControlImpl,
+        Option<Shims<'static>>,
+        Option<&'s Self::Input>,
+        Option<&'s Self::Output>,
+    ) {
+        match Self::try_init_with_config(b, config) {
+            Ok(res) => res,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! The WebSocket client for WebAssembly, built on the browser's `WebSocket`.
+
+use lc3_traits::control::rpc::Transport;
+use lc3_traits::control::{version_from_crate, Identifier, Version};
+
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{CloseEvent, MessageEvent, WebSocket};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// Errors from the browser's `WebSocket` API.
+pub type WebSocketError = JsValue;
+
+// Shared with the event handlers.
+#[derive(Debug, Default)]
+struct Shared {
+    received: VecDeque<String>,
+    // Messages sent before the connection was open.
+    unsent: Vec<String>,
+    num_get_errors: u64,
+    closed: bool,
+}
+
+/// A [`Transport`] that sends and receives (JSON encoded) messages over a
+/// browser `WebSocket`; the counterpart of the `WebSocketTransport` that the
+/// server uses on other platforms.
+///
+/// Messages arrive through the browser's event loop so nothing shows up in
+/// [`get`](Transport::get) until the thread has yielded to it; pair this with
+/// an [`AsyncController`] and a [`Wait`] strategy that yields.
+///
+/// Messages sent before the connection is open are queued up and sent once it
+/// is. Once the connection closes, [`get`](Transport::get) returns errors and
+/// [`send`](Transport::send) quietly discards messages (see
+/// [`is_closed`](WebSocketTransport::is_closed)).
+///
+/// [`AsyncController`]: `lc3_traits::control::rpc::AsyncController`
+/// [`Wait`]: `lc3_traits::control::rpc::Wait`
+#[derive(Debug)]
+pub struct WebSocketTransport {
+    socket: WebSocket,
+    shared: Rc<RefCell<Shared>>,
+    // The event handlers only live for as long as these do:
+    _on_open: Closure<dyn FnMut()>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Closure<dyn FnMut(CloseEvent)>,
+}
+
+impl WebSocketTransport {
+    /// Starts connecting to the WebSocket server at `url` (i.e.
+    /// `ws://127.0.0.1:9001`).
+    ///
+    /// This doesn't wait for the connection to open; errors are only returned
+    /// for bad URLs.
+    pub fn connect(url: &str) -> Result<Self, WebSocketError> {
+        let socket = WebSocket::new(url)?;
+        let shared = Rc::new(RefCell::new(Shared::default()));
+
+        let on_open = {
+            let (socket, shared) = (socket.clone(), shared.clone());
+            Closure::wrap(Box::new(move || {
+                let mut shared = shared.borrow_mut();
+
+                for message in core::mem::take(&mut shared.unsent) {
+                    if socket.send_with_str(&message).is_err() {
+                        shared.closed = true;
+                        break;
+                    }
+                }
+            }) as Box<dyn FnMut()>)
+        };
+
+        let on_message = {
+            let shared = shared.clone();
+            Closure::wrap(Box::new(move |event: MessageEvent| {
+                let mut shared = shared.borrow_mut();
+
+                // We only speak text; count anything else as a bad message:
+                match event.data().as_string() {
+                    Some(message) => shared.received.push_back(message),
+                    None => shared.num_get_errors += 1,
+                }
+            }) as Box<dyn FnMut(MessageEvent)>)
+        };
+
+        let on_close = {
+            let shared = shared.clone();
+            Closure::wrap(Box::new(move |_: CloseEvent| {
+                shared.borrow_mut().closed = true;
+            }) as Box<dyn FnMut(CloseEvent)>)
+        };
+
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Ok(Self {
+            socket,
+            shared,
+            _on_open: on_open,
+            _on_message: on_message,
+            _on_close: on_close,
+        })
+    }
+
+    /// Whether the connection has closed (or failed to open).
+    pub fn is_closed(&self) -> bool {
+        self.shared.borrow().closed
+    }
+}
+
+impl Transport<String, String> for WebSocketTransport {
+    type RecvErr = WebSocketError;
+    type SendErr = WebSocketError;
+
+    const ID: Identifier =
+        Identifier::new_from_str_that_crashes_on_invalid_inputs("WSKT");
+    const VER: Version = {
+        let ver = version_from_crate!();
+
+        let id =
+            Identifier::new_from_str_that_crashes_on_invalid_inputs("wasm");
+
+        Version::new(ver.major, ver.minor, ver.patch, Some(id))
+    };
+
+    fn send(&self, message: String) -> Result<(), WebSocketError> {
+        match self.socket.ready_state() {
+            WebSocket::CONNECTING => {
+                self.shared.borrow_mut().unsent.push(message);
+                Ok(())
+            }
+            WebSocket::OPEN => self.socket.send_with_str(&message),
+            _ => Ok(()),
+        }
+    }
+
+    fn get(&self) -> Result<String, Option<WebSocketError>> {
+        let mut shared = self.shared.borrow_mut();
+
+        match shared.received.pop_front() {
+            Some(message) => Ok(message),
+            None if shared.closed => Err(Some(JsValue::from_str("connection closed"))),
+            None => Err(None),
+        }
+    }
+
+    fn num_get_errors(&self) -> u64 {
+        self.shared.borrow().num_get_errors
+    }
+
+    fn record_get_error(&self) {
+        self.shared.borrow_mut().num_get_errors += 1;
+    }
+}
+
+impl Drop for WebSocketTransport {
+    fn drop(&mut self) {
+        // The handlers are about to go away:
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+
+        let _ = self.socket.close();
+    }
+}
@@ -5,6 +5,7 @@
 //! [`Output`]: `lc3_traits::peripherals::Output`
 
 use lc3_shims::peripherals::SourceShim;
+use lc3_traits::control::rpc::Console;
 
 use std::sync::{Arc, Mutex};
 
@@ -150,3 +151,26 @@ impl<O: OutputSource> OutputSource for Arc<O> {
         O::get_chars(self)
     }
 }
+
+/// Relays a simulator's console to and from the controllers of a [`Device`] or
+/// a [`Hub`]: whatever's been written to `output` is pushed to the controllers
+/// that have subscribed to it and `input` is handed one character of the
+/// console input they've sent (the input peripheral only holds one).
+///
+/// Device servers should call this between steps.
+///
+/// [`Device`]: lc3_traits::control::rpc::Device
+/// [`Hub`]: lc3_traits::control::rpc::Hub
+pub fn relay_console(
+    device: &mut impl Console,
+    input: Option<&dyn InputSink>,
+    output: Option<&dyn OutputSource>,
+) {
+    if let Some(chars) = output.and_then(|o| o.get_chars()) {
+        device.push_console(chars.as_bytes());
+    }
+
+    if let (Some(input), Some(c)) = (input, device.take_console_input()) {
+        let _ = input.put_char(c as char);
+    }
+}
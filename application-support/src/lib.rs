@@ -47,4 +47,5 @@ macro_rules! specialize {
 pub mod event_loop;
 pub mod init;
 pub mod io_peripherals;
+pub mod scenario;
 pub mod shim_support;
@@ -0,0 +1,314 @@
+//! Scripted 'virtual hardware' for the shim peripherals.
+//!
+//! A [`Scenario`] describes what the outside world does to a simulated board:
+//! "at t=50ms raise G3, at t=120ms set A0 to 200, every 10ms type 'a'". A
+//! [`ScenarioPlayer`] plays a scenario against a set of [`Shims`] (and an
+//! [`InputSink`] for typed characters) and records what the simulated program
+//! does with the GPIO and PWM outputs in the meantime, so that lab exercises
+//! and tests can make assertions about the resulting waveforms.
+//!
+//! Scenarios are written in JSON. Times are in milliseconds from the start of
+//! the scenario; events that happen at the same time are applied in the order
+//! they're listed in:
+//!
+//! ```json
+//! {
+//!   "events": [
+//!     { "at": 50, "gpio": { "pin": "G3", "level": true } },
+//!     { "at": 120, "adc": { "pin": "A0", "value": 200 } },
+//!     { "at": 0, "every": 10, "until": 100, "input": { "chars": "a" } }
+//!   ]
+//! }
+//! ```
+//!
+//! The player doesn't keep time itself; it is [advanced](ScenarioPlayer::advance_to)
+//! to a point in time by whoever is driving it. This means the same scenario
+//! can be played in real time (`ScenarioPlayer::play_in_real_time`; not on
+//! wasm) or in whatever virtual time the caller has available (i.e. time
+//! derived from the number of instructions the simulator has executed), which
+//! is what makes scenarios usable in deterministic tests.
+
+use crate::io_peripherals::InputSink;
+use crate::shim_support::Shims;
+
+use lc3_traits::peripherals::adc::AdcPin;
+use lc3_traits::peripherals::gpio::{Gpio, GpioPin, GpioPinArr, GpioState, GPIO_PINS};
+use lc3_traits::peripherals::pwm::{Pwm, PwmDutyCycle, PwmPeriod, PwmPin, PwmPinArr, PwmState, PWM_PINS};
+
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::num::NonZeroU64;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+/// Something the outside world does to the simulated board.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Drives a GPIO pin (that's in input or interrupt mode) to a level.
+    Gpio { pin: GpioPin, level: bool },
+    /// Sets the value an (enabled) ADC pin reads.
+    Adc { pin: AdcPin, value: u8 },
+    /// Types characters into the input peripheral.
+    ///
+    /// The characters are all put into the [`InputSink`] at once; sinks that
+    /// only hold one character (like [`SourceShim`]) will only keep the last
+    /// one, so use one character per event with those.
+    ///
+    /// [`SourceShim`]: lc3_shims::peripherals::SourceShim
+    Input { chars: String },
+}
+
+/// An [`Action`] and when it happens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    /// When the event (first) happens, in milliseconds.
+    pub at: u64,
+    /// If present, the event repeats with this period (in milliseconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every: Option<NonZeroU64>,
+    /// If present, a repeating event stops repeating after this time (in
+    /// milliseconds). Otherwise repeating events go on forever.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<u64>,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+/// A script of [`Event`]s; see the [module docs](self) for the format.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub events: Vec<Event>,
+}
+
+impl Scenario {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// The values a signal took on over time.
+///
+/// Only changes are stored: each entry is the time at which the signal took on
+/// a new value and that value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Waveform<T> {
+    transitions: Vec<(Duration, T)>,
+}
+
+impl<T> Default for Waveform<T> {
+    fn default() -> Self {
+        Self { transitions: Vec::new() }
+    }
+}
+
+impl<T: PartialEq> Waveform<T> {
+    fn record(&mut self, time: Duration, value: T) {
+        if self.transitions.last().map(|(_, v)| *v != value).unwrap_or(true) {
+            self.transitions.push((time, value));
+        }
+    }
+
+    /// The value the signal had at the given time; `None` if the signal hadn't
+    /// been sampled yet.
+    pub fn value_at(&self, time: Duration) -> Option<&T> {
+        self.transitions
+            .iter()
+            .take_while(|(t, _)| *t <= time)
+            .last()
+            .map(|(_, v)| v)
+    }
+
+    pub fn transitions(&self) -> &[(Duration, T)] {
+        &self.transitions
+    }
+}
+
+/// What a PWM pin is putting out.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PwmOutput {
+    /// `None` when the pin is disabled.
+    pub period: Option<PwmPeriod>,
+    pub duty_cycle: PwmDutyCycle,
+}
+
+/// What a [`ScenarioPlayer`] saw while playing a [`Scenario`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    /// The level of each GPIO pin that's in [output mode](GpioState::Output);
+    /// `None` when the pin isn't an output.
+    pub gpio: GpioPinArr<Waveform<Option<bool>>>,
+    /// The period and duty cycle of each PWM pin.
+    pub pwm: PwmPinArr<Waveform<PwmOutput>>,
+    /// Actions that couldn't be applied (i.e. driving a GPIO pin that's an
+    /// output or setting the value of a disabled ADC pin) and when.
+    pub failures: Vec<(Duration, Action)>,
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Self {
+            gpio: GpioPinArr([(); GpioPin::NUM_PINS].map(|_| Waveform::default())),
+            pwm: PwmPinArr([(); PwmPin::NUM_PINS].map(|_| Waveform::default())),
+            failures: Vec::new(),
+        }
+    }
+}
+
+/// Plays a [`Scenario`] against a set of [`Shims`]; see the
+/// [module docs](self).
+pub struct ScenarioPlayer<'s, 'int, I: InputSink> {
+    shims: &'s Shims<'int>,
+    input: &'s I,
+
+    events: Vec<Event>,
+    // Next firing time (in ms) and index of each pending event; the index
+    // breaks ties so that simultaneous events fire in the order they're listed.
+    pending: BinaryHeap<Reverse<(u64, usize)>>,
+
+    now: Duration,
+    recording: Recording,
+}
+
+impl<'s, 'int, I: InputSink> ScenarioPlayer<'s, 'int, I> {
+    pub fn new(scenario: Scenario, shims: &'s Shims<'int>, input: &'s I) -> Self {
+        let pending = scenario
+            .events
+            .iter()
+            .enumerate()
+            .map(|(idx, event)| Reverse((event.at, idx)))
+            .collect();
+
+        Self {
+            shims,
+            input,
+            events: scenario.events,
+            pending,
+            now: Duration::from_millis(0),
+            recording: Recording::default(),
+        }
+    }
+
+    /// The time the player has been advanced to.
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// Whether there are events left to play.
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    pub fn into_recording(self) -> Recording {
+        self.recording
+    }
+
+    /// Applies every event that happens at or before `time` (in order) and then
+    /// samples the outputs.
+    ///
+    /// Time can't go backwards; advancing to a time before [`now`](Self::now)
+    /// just samples the outputs again.
+    pub fn advance_to(&mut self, time: Duration) {
+        let time = time.max(self.now);
+
+        while let Some(&Reverse((at, idx))) = self.pending.peek() {
+            if Duration::from_millis(at) > time {
+                break;
+            }
+
+            self.pending.pop();
+            let event = &self.events[idx];
+
+            if let Some(every) = event.every {
+                let next = at + every.get();
+                if event.until.map(|until| next <= until).unwrap_or(true) {
+                    self.pending.push(Reverse((next, idx)));
+                }
+            }
+
+            let action = event.action.clone();
+            self.apply(Duration::from_millis(at), action);
+        }
+
+        self.now = time;
+        self.sample();
+    }
+
+    pub fn advance_by(&mut self, delta: Duration) {
+        self.advance_to(self.now + delta)
+    }
+
+    fn apply(&mut self, time: Duration, action: Action) {
+        let applied = match &action {
+            Action::Gpio { pin, level } => RwLock::write(&self.shims.gpio).unwrap().set_pin(*pin, *level).is_some(),
+            Action::Adc { pin, value } => RwLock::write(&self.shims.adc).unwrap().set_value(*pin, *value).is_ok(),
+            Action::Input { chars } => chars.chars().all(|c| self.input.put_char(c).is_some()),
+        };
+
+        if !applied {
+            self.recording.failures.push((time, action));
+        }
+    }
+
+    fn sample(&mut self) {
+        let now = self.now;
+
+        {
+            let gpio = RwLock::read(&self.shims.gpio).unwrap();
+            for pin in GPIO_PINS.iter() {
+                let level = match Gpio::get_state(&*gpio, *pin) {
+                    GpioState::Output => gpio.get_pin(*pin),
+                    _ => None,
+                };
+
+                self.recording.gpio[*pin].record(now, level);
+            }
+        }
+
+        let pwm = Mutex::lock(&self.shims.pwm).unwrap();
+        for pin in PWM_PINS.iter() {
+            let period = match Pwm::get_state(&*pwm, *pin) {
+                PwmState::Enabled(period) => Some(period),
+                PwmState::Disabled => None,
+            };
+
+            self.recording.pwm[*pin].record(now, PwmOutput {
+                period,
+                duty_cycle: Pwm::get_duty_cycle(&*pwm, *pin),
+            });
+        }
+    }
+}
+
+// There's no wall clock to sleep on in wasm; callers there have to advance the
+// player themselves (i.e. from an animation frame callback).
+not_wasm! {
+    use std::time::Instant;
+
+    impl<'s, 'int, I: InputSink> ScenarioPlayer<'s, 'int, I> {
+        /// Plays the scenario against the wall clock for the given length of
+        /// time, sampling the outputs every `resolution`.
+        ///
+        /// This blocks; the simulator is expected to be running on another
+        /// thread.
+        pub fn play_in_real_time(&mut self, length: Duration, resolution: Duration) {
+            let start = Instant::now() - self.now;
+            let end = self.now + length;
+
+            while self.now < end {
+                std::thread::sleep(resolution.min(end - self.now));
+                self.advance_to(start.elapsed().min(end));
+            }
+        }
+    }
+}
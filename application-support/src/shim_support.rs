@@ -14,8 +14,8 @@ pub type ShimPeripheralSet<'int, 'io> = ShareablePeripheralsShim<'int, 'io>;
 
 pub struct Shims<'int> {
     pub gpio: Arc<RwLock<GpioShim<'int>>>,
-    pub adc: Arc<RwLock<AdcShim>>,
-    pub pwm: Arc<Mutex<PwmShim>>,
+    pub adc: Arc<RwLock<AdcShim<'int>>>,
+    pub pwm: Arc<Mutex<PwmShim<'int>>>,
     pub timers: Arc<Mutex<TimersShim<'int>>>,
     pub clock: Arc<RwLock<ClockShim>>,
 }
@@ -0,0 +1,247 @@
+//! Drives an `AsyncController` from a tiny single-threaded executor against a
+//! simulator.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use lc3_isa::{Instruction, Reg::*, Word};
+use lc3_traits::control::rpc::{
+    encoding::Transparent, AsyncController, Envelope, MpscTransport, Notification,
+    RequestEnvelope, RequestMessage, ResponseEnvelope, ResponseMessage, RetryPolicy, RpcError,
+    Topics, Transport,
+};
+use lc3_traits::control::{Event, FaultHandling, State};
+use lc3_traits::error::Error;
+
+use pretty_assertions::assert_eq;
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::thread;
+use std::time::Duration;
+
+// Polls until done; the controller's default `Wait` strategy wakes futures
+// right away so there's nothing to wait on.
+fn block_on<F: Future>(fut: F) -> F::Output {
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker { noop_raw_waker() }
+        fn noop(_: *const ()) { }
+
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+
+    let mut fut = Box::pin(fut);
+    loop {
+        if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+            return out;
+        }
+
+        thread::yield_now();
+    }
+}
+
+// Polls both futures (on the same thread) until both are done.
+struct Join<A: Future, B: Future> {
+    a: Pin<Box<A>>,
+    b: Pin<Box<B>>,
+    a_out: Option<A::Output>,
+    b_out: Option<B::Output>,
+}
+
+impl<A: Future, B: Future> Future for Join<A, B>
+where
+    A::Output: Unpin,
+    B::Output: Unpin,
+{
+    type Output = (A::Output, B::Output);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if this.a_out.is_none() {
+            if let Poll::Ready(out) = this.a.as_mut().poll(cx) { this.a_out = Some(out); }
+        }
+        if this.b_out.is_none() {
+            if let Poll::Ready(out) = this.b.as_mut().poll(cx) { this.b_out = Some(out); }
+        }
+
+        if this.a_out.is_some() && this.b_out.is_some() {
+            Poll::Ready((this.a_out.take().unwrap(), this.b_out.take().unwrap()))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+fn join<A: Future, B: Future>(a: A, b: B) -> Join<A, B> {
+    Join { a: Box::pin(a), b: Box::pin(b), a_out: None, b_out: None }
+}
+
+fn device(transport: MpscTransport<ResponseEnvelope, RequestEnvelope>) {
+    common::serve_sim(Transparent::default(), Transparent::default(), transport);
+}
+
+#[test]
+fn operations_are_futures() {
+    let (cont, dev) = MpscTransport::new();
+    device(dev);
+
+    let mut cont = AsyncController::new(Transparent::default(), Transparent::default(), cont);
+
+    block_on(async {
+        cont.handshake().await.unwrap();
+
+        const LEN: usize = 40;
+        for offset in 0..(LEN as Word) {
+            cont.write_word(0x4000 + offset, offset * 5).await.unwrap();
+        }
+
+        // Operations can be in flight at the same time:
+        cont.set_pc(0x4000).await.unwrap();
+        let (word, pc) = join(cont.read_word(0x4003), cont.get_pc()).await;
+        assert_eq!((word, pc), (Ok(15), Ok(0x4000)));
+
+        let mut words = [0; LEN];
+        cont.read_words(0x4000, &mut words).await.unwrap();
+        for (offset, w) in words.iter().enumerate() {
+            assert_eq!(*w, offset as Word * 5);
+        }
+
+        // A loop that counts up in R1 with a breakpoint at the end:
+        cont.write_word(0x3000, Instruction::new_add_imm(R1, R1, 1).into()).await.unwrap();
+        cont.write_word(0x3001, Instruction::new_br(true, true, true, -2).into()).await.unwrap();
+        cont.set_pc(0x3000).await.unwrap();
+        cont.set_register(R1, 0).await.unwrap();
+
+        assert_eq!(cont.step().await, Ok(None));
+        assert_eq!(cont.get_register(R1).await, Ok(1));
+
+        assert_eq!(cont.set_breakpoint(0x3001).await, Ok(Ok(0)));
+        assert_eq!(cont.run_until_event().await, Ok(Event::Breakpoint { addr: 0x3001 }));
+        assert_eq!(cont.unset_breakpoint(0).await, Ok(Ok(())));
+
+        // Everyone waiting on a run gets the same event, and other requests
+        // keep working while the device runs:
+        let (events, (state, ())) = join(
+            join(cont.run_until_event(), cont.run_until_event()),
+            async {
+                let state = cont.get_state().await.unwrap();
+                cont.pause().await.unwrap();
+                (state, ())
+            },
+        ).await;
+
+        assert_eq!(state, State::RunningUntilEvent);
+        assert_eq!(events, (Ok(Event::Interrupted), Ok(Event::Interrupted)));
+        assert_eq!(cont.get_state().await, Ok(State::Paused));
+
+        // And the next run is a new one:
+        assert_eq!(cont.set_breakpoint(0x3001).await, Ok(Ok(0)));
+        assert_eq!(cont.run_until_event().await, Ok(Event::Breakpoint { addr: 0x3001 }));
+
+        // Faults can stop the device instead of going to the OS:
+        assert_eq!(cont.get_fault_handling().await, Ok(FaultHandling::Vector));
+        cont.set_fault_handling(FaultHandling::Stop).await.unwrap();
+        assert_eq!(cont.get_fault_handling().await, Ok(FaultHandling::Stop));
+
+        cont.write_word(0x3002, 0xD000).await.unwrap();
+        cont.set_pc(0x3002).await.unwrap();
+        assert_eq!(
+            cont.step().await,
+            Ok(Some(Event::Error { err: Error::IllegalOpcode { pc: 0x3002, insn: 0xD000 } })),
+        );
+        assert_eq!(cont.get_pc().await, Ok(0x3002));
+
+        // Notifications are held on to until they're taken:
+        assert_eq!(cont.subscribe(Topics::STATE).await, Ok(Topics::STATE));
+        assert_eq!(cont.take_notification(), Ok(Some(Notification::State(State::Paused))));
+        assert_eq!(cont.take_notification(), Ok(None));
+    });
+}
+
+/// A device that takes its time answering requests (with `answer`) and
+/// records, for each one, whether another request showed up in the meantime.
+fn slow_device(
+    transport: MpscTransport<ResponseEnvelope, RequestEnvelope>,
+    answer: fn(&RequestMessage) -> ResponseMessage,
+) -> Arc<Mutex<Vec<(RequestMessage, bool)>>> {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let device_log = log.clone();
+
+    let _ = thread::spawn(move || {
+        let mut pending = VecDeque::new();
+        loop {
+            match transport.get() {
+                Ok(env) => pending.push_back(env),
+                Err(None) => {},
+                // The controller's gone.
+                Err(Some(_)) => break,
+            }
+
+            if let Some(Envelope { seq, message }) = pending.pop_front() {
+                thread::sleep(Duration::from_millis(20));
+                while let Ok(env) = transport.get() { pending.push_back(env) }
+
+                let response = answer(&message);
+                Mutex::lock(&device_log).unwrap().push((message, !pending.is_empty()));
+                transport.send(Envelope::new(seq, response)).unwrap();
+            } else {
+                thread::yield_now();
+            }
+        }
+    });
+
+    log
+}
+
+#[test]
+fn requests_that_cant_be_repeated_go_alone() {
+    let (cont, dev) = MpscTransport::new();
+    let log = slow_device(dev, |m| match m {
+        RequestMessage::GetPc => ResponseMessage::GetPc(0x3000),
+        RequestMessage::Step => ResponseMessage::Step(None),
+        other => panic!("unexpected request: `{:?}`", other),
+    });
+
+    let cont = AsyncController::new(Transparent::default(), Transparent::default(), cont)
+        .with_retry_policy(RetryPolicy { retry_non_idempotent: true, ..RetryPolicy::default() });
+
+    block_on(async {
+        let (pc, (step, pc_again)) = join(
+            cont.get_pc(),
+            join(cont.step(), cont.get_pc()),
+        ).await;
+        assert_eq!((pc, step, pc_again), (Ok(0x3000), Ok(None), Ok(0x3000)));
+    });
+
+    // The `step` waits for the first `get_pc` and holds off the second:
+    assert_eq!(*Mutex::lock(&log).unwrap(), vec![
+        (RequestMessage::GetPc, false),
+        (RequestMessage::Step, false),
+        (RequestMessage::GetPc, false),
+    ]);
+}
+
+#[test]
+fn not_permitted_is_an_error() {
+    let (cont, dev) = MpscTransport::new();
+    let _ = slow_device(dev, |_| ResponseMessage::NotPermitted);
+
+    let cont = AsyncController::new(Transparent::default(), Transparent::default(), cont);
+
+    block_on(async {
+        assert_eq!(cont.set_pc(0x3000).await, Err(RpcError::NotPermitted));
+        assert_eq!(cont.run_until_event().await, Err(RpcError::NotPermitted));
+
+        let mut words = [0; 4];
+        assert_eq!(cont.read_words(0x3000, &mut words).await, Err(RpcError::NotPermitted));
+    });
+}
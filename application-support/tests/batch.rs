@@ -0,0 +1,106 @@
+//! Batched and pipelined requests against a simulator.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use lc3_isa::Word;
+use lc3_traits::control::rpc::{
+    encoding::Transparent, futures::SyncEventFutureSharedState, Controller,
+    MpscTransport, RequestEnvelope, RequestMessage, ResponseEnvelope,
+    ResponseMessage, Transport, READ_WORDS_CHUNK_LEN,
+};
+use lc3_traits::control::{version_from_crate, Control, Identifier, Version};
+
+use pretty_assertions::assert_eq;
+
+use std::cell::Cell;
+
+/// Counts the messages sent through it.
+struct CountingTransport {
+    inner: MpscTransport<RequestEnvelope, ResponseEnvelope>,
+    num_sent: Cell<usize>,
+}
+
+impl Transport<RequestEnvelope, ResponseEnvelope> for CountingTransport {
+    type RecvErr = <MpscTransport<RequestEnvelope, ResponseEnvelope> as Transport<RequestEnvelope, ResponseEnvelope>>::RecvErr;
+    type SendErr = <MpscTransport<RequestEnvelope, ResponseEnvelope> as Transport<RequestEnvelope, ResponseEnvelope>>::SendErr;
+
+    const ID: Identifier = Identifier::new_from_str_that_crashes_on_invalid_inputs("CNT");
+    const VER: Version = version_from_crate!();
+
+    fn send(&self, message: RequestEnvelope) -> Result<(), Self::SendErr> {
+        self.num_sent.set(self.num_sent.get() + 1);
+        self.inner.send(message)
+    }
+
+    fn get(&self) -> Result<ResponseEnvelope, Option<Self::RecvErr>> {
+        self.inner.get()
+    }
+}
+
+type Cont = Controller<'static, CountingTransport, SyncEventFutureSharedState>;
+
+fn controller() -> Cont {
+    let (cont, dev) = MpscTransport::new();
+
+    common::serve_sim(Transparent::default(), Transparent::default(), dev);
+
+    let state: &'static _ = Box::leak(Box::new(SyncEventFutureSharedState::new()));
+    Controller::new(
+        Transparent::default(),
+        Transparent::default(),
+        CountingTransport { inner: cont, num_sent: Cell::new(0) },
+        state,
+    )
+}
+
+#[test]
+fn batches() {
+    let mut cont = controller();
+
+    const LEN: usize = 50;
+    for offset in 0..LEN {
+        cont.write_word(0x4000 + offset as Word, offset as Word * 3);
+    }
+
+    // A memory panel's worth of words costs one request per chunk:
+    let sent = cont.transport.num_sent.get();
+    let mut words = [0; LEN];
+    cont.read_words(0x4000, &mut words);
+
+    assert_eq!(cont.transport.num_sent.get() - sent, (LEN + READ_WORDS_CHUNK_LEN - 1) / READ_WORDS_CHUNK_LEN);
+    for (offset, w) in words.iter().enumerate() {
+        assert_eq!(*w, offset as Word * 3);
+    }
+
+    // Any requests can be batched; responses are matched back up with their
+    // requests and requests that aren't idempotent still happen in order:
+    use RequestMessage::*;
+    let requests = [
+        GetPc,
+        SetPc { addr: 0x4000 },
+        GetPc,
+        ReadWord { addr: 0x4001 },
+        SetBreakpoint { addr: 0x4002 },
+        GetBreakpoints,
+    ];
+
+    let mut responses: [Option<ResponseMessage>; 6] = Default::default();
+    cont.pipeline_depth = 4;
+    cont.request_batch(requests.iter().cloned(), |idx, resp| {
+        assert!(responses[idx].replace(resp).is_none());
+    })
+    .unwrap();
+
+    let responses = responses.map(Option::unwrap);
+    assert!(matches!(responses[0], ResponseMessage::GetPc(_)));
+    assert_eq!(responses[1], ResponseMessage::SetPc);
+    assert_eq!(responses[2], ResponseMessage::GetPc(0x4000));
+    assert_eq!(responses[3], ResponseMessage::ReadWord(3));
+    assert_eq!(responses[4], ResponseMessage::SetBreakpoint(Ok(0)));
+    match &responses[5] {
+        ResponseMessage::GetBreakpoints(bps) => assert_eq!(bps[0], Some(0x4002)),
+        other => panic!("{:?}", other),
+    }
+}
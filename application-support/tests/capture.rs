@@ -0,0 +1,108 @@
+//! Records a session with a simulator and then replays it without one.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use lc3_device_support::rpc::{
+    encoding::{Cobs, PostcardDecode, PostcardEncode},
+    transport::capture::{Capture, RecordingTransport, ReplayTransport, Side},
+};
+use lc3_device_support::util::Fifo;
+use lc3_isa::{Reg::*, Word};
+use lc3_traits::control::rpc::{
+    futures::SyncEventFutureSharedState, Controller, EventFutureSharedStatePorcelain,
+    MpscTransport, RequestEnvelope, RequestMessage, ResponseEnvelope, ResponseMessage,
+};
+use lc3_traits::control::Control;
+
+use pretty_assertions::assert_eq;
+
+
+const ADD_R1_R1_1: Word = 0b0001_001_001_1_00001;
+
+fn new_cobs_fifo() -> Cobs<Fifo<u8>> {
+    Cobs::try_new(Fifo::new()).unwrap()
+}
+
+fn controller<T, S>(transport: T, state: &'static S) -> Controller<
+    'static,
+    T,
+    S,
+    RequestMessage,
+    ResponseMessage,
+    PostcardEncode<RequestEnvelope, Cobs<Fifo<u8>>, fn() -> Cobs<Fifo<u8>>>,
+    PostcardDecode<ResponseEnvelope, Cobs<Fifo<u8>>>,
+>
+where
+    T: lc3_traits::control::rpc::Transport<Fifo<u8>, Fifo<u8>>,
+    S: EventFutureSharedStatePorcelain,
+{
+    Controller::new(
+        PostcardEncode::new(new_cobs_fifo as fn() -> _),
+        PostcardDecode::new(),
+        transport,
+        state,
+    )
+}
+
+// The same calls, whether we're talking to the simulator or to a replay.
+fn session(cont: &mut impl Control) -> (Word, Word, Word) {
+    for addr in 0x3000..0x3004 {
+        cont.write_word(addr, ADD_R1_R1_1);
+    }
+    cont.set_pc(0x3000);
+    cont.set_register(R1, 0);
+
+    cont.step();
+    cont.step();
+
+    (cont.read_word(0x3003), cont.get_pc(), cont.get_register(R1))
+}
+
+#[test]
+fn record_and_replay() {
+    let (cont, dev) = MpscTransport::<Fifo<u8>, Fifo<u8>>::new();
+
+    common::serve_sim(
+        PostcardEncode::<ResponseEnvelope, _, _>::new(new_cobs_fifo),
+        PostcardDecode::<RequestEnvelope, Cobs<Fifo<u8>>>::new(),
+        dev,
+    );
+
+    // Record:
+    let state: &'static _ = Box::leak(Box::new(SyncEventFutureSharedState::new()));
+    let mut cont = controller(RecordingTransport::new(cont, Side::Controller, Vec::new()), state);
+
+    cont.handshake().unwrap();
+    let recorded = session(&mut cont);
+    assert_eq!(recorded, (ADD_R1_R1_1, 0x3002, 2));
+
+    assert!(cont.transport.take_error().is_none());
+    let (_, file) = cont.transport.into_inner();
+    let capture = Capture::read(&file[..]).unwrap();
+
+    // Print:
+    let mut printed = Vec::new();
+    capture.print(
+        &mut printed,
+        PostcardDecode::<RequestEnvelope, Cobs<Fifo<u8>>>::new(),
+        PostcardDecode::<ResponseEnvelope, Cobs<Fifo<u8>>>::new(),
+    ).unwrap();
+    let printed = String::from_utf8(printed).unwrap();
+
+    assert_eq!(printed.lines().count(), capture.frames.len());
+    assert!(printed.contains("ReadWord { addr: 12291 }"), "{}", printed);
+    assert!(printed.contains("Step(None)"), "{}", printed);
+    assert!(!printed.contains('<'), "everything should decode: {}", printed);
+
+    // Replay, without the simulator this time:
+    let state: &'static _ = Box::leak(Box::new(SyncEventFutureSharedState::new()));
+    let mut cont = controller(ReplayTransport::new(capture, Side::Controller), state);
+
+    cont.handshake().unwrap();
+    assert_eq!(session(&mut cont), recorded);
+
+    assert_eq!(cont.transport.mismatches(), vec![]);
+    assert!(cont.transport.finished());
+}
@@ -0,0 +1,168 @@
+//! Things the tests that serve a simulator over a real connection share.
+
+// Not every test uses everything in here.
+#![allow(dead_code)]
+
+use lc3_application_support::init::{BlackBox, Init, SimDevice};
+use lc3_application_support::io_peripherals::{InputSink, OutputSource};
+use lc3_isa::{insn, Reg::*, Word};
+use lc3_traits::control::rpc::{
+    Decode, Device, Encode, RequestEnvelope, RequestMessage, ResponseEnvelope,
+    ResponseMessage, Transport,
+};
+use lc3_traits::control::{Control, State};
+
+use pretty_assertions::assert_eq;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+const ADD_R1_R1_1: Word = 0b0001_001_001_1_00001;
+
+/// Runs `serve` on a thread of its own with storage for a simulator.
+///
+/// Only one simulator should be running at a time; they share static state.
+pub fn spawn_device(serve: impl FnOnce(&'static mut BlackBox) + Send + 'static) {
+    // The simulator needs a bigger stack than spawned threads get by default.
+    let _ = thread::Builder::new()
+        .name("Device Thread".to_string())
+        .stack_size(1024 * 1024 * 8)
+        .spawn(move || serve(Box::leak(Box::new(BlackBox::new()))))
+        .unwrap();
+}
+
+/// The simulator a [`SimDevice`] hands out.
+pub type Sim = <SimDevice<'static> as Init<'static>>::ControlImpl;
+
+/// The [`Device`] that [`serve_sim`] and [`serve_sim_with`] run.
+pub type SimServer<T, E, D> = Device<T, Sim, RequestMessage, ResponseMessage, D, E>;
+
+/// Serves a simulator over `transport` (with `enc` and `dec` as the encoding
+/// layer) from a thread of its own.
+pub fn serve_sim<E, D, T>(enc: E, dec: D, transport: T)
+where
+    E: Encode<ResponseEnvelope> + Send + 'static,
+    D: Decode<RequestEnvelope> + Send + 'static,
+    T: Transport<E::Encoded, D::Encoded> + Send + 'static,
+{
+    serve_sim_with(enc, dec, transport, |_| {})
+}
+
+/// Like [`serve_sim`] but calls `each_step` with the device after every step.
+pub fn serve_sim_with<E, D, T>(
+    enc: E,
+    dec: D,
+    transport: T,
+    mut each_step: impl FnMut(&mut SimServer<T, E, D>) + Send + 'static,
+) where
+    E: Encode<ResponseEnvelope> + Send + 'static,
+    D: Decode<RequestEnvelope> + Send + 'static,
+    T: Transport<E::Encoded, D::Encoded> + Send + 'static,
+{
+    spawn_device(move |b| {
+        let (sim, _, _, _) = SimDevice::init(b);
+        let mut device = Device::new(enc, dec, transport);
+
+        loop {
+            device.step(sim);
+            each_step(&mut device);
+            thread::yield_now();
+        }
+    })
+}
+
+/// Drives the device with a few controllers, one after the other, each made
+/// with `connect`. Checks that the device's state outlives its controllers and
+/// that a controller that goes away leaves the device paused.
+pub fn controllers_take_turns<C, F>(mut connect: F)
+where
+    C: Control + ?Sized,
+    F: for<'b> FnMut(&'b mut BlackBox) -> &'b mut C,
+{
+    {
+        let mut b = BlackBox::new();
+        let cont = connect(&mut b);
+
+        cont.write_word(0x3000, ADD_R1_R1_1);
+        assert_eq!(cont.read_word(0x3000), ADD_R1_R1_1);
+
+        cont.set_pc(0x3000);
+        cont.set_register(R1, 5);
+        cont.step();
+
+        assert_eq!(cont.get_pc(), 0x3001);
+        assert_eq!(cont.get_register(R1), 6);
+    }
+
+    // The first controller is gone; the device should pick up the next one and
+    // the simulator's state should have stuck around:
+    {
+        let mut b = BlackBox::new();
+        let cont = connect(&mut b);
+
+        assert_eq!(cont.read_word(0x3000), ADD_R1_R1_1);
+        assert_eq!(cont.get_register(R1), 6);
+
+        // Leave the simulator running, forever:
+        cont.write_word(0x3001, insn!(BRnzp #-1).into());
+        cont.set_pc(0x3001);
+        let _ = cont.run_until_event();
+        assert_eq!(cont.get_state(), State::RunningUntilEvent);
+    }
+
+    let mut b = BlackBox::new();
+    let cont = connect(&mut b);
+
+    assert_eq!(cont.get_state(), State::Paused);
+    assert_eq!(cont.get_pc(), 0x3001);
+}
+
+/// A program that echoes what's typed, forever. It can go anywhere in memory.
+pub fn echo_program() -> [Word; 11] {
+    // There's no OS so we poll the device registers ourselves:
+    [
+        insn!(LDI R0, #6).into(),       // wait for the keyboard
+        insn!(BRzp #-2).into(),
+        insn!(LDI R0, #5).into(),
+        insn!(LDI R1, #5).into(),       // wait for the display
+        insn!(BRzp #-2).into(),
+        insn!(STI R0, #4).into(),
+        insn!(BRnzp #-7).into(),
+        0xFE00,                         // KBSR
+        0xFE02,                         // KBDR
+        0xFE04,                         // DSR
+        0xFE06,                         // DDR
+    ]
+}
+
+/// Runs a program that echoes what's typed and checks that what's put into
+/// `input` comes back out of `output` (the controller's console).
+pub fn console_is_relayed<C: Control + ?Sized>(
+    cont: &mut C,
+    input: &dyn InputSink,
+    output: &dyn OutputSource,
+) {
+    for (addr, word) in (0x3000..).zip(echo_program().iter()) {
+        cont.write_word(addr, *word);
+    }
+
+    cont.set_pc(0x3000);
+    let _ = cont.run_until_event();
+
+    let mut echoed = String::new();
+    for (idx, c) in "hello".chars().enumerate() {
+        input.put_char(c).unwrap();
+
+        let start = Instant::now();
+        while echoed.len() <= idx {
+            assert!(start.elapsed() < Duration::from_secs(10), "`{}` wasn't echoed", c);
+
+            cont.tick();
+            echoed.extend(output.get_chars());
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    assert_eq!(echoed, "hello");
+    cont.pause();
+}
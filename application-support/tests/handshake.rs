@@ -0,0 +1,112 @@
+//! Runs `Controller::handshake` against a simulator and against devices that
+//! answer it with something else (or not at all).
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use lc3_traits::control::rpc::{
+    encoding::Transparent,
+    futures::SyncEventFutureSharedState,
+    handshake::{Features, Mismatch, ProtocolVersion, MESSAGE_HASH},
+    Controller, Envelope, HandshakeError, Hello, MpscTransport,
+    RequestEnvelope, RequestMessage, ResponseEnvelope, ResponseMessage,
+    RetryPolicy, Timeout, Transport, PROTOCOL_VERSION,
+};
+use lc3_traits::control::Control;
+
+use pretty_assertions::assert_eq;
+
+use std::thread;
+use std::time::Duration;
+
+type Cont = Controller<
+    'static,
+    MpscTransport<RequestEnvelope, ResponseEnvelope>,
+    SyncEventFutureSharedState,
+>;
+
+fn controller(transport: MpscTransport<RequestEnvelope, ResponseEnvelope>) -> Cont {
+    let state: &'static _ = Box::leak(Box::new(SyncEventFutureSharedState::new()));
+
+    Controller::new(Transparent::default(), Transparent::default(), transport, state)
+        .with_retry_policy(RetryPolicy {
+            timeout: Timeout::After(Duration::from_millis(50)),
+            max_retries: 1,
+            retry_non_idempotent: true,
+        })
+}
+
+/// A device that answers handshakes with `hello` (or not at all) and ignores
+/// everything else.
+fn fake_device(hello: Option<Hello>) -> Cont {
+    let (cont, dev) = MpscTransport::<RequestEnvelope, ResponseEnvelope>::new();
+
+    let _ = thread::spawn(move || loop {
+        match dev.get() {
+            Ok(Envelope { seq, message: RequestMessage::Handshake { .. } }) => {
+                if let Some(hello) = hello {
+                    dev.send(Envelope::new(seq, ResponseMessage::Handshake(hello))).unwrap();
+                }
+            },
+            Ok(_) => {},
+            Err(None) => thread::yield_now(),
+            // The controller's gone.
+            Err(Some(_)) => break,
+        }
+    });
+
+    controller(cont)
+}
+
+#[test]
+fn agrees_with_a_simulator() {
+    let (cont, dev) = MpscTransport::new();
+
+    common::serve_sim(Transparent::default(), Transparent::default(), dev);
+
+    let mut cont = controller(cont);
+    assert_eq!(cont.handshake(), Ok(Features::SUPPORTED));
+    assert!(cont.retry_policy.retry_non_idempotent);
+
+    // And the session carries on as usual:
+    cont.set_pc(0x3000);
+    assert_eq!(cont.get_pc(), 0x3000);
+}
+
+#[test]
+fn reports_mismatches() {
+    let theirs = Hello { message_hash: !MESSAGE_HASH, ..Hello::OURS };
+    assert_eq!(
+        fake_device(Some(theirs)).handshake(),
+        Err(HandshakeError::Incompatible(Mismatch::Messages { ours: MESSAGE_HASH, theirs: !MESSAGE_HASH })),
+    );
+
+    let version = ProtocolVersion { major: PROTOCOL_VERSION.major + 1, minor: 0 };
+    let theirs = Hello { version, ..Hello::OURS };
+    assert_eq!(
+        fake_device(Some(theirs)).handshake(),
+        Err(HandshakeError::Incompatible(Mismatch::Version { ours: PROTOCOL_VERSION, theirs: version })),
+    );
+}
+
+#[test]
+fn minor_differences_disable_features() {
+    let theirs = Hello { features: Features::NOTIFICATIONS, ..Hello::OURS };
+
+    let mut cont = fake_device(Some(theirs));
+    assert_eq!(cont.handshake(), Ok(Features::NOTIFICATIONS));
+    assert!(!cont.retry_policy.retry_non_idempotent);
+}
+
+#[test]
+fn falls_back_for_devices_that_dont_answer() {
+    let mut cont = fake_device(None).with_retry_policy(RetryPolicy::NEVER_GIVE_UP);
+
+    assert_eq!(cont.handshake(), Ok(Features::NONE));
+    assert_eq!(cont.retry_policy, RetryPolicy::NEVER_GIVE_UP);
+
+    let mut cont = fake_device(None);
+    assert_eq!(cont.handshake(), Ok(Features::NONE));
+    assert!(!cont.retry_policy.retry_non_idempotent);
+}
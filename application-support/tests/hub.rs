@@ -0,0 +1,152 @@
+//! Runs an owner and a viewer `Controller` against one simulator through a
+//! `Hub`.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use lc3_application_support::init::{Init, SimDevice};
+use lc3_application_support::io_peripherals::{relay_console, InputSink, OutputSource};
+use lc3_isa::{Reg::*, Word};
+use lc3_traits::control::rpc::{
+    encoding::Transparent, futures::SyncEventFutureSharedState, Controller,
+    EventFuture, Hub, MpscTransport, RequestEnvelope, RequestMessage,
+    ResponseEnvelope, ResponseMessage, Role, RpcError, Topics,
+};
+use lc3_traits::control::{Control, Event};
+
+use pretty_assertions::assert_eq;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const ADD_R1_R1_1: Word = 0b0001_001_001_1_00001;
+
+type Cont = Controller<
+    'static,
+    MpscTransport<RequestEnvelope, ResponseEnvelope>,
+    SyncEventFutureSharedState,
+>;
+
+fn controller(transport: MpscTransport<RequestEnvelope, ResponseEnvelope>) -> Cont {
+    let state: &'static _ = Box::leak(Box::new(SyncEventFutureSharedState::new()));
+
+    Controller::new(Transparent::default(), Transparent::default(), transport, state)
+}
+
+static VIEWER_CONSOLE: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+fn viewer_console(bytes: &[u8]) {
+    VIEWER_CONSOLE.lock().unwrap().extend_from_slice(bytes);
+}
+
+struct NoOpWaker;
+impl Wake for NoOpWaker {
+    fn wake(self: Arc<Self>) { }
+}
+
+fn wait_for(cont: &mut Cont, mut fut: EventFuture<'static, SyncEventFutureSharedState>) -> Event {
+    let waker = Waker::from(Arc::new(NoOpWaker));
+
+    loop {
+        Control::tick(cont);
+
+        if let Poll::Ready(event) = Pin::new(&mut fut).poll(&mut Context::from_waker(&waker)) {
+            break event;
+        }
+
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn owner_and_viewer() {
+    let (owner, owner_dev) = MpscTransport::new();
+    let (viewer, viewer_dev) = MpscTransport::new();
+
+    common::spawn_device(move |b| {
+        let (sim, _, input, output) = SimDevice::init(b);
+        let input = input.map(|i| i as &dyn InputSink);
+        let output = output.map(|o| o as &dyn OutputSource);
+
+        let mut hub = Hub::new(Transparent::default(), Transparent::default());
+        let owner = hub.add_session(owner_dev);
+        let _viewer = hub.add_session(viewer_dev);
+        assert!(hub.set_owner(Some(owner)));
+
+        loop {
+            hub.step(sim);
+            relay_console(&mut hub, input, output);
+            thread::yield_now();
+        }
+    });
+
+    let mut owner = controller(owner);
+    let mut viewer = controller(viewer).with_console_sink(&viewer_console);
+
+    assert_eq!(owner.session_info().unwrap().role, Role::Owner);
+    assert_eq!(viewer.session_info().unwrap().role, Role::Viewer);
+    let changes = viewer.session_info().unwrap().changes;
+
+    // The viewer sees what the owner does:
+    for addr in 0x3000..0x3010 {
+        owner.write_word(addr, ADD_R1_R1_1);
+    }
+    owner.set_pc(0x3000);
+    owner.set_register(R1, 0);
+    assert_eq!(owner.set_breakpoint(0x3003), Ok(0));
+
+    assert_eq!(viewer.read_word(0x300F), ADD_R1_R1_1);
+    assert_eq!(viewer.get_breakpoints()[0], Some(0x3003));
+    assert!(viewer.session_info().unwrap().changes > changes);
+
+    // But can't do anything itself:
+    assert_eq!(
+        viewer.request(RequestMessage::SetPc { addr: 0x4000 }),
+        Ok(ResponseMessage::NotPermitted),
+    );
+    assert_eq!(viewer.get_pc(), 0x3000);
+
+    // Through `Control` the requests are just ignored; the `try_*` methods say
+    // why:
+    assert_eq!(viewer.step(), None);
+    assert_eq!(viewer.set_breakpoint(0x3004), Err(()));
+    assert_eq!(viewer.try_step(), Err(RpcError::NotPermitted));
+    assert_eq!(viewer.get_pc(), 0x3000);
+    assert_eq!(viewer.get_breakpoints()[1], None);
+
+    // Waiting for an event as a viewer doesn't start the simulator...
+    let viewer_fut = viewer.run_until_event();
+    assert_eq!(viewer.get_pc(), 0x3000);
+
+    // ...but everyone hears about the event once the owner does:
+    let owner_fut = owner.run_until_event();
+    assert_eq!(wait_for(&mut owner, owner_fut), Event::Breakpoint { addr: 0x3003 });
+    assert_eq!(wait_for(&mut viewer, viewer_fut), Event::Breakpoint { addr: 0x3003 });
+
+    assert_eq!(viewer.get_register(R1), 3);
+
+    // The owner's console input goes to the simulator and everyone who's
+    // subscribed sees its output:
+    assert_eq!(viewer.subscribe(Topics::CONSOLE), Ok(Topics::CONSOLE));
+    for (addr, word) in (0x3100..).zip(common::echo_program().iter()) {
+        owner.write_word(addr, *word);
+    }
+    owner.set_pc(0x3100);
+    let _ = owner.run_until_event();
+
+    assert_eq!(viewer.send_console_input(b"hi"), Err(RpcError::NotPermitted));
+    assert_eq!(owner.send_console_input(b"hi"), Ok(2));
+
+    let start = Instant::now();
+    while *VIEWER_CONSOLE.lock().unwrap() != b"hi" {
+        assert!(start.elapsed() < Duration::from_secs(10), "{:?}", VIEWER_CONSOLE.lock().unwrap());
+
+        Control::tick(&mut viewer);
+        thread::sleep(Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,201 @@
+//! Runs a `Controller` against a simulator over a transport that drops and
+//! mangles messages.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use lc3_isa::{Reg::*, Word};
+use lc3_traits::control::rpc::{
+    encoding::JsonEncoding, futures::SyncEventFutureSharedState,
+    handshake::Features, Controller,
+    MpscTransport, RequestMessage, ResponseMessage, RetryPolicy,
+    RpcError, Timeout, Transport,
+};
+use lc3_traits::control::{version_from_crate, Control, Identifier, Version};
+
+use pretty_assertions::assert_eq;
+
+use std::cell::Cell;
+use std::time::Duration;
+
+const ADD_R1_R1_1: Word = 0b0001_001_001_1_00001;
+
+/// Drops every `drop_every`th message and truncates every `garble_every`th
+/// message that it's asked to send.
+struct LossyTransport {
+    inner: MpscTransport<String, String>,
+    drop_every: u64,
+    garble_every: u64,
+    num_sent: Cell<u64>,
+    num_get_errors: Cell<u64>,
+}
+
+impl LossyTransport {
+    fn pair(
+        (cont_drop, cont_garble): (u64, u64),
+        (dev_drop, dev_garble): (u64, u64),
+    ) -> (Self, Self) {
+        let (cont, dev) = MpscTransport::new();
+        let lossy = |inner, drop_every, garble_every| LossyTransport {
+            inner,
+            drop_every,
+            garble_every,
+            num_sent: Cell::new(0),
+            num_get_errors: Cell::new(0),
+        };
+
+        (
+            lossy(cont, cont_drop, cont_garble),
+            lossy(dev, dev_drop, dev_garble),
+        )
+    }
+}
+
+impl Transport<String, String> for LossyTransport {
+    type RecvErr = <MpscTransport<String, String> as Transport<String, String>>::RecvErr;
+    type SendErr = <MpscTransport<String, String> as Transport<String, String>>::SendErr;
+
+    const ID: Identifier = Identifier::new_from_str_that_crashes_on_invalid_inputs("LOSY");
+    const VER: Version = version_from_crate!();
+
+    fn send(&self, mut message: String) -> Result<(), Self::SendErr> {
+        let n = self.num_sent.get() + 1;
+        self.num_sent.set(n);
+
+        if n % self.drop_every == 0 {
+            return Ok(());
+        }
+
+        if n % self.garble_every == 0 {
+            message.truncate(message.len() / 2);
+        }
+
+        self.inner.send(message)
+    }
+
+    fn get(&self) -> Result<String, Option<Self::RecvErr>> {
+        self.inner.get()
+    }
+
+    fn num_get_errors(&self) -> u64 {
+        self.num_get_errors.get()
+    }
+
+    fn record_get_error(&self) {
+        self.num_get_errors.set(self.num_get_errors.get() + 1);
+    }
+}
+
+type Cont = Controller<
+    'static,
+    LossyTransport,
+    SyncEventFutureSharedState,
+    RequestMessage,
+    ResponseMessage,
+    JsonEncoding,
+    JsonEncoding,
+>;
+
+fn controller(transport: LossyTransport, retry_policy: RetryPolicy) -> Cont {
+    let state: &'static _ = Box::leak(Box::new(SyncEventFutureSharedState::new()));
+
+    Controller::new(JsonEncoding, JsonEncoding, transport, state)
+        .with_retry_policy(retry_policy)
+}
+
+fn policy(max_retries: u8, retry_non_idempotent: bool) -> RetryPolicy {
+    RetryPolicy {
+        timeout: Timeout::After(Duration::from_millis(50)),
+        max_retries,
+        retry_non_idempotent,
+    }
+}
+
+#[test]
+fn survives_lost_and_garbled_messages() {
+    // Requests and responses both get lost and mangled (at different rates so
+    // that we hit all the combinations):
+    let (cont, dev) = LossyTransport::pair((5, 7), (4, 9));
+
+    common::serve_sim(JsonEncoding, JsonEncoding, dev);
+
+    let mut cont = controller(cont, policy(20, true));
+    assert_eq!(cont.handshake(), Ok(Features::SUPPORTED));
+
+    const STEPS: Word = 40;
+    for addr in 0x3000..(0x3000 + STEPS) {
+        cont.write_word(addr, ADD_R1_R1_1);
+    }
+    for addr in 0x3000..(0x3000 + STEPS) {
+        assert_eq!(cont.read_word(addr), ADD_R1_R1_1);
+    }
+
+    // Same thing, but pipelined:
+    let mut words = [0; STEPS as usize];
+    cont.read_words(0x3000, &mut words);
+    assert_eq!(words, [ADD_R1_R1_1; STEPS as usize]);
+
+    cont.set_pc(0x3000);
+    cont.set_register(R1, 0);
+
+    // Stepping isn't idempotent; if the device were to run any of the retried
+    // steps again we'd end up too far along:
+    for _ in 0..STEPS {
+        cont.step();
+    }
+
+    assert_eq!(cont.get_pc(), 0x3000 + STEPS);
+    assert_eq!(cont.get_register(R1), STEPS);
+
+    // Neither is setting breakpoints:
+    assert_eq!(cont.set_breakpoint(0x3100), Ok(0));
+    assert_eq!(cont.set_breakpoint(0x3101), Ok(1));
+    assert_eq!(cont.set_breakpoint(0x3102), Ok(2));
+
+    // The garbled responses should have been noticed:
+    assert!(cont.transport.num_get_errors() > 0);
+}
+
+#[test]
+fn gives_up_eventually() {
+    // Nothing is listening on the other end:
+    let (cont, _dev) = LossyTransport::pair((u64::MAX, u64::MAX), (1, 1));
+    let cont = controller(cont, policy(3, true));
+
+    assert_eq!(
+        cont.request(RequestMessage::GetPc),
+        Err(RpcError::NoResponse { seq: 0, attempts: 4 }),
+    );
+    assert_eq!(
+        cont.request(RequestMessage::Step),
+        Err(RpcError::NoResponse { seq: 1, attempts: 4 }),
+    );
+}
+
+#[test]
+fn does_not_retry_non_idempotent_requests_when_told_not_to() {
+    let (cont, _dev) = LossyTransport::pair((u64::MAX, u64::MAX), (1, 1));
+    let cont = controller(cont, policy(3, false));
+
+    assert_eq!(
+        cont.request(RequestMessage::GetPc),
+        Err(RpcError::NoResponse { seq: 0, attempts: 4 }),
+    );
+    assert_eq!(
+        cont.request(RequestMessage::Step),
+        Err(RpcError::NoResponse { seq: 1, attempts: 1 }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "no response to request #1 (sent 2 times)")]
+fn control_impl_panics_with_the_error() {
+    let (cont, _dev) = LossyTransport::pair((u64::MAX, u64::MAX), (1, 1));
+    let cont = controller(cont, policy(1, true));
+
+    // The `try_*` counterparts report it instead:
+    assert_eq!(cont.try_get_pc(), Err(RpcError::NoResponse { seq: 0, attempts: 2 }));
+
+    cont.get_pc();
+}
@@ -0,0 +1,161 @@
+//! Subscribes to notifications from a simulator and checks that the
+//! `Controller` answers from them instead of asking the device.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use lc3_isa::{Instruction, Reg::*};
+use lc3_traits::control::rpc::{
+    encoding::Transparent, futures::SyncEventFutureSharedState, Controller,
+    Envelope, MpscTransport, Notification, RequestEnvelope,
+    RequestMessage, ResponseEnvelope, ResponseMessage, Topics, Transport,
+};
+use lc3_traits::control::{version_from_crate, Control, Identifier, State, Version};
+
+use pretty_assertions::assert_eq;
+
+use std::cell::Cell;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Counts the messages sent through it.
+struct CountingTransport {
+    inner: MpscTransport<RequestEnvelope, ResponseEnvelope>,
+    num_sent: Cell<usize>,
+}
+
+impl Transport<RequestEnvelope, ResponseEnvelope> for CountingTransport {
+    type RecvErr = <MpscTransport<RequestEnvelope, ResponseEnvelope> as Transport<RequestEnvelope, ResponseEnvelope>>::RecvErr;
+    type SendErr = <MpscTransport<RequestEnvelope, ResponseEnvelope> as Transport<RequestEnvelope, ResponseEnvelope>>::SendErr;
+
+    const ID: Identifier = Identifier::new_from_str_that_crashes_on_invalid_inputs("CNT");
+    const VER: Version = version_from_crate!();
+
+    fn send(&self, message: RequestEnvelope) -> Result<(), Self::SendErr> {
+        self.num_sent.set(self.num_sent.get() + 1);
+        self.inner.send(message)
+    }
+
+    fn get(&self) -> Result<ResponseEnvelope, Option<Self::RecvErr>> {
+        self.inner.get()
+    }
+}
+
+type Cont = Controller<'static, CountingTransport, SyncEventFutureSharedState>;
+
+static CONSOLE: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+fn console_sink(bytes: &[u8]) {
+    CONSOLE.lock().unwrap().extend_from_slice(bytes);
+}
+
+fn device(transport: MpscTransport<ResponseEnvelope, RequestEnvelope>, console: Receiver<Vec<u8>>) {
+    common::serve_sim_with(Transparent::default(), Transparent::default(), transport, move |device| {
+        if let Ok(bytes) = console.try_recv() {
+            device.push_console(&bytes);
+        }
+    });
+}
+
+#[test]
+fn pushed_values_replace_polling() {
+    let (cont, dev) = MpscTransport::new();
+    let (console, console_rx): (Sender<Vec<u8>>, _) = channel();
+    device(dev, console_rx);
+
+    let state: &'static _ = Box::leak(Box::new(SyncEventFutureSharedState::new()));
+    let mut cont: Cont = Controller::new(
+        Transparent::default(),
+        Transparent::default(),
+        CountingTransport { inner: cont, num_sent: Cell::new(0) },
+        state,
+    )
+    .with_console_sink(&console_sink);
+
+    assert_eq!(cont.subscribe(Topics::ALL), Ok(Topics::ALL));
+
+    // These are all answered without asking the device:
+    let sent = cont.transport.num_sent.get();
+    assert_eq!(cont.get_state(), State::Paused);
+    let gpio = cont.get_gpio_states();
+    let pwm = cont.get_pwm_config();
+    let _ = cont.get_timer_modes();
+    let _ = cont.get_clock();
+    assert_eq!(cont.transport.num_sent.get(), sent);
+
+    // And match what the device would have said:
+    assert_eq!(cont.request(RequestMessage::GetGpioStates), Ok(ResponseMessage::GetGpioStates(gpio)));
+    assert_eq!(cont.request(RequestMessage::GetPwmConfig), Ok(ResponseMessage::GetPwmConfig(pwm)));
+
+    // State changes are pushed as they happen:
+    cont.write_word(0x3000, Instruction::new_add_imm(R1, R1, 1).into());
+    cont.write_word(0x3001, Instruction::new_br(true, true, true, -2).into());
+    cont.set_pc(0x3000);
+
+    let _fut = cont.run_until_event();
+    assert_eq!(cont.get_state(), State::RunningUntilEvent);
+
+    cont.pause();
+    assert_eq!(cont.get_state(), State::Paused);
+
+    // Console output (more than fits in one notification):
+    let message = b"hello from the device! this is a long message.";
+    console.send(message.to_vec()).unwrap();
+
+    for _ in 0..1000 {
+        Control::tick(&mut cont);
+
+        if CONSOLE.lock().unwrap().len() == message.len() {
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(&*CONSOLE.lock().unwrap(), message);
+
+    // Once we unsubscribe, we go back to asking:
+    assert_eq!(cont.subscribe(Topics::NONE), Ok(Topics::NONE));
+
+    let sent = cont.transport.num_sent.get();
+    assert_eq!(cont.get_state(), State::Paused);
+    assert_eq!(cont.transport.num_sent.get(), sent + 1);
+}
+
+#[test]
+fn missed_notifications_fall_back_to_requests() {
+    let (cont, dev) = MpscTransport::new();
+
+    let state: &'static _ = Box::leak(Box::new(SyncEventFutureSharedState::new()));
+    let cont: Cont = Controller::new(
+        Transparent::default(),
+        Transparent::default(),
+        CountingTransport { inner: cont, num_sent: Cell::new(0) },
+        state,
+    );
+
+    // We play the device, answering ahead of time:
+    let notification = |seq, n| Envelope::new(seq, ResponseMessage::Notification(n));
+
+    dev.send(notification(1, Notification::State(State::Paused))).unwrap();
+    dev.send(Envelope::new(0, ResponseMessage::Subscribe(Topics::STATE))).unwrap();
+    assert_eq!(cont.subscribe(Topics::STATE), Ok(Topics::STATE));
+
+    let sent = cont.transport.num_sent.get();
+    assert_eq!(cont.get_state(), State::Paused);
+    assert_eq!(cont.transport.num_sent.get(), sent);
+
+    // #2 goes missing; the pushed state can't be trusted anymore:
+    dev.send(notification(3, Notification::Clock(0))).unwrap();
+    dev.send(Envelope::new(1, ResponseMessage::GetState(State::Halted))).unwrap();
+
+    assert_eq!(cont.get_state(), State::Halted);
+    assert_eq!(cont.transport.num_sent.get(), sent + 1);
+
+    // Until it's pushed again:
+    dev.send(notification(4, Notification::State(State::Paused))).unwrap();
+    assert_eq!(cont.get_state(), State::Paused);
+    assert_eq!(cont.transport.num_sent.get(), sent + 1);
+}
@@ -0,0 +1,206 @@
+//! Plays scenarios against a set of shims in virtual time.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use lc3_application_support::io_peripherals::InputSink;
+use lc3_application_support::scenario::{Action, PwmOutput, Scenario, ScenarioPlayer, Waveform};
+use lc3_application_support::shim_support::Shims;
+use lc3_shims::peripherals::{AdcShim, ClockShim, GpioShim, PwmShim, TimersShim};
+use lc3_traits::peripherals::adc::{Adc, AdcPin::*, AdcState};
+use lc3_traits::peripherals::gpio::{Gpio, GpioPin::*, GpioState};
+use lc3_traits::peripherals::pwm::{Pwm, PwmPeriod, PwmPin::*, PwmState};
+
+use pretty_assertions::assert_eq;
+
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+fn shims() -> Shims<'static> {
+    Shims {
+        gpio: Arc::new(RwLock::new(GpioShim::default())),
+        adc: Arc::new(RwLock::new(AdcShim::default())),
+        pwm: Arc::new(Mutex::new(PwmShim::default())),
+        timers: Arc::new(Mutex::new(TimersShim::default())),
+        clock: Arc::new(RwLock::new(ClockShim::default())),
+    }
+}
+
+#[derive(Default)]
+struct Typed(RefCell<String>);
+
+impl InputSink for Typed {
+    fn put_char(&self, c: char) -> Option<()> {
+        self.0.borrow_mut().push(c);
+        Some(())
+    }
+}
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn scenario(json: &str) -> Scenario {
+    Scenario::from_json(json).unwrap()
+}
+
+#[test]
+fn json_round_trip() {
+    let s = scenario(r#"{
+        "events": [
+            { "at": 50, "gpio": { "pin": "G3", "level": true } },
+            { "at": 120, "adc": { "pin": "A0", "value": 200 } },
+            { "at": 0, "every": 10, "until": 100, "input": { "chars": "a" } }
+        ]
+    }"#);
+
+    assert_eq!(s.events.len(), 3);
+    assert_eq!(s.events[1].action, Action::Adc { pin: A0, value: 200 });
+    assert_eq!(s.events[2].every.map(|e| e.get()), Some(10));
+    assert_eq!(Scenario::from_json(&s.to_json().unwrap()).unwrap(), s);
+}
+
+#[test]
+fn repeating_events() {
+    let shims = shims();
+    let typed = Typed::default();
+    let mut player = ScenarioPlayer::new(scenario(r#"{
+        "events": [{ "at": 5, "every": 10, "until": 30, "input": { "chars": "a" } }]
+    }"#), &shims, &typed);
+
+    player.advance_to(ms(4));
+    assert_eq!(*typed.0.borrow(), "");
+
+    player.advance_to(ms(15));
+    assert_eq!(*typed.0.borrow(), "aa");
+    assert!(!player.is_done());
+
+    // The last repeat is at 25; 35 is past `until`:
+    player.advance_to(ms(1000));
+    assert_eq!(*typed.0.borrow(), "aaa");
+    assert!(player.is_done());
+}
+
+#[test]
+fn simultaneous_events_keep_their_order() {
+    let shims = shims();
+    let typed = Typed::default();
+    let mut player = ScenarioPlayer::new(scenario(r#"{
+        "events": [
+            { "at": 10, "input": { "chars": "b" } },
+            { "at": 10, "input": { "chars": "c" } },
+            { "at": 0, "input": { "chars": "a" } }
+        ]
+    }"#), &shims, &typed);
+
+    player.advance_to(ms(10));
+    assert_eq!(*typed.0.borrow(), "abc");
+}
+
+#[test]
+fn time_does_not_go_backwards() {
+    let shims = shims();
+    let typed = Typed::default();
+    let mut player = ScenarioPlayer::new(Scenario::default(), &shims, &typed);
+
+    player.advance_to(ms(20));
+    player.advance_to(ms(10));
+    assert_eq!(player.now(), ms(20));
+
+    player.advance_by(ms(5));
+    assert_eq!(player.now(), ms(25));
+}
+
+#[test]
+fn failed_actions_are_recorded() {
+    let shims = shims();
+    let typed = Typed::default();
+    Gpio::set_state(&mut *RwLock::write(&shims.gpio).unwrap(), G0, GpioState::Output).unwrap();
+    Gpio::set_state(&mut *RwLock::write(&shims.gpio).unwrap(), G1, GpioState::Input).unwrap();
+
+    let mut player = ScenarioPlayer::new(scenario(r#"{
+        "events": [
+            { "at": 1, "gpio": { "pin": "G0", "level": true } },
+            { "at": 2, "gpio": { "pin": "G1", "level": true } },
+            { "at": 3, "adc": { "pin": "A0", "value": 7 } }
+        ]
+    }"#), &shims, &typed);
+    player.advance_to(ms(3));
+
+    assert_eq!(player.recording().failures, vec![
+        (ms(1), Action::Gpio { pin: G0, level: true }),
+        (ms(3), Action::Adc { pin: A0, value: 7 }),
+    ]);
+
+    assert_eq!(Gpio::read(&*RwLock::read(&shims.gpio).unwrap(), G1), Ok(true));
+}
+
+#[test]
+fn adc_values() {
+    let shims = shims();
+    let typed = Typed::default();
+    Adc::set_state(&mut *RwLock::write(&shims.adc).unwrap(), A2, AdcState::Enabled).unwrap();
+
+    let mut player = ScenarioPlayer::new(scenario(r#"{
+        "events": [{ "at": 0, "adc": { "pin": "A2", "value": 200 } }]
+    }"#), &shims, &typed);
+    player.advance_to(ms(0));
+
+    assert!(player.recording().failures.is_empty());
+    assert_eq!(Adc::read(&*RwLock::read(&shims.adc).unwrap(), A2), Ok(200));
+}
+
+#[test]
+fn gpio_waveforms() {
+    let shims = shims();
+    let typed = Typed::default();
+    let mut player = ScenarioPlayer::new(Scenario::default(), &shims, &typed);
+
+    let set = |state| Gpio::set_state(&mut *RwLock::write(&shims.gpio).unwrap(), G4, state).unwrap();
+    let write = |level| Gpio::write(&mut *RwLock::write(&shims.gpio).unwrap(), G4, level).unwrap();
+
+    player.advance_to(ms(0));
+    set(GpioState::Output);
+    player.advance_to(ms(10));
+    write(true);
+    player.advance_to(ms(20));
+    player.advance_to(ms(25)); // no change, no transition
+    write(false);
+    player.advance_to(ms(30));
+    set(GpioState::Input);
+    player.advance_to(ms(40));
+
+    let wave: &Waveform<Option<bool>> = &player.recording().gpio[G4];
+    assert_eq!(wave.transitions(), &[
+        (ms(0), None),
+        (ms(10), Some(false)),
+        (ms(20), Some(true)),
+        (ms(30), Some(false)),
+        (ms(40), None),
+    ]);
+
+    assert_eq!(wave.value_at(ms(27)), Some(&Some(true)));
+    assert_eq!(player.recording().gpio[G0].transitions(), &[(ms(0), None)]);
+}
+
+#[test]
+fn pwm_waveforms() {
+    let shims = shims();
+    let typed = Typed::default();
+    let mut player = ScenarioPlayer::new(Scenario::default(), &shims, &typed);
+
+    let period = PwmPeriod::from_millis(20).unwrap();
+
+    player.advance_to(ms(0));
+    Pwm::set_duty_cycle(&mut *Mutex::lock(&shims.pwm).unwrap(), P1, 0x8000);
+    Pwm::set_state(&mut *Mutex::lock(&shims.pwm).unwrap(), P1, PwmState::Enabled(period));
+    player.advance_to(ms(5));
+    Pwm::set_state(&mut *Mutex::lock(&shims.pwm).unwrap(), P1, PwmState::Disabled);
+    player.advance_to(ms(10));
+
+    assert_eq!(player.into_recording().pwm[P1].transitions(), &[
+        (ms(0), PwmOutput { period: None, duty_cycle: 0 }),
+        (ms(5), PwmOutput { period: Some(period), duty_cycle: 0x8000 }),
+        (ms(10), PwmOutput { period: None, duty_cycle: 0x8000 }),
+    ]);
+}
@@ -0,0 +1,63 @@
+//! Runs a `Controller` against a simulator served over TCP and Unix domain
+//! sockets.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use lc3_application_support::event_loop::Backoff;
+use lc3_application_support::init::{
+    serve_over_socket, BlackBox, Init, SimDevice, SocketConfig, SocketDevice,
+};
+use lc3_application_support::io_peripherals::{InputSink, OutputSource};
+use lc3_device_support::rpc::transport::socket_host::{
+    HostSocketTransport, SocketAddress,
+};
+
+use std::net::TcpListener;
+
+fn spawn_server(transport: HostSocketTransport) {
+    common::spawn_device(move |b| {
+        let (sim, _, input, output) = SimDevice::init(b);
+
+        let input = input.map(|i| i as &dyn InputSink);
+        let output = output.map(|o| o as &dyn OutputSource);
+
+        serve_over_socket(sim, transport, input, output, &Backoff::default())
+    });
+}
+
+fn run(addr: SocketAddress) {
+    common::controllers_take_turns(|b| {
+        SocketDevice::init_with_config(b, SocketConfig::new(addr.clone())).0
+    });
+
+    let mut b = BlackBox::new();
+    let (cont, _, input, output) =
+        SocketDevice::init_with_config(&mut b, SocketConfig::new(addr));
+
+    common::console_is_relayed(cont, input.unwrap(), output.unwrap());
+}
+
+// Both transports are exercised in one test since the simulator uses static
+// state that can't be shared between simulators running at the same time.
+#[test]
+fn loopback() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = SocketAddress::Tcp(listener.local_addr().unwrap());
+
+    spawn_server(HostSocketTransport::from_tcp_listener(listener).unwrap());
+    run(addr);
+
+    #[cfg(unix)]
+    {
+        let path = std::env::temp_dir()
+            .join(format!("lc3-socket-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        spawn_server(HostSocketTransport::listen_unix(&path).unwrap());
+        run(SocketAddress::Unix(path.clone()));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
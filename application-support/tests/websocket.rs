@@ -0,0 +1,52 @@
+//! Runs a `Controller` against a simulator served over a WebSocket on the
+//! loopback interface.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use lc3_application_support::event_loop::Backoff;
+use lc3_application_support::init::{
+    BlackBox, Init, SimDevice, WebSocketConfig, WebSocketDevice,
+    WebSocketServer,
+};
+use lc3_application_support::io_peripherals::{InputSink, OutputSource};
+
+// Serves a simulator (with its console) on another thread and returns the URL
+// to connect to.
+fn serve() -> String {
+    let server = WebSocketServer::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", server.local_addr().unwrap());
+
+    common::spawn_device(move |b| {
+        let (sim, _, input, output) = SimDevice::init(b);
+
+        let input = input.map(|i| i as &dyn InputSink);
+        let output = output.map(|o| o as &dyn OutputSource);
+
+        server.serve(sim, input, output, &Backoff::default()).unwrap();
+    });
+
+    url
+}
+
+// The console is checked in the same test since the simulator uses static
+// state that can't be shared between simulators running at the same time.
+#[test]
+fn loopback() {
+    let url = serve();
+
+    common::controllers_take_turns(|b| {
+        WebSocketDevice::init_with_config(b, WebSocketConfig::new(url.clone())).0
+    });
+
+    console(url);
+}
+
+fn console(url: String) {
+    let mut b = BlackBox::new();
+    let (cont, _, input, output) =
+        WebSocketDevice::init_with_config(&mut b, WebSocketConfig::new(url));
+
+    common::console_is_relayed(cont, input.unwrap(), output.unwrap());
+}
@@ -0,0 +1,357 @@
+//! A faster [`InstructionInterpreter`] that caches decoded basic blocks.
+//!
+//! [`BlockInterpreter`] wraps an [`Interpreter`] and only changes how
+//! instructions are fetched: instructions are decoded a basic block (a run of
+//! instructions ending in a control flow instruction) at a time and the
+//! decoded blocks are kept in a small cache. Going around a loop then doesn't
+//! read and decode the same words over and over. Everything else (execution,
+//! devices, interrupts, faults, the call stack) is the [`Interpreter`]'s, so
+//! the two produce identical results.
+//!
+//! Pages that hold cached blocks are watched for writes, whether from the
+//! program, from [`Control`], from loading a program or from a reset. Writing
+//! to one drops all the blocks in that page, so self-modifying code behaves as
+//! it does on the [`Interpreter`].
+//!
+//! [`step`] still executes a single instruction; [`step_many`] runs straight
+//! through the cached blocks. The [`Simulator`] only uses [`step_many`] when
+//! there are no breakpoints, watchpoints or depth conditions to check between
+//! instructions and otherwise falls back to single-stepping.
+//!
+//! [`Control`]: lc3_traits::control::Control
+//! [`Simulator`]: crate::sim::Simulator
+//! [`step`]: InstructionInterpreter::step
+//! [`step_many`]: InstructionInterpreter::step_many
+
+use crate::interp::{
+    CallStack, FaultHandling, InstructionInterpreter, InstructionInterpreterPeripheralAccess,
+    Interpreter, MachineState, ReadAttempt, UnbalancedReturn, WriteAttempt,
+};
+
+use lc3_isa::{Addr, Instruction, Reg, Word, MEM_MAPPED_START_ADDR};
+use lc3_traits::control::load::{PageIndex, NUM_PAGES, PAGE_SIZE_IN_WORDS};
+use lc3_traits::control::metadata::{Identifier, ProgramMetadata, Version, version_from_crate};
+use lc3_traits::error::Error;
+use lc3_traits::{memory::Memory, peripherals::Peripherals};
+
+use core::any::TypeId;
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+
+/// A set of memory pages.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct PageSet([u64; NUM_PAGES / 64]);
+
+impl PageSet {
+    pub(crate) const fn new() -> Self {
+        Self([0; NUM_PAGES / 64])
+    }
+
+    const fn bit(page: PageIndex) -> (usize, u64) {
+        ((page / 64) as usize, 1 << (page % 64))
+    }
+
+    pub(crate) fn insert(&mut self, page: PageIndex) {
+        let (idx, bit) = Self::bit(page);
+        self.0[idx] |= bit;
+    }
+
+    pub(crate) fn contains(&self, page: PageIndex) -> bool {
+        let (idx, bit) = Self::bit(page);
+        self.0[idx] & bit != 0
+    }
+
+    pub(crate) fn remove_all(&mut self, other: &PageSet) {
+        self.0.iter_mut().zip(other.0.iter()).for_each(|(s, o)| *s &= !o);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.iter().all(|s| *s == 0)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = PageIndex> + '_ {
+        (0..NUM_PAGES).map(|p| p as PageIndex).filter(move |p| self.contains(*p))
+    }
+}
+
+const fn page_of(addr: Addr) -> PageIndex {
+    (addr / PAGE_SIZE_IN_WORDS) as PageIndex
+}
+
+// Bigger caches and longer blocks help programs with larger loops but make the
+// interpreter bigger (this is ~17KiB).
+const NUM_BLOCKS: usize = 64;
+const MAX_BLOCK_LEN: usize = 32;
+
+#[derive(Debug, Copy, Clone)]
+struct Block {
+    start: Addr,
+    // Empty slots have a length of 0.
+    len: usize,
+    insns: [(Word, Option<Instruction>); MAX_BLOCK_LEN],
+}
+
+impl Block {
+    const EMPTY: Block = Block { start: 0, len: 0, insns: [(0, None); MAX_BLOCK_LEN] };
+
+    // Blocks never span pages.
+    fn page(&self) -> PageIndex {
+        page_of(self.start)
+    }
+
+    fn ends_with(insn: Option<Instruction>) -> bool {
+        use Instruction::*;
+
+        // Extended instructions are free to jump and words that aren't
+        // instructions fault, so they end blocks too.
+        matches!(
+            insn,
+            Some(Br { .. } | Jmp { .. } | Jsr { .. } | Jsrr { .. } | Ret | Rti | Trap { .. } | Extended { .. })
+                | None
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct BlockInterpreter<'per, M: Memory, P: Peripherals<'per>> {
+    inner: Interpreter<'per, M, P>,
+    blocks: [Block; NUM_BLOCKS],
+}
+
+impl<'a, M: Memory + Default, P: Peripherals<'a>> Default for BlockInterpreter<'a, M, P> {
+    fn default() -> Self {
+        Interpreter::<M, P>::default().into()
+    }
+}
+
+/// Build an [`Interpreter`] (i.e. with the
+/// [`InterpreterBuilder`](crate::interp::InterpreterBuilder)) and then convert
+/// it; the machine's state is left as is.
+impl<'a, M: Memory, P: Peripherals<'a>> From<Interpreter<'a, M, P>> for BlockInterpreter<'a, M, P> {
+    fn from(mut inner: Interpreter<'a, M, P>) -> Self {
+        inner.unwatch_all_pages();
+        Self { inner, blocks: [Block::EMPTY; NUM_BLOCKS] }
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> Index<Reg> for BlockInterpreter<'a, M, P> {
+    type Output = Word;
+
+    fn index(&self, reg: Reg) -> &Self::Output {
+        &self.inner[reg]
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> IndexMut<Reg> for BlockInterpreter<'a, M, P> {
+    fn index_mut(&mut self, reg: Reg) -> &mut Self::Output {
+        &mut self.inner[reg]
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> Deref for BlockInterpreter<'a, M, P> {
+    type Target = P;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> DerefMut for BlockInterpreter<'a, M, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreterPeripheralAccess<'a>
+    for BlockInterpreter<'a, M, P>
+{ }
+
+impl<'a, M: Memory, P: Peripherals<'a>> BlockInterpreter<'a, M, P> {
+    // Returns the slot of the block starting at `start`, decoding it first if
+    // it isn't cached. `start` must be memory backed.
+    fn block_at(&mut self, start: Addr) -> usize {
+        let slot = (start as usize) % NUM_BLOCKS;
+        if self.blocks[slot].len != 0 && self.blocks[slot].start == start {
+            return slot;
+        }
+
+        let page = page_of(start);
+        let mut block = Block { start, ..Block::EMPTY };
+        let mut addr = start;
+
+        loop {
+            let (word, insn) = self.inner.fetch(addr);
+            block.insns[block.len] = (word, insn);
+            block.len += 1;
+
+            addr = addr.wrapping_add(1);
+            if Block::ends_with(insn) || block.len == MAX_BLOCK_LEN || page_of(addr) != page {
+                break;
+            }
+        }
+
+        self.inner.watch_page(page);
+        self.blocks[slot] = block;
+
+        slot
+    }
+
+    // Drops the blocks in pages that have been written to since the last
+    // call; returns whether there were any such pages.
+    fn drop_written_blocks(&mut self) -> bool {
+        match self.inner.take_dirtied_pages() {
+            Some(pages) => {
+                self.blocks
+                    .iter_mut()
+                    .filter(|b| pages.contains(b.page()))
+                    .for_each(|b| b.len = 0);
+
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for BlockInterpreter<'a, M, P> {
+    const ID: Identifier = Identifier::new_from_str_that_crashes_on_invalid_inputs("Blck");
+    const VER: Version = version_from_crate!();
+
+    fn step(&mut self) -> MachineState {
+        self.step_many(1).1
+    }
+
+    fn step_many(&mut self, max_steps: usize) -> (usize, MachineState) {
+        let mut steps = 0;
+
+        while steps < max_steps {
+            if let state @ MachineState::Halted = self.get_machine_state() {
+                return (steps, state);
+            }
+
+            let _ = self.drop_written_blocks();
+
+            let start = self.get_pc();
+            if start >= MEM_MAPPED_START_ADDR {
+                // Only memory is cached; anything else goes the usual route.
+                let state = self.inner.step();
+                steps += 1;
+
+                if let MachineState::Halted = state { return (steps, state) }
+                if self.inner.should_stop() { return (steps, state) }
+
+                continue;
+            }
+
+            let slot = self.block_at(start);
+            for idx in 0..self.blocks[slot].len {
+                // Interrupts, faults and the like take us out of the block:
+                if self.get_pc() != start.wrapping_add(idx as Addr) {
+                    break;
+                }
+
+                let insn = self.blocks[slot].insns[idx];
+                let state = self.inner.step_with(|_, _| insn);
+                steps += 1;
+
+                // The block may have just overwritten itself so check for
+                // writes before going on:
+                let written = self.drop_written_blocks();
+
+                if let MachineState::Halted = state { return (steps, state) }
+                if self.inner.should_stop() || steps == max_steps { return (steps, state) }
+                if written { break }
+            }
+        }
+
+        (steps, self.get_machine_state())
+    }
+
+    fn set_pc(&mut self, addr: Addr) {
+        self.inner.set_pc(addr)
+    }
+
+    fn get_pc(&self) -> Addr {
+        self.inner.get_pc()
+    }
+
+    fn set_word(&mut self, addr: Addr, word: Word) -> WriteAttempt {
+        self.inner.set_word(addr, word)
+    }
+
+    fn get_word(&self, addr: Addr) -> ReadAttempt {
+        self.inner.get_word(addr)
+    }
+
+    fn set_word_unchecked(&mut self, addr: Addr, word: Word) {
+        self.inner.set_word_unchecked(addr, word)
+    }
+
+    fn get_word_unchecked(&self, addr: Addr) -> Word {
+        self.inner.get_word_unchecked(addr)
+    }
+
+    fn set_word_force_memory_backed(&mut self, addr: Addr, word: Word) {
+        self.inner.set_word_force_memory_backed(addr, word)
+    }
+
+    fn get_word_force_memory_backed(&self, addr: Addr) -> Word {
+        self.inner.get_word_force_memory_backed(addr)
+    }
+
+    fn get_machine_state(&self) -> MachineState {
+        self.inner.get_machine_state()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset()
+    }
+
+    fn halt(&mut self) {
+        self.inner.halt()
+    }
+
+    fn set_error(&self, err: Error) {
+        self.inner.set_error(err)
+    }
+
+    fn get_error(&self) -> Option<Error> {
+        self.inner.get_error()
+    }
+
+    fn set_fault_handling(&mut self, handling: FaultHandling) {
+        self.inner.set_fault_handling(handling)
+    }
+
+    fn get_fault_handling(&self) -> FaultHandling {
+        self.inner.get_fault_handling()
+    }
+
+    fn get_call_stack(&self) -> &CallStack {
+        self.inner.get_call_stack()
+    }
+
+    fn get_call_stack_depth(&self) -> u64 {
+        self.inner.get_call_stack_depth()
+    }
+
+    fn take_unbalanced_return(&mut self) -> Option<UnbalancedReturn> {
+        self.inner.take_unbalanced_return()
+    }
+
+    fn commit_page(&mut self, page_idx: PageIndex, page: &[Word; PAGE_SIZE_IN_WORDS as usize]) {
+        self.inner.commit_page(page_idx, page)
+    }
+
+    fn get_program_metadata(&self) -> ProgramMetadata {
+        self.inner.get_program_metadata()
+    }
+
+    fn set_program_metadata(&mut self, metadata: ProgramMetadata) {
+        self.inner.set_program_metadata(metadata)
+    }
+
+    fn type_id() -> TypeId {
+        TypeId::of::<BlockInterpreter<'static, lc3_traits::memory::MemoryStub, lc3_traits::peripherals::stubs::PeripheralsStub<'static>>>()
+    }
+}
@@ -0,0 +1,105 @@
+//! Executing [ISA extensions](lc3_isa::ext) in the
+//! [`Interpreter`](crate::interp::Interpreter).
+//!
+//! An [`ExtensionHandler`] decodes instructions under the reserved opcode (as
+//! an [`IsaExtension`]) and executes them against an [`ExtensionContext`].
+//! Encodings that the handler doesn't claim still raise an illegal opcode
+//! exception, as does everything under the reserved opcode when no handler is
+//! registered.
+//!
+//! [`MulXor`] is an example: it adds `MUL` and `XOR`.
+
+use crate::interp::{Acv, ReadAttempt, WriteAttempt};
+
+use lc3_isa::ext::IsaExtension;
+use lc3_isa::{Addr, Instruction, Reg, Word};
+
+use core::convert::TryInto;
+use core::fmt::{self, Debug, Formatter};
+
+/// What an extension's instructions get to do to the machine.
+///
+/// Memory accesses are checked like the standard instructions' are; return
+/// the [`Acv`] from [`ExtensionHandler::execute`] to have it handled like any
+/// other access control violation.
+pub trait ExtensionContext {
+    fn reg(&self, reg: Reg) -> Word;
+    fn set_reg(&mut self, reg: Reg, word: Word);
+    /// Sets the condition codes based on `word`, like `ADD` and `LD` do.
+    fn set_condition_codes(&mut self, word: Word);
+
+    /// The address of the next instruction (i.e. already incremented).
+    fn pc(&self) -> Addr;
+    fn jump(&mut self, addr: Addr);
+
+    fn load(&mut self, addr: Addr) -> ReadAttempt;
+    fn store(&mut self, addr: Addr, word: Word) -> WriteAttempt;
+}
+
+/// Executes the instructions of an [`IsaExtension`].
+///
+/// `execute` is only called with bits that the extension
+/// [decodes](IsaExtension::decodes).
+pub trait ExtensionHandler: IsaExtension + Debug + Sync {
+    fn execute(&self, bits: u16, cpu: &mut dyn ExtensionContext) -> Result<(), Acv>;
+}
+
+/// Adds `MUL` and `XOR`:
+///
+/// ```text
+/// MUL DR, SR1, SR2: 1101 DDD SSS 000 TTT
+/// XOR DR, SR1, SR2: 1101 DDD SSS 001 TTT
+/// ```
+///
+/// Both set the condition codes.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct MulXor;
+
+impl MulXor {
+    const MUL: u16 = 0b000;
+    const XOR: u16 = 0b001;
+
+    const fn encode(op: u16, dr: Reg, sr1: Reg, sr2: Reg) -> Instruction {
+        Instruction::new_extended(
+            ((dr as u16) << 9) | ((sr1 as u16) << 6) | (op << 3) | (sr2 as u16),
+        )
+    }
+
+    pub const fn new_mul(dr: Reg, sr1: Reg, sr2: Reg) -> Instruction {
+        Self::encode(Self::MUL, dr, sr1, sr2)
+    }
+
+    pub const fn new_xor(dr: Reg, sr1: Reg, sr2: Reg) -> Instruction {
+        Self::encode(Self::XOR, dr, sr1, sr2)
+    }
+
+    fn decode(bits: u16) -> (u16, Reg, Reg, Reg) {
+        let reg = |b: u16| -> Reg { ((b & 0b111) as u8).try_into().unwrap() };
+        ((bits >> 3) & 0b111, reg(bits >> 9), reg(bits >> 6), reg(bits))
+    }
+}
+
+impl IsaExtension for MulXor {
+    fn decodes(&self, bits: u16) -> bool {
+        matches!((bits >> 3) & 0b111, Self::MUL | Self::XOR)
+    }
+
+    fn fmt(&self, bits: u16, fmt: &mut Formatter<'_>) -> fmt::Result {
+        let (op, dr, sr1, sr2) = Self::decode(bits);
+        let name = if op == Self::MUL { "MUL" } else { "XOR" };
+        write!(fmt, "{}   {}, {}, {}", name, dr, sr1, sr2)
+    }
+}
+
+impl ExtensionHandler for MulXor {
+    fn execute(&self, bits: u16, cpu: &mut dyn ExtensionContext) -> Result<(), Acv> {
+        let (op, dr, sr1, sr2) = Self::decode(bits);
+        let (a, b) = (cpu.reg(sr1), cpu.reg(sr2));
+
+        let res = if op == Self::MUL { a.wrapping_mul(b) } else { a ^ b };
+        cpu.set_reg(dr, res);
+        cpu.set_condition_codes(res);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,386 @@
+//! Optional tracking of which registers and memory locations have been written
+//! for the [`Simulator`].
+//!
+//! An [`InitTracker`] keeps a shadow "initialized" bit for every register and
+//! for every memory location below the memory mapped region. Bits are set for
+//! what the [`Simulator`] has already loaded when it's given the tracker
+//! (system space, where the OS lives, and pages committed through the load
+//! API), for any other program image the tracker is seeded with (see
+//! [`InitTracker::with_program`]), by writes made through [`Control`] and by
+//! the instructions the [`Simulator`] steps. The [`Simulator`] reports an
+//! [`Event::UninitializedRead`] when an instruction consumes a value (or is
+//! fetched from a location) whose bit isn't set.
+//!
+//! Condition codes, the PSR and device registers always count as initialized.
+//!
+//! Programs that read uninitialized values often happen to work because memory
+//! and registers start out zeroed; to flush such bugs out, a tracker can
+//! [fill](InitTracker::randomize_on_reset) everything that isn't initialized
+//! with random values when the simulator is reset.
+//!
+//! Trackers are ~16 KiB, so the [`Simulator`] keeps the one it's given in a
+//! `Box`; [`set_init_tracker`] is only available with the `std` feature.
+//!
+//! [`set_init_tracker`]: crate::sim::Simulator::set_init_tracker
+//! [`Simulator`]: crate::sim::Simulator
+//! [`Control`]: lc3_traits::control::Control
+//! [`Event::UninitializedRead`]: lc3_traits::control::Event::UninitializedRead
+
+// The hooks the simulator calls go unused without `std`.
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
+
+#[cfg(feature = "std")]
+use crate::block::PageSet;
+use crate::interp::InstructionInterpreterPeripheralAccess;
+use crate::lc3b::is_lc3b;
+
+use lc3_isa::util::AssembledProgram;
+use lc3_isa::{Addr, Instruction, Reg, Word, MEM_MAPPED_START_ADDR, USER_PROGRAM_START_ADDR};
+use lc3_traits::control::load::{Index as PIdx, PageIndex};
+use lc3_traits::control::UninitializedRead;
+use lc3_traits::peripherals::Peripherals;
+
+use core::convert::TryFrom;
+use core::ops::{Deref, RangeInclusive};
+
+const TRACKED_WORDS: usize = MEM_MAPPED_START_ADDR as usize;
+
+type Bits = [u32; TRACKED_WORDS / 32];
+
+// What the tracker needs to remember about the machine from before a step.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct PreStep {
+    pub(crate) pc: Addr,
+    depth: u64,
+    regs: [Word; Reg::NUM_REGS],
+    read: Option<UninitializedRead>,
+    dest: Option<Reg>,
+    store_addr: Option<Addr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InitTracker {
+    // What's initialized right after a reset: the program image the tracker
+    // was seeded with and any pages committed through the load API since.
+    loaded: Bits,
+    memory: Bits,
+    registers: [bool; Reg::NUM_REGS],
+    rng: Option<XorShift>,
+}
+
+impl Default for InitTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InitTracker {
+    /// A tracker that considers nothing initialized.
+    pub fn new() -> Self {
+        Self {
+            loaded: [0; TRACKED_WORDS / 32],
+            memory: [0; TRACKED_WORDS / 32],
+            registers: [false; Reg::NUM_REGS],
+            rng: None,
+        }
+    }
+
+    /// Marks the words set in `program` as initialized (i.e. a user program
+    /// that's written to memory after the tracker is set).
+    pub fn with_program(mut self, program: &AssembledProgram) -> Self {
+        for (addr, _) in program {
+            self.load(addr);
+        }
+
+        self
+    }
+
+    pub fn with_initialized_memory(mut self, addrs: RangeInclusive<Addr>) -> Self {
+        for addr in addrs {
+            self.load(addr);
+        }
+
+        self
+    }
+
+    pub fn with_initialized_registers(mut self, regs: &[Reg]) -> Self {
+        regs.iter().for_each(|r| self.mark_register(*r));
+        self
+    }
+
+    /// On every reset, fill the memory and registers that aren't initialized
+    /// with values from a PRNG seeded with `seed`.
+    pub fn randomize_on_reset(mut self, seed: u64) -> Self {
+        self.rng = Some(XorShift::new(seed));
+        self
+    }
+
+    pub fn is_memory_initialized(&self, addr: Addr) -> bool {
+        addr >= MEM_MAPPED_START_ADDR || get(&self.memory, addr)
+    }
+
+    pub fn is_register_initialized(&self, reg: Reg) -> bool {
+        self.registers[idx(reg)]
+    }
+
+    fn load(&mut self, addr: Addr) {
+        if addr < MEM_MAPPED_START_ADDR {
+            set(&mut self.loaded, addr);
+            set(&mut self.memory, addr);
+        }
+    }
+
+    pub(crate) fn mark_memory(&mut self, addr: Addr) {
+        if addr < MEM_MAPPED_START_ADDR {
+            set(&mut self.memory, addr);
+        }
+    }
+
+    pub(crate) fn mark_register(&mut self, reg: Reg) {
+        self.registers[idx(reg)] = true;
+    }
+
+    // Called by the simulator when the tracker is set. Pages committed before
+    // then are assumed to have made it into memory already.
+    #[cfg(feature = "std")]
+    pub(crate) fn seed(&mut self, committed_pages: &PageSet) {
+        for addr in 0..USER_PROGRAM_START_ADDR {
+            self.load(addr);
+        }
+
+        for page_idx in committed_pages.iter() {
+            for addr in PIdx(page_idx).as_index_range() {
+                if let Ok(addr) = Addr::try_from(addr) {
+                    self.load(addr);
+                }
+            }
+        }
+    }
+
+    // Committed pages only show up in memory after the next reset.
+    pub(crate) fn commit_page(&mut self, page_idx: PageIndex) {
+        for addr in PIdx(page_idx).as_index_range() {
+            if let Ok(addr) = Addr::try_from(addr) {
+                if addr < MEM_MAPPED_START_ADDR {
+                    set(&mut self.loaded, addr);
+                }
+            }
+        }
+    }
+
+    // To be called after the interpreter is reset: memory goes back to the
+    // loaded image and the registers are all cleared.
+    pub(crate) fn reset<'a, I: InstructionInterpreterPeripheralAccess<'a>>(&mut self, interp: &mut I)
+    where
+        <I as Deref>::Target: Peripherals<'a>,
+    {
+        self.memory = self.loaded;
+        self.registers = [false; Reg::NUM_REGS];
+
+        if let Some(rng) = self.rng.as_mut() {
+            for addr in 0..MEM_MAPPED_START_ADDR {
+                if !get(&self.memory, addr) {
+                    interp.set_word_unchecked(addr, rng.next_word());
+                }
+            }
+
+            for reg in Reg::REGS.iter() {
+                interp[*reg] = rng.next_word();
+            }
+        }
+    }
+
+    pub(crate) fn pre_step<'a, I: InstructionInterpreterPeripheralAccess<'a>>(&self, interp: &I) -> PreStep
+    where
+        <I as Deref>::Target: Peripherals<'a>,
+    {
+        use Instruction::*;
+        use UninitializedRead::*;
+
+        let pc = interp.get_pc();
+
+        let mut regs = [0; Reg::NUM_REGS];
+        Reg::REGS.iter().for_each(|r| regs[idx(*r)] = interp[*r]);
+
+        let mut pre = PreStep {
+            pc,
+            depth: interp.get_call_stack_depth(),
+            regs,
+            read: None,
+            dest: None,
+            store_addr: None,
+        };
+
+        if !self.is_memory_initialized(pc) {
+            pre.read = Some(Memory(pc));
+            return pre;
+        }
+
+        // We can't pick out the operands of LC-3b instructions or of
+        // instructions in the memory mapped region (reads there can have side
+        // effects).
+        if is_lc3b::<I>() || pc >= MEM_MAPPED_START_ADDR {
+            return pre;
+        }
+
+        let insn = match Instruction::try_from(interp.get_word_unchecked(pc)) {
+            Ok(insn) => insn,
+            Err(_) => return pre,
+        };
+
+        let reg = |r: Reg| regs[idx(r)];
+        let rel = |offset: i16| pc.wrapping_add(1).wrapping_add(offset as Word);
+        let indirect = |ptr: Addr| if ptr < MEM_MAPPED_START_ADDR {
+            Some(interp.get_word_unchecked(ptr))
+        } else {
+            None
+        };
+
+        let (sources, loads): ([Option<Reg>; 2], [Option<Addr>; 2]) = match insn {
+            AddReg { dr, sr1, sr2 } | AndReg { dr, sr1, sr2 } => {
+                pre.dest = Some(dr);
+                ([Some(sr1), Some(sr2)], [None, None])
+            }
+            // `AND Rx, Rx, #0` is how registers get cleared:
+            AndImm { dr, imm5: 0, .. } => {
+                pre.dest = Some(dr);
+                ([None, None], [None, None])
+            }
+            AddImm { dr, sr1, .. } | AndImm { dr, sr1, .. } | Not { dr, sr: sr1 } => {
+                pre.dest = Some(dr);
+                ([Some(sr1), None], [None, None])
+            }
+            Jmp { base } => ([Some(base), None], [None, None]),
+            Jsrr { base } => {
+                pre.dest = Some(Reg::R7);
+                ([Some(base), None], [None, None])
+            }
+            Jsr { .. } => {
+                pre.dest = Some(Reg::R7);
+                ([None, None], [None, None])
+            }
+            Ret => ([Some(Reg::R7), None], [None, None]),
+            Ld { dr, offset9 } => {
+                pre.dest = Some(dr);
+                ([None, None], [Some(rel(offset9)), None])
+            }
+            Ldi { dr, offset9 } => {
+                pre.dest = Some(dr);
+                let ptr = rel(offset9);
+                ([None, None], [Some(ptr), indirect(ptr)])
+            }
+            Ldr { dr, base, offset6 } => {
+                pre.dest = Some(dr);
+                ([Some(base), None], [Some(reg(base).wrapping_add(offset6 as Word)), None])
+            }
+            Lea { dr, .. } => {
+                pre.dest = Some(dr);
+                ([None, None], [None, None])
+            }
+            St { sr, offset9 } => {
+                pre.store_addr = Some(rel(offset9));
+                ([Some(sr), None], [None, None])
+            }
+            Sti { sr, offset9 } => {
+                let ptr = rel(offset9);
+                pre.store_addr = indirect(ptr);
+                ([Some(sr), None], [Some(ptr), None])
+            }
+            Str { sr, base, offset6 } => {
+                pre.store_addr = Some(reg(base).wrapping_add(offset6 as Word));
+                ([Some(sr), Some(base)], [None, None])
+            }
+            // The saved PC and PSR:
+            Rti => {
+                let sp = reg(Reg::R6);
+                ([Some(Reg::R6), None], [Some(sp), Some(sp.wrapping_add(1))])
+            }
+            Br { .. } | Trap { .. } | Extended { .. } => ([None, None], [None, None]),
+        };
+
+        // Note that an LDI's second load is only checked if its first one
+        // passes:
+        pre.read = sources.iter().flatten()
+            .find(|r| !self.is_register_initialized(**r))
+            .map(|r| Register(*r))
+            .or_else(|| loads.iter().flatten()
+                .find(|a| !self.is_memory_initialized(**a))
+                .map(|a| Memory(*a)));
+
+        pre
+    }
+
+    pub(crate) fn post_step<'a, I: InstructionInterpreterPeripheralAccess<'a>>(&mut self, interp: &I, pre: PreStep) -> Option<UninitializedRead>
+    where
+        <I as Deref>::Target: Peripherals<'a>,
+    {
+        let call_stack = interp.get_call_stack();
+        let depth = call_stack.depth();
+
+        // Registers the step changed (i.e. R6 on a stack switch) are now
+        // initialized:
+        for reg in Reg::REGS.iter() {
+            if interp[*reg] != pre.regs[idx(*reg)] {
+                self.mark_register(*reg);
+            }
+        }
+
+        // As are the PSR and PC pushed when entering a trap, interrupt or
+        // exception:
+        if depth > pre.depth {
+            let vectored = call_stack.frame((depth - 1) as usize).map_or(true, |f| f.kind.is_vectored());
+            if vectored {
+                let sp = interp[Reg::R6];
+                self.mark_memory(sp);
+                self.mark_memory(sp.wrapping_add(1));
+            }
+        }
+
+        // An interrupt or a fault means the instruction at the old PC didn't
+        // run:
+        if call_stack.preempted(pre.depth, pre.pc) {
+            return None;
+        }
+
+        if let Some(dr) = pre.dest {
+            self.mark_register(dr);
+        }
+
+        if let Some(addr) = pre.store_addr {
+            self.mark_memory(addr);
+        }
+
+        pre.read
+    }
+}
+
+fn idx(reg: Reg) -> usize {
+    Into::<u8>::into(reg) as usize
+}
+
+fn get(bits: &Bits, addr: Addr) -> bool {
+    bits[addr as usize / 32] & (1 << (addr % 32)) != 0
+}
+
+fn set(bits: &mut Bits, addr: Addr) {
+    bits[addr as usize / 32] |= 1 << (addr % 32);
+}
+
+// Good enough for filling memory with junk.
+#[derive(Debug, Copy, Clone)]
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // The state can't be zero:
+        Self(seed | 1)
+    }
+
+    fn next_word(&mut self) -> Word {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 >> 32) as Word
+    }
+}
@@ -11,12 +11,20 @@ use lc3_isa::{
 };
 use lc3_traits::control::metadata::{Identifier, ProgramMetadata, Version, version_from_crate};
 use lc3_traits::control::load::{PageIndex, PAGE_SIZE_IN_WORDS};
+#[cfg(not(feature = "std"))]
 use lc3_traits::control::control::MAX_CALL_STACK_DEPTH;
-use lc3_traits::peripherals::{gpio::GpioPinArr, timers::TimerArr};
+use lc3_traits::control::call_stack::{CallStackFrame, CallStackPage, FrameKind, CALL_STACK_PAGE_SIZE};
+use lc3_traits::peripherals::{adc::AdcPinArr, gpio::GpioPinArr, pwm::PwmPinArr, timers::TimerArr};
 use lc3_traits::{memory::Memory, peripherals::Peripherals};
-use lc3_traits::peripherals::{gpio::Gpio, input::Input, output::Output, timers::Timers};
+use lc3_traits::peripherals::{adc::Adc, gpio::Gpio, input::Input, output::Output, pwm::Pwm, timers::Timers};
 use lc3_traits::error::Error;
-use crate::mem_mapped::Interrupt;
+use crate::mem_mapped::{
+    interrupt_mask, interrupt_priority, interrupt_source_ready, reset_interrupt_controller,
+    reset_interrupt_source_flag, INTERRUPT_VECTORS,
+};
+use lc3_traits::control::InterruptSource;
+use crate::ext::{ExtensionContext, ExtensionHandler};
+use crate::block::PageSet;
 
 use core::any::TypeId;
 use core::convert::TryInto;
@@ -67,9 +75,9 @@ where
     }
 
     fn reset_peripherals(&mut self) {
-        use lc3_traits::peripherals::gpio::{GPIO_PINS, GpioState};
-        use lc3_traits::peripherals::adc::{Adc, ADC_PINS, AdcState};
-        use lc3_traits::peripherals::pwm::{Pwm, PWM_PINS, PwmState};
+        use lc3_traits::peripherals::gpio::{GPIO_PINS, GpioState, GpioTrigger, GpioPull};
+        use lc3_traits::peripherals::adc::{ADC_PINS, AdcState, AdcMode, AdcInterrupt, ADC_DEFAULT_SAMPLE_PERIOD};
+        use lc3_traits::peripherals::pwm::{PWM_PINS, PwmState};
         use lc3_traits::peripherals::timers::{TIMERS, TimerMode, TimerState};
         use lc3_traits::peripherals::clock::Clock;
 
@@ -77,21 +85,30 @@ where
 
         for pin in GPIO_PINS.iter() {
             let _ = Gpio::set_state(self.get_peripherals_mut(), *pin, GpioState::Disabled);
+            let _ = Gpio::set_trigger(self.get_peripherals_mut(), *pin, GpioTrigger::RisingEdge);
+            let _ = Gpio::set_pull(self.get_peripherals_mut(), *pin, GpioPull::Floating);
             Gpio::reset_interrupt_flag(self.get_peripherals_mut(), *pin);
         }
 
         for pin in ADC_PINS.iter() {
             let _ = Adc::set_state(self.get_peripherals_mut(), *pin, AdcState::Disabled);
+            let _ = Adc::set_mode(self.get_peripherals_mut(), *pin, AdcMode::OneShot);
+            let _ = Adc::set_interrupt(self.get_peripherals_mut(), *pin, AdcInterrupt::Disabled);
+            Adc::reset_interrupt_flag(self.get_peripherals_mut(), *pin);
         }
+        let _ = Adc::set_sample_period(self.get_peripherals_mut(), ADC_DEFAULT_SAMPLE_PERIOD);
 
         for pin in PWM_PINS.iter() {
             Pwm::set_state(self.get_peripherals_mut(), *pin, PwmState::Disabled);
             Pwm::set_duty_cycle(self.get_peripherals_mut(), *pin, 0);
+            Pwm::set_rollover_interrupt(self.get_peripherals_mut(), *pin, false);
+            Pwm::reset_interrupt_flag(self.get_peripherals_mut(), *pin);
         }
 
         for id in TIMERS.iter() {
             Timers::set_mode(self.get_peripherals_mut(), *id, TimerMode::SingleShot);
             Timers::set_state(self.get_peripherals_mut(), *id, TimerState::Disabled);
+            Timers::set_capture_source(self.get_peripherals_mut(), *id, None);
             Timers::reset_interrupt_flag(self.get_peripherals_mut(), *id);
         }
 
@@ -99,6 +116,27 @@ where
         Input::reset_interrupt_flag(self.get_peripherals_mut());
         Output::reset_interrupt_flag(self.get_peripherals_mut());
     }
+
+    /// Feeds the current level of each timer's capture pin to the timer so
+    /// that it can latch its count on the right edges.
+    fn sample_capture_inputs(&mut self) {
+        use lc3_traits::peripherals::timers::TIMERS;
+
+        // This runs every step; skip the GPIO reads in the common case.
+        if !Timers::has_capture_sources(self.get_peripherals()) {
+            return;
+        }
+
+        for id in TIMERS.iter() {
+            if let Some(source) = Timers::get_capture_source(self.get_peripherals(), *id) {
+                // Pins that can't be read (i.e. disabled pins) don't produce
+                // edges.
+                if let Ok(level) = Gpio::read(self.get_peripherals(), source.pin) {
+                    Timers::capture_input(self.get_peripherals_mut(), *id, level);
+                }
+            }
+        }
+    }
 }
 
 pub trait InstructionInterpreter:
@@ -110,6 +148,19 @@ pub trait InstructionInterpreter:
 
     fn step(&mut self) -> MachineState;
 
+    /// Executes up to `max_steps` instructions, returning how many were run
+    /// and the resulting machine state.
+    ///
+    /// This is allowed to stop early (the default only ever runs a single
+    /// instruction) and must stop once the machine halts, an error is raised
+    /// or an [unbalanced return](UnbalancedReturn) is recorded so that the
+    /// caller sees the machine as it was right after. It's meant for callers that don't need to look at the machine
+    /// between instructions; i.e. when there are no breakpoints to check.
+    fn step_many(&mut self, max_steps: usize) -> (usize, MachineState) {
+        let _ = max_steps;
+        (1, self.step())
+    }
+
     fn set_pc(&mut self, addr: Addr);
     fn get_pc(&self) -> Addr;
 
@@ -133,8 +184,12 @@ pub trait InstructionInterpreter:
     fn set_error(&self, err: Error);
     fn get_error(&self) -> Option<Error>;
 
-    fn get_call_stack(&self) -> [Option<(Addr, ProcessorMode)>; MAX_CALL_STACK_DEPTH];
+    fn set_fault_handling(&mut self, handling: FaultHandling);
+    fn get_fault_handling(&self) -> FaultHandling;
+
+    fn get_call_stack(&self) -> &CallStack;
     fn get_call_stack_depth(&self) -> u64;
+    fn take_unbalanced_return(&mut self) -> Option<UnbalancedReturn>;
 
     // Taken straight from Memory:
     fn commit_page(&mut self, page_idx: PageIndex, page: &[Word; PAGE_SIZE_IN_WORDS as usize]);
@@ -146,8 +201,10 @@ pub trait InstructionInterpreter:
     fn type_id() -> TypeId { core::any::TypeId::of::<Instruction>() }
 }
 
+/// An access control violation; `addr` is the address that couldn't be
+/// accessed.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Acv;
+pub struct Acv { pub addr: Addr }
 
 pub type ReadAttempt = Result<Word, Acv>;
 
@@ -171,12 +228,14 @@ impl Default for MachineState {
     }
 }
 
+pub use lc3_traits::control::FaultHandling;
+
 #[derive(Debug)]
 pub struct PeripheralInterruptFlags {
     gpio: GpioPinArr<AtomicBool>, // No payload; just tell us if a rising edge has happened
-    // adc: AdcPinArr<bool>, // We're not going to have Adc Interrupts
-    // pwm: PwmPinArr<bool>, // No Pwm Interrupts
-    timers: TimerArr<AtomicBool>, // No payload; timers don't actually expose counts anyways
+    adc: AdcPinArr<AtomicBool>, // No payload; check the data register for the latest sample
+    pwm: PwmPinArr<AtomicBool>, // No payload; just tell us if a new period has started
+    timers: TimerArr<AtomicBool>, // No payload; check TxDR/TxCPR for counts
     // clock: bool, // No Clock Interrupt
     input: AtomicBool, // No payload; check KBDR for the current character
     output: AtomicBool, // Technically this has an interrupt, but I have no idea why; UPDATE: it interrupts when it's ready to accept more data
@@ -194,7 +253,9 @@ impl PeripheralInterruptFlags {
         // TODO: make this less gross..
         Self {
             gpio: GpioPinArr([b!(), b!(), b!(), b!(), b!(), b!(), b!(), b!()]),
-            timers: TimerArr([b!(), b!()]),
+            adc: AdcPinArr([b!(), b!(), b!(), b!(), b!(), b!()]),
+            pwm: PwmPinArr([b!(), b!()]),
+            timers: TimerArr([b!(), b!(), b!(), b!()]),
             input: AtomicBool::new(false),
             output: AtomicBool::new(false),
         }
@@ -245,58 +306,183 @@ impl<T> Deref for OwnedOrRef<'_, T> {
 //     }
 // }
 
+/// The number of call stack frames std builds record by default (see
+/// [`Interpreter::set_call_stack_limit`]).
+#[cfg(feature = "std")]
+pub const DEFAULT_CALL_STACK_LIMIT: usize = 1 << 12;
+
+/// A return that didn't go back to where the innermost call expected (see
+/// [`Event::UnbalancedReturn`](lc3_traits::control::Event::UnbalancedReturn)).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnbalancedReturn { pub to: Addr, pub expected: Addr }
+
+/// Calls (`JSR`, `JSRR`, traps, interrupts and exceptions) that haven't
+/// returned yet.
+///
+/// Every call counts towards the depth but only the outermost frames are
+/// recorded: [`MAX_CALL_STACK_DEPTH`] of them on no_std builds and a
+/// configurable number (by default `DEFAULT_CALL_STACK_LIMIT`) on std builds.
+///
+/// [`MAX_CALL_STACK_DEPTH`]: lc3_traits::control::control::MAX_CALL_STACK_DEPTH
 #[derive(Debug)]
 pub struct CallStack {
-    stack: [Option<(Addr, ProcessorMode)>; MAX_CALL_STACK_DEPTH],
+    #[cfg(not(feature = "std"))]
+    frames: [Option<CallStackFrame>; MAX_CALL_STACK_DEPTH],
+    #[cfg(feature = "std")]
+    frames: Vec<CallStackFrame>,
+    #[cfg(feature = "std")]
+    limit: usize,
     depth: u64,
+    unbalanced: Option<UnbalancedReturn>,
 }
 
 impl CallStack {
+    #[cfg(not(feature = "std"))]
     pub const fn new() -> Self {
         Self {
-            stack: [None; MAX_CALL_STACK_DEPTH],
+            frames: [None; MAX_CALL_STACK_DEPTH],
             depth: 0,
+            unbalanced: None,
         }
     }
 
-    // Push subroutine address to call stack if stack is not full
+    #[cfg(feature = "std")]
+    pub const fn new() -> Self {
+        Self::with_limit(DEFAULT_CALL_STACK_LIMIT)
+    }
+
+    #[cfg(feature = "std")]
+    pub const fn with_limit(limit: usize) -> Self {
+        Self {
+            frames: Vec::new(),
+            limit,
+            depth: 0,
+            unbalanced: None,
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn limit(&self) -> usize { MAX_CALL_STACK_DEPTH }
+
+    #[cfg(feature = "std")]
+    fn limit(&self) -> usize { self.limit }
+
+    pub fn depth(&self) -> u64 { self.depth }
+
+    /// Frames are numbered from the bottom of the stack; `None` if the frame
+    /// doesn't exist or wasn't recorded.
+    #[cfg(not(feature = "std"))]
+    pub fn frame(&self, idx: usize) -> Option<&CallStackFrame> {
+        self.frames.get(idx).and_then(Option::as_ref)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn frame(&self, idx: usize) -> Option<&CallStackFrame> {
+        self.frames.get(idx)
+    }
+
+    pub fn page(&self, page: u16) -> CallStackPage {
+        let start = page as usize * CALL_STACK_PAGE_SIZE;
+
+        let mut frames = [None; CALL_STACK_PAGE_SIZE];
+        for (i, frame) in frames.iter_mut().enumerate() {
+            *frame = self.frame(start + i).copied();
+        }
+
+        frames
+    }
+
     // Always increments depth
-    // -> true if pushed, false otherwise
-    pub fn push(&mut self, subroutine: Addr, in_user_mode: bool) -> bool {
-        let mut success = false;
-
-        // Check if stack is not full
-        if self.depth < MAX_CALL_STACK_DEPTH as u64 {
-            let processor_mode = if in_user_mode {ProcessorMode::User} else {ProcessorMode::Supervisor};
-            self.stack[self.depth as usize] = Some((subroutine, processor_mode));
-            success = true;
+    // -> true if the frame was recorded, false otherwise
+    pub fn push(&mut self, frame: CallStackFrame) -> bool {
+        let recorded = self.depth < self.limit() as u64;
+
+        if recorded {
+            #[cfg(not(feature = "std"))]
+            { self.frames[self.depth as usize] = Some(frame); }
+            #[cfg(feature = "std")]
+            self.frames.push(frame);
         }
 
-        // Increment depth
         self.depth = match self.depth.checked_add(1) {
             Some(val) => val,
             None => panic!("Overflowed depth of call stack!"),
         };
 
-        success
+        recorded
+    }
+
+    // Drops every frame at or above `depth`.
+    fn truncate(&mut self, depth: u64) {
+        #[cfg(not(feature = "std"))]
+        self.frames.iter_mut().skip(depth as usize).for_each(|f| *f = None);
+        #[cfg(feature = "std")]
+        self.frames.truncate(depth as usize);
+
+        self.depth = depth;
+    }
+
+    // Matches a return (to `to`) against the stack.
+    //
+    // Returning to the innermost frame's return address pops it. Otherwise
+    // the return is unbalanced: if an outer frame was going to return to `to`
+    // (i.e. the frames above it were skipped) everything down to and including
+    // that frame is popped and if none was just the innermost frame is (the
+    // routine returned somewhere else, past some inline arguments, say).
+    //
+    // Returns with nothing on the stack (like the OS's first `RTI` into the
+    // user program) and returns from frames that weren't recorded just
+    // decrement the depth.
+    //
+    // -> true if the return was balanced, false otherwise
+    pub fn ret(&mut self, to: Addr) -> bool {
+        if self.depth == 0 {
+            return true;
+        }
+
+        let top = (self.depth - 1) as usize;
+        let expected = match self.frame(top) {
+            Some(frame) => frame.return_addr,
+            None => {
+                self.depth -= 1;
+                return true;
+            }
+        };
+
+        if expected == to {
+            self.truncate(top as u64);
+            return true;
+        }
+
+        let outer = (0..top).rev().find(|i| self.frame(*i).map(|f| f.return_addr) == Some(to));
+        self.truncate(outer.unwrap_or(top) as u64);
+
+        self.unbalanced = Some(UnbalancedReturn { to, expected });
+        false
     }
 
-    // Pop subroutine address off of call stack if top of stack matches current depth
-    // Always decrements depth
-    // -> true if popped, false otherwise
-    pub fn pop(&mut self) -> bool {
-        let mut success = false;
+    /// The last unbalanced return, if there's been one since this was last
+    /// called.
+    pub fn take_unbalanced_return(&mut self) -> Option<UnbalancedReturn> {
+        self.unbalanced.take()
+    }
 
-        // Decrement depth, saturates at 0 (unsigned)
-        self.depth = self.depth.saturating_sub(1);
+    pub(crate) fn has_unbalanced_return(&self) -> bool {
+        self.unbalanced.is_some()
+    }
 
-        // Check if depth exceeds max saved addrs
-        if self.depth < MAX_CALL_STACK_DEPTH as u64 {
-            self.stack[self.depth as usize] = None;
-            success = true;
-        }
+    // Whether the instruction at `pc` (stepped with the stack `prev_depth`
+    // deep) was preempted by an interrupt or a fault instead of being run.
+    pub(crate) fn preempted(&self, prev_depth: u64, pc: Addr) -> bool {
+        self.depth > prev_depth && self.frame((self.depth - 1) as usize).map_or(false, |f| {
+            matches!(f.kind, FrameKind::Interrupt { .. } | FrameKind::Exception { .. })
+                && f.return_addr == pc
+        })
+    }
 
-        success
+    pub fn clear(&mut self) {
+        self.truncate(0);
+        self.unbalanced = None;
     }
 }
 
@@ -311,7 +497,13 @@ pub struct Interpreter<'per, M: Memory, P: Peripherals<'per>> {
     pc: Word, //TODO: what should the default for this be
     state: MachineState,
     error: Cell<Option<Error>>,
+    fault_handling: FaultHandling,
+    extension: Option<&'per dyn ExtensionHandler>,
     call_stack: CallStack,
+    // Pages whose writes are being watched for and those of them that have
+    // been written to since (see `take_dirtied_pages`).
+    watched_pages: PageSet,
+    dirtied_pages: PageSet,
 }
 
 impl<'a, M: Memory + Default, P: Peripherals<'a>> Default for Interpreter<'a, M, P> {
@@ -616,7 +808,11 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
             pc,
             state,
             error: Cell::new(None),
+            fault_handling: FaultHandling::default(),
+            extension: None,
             call_stack: CallStack::new(),
+            watched_pages: PageSet::new(),
+            dirtied_pages: PageSet::new(),
         };
 
         // TODO: we can't call this.
@@ -681,12 +877,57 @@ impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreterPeripheralAccess<'
 impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
     pub fn init(&mut self, flags: &'a PeripheralInterruptFlags) {
         Gpio::<'a>::register_interrupt_flags(&mut self.peripherals, &flags.gpio);
+        Adc::<'a>::register_interrupt_flags(&mut self.peripherals, &flags.adc);
+        Pwm::<'a>::register_interrupt_flags(&mut self.peripherals, &flags.pwm);
         Timers::<'a>::register_interrupt_flags(&mut self.peripherals, &flags.timers);
         Input::<'a>::register_interrupt_flag(&mut self.peripherals, &flags.input);
         Output::<'a>::register_interrupt_flag(&mut self.peripherals, &flags.output);
     }
 }
 
+impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
+    /// Registers a handler for instructions that use the reserved opcode (see
+    /// [`ext`](crate::ext)); `None` (the default) is standard LC-3.
+    ///
+    /// Like the fault handling setting, this survives resets.
+    pub fn set_extension(&mut self, extension: Option<&'a dyn ExtensionHandler>) {
+        self.extension = extension;
+    }
+
+    pub fn get_extension(&self) -> Option<&'a dyn ExtensionHandler> {
+        self.extension
+    }
+
+    pub fn with_extension(mut self, extension: &'a dyn ExtensionHandler) -> Self {
+        self.set_extension(Some(extension));
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
+    /// Sets how many call stack frames are recorded (the depth is always
+    /// tracked).
+    ///
+    /// This clears the call stack; like the fault handling setting, it survives
+    /// resets.
+    pub fn set_call_stack_limit(&mut self, limit: usize) {
+        self.call_stack = CallStack::with_limit(limit);
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> ExtensionContext for Interpreter<'a, M, P> {
+    fn reg(&self, reg: Reg) -> Word { self[reg] }
+    fn set_reg(&mut self, reg: Reg, word: Word) { self[reg] = word; }
+    fn set_condition_codes(&mut self, word: Word) { self.set_cc(word) }
+
+    fn pc(&self) -> Addr { self.get_pc() }
+    fn jump(&mut self, addr: Addr) { self.set_pc(addr) }
+
+    fn load(&mut self, addr: Addr) -> ReadAttempt { self.get_word(addr) }
+    fn store(&mut self, addr: Addr, word: Word) -> WriteAttempt { self.set_word(addr, word) }
+}
+
 impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
     fn set_cc(&mut self, word: Word) {
         <PSR as MemMapped>::from(self).unwrap().set_cc(self, word)
@@ -702,7 +943,7 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
         if self[R6] <= lc3_isa::OS_START_ADDR {
             self.set_error(SystemStackOverflow);
             self.halt();
-            return Err(Acv);    // TODO: Kind of an ACV, but not really?
+            return Err(Acv { addr: self[R6].wrapping_sub(1) });    // TODO: Kind of an ACV, but not really?
         }
 
         self[R6] -= 1;
@@ -728,12 +969,9 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
     }
 
     fn restore_state(&mut self) -> Result<(), Acv> {
-        // Update call stack on return
-        self.pop_call_stack();
-
-        // Restore the PC and then the PSR.
+        // Restore the PC (updating the call stack) and then the PSR.
         self.pop()
-            .map(|w| self.set_pc(w))
+            .map(|w| { self.set_pc(w); self.pop_call_stack(w); })
             .and_then(|()| self.pop().map(|w| self.set_special_reg::<PSR>(w)))
     }
 
@@ -767,7 +1005,7 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
 
         // We're in privileged mode now so this should only error if we've
         // overflowed our stack.
-        if let Err(Acv) = self.push_state(saved_psr) {
+        if let Err(Acv { .. }) = self.push_state(saved_psr) {
             debug_assert_eq!(self.state, MachineState::Halted);
             return;
         }
@@ -775,30 +1013,42 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
 //        self.get_special_reg::<PSR>().set_priority(self, 3);
     }
 
-    fn handle_trap(&mut self, trap_vec: u8) {
+    // Goes to the routine in the given vector table entry (for traps,
+    // exceptions and interrupts).
+    fn vector_to(&mut self, table_entry: Addr, kind: FrameKind) {
+        let (return_addr, saved_psr) = (self.pc, *self.get_special_reg::<PSR>());
         self.prep_for_execution_event();
 
-        // Go to the trap routine:
         // (this should also not panic)
-        self.pc = self
-            .get_word(TRAP_VECTOR_TABLE_START_ADDR | (Into::<Word>::into(trap_vec)))
-            .unwrap();
+        self.pc = self.get_word(table_entry).unwrap();
 
-        self.push_call_stack(self.pc, self.get_special_reg::<PSR>().in_user_mode());
+        self.push_call_stack(kind, self.pc, return_addr, saved_psr);
     }
 
-    // TODO: find a word that generalizes exception and trap...
-    // since that's what this handles
-    fn handle_exception(&mut self, ex_vec: u8) {
-        self.prep_for_execution_event();
+    fn handle_trap(&mut self, trap_vec: u8) {
+        self.vector_to(
+            TRAP_VECTOR_TABLE_START_ADDR | (Into::<Word>::into(trap_vec)),
+            FrameKind::Trap { vector: trap_vec },
+        );
+    }
 
-        // Go to the exception routine:
-        // (this should also not panic)
-        self.pc = self
-            .get_word(INTERRUPT_VECTOR_TABLE_START_ADDR | (Into::<Word>::into(ex_vec)))
-            .unwrap();
+    fn handle_exception(&mut self, ex_vec: u8) {
+        self.vector_to(
+            INTERRUPT_VECTOR_TABLE_START_ADDR | (Into::<Word>::into(ex_vec)),
+            FrameKind::Exception { vector: ex_vec },
+        );
+    }
 
-        self.push_call_stack(self.pc, self.get_special_reg::<PSR>().in_user_mode());
+    // Faults either vector into the OS or, if we've been told to stop on them,
+    // put the PC back on the faulting instruction and get reported.
+    fn handle_fault(&mut self, pc: Addr, err: Error, ex_vec: u8) {
+        match self.fault_handling {
+            FaultHandling::Vector => self.handle_exception(ex_vec),
+            FaultHandling::Stop => {
+                self.set_pc(pc);
+                self.set_error(err);
+            }
+        }
     }
 
     fn handle_interrupt(&mut self, int_vec: u8, priority: u8) -> bool {
@@ -813,7 +1063,10 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
         // Haven't executed instruction at PC-1, so must store PC-1 on stack, not PC
         self.pc -= 1;
 
-        self.handle_exception(int_vec);
+        self.vector_to(
+            INTERRUPT_VECTOR_TABLE_START_ADDR | (Into::<Word>::into(int_vec)),
+            FrameKind::Interrupt { vector: int_vec },
+        );
         self.set_cc(0);
         self.get_special_reg::<PSR>().set_priority(self, priority);
 
@@ -830,36 +1083,42 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
     }
 
     fn check_interrupts(&mut self) -> bool {
-        macro_rules! assert_in_priority_order {
-            ($dev1: ty, $dev2: ty, $($rest:ty),*) => {
-                sa::const_assert!(<$dev1>::PRIORITY >= <$dev2>::PRIORITY);
+        match self.pending_interrupt() {
+            Some((int_vec, priority)) => self.handle_interrupt(int_vec, priority),
+            None => false,
+        }
+    }
 
-                assert_in_priority_order!($dev2, $($rest),*);
-            };
-            ($dev1: ty, $dev2: ty) => {
-                sa::const_assert!(<$dev1>::PRIORITY >= <$dev2>::PRIORITY);
-            }
+    // Finds the highest priority interrupt that can preempt what's running,
+    // clears its flag, and returns its vector and priority.
+    //
+    // Masks and priorities come from the interrupt controller (see
+    // `mem_mapped`); priorities are checked first so devices are only polled
+    // when they could actually fire.
+    pub(crate) fn pending_interrupt(&mut self) -> Option<(u8, u8)> {
+        if !self.get_special_reg::<ICR>().interrupts_enabled() {
+            return None;
         }
 
-        macro_rules! int_devices {
-            ($($dev:ty),* $(,)?) => {
-                let cur_priority: u8 = self.get_special_reg::<PSR>().get_priority();
-                $(
-                    if <$dev>::PRIORITY <= cur_priority { return false; }
-                    else if <$dev as Interrupt>::interrupt(self) {
-                        <$dev as Interrupt>::reset_interrupt_flag(self);
-                        return self.handle_interrupt(<$dev>::INT_VEC, <$dev>::PRIORITY);
-                    }
-                )*
+        let cur_priority: u8 = self.get_special_reg::<PSR>().get_priority();
+        let masked = interrupt_mask(self);
+
+        let mut chosen: Option<(usize, u8)> = None;
+        for source in 0..InterruptSource::NUM_SOURCES {
+            if masked & (1 << source) != 0 { continue; }
 
-                assert_in_priority_order!($($dev),*);
+            let priority = interrupt_priority(self, source);
+            let floor = chosen.map_or(cur_priority, |(_, p)| p);
+
+            if priority > floor && interrupt_source_ready(self, source) {
+                chosen = Some((source, priority));
             }
         }
 
-        int_devices!(
-            KBSR, DSR, G0CR, G1CR, G2CR, G3CR, G4CR, G5CR, G6CR, G7CR, T0CR, T1CR
-        );
-        false
+        chosen.map(|(source, priority)| {
+            reset_interrupt_source_flag(self, source);
+            (INTERRUPT_VECTORS[source], priority)
+        })
     }
 
     fn is_acv(&self, addr: Word) -> bool {
@@ -900,15 +1159,16 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
                 i!(PC <- pc);
             }};
 
-            ([S+] PC <- $($rest:tt)*) => {{
+            ([S+ $kind:expr] PC <- $($rest:tt)*) => {{
                 _insn_inner_gen!($);
                 #[allow(unused_mut)]
                 let mut pc: Addr;
+                let return_addr = self.get_pc();
 
                 _insn_inner!(pc | $($rest)*);
                 i!(PC <- pc);
 
-                self.push_call_stack(pc, self.get_special_reg::<PSR>().in_user_mode());
+                self.push_call_stack($kind, pc, return_addr, *self.get_special_reg::<PSR>());
             }};
 
             ([S-] PC <- $($rest:tt)*) => {{
@@ -919,7 +1179,7 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
                 _insn_inner!(pc | $($rest)*);
                 i!(PC <- pc);
 
-                self.pop_call_stack();
+                self.pop_call_stack(pc);
             }};
 
             (mem[$($addr:tt)*] <- $($word:tt)*) => {{
@@ -1005,11 +1265,11 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
             Jmp { base } => I!(PC <- R[base]),
             Jsr { offset11 } => {
                 I!(R7 <- PC);
-                I!([S+] PC <- PC + offset11)
+                I!([S+ FrameKind::Jsr] PC <- PC + offset11)
             }
             Jsrr { base } => {
                 let (pc, new_pc) = (self.get_pc(), self[base]);
-                I!([S+] PC <- new_pc);
+                I!([S+ FrameKind::Jsrr] PC <- new_pc);
                 I!(R7 <- pc)
             }
             Ld { dr, offset9 } => I!(dr <- mem[PC + offset9]),
@@ -1030,45 +1290,62 @@ impl<'a, M: Memory, P: Peripherals<'a>> Interpreter<'a, M, P> {
                 } else {
                     // If RTI is called from user mode, raise the privilege mode
                     // exception:
-                    self.handle_exception(PRIVILEGE_MODE_VIOLATION_EXCEPTION_VECTOR)
+                    let pc = self.get_pc().wrapping_sub(1);
+                    self.handle_fault(
+                        pc,
+                        Error::PrivilegeModeViolation { pc, insn: insn.into() },
+                        PRIVILEGE_MODE_VIOLATION_EXCEPTION_VECTOR,
+                    )
                 }
             }
             St { sr, offset9 } => I!(mem[PC + offset9] <- R[sr]),
             Sti { sr, offset9 } => I!(mem[mem[PC + offset9]] <- R[sr]),
             Str { sr, base, offset6 } => I!(mem[R[base] + offset6] <- R[sr]),
             Trap { trapvec } => self.handle_trap(trapvec),
+            Extended { bits } => match self.extension {
+                Some(ext) if ext.decodes(bits) => ext.execute(bits, self)?,
+                // Words are only decoded to these when there's an extension to
+                // claim them but `Instruction`s can come from elsewhere:
+                _ => {
+                    let pc = self.get_pc().wrapping_sub(1);
+                    self.handle_fault(
+                        pc,
+                        Error::IllegalOpcode { pc, insn: insn.into() },
+                        ILLEGAL_OPCODE_EXCEPTION_VECTOR,
+                    )
+                }
+            },
         }
 
         Ok(())
     }
 
-    fn push_call_stack(&mut self, subroutine: Addr, in_user_mode: bool) -> bool {
-        self.call_stack.push(subroutine, in_user_mode)
+    pub(crate) fn push_call_stack(&mut self, kind: FrameKind, target: Addr, return_addr: Addr, saved_psr: Word) -> bool {
+        let stack_pointer = self[R6];
+        self.call_stack.push(CallStackFrame { kind, target, return_addr, saved_psr, stack_pointer })
     }
 
-    fn pop_call_stack(&mut self) -> bool {
-        self.call_stack.pop()
+    // Reads and decodes the instruction at `addr`; `None` if it's not a valid
+    // instruction.
+    pub(crate) fn fetch(&self, addr: Addr) -> (Word, Option<Instruction>) {
+        let word = self.get_word_unchecked(addr);
+        (word, self.decode(word))
     }
-}
 
-use super::mem_mapped::{
-    KBSR, KBDR,
-    DSR, DDR,
-    BSP, PSR,
-    G0CR, G0DR, G1CR, G1DR, G2CR, G2DR, G3CR, G3DR, G4CR, G4DR, G5CR, G5DR, G6CR, G6DR, G7CR, G7DR,
-    A0CR, A0DR, A1CR, A1DR, A2CR, A2DR, A3CR, A3DR, A4CR, A4DR, A5CR, A5DR,
-    P0CR, P0DR, P1CR, P1DR,
-    CLKR,
-    T0CR, T0DR, T1CR, T1DR
-};
-use lc3_traits::error::Error::SystemStackOverflow;
-use lc3_traits::control::ProcessorMode;
-
-impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'a, M, P> {
-    const ID: Identifier = Identifier::new_from_str_that_crashes_on_invalid_inputs("Base");
-    const VER: Version = version_from_crate!();
+    pub(crate) fn decode(&self, word: Word) -> Option<Instruction> {
+        match self.extension {
+            Some(ext) => Instruction::decode_with(word, ext).ok(),
+            None => word.try_into().ok(),
+        }
+    }
 
-    fn step(&mut self) -> MachineState {
+    // Runs one instruction cycle, getting the instruction from `fetch`
+    // (`Interpreter::fetch` for regular steps) once it's established that the
+    // PC can be read from.
+    pub(crate) fn step_with(
+        &mut self,
+        fetch: impl FnOnce(&Self, Addr) -> (Word, Option<Instruction>),
+    ) -> MachineState {
         if let state @ MachineState::Halted = self.get_machine_state() {
             return state;
         }
@@ -1077,25 +1354,108 @@ impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'
         let current_pc = self.get_pc();
         self.set_pc(current_pc.wrapping_add(1)); // TODO: ???
 
+        self.sample_capture_inputs();
+
         if self.check_interrupts() {
             return self.get_machine_state();
         };
 
-        match self.get_word(current_pc).and_then(|w| match w.try_into() {
-            Ok(insn) => self.instruction_step_inner(insn),
-            Err(_) => {
-                self.handle_exception(ILLEGAL_OPCODE_EXCEPTION_VECTOR);
+        let fetched = if self.is_acv(current_pc) {
+            Err(Acv { addr: current_pc })
+        } else {
+            Ok(fetch(self, current_pc))
+        };
+
+        match fetched.and_then(|(w, insn)| match insn {
+            Some(insn) => self.instruction_step_inner(insn),
+            None => {
+                self.handle_fault(
+                    current_pc,
+                    Error::IllegalOpcode { pc: current_pc, insn: w },
+                    ILLEGAL_OPCODE_EXCEPTION_VECTOR,
+                );
                 Ok(())
             }
         }) {
             Ok(()) => {}
             // Access control violation: triggered when getting the current instruction or when executing it
-            Err(Acv) => self.handle_exception(ACCESS_CONTROL_VIOLATION_EXCEPTION_VECTOR),
+            Err(Acv { addr }) => self.handle_fault(
+                current_pc,
+                Error::AccessControlViolation { pc: current_pc, insn: fetched.ok().map(|(w, _)| w), addr },
+                ACCESS_CONTROL_VIOLATION_EXCEPTION_VECTOR,
+            ),
         }
 
         self.get_machine_state()
     }
 
+    pub(crate) fn has_error(&self) -> bool {
+        self.error.get().is_some()
+    }
+
+    // Whether there's anything that has to be reported before going on; see
+    // `InstructionInterpreter::step_many`.
+    pub(crate) fn should_stop(&self) -> bool {
+        self.has_error() || self.call_stack.has_unbalanced_return()
+    }
+
+    // Writes to memory in watched pages are recorded until the next call to
+    // `take_dirtied_pages`; the block cache uses this to spot writes to code.
+    pub(crate) fn watch_page(&mut self, page: PageIndex) {
+        self.watched_pages.insert(page);
+    }
+
+    pub(crate) fn unwatch_all_pages(&mut self) {
+        self.watched_pages = PageSet::new();
+        self.dirtied_pages = PageSet::new();
+    }
+
+    // Returns the watched pages that have been written to (if any) and stops
+    // watching them.
+    pub(crate) fn take_dirtied_pages(&mut self) -> Option<PageSet> {
+        if self.dirtied_pages.is_empty() {
+            return None;
+        }
+
+        let dirtied = core::mem::replace(&mut self.dirtied_pages, PageSet::new());
+        self.watched_pages.remove_all(&dirtied);
+
+        Some(dirtied)
+    }
+
+    fn note_write(&mut self, page: PageIndex) {
+        if self.watched_pages.contains(page) {
+            self.dirtied_pages.insert(page);
+        }
+    }
+
+    pub(crate) fn pop_call_stack(&mut self, return_addr: Addr) -> bool {
+        self.call_stack.ret(return_addr)
+    }
+}
+
+use super::mem_mapped::{
+    KBSR, KBDR,
+    DSR, DDR,
+    BSP, PSR,
+    G0CR, G0DR, G1CR, G1DR, G2CR, G2DR, G3CR, G3DR, G4CR, G4DR, G5CR, G5DR, G6CR, G6DR, G7CR, G7DR,
+    A0CR, A0DR, A1CR, A1DR, A2CR, A2DR, A3CR, A3DR, A4CR, A4DR, A5CR, A5DR, ADCSPR,
+    P0CR, P0DR, P1CR, P1DR, P0PR, P1PR, P0IR, P1IR,
+    CLKR,
+    T0CR, T0DR, T1CR, T1DR, T2CR, T2DR, T3CR, T3DR,
+    T0CCR, T1CCR, T2CCR, T3CCR, T0CPR, T1CPR, T2CPR, T3CPR,
+    ICR, IPNR0, IPNR1, IMR0, IMR1, IPR0, IPR1, IPR2, IPR3, IPR4, IPR5
+};
+use lc3_traits::error::Error::SystemStackOverflow;
+
+impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'a, M, P> {
+    const ID: Identifier = Identifier::new_from_str_that_crashes_on_invalid_inputs("Base");
+    const VER: Version = version_from_crate!();
+
+    fn step(&mut self) -> MachineState {
+        self.step_with(Self::fetch)
+    }
+
     fn set_pc(&mut self, addr: Addr) {
         self.pc = addr;
     }
@@ -1107,7 +1467,7 @@ impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'
     // Checked access:
     fn set_word(&mut self, addr: Addr, word: Word) -> WriteAttempt {
         if self.is_acv(addr) {
-            Err(Acv)
+            Err(Acv { addr })
         } else {
             Ok(self.set_word_unchecked(addr, word))
         }
@@ -1115,7 +1475,7 @@ impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'
 
     fn get_word(&self, addr: Addr) -> ReadAttempt {
         if self.is_acv(addr) {
-            Err(Acv)
+            Err(Acv { addr })
         } else {
             Ok(self.get_word_unchecked(addr))
         }
@@ -1139,10 +1499,12 @@ impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'
                 DSR, DDR,
                 BSP, PSR, MCR,
                 G0CR, G0DR, G1CR, G1DR, G2CR, G2DR, G3CR, G3DR, G4CR, G4DR, G5CR, G5DR, G6CR, G6DR, G7CR, G7DR,
-                A0CR, A0DR, A1CR, A1DR, A2CR, A2DR, A3CR, A3DR, A4CR, A4DR, A5CR, A5DR,
-                P0CR, P0DR, P1CR, P1DR,
+                A0CR, A0DR, A1CR, A1DR, A2CR, A2DR, A3CR, A3DR, A4CR, A4DR, A5CR, A5DR, ADCSPR,
+                P0CR, P0DR, P1CR, P1DR, P0PR, P1PR, P0IR, P1IR,
                 CLKR,
-                T0CR, T0DR, T1CR, T1DR
+                T0CR, T0DR, T1CR, T1DR, T2CR, T2DR, T3CR, T3DR,
+                T0CCR, T1CCR, T2CCR, T3CCR, T0CPR, T1CPR, T2CPR, T3CPR,
+                ICR, IPNR0, IPNR1, IMR0, IMR1, IPR0, IPR1, IPR2, IPR3, IPR4, IPR5
             )
         } else {
             self.set_word_force_memory_backed(addr, word)
@@ -1168,10 +1530,12 @@ impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'
                 DSR, DDR,
                 BSP, PSR, MCR,
                 G0CR, G0DR, G1CR, G1DR, G2CR, G2DR, G3CR, G3DR, G4CR, G4DR, G5CR, G5DR, G6CR, G6DR, G7CR, G7DR,
-                A0CR, A0DR, A1CR, A1DR, A2CR, A2DR, A3CR, A3DR, A4CR, A4DR, A5CR, A5DR,
-                P0CR, P0DR, P1CR, P1DR,
+                A0CR, A0DR, A1CR, A1DR, A2CR, A2DR, A3CR, A3DR, A4CR, A4DR, A5CR, A5DR, ADCSPR,
+                P0CR, P0DR, P1CR, P1DR, P0PR, P1PR, P0IR, P1IR,
                 CLKR,
-                T0CR, T0DR, T1CR, T1DR
+                T0CR, T0DR, T1CR, T1DR, T2CR, T2DR, T3CR, T3DR,
+                T0CCR, T1CCR, T2CCR, T3CCR, T0CPR, T1CPR, T2CPR, T3CPR,
+                ICR, IPNR0, IPNR1, IMR0, IMR1, IPR0, IPR1, IPR2, IPR3, IPR4, IPR5
             )
         } else {
             self.get_word_force_memory_backed(addr)
@@ -1179,6 +1543,7 @@ impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'
     }
 
     fn set_word_force_memory_backed(&mut self, addr: Addr, word: Word) {
+        self.note_write((addr / PAGE_SIZE_IN_WORDS) as PageIndex);
         self.memory.write_word(addr, word)
     }
 
@@ -1203,10 +1568,12 @@ impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'
         // Reset memory _before_ setting the PSR and MCR so we don't wipe out
         // their values.
         self.memory.reset();
+        self.dirtied_pages = self.watched_pages;
 
         self.get_special_reg::<PSR>().set_priority(self, 7);
         self.get_special_reg::<MCR>().run(self);
         self.set_cc(0);
+        reset_interrupt_controller(self);
 
         self.regs = [0; Reg::NUM_REGS];
 
@@ -1214,7 +1581,7 @@ impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'
         self.state = MachineState::Running;
 
         self.error.set(None);
-        self.call_stack = CallStack::new();
+        self.call_stack.clear();
     }
 
     fn halt(&mut self) {
@@ -1233,15 +1600,28 @@ impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Interpreter<'
         self.error.take()
     }
 
-    fn get_call_stack(&self) -> [Option<(Addr, ProcessorMode)>; MAX_CALL_STACK_DEPTH] {
-        self.call_stack.stack
+    fn set_fault_handling(&mut self, handling: FaultHandling) {
+        self.fault_handling = handling;
+    }
+
+    fn get_fault_handling(&self) -> FaultHandling {
+        self.fault_handling
+    }
+
+    fn get_call_stack(&self) -> &CallStack {
+        &self.call_stack
     }
 
     fn get_call_stack_depth(&self) -> u64 {
-        self.call_stack.depth
+        self.call_stack.depth()
+    }
+
+    fn take_unbalanced_return(&mut self) -> Option<UnbalancedReturn> {
+        self.call_stack.take_unbalanced_return()
     }
 
     fn commit_page(&mut self, page_idx: PageIndex, page: &[Word; PAGE_SIZE_IN_WORDS as usize]) {
+        self.note_write(page_idx);
         self.memory.commit_page(page_idx, page)
     }
 
@@ -0,0 +1,477 @@
+//! An interpreter for the [LC-3b](lc3_isa::lc3b).
+//!
+//! [`Lc3bInterpreter`] wraps an [`Interpreter`] and shares its memory, devices,
+//! registers, fault handling and call stack tracking; only instruction
+//! execution and addressing differ. It implements the same traits as the
+//! [`Interpreter`] so it can be driven by a [`Simulator`] (and, through
+//! [`Control`], by the RPC stack and the UIs).
+//!
+//! All addresses the interpreter deals in (the PC, [`get_word`] and friends,
+//! breakpoints, etc.) are byte addresses. Memory backed addresses are mapped to
+//! memory words with [`memory_index`]; device registers keep their LC-3
+//! addresses. The standard device registers (`KBSR`, `KBDR`, `DSR`, `DDR`,
+//! `PSR`, `MCR`, ...) all have even addresses so LC-3b programs can use them
+//! as usual; instructions always access the (aligned) word that holds a byte
+//! so device registers at odd addresses are only reachable through [`Control`].
+//!
+//! Traps and exceptions use the LC-3b's vector tables (see
+//! [`lc3_isa::lc3b`]); interrupts and exceptions use the same vectors as the
+//! LC-3.
+//!
+//! [`Simulator`]: crate::sim::Simulator
+//! [`Control`]: lc3_traits::control::Control
+//! [`get_word`]: InstructionInterpreter::get_word
+//! [`memory_index`]: lc3_isa::lc3b::memory_index
+
+use crate::interp::{
+    Acv, CallStack, FaultHandling, InstructionInterpreter, InstructionInterpreterPeripheralAccess,
+    Interpreter, MachineState, ReadAttempt, UnbalancedReturn, WriteAttempt,
+};
+use crate::mem_mapped::{MemMapped, MemMappedSpecial, BSP, PSR};
+
+use lc3_isa::lc3b::{
+    memory_index, Instruction, Shift, INTERRUPT_VECTOR_TABLE_START_ADDR, OS_START_ADDR,
+    TRAP_VECTOR_TABLE_START_ADDR,
+};
+use lc3_isa::{
+    Addr, Reg::{self, *}, SignedWord, Word, ACCESS_CONTROL_VIOLATION_EXCEPTION_VECTOR,
+    ILLEGAL_OPCODE_EXCEPTION_VECTOR, MEM_MAPPED_START_ADDR,
+    PRIVILEGE_MODE_VIOLATION_EXCEPTION_VECTOR, USER_PROGRAM_START_ADDR,
+};
+use lc3_traits::control::call_stack::FrameKind;
+use lc3_traits::control::load::{PageIndex, PAGE_SIZE_IN_WORDS};
+use lc3_traits::control::metadata::{Identifier, ProgramMetadata, Version, version_from_crate};
+use lc3_traits::error::Error;
+use lc3_traits::{memory::Memory, peripherals::Peripherals};
+
+use core::any::TypeId;
+use core::convert::TryFrom;
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+
+#[derive(Debug)]
+pub struct Lc3bInterpreter<'per, M: Memory, P: Peripherals<'per>> {
+    inner: Interpreter<'per, M, P>,
+}
+
+// Whether `I` runs the LC-3b's instruction set rather than the LC-3's.
+pub(crate) fn is_lc3b<I: InstructionInterpreter>() -> bool {
+    I::type_id() == TypeId::of::<Lc3bInterpreter<'static, lc3_traits::memory::MemoryStub, lc3_traits::peripherals::stubs::PeripheralsStub<'static>>>()
+}
+
+impl<'a, M: Memory + Default, P: Peripherals<'a>> Default for Lc3bInterpreter<'a, M, P> {
+    fn default() -> Self {
+        Interpreter::<M, P>::default().into()
+    }
+}
+
+/// Build an [`Interpreter`] (i.e. with the
+/// [`InterpreterBuilder`](crate::interp::InterpreterBuilder)) and then convert
+/// it; this resets the machine.
+impl<'a, M: Memory, P: Peripherals<'a>> From<Interpreter<'a, M, P>> for Lc3bInterpreter<'a, M, P> {
+    fn from(inner: Interpreter<'a, M, P>) -> Self {
+        let mut interp = Self { inner };
+        interp.reset();
+        interp
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> Index<Reg> for Lc3bInterpreter<'a, M, P> {
+    type Output = Word;
+
+    fn index(&self, reg: Reg) -> &Self::Output {
+        &self.inner[reg]
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> IndexMut<Reg> for Lc3bInterpreter<'a, M, P> {
+    fn index_mut(&mut self, reg: Reg) -> &mut Self::Output {
+        &mut self.inner[reg]
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> Deref for Lc3bInterpreter<'a, M, P> {
+    type Target = P;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> DerefMut for Lc3bInterpreter<'a, M, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreterPeripheralAccess<'a>
+    for Lc3bInterpreter<'a, M, P>
+{ }
+
+impl<'a, M: Memory, P: Peripherals<'a>> Lc3bInterpreter<'a, M, P> {
+    fn is_acv(&self, addr: Addr) -> bool {
+        if self.get_special_reg::<PSR>().in_user_mode() {
+            (addr < USER_PROGRAM_START_ADDR) | (addr >= MEM_MAPPED_START_ADDR)
+        } else {
+            false
+        }
+    }
+
+    fn set_cc(&mut self, word: Word) {
+        <PSR as MemMapped>::from(self).unwrap().set_cc(self, word)
+    }
+
+    fn get_cc(&self) -> (bool, bool, bool) {
+        self.get_special_reg::<PSR>().get_cc()
+    }
+
+    fn in_user_mode(&self) -> bool {
+        self.get_special_reg::<PSR>().in_user_mode()
+    }
+
+    // Instructions always access the word that holds the byte at `addr`.
+    fn load_word(&self, addr: Addr) -> ReadAttempt {
+        self.get_word(addr & !1)
+    }
+
+    fn store_word(&mut self, addr: Addr, word: Word) -> WriteAttempt {
+        self.set_word(addr & !1, word)
+    }
+
+    fn load_byte(&self, addr: Addr) -> Result<u8, Acv> {
+        let [low, high] = self.load_word(addr)?.to_le_bytes();
+        Ok(if addr & 1 == 0 { low } else { high })
+    }
+
+    fn store_byte(&mut self, addr: Addr, byte: u8) -> WriteAttempt {
+        let aligned = addr & !1;
+
+        // Reading device registers can have side effects so we don't merge
+        // with their current value.
+        let [low, high] = if aligned >= MEM_MAPPED_START_ADDR {
+            [0, 0]
+        } else {
+            self.load_word(aligned)?.to_le_bytes()
+        };
+
+        let word = if addr & 1 == 0 { [byte, high] } else { [low, byte] };
+        self.store_word(aligned, Word::from_le_bytes(word))
+    }
+
+    fn set_reg_and_cc(&mut self, dr: Reg, word: Word) {
+        self[dr] = word;
+        self.set_cc(word);
+    }
+
+    // Word offsets (everything but `LDB`/`STB`) are shifted left by one.
+    fn offset(addr: Addr, offset: SignedWord) -> Addr {
+        addr.wrapping_add((offset as Word) << 1)
+    }
+
+    // `JSR`, `JSRR` and `TRAP` all link through R7.
+    fn call(&mut self, kind: FrameKind, return_addr: Addr, target: Addr) {
+        self[R7] = return_addr;
+        self.set_pc(target);
+        self.inner.push_call_stack(kind, target, return_addr, *self.get_special_reg::<PSR>());
+    }
+
+    // Only ever pushes onto the system stack.
+    fn push(&mut self, word: Word) -> WriteAttempt {
+        if self[R6] <= OS_START_ADDR {
+            self.set_error(Error::SystemStackOverflow);
+            self.halt();
+            return Err(Acv { addr: self[R6].wrapping_sub(2) });
+        }
+
+        self[R6] -= 2;
+        self.store_word(self[R6], word)
+    }
+
+    fn pop(&mut self) -> ReadAttempt {
+        let word = self.load_word(self[R6])?;
+        self[R6] += 2;
+
+        Ok(word)
+    }
+
+    // Infallible since BSP is 'special'.
+    fn swap_stacks(&mut self) {
+        let (sp, bsp) = (self[R6], *self.get_special_reg::<BSP>());
+
+        BSP::set_special(self, sp);
+        self[R6] = bsp;
+    }
+
+    fn handle_exception(&mut self, ex_vec: u8) {
+        self.vector_to(ex_vec, FrameKind::Exception { vector: ex_vec })
+    }
+
+    // Goes to the routine in the given interrupt vector table entry (for
+    // exceptions and interrupts; traps link through R7 instead).
+    fn vector_to(&mut self, vec: u8, kind: FrameKind) {
+        let mut psr = self.get_special_reg::<PSR>();
+        let (saved_psr, return_addr): (Word, Addr) = (*psr, self.get_pc());
+
+        if psr.in_user_mode() {
+            psr.to_privileged_mode(self);
+            self.swap_stacks();
+        }
+
+        // Push the PSR and then the PC so that the PC gets popped first.
+        if self.push(saved_psr).and_then(|()| self.push(self.get_pc())).is_err() {
+            debug_assert_eq!(self.get_machine_state(), MachineState::Halted);
+            return;
+        }
+
+        // (this should not fail; we're in privileged mode now)
+        let handler = self
+            .load_word(INTERRUPT_VECTOR_TABLE_START_ADDR + ((vec as Addr) << 1))
+            .unwrap();
+        self.set_pc(handler);
+
+        self.inner.push_call_stack(kind, handler, return_addr, saved_psr);
+    }
+
+    fn handle_fault(&mut self, pc: Addr, err: Error, ex_vec: u8) {
+        match self.get_fault_handling() {
+            FaultHandling::Vector => self.handle_exception(ex_vec),
+            FaultHandling::Stop => {
+                self.set_pc(pc);
+                self.set_error(err);
+            }
+        }
+    }
+
+    fn check_interrupts(&mut self) -> bool {
+        match self.inner.pending_interrupt() {
+            Some((int_vec, priority)) => {
+                // Haven't executed the instruction at PC - 2 yet:
+                self.set_pc(self.get_pc().wrapping_sub(2));
+
+                self.vector_to(int_vec, FrameKind::Interrupt { vector: int_vec });
+                self.set_cc(0);
+                self.get_special_reg::<PSR>().set_priority(self, priority);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn execute(&mut self, insn: Instruction) -> Result<(), Acv> {
+        use Instruction::*;
+
+        let pc = self.get_pc();
+
+        match insn {
+            AddReg { dr, sr1, sr2 } => self.set_reg_and_cc(dr, self[sr1].wrapping_add(self[sr2])),
+            AddImm { dr, sr1, imm5 } => self.set_reg_and_cc(dr, self[sr1].wrapping_add(imm5 as Word)),
+            AndReg { dr, sr1, sr2 } => self.set_reg_and_cc(dr, self[sr1] & self[sr2]),
+            AndImm { dr, sr1, imm5 } => self.set_reg_and_cc(dr, self[sr1] & (imm5 as Word)),
+            XorReg { dr, sr1, sr2 } => self.set_reg_and_cc(dr, self[sr1] ^ self[sr2]),
+            XorImm { dr, sr1, imm5 } => self.set_reg_and_cc(dr, self[sr1] ^ (imm5 as Word)),
+            Shf { dr, sr, shift, amount4 } => {
+                let (val, amount) = (self[sr], amount4 as u32);
+                let res = match shift {
+                    Shift::Left => val << amount,
+                    Shift::RightLogical => val >> amount,
+                    Shift::RightArithmetic => ((val as SignedWord) >> amount) as Word,
+                };
+
+                self.set_reg_and_cc(dr, res)
+            }
+            Br { n, z, p, offset9 } => {
+                let (cc_n, cc_z, cc_p) = self.get_cc();
+                if n && cc_n || z && cc_z || p && cc_p {
+                    self.set_pc(Self::offset(pc, offset9));
+                }
+            }
+            Jmp { base } => {
+                self.set_pc(self[base]);
+                if base == R7 {
+                    self.inner.pop_call_stack(self[R7]);
+                }
+            }
+            Jsr { offset11 } => self.call(FrameKind::Jsr, pc, Self::offset(pc, offset11)),
+            Jsrr { base } => self.call(FrameKind::Jsrr, pc, self[base]),
+            Trap { trapvec } => {
+                let routine =
+                    self.load_word(TRAP_VECTOR_TABLE_START_ADDR + ((trapvec as Addr) << 1))?;
+                self.call(FrameKind::Trap { vector: trapvec }, pc, routine)
+            }
+            Ldb { dr, base, offset6 } => {
+                let byte = self.load_byte(self[base].wrapping_add(offset6 as Word))?;
+                self.set_reg_and_cc(dr, byte as i8 as SignedWord as Word)
+            }
+            Ldw { dr, base, offset6 } => {
+                let word = self.load_word(Self::offset(self[base], offset6))?;
+                self.set_reg_and_cc(dr, word)
+            }
+            Lea { dr, offset9 } => self[dr] = Self::offset(pc, offset9),
+            Stb { sr, base, offset6 } => {
+                self.store_byte(self[base].wrapping_add(offset6 as Word), self[sr] as u8)?
+            }
+            Stw { sr, base, offset6 } => self.store_word(Self::offset(self[base], offset6), self[sr])?,
+            Rti => {
+                if self.in_user_mode() {
+                    let pc = pc.wrapping_sub(2);
+                    self.handle_fault(
+                        pc,
+                        Error::PrivilegeModeViolation { pc, insn: insn.into() },
+                        PRIVILEGE_MODE_VIOLATION_EXCEPTION_VECTOR,
+                    );
+                } else {
+                    let pc = self.pop()?;
+                    let psr = self.pop()?;
+                    self.set_pc(pc);
+                    self.inner.pop_call_stack(pc);
+                    self.set_special_reg::<PSR>(psr);
+
+                    if self.in_user_mode() {
+                        self.swap_stacks();
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, M: Memory, P: Peripherals<'a>> InstructionInterpreter for Lc3bInterpreter<'a, M, P> {
+    const ID: Identifier = Identifier::new_from_str_that_crashes_on_invalid_inputs("LC3b");
+    const VER: Version = version_from_crate!();
+
+    fn step(&mut self) -> MachineState {
+        if let state @ MachineState::Halted = self.get_machine_state() {
+            return state;
+        }
+
+        let current_pc = self.get_pc();
+        self.set_pc(current_pc.wrapping_add(2));
+
+        self.sample_capture_inputs();
+
+        if self.check_interrupts() {
+            return self.get_machine_state();
+        }
+
+        let fetched = self.load_word(current_pc);
+        match fetched.and_then(|w| match Instruction::try_from(w) {
+            Ok(insn) => self.execute(insn),
+            Err(_) => {
+                self.handle_fault(
+                    current_pc,
+                    Error::IllegalOpcode { pc: current_pc, insn: w },
+                    ILLEGAL_OPCODE_EXCEPTION_VECTOR,
+                );
+                Ok(())
+            }
+        }) {
+            Ok(()) => {}
+            Err(Acv { addr }) => self.handle_fault(
+                current_pc,
+                Error::AccessControlViolation { pc: current_pc, insn: fetched.ok(), addr },
+                ACCESS_CONTROL_VIOLATION_EXCEPTION_VECTOR,
+            ),
+        }
+
+        self.get_machine_state()
+    }
+
+    fn set_pc(&mut self, addr: Addr) {
+        self.inner.set_pc(addr)
+    }
+
+    fn get_pc(&self) -> Addr {
+        self.inner.get_pc()
+    }
+
+    fn set_word(&mut self, addr: Addr, word: Word) -> WriteAttempt {
+        if self.is_acv(addr) {
+            Err(Acv { addr })
+        } else {
+            Ok(self.set_word_unchecked(addr, word))
+        }
+    }
+
+    fn get_word(&self, addr: Addr) -> ReadAttempt {
+        if self.is_acv(addr) {
+            Err(Acv { addr })
+        } else {
+            Ok(self.get_word_unchecked(addr))
+        }
+    }
+
+    fn set_word_unchecked(&mut self, addr: Addr, word: Word) {
+        self.inner.set_word_unchecked(memory_index(addr), word)
+    }
+
+    fn get_word_unchecked(&self, addr: Addr) -> Word {
+        self.inner.get_word_unchecked(memory_index(addr))
+    }
+
+    fn set_word_force_memory_backed(&mut self, addr: Addr, word: Word) {
+        self.inner.set_word_force_memory_backed(memory_index(addr), word)
+    }
+
+    fn get_word_force_memory_backed(&self, addr: Addr) -> Word {
+        self.inner.get_word_force_memory_backed(memory_index(addr))
+    }
+
+    fn get_machine_state(&self) -> MachineState {
+        self.inner.get_machine_state()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.set_pc(OS_START_ADDR);
+    }
+
+    fn halt(&mut self) {
+        self.inner.halt()
+    }
+
+    fn set_error(&self, err: Error) {
+        self.inner.set_error(err)
+    }
+
+    fn get_error(&self) -> Option<Error> {
+        self.inner.get_error()
+    }
+
+    fn set_fault_handling(&mut self, handling: FaultHandling) {
+        self.inner.set_fault_handling(handling)
+    }
+
+    fn get_fault_handling(&self) -> FaultHandling {
+        self.inner.get_fault_handling()
+    }
+
+    fn get_call_stack(&self) -> &CallStack {
+        self.inner.get_call_stack()
+    }
+
+    fn get_call_stack_depth(&self) -> u64 {
+        self.inner.get_call_stack_depth()
+    }
+
+    fn take_unbalanced_return(&mut self) -> Option<UnbalancedReturn> {
+        self.inner.take_unbalanced_return()
+    }
+
+    fn commit_page(&mut self, page_idx: PageIndex, page: &[Word; PAGE_SIZE_IN_WORDS as usize]) {
+        self.inner.commit_page(page_idx, page)
+    }
+
+    fn get_program_metadata(&self) -> ProgramMetadata {
+        self.inner.get_program_metadata()
+    }
+
+    fn set_program_metadata(&mut self, metadata: ProgramMetadata) {
+        self.inner.set_program_metadata(metadata)
+    }
+
+    fn type_id() -> TypeId {
+        TypeId::of::<Lc3bInterpreter<'static, lc3_traits::memory::MemoryStub, lc3_traits::peripherals::stubs::PeripheralsStub<'static>>>()
+    }
+}
@@ -14,8 +14,13 @@
 
 extern crate static_assertions as sa;
 
+pub mod block;
+pub mod ext;
+pub mod init_check;
 pub mod interp;
+pub mod lc3b;
 pub mod mem_mapped;
 pub mod sim;
+pub mod stack_check;
 
 pub use mem_mapped::*;
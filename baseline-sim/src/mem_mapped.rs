@@ -73,6 +73,17 @@ pub const A4DR_ADDR: Addr = ADC_MEM_MAPPED_BASE + ADC_PIN_ADDRS * 4 + 1; // xFE4
 pub const A5CR_ADDR: Addr = ADC_MEM_MAPPED_BASE + ADC_PIN_ADDRS * 5 + 0; // xFE4A
 pub const A5DR_ADDR: Addr = ADC_MEM_MAPPED_BASE + ADC_PIN_ADDRS * 5 + 1; // xFE4B
 
+pub const ADCSPR_ADDR: Addr = ADC_MEM_MAPPED_BASE + ADC_PIN_ADDRS * 6 + 0; // xFE4C
+
+pub const ADC_BASE_INT_VEC: Addr = INTERRUPT_SERVICE_ROUTINES_START_ADDR + (ADC_OFFSET as Addr); // x1C0
+pub const A0_INT_VEC: u8 = 128 + ADC_OFFSET + 0; // xC0
+pub const A1_INT_VEC: u8 = 128 + ADC_OFFSET + 1; // xC1
+pub const A2_INT_VEC: u8 = 128 + ADC_OFFSET + 2; // xC2
+pub const A3_INT_VEC: u8 = 128 + ADC_OFFSET + 3; // xC3
+pub const A4_INT_VEC: u8 = 128 + ADC_OFFSET + 4; // xC4
+pub const A5_INT_VEC: u8 = 128 + ADC_OFFSET + 5; // xC5
+pub const ADC_INT_PRIORITY: u8 = 4;
+
 pub const PWM_OFFSET: u8 = 0x50;
 const PWM_MEM_MAPPED_BASE: Addr = MEM_MAPPED_START_ADDR + (PWM_OFFSET as Addr);
 const PWM_PIN_ADDRS: Addr = 2;
@@ -81,6 +92,15 @@ pub const P0CR_ADDR: Addr = PWM_MEM_MAPPED_BASE + PWM_PIN_ADDRS * 0 + 0; // xFE5
 pub const P0DR_ADDR: Addr = PWM_MEM_MAPPED_BASE + PWM_PIN_ADDRS * 0 + 1; // xFE51
 pub const P1CR_ADDR: Addr = PWM_MEM_MAPPED_BASE + PWM_PIN_ADDRS * 1 + 0; // xFE52
 pub const P1DR_ADDR: Addr = PWM_MEM_MAPPED_BASE + PWM_PIN_ADDRS * 1 + 1; // xFE53
+pub const P0PR_ADDR: Addr = PWM_MEM_MAPPED_BASE + PWM_PIN_ADDRS * 2 + 0; // xFE54
+pub const P1PR_ADDR: Addr = PWM_MEM_MAPPED_BASE + PWM_PIN_ADDRS * 2 + 1; // xFE55
+pub const P0IR_ADDR: Addr = PWM_MEM_MAPPED_BASE + PWM_PIN_ADDRS * 3 + 0; // xFE56
+pub const P1IR_ADDR: Addr = PWM_MEM_MAPPED_BASE + PWM_PIN_ADDRS * 3 + 1; // xFE57
+
+pub const PWM_BASE_INT_VEC: Addr = INTERRUPT_SERVICE_ROUTINES_START_ADDR + (PWM_OFFSET as Addr); // x1D0
+pub const P0_INT_VEC: u8 = 128 + PWM_OFFSET + 0; // xD0
+pub const P1_INT_VEC: u8 = 128 + PWM_OFFSET + 1; // xD1
+pub const PWM_INT_PRIORITY: u8 = 4;
 
 pub const TIMER_OFFSET: u8 = 0x60;
 const TIMER_MEM_MAPPED_BASE: Addr = MEM_MAPPED_START_ADDR + (TIMER_OFFSET as Addr);
@@ -90,10 +110,25 @@ pub const T0CR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 0 + 0; //
 pub const T0DR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 0 + 1; // xFE61
 pub const T1CR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 1 + 0; // xFE62
 pub const T1DR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 1 + 1; // xFE63
+pub const T2CR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 2 + 0; // xFE64
+pub const T2DR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 2 + 1; // xFE65
+pub const T3CR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 3 + 0; // xFE66
+pub const T3DR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 3 + 1; // xFE67
+
+pub const T0CCR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 4 + 0; // xFE68
+pub const T1CCR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 4 + 1; // xFE69
+pub const T2CCR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 4 + 2; // xFE6A
+pub const T3CCR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 4 + 3; // xFE6B
+pub const T0CPR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 6 + 0; // xFE6C
+pub const T1CPR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 6 + 1; // xFE6D
+pub const T2CPR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 6 + 2; // xFE6E
+pub const T3CPR_ADDR: Addr = TIMER_MEM_MAPPED_BASE + TIMER_PIN_ADDRS * 6 + 3; // xFE6F
 
 pub const TIMER_BASE_INT_VEC: Addr = INTERRUPT_SERVICE_ROUTINES_START_ADDR + (TIMER_OFFSET as Addr); // x1E0;       // TODO: do this in a better way
 pub const T0_INT_VEC: u8 = 128 + TIMER_OFFSET + 0; // xE0
 pub const T1_INT_VEC: u8 = 128 + TIMER_OFFSET + 1; // xE1;
+pub const T2_INT_VEC: u8 = 128 + TIMER_OFFSET + 2; // xE2;
+pub const T3_INT_VEC: u8 = 128 + TIMER_OFFSET + 3; // xE3;
 pub const TIMER_INT_PRIORITY: u8 = 4;
 
 // (For one off peripherals like the clock and the display, etc.)
@@ -102,6 +137,21 @@ const MISC_MEM_MAPPED_BASE: Addr = MEM_MAPPED_START_ADDR + (MISC_OFFSET as Addr)
 
 pub const CLKR_ADDR: Addr = MISC_MEM_MAPPED_BASE + 0; // xFE70
 
+pub const INTERRUPT_OFFSET: u8 = 0x80;
+const INTERRUPT_MEM_MAPPED_BASE: Addr = MEM_MAPPED_START_ADDR + (INTERRUPT_OFFSET as Addr);
+
+pub const ICR_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 0; // xFE80
+pub const IPNR0_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 1; // xFE81
+pub const IPNR1_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 2; // xFE82
+pub const IMR0_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 3; // xFE83
+pub const IMR1_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 4; // xFE84
+pub const IPR0_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 5; // xFE85
+pub const IPR1_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 6; // xFE86
+pub const IPR2_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 7; // xFE87
+pub const IPR3_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 8; // xFE88
+pub const IPR4_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 9; // xFE89
+pub const IPR5_ADDR: Addr = INTERRUPT_MEM_MAPPED_BASE + 10; // xFE8A
+
 pub const BSP_ADDR: Addr = 0xFFFA;
 
 use crate::interp::InstructionInterpreterPeripheralAccess;
@@ -579,17 +629,13 @@ macro_rules! gpio_mem_mapped {
                 I: InstructionInterpreterPeripheralAccess<'a>,
                 <I as Deref>::Target: Peripherals<'a>,
             {
-                let state = Gpio::get_state(interp.get_peripherals(), $pin);
+                let p = interp.get_peripherals();
 
-                use lc3_traits::peripherals::gpio::GpioState::*;
-                let word: Word = match state {
-                    Disabled => 0,
-                    Output => 1,
-                    Input => 2,
-                    Interrupt => 3,
-                };
-
-                Ok(Self::with_value(word))
+                Ok(Self::with_value(gpio_cr_word(
+                    Gpio::get_state(p, $pin),
+                    Gpio::get_trigger(p, $pin),
+                    Gpio::get_pull(p, $pin),
+                )))
             }
 
             fn set<'a, I>(interp: &mut I, value: Word) -> WriteAttempt
@@ -597,16 +643,16 @@ macro_rules! gpio_mem_mapped {
                 I: InstructionInterpreterPeripheralAccess<'a>,
                 <I as Deref>::Target: Peripherals<'a>,
             {
-                use lc3_traits::peripherals::gpio::GpioState::*;
-                let state = match value.bits(0..1) {
-                    0 => Disabled,
-                    1 => Output,
-                    2 => Input,
-                    3 => Interrupt,
-                    _ => unreachable!()
-                };
+                let (state, trigger, pull) = gpio_cr_fields(value);
+
+                // The trigger and pull are updated before the state so that
+                // a pin that's being put into interrupt mode starts out with
+                // the right configuration.
+                let res = Gpio::set_pull(interp.get_peripherals_mut(), $pin, pull)
+                    .and_then(|()| Gpio::set_trigger(interp.get_peripherals_mut(), $pin, trigger))
+                    .and_then(|()| Gpio::set_state(interp.get_peripherals_mut(), $pin, state));
 
-                match Gpio::set_state(interp.get_peripherals_mut(), $pin, state) {
+                match res {
                     Ok(()) => Ok(()),
                     Err(err) => {
                         interp.set_error(Error::from(err));
@@ -699,6 +745,69 @@ macro_rules! gpio_mem_mapped {
 }
 
 use lc3_traits::peripherals::gpio::{Gpio, GpioPin::*, GpioPinArr, GpioPin, GPIO_PINS};
+use lc3_traits::peripherals::gpio::{GpioPull, GpioState, GpioTrigger};
+
+// GxCR layout:
+//   [1:0] -> mode    (00: Disabled, 01: Output, 10: Input, 11: Interrupt)
+//   [4:2] -> trigger (000: Rising Edge, 001: Falling Edge, 010: Both Edges,
+//                     011: High Level, 100: Low Level; others: Rising Edge)
+//   [6:5] -> pull    (00: Floating, 01: Pull Up, 10: Pull Down; 11: Floating)
+//
+// The rest of the bits are ignored on writes and read as zeros.
+pub const GPIO_CR_MODE_BITS: core::ops::Range<u32> = 0..1;
+pub const GPIO_CR_TRIGGER_BITS: core::ops::Range<u32> = 2..4;
+pub const GPIO_CR_PULL_BITS: core::ops::Range<u32> = 5..6;
+
+pub fn gpio_cr_word(state: GpioState, trigger: GpioTrigger, pull: GpioPull) -> Word {
+    let state: Word = match state {
+        GpioState::Disabled => 0b00,
+        GpioState::Output => 0b01,
+        GpioState::Input => 0b10,
+        GpioState::Interrupt => 0b11,
+    };
+
+    let trigger: Word = match trigger {
+        GpioTrigger::RisingEdge => 0b000,
+        GpioTrigger::FallingEdge => 0b001,
+        GpioTrigger::BothEdges => 0b010,
+        GpioTrigger::HighLevel => 0b011,
+        GpioTrigger::LowLevel => 0b100,
+    };
+
+    let pull: Word = match pull {
+        GpioPull::Floating => 0b00,
+        GpioPull::PullUp => 0b01,
+        GpioPull::PullDown => 0b10,
+    };
+
+    state | (trigger << GPIO_CR_TRIGGER_BITS.start) | (pull << GPIO_CR_PULL_BITS.start)
+}
+
+pub fn gpio_cr_fields(word: Word) -> (GpioState, GpioTrigger, GpioPull) {
+    let state = match word.bits(GPIO_CR_MODE_BITS) {
+        0b00 => GpioState::Disabled,
+        0b01 => GpioState::Output,
+        0b10 => GpioState::Input,
+        0b11 => GpioState::Interrupt,
+        _ => unreachable!(),
+    };
+
+    let trigger = match word.bits(GPIO_CR_TRIGGER_BITS) {
+        0b001 => GpioTrigger::FallingEdge,
+        0b010 => GpioTrigger::BothEdges,
+        0b011 => GpioTrigger::HighLevel,
+        0b100 => GpioTrigger::LowLevel,
+        _ => GpioTrigger::RisingEdge,
+    };
+
+    let pull = match word.bits(GPIO_CR_PULL_BITS) {
+        0b01 => GpioPull::PullUp,
+        0b10 => GpioPull::PullDown,
+        _ => GpioPull::Floating,
+    };
+
+    (state, trigger, pull)
+}
 
 gpio_mem_mapped!(G0, "G0", G0CR, G0DR, G0CR_ADDR, G0DR_ADDR, G0_INT_VEC);
 gpio_mem_mapped!(G1, "G1", G1CR, G1DR, G1CR_ADDR, G1DR_ADDR, G1_INT_VEC);
@@ -782,7 +891,7 @@ impl MemMapped for GPIODR {
 //}
 
 macro_rules! adc_mem_mapped {
-    ($pin:expr, $pin_name:literal, $cr:ident, $dr:ident, $cr_addr:expr, $dr_addr:expr) => {
+    ($pin:expr, $pin_name:literal, $cr:ident, $dr:ident, $cr_addr:expr, $dr_addr:expr, $int_vec:expr) => {
         #[doc=$pin_name]
         #[doc="ADC Pin Control Register"] // TODO: format correctly
         #[derive(Copy, Clone, Debug, PartialEq)]
@@ -804,13 +913,12 @@ macro_rules! adc_mem_mapped {
                 I: InstructionInterpreterPeripheralAccess<'a>,
                 <I as Deref>::Target: Peripherals<'a>,
             {
-                let state = Adc::get_state(interp.get_peripherals(), $pin);
-
-                use lc3_traits::peripherals::adc::AdcState::*;
-                let word: Word = match state {
-                    Disabled => 0,
-                    Enabled => 1,
-                };
+                let p = interp.get_peripherals();
+                let word = adc_cr_word(
+                    Adc::get_state(p, $pin),
+                    Adc::get_mode(p, $pin),
+                    Adc::get_interrupt(p, $pin),
+                );
 
                 Ok(Self::with_value(word))
             }
@@ -820,13 +928,15 @@ macro_rules! adc_mem_mapped {
                 I: InstructionInterpreterPeripheralAccess<'a>,
                 <I as Deref>::Target: Peripherals<'a>,
             {
-                use lc3_traits::peripherals::adc::AdcState::*;
-                let state = match value.bit(0) {
-                    false => Disabled,
-                    true => Enabled,
-                };
+                let (state, mode, interrupt) = adc_cr_fields(value);
 
-                match Adc::set_state(interp.get_peripherals_mut(), $pin, state) {
+                // As with the GPIO pins, the state goes last so that a pin
+                // that's being enabled starts out with the right configuration.
+                let res = Adc::set_interrupt(interp.get_peripherals_mut(), $pin, interrupt)
+                    .and_then(|()| Adc::set_mode(interp.get_peripherals_mut(), $pin, mode))
+                    .and_then(|()| Adc::set_state(interp.get_peripherals_mut(), $pin, state));
+
+                match res {
                     Ok(()) => Ok(()),
                     Err(err) => {
                         interp.set_error(Error::from(err));
@@ -836,6 +946,37 @@ macro_rules! adc_mem_mapped {
             }
         }
 
+        impl Interrupt for $cr {
+            const INT_VEC: u8 = $int_vec;
+            const PRIORITY: u8 = ADC_INT_PRIORITY;
+
+            fn interrupt_ready<'a, I>(interp: &I) -> bool
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                Adc::interrupt_occurred(interp.get_peripherals(), $pin)
+            }
+
+            fn interrupt_enabled<'a, I>(interp: &I) -> bool
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>
+            {
+                Adc::interrupts_enabled(interp.get_peripherals(), $pin)
+            }
+
+            fn reset_interrupt_flag<'a, I>(interp: &mut I)
+                where
+                    I: InstructionInterpreterPeripheralAccess<'a>,
+                    <I as Deref>::Target: Peripherals<'a>
+            {
+                if Adc::interrupts_enabled(interp.get_peripherals(), $pin) {
+                    Adc::reset_interrupt_flag(interp.get_peripherals_mut(), $pin);
+                }
+            }
+        }
+
         #[doc=$pin_name]
         #[doc="ADC Pin Data Register"] // TODO: format correctly
         #[derive(Copy, Clone, Debug, PartialEq)]
@@ -881,13 +1022,116 @@ macro_rules! adc_mem_mapped {
 }
 
 use lc3_traits::peripherals::adc::{Adc, AdcPin::*};
+use lc3_traits::peripherals::adc::{AdcInterrupt, AdcMiscError, AdcMode, AdcSamplePeriod, AdcState};
+
+// AxCR layout:
+//   [0]    -> state     (0: Disabled, 1: Enabled)
+//   [1]    -> mode      (0: One Shot, 1: Continuous)
+//   [3:2]  -> interrupt (00: Disabled, 01: Conversion Complete,
+//                        10: Threshold Crossing; 11: Disabled)
+//   [15:8] -> threshold (only used for threshold crossing interrupts)
+//
+// The rest of the bits are ignored on writes and read as zeros.
+pub const ADC_CR_STATE_BIT: u32 = 0;
+pub const ADC_CR_MODE_BIT: u32 = 1;
+pub const ADC_CR_INTERRUPT_BITS: core::ops::Range<u32> = 2..3;
+pub const ADC_CR_THRESHOLD_BITS: core::ops::Range<u32> = 8..15;
+
+pub fn adc_cr_word(state: AdcState, mode: AdcMode, interrupt: AdcInterrupt) -> Word {
+    let state: Word = match state {
+        AdcState::Disabled => 0,
+        AdcState::Enabled => 1,
+    };
+
+    let mode: Word = match mode {
+        AdcMode::OneShot => 0,
+        AdcMode::Continuous => 1,
+    };
+
+    let (interrupt, threshold): (Word, Word) = match interrupt {
+        AdcInterrupt::Disabled => (0b00, 0),
+        AdcInterrupt::ConversionComplete => (0b01, 0),
+        AdcInterrupt::ThresholdCrossing(t) => (0b10, t as Word),
+    };
+
+    (state << ADC_CR_STATE_BIT)
+        | (mode << ADC_CR_MODE_BIT)
+        | (interrupt << ADC_CR_INTERRUPT_BITS.start)
+        | (threshold << ADC_CR_THRESHOLD_BITS.start)
+}
+
+pub fn adc_cr_fields(word: Word) -> (AdcState, AdcMode, AdcInterrupt) {
+    let state = match word.bit(ADC_CR_STATE_BIT) {
+        false => AdcState::Disabled,
+        true => AdcState::Enabled,
+    };
+
+    let mode = match word.bit(ADC_CR_MODE_BIT) {
+        false => AdcMode::OneShot,
+        true => AdcMode::Continuous,
+    };
+
+    let interrupt = match word.bits(ADC_CR_INTERRUPT_BITS) {
+        0b01 => AdcInterrupt::ConversionComplete,
+        0b10 => AdcInterrupt::ThresholdCrossing(word.bits(ADC_CR_THRESHOLD_BITS) as u8),
+        _ => AdcInterrupt::Disabled,
+    };
+
+    (state, mode, interrupt)
+}
 
-adc_mem_mapped!(A0, "A0", A0CR, A0DR, A0CR_ADDR, A0DR_ADDR);
-adc_mem_mapped!(A1, "A1", A1CR, A1DR, A1CR_ADDR, A1DR_ADDR);
-adc_mem_mapped!(A2, "A2", A2CR, A2DR, A2CR_ADDR, A2DR_ADDR);
-adc_mem_mapped!(A3, "A3", A3CR, A3DR, A3CR_ADDR, A3DR_ADDR);
-adc_mem_mapped!(A4, "A4", A4CR, A4DR, A4CR_ADDR, A4DR_ADDR);
-adc_mem_mapped!(A5, "A5", A5CR, A5DR, A5CR_ADDR, A5DR_ADDR);
+adc_mem_mapped!(A0, "A0", A0CR, A0DR, A0CR_ADDR, A0DR_ADDR, A0_INT_VEC);
+adc_mem_mapped!(A1, "A1", A1CR, A1DR, A1CR_ADDR, A1DR_ADDR, A1_INT_VEC);
+adc_mem_mapped!(A2, "A2", A2CR, A2DR, A2CR_ADDR, A2DR_ADDR, A2_INT_VEC);
+adc_mem_mapped!(A3, "A3", A3CR, A3DR, A3CR_ADDR, A3DR_ADDR, A3_INT_VEC);
+adc_mem_mapped!(A4, "A4", A4CR, A4DR, A4CR_ADDR, A4DR_ADDR, A4_INT_VEC);
+adc_mem_mapped!(A5, "A5", A5CR, A5DR, A5CR_ADDR, A5DR_ADDR, A5_INT_VEC);
+
+/// ADC Sample Period Register
+///
+/// The time between conversions for pins in continuous mode, in milliseconds.
+/// Writing a 0 is an error.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ADCSPR(Word);
+
+impl Deref for ADCSPR {
+    type Target = Word;
+
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+impl MemMapped for ADCSPR {
+    const ADDR: Addr = ADCSPR_ADDR;
+
+    fn with_value(value: Word) -> Self { Self(value) }
+
+    fn from<'a, I> (interp: &I) -> Result<Self, Acv>
+    where
+        I: InstructionInterpreterPeripheralAccess<'a>,
+        <I as Deref>::Target: Peripherals<'a>,
+    {
+        Ok(Self::with_value(Adc::get_sample_period(interp.get_peripherals()).get()))
+    }
+
+    fn set<'a, I>(interp: &mut I, value: Word) -> WriteAttempt
+    where
+        I: InstructionInterpreterPeripheralAccess<'a>,
+        <I as Deref>::Target: Peripherals<'a>,
+    {
+        let res = match AdcSamplePeriod::new(value) {
+            Some(period) => Adc::set_sample_period(interp.get_peripherals_mut(), period),
+            None => Err(AdcMiscError),
+        };
+
+        match res {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                interp.set_error(Error::from(err));
+                Ok(())
+            }
+        }
+    }
+}
 
 use lc3_traits::peripherals::clock::Clock;
 #[doc = "Clock Register"]
@@ -928,8 +1172,24 @@ impl MemMapped for CLKR {
     }
 }
 
+// PxCR holds the period in milliseconds; only the low 8 bits are used and 0
+// disables the pin. Periods that aren't a whole number of milliseconds read as
+// the nearest one.
+//
+// PxPR holds the period in microseconds; 0 disables the pin (just like the
+// timer period registers). Periods longer than xFFFF microseconds (which can
+// only be set through PxCR) read as xFFFF.
+//
+// PxDR holds the duty cycle as a fraction of `MAX_DUTY_CYCLE` (xFFFF).
+//
+// PxIR layout:
+//   [0] -> rollover interrupt (0: Disabled, 1: Enabled)
+//
+// The rest of the bits are ignored on writes and read as zeros.
+pub const PWM_IR_INTERRUPT_BIT: u32 = 0;
+
 macro_rules! pwm_mem_mapped {
-    ($pin:expr, $pin_name:literal, $cr:ident, $dr:ident, $cr_addr:expr, $dr_addr:expr) => {
+    ($pin:expr, $pin_name:literal, $cr:ident, $dr:ident, $pr:ident, $ir:ident, $cr_addr:expr, $dr_addr:expr, $pr_addr:expr, $ir_addr:expr, $int_vec:expr) => {
         #[doc=$pin_name]
         #[doc="PWM Pin Control Register"] // TODO: format correctly
         #[derive(Copy, Clone, Debug, PartialEq)]
@@ -956,7 +1216,7 @@ macro_rules! pwm_mem_mapped {
                 use lc3_traits::peripherals::pwm::PwmState::*;
                 let word: Word = match state {
                     Disabled => 0,
-                    Enabled(ref nzu8) => nzu8.get() as Word,
+                    Enabled(period) => period.as_millis().max(1) as Word,
                 };
 
                 Ok(Self::with_value(word))
@@ -968,12 +1228,133 @@ macro_rules! pwm_mem_mapped {
                 <I as Deref>::Target: Peripherals<'a>,
             {
                 use lc3_traits::peripherals::pwm::PwmState::*;
-                use core::num::NonZeroU8;
+                use lc3_traits::peripherals::pwm::PwmPeriod;
 
-                let state_val: u8 = value as u8;
-                let state = match state_val {
-                    0 => Disabled,
-                    _ => Enabled(NonZeroU8::new(state_val).unwrap()),
+                let state = match PwmPeriod::from_millis(value as u8 as u16) {
+                    None => Disabled,
+                    Some(period) => Enabled(period),
+                };
+
+                Pwm::set_state(interp.get_peripherals_mut(), $pin, state);
+
+                Ok(())
+            }
+        }
+
+        #[doc=$pin_name]
+        #[doc="PWM Pin Interrupt Register"] // TODO: format correctly
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        pub struct $ir(Word);
+
+        impl Deref for $ir {
+            type Target = Word;
+
+            fn deref(&self) -> &Self::Target { &self.0 }
+        }
+
+        impl MemMapped for $ir {
+            const ADDR: Addr = $ir_addr;
+
+            fn with_value(value: Word) -> Self { Self(value) }
+
+            fn from<'a, I> (interp: &I) -> Result<Self, Acv>
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                let interrupt = Pwm::get_rollover_interrupt(interp.get_peripherals(), $pin);
+                let word = (interrupt as Word) << PWM_IR_INTERRUPT_BIT;
+
+                Ok(Self::with_value(word))
+            }
+
+            fn set<'a, I>(interp: &mut I, value: Word) -> WriteAttempt
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                let interrupt = value.bit(PWM_IR_INTERRUPT_BIT);
+                Pwm::set_rollover_interrupt(interp.get_peripherals_mut(), $pin, interrupt);
+
+                Ok(())
+            }
+        }
+
+        impl Interrupt for $ir {
+            const INT_VEC: u8 = $int_vec;
+            const PRIORITY: u8 = PWM_INT_PRIORITY;
+
+            fn interrupt_ready<'a, I>(interp: &I) -> bool
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                Pwm::interrupt_occurred(interp.get_peripherals(), $pin)
+            }
+
+            fn interrupt_enabled<'a, I>(interp: &I) -> bool
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>
+            {
+                Pwm::interrupts_enabled(interp.get_peripherals(), $pin)
+            }
+
+            fn reset_interrupt_flag<'a, I>(interp: &mut I)
+                where
+                    I: InstructionInterpreterPeripheralAccess<'a>,
+                    <I as Deref>::Target: Peripherals<'a>
+            {
+                if Pwm::interrupts_enabled(interp.get_peripherals(), $pin) {
+                    Pwm::reset_interrupt_flag(interp.get_peripherals_mut(), $pin);
+                }
+            }
+
+        }
+
+        #[doc=$pin_name]
+        #[doc="PWM Pin Period Register"] // TODO: format correctly
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        pub struct $pr(Word);
+
+        impl Deref for $pr {
+            type Target = Word;
+
+            fn deref(&self) -> &Self::Target { &self.0 }
+        }
+
+        impl MemMapped for $pr {
+            const ADDR: Addr = $pr_addr;
+
+            fn with_value(value: Word) -> Self { Self(value) }
+
+            fn from<'a, I> (interp: &I) -> Result<Self, Acv>
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                let state = Pwm::get_state(interp.get_peripherals(), $pin);
+
+                use lc3_traits::peripherals::pwm::PwmState::*;
+                let word: Word = match state {
+                    Disabled => 0,
+                    Enabled(period) => period.as_micros().min(Word::MAX as u32) as Word,
+                };
+
+                Ok(Self::with_value(word))
+            }
+
+            fn set<'a, I>(interp: &mut I, value: Word) -> WriteAttempt
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                use lc3_traits::peripherals::pwm::PwmState::*;
+                use lc3_traits::peripherals::pwm::PwmPeriod;
+
+                let state = match PwmPeriod::from_micros(value as u32) {
+                    None => Disabled,
+                    Some(period) => Enabled(period),
                 };
 
                 Pwm::set_state(interp.get_peripherals_mut(), $pin, state);
@@ -1003,7 +1384,7 @@ macro_rules! pwm_mem_mapped {
                 I: InstructionInterpreterPeripheralAccess<'a>,
                 <I as Deref>::Target: Peripherals<'a>,
             {
-                let word = Pwm::get_duty_cycle(interp.get_peripherals(), $pin) as Word;
+                let word = Pwm::get_duty_cycle(interp.get_peripherals(), $pin);
 
                 Ok(Self::with_value(word))
             }
@@ -1013,9 +1394,7 @@ macro_rules! pwm_mem_mapped {
                 I: InstructionInterpreterPeripheralAccess<'a>,
                 <I as Deref>::Target: Peripherals<'a>,
             {
-                let duty_val: u8 = value as u8;
-
-                Pwm::set_duty_cycle(interp.get_peripherals_mut(), $pin, duty_val);
+                Pwm::set_duty_cycle(interp.get_peripherals_mut(), $pin, value);
 
                 Ok(())
             }
@@ -1025,11 +1404,11 @@ macro_rules! pwm_mem_mapped {
 
 use lc3_traits::peripherals::pwm::{Pwm, PwmPin::*};
 
-pwm_mem_mapped!(P0, "P0", P0CR, P0DR, P0CR_ADDR, P0DR_ADDR);
-pwm_mem_mapped!(P1, "P1", P1CR, P1DR, P1CR_ADDR, P1DR_ADDR);
+pwm_mem_mapped!(P0, "P0", P0CR, P0DR, P0PR, P0IR, P0CR_ADDR, P0DR_ADDR, P0PR_ADDR, P0IR_ADDR, P0_INT_VEC);
+pwm_mem_mapped!(P1, "P1", P1CR, P1DR, P1PR, P1IR, P1CR_ADDR, P1DR_ADDR, P1PR_ADDR, P1IR_ADDR, P1_INT_VEC);
 
 macro_rules! timer_mem_mapped {
-    ($id:expr, $id_name:literal, $cr:ident, $dr:ident, $cr_addr:expr, $dr_addr:expr, $int_vec:expr) => {
+    ($id:expr, $id_name:literal, $cr:ident, $dr:ident, $ccr:ident, $cpr:ident, $cr_addr:expr, $dr_addr:expr, $ccr_addr:expr, $cpr_addr:expr, $int_vec:expr) => {
         #[doc=$id_name]
         #[doc="Timer Control Register"] // TODO: format correctly
         #[derive(Copy, Clone, Debug, PartialEq)]
@@ -1055,8 +1434,9 @@ macro_rules! timer_mem_mapped {
 
                 use lc3_traits::peripherals::timers::TimerMode::*;
                 let word: Word = match mode {
-                    SingleShot => 0,
-                    Repeated => 1,
+                    SingleShot => 0b00,
+                    Repeated => 0b01,
+                    FreeRunning => 0b10,
                 };
 
                 Ok(Self::with_value(word))
@@ -1068,10 +1448,10 @@ macro_rules! timer_mem_mapped {
                 <I as Deref>::Target: Peripherals<'a>,
             {
                 use lc3_traits::peripherals::timers::TimerMode::*;
-                let mode = if value.bit(0) {
-                    Repeated
-                } else {
-                    SingleShot
+                let mode = match value.bits(TIMER_CR_MODE_BITS) {
+                    0b01 | 0b11 => Repeated,
+                    0b10 => FreeRunning,
+                    _ => SingleShot,
                 };
 
                 Timers::set_mode(interp.get_peripherals_mut(), $id, mode);
@@ -1134,11 +1514,14 @@ macro_rules! timer_mem_mapped {
                 <I as Deref>::Target: Peripherals<'a>,
             {
                 let state = Timers::get_state(interp.get_peripherals(), $id);
+                let mode = Timers::get_mode(interp.get_peripherals(), $id);
 
-                use lc3_traits::peripherals::timers::TimerState::*;
-                let value = match state {
-                    Disabled => 0,
-                    WithPeriod(period) => period.into(),
+                use lc3_traits::peripherals::timers::{TimerMode::FreeRunning, TimerState::*};
+                let value = match (state, mode) {
+                    (Disabled, _) => 0,
+                    // Free running timers expose their count instead:
+                    (WithPeriod(_), FreeRunning) => Timers::get_count(interp.get_peripherals(), $id),
+                    (WithPeriod(period), _) => period.into(),
                 };
 
                 Ok(Self::with_value(value))
@@ -1160,13 +1543,127 @@ macro_rules! timer_mem_mapped {
                 Ok(())
             }
         }
+
+        #[doc=$id_name]
+        #[doc="Timer Capture Control Register"] // TODO: format correctly
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        pub struct $ccr(Word);
+
+        impl Deref for $ccr {
+            type Target = Word;
+
+            fn deref(&self) -> &Self::Target { &self.0 }
+        }
+
+        impl MemMapped for $ccr {
+            const ADDR: Addr = $ccr_addr;
+
+            fn with_value(value: Word) -> Self { Self(value) }
+
+            fn from<'a, I> (interp: &I) -> Result<Self, Acv>
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                let source = Timers::get_capture_source(interp.get_peripherals(), $id);
+
+                Ok(Self::with_value(timer_ccr_word(source)))
+            }
+
+            fn set<'a, I>(interp: &mut I, value: Word) -> WriteAttempt
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                Timers::set_capture_source(interp.get_peripherals_mut(), $id, timer_ccr_source(value));
+
+                Ok(())
+            }
+        }
+
+        #[doc=$id_name]
+        #[doc="Timer Capture Register"] // TODO: format correctly
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        pub struct $cpr(Word);
+
+        impl Deref for $cpr {
+            type Target = Word;
+
+            fn deref(&self) -> &Self::Target { &self.0 }
+        }
+
+        impl MemMapped for $cpr {
+            const ADDR: Addr = $cpr_addr;
+
+            fn with_value(value: Word) -> Self { Self(value) }
+
+            fn from<'a, I> (interp: &I) -> Result<Self, Acv>
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                Ok(Self::with_value(Timers::get_capture(interp.get_peripherals(), $id)))
+            }
+
+            // The captured count is read-only; writes are ignored.
+            fn set<'a, I>(_interp: &mut I, _value: Word) -> WriteAttempt
+            where
+                I: InstructionInterpreterPeripheralAccess<'a>,
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                Ok(())
+            }
+        }
     };
 }
 
-use lc3_traits::peripherals::timers::{Timers, TimerId::*};
+use lc3_traits::peripherals::timers::{Timers, TimerId::*, CaptureEdge, CaptureSource};
+
+// TxCR layout:
+//   [1:0] -> mode (00: Single Shot, 01: Repeated, 10: Free Running, 11: Repeated)
+//
+// TxCCR layout:
+//   [1:0] -> capture edge (00: Disabled, 01: Rising Edge, 10: Falling Edge,
+//                          11: Both Edges)
+//   [4:2] -> capture GPIO pin number
+//
+// The rest of the bits are ignored on writes and read as zeros.
+pub const TIMER_CR_MODE_BITS: core::ops::Range<u32> = 0..1;
+pub const TIMER_CCR_EDGE_BITS: core::ops::Range<u32> = 0..1;
+pub const TIMER_CCR_PIN_BITS: core::ops::Range<u32> = 2..4;
+
+pub fn timer_ccr_word(source: Option<CaptureSource>) -> Word {
+    match source {
+        None => 0,
+        Some(CaptureSource { pin, edge }) => {
+            let edge: Word = match edge {
+                CaptureEdge::Rising => 0b01,
+                CaptureEdge::Falling => 0b10,
+                CaptureEdge::Both => 0b11,
+            };
+
+            edge | ((usize::from(pin) as Word) << TIMER_CCR_PIN_BITS.start)
+        }
+    }
+}
+
+pub fn timer_ccr_source(word: Word) -> Option<CaptureSource> {
+    let edge = match word.bits(TIMER_CCR_EDGE_BITS) {
+        0b01 => CaptureEdge::Rising,
+        0b10 => CaptureEdge::Falling,
+        0b11 => CaptureEdge::Both,
+        _ => return None,
+    };
 
-timer_mem_mapped!(T0, "T0", T0CR, T0DR, T0CR_ADDR, T0DR_ADDR, T0_INT_VEC);
-timer_mem_mapped!(T1, "T1", T1CR, T1DR, T1CR_ADDR, T1DR_ADDR, T1_INT_VEC);
+    let pin = GPIO_PINS.0[word.bits(TIMER_CCR_PIN_BITS) as usize];
+
+    Some(CaptureSource { pin, edge })
+}
+
+timer_mem_mapped!(T0, "T0", T0CR, T0DR, T0CCR, T0CPR, T0CR_ADDR, T0DR_ADDR, T0CCR_ADDR, T0CPR_ADDR, T0_INT_VEC);
+timer_mem_mapped!(T1, "T1", T1CR, T1DR, T1CCR, T1CPR, T1CR_ADDR, T1DR_ADDR, T1CCR_ADDR, T1CPR_ADDR, T1_INT_VEC);
+timer_mem_mapped!(T2, "T2", T2CR, T2DR, T2CCR, T2CPR, T2CR_ADDR, T2DR_ADDR, T2CCR_ADDR, T2CPR_ADDR, T2_INT_VEC);
+timer_mem_mapped!(T3, "T3", T3CR, T3DR, T3CCR, T3CPR, T3CR_ADDR, T3DR_ADDR, T3CCR_ADDR, T3CPR_ADDR, T3_INT_VEC);
 
 mem_mapped!(special: BSP, BSP_ADDR, "Backup Stack Pointer.");
 
@@ -1352,3 +1849,263 @@ impl MCR {
         self.set_running_bit(interp, true);
     }
 }
+
+// Interrupt controller.
+//
+// Interrupt sources are numbered in the order they're listed below (the same
+// order as `lc3_traits::control::INTERRUPT_SOURCES`); source `i`'s pending and
+// mask bits are bit `i % 16` of IPNR(i / 16) and IMR(i / 16) and its priority
+// is in IPR(i / 4).
+//
+// ICR layout:
+//   [15] -> interrupts (0: Disabled, 1: Enabled)
+//
+// The rest of the bits are ignored on writes and read as zeros.
+//
+// IPNRx are read only; a bit is set when the source's device has an interrupt
+// enabled and ready, whether or not the source is masked. Writes are ignored.
+//
+// IMRx: a set bit masks the source (it'll stay pending but won't fire).
+//
+// IPRx layout (4 sources per register):
+//   [2:0]   -> priority of source 4x
+//   [6:4]   -> priority of source 4x + 1
+//   [10:8]  -> priority of source 4x + 2
+//   [14:12] -> priority of source 4x + 3
+//
+// A source only fires if its priority is higher than the current priority (in
+// the PSR); priority 0 sources never fire. When several sources can fire, the
+// one with the highest priority wins (ties go to the lower numbered source).
+//
+// On reset interrupts are enabled, nothing is masked and every source has its
+// device's default priority.
+pub const ICR_ENABLE_BIT: u32 = 15;
+
+macro_rules! interrupt_sources {
+    ($($dev:ty),* $(,)?) => {
+        /// The interrupt vector of each interrupt source.
+        pub const INTERRUPT_VECTORS: [u8; InterruptSource::NUM_SOURCES] = [$(<$dev>::INT_VEC),*];
+
+        /// The priority each interrupt source starts out with.
+        pub const DEFAULT_INTERRUPT_PRIORITIES: [u8; InterruptSource::NUM_SOURCES] = [$(<$dev>::PRIORITY),*];
+
+        /// Whether an interrupt source's device has an interrupt enabled and
+        /// ready to fire.
+        pub fn interrupt_source_ready<'a, I>(interp: &I, source: usize) -> bool
+        where
+            I: InstructionInterpreterPeripheralAccess<'a>,
+            <I as Deref>::Target: Peripherals<'a>,
+        {
+            let mut idx = 0;
+            $(
+                if idx == source { return <$dev as Interrupt>::interrupt(interp); }
+                idx += 1;
+            )*
+
+            let _ = idx;
+            false
+        }
+
+        pub(crate) fn reset_interrupt_source_flag<'a, I>(interp: &mut I, source: usize)
+        where
+            I: InstructionInterpreterPeripheralAccess<'a>,
+            <I as Deref>::Target: Peripherals<'a>,
+        {
+            let mut idx = 0;
+            $(
+                if idx == source { return <$dev as Interrupt>::reset_interrupt_flag(interp); }
+                idx += 1;
+            )*
+
+            let _ = idx;
+        }
+    };
+}
+
+interrupt_sources!(
+    KBSR, DSR, G0CR, G1CR, G2CR, G3CR, G4CR, G5CR, G6CR, G7CR,
+    A0CR, A1CR, A2CR, A3CR, A4CR, A5CR, P0IR, P1IR, T0CR, T1CR, T2CR, T3CR
+);
+
+use lc3_traits::control::{InterruptControllerState, InterruptSource};
+
+/// Interrupt Control Register
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ICR(Word);
+
+impl Deref for ICR {
+    type Target = Word;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl MemMapped for ICR {
+    const ADDR: Addr = ICR_ADDR;
+    fn with_value(value: Word) -> Self {
+        Self(value)
+    }
+    fn from<'a, I: InstructionInterpreterPeripheralAccess<'a>>(interp: &I) -> Result<Self, Acv>
+    where
+        <I as Deref>::Target: Peripherals<'a>,
+    {
+        Ok(Self::with_value(
+            interp.get_word_force_memory_backed(Self::ADDR),
+        ))
+    }
+
+    fn set<'a, I: InstructionInterpreterPeripheralAccess<'a>>(
+        interp: &mut I,
+        value: Word,
+    ) -> WriteAttempt
+    where
+        <I as Deref>::Target: Peripherals<'a>,
+    {
+        interp.set_word_force_memory_backed(Self::ADDR, value.select(ICR_ENABLE_BIT..ICR_ENABLE_BIT));
+        Ok(())
+    }
+}
+
+impl MemMappedSpecial for ICR {}
+
+impl ICR {
+    pub fn interrupts_enabled(&self) -> bool {
+        self.0.bit(ICR_ENABLE_BIT)
+    }
+}
+
+/// Pending interrupt bits for the interrupt sources starting at `first`.
+fn pending_interrupts<'a, I>(interp: &I, first: usize) -> Word
+where
+    I: InstructionInterpreterPeripheralAccess<'a>,
+    <I as Deref>::Target: Peripherals<'a>,
+{
+    (first..InterruptSource::NUM_SOURCES.min(first + 16))
+        .filter(|s| interrupt_source_ready(interp, *s))
+        .fold(0, |pending, s| pending | (1 << (s - first)))
+}
+
+macro_rules! ipnr_mem_mapped {
+    ($name:ident, $addr:expr, $first:literal, $comment:literal) => {
+        #[doc=$comment]
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        pub struct $name(Word);
+
+        impl Deref for $name {
+            type Target = Word;
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl MemMapped for $name {
+            const ADDR: Addr = $addr;
+            fn with_value(value: Word) -> Self {
+                Self(value)
+            }
+            fn from<'a, I: InstructionInterpreterPeripheralAccess<'a>>(interp: &I) -> Result<Self, Acv>
+            where
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                Ok(Self::with_value(pending_interrupts(interp, $first)))
+            }
+
+            fn set<'a, I: InstructionInterpreterPeripheralAccess<'a>>(
+                _interp: &mut I,
+                _value: Word,
+            ) -> WriteAttempt
+            where
+                <I as Deref>::Target: Peripherals<'a>,
+            {
+                Ok(()) // Read only.
+            }
+        }
+
+        impl MemMappedSpecial for $name {}
+    };
+}
+
+ipnr_mem_mapped!(IPNR0, IPNR0_ADDR, 0, "Interrupt Pending Register 0 (sources 0 - 15)");
+ipnr_mem_mapped!(IPNR1, IPNR1_ADDR, 16, "Interrupt Pending Register 1 (sources 16 - 21)");
+
+mem_mapped!(special: IMR0, IMR0_ADDR, "Interrupt Mask Register 0 (sources 0 - 15).");
+mem_mapped!(special: IMR1, IMR1_ADDR, "Interrupt Mask Register 1 (sources 16 - 21).");
+
+mem_mapped!(special: IPR0, IPR0_ADDR, "Interrupt Priority Register 0 (sources 0 - 3).");
+mem_mapped!(special: IPR1, IPR1_ADDR, "Interrupt Priority Register 1 (sources 4 - 7).");
+mem_mapped!(special: IPR2, IPR2_ADDR, "Interrupt Priority Register 2 (sources 8 - 11).");
+mem_mapped!(special: IPR3, IPR3_ADDR, "Interrupt Priority Register 3 (sources 12 - 15).");
+mem_mapped!(special: IPR4, IPR4_ADDR, "Interrupt Priority Register 4 (sources 16 - 19).");
+mem_mapped!(special: IPR5, IPR5_ADDR, "Interrupt Priority Register 5 (sources 20 - 21).");
+
+// The priority registers are read and written by address since sources are
+// picked at runtime.
+const fn ipr_addr(source: usize) -> Addr {
+    IPR0_ADDR + (source / 4) as Addr
+}
+const fn ipr_shift(source: usize) -> u32 {
+    (source % 4) as u32 * 4
+}
+
+pub fn interrupt_mask<'a, I>(interp: &I) -> u32
+where
+    I: InstructionInterpreterPeripheralAccess<'a>,
+    <I as Deref>::Target: Peripherals<'a>,
+{
+    (*interp.get_special_reg::<IMR0>() as u32) | ((*interp.get_special_reg::<IMR1>() as u32) << 16)
+}
+
+pub fn interrupt_priority<'a, I>(interp: &I, source: usize) -> u8
+where
+    I: InstructionInterpreterPeripheralAccess<'a>,
+    <I as Deref>::Target: Peripherals<'a>,
+{
+    let shift = ipr_shift(source);
+    interp.get_word_force_memory_backed(ipr_addr(source)).u8(shift..(shift + 2))
+}
+
+pub fn set_interrupt_priority<'a, I>(interp: &mut I, source: usize, priority: u8)
+where
+    I: InstructionInterpreterPeripheralAccess<'a>,
+    <I as Deref>::Target: Peripherals<'a>,
+{
+    let (addr, shift) = (ipr_addr(source), ipr_shift(source));
+    let word = interp.get_word_force_memory_backed(addr);
+    let word = (word & !WORD_MAX_VAL.select(shift..(shift + 2))) | ((priority as Word).u16(0..2) << shift);
+
+    interp.set_word_force_memory_backed(addr, word);
+}
+
+/// Puts the interrupt controller in its reset state: interrupts enabled,
+/// nothing masked and every source at its default priority.
+pub(crate) fn reset_interrupt_controller<'a, I>(interp: &mut I)
+where
+    I: InstructionInterpreterPeripheralAccess<'a>,
+    <I as Deref>::Target: Peripherals<'a>,
+{
+    interp.set_special_reg::<ICR>(1 << ICR_ENABLE_BIT);
+    interp.set_special_reg::<IMR0>(0);
+    interp.set_special_reg::<IMR1>(0);
+
+    for (source, priority) in DEFAULT_INTERRUPT_PRIORITIES.iter().enumerate() {
+        set_interrupt_priority(interp, source, *priority);
+    }
+}
+
+pub fn interrupt_controller_state<'a, I>(interp: &I) -> InterruptControllerState
+where
+    I: InstructionInterpreterPeripheralAccess<'a>,
+    <I as Deref>::Target: Peripherals<'a>,
+{
+    let mut priorities = [0; InterruptSource::NUM_SOURCES];
+    for (source, priority) in priorities.iter_mut().enumerate() {
+        *priority = interrupt_priority(interp, source);
+    }
+
+    InterruptControllerState {
+        enabled: interp.get_special_reg::<ICR>().interrupts_enabled(),
+        pending: (*interp.get_special_reg::<IPNR0>() as u32) | ((*interp.get_special_reg::<IPNR1>() as u32) << 16),
+        masked: interrupt_mask(interp),
+        priorities,
+    }
+}
@@ -1,11 +1,16 @@
 //! TODO!
 
-use crate::interp::{InstructionInterpreter, InstructionInterpreterPeripheralAccess, MachineState};
-use crate::mem_mapped::{MemMapped, KBDR};
+use crate::interp::{InstructionInterpreter, InstructionInterpreterPeripheralAccess, MachineState, FaultHandling, UnbalancedReturn};
+use crate::mem_mapped::{interrupt_controller_state, MemMapped, KBDR};
+#[cfg(feature = "std")]
+use crate::block::PageSet;
+#[cfg(feature = "std")]
+use crate::init_check::InitTracker;
+use crate::stack_check::StackChecker;
 
 use lc3_isa::{Addr, Reg, Word};
-use lc3_traits::control::{Control, Event, State, UnifiedRange, Idx, ProcessorMode};
-use lc3_traits::control::control::{MAX_BREAKPOINTS, MAX_MEMORY_WATCHPOINTS, MAX_CALL_STACK_DEPTH};
+use lc3_traits::control::{Control, Event, State, UnifiedRange, Idx, CallStackPage, InterruptControllerState, StackViolation, UninitializedRead};
+use lc3_traits::control::control::{MAX_BREAKPOINTS, MAX_MEMORY_WATCHPOINTS};
 use lc3_traits::control::metadata::{Identifier, ProgramMetadata, DeviceInfo};
 use lc3_traits::control::load::{
     PageIndex, PageWriteStart, StartPageWriteError, PageChunkError,
@@ -19,7 +24,7 @@ use lc3_traits::error::Error;
 use lc3_traits::peripherals::adc::{Adc, AdcPinArr, AdcReadError, AdcState};
 use lc3_traits::peripherals::clock::Clock;
 use lc3_traits::peripherals::gpio::{Gpio, GpioPinArr, GpioReadError, GpioState};
-use lc3_traits::peripherals::pwm::{Pwm, PwmPinArr, PwmState};
+use lc3_traits::peripherals::pwm::{Pwm, PwmConfig, PwmPinArr, PwmState};
 use lc3_traits::peripherals::timers::{Timers, TimerArr, TimerMode, TimerState};
 use lc3_traits::peripherals::Peripherals;
 
@@ -76,6 +81,19 @@ where
     state: State,
     shared_state: Option<&'ss S>,
     load_api_state: LoadApiState,
+    stack_checker: Option<StackChecker>,
+    // The violation (and the PC of the offending instruction) from the last
+    // step, if any.
+    stack_violation: Option<(Addr, StackViolation)>,
+    // Boxed since it's big (a couple of bits for every word of memory).
+    #[cfg(feature = "std")]
+    init_tracker: Option<Box<InitTracker>>,
+    // Pages committed through the load API; trackers are seeded with these
+    // when they're set.
+    #[cfg(feature = "std")]
+    committed_pages: PageSet,
+    // Same as above, for uninitialized reads.
+    uninitialized_read: Option<(Addr, UninitializedRead)>,
     _i: PhantomData<&'int ()>,
 }
 
@@ -104,6 +122,13 @@ where
             state: State::Paused,
             shared_state: None,
             load_api_state: LoadApiState::default(),
+            stack_checker: None,
+            stack_violation: None,
+            #[cfg(feature = "std")]
+            init_tracker: None,
+            #[cfg(feature = "std")]
+            committed_pages: PageSet::new(),
+            uninitialized_read: None,
             _i: PhantomData,
         }
     }
@@ -118,6 +143,222 @@ where
     pub fn set_shared_state(&mut self, state: &'s S) {
         self.shared_state = Some(state);
     }
+
+    /// Checks the stacks after every instruction, stopping the simulator with
+    /// an [`Event::StackViolation`] when the checker finds a problem. `None`
+    /// turns checking off.
+    ///
+    /// Note that running with a checker means the interpreter is always
+    /// stepped one instruction at a time.
+    pub fn set_stack_checker(&mut self, checker: Option<StackChecker>) {
+        self.stack_checker = checker;
+        self.stack_violation = None;
+    }
+
+    pub fn get_stack_checker(&self) -> Option<&StackChecker> {
+        self.stack_checker.as_ref()
+    }
+
+    /// Tracks which registers and memory locations have been written,
+    /// stopping the simulator with an [`Event::UninitializedRead`] when an
+    /// instruction uses one that hasn't been. `None` turns tracking off.
+    ///
+    /// As with [`set_stack_checker`](Self::set_stack_checker), this means the
+    /// interpreter is always stepped one instruction at a time.
+    ///
+    /// The tracker is seeded with what's already been loaded: system space
+    /// (where the OS lives) and any pages committed through the load API.
+    #[cfg(feature = "std")]
+    #[cfg_attr(all(docs, not(doctest)), doc(cfg(feature = "std")))]
+    pub fn set_init_tracker(&mut self, tracker: Option<InitTracker>) {
+        let committed_pages = &self.committed_pages;
+        self.init_tracker = tracker.map(|mut tracker| {
+            tracker.seed(committed_pages);
+            Box::new(tracker)
+        });
+        self.uninitialized_read = None;
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg_attr(all(docs, not(doctest)), doc(cfg(feature = "std")))]
+    pub fn get_init_tracker(&self) -> Option<&InitTracker> {
+        self.init_tracker.as_deref()
+    }
+
+    #[cfg(feature = "std")]
+    fn tracking_inits(&self) -> bool {
+        self.init_tracker.is_some()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn tracking_inits(&self) -> bool {
+        false
+    }
+
+    // Works out what (if any) event the machine's state after a step produces
+    // and updates our state accordingly.
+    fn handle_machine_state(&mut self, current_machine_state: MachineState) -> Option<Event> {
+        use State::*;
+
+        // Only the step that made an unbalanced return can report it:
+        let unbalanced_return = self.interp.take_unbalanced_return();
+        let stack_violation = self.stack_violation.take();
+        let uninitialized_read = self.uninitialized_read.take();
+
+        let (new_state, event) = (|m: MachineState| match m {
+            MachineState::Halted => {
+                // If we're halted, we can't have hit a breakpoint or a watchpoint,
+                // but we might have overflowed our stack or run into some other error.
+                let event = if let Some(err) = self.get_error() {
+                    Event::Error { err }
+                } else {
+                    Event::Halted
+                };
+
+                (Halted, Some(event))
+            }
+            MachineState::Running => {
+                // Check for breakpoints:
+                // (Note that if a breakpoint and a watchpoint occur at the same time,
+                // the breakpoint takes precedence)
+                if self.num_set_breakpoints > 0 {
+                    let pc = self.get_pc();
+                    if let Some(addr) = self.breakpoints.iter().filter_map(|b| *b).filter(|a| *a == pc).next() {
+                        return (Paused, Some(Event::Breakpoint { addr }));
+                    }
+                }
+
+                // And watchpoints:
+                if self.num_set_watchpoints > 0 {
+                    for i in 0..self.watchpoints.len() {
+                        if let Some((addr, old_val)) = self.watchpoints[i] {
+                            let data = self.read_word(addr);
+
+                            if data != old_val {
+                                self.watchpoints[i] = Some((addr, data));
+                                return (Paused, Some(Event::MemoryWatch { addr, data }));
+                            }
+                        }
+                    }
+
+                    // if let Some((addr, data)) = self.watchpoints.iter().filter_map(|w| *w).filter(|(addr, val)| {
+                    //     let current_val = self.read_word(*addr);
+                    //     if current_val != *val {
+                    //         *val = current_val;
+                    //         true
+                    //     } else { false }
+                    // }).next() {
+                    //     return (Paused, Some(Event::MemoryWatch { addr, data }));
+                    // }
+                }
+
+                // And errors
+                match self.get_error() {
+                    Some(err) => {
+                        return (Paused, Some(Event::Error { err }));
+                    },
+                    None => {},
+                }
+
+                // And unbalanced returns
+                if let Some(UnbalancedReturn { to, expected }) = unbalanced_return {
+                    return (Paused, Some(Event::UnbalancedReturn { to, expected }));
+                }
+
+                // And stack violations
+                if let Some((pc, violation)) = stack_violation {
+                    return (Paused, Some(Event::StackViolation { pc, violation }));
+                }
+
+                // And uninitialized reads
+                if let Some((pc, read)) = uninitialized_read {
+                    return (Paused, Some(Event::UninitializedRead { pc, read }));
+                }
+
+                // And the depth breakpoint
+                match &self.depth_breakpoint_range {
+                    Some(range) => {
+                        let cur_depth = self.interp.get_call_stack_depth();
+                        if range.contains(&cur_depth) {
+                            return (Paused, Some(Event::DepthReached{current_depth: cur_depth}));
+                        }
+                    },
+                    None => {},
+                }
+
+                // If we didn't hit a breakpoint/watchpoint, the state doesn't change.
+                // If we were running, we're still running.
+                // If we were halted before, we're still halted (handled above).
+                // If we were paused, we're still paused.
+                (self.get_state(), None)
+            }
+        })(current_machine_state);
+
+        let current_state = self.get_state();
+        match (current_state, new_state, event) {
+            // (RunningUntilEvent, RunningUntilEvent, Some(e)) => unreachable!(),
+            // (RunningUntilEvent, RunningUntilEvent, None) => regular,
+            // (RunningUntilEvent, Paused, Some(e)) => resolve,
+            // (RunningUntilEvent, Paused, None) => unreachable!(), // can't stop running without an event
+            // (RunningUntilEvent, Halted, Some(e)) => resolve,
+            // (RunningUntilEvent, Halted, None) => unreachable!(), // can't stop running without an event
+
+            // (Paused, RunningUntilEvent, Some(e)) => unreachable!(),    // can't start running until an event in this function
+            // (Paused, RunningUntilEvent, None) => unreachable!(),       // can't start running until an event in this function
+            // (Paused, Paused, Some(e)) => regular,
+            // (Paused, Paused, None) => regular,
+            // (Paused, Halted, Some(e)) => regular,
+            // (Paused, Halted, None) => unreachable!(), // this is fine but will never happen as impl'ed above
+
+            // (Halted, RunningUntilEvent, Some(e)) => unreachable!(),    // can't start running until an event in this function
+            // (Halted, RunningUntilEvent, None) => unreachable!(),       // can't start running until an event in this function
+            // (Halted, Paused, Some(e)) => unreachable!(),            // can't transition out of halted in this function
+            // (Halted, Paused, None) => unreachable!(),               // can't transition out of halted in this function
+            // (Halted, Halted, Some(e)) => regular,
+            // (Halted, Halted, None) => unreachable!(), // this is fine but will never happen as impl'ed above
+
+            (RunningUntilEvent, Paused, Some(e)) |
+            (RunningUntilEvent, Halted, Some(e @ Event::Error { .. })) |
+            (RunningUntilEvent, Halted, Some(e @ Event::Halted)) => {
+                // Unset the depth breakpoint upon any event
+                self.unset_depth_condition();
+                // println!("resolving the device future");
+                self.shared_state.as_ref().expect("unreachable; must have a shared state to call a run_until_event and therefore be in `RunningUntilEvent`").resolve_all(e).unwrap();
+                self.state = new_state;
+                Some(e)
+            },
+
+            (RunningUntilEvent, RunningUntilEvent, e @ None) |
+            (Paused, Paused, e @ Some(_))                    |
+            (Paused, Paused, e @ None)                       |
+            (Paused, Halted, e @ Some(Event::Halted))        |
+            (Paused, Halted, e @ Some(Event::Error { .. }))  |
+            (Halted, Halted, e @ Some(Event::Halted)) => {
+                self.state = new_state;
+                e
+            }
+
+            (RunningUntilEvent, Halted, Some(_)) |
+            (Paused, Halted, Some(_))            |
+            (Halted, Halted, Some(_)) => unreachable!("Transitions to the `Halted` state must only produce halted events or error events."),
+
+            (RunningUntilEvent, RunningUntilEvent, Some(_)) => unreachable!("Can't yield an event and not finish a `RunningUntilEvent`."),
+
+            (RunningUntilEvent, Paused, None) |
+            (RunningUntilEvent, Halted, None) => unreachable!("Can't finish a `RunningUntilEvent` without an event."),
+
+            (Paused, RunningUntilEvent, Some(_)) |
+            (Paused, RunningUntilEvent, None)    |
+            (Halted, RunningUntilEvent, Some(_)) |
+            (Halted, RunningUntilEvent, None) => unreachable!("Can't start a 'run until event' in this function."),
+
+            (Halted, Paused, Some(_)) |
+            (Halted, Paused, None) => unreachable!("Can't get out of the `Halted` state in this function."),
+
+            (Paused, Halted, None) |
+            (Halted, Halted, None) => unreachable!("Always produce an event when the next state is `Halted`."),
+        }
+    }
 }
 
 // impl<'a, I: InstructionInterpreterPeripheralAccess<'a>> Simulator<'a, I>
@@ -148,6 +389,11 @@ where
     }
 
     fn set_register(&mut self, reg: Reg, data: Word) {
+        #[cfg(feature = "std")]
+        if let Some(tracker) = self.init_tracker.as_mut() {
+            tracker.mark_register(reg);
+        }
+
         self.interp.set_register(reg, data)
     }
 
@@ -162,6 +408,11 @@ where
     }
 
     fn write_word(&mut self, addr: Addr, word: Word) {
+        #[cfg(feature = "std")]
+        if let Some(tracker) = self.init_tracker.as_mut() {
+            tracker.mark_memory(addr);
+        }
+
         self.interp.set_word_unchecked(addr, word)
     }
 
@@ -248,6 +499,14 @@ where
                     }
 
                     self.interp.commit_page(page_idx, &page);
+                    #[cfg(feature = "std")]
+                    {
+                        self.committed_pages.insert(page_idx);
+                        if let Some(tracker) = self.init_tracker.as_mut() {
+                            tracker.commit_page(page_idx);
+                        }
+                    }
+
                     Ok(())
                 })();
 
@@ -354,8 +613,8 @@ where
         Ok(self.interp.get_call_stack_depth())
     }
 
-    fn get_call_stack(&self) -> [Option<(Addr, ProcessorMode)>; MAX_CALL_STACK_DEPTH] {
-        self.interp.get_call_stack()
+    fn get_call_stack(&self, page: u16) -> CallStackPage {
+        self.interp.get_call_stack().page(page)
     }
 
     fn run_until_event(&mut self) -> <Self as Control>::EventFuture {
@@ -385,15 +644,28 @@ where
 
         if let RunningUntilEvent = self.get_state() {
             // TODO: Some configurable flag for events
-//            // TODO: does this weird micro optimization help?
-//            if self.num_set_watchpoints == 0 && self.num_set_breakpoints == 0 {
-//                for _ in 0..STEPS_IN_A_TICK {
-//                    // this is safe since overshooting (calling step when we have NOPs) is fine
-//                    self.step();
-//                }
-//
-//                return STEPS_IN_A_TICK;
-//            }
+
+            // If there's nothing to check between instructions, let the
+            // interpreter run as many instructions at once as it likes:
+            if self.num_set_breakpoints == 0
+                && self.num_set_watchpoints == 0
+                && self.depth_breakpoint_range.is_none()
+                && self.stack_checker.is_none()
+                && !self.tracking_inits()
+            {
+                let mut steps = 0;
+                while steps < STEPS_IN_A_TICK {
+                    let (num_steps, machine_state) =
+                        self.interp.step_many(STEPS_IN_A_TICK - steps);
+                    steps += num_steps;
+
+                    if let Some(_e) = self.handle_machine_state(machine_state) {
+                        return STEPS_IN_A_TICK; // this is not accurate but this is allowed
+                    }
+                }
+
+                return STEPS_IN_A_TICK;
+            }
 
             for _ in 0..STEPS_IN_A_TICK {
                 if let Some(_e) = self.step() {
@@ -407,146 +679,22 @@ where
     }
 
     fn step(&mut self) -> Option<Event> {
-        use State::*;
-        let current_machine_state = self.interp.step();
-        let (new_state, event) = (|m: MachineState| match m {
-            MachineState::Halted => {
-                // If we're halted, we can't have hit a breakpoint or a watchpoint,
-                // but we might have overflowed our stack or run into some other error.
-                let event = if let Some(err) = self.get_error() {
-                    Event::Error { err }
-                } else {
-                    Event::Halted
-                };
-
-                (Halted, Some(event))
-            }
-            MachineState::Running => {
-                // Check for breakpoints:
-                // (Note that if a breakpoint and a watchpoint occur at the same time,
-                // the breakpoint takes precedence)
-                if self.num_set_breakpoints > 0 {
-                    let pc = self.get_pc();
-                    if let Some(addr) = self.breakpoints.iter().filter_map(|b| *b).filter(|a| *a == pc).next() {
-                        return (Paused, Some(Event::Breakpoint { addr }));
-                    }
-                }
+        let pre_step = self.stack_checker.as_ref().map(|c| c.pre_step(&self.interp));
+        #[cfg(feature = "std")]
+        let pre_step_init = self.init_tracker.as_ref().map(|t| t.pre_step(&self.interp));
 
-                // And watchpoints:
-                if self.num_set_watchpoints > 0 {
-                    for i in 0..self.watchpoints.len() {
-                        if let Some((addr, old_val)) = self.watchpoints[i] {
-                            let data = self.read_word(addr);
-
-                            if data != old_val {
-                                self.watchpoints[i] = Some((addr, data));
-                                return (Paused, Some(Event::MemoryWatch { addr, data }));
-                            }
-                        }
-                    }
-
-                    // if let Some((addr, data)) = self.watchpoints.iter().filter_map(|w| *w).filter(|(addr, val)| {
-                    //     let current_val = self.read_word(*addr);
-                    //     if current_val != *val {
-                    //         *val = current_val;
-                    //         true
-                    //     } else { false }
-                    // }).next() {
-                    //     return (Paused, Some(Event::MemoryWatch { addr, data }));
-                    // }
-                }
-
-                // And errors
-                match self.get_error() {
-                    Some(err) => {
-                        return (Paused, Some(Event::Error { err }));
-                    },
-                    None => {},
-                }
-
-                // And the depth breakpoint
-                match &self.depth_breakpoint_range {
-                    Some(range) => {
-                        let cur_depth = self.interp.get_call_stack_depth();
-                        if range.contains(&cur_depth) {
-                            return (Paused, Some(Event::DepthReached{current_depth: cur_depth}));
-                        }
-                    },
-                    None => {},
-                }
-
-                // If we didn't hit a breakpoint/watchpoint, the state doesn't change.
-                // If we were running, we're still running.
-                // If we were halted before, we're still halted (handled above).
-                // If we were paused, we're still paused.
-                (self.get_state(), None)
-            }
-        })(current_machine_state);
-
-        let current_state = self.get_state();
-        match (current_state, new_state, event) {
-            // (RunningUntilEvent, RunningUntilEvent, Some(e)) => unreachable!(),
-            // (RunningUntilEvent, RunningUntilEvent, None) => regular,
-            // (RunningUntilEvent, Paused, Some(e)) => resolve,
-            // (RunningUntilEvent, Paused, None) => unreachable!(), // can't stop running without an event
-            // (RunningUntilEvent, Halted, Some(e)) => resolve,
-            // (RunningUntilEvent, Halted, None) => unreachable!(), // can't stop running without an event
-
-            // (Paused, RunningUntilEvent, Some(e)) => unreachable!(),    // can't start running until an event in this function
-            // (Paused, RunningUntilEvent, None) => unreachable!(),       // can't start running until an event in this function
-            // (Paused, Paused, Some(e)) => regular,
-            // (Paused, Paused, None) => regular,
-            // (Paused, Halted, Some(e)) => regular,
-            // (Paused, Halted, None) => unreachable!(), // this is fine but will never happen as impl'ed above
-
-            // (Halted, RunningUntilEvent, Some(e)) => unreachable!(),    // can't start running until an event in this function
-            // (Halted, RunningUntilEvent, None) => unreachable!(),       // can't start running until an event in this function
-            // (Halted, Paused, Some(e)) => unreachable!(),            // can't transition out of halted in this function
-            // (Halted, Paused, None) => unreachable!(),               // can't transition out of halted in this function
-            // (Halted, Halted, Some(e)) => regular,
-            // (Halted, Halted, None) => unreachable!(), // this is fine but will never happen as impl'ed above
-
-            (RunningUntilEvent, Paused, Some(e)) |
-            (RunningUntilEvent, Halted, Some(e @ Event::Error { .. })) |
-            (RunningUntilEvent, Halted, Some(e @ Event::Halted)) => {
-                // Unset the depth breakpoint upon any event
-                self.unset_depth_condition();
-                // println!("resolving the device future");
-                self.shared_state.as_ref().expect("unreachable; must have a shared state to call a run_until_event and therefore be in `RunningUntilEvent`").resolve_all(e).unwrap();
-                self.state = new_state;
-                Some(e)
-            },
-
-            (RunningUntilEvent, RunningUntilEvent, e @ None) |
-            (Paused, Paused, e @ Some(_))                    |
-            (Paused, Paused, e @ None)                       |
-            (Paused, Halted, e @ Some(Event::Halted))        |
-            (Paused, Halted, e @ Some(Event::Error { .. }))  |
-            (Halted, Halted, e @ Some(Event::Halted)) => {
-                self.state = new_state;
-                e
-            }
-
-            (RunningUntilEvent, Halted, Some(_)) |
-            (Paused, Halted, Some(_))            |
-            (Halted, Halted, Some(_)) => unreachable!("Transitions to the `Halted` state must only produce halted events or error events."),
-
-            (RunningUntilEvent, RunningUntilEvent, Some(_)) => unreachable!("Can't yield an event and not finish a `RunningUntilEvent`."),
-
-            (RunningUntilEvent, Paused, None) |
-            (RunningUntilEvent, Halted, None) => unreachable!("Can't finish a `RunningUntilEvent` without an event."),
-
-            (Paused, RunningUntilEvent, Some(_)) |
-            (Paused, RunningUntilEvent, None)    |
-            (Halted, RunningUntilEvent, Some(_)) |
-            (Halted, RunningUntilEvent, None) => unreachable!("Can't start a 'run until event' in this function."),
+        let current_machine_state = self.interp.step();
 
-            (Halted, Paused, Some(_)) |
-            (Halted, Paused, None) => unreachable!("Can't get out of the `Halted` state in this function."),
+        if let (Some(checker), Some(pre)) = (self.stack_checker.as_mut(), pre_step) {
+            self.stack_violation = checker.post_step(&self.interp, pre).map(|v| (pre.pc, v));
+        }
 
-            (Paused, Halted, None) |
-            (Halted, Halted, None) => unreachable!("Always produce an event when the next state is `Halted`."),
+        #[cfg(feature = "std")]
+        if let (Some(tracker), Some(pre)) = (self.init_tracker.as_mut(), pre_step_init) {
+            self.uninitialized_read = tracker.post_step(&self.interp, pre).map(|r| (pre.pc, r));
         }
+
+        self.handle_machine_state(current_machine_state)
     }
 
     fn pause(&mut self) {
@@ -583,6 +731,17 @@ where
         InstructionInterpreter::reset(&mut self.interp);
         self.state = State::Paused;
 
+        if let Some(checker) = self.stack_checker.as_mut() {
+            checker.reset();
+        }
+        self.stack_violation = None;
+
+        #[cfg(feature = "std")]
+        if let Some(tracker) = self.init_tracker.as_mut() {
+            tracker.reset(&mut self.interp);
+        }
+        self.uninitialized_read = None;
+
         // For now, we won't force all futures to have resolved on a reset.
         // We're still calling reset here (currently a no-op) because eventually
         // this should advance the batch counter (though that may happen
@@ -596,6 +755,14 @@ where
         self.interp.get_error()
     }
 
+    fn set_fault_handling(&mut self, handling: FaultHandling) {
+        self.interp.set_fault_handling(handling)
+    }
+
+    fn get_fault_handling(&self) -> FaultHandling {
+        self.interp.get_fault_handling()
+    }
+
     fn get_gpio_states(&self) -> GpioPinArr<GpioState> {
         Gpio::get_states(self.interp.get_peripherals())
     }
@@ -624,14 +791,18 @@ where
         Pwm::get_states(self.interp.get_peripherals())
     }
 
-    fn get_pwm_config(&self) -> PwmPinArr<u8> {
-        Pwm::get_duty_cycles(self.interp.get_peripherals())
+    fn get_pwm_config(&self) -> PwmPinArr<PwmConfig> {
+        Pwm::get_configs(self.interp.get_peripherals())
     }
 
     fn get_clock(&self) -> Word {
         Clock::get_milliseconds(self.interp.get_peripherals())
     }
 
+    fn get_interrupt_controller_state(&self) -> InterruptControllerState {
+        interrupt_controller_state(&self.interp)
+    }
+
     fn get_device_info(&self) -> DeviceInfo {
         DeviceInfo::new(
             self.id(),
@@ -0,0 +1,272 @@
+//! An optional stack discipline checker for the [`Simulator`].
+//!
+//! A [`StackChecker`] looks at the machine before and after every instruction
+//! the [`Simulator`] steps and reports the first [`StackViolation`] it finds:
+//!   - either stack pointer moving outside of its stack's [bounds](StackBounds)
+//!     (the stack that isn't in use has its pointer in [`BSP`])
+//!   - a store through R6 to an address below the current stack's limit
+//!   - a subroutine (`JSR`/`JSRR`) returning without restoring one of the
+//!     registers the [`CallingConvention`] says it has to preserve
+//!
+//! Registers are only checked for the outermost [`MAX_CALL_STACK_DEPTH`]
+//! subroutine calls.
+//!
+//! [`Simulator`]: crate::sim::Simulator
+//! [`BSP`]: crate::mem_mapped::BSP
+
+use crate::interp::InstructionInterpreterPeripheralAccess;
+use crate::lc3b::is_lc3b;
+use crate::mem_mapped::{BSP, PSR};
+
+use lc3_isa::{Addr, Instruction, Reg, Word, MEM_MAPPED_START_ADDR};
+use lc3_traits::control::control::MAX_CALL_STACK_DEPTH;
+use lc3_traits::control::{FrameKind, ProcessorMode, StackViolation};
+use lc3_traits::peripherals::Peripherals;
+
+use core::convert::TryFrom;
+use core::ops::Deref;
+
+/// The extent of a stack. Stacks grow down so a stack pointer is valid when
+/// `limit <= sp <= base` (`sp == base` is an empty stack).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct StackBounds {
+    pub base: Addr,
+    pub limit: Addr,
+}
+
+impl StackBounds {
+    pub fn contains(&self, sp: Word) -> bool {
+        self.limit <= sp && sp <= self.base
+    }
+}
+
+/// Which registers a subroutine has to leave as it found them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CallingConvention {
+    callee_saved: [bool; Reg::NUM_REGS],
+}
+
+impl CallingConvention {
+    pub fn new(callee_saved: &[Reg]) -> Self {
+        let mut conv = Self { callee_saved: [false; Reg::NUM_REGS] };
+        callee_saved.iter().for_each(|r| conv.callee_saved[idx(*r)] = true);
+
+        conv
+    }
+
+    pub fn is_callee_saved(&self, reg: Reg) -> bool {
+        self.callee_saved[idx(reg)]
+    }
+}
+
+impl Default for CallingConvention {
+    /// R1 through R6; R0 holds return values and R7 the return address.
+    fn default() -> Self {
+        use Reg::*;
+        Self::new(&[R1, R2, R3, R4, R5, R6])
+    }
+}
+
+fn idx(reg: Reg) -> usize {
+    Into::<u8>::into(reg) as usize
+}
+
+// What the checker needs to remember about the machine from before a step.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct PreStep {
+    pub(crate) pc: Addr,
+    mode: ProcessorMode,
+    user_sp: Word,
+    supervisor_sp: Word,
+    depth: u64,
+    store_addr: Option<Addr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StackChecker {
+    user_stack: Option<StackBounds>,
+    supervisor_stack: Option<StackBounds>,
+    calling_convention: Option<CallingConvention>,
+    // Registers on entry to each (recorded) subroutine call, by depth.
+    saved_regs: [Option<[Word; Reg::NUM_REGS]>; MAX_CALL_STACK_DEPTH],
+}
+
+impl Default for StackChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StackChecker {
+    /// A checker that doesn't check anything; use the `with_*` functions to
+    /// turn on checks.
+    pub fn new() -> Self {
+        Self {
+            user_stack: None,
+            supervisor_stack: None,
+            calling_convention: None,
+            saved_regs: [None; MAX_CALL_STACK_DEPTH],
+        }
+    }
+
+    pub fn with_user_stack(mut self, bounds: StackBounds) -> Self {
+        self.user_stack = Some(bounds);
+        self
+    }
+
+    /// The OS's supervisor stack is described by `lc3_os::OS_STACK_BOUNDS`.
+    pub fn with_supervisor_stack(mut self, bounds: StackBounds) -> Self {
+        self.supervisor_stack = Some(bounds);
+        self
+    }
+
+    pub fn with_calling_convention(mut self, conv: CallingConvention) -> Self {
+        self.calling_convention = Some(conv);
+        self
+    }
+
+    pub fn get_user_stack(&self) -> Option<StackBounds> { self.user_stack }
+    pub fn get_supervisor_stack(&self) -> Option<StackBounds> { self.supervisor_stack }
+    pub fn get_calling_convention(&self) -> Option<CallingConvention> { self.calling_convention }
+
+    /// Forgets the registers saved for the subroutine calls in progress.
+    pub fn reset(&mut self) {
+        self.saved_regs = [None; MAX_CALL_STACK_DEPTH];
+    }
+
+    fn bounds(&self, stack: ProcessorMode) -> Option<StackBounds> {
+        match stack {
+            ProcessorMode::User => self.user_stack,
+            ProcessorMode::Supervisor => self.supervisor_stack,
+        }
+    }
+
+    pub(crate) fn pre_step<'a, I: InstructionInterpreterPeripheralAccess<'a>>(&self, interp: &I) -> PreStep
+    where
+        <I as Deref>::Target: Peripherals<'a>,
+    {
+        let pc = interp.get_pc();
+        let (mode, user_sp, supervisor_sp) = stack_pointers(interp);
+
+        // Only stores through R6 are checked. We can't pick them out when the
+        // interpreter runs the LC-3b's instruction set; we also steer clear of
+        // the memory mapped region since reads there can have side effects.
+        let store_addr = if !is_lc3b::<I>() && pc < MEM_MAPPED_START_ADDR {
+            match Instruction::try_from(interp.get_word_unchecked(pc)) {
+                Ok(Instruction::Str { base: Reg::R6, offset6, .. }) => {
+                    Some(interp[Reg::R6].wrapping_add(offset6 as Word))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        PreStep {
+            pc,
+            mode,
+            user_sp,
+            supervisor_sp,
+            depth: interp.get_call_stack_depth(),
+            store_addr,
+        }
+    }
+
+    pub(crate) fn post_step<'a, I: InstructionInterpreterPeripheralAccess<'a>>(&mut self, interp: &I, pre: PreStep) -> Option<StackViolation>
+    where
+        <I as Deref>::Target: Peripherals<'a>,
+    {
+        use StackViolation::*;
+
+        let (_, user_sp, supervisor_sp) = stack_pointers(interp);
+        let call_stack = interp.get_call_stack();
+        let depth = call_stack.depth();
+        let top = depth.checked_sub(1).and_then(|d| call_stack.frame(d as usize));
+
+        // An interrupt or a fault means the instruction at the old PC didn't
+        // (fully) run:
+        let preempted = call_stack.preempted(pre.depth, pre.pc);
+
+        let mut violation = None;
+
+        if let (Some(addr), false) = (pre.store_addr, preempted) {
+            if let Some(bounds) = self.bounds(pre.mode) {
+                if addr < bounds.limit {
+                    violation = Some(WriteBelowLimit { stack: pre.mode, addr });
+                }
+            }
+        }
+
+        let stacks = [
+            (ProcessorMode::User, pre.user_sp, user_sp),
+            (ProcessorMode::Supervisor, pre.supervisor_sp, supervisor_sp),
+        ];
+
+        for (stack, old, sp) in stacks.iter().copied() {
+            if violation.is_some() { break; }
+            if old == sp { continue; }
+
+            if let Some(bounds) = self.bounds(stack) {
+                if sp < bounds.limit {
+                    violation = Some(Overflow { stack, sp });
+                } else if sp > bounds.base {
+                    violation = Some(Underflow { stack, sp });
+                }
+            }
+        }
+
+        if let Some(conv) = self.calling_convention {
+            let regs = {
+                let mut regs = [0; Reg::NUM_REGS];
+                Reg::REGS.iter().for_each(|r| regs[idx(*r)] = interp[*r]);
+                regs
+            };
+
+            if depth > pre.depth {
+                if let Some(slot) = self.saved_regs.get_mut(depth as usize - 1) {
+                    *slot = match top.map(|f| f.kind) {
+                        Some(FrameKind::Jsr) | Some(FrameKind::Jsrr) => Some(regs),
+                        _ => None,
+                    };
+                }
+            } else if depth < pre.depth {
+                // If this return skipped over frames, the frame to check is
+                // the outermost one that was returned from:
+                let saved = self.saved_regs.get(depth as usize).copied().flatten();
+
+                let end = (pre.depth as usize).min(MAX_CALL_STACK_DEPTH);
+                if let Some(slots) = self.saved_regs.get_mut(depth as usize..end) {
+                    slots.iter_mut().for_each(|s| *s = None);
+                }
+
+                if let (Some(saved), None) = (saved, violation) {
+                    violation = Reg::REGS.iter()
+                        .filter(|r| conv.is_callee_saved(**r))
+                        .find(|r| saved[idx(**r)] != regs[idx(**r)])
+                        .map(|r| CalleeSavedRegister {
+                            reg: *r,
+                            expected: saved[idx(*r)],
+                            found: regs[idx(*r)],
+                        });
+                }
+            }
+        }
+
+        violation
+    }
+}
+
+// The current mode and the user and supervisor stack pointers.
+fn stack_pointers<'a, I: InstructionInterpreterPeripheralAccess<'a>>(interp: &I) -> (ProcessorMode, Word, Word)
+where
+    <I as Deref>::Target: Peripherals<'a>,
+{
+    let r6 = interp[Reg::R6];
+    let bsp = *interp.get_special_reg::<BSP>();
+
+    if interp.get_special_reg::<PSR>().in_user_mode() {
+        (ProcessorMode::User, r6, bsp)
+    } else {
+        (ProcessorMode::Supervisor, bsp, r6)
+    }
+}
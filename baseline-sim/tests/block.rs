@@ -0,0 +1,183 @@
+extern crate lc3_test_infrastructure as lti;
+
+use lc3_baseline_sim::block::BlockInterpreter;
+use lc3_baseline_sim::interp::{FaultHandling, InstructionInterpreter, MachineState};
+use lc3_baseline_sim::sim::Simulator;
+use lc3_isa::{insn, program, Addr, Reg::{self, *}, Word, ADDR_SPACE_SIZE_IN_WORDS};
+use lc3_traits::control::rpc::device::RW_CLONE;
+use lc3_traits::control::rpc::SimpleEventFutureSharedState;
+use lc3_traits::control::{Control, Event, State};
+use lc3_traits::error::Error;
+use lti::{interp_with_memory, with_larger_stack, MemoryShim, PeripheralsShim, ShimInterp};
+
+use core::convert::TryInto;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+type Block<'a> = BlockInterpreter<'a, MemoryShim, PeripheralsShim<'a>>;
+
+// Sums 10 + 9 + ... + 1 into a table and calls a subroutine that it rewrites
+// after the first call; ends up spinning at `END`.
+fn sum_program() -> [(Word, bool); ADDR_SPACE_SIZE_IN_WORDS] {
+    program! {
+        .ORIG #0x3000;
+        AND R0, R0, #0;
+        ADD R1, R0, #10;
+        ADD R3, R0, #1;
+        LEA R2, @TABLE;
+        LD R4, @PATCH;
+        @LOOP ADD R0, R0, R1;
+        STR R0, R2, #0;
+        ADD R2, R2, #1;
+        JSR @STEP;
+        ST R4, @STEP;
+        ADD R1, R1, #-1;
+        BRp @LOOP;
+        @END BRnzp @END;
+        @STEP ADD R3, R3, R3;
+        RET;
+        @PATCH .FILL #0x16E1; // ADD R3, R3, #1
+        @TABLE .FILL #0;
+    }
+}
+
+const END_ADDR: Addr = 0x300C;
+const TABLE_ADDR: Addr = 0x3010;
+
+#[test]
+fn matches_the_interpreter() { with_larger_stack(None, || {
+    const STEPS: usize = 200;
+
+    let mut interp: ShimInterp = interp_with_memory(sum_program());
+    for _ in 0..STEPS {
+        interp.step();
+    }
+
+    let mut block: Block = interp_with_memory(sum_program());
+    let mut steps = 0;
+    while steps < STEPS {
+        steps += block.step_many(STEPS - steps).0;
+    }
+    assert_eq!(steps, STEPS);
+
+    assert_eq!(interp.get_pc(), END_ADDR);
+    assert_eq!(block.get_pc(), END_ADDR);
+
+    for r in 0..Reg::NUM_REGS {
+        let r: Reg = (r as u8).try_into().unwrap();
+        assert_eq!(interp[r], block[r], "{}", r);
+    }
+
+    for addr in 0x3000..(TABLE_ADDR + 10) {
+        assert_eq!(interp.get_word_unchecked(addr), block.get_word_unchecked(addr), "{:#06X}", addr);
+    }
+
+    assert_eq!((block[R0], block[R3]), (55, 2 + 9));
+    assert_eq!(block.get_word_unchecked(TABLE_ADDR + 9), 55);
+})}
+
+#[test]
+fn writes_to_code_drop_cached_blocks() { with_larger_stack(None, || {
+    // Writes from the program, including to the block that's running:
+    let mut block: Block = interp_with_memory(program! {
+        .ORIG #0x3000;
+        LD R1, @NEW;
+        ST R1, @NEXT;
+        @NEXT ADD R2, R2, #1;
+        @END BRnzp @END;
+        @NEW .FILL #0x14A2; // ADD R2, R2, #2
+    });
+
+    assert_eq!(block.step_many(3), (3, MachineState::Running));
+    assert_eq!(block[R2], 2);
+
+    // Writes from outside:
+    block.set_word_unchecked(0x3002, insn!(ADD R2, R2, #5).into());
+    block.set_pc(0x3002);
+    block.step_many(1);
+    assert_eq!(block[R2], 7);
+
+    // Resets (which restore the original program):
+    block.reset();
+    block.set_pc(0x3002);
+    block.step_many(1);
+    assert_eq!(block[R2], 1);
+})}
+
+#[test]
+fn stops_on_errors() { with_larger_stack(None, || {
+    let mut block: Block = interp_with_memory(program! {
+        .ORIG #0x3000;
+        ADD R0, R0, #1;
+        .FILL #0xD000;
+        ADD R0, R0, #1;
+    });
+    block.set_fault_handling(FaultHandling::Stop);
+
+    assert_eq!(block.step_many(10).0, 2);
+    assert_eq!(block.get_error(), Some(Error::IllegalOpcode { pc: 0x3001, insn: 0xD000 }));
+    assert_eq!((block.get_pc(), block[R0]), (0x3001, 1));
+})}
+
+fn run<C: Control>(sim: &mut C) -> Event
+where
+    C::EventFuture: Unpin,
+{
+    let mut fut = sim.run_until_event();
+    let waker = unsafe { Waker::from_raw(RW_CLONE(&())) };
+
+    loop {
+        if let Poll::Ready(event) = Pin::new(&mut fut).poll(&mut Context::from_waker(&waker)) {
+            return event;
+        }
+
+        sim.tick();
+    }
+}
+
+#[test]
+fn simulator_checks_breakpoints_and_watchpoints() { with_larger_stack(None, || {
+    let state = SimpleEventFutureSharedState::new();
+    let mut sim: Simulator<Block, _> = Simulator::new_with_state(interp_with_memory(sum_program()), &state);
+
+    // Both in the middle of the loop's block:
+    let _ = sim.set_breakpoint(0x3007).unwrap();
+    let _ = sim.set_memory_watchpoint(TABLE_ADDR + 1).unwrap();
+
+    assert_eq!(run(&mut sim), Event::Breakpoint { addr: 0x3007 });
+    assert_eq!(sim.get_register(R0), 10);
+
+    let _ = sim.unset_breakpoint(0).unwrap();
+    assert_eq!(run(&mut sim), Event::MemoryWatch { addr: TABLE_ADDR + 1, data: 19 });
+    assert_eq!((sim.get_pc(), sim.get_register(R0)), (0x3007, 10 + 9));
+
+    // With nothing to check the simulator can run whole blocks at a time:
+    let _ = sim.unset_memory_watchpoint(0).unwrap();
+    let mut fut = sim.run_until_event();
+    for _ in 0..10 {
+        sim.tick();
+    }
+    assert_eq!(sim.get_state(), State::RunningUntilEvent);
+    assert_eq!((sim.get_pc(), sim.get_register(R0)), (END_ADDR, 55));
+
+    sim.pause();
+    let waker = unsafe { Waker::from_raw(RW_CLONE(&())) };
+    assert_eq!(Pin::new(&mut fut).poll(&mut Context::from_waker(&waker)), Poll::Ready(Event::Interrupted));
+})}
+
+#[test]
+fn simulator_reports_unbalanced_returns_where_they_happen() { with_larger_stack(None, || {
+    let state = SimpleEventFutureSharedState::new();
+    let mut sim: Simulator<Block, _> = Simulator::new_with_state(interp_with_memory(program! {
+        .ORIG #0x3000;
+        JSR #1;
+        ADD R0, R0, #1;
+        ADD R7, R7, #1; // Returns to itself (over and over):
+        RET;
+    }), &state);
+
+    // Nothing to check between instructions so this goes through `step_many`:
+    assert_eq!(run(&mut sim), Event::UnbalancedReturn { to: 0x3002, expected: 0x3001 });
+    assert_eq!((sim.get_pc(), sim.get_register(R7)), (0x3002, 0x3002));
+})}
@@ -0,0 +1,160 @@
+extern crate lc3_test_infrastructure as lti;
+
+use lc3_baseline_sim::interp::{InstructionInterpreter, InstructionInterpreterPeripheralAccess, UnbalancedReturn};
+use lc3_baseline_sim::sim::Simulator;
+use lc3_baseline_sim::{BSP, G0_INT_VEC, PSR};
+use lc3_isa::{insn, Addr, Reg::*, Word, INTERRUPT_VECTOR_TABLE_START_ADDR, TRAP_VECTOR_TABLE_START_ADDR};
+use lc3_traits::control::{CallStackFrame, Control, Event, FrameKind, ProcessorMode, CALL_STACK_PAGE_SIZE};
+use lc3_traits::peripherals::gpio::{Gpio, GpioPin, GpioState, GpioTrigger};
+use lti::{interp_steps, interp_with_program, with_larger_stack, ShimInterp};
+
+// An interpreter in user mode about to run `program` (at 0x3000).
+fn with_program<'a>(program: &[Word]) -> ShimInterp<'a> {
+    let mut interp = interp_with_program(program);
+
+    interp[R6] = 0x4000;
+    interp.set_special_reg::<BSP>(0x2FF0);
+    interp.set_special_reg::<PSR>(0x8002);
+
+    interp
+}
+
+#[test]
+fn frames_record_calls() { with_larger_stack(None, || {
+    let mut interp = with_program(&[
+        insn!(JSR #1).into(),
+        insn!(ADD R0, R0, #1).into(),
+        insn!(TRAP #0x30).into(),
+        insn!(RET).into(),
+    ]);
+    interp.set_word_unchecked(TRAP_VECTOR_TABLE_START_ADDR + 0x30, 0x0500);
+    interp.set_word_unchecked(0x0500, insn!(RTI).into());
+
+    interp_steps(&mut interp, 2);
+    assert_eq!(interp.get_pc(), 0x0500);
+
+    let call_stack = interp.get_call_stack();
+    assert_eq!(call_stack.depth(), 2);
+
+    let jsr = CallStackFrame { kind: FrameKind::Jsr, target: 0x3002, return_addr: 0x3001, saved_psr: 0x8002, stack_pointer: 0x4000 };
+    let trap = CallStackFrame { kind: FrameKind::Trap { vector: 0x30 }, target: 0x0500, return_addr: 0x3003, saved_psr: 0x8002, stack_pointer: 0x2FEE };
+    assert_eq!(call_stack.page(0), [Some(jsr), Some(trap), None, None]);
+    assert_eq!(call_stack.page(1), [None; CALL_STACK_PAGE_SIZE]);
+    assert_eq!((jsr.mode(), trap.mode()), (ProcessorMode::User, ProcessorMode::Supervisor));
+
+    // RTI and then RET:
+    interp_steps(&mut interp, 2);
+    assert_eq!(interp.get_pc(), 0x3001);
+    assert_eq!(interp.get_call_stack_depth(), 0);
+    assert_eq!(interp.take_unbalanced_return(), None);
+})}
+
+#[test]
+fn interrupt_frames() { with_larger_stack(None, || {
+    let mut interp = with_program(&[insn!(ADD R0, R0, #1).into()]);
+
+    // Low level triggered, so it's pending right away:
+    let p = interp.get_peripherals_mut();
+    Gpio::set_trigger(p, GpioPin::G0, GpioTrigger::LowLevel).unwrap();
+    Gpio::set_state(p, GpioPin::G0, GpioState::Interrupt).unwrap();
+    interp.set_word_unchecked(INTERRUPT_VECTOR_TABLE_START_ADDR + G0_INT_VEC as Addr, 0x0700);
+
+    interp.step();
+    assert_eq!(interp.get_pc(), 0x0700);
+    assert_eq!(interp.get_call_stack().frame(0), Some(&CallStackFrame {
+        kind: FrameKind::Interrupt { vector: G0_INT_VEC },
+        target: 0x0700,
+        return_addr: 0x3000,
+        saved_psr: 0x8002,
+        stack_pointer: 0x2FEE,
+    }));
+})}
+
+#[test]
+fn returns_past_inline_arguments() { with_larger_stack(None, || {
+    let mut interp = with_program(&[
+        insn!(JSR #2).into(),
+        7,                          // an argument for the subroutine
+        insn!(ADD R0, R0, #1).into(),
+        insn!(ADD R7, R7, #1).into(),
+        insn!(RET).into(),
+    ]);
+
+    interp_steps(&mut interp, 3);
+    assert_eq!(interp.get_pc(), 0x3002);
+    assert_eq!(interp.get_call_stack_depth(), 0);
+    assert_eq!(interp.take_unbalanced_return(), Some(UnbalancedReturn { to: 0x3002, expected: 0x3001 }));
+    assert_eq!(interp.take_unbalanced_return(), None);
+})}
+
+#[test]
+fn returns_that_skip_frames() { with_larger_stack(None, || {
+    let mut interp = with_program(&[
+        insn!(JSR #1).into(),
+        insn!(ADD R0, R0, #1).into(),
+        insn!(ADD R5, R7, #0).into(),   // A: calls B, which returns from A too
+        insn!(JSR #0).into(),
+        insn!(ADD R7, R5, #0).into(),   // B
+        insn!(RET).into(),
+    ]);
+
+    interp_steps(&mut interp, 3);
+    assert_eq!(interp.get_call_stack_depth(), 2);
+
+    interp_steps(&mut interp, 2);
+    assert_eq!(interp.get_pc(), 0x3001);
+    assert_eq!(interp.get_call_stack_depth(), 0);
+    assert_eq!(interp.take_unbalanced_return(), Some(UnbalancedReturn { to: 0x3001, expected: 0x3004 }));
+})}
+
+#[test]
+fn unbalanced_return_event() { with_larger_stack(None, || {
+    let mut sim: Simulator<ShimInterp> = Simulator::default();
+
+    sim.write_word(0x3000, insn!(JSR #1).into());
+    sim.write_word(0x3002, insn!(ADD R7, R7, #1).into());
+    sim.write_word(0x3003, insn!(RET).into());
+    sim.set_pc(0x3000);
+
+    assert_eq!(sim.step(), None);
+    assert_eq!(sim.step(), None);
+    assert_eq!(sim.step(), Some(Event::UnbalancedReturn { to: 0x3002, expected: 0x3001 }));
+    assert_eq!(sim.get_pc(), 0x3002);
+})}
+
+#[test]
+fn deep_stacks_are_paged() { with_larger_stack(None, || {
+    let mut sim: Simulator<ShimInterp> = Simulator::default();
+
+    // Recurses forever:
+    sim.write_word(0x3000, insn!(JSR #-1).into());
+    sim.set_pc(0x3000);
+
+    for _ in 0..13 { sim.step(); }
+    assert_eq!(sim.get_depth(), Ok(13));
+
+    let frames: Vec<CallStackFrame> = (0..)
+        .map(|page| sim.get_call_stack(page))
+        .take_while(|page| page[0].is_some())
+        .flat_map(|page| page.iter().flatten().copied().collect::<Vec<_>>())
+        .collect();
+
+    #[cfg(not(feature = "std"))]
+    assert_eq!(frames.len(), lc3_traits::control::control::MAX_CALL_STACK_DEPTH);
+    #[cfg(feature = "std")]
+    assert_eq!(frames.len(), 13);
+
+    assert!(frames.iter().all(|f| (f.kind, f.target, f.return_addr) == (FrameKind::Jsr, 0x3000, 0x3001)));
+})}
+
+#[cfg(feature = "std")]
+#[test]
+fn call_stack_limit() { with_larger_stack(None, || {
+    let mut interp = with_program(&[insn!(JSR #-1).into()]);
+    interp.set_call_stack_limit(3);
+
+    interp_steps(&mut interp, 5);
+    assert_eq!(interp.get_call_stack_depth(), 5);
+    assert!(interp.get_call_stack().frame(2).is_some());
+    assert!(interp.get_call_stack().frame(3).is_none());
+})}
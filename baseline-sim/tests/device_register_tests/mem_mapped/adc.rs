@@ -1,6 +1,6 @@
 use super::*;
 
-use lc3_traits::peripherals::adc::{Adc, AdcPin, AdcState, ADC_PINS, AdcReadError};
+use lc3_traits::peripherals::adc::{Adc, AdcPin, AdcState, AdcMode, AdcInterrupt, ADC_PINS, AdcReadError};
 use lc3_baseline_sim::mem_mapped::{
     A0CR_ADDR, A0DR_ADDR,
     A1CR_ADDR, A1DR_ADDR,
@@ -8,6 +8,7 @@ use lc3_baseline_sim::mem_mapped::{
     A3CR_ADDR, A3DR_ADDR,
     A4CR_ADDR, A4DR_ADDR,
     A5CR_ADDR, A5DR_ADDR,
+    ADCSPR_ADDR,
 };
 
 use AdcState::*;
@@ -157,6 +158,52 @@ mod states {
         post: |i| { eq!(Enabled, Adc::get_state(i.get_peripherals(), A0)); }
     }
 
+    // The mode ([1]), interrupt ([3:2]) and threshold ([15:8]) live above the
+    // state bit:
+    single_test! {
+        adc_cr_pin0_read_mode_and_interrupt,
+        prefill: { 0x3010: A0CR_ADDR },
+        insns: [ { LDI R0, #0xF } ],
+        steps: 1,
+        regs: { R0: 0x80_0B },
+        pre: |p| {
+            Adc::set_state(p, A0, Enabled).unwrap();
+            Adc::set_mode(p, A0, AdcMode::Continuous).unwrap();
+            Adc::set_interrupt(p, A0, AdcInterrupt::ThresholdCrossing(0x80)).unwrap();
+        },
+    }
+
+    single_test! {
+        adc_cr_pin0_set_mode_and_interrupt,
+        prefill: { 0x3010: A0CR_ADDR, 0x3011: 0x00_07 },
+        insns: [ { LD R0, #0x10 }, { STI R0, #0xE } ],
+        steps: 2,
+        regs: { R0: 0x00_07 },
+        post: |i| {
+            let p = i.get_peripherals();
+            eq!(Enabled, Adc::get_state(p, A0));
+            eq!(AdcMode::Continuous, Adc::get_mode(p, A0));
+            eq!(AdcInterrupt::ConversionComplete, Adc::get_interrupt(p, A0));
+        }
+    }
+
+    single_test! {
+        adc_spr_read,
+        prefill: { 0x3010: ADCSPR_ADDR },
+        insns: [ { LDI R0, #0xF } ],
+        steps: 1,
+        regs: { R0: 10 },
+    }
+
+    single_test! {
+        adc_spr_write,
+        prefill: { 0x3010: ADCSPR_ADDR },
+        insns: [ { AND R0, R0, #0 }, { ADD R0, R0, #15 }, { STI R0, #0xD } ],
+        steps: 3,
+        regs: { R0: 15 },
+        post: |i| { eq!(15, Adc::get_sample_period(i.get_peripherals()).get()); }
+    }
+
 }
 
 mod read {
@@ -1,6 +1,6 @@
 use super::*;
 
-use lc3_traits::peripherals::gpio::{Gpio, GpioPin, GpioState, GPIO_PINS};
+use lc3_traits::peripherals::gpio::{Gpio, GpioPin, GpioState, GpioTrigger, GpioPull, GPIO_PINS};
 use lc3_baseline_sim::interp::InstructionInterpreter;
 use lc3_baseline_sim::mem_mapped::{
     G0CR_ADDR, G0DR_ADDR, /* G0_INT_VEC, */ // TODO!
@@ -174,7 +174,7 @@ mod states {
     }
 
     // We should also test that we actually only use the lower two bits of the
-    // value we're given in the G*_CR registers.
+    // value we're given in the G*_CR registers for the mode.
     single_test! {
         gpio_cr_pin0_set_output_invalid,
         prefill: { 0x3010: G0CR_ADDR },
@@ -184,6 +184,37 @@ mod states {
         post: |i| { eq!(Output, Gpio::get_state(i.get_peripherals(), G0)); }
     }
 
+    // The trigger ([4:2]) and pull ([6:5]) live above the mode bits:
+    single_test! {
+        gpio_cr_pin0_read_trigger_and_pull,
+        prefill: { 0x3010: G0CR_ADDR },
+        insns: [ { LDI R0, #0xF } ],
+        steps: 1,
+        regs: { R0: 0b10_010_11 },
+        pre: |p| {
+            Gpio::set_trigger(p, G0, GpioTrigger::BothEdges).unwrap();
+            Gpio::set_pull(p, G0, GpioPull::PullDown).unwrap();
+            Gpio::set_state(p, G0, Interrupt).unwrap();
+        },
+    }
+
+    single_test! {
+        gpio_cr_pin0_set_trigger_and_pull,
+        prefill: { 0x3010: G0CR_ADDR, 0x3011: 0b01_001_10 },
+        insns: [ { LD R0, #0x10 }, { STI R0, #0xE } ],
+        steps: 2,
+        regs: { R0: 0b01_001_10 },
+        post: |i| {
+            let p = i.get_peripherals();
+            eq!(Input, Gpio::get_state(p, G0));
+            eq!(GpioTrigger::FallingEdge, Gpio::get_trigger(p, G0));
+            eq!(GpioPull::PullUp, Gpio::get_pull(p, G0));
+
+            // The pull should take effect when the pin becomes an input:
+            eq!(Ok(true), Gpio::read(p, G0));
+        }
+    }
+
 }
 
 mod read {
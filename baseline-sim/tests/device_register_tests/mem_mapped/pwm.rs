@@ -1,7 +1,7 @@
 use super::*;
 
 use lc3_baseline_sim::mem_mapped::{
-    P0CR_ADDR, P0DR_ADDR,
+    P0CR_ADDR, P0DR_ADDR, P0PR_ADDR, P0IR_ADDR,
 };
 
 single_test! {
@@ -13,6 +13,24 @@ single_test! {
     memory: { }
 }
 
+single_test! {
+    get_initial_period,
+    prefill: { 0x3010: P0PR_ADDR, },
+    insns: [ { LDI R0, #0xF }, ],
+    steps: 1,
+    regs: { R0: 0x0000 },
+    memory: { }
+}
+
+single_test! {
+    get_initial_interrupt,
+    prefill: { 0x3010: P0IR_ADDR, },
+    insns: [ { LDI R0, #0xF }, ],
+    steps: 1,
+    regs: { R0: 0x0000 },
+    memory: { }
+}
+
 single_test! {
     get_initial_duty_cycle,
     prefill: { 0x3010: P0DR_ADDR, },
@@ -54,6 +72,91 @@ single_test! {
     memory: { }
 }
 
+// Periods in PxPR use the full word:
+single_test! {
+    set_period,
+    prefill: {
+        0x3010: 0xBEEF,
+        0x3011: P0PR_ADDR,
+    },
+    insns: [
+        { LD R0, #0xF },
+        { STI R0, #0xF },
+        { LDI R0, #0xE },
+    ],
+    steps: 3,
+    regs: { R0: 0xBEEF },
+    memory: { }
+}
+
+// PxCR and PxPR are the same period in different units:
+single_test! {
+    set_state_read_period,
+    prefill: {
+        0x3010: 20,
+        0x3011: P0CR_ADDR,
+        0x3012: P0PR_ADDR,
+    },
+    insns: [
+        { LD R0, #0xF },
+        { STI R0, #0xF },
+        { LDI R0, #0xF },
+    ],
+    steps: 3,
+    regs: { R0: 20000 },
+    memory: { }
+}
+
+single_test! {
+    set_period_read_state_rounds,
+    prefill: {
+        0x3010: 1500,
+        0x3011: P0PR_ADDR,
+        0x3012: P0CR_ADDR,
+    },
+    insns: [
+        { LD R0, #0xF },
+        { STI R0, #0xF },
+        { LDI R0, #0xF },
+    ],
+    steps: 3,
+    regs: { R0: 2 },
+    memory: { }
+}
+
+single_test! {
+    long_periods_saturate_the_period_register,
+    prefill: {
+        0x3010: 255,
+        0x3011: P0CR_ADDR,
+        0x3012: P0PR_ADDR,
+    },
+    insns: [
+        { LD R0, #0xF },
+        { STI R0, #0xF },
+        { LDI R0, #0xF },
+    ],
+    steps: 3,
+    regs: { R0: 0xFFFF },
+    memory: { }
+}
+
+single_test! {
+    set_interrupt_masks,
+    prefill: {
+        0x3010: 0xBEEF,
+        0x3011: P0IR_ADDR,
+    },
+    insns: [
+        { LD R0, #0xF },
+        { STI R0, #0xF },
+        { LDI R0, #0xE },
+    ],
+    steps: 3,
+    regs: { R0: 0x0001 },
+    memory: { }
+}
+
 single_test! {
     set_duty_cycle,
     prefill: {
@@ -70,8 +173,9 @@ single_test! {
     memory: { }
 }
 
+// Duty cycles use the full word:
 single_test! {
-    set_duty_cycle_masks,
+    set_duty_cycle_full_width,
     prefill: {
         0x3010: 0xBEEF,
         0x3011: P0DR_ADDR,
@@ -82,6 +186,6 @@ single_test! {
         { LDI R0, #0xE },
     ],
     steps: 3,
-    regs: { R0: 0x00EF },
+    regs: { R0: 0xBEEF },
     memory: { }
 }
@@ -2,7 +2,7 @@ use super::*;
 
 use lc3_baseline_sim::mem_mapped::{
     MemMapped,
-    T0CR_ADDR, T0DR_ADDR,
+    T0CR_ADDR, T0DR_ADDR, T2CR_ADDR, T1CCR_ADDR, T3CCR_ADDR, T3CPR_ADDR,
     TIMER_BASE_INT_VEC,
     PSR,
     MCR
@@ -75,3 +75,70 @@ single_test! {
         { RTI } // 0x300D
     ],
 }
+
+single_test! {
+    set_free_running_mode,
+    prefill: {
+        0x3010: 0b10,
+        0x3011: T2CR_ADDR,
+    },
+    insns: [
+        { LD R0, #0xF },
+        { STI R0, #0xF },
+        { LDI R0, #0xE },
+    ],
+    steps: 3,
+    regs: { R0: 0b10 },
+    memory: { }
+}
+
+// Capture on both edges of G5:
+single_test! {
+    set_capture_source,
+    prefill: {
+        0x3010: (5 << 2) | 0b11,
+        0x3011: T1CCR_ADDR,
+    },
+    insns: [
+        { LD R0, #0xF },
+        { STI R0, #0xF },
+        { LDI R0, #0xE },
+    ],
+    steps: 3,
+    regs: { R0: (5 << 2) | 0b11 },
+    memory: { }
+}
+
+// Without an edge, the pin number is dropped:
+single_test! {
+    set_capture_source_disabled,
+    prefill: {
+        0x3010: 5 << 2,
+        0x3011: T3CCR_ADDR,
+    },
+    insns: [
+        { LD R0, #0xF },
+        { STI R0, #0xF },
+        { LDI R0, #0xE },
+    ],
+    steps: 3,
+    regs: { R0: 0 },
+    memory: { }
+}
+
+// The capture register is read-only:
+single_test! {
+    capture_register_read_only,
+    prefill: {
+        0x3010: 0xBEEF,
+        0x3011: T3CPR_ADDR,
+    },
+    insns: [
+        { LD R0, #0xF },
+        { STI R0, #0xF },
+        { LDI R0, #0xE },
+    ],
+    steps: 3,
+    regs: { R0: 0 },
+    memory: { }
+}
@@ -0,0 +1,86 @@
+extern crate lc3_test_infrastructure as lti;
+
+use lc3_baseline_sim::block::BlockInterpreter;
+use lc3_baseline_sim::interp::Interpreter;
+use lc3_baseline_sim::sim::Simulator;
+use lc3_isa::{insn, Instruction, Reg::*, Word};
+use lc3_traits::control::Control;
+use lti::diff::{differential_test, minimize, run_in_lockstep, run_loaded_in_lockstep, GenConfig, Program};
+use lti::{with_larger_stack, MemoryShim, PeripheralsShim};
+
+type Interp<'a> = Interpreter<'a, MemoryShim, PeripheralsShim<'a>>;
+type Block<'a> = BlockInterpreter<'a, MemoryShim, PeripheralsShim<'a>>;
+
+#[test]
+fn interpreters_agree() { with_larger_stack(None, || {
+    let mut interp: Simulator<Interp> = Simulator::default();
+    let mut block: Simulator<Block> = Simulator::default();
+
+    for control_flow in [false, true] {
+        let config = GenConfig { control_flow, ..GenConfig::default() };
+
+        if let Err(div) = differential_test(&mut interp, &mut block, &config, 0xC0FFEE, 50, 150) {
+            panic!("{}", div);
+        }
+    }
+})}
+
+#[test]
+fn generated_programs_stay_put() { with_larger_stack(None, || {
+    let mut sim: Simulator<Interp> = Simulator::default();
+
+    for seed in 0..20 {
+        let program = Program::from_seed(&GenConfig::default(), seed);
+        let end = program.orig + program.code.len() as u16;
+
+        program.load(&mut sim);
+        for _ in 0..500 {
+            let _ = sim.step();
+
+            let pc = sim.get_pc();
+            assert!((program.orig..end).contains(&pc), "{:#06X} is outside of:\n{}", pc, program);
+        }
+    }
+})}
+
+#[test]
+fn minimizes_reproducers() {
+    let planted: Word = insn!(ADD R3, R3, #7).into();
+
+    let mut program = Program::from_seed(&GenConfig::default(), 7);
+    program.code[20] = planted;
+
+    let min = minimize(&program, |p| p.code.contains(&planted));
+
+    assert_eq!(min.code[0], program.code[0]);
+    assert_eq!(min.code[20], planted);
+    assert_eq!(*min.code.last().unwrap(), *program.code.last().unwrap());
+    assert!(min.code[1..20].iter().chain(min.code[21..(min.code.len() - 1)].iter()).all(|w| *w == 0));
+    assert_eq!(min.regs, [0; 8]);
+    assert!(min.data.iter().all(|w| *w == 0));
+
+    // Still a valid program:
+    assert!(format!("{}", min).contains("ADD   R3, R3, #7"));
+}
+
+#[test]
+fn reports_the_first_divergence() { with_larger_stack(None, || {
+    let mut left: Simulator<Interp> = Simulator::default();
+    let mut right: Simulator<Interp> = Simulator::default();
+
+    let config = GenConfig { control_flow: false, ..GenConfig::default() };
+    let mut program = Program::from_seed(&config, 1);
+    program.code[1] = insn!(AND R0, R0, #0).into();
+    program.code[2] = insn!(ADD R0, R0, #1).into();
+
+    assert_eq!(run_in_lockstep(&mut left, &mut right, &program, 100), Ok(()));
+
+    program.load(&mut left);
+    program.load(&mut right);
+    right.write_word(program.orig + 2, Instruction::new_add_imm(R0, R0, 2).into());
+
+    let div = run_loaded_in_lockstep(&mut left, &mut right, &program, 100).unwrap_err();
+    assert_eq!(div.step, 3);
+    assert_eq!((div.left.regs[0], div.right.regs[0]), (1, 2));
+    assert!(format!("{}", div).contains("R0: 0x0001 vs 0x0002"));
+})}
@@ -0,0 +1,103 @@
+extern crate lc3_test_infrastructure as lti;
+
+use lc3_baseline_sim::ext::{ExtensionContext, ExtensionHandler, MulXor};
+use lc3_baseline_sim::interp::{
+    Acv, FaultHandling, InstructionInterpreter, InstructionInterpreterPeripheralAccess,
+    Interpreter,
+};
+use lc3_baseline_sim::PSR;
+use lc3_isa::ext::IsaExtension;
+use lc3_isa::{Instruction, Reg::*, Word};
+use lc3_traits::error::Error;
+use lti::{with_larger_stack, MemoryShim, PeripheralsShim};
+
+use core::fmt::{self, Formatter};
+
+type Interp<'a> = Interpreter<'a, MemoryShim, PeripheralsShim<'a>>;
+
+// Runs the given words starting at 0x3000.
+fn run<'a>(interp: &mut Interp<'a>, words: &[Word]) {
+    for (offset, w) in words.iter().enumerate() {
+        interp.set_word_unchecked(0x3000 + offset as Word, *w);
+    }
+    interp.set_pc(0x3000);
+
+    for _ in words {
+        interp.step();
+    }
+}
+
+#[test]
+fn mul_xor() { with_larger_stack(None, || {
+    let mut interp = Interp::default().with_extension(&MulXor);
+    interp[R1] = 6;
+    interp[R2] = 7;
+
+    run(&mut interp, &[
+        MulXor::new_mul(R0, R1, R2).into(),
+        MulXor::new_xor(R3, R1, R2).into(),
+        MulXor::new_xor(R4, R1, R1).into(),
+    ]);
+
+    assert_eq!(interp.get_error(), None);
+    assert_eq!((interp[R0], interp[R3], interp[R4]), (42, 1, 0));
+    assert!(interp.get_special_reg::<PSR>().get_cc().1);
+
+    assert_eq!(
+        format!("{}", MulXor::new_mul(R0, R1, R2).display_with(&MulXor)),
+        "MUL   R0, R1, R2",
+    );
+})}
+
+#[test]
+fn standard_without_an_extension() { with_larger_stack(None, || {
+    let mul: Word = MulXor::new_mul(R0, R1, R2).into();
+
+    let mut interp = Interp::default();
+    interp.set_fault_handling(FaultHandling::Stop);
+    run(&mut interp, &[mul]);
+    assert_eq!(interp.get_error(), Some(Error::IllegalOpcode { pc: 0x3000, insn: mul }));
+
+    // Encodings the extension doesn't claim are still illegal:
+    let unclaimed: Word = 0b1101_000_000_111_000;
+    let mut interp = Interp::default().with_extension(&MulXor);
+    interp.set_fault_handling(FaultHandling::Stop);
+    run(&mut interp, &[unclaimed]);
+    assert_eq!(interp.get_error(), Some(Error::IllegalOpcode { pc: 0x3000, insn: unclaimed }));
+})}
+
+// Stores R0 to the address in R1.
+#[derive(Debug)]
+struct Poke;
+
+impl IsaExtension for Poke {
+    fn decodes(&self, bits: u16) -> bool { bits == 0 }
+    fn fmt(&self, _bits: u16, fmt: &mut Formatter<'_>) -> fmt::Result { write!(fmt, "POKE") }
+}
+
+impl ExtensionHandler for Poke {
+    fn execute(&self, _bits: u16, cpu: &mut dyn ExtensionContext) -> Result<(), Acv> {
+        let (addr, word) = (cpu.reg(R1), cpu.reg(R0));
+        cpu.store(addr, word)
+    }
+}
+
+#[test]
+fn extension_memory_accesses_are_checked() { with_larger_stack(None, || {
+    let poke: Word = Instruction::new_extended(0).into();
+
+    let mut interp = Interp::default().with_extension(&Poke);
+    interp.set_fault_handling(FaultHandling::Stop);
+    interp[R0] = 0xBEEF;
+    interp[R1] = 0x4000;
+    run(&mut interp, &[poke]);
+    assert_eq!(interp.get_word_unchecked(0x4000), 0xBEEF);
+
+    interp[R1] = 0x0100;
+    interp.set_special_reg::<PSR>(0x8002);
+    run(&mut interp, &[poke]);
+    assert_eq!(
+        interp.get_error(),
+        Some(Error::AccessControlViolation { pc: 0x3000, insn: Some(poke), addr: 0x0100 }),
+    );
+})}
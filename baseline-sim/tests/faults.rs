@@ -0,0 +1,104 @@
+extern crate lc3_test_infrastructure as lti;
+
+use lc3_baseline_sim::interp::{
+    FaultHandling, InstructionInterpreter, InstructionInterpreterPeripheralAccess,
+    Interpreter, MachineState,
+};
+use lc3_baseline_sim::sim::Simulator;
+use lc3_baseline_sim::PSR;
+use lc3_isa::{insn, Addr, Reg::*, Word, INTERRUPT_VECTOR_TABLE_START_ADDR, ILLEGAL_OPCODE_EXCEPTION_VECTOR};
+use lc3_traits::control::{Control, Event, State};
+use lc3_traits::error::Error;
+use lti::{with_larger_stack, MemoryShim, PeripheralsShim};
+
+type Interp<'a> = Interpreter<'a, MemoryShim, PeripheralsShim<'a>>;
+
+const USER_MODE_PSR: Word = 0x8002;
+const RESERVED: Word = 0b1101_0000_0000_0000;
+
+// Runs one instruction (at 0x3000, in user mode) and returns the interpreter.
+fn fault<'a>(word: Word, handling: FaultHandling) -> Interp<'a> {
+    let mut interp = Interp::default();
+    interp.set_fault_handling(handling);
+
+    interp.set_word_unchecked(0x3000, word);
+    interp.set_pc(0x3000);
+    interp.set_special_reg::<PSR>(USER_MODE_PSR);
+
+    assert_eq!(interp.step(), MachineState::Running);
+    interp
+}
+
+#[test]
+fn stops_on_faults() { with_larger_stack(None, || {
+    // ACV on a data access:
+    let ldr: Word = insn!(LDR R0, R1, #0).into();
+    let mut interp = Interp::default();
+    interp.set_fault_handling(FaultHandling::Stop);
+    interp.set_word_unchecked(0x3000, ldr);
+    interp.set_pc(0x3000);
+    interp[R1] = 0x0100;
+    interp.set_special_reg::<PSR>(USER_MODE_PSR);
+
+    interp.step();
+    assert_eq!(interp.get_error(), Some(Error::AccessControlViolation { pc: 0x3000, insn: Some(ldr), addr: 0x0100 }));
+    assert_eq!(interp.get_pc(), 0x3000);
+
+    // ACV on the instruction fetch:
+    let mut interp = Interp::default();
+    interp.set_fault_handling(FaultHandling::Stop);
+    interp.set_pc(0x0200);
+    interp.set_special_reg::<PSR>(USER_MODE_PSR);
+
+    interp.step();
+    assert_eq!(interp.get_error(), Some(Error::AccessControlViolation { pc: 0x0200, insn: None, addr: 0x0200 }));
+    assert_eq!(interp.get_pc(), 0x0200);
+
+    // Illegal opcode:
+    let interp = fault(RESERVED, FaultHandling::Stop);
+    assert_eq!(interp.get_error(), Some(Error::IllegalOpcode { pc: 0x3000, insn: RESERVED }));
+    assert_eq!(interp.get_pc(), 0x3000);
+
+    // RTI in user mode:
+    let rti: Word = insn!(RTI).into();
+    let interp = fault(rti, FaultHandling::Stop);
+    assert_eq!(interp.get_error(), Some(Error::PrivilegeModeViolation { pc: 0x3000, insn: rti }));
+    assert_eq!(interp.get_pc(), 0x3000);
+})}
+
+#[test]
+fn vectors_by_default() { with_larger_stack(None, || {
+    assert_eq!(Interp::default().get_fault_handling(), FaultHandling::Vector);
+
+    const HANDLER: Addr = 0x0700;
+    let mut interp = Interp::default();
+    interp.set_word_unchecked(INTERRUPT_VECTOR_TABLE_START_ADDR + ILLEGAL_OPCODE_EXCEPTION_VECTOR as Addr, HANDLER);
+    interp.set_word_unchecked(0x3000, RESERVED);
+    interp.set_pc(0x3000);
+    interp[R6] = 0x2FF0;
+
+    interp.step();
+    assert_eq!(interp.get_error(), None);
+    assert_eq!(interp.get_pc(), HANDLER);
+})}
+
+#[test]
+fn simulator_reports_faults() { with_larger_stack(None, || {
+    let mut sim: Simulator<Interp> = Simulator::default();
+    sim.set_fault_handling(FaultHandling::Stop);
+
+    sim.write_word(0x3000, insn!(ADD R0, R0, #1).into());
+    sim.write_word(0x3001, RESERVED);
+    sim.set_pc(0x3000);
+
+    assert_eq!(sim.step(), None);
+    assert_eq!(sim.step(), Some(Event::Error { err: Error::IllegalOpcode { pc: 0x3001, insn: RESERVED } }));
+    assert_eq!(sim.get_state(), State::Paused);
+    assert_eq!(sim.get_pc(), 0x3001);
+
+    let err = Error::IllegalOpcode { pc: 0x3001, insn: RESERVED };
+    assert_eq!(
+        err.to_string(),
+        "Illegal opcode: the instruction at 0x3001 (0xd000) uses the reserved opcode",
+    );
+})}
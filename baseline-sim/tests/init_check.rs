@@ -0,0 +1,169 @@
+extern crate lc3_test_infrastructure as lti;
+
+use lc3_baseline_sim::init_check::InitTracker;
+use lc3_isa::util::AssembledProgram;
+use lc3_isa::{insn, Reg::*, Word, ADDR_SPACE_SIZE_IN_WORDS, TRAP_VECTOR_TABLE_START_ADDR};
+use lc3_isa::util::MemoryDump;
+use lc3_traits::control::load::load_memory_dump_without_progress;
+use lc3_traits::control::{Control, Event, UninitializedRead::{self, *}};
+use lti::{sim_steps, sim_with_program, with_larger_stack, ShimSim};
+
+// A simulator about to run `program` (at 0x3000); the program is written after
+// the tracker is set up so it counts as initialized.
+fn with_program<'a>(program: &[Word], tracker: Option<InitTracker>) -> ShimSim<'a> {
+    sim_with_program(program, |sim| sim.set_init_tracker(tracker))
+}
+
+fn read(pc: u16, read: UninitializedRead) -> Option<Event> {
+    Some(Event::UninitializedRead { pc, read })
+}
+
+#[test]
+fn off_by_default() { with_larger_stack(None, || {
+    let mut sim = with_program(&[insn!(ADD R0, R1, #0).into(), insn!(LD R2, #5).into()], None);
+
+    assert!(sim.get_init_tracker().is_none());
+    assert_eq!(sim_steps(&mut sim, 2), vec![None, None]);
+})}
+
+#[test]
+fn registers() { with_larger_stack(None, || {
+    let program = [
+        insn!(AND R1, R1, #0).into(),   // clearing a register isn't a read
+        insn!(ADD R0, R1, #1).into(),
+        insn!(NOT R3, R0).into(),
+        insn!(ADD R4, R3, R2).into(),
+    ];
+    let mut sim = with_program(&program, Some(InitTracker::new()));
+
+    assert_eq!(sim_steps(&mut sim, 4), vec![None, None, None, read(0x3003, Register(R2))]);
+    assert!(sim.get_init_tracker().unwrap().is_register_initialized(R3));
+
+    // Registers set through `Control` count:
+    let mut sim = with_program(&program, Some(InitTracker::new()));
+    sim.set_register(R2, 7);
+    assert_eq!(sim_steps(&mut sim, 4), vec![None; 4]);
+})}
+
+#[test]
+fn memory() { with_larger_stack(None, || {
+    let tracker = InitTracker::new().with_initialized_registers(&[R6]);
+    let mut sim = with_program(&[
+        insn!(LD R0, #3).into(),
+        insn!(LDR R1, R6, #0).into(),
+        insn!(LDI R2, #2).into(),
+        0x3006,                         // initialized
+        0x3007,                         // a pointer to something initialized
+        0x3008,                         // and to something that isn't
+    ], Some(tracker));
+    sim.set_register(R6, 0x3004);
+
+    assert_eq!(sim.step(), None);
+    assert_eq!(sim.step(), None);
+
+    // The pointer's fine but what it points to isn't:
+    assert_eq!(sim.step(), read(0x3002, Memory(0x3008)));
+})}
+
+#[test]
+fn stores_initialize_memory() { with_larger_stack(None, || {
+    let mut sim = with_program(&[
+        insn!(AND R0, R0, #0).into(),
+        insn!(ST R0, #2).into(),
+        insn!(LD R1, #1).into(),
+        insn!(LD R2, #1).into(),
+    ], Some(InitTracker::new()));
+
+    // The first load reads what the store wrote; the second one reads past
+    // the end of the program.
+    assert_eq!(sim_steps(&mut sim, 4), vec![None, None, None, read(0x3003, Memory(0x3005))]);
+})}
+
+#[test]
+fn fetches() { with_larger_stack(None, || {
+    let mut sim = with_program(&[insn!(AND R0, R0, #0).into()], Some(InitTracker::new()));
+
+    // Running off the end of the program:
+    assert_eq!(sim_steps(&mut sim, 2), vec![None, read(0x3001, Memory(0x3001))]);
+})}
+
+#[test]
+fn traps() { with_larger_stack(None, || {
+    let mut sim = with_program(&[insn!(TRAP #0x30).into(), insn!(AND R0, R0, #0).into()], Some(InitTracker::new()));
+
+    sim.set_register(R6, 0x3100);
+    sim.write_word(TRAP_VECTOR_TABLE_START_ADDR + 0x30, 0x0500);
+    sim.write_word(0x0500, insn!(RTI).into());
+
+    // The RTI reads back what the trap pushed:
+    assert_eq!(sim_steps(&mut sim, 3), vec![None, None, None]);
+    assert_eq!(sim.get_pc(), 0x3002);
+})}
+
+#[test]
+fn seeded_from_a_program() { with_larger_stack(None, || {
+    let mut program = AssembledProgram::new([(0, false); ADDR_SPACE_SIZE_IN_WORDS]);
+    program[0x3000] = (insn!(LD R0, #1).into(), true);
+    program[0x3002] = (0, true);
+
+    let tracker = InitTracker::new().with_program(&program);
+    assert!(tracker.is_memory_initialized(0x3000));
+    assert!(!tracker.is_memory_initialized(0x3001));
+    assert!(tracker.is_memory_initialized(0x3002));
+
+    // Device registers always are:
+    assert!(tracker.is_memory_initialized(0xFE00));
+})}
+
+#[test]
+fn randomize_on_reset() { with_larger_stack(None, || {
+    let tracker = InitTracker::new()
+        .with_initialized_memory(0x3000..=0x3000)
+        .randomize_on_reset(0xC0FFEE);
+    let mut sim = with_program(&[insn!(AND R0, R0, #0).into(), 0x1234], Some(tracker));
+    sim.reset();
+
+    // Memory goes back to what was loaded; the word written afterwards isn't
+    // initialized anymore and is replaced with junk:
+    let tracker = sim.get_init_tracker().unwrap();
+    assert!(tracker.is_memory_initialized(0x3000));
+    assert!(!tracker.is_memory_initialized(0x3001));
+    assert_eq!(sim.read_word(0x3000), 0);
+    assert!((0x3001..0x3010).any(|a| sim.read_word(a) != 0));
+
+    assert!(!tracker.is_register_initialized(R0));
+    assert!([R0, R1, R2, R3, R4, R5, R6, R7].iter().any(|r| sim.get_register(*r) != 0));
+
+    // Same seed, same junk:
+    let junk: Vec<Word> = (0x3001..0x3010).map(|a| sim.read_word(a)).collect();
+    let mut other = with_program(&[], Some(InitTracker::new().with_initialized_memory(0x3000..=0x3000).randomize_on_reset(0xC0FFEE)));
+    other.reset();
+    assert_eq!(junk, (0x3001..0x3010).map(|a| other.read_word(a)).collect::<Vec<_>>());
+})}
+
+#[test]
+fn seeded_by_the_simulator() { with_larger_stack(None, || {
+    // Only the page with the change is sent:
+    let previous = MemoryDump::from([0; ADDR_SPACE_SIZE_IN_WORDS]);
+    let mut dump = MemoryDump::from([0; ADDR_SPACE_SIZE_IN_WORDS]);
+    dump.0[0x4000] = 0x1234;
+
+    let mut sim = ShimSim::default();
+    load_memory_dump_without_progress(&mut sim, &dump, &previous).unwrap();
+    sim.reset();
+    sim.set_init_tracker(Some(InitTracker::new().randomize_on_reset(0xC0FFEE)));
+
+    let tracker = sim.get_init_tracker().unwrap();
+    assert!(tracker.is_memory_initialized(0x0000));
+    assert!(tracker.is_memory_initialized(0x2FFF));
+    assert!(tracker.is_memory_initialized(0x4000));
+    assert!(tracker.is_memory_initialized(0x40FF));
+    assert!(!tracker.is_memory_initialized(0x3000));
+    assert!(!tracker.is_memory_initialized(0x4100));
+
+    // Which means resets leave the OS and the loaded page alone:
+    sim.reset();
+    assert!((0x0000..0x3000).all(|a| sim.read_word(a) == 0));
+    assert_eq!(sim.read_word(0x4000), 0x1234);
+    assert!((0x3000..0x3010).any(|a| sim.read_word(a) != 0));
+})}
@@ -0,0 +1,146 @@
+extern crate lc3_test_infrastructure as lti;
+
+use lc3_baseline_sim::interp::{InstructionInterpreter, InstructionInterpreterPeripheralAccess, Interpreter};
+use lc3_baseline_sim::sim::Simulator;
+use lc3_baseline_sim::{
+    ICR_ADDR, IMR0_ADDR, IPNR0_ADDR, IPNR1_ADDR, IPR0_ADDR, IPR5_ADDR, G0_INT_VEC, G1_INT_VEC,
+    PSR,
+};
+use lc3_isa::{insn, Addr, Reg::*, Word, INTERRUPT_VECTOR_TABLE_START_ADDR};
+use lc3_traits::control::{Control, InterruptSource};
+use lc3_traits::peripherals::gpio::{Gpio, GpioPin, GpioState, GpioTrigger};
+use lti::{with_larger_stack, MemoryShim, PeripheralsShim};
+
+type Interp<'a> = Interpreter<'a, MemoryShim, PeripheralsShim<'a>>;
+
+// Source numbers of G0 and G1:
+const G0: u16 = 2;
+const G1: u16 = 3;
+
+const G0_HANDLER: Addr = 0x0700;
+const G1_HANDLER: Addr = 0x0800;
+
+// An interpreter in supervisor mode at priority 0 about to run an `ADD` at
+// 0x3000, with handlers for G0 and G1 (which are both low level triggered,
+// so they're pending from the start).
+fn setup<'a>() -> Interp<'a> {
+    let mut interp = Interp::default();
+
+    for pin in [GpioPin::G0, GpioPin::G1] {
+        let p = interp.get_peripherals_mut();
+        Gpio::set_trigger(p, pin, GpioTrigger::LowLevel).unwrap();
+        Gpio::set_state(p, pin, GpioState::Interrupt).unwrap();
+    }
+
+    interp.set_word_unchecked(INTERRUPT_VECTOR_TABLE_START_ADDR + G0_INT_VEC as Addr, G0_HANDLER);
+    interp.set_word_unchecked(INTERRUPT_VECTOR_TABLE_START_ADDR + G1_INT_VEC as Addr, G1_HANDLER);
+    interp.set_word_unchecked(0x3000, insn!(ADD R0, R0, #1).into());
+
+    interp.set_pc(0x3000);
+    interp[R6] = 0x2FF0;
+    interp.set_special_reg::<PSR>(0x0002);
+
+    interp
+}
+
+fn set_priority(interp: &mut Interp, source: u16, priority: Word) {
+    let addr = IPR0_ADDR + source / 4;
+    let shift = (source % 4) * 4;
+
+    let word = interp.get_word_unchecked(addr);
+    interp.set_word_unchecked(addr, (word & !(0b111 << shift)) | (priority << shift));
+}
+
+#[test]
+fn reset_state() { with_larger_stack(None, || {
+    let interp = Interp::default();
+
+    assert_eq!(interp.get_word_unchecked(ICR_ADDR), 0x8000);
+    assert_eq!(interp.get_word_unchecked(IMR0_ADDR), 0);
+    assert_eq!(interp.get_word_unchecked(IPR0_ADDR), 0x4444);
+    assert_eq!(interp.get_word_unchecked(IPR5_ADDR), 0x0044);
+    assert_eq!((interp.get_word_unchecked(IPNR0_ADDR), interp.get_word_unchecked(IPNR1_ADDR)), (0, 0));
+})}
+
+#[test]
+fn registers_ignore_reserved_bits() { with_larger_stack(None, || {
+    let mut interp = setup();
+
+    interp.set_word_unchecked(ICR_ADDR, 0x7FFF);
+    assert_eq!(interp.get_word_unchecked(ICR_ADDR), 0);
+
+    // The pending registers are read only:
+    interp.set_word_unchecked(IPNR0_ADDR, 0xFFFF);
+    assert_eq!(interp.get_word_unchecked(IPNR0_ADDR), (1 << G0) | (1 << G1));
+})}
+
+#[test]
+fn highest_priority_fires_first() { with_larger_stack(None, || {
+    // Ties go to the lower numbered source:
+    let mut interp = setup();
+    interp.step();
+    assert_eq!(interp.get_pc(), G0_HANDLER);
+    assert_eq!(interp.get_special_reg::<PSR>().get_priority(), 4);
+
+    let mut interp = setup();
+    set_priority(&mut interp, G0, 2);
+    set_priority(&mut interp, G1, 5);
+
+    interp.step();
+    assert_eq!(interp.get_pc(), G1_HANDLER);
+    assert_eq!(interp.get_special_reg::<PSR>().get_priority(), 5);
+
+    // G0 can't preempt G1's handler:
+    interp.step();
+    assert_eq!(interp.get_pc(), G1_HANDLER + 1);
+})}
+
+#[test]
+fn masked_sources_stay_pending() { with_larger_stack(None, || {
+    let mut interp = setup();
+    interp.set_word_unchecked(IMR0_ADDR, 1 << G0);
+
+    interp.step();
+    assert_eq!(interp.get_pc(), G1_HANDLER);
+    assert_eq!(interp.get_word_unchecked(IPNR0_ADDR) & (1 << G0), 1 << G0);
+
+    // Priority 0 sources never fire:
+    let mut interp = setup();
+    interp.set_word_unchecked(IMR0_ADDR, 1 << G0);
+    set_priority(&mut interp, G1, 0);
+
+    interp.step();
+    assert_eq!((interp.get_pc(), interp[R0]), (0x3001, 1));
+})}
+
+#[test]
+fn global_disable() { with_larger_stack(None, || {
+    let mut interp = setup();
+    interp.set_word_unchecked(ICR_ADDR, 0);
+
+    interp.step();
+    assert_eq!((interp.get_pc(), interp[R0]), (0x3001, 1));
+
+    interp.set_word_unchecked(ICR_ADDR, 0x8000);
+    interp.step();
+    assert_eq!(interp.get_pc(), G0_HANDLER);
+})}
+
+#[test]
+fn control_getter() { with_larger_stack(None, || {
+    let mut sim: Simulator<Interp> = Simulator::default();
+
+    let state = sim.get_interrupt_controller_state();
+    assert!(state.enabled);
+    assert_eq!((state.pending, state.masked), (0, 0));
+    assert!(state.priorities.iter().all(|p| *p == 4));
+
+    sim.write_word(IMR0_ADDR, 1 << G1);
+    sim.write_word(IPR0_ADDR, 0x0123);
+
+    let gpio = InterruptSource::Gpio(GpioPin::G1);
+    let state = sim.get_interrupt_controller_state();
+    assert!(state.is_masked(gpio));
+    assert_eq!(state.priority(gpio), 0);
+    assert_eq!(state.priority(InterruptSource::Keyboard), 3);
+})}
@@ -0,0 +1,134 @@
+extern crate lc3_test_infrastructure as lti;
+
+use lc3_baseline_sim::interp::{
+    FaultHandling, InstructionInterpreter, InstructionInterpreterPeripheralAccess,
+    InterpreterBuilder,
+};
+use lc3_baseline_sim::lc3b::Lc3bInterpreter;
+use lc3_baseline_sim::sim::Simulator;
+use lc3_baseline_sim::{BSP, PSR};
+use lc3_isa::lc3b::OS_START_ADDR;
+use lc3_isa::{lc3b_insn, lc3b_program, Reg::*};
+use lc3_traits::control::{Control, Event};
+use lc3_traits::error::Error;
+use lti::{interp_steps, interp_with_memory, with_larger_stack, MemoryShim, PeripheralsShim};
+
+type Interp<'a> = Lc3bInterpreter<'a, MemoryShim, PeripheralsShim<'a>>;
+
+
+#[test]
+fn bytes_shifts_and_xor() { with_larger_stack(None, || {
+    let mut interp: Interp = interp_with_memory(lc3b_program! {
+        .ORIG #0x3000;
+        LEA R0, @DATA;
+        LDB R1, R0, #0;
+        LDB R2, R0, #1;
+        LDW R3, R0, #0;
+        STB R1, R0, #3;
+        LSHF R4, R3, #4;
+        RSHFL R5, R3, #4;
+        RSHFA R6, R3, #4;
+        XOR R7, R3, R3;
+        HALT;
+        @DATA .FILL #0xBEEF;
+        .FILL #0x1234;
+    });
+
+    interp_steps(&mut interp, 9);
+
+    assert_eq!(interp.get_error(), None);
+    assert_eq!(interp.get_pc(), 0x3012);
+    assert_eq!(interp[R0], 0x3014);
+    assert_eq!((interp[R1], interp[R2], interp[R3]), (0xFFEF, 0xFFBE, 0xBEEF));
+    assert_eq!(interp.get_word_unchecked(0x3016), 0xEF34);
+    assert_eq!((interp[R4], interp[R5], interp[R6], interp[R7]), (0xEEF0, 0x0BEE, 0xFBEE, 0));
+    assert_eq!(interp.get_special_reg::<PSR>().get_cc(), (false, true, false));
+})}
+
+#[test]
+fn calls_and_traps_link_through_r7() { with_larger_stack(None, || {
+    let mut interp: Interp = interp_with_memory(lc3b_program! {
+        .ORIG #0x3000;
+        JSR @SUB;
+        TRAP #0x25;
+        @SUB ADD R0, R0, #1;
+        RET;
+    });
+
+    // The trap vector table entry for x25 is at x4A:
+    interp.set_word_unchecked(0x004A, 0x0500);
+    interp.set_word_unchecked(0x0500, lc3b_insn!(ADD R1, R1, #7).into());
+    interp.set_word_unchecked(0x0502, lc3b_insn!(RET).into());
+
+    interp.step();
+    assert_eq!((interp.get_pc(), interp[R7]), (0x3004, 0x3002));
+    assert_eq!(interp.get_call_stack_depth(), 1);
+
+    interp_steps(&mut interp, 3);
+    assert_eq!((interp.get_pc(), interp[R7]), (0x0500, 0x3004));
+
+    interp_steps(&mut interp, 2);
+    assert_eq!(interp.get_pc(), 0x3004);
+    assert_eq!((interp[R0], interp[R1]), (1, 7));
+    assert_eq!(interp.get_call_stack_depth(), 0);
+})}
+
+#[test]
+fn exceptions_use_the_lc3b_vector_table() { with_larger_stack(None, || {
+    let mut interp: Interp = interp_with_memory(lc3b_program! {
+        .ORIG #0x3000;
+        LDW R0, R1, #0;
+    });
+
+    // ACV handler (vector 2, so at x0200 + 2 * 2):
+    interp.set_word_unchecked(0x0204, 0x0600);
+    interp.set_word_unchecked(0x0600, lc3b_insn!(RTI).into());
+
+    interp[R1] = 0x0100;
+    interp[R6] = 0xF000;
+    interp.set_special_reg::<BSP>(0x2000);
+    interp.set_special_reg::<PSR>(0x8002);
+
+    interp.step();
+    assert_eq!(interp.get_pc(), 0x0600);
+    assert!(interp.get_special_reg::<PSR>().in_privileged_mode());
+    assert_eq!(interp[R6], 0x1FFC);
+    assert_eq!(interp.get_word_unchecked(0x1FFC), 0x3002);
+    assert_eq!(interp.get_word_unchecked(0x1FFE), 0x8002);
+
+    interp.step();
+    assert_eq!(interp.get_pc(), 0x3002);
+    assert!(interp.get_special_reg::<PSR>().in_user_mode());
+    assert_eq!(interp[R6], 0xF000);
+})}
+
+#[test]
+fn starts_in_the_os() { with_larger_stack(None, || {
+    let interp: Interp = InterpreterBuilder::<_, PeripheralsShim>::new()
+        .with_defaults()
+        .with_memory(MemoryShim::default())
+        .build()
+        .into();
+
+    assert_eq!(interp.get_pc(), OS_START_ADDR);
+})}
+
+#[test]
+fn works_with_the_simulator() { with_larger_stack(None, || {
+    let mut sim: Simulator<Interp> = Simulator::default();
+    sim.set_fault_handling(FaultHandling::Stop);
+
+    sim.write_word(0x3000, lc3b_insn!(LSHF R0, R0, #1).into());
+    sim.write_word(0x3002, 0xA000);
+    sim.set_register(R0, 3);
+    sim.set_pc(0x3000);
+
+    assert_eq!(sim.step(), None);
+    assert_eq!((sim.get_pc(), sim.get_register(R0)), (0x3002, 6));
+    assert_eq!(sim.read_word(0x3000), lc3b_insn!(LSHF R0, R0, #1).to_word());
+
+    assert_eq!(
+        sim.step(),
+        Some(Event::Error { err: Error::IllegalOpcode { pc: 0x3002, insn: 0xA000 } }),
+    );
+})}
@@ -0,0 +1,121 @@
+//! Checks the interpreter against the reference model in
+//! `lc3_test_infrastructure::model`, one instruction at a time, from random
+//! machine states.
+
+extern crate lc3_test_infrastructure as lti;
+
+use lc3_baseline_sim::interp::{
+    InstructionInterpreter, InstructionInterpreterPeripheralAccess, Interpreter, InterpreterBuilder,
+};
+use lc3_baseline_sim::{BSP, PSR};
+use lc3_isa::strategies::{instruction, word};
+use lc3_isa::{insn, Addr, Reg::{self, *}, Word};
+use lti::model::{machine, Machine};
+use lti::{with_larger_stack, MemoryShim, PeripheralsShim};
+
+use proptest::prelude::*;
+
+type Interp<'a> = Interpreter<'a, MemoryShim, PeripheralsShim<'a>>;
+
+fn interp_for<'a>(m: &Machine) -> Interp<'a> {
+    let mut interp: Interp<'a> = InterpreterBuilder::<_, PeripheralsShim<'a>>::new()
+        .with_defaults()
+        .build();
+
+    for (addr, word) in m.memory.iter() {
+        interp.set_word_unchecked(*addr, *word);
+    }
+    for (reg, word) in Reg::REGS.iter().zip(m.regs.iter()) {
+        interp[*reg] = *word;
+    }
+
+    interp.set_pc(m.pc);
+    interp.set_special_reg::<BSP>(m.saved_sp);
+    interp.set_special_reg::<PSR>(m.psr);
+
+    interp
+}
+
+// Runs one step on the model and on the interpreter and compares the two;
+// states the model can't handle are discarded.
+fn check(mut model: Machine) -> Result<(), TestCaseError> {
+    let before = model.clone();
+    let mut interp = interp_for(&before);
+
+    prop_assume!(model.step().is_ok());
+    let _ = interp.step();
+
+    let regs: Vec<Word> = Reg::REGS.iter().map(|r| interp[*r]).collect();
+    prop_assert_eq!(&regs[..], &model.regs[..], "registers; started from {:#X?}", before);
+    prop_assert_eq!(interp.get_pc(), model.pc, "PC; started from {:#X?}", before);
+    prop_assert_eq!(*interp.get_special_reg::<PSR>(), model.psr, "PSR; started from {:#X?}", before);
+    prop_assert_eq!(*interp.get_special_reg::<BSP>(), model.saved_sp, "BSP; started from {:#X?}", before);
+
+    // Everything the model has written and anything either machine may have
+    // pushed onto a stack:
+    let stacks = [before.regs[6], before.saved_sp]
+        .iter()
+        .flat_map(|sp| sp.saturating_sub(2)..*sp)
+        .collect::<Vec<Addr>>();
+
+    for addr in model.memory.keys().copied().chain(stacks) {
+        if addr < lc3_isa::MEM_MAPPED_START_ADDR {
+            prop_assert_eq!(
+                interp.get_word_unchecked(addr), model.word(addr),
+                "memory at {:#06X}; started from {:#X?}", addr, before
+            );
+        }
+    }
+
+    Ok(())
+}
+
+const CASES: u32 = 2048;
+
+#[test]
+fn instructions_match_the_model() { with_larger_stack(None, || {
+    proptest!(ProptestConfig::with_cases(CASES), |(m in machine(instruction().prop_map(Word::from)))| {
+        check(m)?;
+    });
+})}
+
+// Also covers illegal opcodes.
+#[test]
+fn words_match_the_model() { with_larger_stack(None, || {
+    proptest!(ProptestConfig::with_cases(CASES), |(m in machine(word()))| {
+        check(m)?;
+    });
+})}
+
+// A few spot checks of the model itself:
+
+fn model_with(insn: Word, psr: Word) -> Machine {
+    let mut memory = std::collections::BTreeMap::new();
+    let _ = memory.insert(0x3000, insn);
+    let _ = memory.insert(0x0025, 0x0400);
+
+    Machine { regs: [0, 1, 2, 3, 4, 5, 0x4000, 7], pc: 0x3000, psr, saved_sp: 0x2FF0, memory }
+}
+
+#[test]
+fn model_sets_condition_codes() {
+    let mut m = model_with(insn!(ADD R0, R1, #-2).into(), 0x0002);
+    m.step().unwrap();
+    assert_eq!((m.regs[0], m.cc()), (0xFFFF, (true, false, false)));
+
+    // LEA doesn't:
+    let mut m = model_with(insn!(LEA R0, #-1).into(), 0x0002);
+    m.step().unwrap();
+    assert_eq!((m.regs[0], m.cc()), (0x3000, (false, true, false)));
+}
+
+#[test]
+fn model_traps_from_user_mode() {
+    let mut m = model_with(insn!(TRAP #0x25).into(), 0x8001);
+    m.step().unwrap();
+
+    assert_eq!(m.pc, 0x0400);
+    assert!(!m.in_user_mode());
+    assert_eq!((m.regs[R6 as usize], m.saved_sp), (0x2FEE, 0x4000));
+    assert_eq!((m.word(0x2FEE), m.word(0x2FEF)), (0x3001, 0x8001));
+}
@@ -8,7 +8,8 @@ mod single_instructions {
     use super::*;
     use Reg::*;
 
-    use lti::{interp_test_runner, with_larger_stack};
+    use lc3_baseline_sim::block::BlockInterpreter;
+    use lti::{interp_test_runner, interp_test_runner_for, with_larger_stack};
 
     // Test that the instructions work
     // Test that the unimplemented instructions do <something>
@@ -34,6 +35,21 @@ mod single_instructions {
             let flags = PeripheralInterruptFlags::new();
 
             interp_test_runner::<MemoryShim, PeripheralsShim, _, _>(
+                Vec::new(),
+                insns.clone(),
+                $steps,
+                regs,
+                Some($pc),
+                checks.clone(),
+                (|_p| {}), // (no-op)
+                (|_p| {}), // (no-op)
+                &flags,
+                None,
+                None,
+            );
+
+            // The block interpreter should behave identically:
+            interp_test_runner_for::<BlockInterpreter<MemoryShim, PeripheralsShim>, MemoryShim, PeripheralsShim, _, _>(
                 Vec::new(),
                 insns,
                 $steps,
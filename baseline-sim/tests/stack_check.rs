@@ -0,0 +1,129 @@
+extern crate lc3_test_infrastructure as lti;
+
+use lc3_baseline_sim::BSP_ADDR;
+use lc3_baseline_sim::stack_check::{CallingConvention, StackBounds, StackChecker};
+use lc3_isa::{insn, Reg::*, Word, PSR, TRAP_VECTOR_TABLE_START_ADDR};
+use lc3_traits::control::{Control, Event, ProcessorMode::*, StackViolation};
+use lti::{sim_steps, sim_with_program, with_larger_stack, ShimSim};
+
+const STACK: StackBounds = StackBounds { base: 0x4000, limit: 0x3FFE };
+const SUPERVISOR_STACK: StackBounds = StackBounds { base: 0x0700, limit: 0x0603 };
+
+// A simulator in supervisor mode about to run `program` (at 0x3000) with R6 at
+// the base of `STACK`.
+fn with_program<'a>(program: &[Word], checker: Option<StackChecker>) -> ShimSim<'a> {
+    sim_with_program(program, |sim| {
+        sim.set_register(R6, STACK.base);
+        sim.set_stack_checker(checker);
+    })
+}
+
+#[test]
+fn off_by_default() { with_larger_stack(None, || {
+    let mut sim = with_program(&[insn!(ADD R6, R6, #-4).into(), insn!(STR R0, R6, #-1).into()], None);
+
+    assert!(sim.get_stack_checker().is_none());
+    assert_eq!(sim_steps(&mut sim, 2), vec![None, None]);
+})}
+
+#[test]
+fn overflow() { with_larger_stack(None, || {
+    let checker = StackChecker::new().with_supervisor_stack(STACK);
+    let mut sim = with_program(&[insn!(ADD R6, R6, #-1).into(); 3], Some(checker));
+
+    assert_eq!(sim_steps(&mut sim, 3), vec![None, None, Some(Event::StackViolation {
+        pc: 0x3002,
+        violation: StackViolation::Overflow { stack: Supervisor, sp: 0x3FFD },
+    })]);
+})}
+
+#[test]
+fn underflow() { with_larger_stack(None, || {
+    let checker = StackChecker::new().with_supervisor_stack(STACK);
+    let mut sim = with_program(&[insn!(ADD R6, R6, #1).into()], Some(checker));
+
+    assert_eq!(sim.step(), Some(Event::StackViolation {
+        pc: 0x3000,
+        violation: StackViolation::Underflow { stack: Supervisor, sp: 0x4001 },
+    }));
+})}
+
+#[test]
+fn user_stack() { with_larger_stack(None, || {
+    let checker = StackChecker::new().with_user_stack(STACK);
+    let mut sim = with_program(&[insn!(ADD R6, R6, #-3).into()], Some(checker));
+    sim.write_word(PSR, 0x8002);
+
+    assert_eq!(sim.step(), Some(Event::StackViolation {
+        pc: 0x3000,
+        violation: StackViolation::Overflow { stack: User, sp: 0x3FFD },
+    }));
+})}
+
+#[test]
+fn switching_stacks() { with_larger_stack(None, || {
+    let checker = StackChecker::new()
+        .with_user_stack(STACK)
+        .with_supervisor_stack(SUPERVISOR_STACK)
+        .with_calling_convention(CallingConvention::default());
+    let mut sim = with_program(&[insn!(TRAP #0x30).into(), insn!(ADD R0, R0, #1).into()], Some(checker));
+
+    sim.write_word(PSR, 0x8002);
+    sim.write_word(BSP_ADDR, SUPERVISOR_STACK.base);
+    sim.write_word(TRAP_VECTOR_TABLE_START_ADDR + 0x30, 0x0500);
+    sim.write_word(0x0500, insn!(RTI).into());
+
+    assert_eq!(sim_steps(&mut sim, 3), vec![None, None, None]);
+    assert_eq!(sim.get_pc(), 0x3002);
+})}
+
+#[test]
+fn writes_below_limit() { with_larger_stack(None, || {
+    let checker = StackChecker::new().with_supervisor_stack(STACK);
+    let mut sim = with_program(&[
+        insn!(STR R0, R6, #-2).into(),
+        insn!(STR R0, R6, #-3).into(),
+    ], Some(checker));
+
+    assert_eq!(sim_steps(&mut sim, 2), vec![None, Some(Event::StackViolation {
+        pc: 0x3001,
+        violation: StackViolation::WriteBelowLimit { stack: Supervisor, addr: 0x3FFD },
+    })]);
+})}
+
+#[test]
+fn callee_saved_registers() { with_larger_stack(None, || {
+    let program = [
+        insn!(JSR #1).into(),
+        insn!(ADD R0, R0, #1).into(),
+        insn!(ADD R0, R0, #1).into(),   // R0 isn't callee saved
+        insn!(ADD R2, R2, #1).into(),
+        insn!(RET).into(),
+    ];
+
+    let checker = StackChecker::new().with_calling_convention(CallingConvention::default());
+    let mut sim = with_program(&program, Some(checker));
+    sim.set_register(R2, 5);
+
+    assert_eq!(sim_steps(&mut sim, 4), vec![None, None, None, Some(Event::StackViolation {
+        pc: 0x3004,
+        violation: StackViolation::CalleeSavedRegister { reg: R2, expected: 5, found: 6 },
+    })]);
+
+    // With a convention that lets subroutines clobber R2:
+    let conv = CallingConvention::new(&[R1, R3, R4, R5, R6]);
+    assert!(!conv.is_callee_saved(R2));
+
+    let checker = StackChecker::new().with_calling_convention(conv);
+    let mut sim = with_program(&program, Some(checker));
+    assert_eq!(sim_steps(&mut sim, 5), vec![None; 5]);
+})}
+
+#[test]
+fn default_convention() { with_larger_stack(None, || {
+    let conv = CallingConvention::default();
+
+    assert!(!conv.is_callee_saved(R0));
+    assert!([R1, R2, R3, R4, R5, R6].iter().all(|r| conv.is_callee_saved(*r)));
+    assert!(!conv.is_callee_saved(R7));
+})}
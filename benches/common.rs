@@ -147,8 +147,8 @@ fn device_thread<ReqDec: 'static, RespEnc: 'static, Transp: 'static>(
     mut device: Device<Transp, Sim<'static>, RequestMessage, ResponseMessage, ReqDec, RespEnc>,
     program: MemoryDump,
 ) where
-    ReqDec: Decode<RequestMessage> + Send,
-    RespEnc: Encode<ResponseMessage> + Send,
+    ReqDec: Decode<RequestEnvelope> + Send,
+    RespEnc: Encode<ResponseEnvelope> + Send,
     Transp: Transport<RespEnc::Encoded, ReqDec::Encoded> + Send,
 {
     ThreadBuilder::new()
@@ -174,13 +174,13 @@ lazy_static! {
     static ref RPC_STATE: SyncEventFutureSharedState = SyncEventFutureSharedState::new();
 }
 
-use lc3_traits::control::rpc::{mpsc_sync_pair, MpscTransport, ResponseMessage, RequestMessage};
+use lc3_traits::control::rpc::{mpsc_sync_pair, MpscTransport, ResponseMessage, RequestMessage, ResponseEnvelope, RequestEnvelope};
 use lc3_traits::control::rpc::encoding::Transparent;
 use std::sync::mpsc::{channel, Receiver, Sender};
 
 // TODO: test spin vs. sleep
 #[allow(unused)]
-pub fn remote_simulator/*<C: Control>*/(program: MemoryDump) -> (Sender<()>, Controller<'static, MpscTransport<RequestMessage, ResponseMessage>, SyncEventFutureSharedState>)
+pub fn remote_simulator/*<C: Control>*/(program: MemoryDump) -> (Sender<()>, Controller<'static, MpscTransport<RequestEnvelope, ResponseEnvelope>, SyncEventFutureSharedState>)
 // where
 //     <C as Control>::EventFuture: Sync + Send,
 {
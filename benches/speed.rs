@@ -16,6 +16,7 @@ const ITERS: [Word; 5] = [1, 10, 100, 1000, 10_000];
 
 
 use criterion::{BenchmarkId, Criterion, Throughput, PlotConfiguration, AxisScale};
+use lc3_baseline_sim::block::BlockInterpreter;
 use lc3_baseline_sim::interp::MachineState;
 
 fn bench_fib(c: &mut Criterion) {
@@ -57,6 +58,19 @@ fn bench_fib(c: &mut Criterion) {
                 })
             },
         );
+
+        group.bench_with_input(
+            BenchmarkId::new("Block Interpreter - step_many", *num_iter),
+            num_iter,
+            |b, num| {
+                let mut int: BlockInterpreter<_, _> =
+                    black_box(bare_interpreter(build_fib_memory_image(*num), &flags).into());
+                b.iter(|| {
+                    int.reset();
+                    while let (_, MachineState::Running) = int.step_many(usize::MAX) {}
+                })
+            },
+        );
     }
 }
 
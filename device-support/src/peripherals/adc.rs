@@ -4,9 +4,10 @@ extern crate embedded_hal;
 
 use core::cell::RefCell;
 use core::marker::PhantomData;
+use core::sync::atomic::{AtomicBool, Ordering};
 use embedded_hal::adc::{Channel, OneShot};
 
-pub struct GenericAdcUnit<U, A0, A1, A2, A3, A4, A5, WORD, ADC>
+pub struct GenericAdcUnit<'a, U, A0, A1, A2, A3, A4, A5, WORD, ADC>
 where
     A0: Channel<ADC>,
     A1: Channel<ADC>,
@@ -36,12 +37,17 @@ where
     a4: RefCell<A4>,
     a5: RefCell<A5>,
     pin_states: AdcPinArr<AdcState>,
+    modes: AdcPinArr<AdcMode>,
+    interrupts: AdcPinArr<AdcInterrupt>,
+    sample_period: AdcSamplePeriod,
+    samples: AdcPinArr<Option<u8>>,
+    flags: Option<&'a AdcPinArr<AtomicBool>>,
     phantom: PhantomData<WORD>,
     phantom2: PhantomData<ADC>,
 }
 
-impl<U, A0, A1, A2, A3, A4, A5, WORD, ADC> Default
-    for GenericAdcUnit<U, A0, A1, A2, A3, A4, A5, WORD, ADC>
+impl<'a, U, A0, A1, A2, A3, A4, A5, WORD, ADC> Default
+    for GenericAdcUnit<'a, U, A0, A1, A2, A3, A4, A5, WORD, ADC>
 where
     A0: Channel<ADC>,
     A1: Channel<ADC>,
@@ -62,8 +68,8 @@ where
     }
 }
 
-impl<U, A0, A1, A2, A3, A4, A5, WORD, ADC>
-    GenericAdcUnit<U, A0, A1, A2, A3, A4, A5, WORD, ADC>
+impl<'a, U, A0, A1, A2, A3, A4, A5, WORD, ADC>
+    GenericAdcUnit<'a, U, A0, A1, A2, A3, A4, A5, WORD, ADC>
 where
     A0: Channel<ADC>,
     A1: Channel<ADC>,
@@ -97,6 +103,11 @@ where
             a4: RefCell::new(p4),
             a5: RefCell::new(p5),
             pin_states: AdcPinArr([AdcState::Disabled; AdcPin::NUM_PINS]),
+            modes: AdcPinArr([AdcMode::OneShot; AdcPin::NUM_PINS]),
+            interrupts: AdcPinArr([AdcInterrupt::Disabled; AdcPin::NUM_PINS]),
+            sample_period: ADC_DEFAULT_SAMPLE_PERIOD,
+            samples: AdcPinArr([None; AdcPin::NUM_PINS]),
+            flags: None,
             phantom: PhantomData,
             phantom2: PhantomData,
         }
@@ -125,8 +136,91 @@ macro_rules! adc_read_pin {
 	}
 }
 
-impl<U, A0, A1, A2, A3, A4, A5, WORD, ADC> Adc
-    for GenericAdcUnit<U, A0, A1, A2, A3, A4, A5, WORD, ADC>
+impl<'a, U, A0, A1, A2, A3, A4, A5, WORD, ADC>
+    GenericAdcUnit<'a, U, A0, A1, A2, A3, A4, A5, WORD, ADC>
+where
+    A0: Channel<ADC>,
+    A1: Channel<ADC>,
+    A2: Channel<ADC>,
+    A3: Channel<ADC>,
+    A4: Channel<ADC>,
+    A5: Channel<ADC>,
+    U: OneShot<ADC, WORD, A0>
+        + OneShot<ADC, WORD, A1>
+        + OneShot<ADC, WORD, A2>
+        + OneShot<ADC, WORD, A3>
+        + OneShot<ADC, WORD, A4>
+        + OneShot<ADC, WORD, A5>,
+    WORD: From<u16> + Into<u16>,
+{
+    fn convert(&self, pin: AdcPin) -> Result<u8, AdcReadError> {
+        //let mut adc_unit = self.hal_adc.borrow_mut();
+        //let mut pins = self.hal_pins.borrow_mut();
+
+        let mut adc_reading: Result<u8, AdcReadError> =
+            Err(AdcReadError((pin, AdcState::Disabled)));
+
+        if self.get_state(pin) == AdcState::Enabled {
+            match pin {
+                AdcPin::A0 => {
+                    adc_read_pin!(a0, self, adc_reading);
+                }
+                AdcPin::A1 => {
+                    adc_read_pin!(a1, self, adc_reading);
+                }
+                AdcPin::A2 => {
+                    adc_read_pin!(a2, self, adc_reading);
+                }
+                AdcPin::A3 => {
+                    adc_read_pin!(a3, self, adc_reading);
+                }
+                AdcPin::A4 => {
+                    adc_read_pin!(a4, self, adc_reading);
+                }
+                AdcPin::A5 => {
+                    adc_read_pin!(a5, self, adc_reading);
+                }
+            }
+        }
+
+        adc_reading
+    }
+
+    /// Does a conversion for every enabled pin that's in
+    /// [continuous](AdcMode::Continuous) mode and raises interrupts for the
+    /// conversions that call for one.
+    ///
+    /// This unit has no time base of its own so it's up to the user to call
+    /// this once every [sample period](Adc::get_sample_period) (i.e. from a
+    /// timer interrupt).
+    pub fn sample(&mut self) {
+        for pin in ADC_PINS.iter() {
+            if self.pin_states[*pin] != AdcState::Enabled || self.modes[*pin] != AdcMode::Continuous {
+                continue;
+            }
+
+            let sample = match self.convert(*pin) {
+                Ok(sample) => sample,
+                Err(_) => continue,
+            };
+            let prev = self.samples[*pin].replace(sample);
+
+            let fire = match (self.interrupts[*pin], prev) {
+                (AdcInterrupt::Disabled, _) => false,
+                (AdcInterrupt::ConversionComplete, _) => true,
+                (AdcInterrupt::ThresholdCrossing(t), Some(prev)) => (prev >= t) != (sample >= t),
+                (AdcInterrupt::ThresholdCrossing(_), None) => false,
+            };
+
+            if let (true, Some(flags)) = (fire, self.flags) {
+                flags[*pin].store(true, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+impl<'a, U, A0, A1, A2, A3, A4, A5, WORD, ADC> Adc<'a>
+    for GenericAdcUnit<'a, U, A0, A1, A2, A3, A4, A5, WORD, ADC>
 where
     A0: Channel<ADC>,
     A1: Channel<ADC>,
@@ -159,6 +253,7 @@ where
     ) -> Result<(), AdcMiscError> {
         //let mut pins = self.pin_.borrow_mut();
         self.pin_states[pin] = state;
+        self.samples[pin] = None;
         Ok(())
     }
     fn get_state(&self, pin: AdcPin) -> AdcState {
@@ -178,36 +273,10 @@ where
     }
 
     fn read(&self, pin: AdcPin) -> Result<u8, AdcReadError> {
-        //let mut adc_unit = self.hal_adc.borrow_mut();
-        //let mut pins = self.hal_pins.borrow_mut();
-
-        let mut adc_reading: Result<u8, AdcReadError> =
-            Err(AdcReadError((pin, AdcState::Disabled)));
-
-        if self.get_state(pin) == AdcState::Enabled {
-            match pin {
-                AdcPin::A0 => {
-                    adc_read_pin!(a0, self, adc_reading);
-                }
-                AdcPin::A1 => {
-                    adc_read_pin!(a1, self, adc_reading);
-                }
-                AdcPin::A2 => {
-                    adc_read_pin!(a2, self, adc_reading);
-                }
-                AdcPin::A3 => {
-                    adc_read_pin!(a3, self, adc_reading);
-                }
-                AdcPin::A4 => {
-                    adc_read_pin!(a4, self, adc_reading);
-                }
-                AdcPin::A5 => {
-                    adc_read_pin!(a5, self, adc_reading);
-                }
-            }
+        match (self.get_state(pin), self.modes[pin], self.samples[pin]) {
+            (AdcState::Enabled, AdcMode::Continuous, Some(sample)) => Ok(sample),
+            _ => self.convert(pin),
         }
-
-        adc_reading
     }
     #[inline]
     fn read_all(&self) -> AdcPinArr<Result<u8, AdcReadError>> {
@@ -218,6 +287,47 @@ where
 
         readings
     }
+
+    // Continuous mode relies on the user calling `sample` (see above).
+    fn set_mode(&mut self, pin: AdcPin, mode: AdcMode) -> Result<(), AdcMiscError> {
+        self.modes[pin] = mode;
+        self.samples[pin] = None;
+        Ok(())
+    }
+    fn get_mode(&self, pin: AdcPin) -> AdcMode {
+        self.modes[pin]
+    }
+
+    fn set_sample_period(&mut self, period: AdcSamplePeriod) -> Result<(), AdcMiscError> {
+        self.sample_period = period;
+        Ok(())
+    }
+    fn get_sample_period(&self) -> AdcSamplePeriod {
+        self.sample_period
+    }
+
+    fn set_interrupt(&mut self, pin: AdcPin, interrupt: AdcInterrupt) -> Result<(), AdcMiscError> {
+        self.interrupts[pin] = interrupt;
+        Ok(())
+    }
+    fn get_interrupt(&self, pin: AdcPin) -> AdcInterrupt {
+        self.interrupts[pin]
+    }
+
+    fn register_interrupt_flags(&mut self, flags: &'a AdcPinArr<AtomicBool>) {
+        self.flags = Some(flags);
+    }
+    fn interrupt_occurred(&self, pin: AdcPin) -> bool {
+        match self.flags {
+            Some(flags) => self.interrupts_enabled(pin) && flags[pin].load(Ordering::SeqCst),
+            None => false,
+        }
+    }
+    fn reset_interrupt_flag(&mut self, pin: AdcPin) {
+        if let Some(flags) = self.flags {
+            flags[pin].store(false, Ordering::SeqCst);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -461,4 +571,44 @@ mod tests {
         );
         assert_eq!(readings[5], Ok(0xff));
     }
+
+    #[test]
+    fn continuous_threshold_test() {
+        use core::sync::atomic::AtomicBool;
+        use lc3_traits::peripherals::adc::{AdcInterrupt, AdcMode, AdcPinArr};
+
+        let expectations = [
+            Transaction::read(0, 0x100),
+            Transaction::read(0, 0x900),
+            Transaction::read(0, 0xa00),
+        ];
+
+        let flags = AdcPinArr([(); AdcPin::NUM_PINS].map(|_| AtomicBool::new(false)));
+        let mut generic_adc = GenericAdcUnit::<_, _, _, _, _, _, _, _, _>::new(
+            Mock::<u16>::new(&expectations),
+            MockChan0,
+            MockChan1,
+            MockChan2,
+            MockChan0.clone(),
+            MockChan1.clone(),
+            MockChan2.clone(),
+        );
+        generic_adc.register_interrupt_flags(&flags);
+        generic_adc.set_state(AdcPin::A0, AdcState::Enabled).unwrap();
+        generic_adc.set_mode(AdcPin::A0, AdcMode::Continuous).unwrap();
+        generic_adc.set_interrupt(AdcPin::A0, AdcInterrupt::ThresholdCrossing(0x80)).unwrap();
+
+        generic_adc.sample();
+        assert_eq!(generic_adc.read(AdcPin::A0), Ok(0x10));
+        assert!(!generic_adc.interrupt_occurred(AdcPin::A0));
+
+        generic_adc.sample();
+        assert_eq!(generic_adc.read(AdcPin::A0), Ok(0x90));
+        assert!(generic_adc.interrupt_occurred(AdcPin::A0));
+        generic_adc.reset_interrupt_flag(AdcPin::A0);
+
+        generic_adc.sample();
+        assert_eq!(generic_adc.read(AdcPin::A0), Ok(0xa0));
+        assert!(!generic_adc.interrupt_occurred(AdcPin::A0));
+    }
 }
@@ -0,0 +1,299 @@
+//! An optional checksum layer for the postcard wire format.
+//!
+//! COBS framing tells us where messages start and end but nothing about
+//! whether the bytes in between survived the trip; a flipped bit in a
+//! postcard encoded message usually still decodes, just as a different
+//! message. [`Crc`] appends a CRC of each (postcard encoded) message and checks
+//! and strips it on the other end so that these messages get dropped instead.
+//!
+//! It's meant to sit between the postcard layer and the COBS layer:
+//!
+//! ```text
+//! message -> [postcard] -> [crc] -> [cobs] -> wire
+//! ```
+//!
+//! [`postcard_crc_encode`] and [`postcard_crc_decode`] build exactly this
+//! using [`ChainedEncode`] and [`ChainedDecode`]. Both ends of a connection
+//! need to agree on whether the layer is there (and which [`Checksum`] it
+//! uses).
+
+use super::{CobsFraming, PostcardDecode, PostcardEncode};
+use crate::util::Fifo;
+
+use lc3_traits::control::rpc::{Decode, Encode};
+use lc3_traits::control::rpc::encoding::{ChainedDecode, ChainedEncode};
+
+use serde::{Deserialize, Serialize};
+
+use core::fmt::{self, Debug, Display};
+use core::marker::PhantomData;
+
+/// A checksum that [`Crc`] can use.
+pub trait Checksum {
+    /// Number of bytes of the checksum that go on the wire (at most 4).
+    const LEN: usize;
+
+    fn checksum(data: &[u8]) -> u32;
+}
+
+/// CRC-16/CCITT-FALSE (poly: `0x1021`, init: `0xFFFF`, not reflected).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Crc16;
+
+impl Checksum for Crc16 {
+    const LEN: usize = 2;
+
+    fn checksum(data: &[u8]) -> u32 {
+        let mut crc: u16 = 0xFFFF;
+
+        for byte in data {
+            crc ^= (*byte as u16) << 8;
+
+            for _ in 0..8 {
+                crc = if crc & 0x8000 != 0 {
+                    (crc << 1) ^ 0x1021
+                } else {
+                    crc << 1
+                };
+            }
+        }
+
+        crc as u32
+    }
+}
+
+/// CRC-32/ISO-HDLC; the one Ethernet and zlib use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Crc32;
+
+impl Checksum for Crc32 {
+    const LEN: usize = 4;
+
+    fn checksum(data: &[u8]) -> u32 {
+        let mut crc: u32 = !0;
+
+        for byte in data {
+            crc ^= *byte as u32;
+
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+
+        !crc
+    }
+}
+
+/// Appends a checksum (little-endian) when encoding; checks it and strips it
+/// when decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc<C: Checksum = Crc16>(PhantomData<C>);
+
+impl<C: Checksum> Crc<C> {
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C: Checksum> Default for Crc<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Checksum> Encode<Fifo<u8>> for Crc<C> {
+    type Encoded = Fifo<u8>;
+
+    fn encode(&mut self, message: &Fifo<u8>) -> Fifo<u8> {
+        let mut out = Fifo::new();
+        let checksum = C::checksum(message.as_slice()).to_le_bytes();
+
+        out.push_slice(message.as_slice())
+            .and_then(|()| out.push_slice(&checksum[..C::LEN]))
+            .expect("a successful encode");
+
+        out
+    }
+}
+
+impl<C: Checksum> Decode<Fifo<u8>> for Crc<C> {
+    type Encoded = Fifo<u8>;
+    type Err = CrcError;
+
+    fn decode(&mut self, encoded: &Fifo<u8>) -> Result<Fifo<u8>, CrcError> {
+        let data = encoded.as_slice();
+        if data.len() < C::LEN {
+            return Err(CrcError::TooShort);
+        }
+
+        let (message, trailer) = data.split_at(data.len() - C::LEN);
+
+        let mut received = [0; 4];
+        received[..C::LEN].copy_from_slice(trailer);
+        let received = u32::from_le_bytes(received);
+
+        let computed = C::checksum(message);
+        if received != computed {
+            return Err(CrcError::Mismatch { received, computed });
+        }
+
+        let mut out = Fifo::new();
+        out.push_slice(message).unwrap();
+
+        Ok(out)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrcError {
+    /// The message was too short to even hold a checksum.
+    TooShort,
+    Mismatch { received: u32, computed: u32 },
+}
+
+impl Display for CrcError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrcError::TooShort => write!(fmt, "message is too short to have a checksum"),
+            CrcError::Mismatch { received, computed } => write!(fmt,
+                "checksum mismatch: message says {:#X}, we got {:#X}",
+                received, computed,
+            ),
+        }
+    }
+}
+
+type PostcardFifo<Inp> = PostcardEncode<Inp, Fifo<u8>, fn() -> Fifo<u8>>;
+
+/// postcard, then a [`Crc`], then COBS; see [`postcard_crc_encode`].
+pub type PostcardCrcEncode<Inp, C = Crc16> = ChainedEncode<
+    Inp,
+    Fifo<u8>,
+    ChainedEncode<Inp, Fifo<u8>, PostcardFifo<Inp>, Crc<C>>,
+    CobsFraming,
+>;
+
+/// The other end of [`PostcardCrcEncode`]; see [`postcard_crc_decode`].
+pub type PostcardCrcDecode<Out, C = Crc16> = ChainedDecode<
+    Out,
+    Fifo<u8>,
+    ChainedDecode<Out, Fifo<u8>, PostcardDecode<Out, Fifo<u8>>, Crc<C>>,
+    CobsFraming,
+>;
+
+/// Like [`PostcardEncode::with_fifo`] but with a checksum.
+pub fn postcard_crc_encode<Inp, C>() -> PostcardCrcEncode<Inp, C>
+where
+    Inp: Debug + Serialize,
+    C: Checksum,
+{
+    ChainedEncode::with(
+        ChainedEncode::with(PostcardEncode::new(Fifo::new as fn() -> _), Crc::new()),
+        CobsFraming,
+    )
+}
+
+/// Decodes what [`postcard_crc_encode`] produces; messages that fail the
+/// checksum produce [`FrameError::Crc`](super::FrameError::Crc).
+pub fn postcard_crc_decode<Out, C>() -> PostcardCrcDecode<Out, C>
+where
+    Out: Debug,
+    for<'de> Out: Deserialize<'de>,
+    C: Checksum,
+{
+    ChainedDecode::with(
+        ChainedDecode::with(PostcardDecode::new(), Crc::new()),
+        CobsFraming,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::encoding::{Cobs, FrameError};
+
+    use lc3_traits::control::rpc::{RequestMessage, RequestEnvelope, Envelope};
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn check_values() {
+        assert_eq!(Crc16::checksum(b"123456789"), 0x29B1);
+        assert_eq!(Crc32::checksum(b"123456789"), 0xCBF4_3926);
+    }
+
+    fn round_trip<C: Checksum>() {
+        let mut enc = postcard_crc_encode::<RequestEnvelope, C>();
+        let mut dec = postcard_crc_decode::<RequestEnvelope, C>();
+
+        for message in [
+            Envelope::new(0, RequestMessage::GetPc),
+            Envelope::new(0xFFFF, RequestMessage::SetPc { addr: 0x3000 }),
+            Envelope::new(23, RequestMessage::WriteWord { addr: 0, word: 0 }),
+        ] {
+            let encoded = enc.encode(&message);
+
+            assert_eq!(encoded.as_slice().iter().filter(|b| **b == 0).count(), 1);
+            assert_eq!(dec.decode(&encoded), Ok(message));
+        }
+    }
+
+    #[test]
+    fn round_trip_crc16() { round_trip::<Crc16>() }
+
+    #[test]
+    fn round_trip_crc32() { round_trip::<Crc32>() }
+
+    #[test]
+    fn flipped_bits_are_caught() {
+        let mut enc = postcard_crc_encode::<RequestEnvelope, Crc16>();
+        let mut dec = postcard_crc_decode::<RequestEnvelope, Crc16>();
+
+        let encoded = enc.encode(&Envelope::new(7, RequestMessage::SetPc { addr: 0x3000 }));
+
+        // Skip the COBS header byte and the sentinel.
+        for idx in 1..(encoded.length() - 1) {
+            for bit in 0..8 {
+                let mut garbled = Fifo::new();
+                garbled.push_slice(encoded.as_slice()).unwrap();
+                garbled[idx] ^= 1 << bit;
+
+                assert!(
+                    dec.decode(&garbled).is_err(),
+                    "flipping bit {} of byte {} wasn't noticed", bit, idx,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_messages_are_caught() {
+        let mut dec = postcard_crc_decode::<RequestEnvelope, Crc32>();
+
+        let mut short = Fifo::new();
+        short.push_slice(&[3, 1, 2, 0]).unwrap();
+
+        assert_eq!(dec.decode(&short), Err(FrameError::Crc(CrcError::TooShort)));
+    }
+
+    #[test]
+    fn same_framing_as_postcard_cobs() {
+        let message = Envelope::new(0x100, RequestMessage::WriteWord { addr: 0, word: 0x0F00 });
+
+        let mut plain = PostcardEncode::<RequestEnvelope, _, _>::new(|| Cobs::try_new(Fifo::new()).unwrap());
+        let mut layered = ChainedEncode::with(
+            PostcardEncode::new(Fifo::new as fn() -> _),
+            CobsFraming,
+        );
+
+        let expected = plain.encode(&message);
+        assert_eq!(layered.encode(&message).as_slice(), expected.as_slice());
+
+        let mut dec = ChainedDecode::with(PostcardDecode::<RequestEnvelope, Fifo<u8>>::new(), CobsFraming);
+        assert_eq!(dec.decode(&expected), Ok(message));
+    }
+}
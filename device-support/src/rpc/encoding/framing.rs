@@ -0,0 +1,133 @@
+//! COBS framing as its own encoding layer.
+//!
+//! [`PostcardEncode::with_fifo`](super::PostcardEncode::with_fifo) does the
+//! COBS encoding as part of serializing which is fine until you want to put
+//! something (i.e. a [`Crc`](super::Crc)) between the two. [`CobsFraming`]
+//! produces the same bytes on the wire (including the `0` sentinel) but works
+//! on already serialized messages.
+
+use super::CrcError;
+use crate::util::Fifo;
+
+use lc3_traits::control::rpc::{Decode, Encode};
+
+use postcard::ser_flavors::{Cobs, Flavor as SerFlavor};
+
+use core::convert::Infallible;
+use core::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CobsFraming;
+
+impl Encode<Fifo<u8>> for CobsFraming {
+    type Encoded = Fifo<u8>;
+
+    fn encode(&mut self, message: &Fifo<u8>) -> Fifo<u8> {
+        Cobs::try_new(Fifo::new())
+            .and_then(|mut cobs| {
+                cobs.try_extend(message.as_slice())?;
+                cobs.finalize()
+            })
+            .expect("a successful encode")
+    }
+}
+
+impl Decode<Fifo<u8>> for CobsFraming {
+    type Encoded = Fifo<u8>;
+    type Err = CobsError;
+
+    // Transports generally strip the sentinel but we don't mind if it's still
+    // there.
+    fn decode(&mut self, encoded: &Fifo<u8>) -> Result<Fifo<u8>, CobsError> {
+        let data = encoded.as_slice();
+        let mut out = Fifo::new();
+        let mut idx = 0;
+
+        while idx < data.len() {
+            let code = data[idx] as usize;
+            if code == 0 {
+                break;
+            }
+            idx += 1;
+
+            for _ in 1..code {
+                match data.get(idx) {
+                    None | Some(0) => return Err(CobsError::Truncated),
+                    Some(b) => out.push(*b).map_err(|()| CobsError::TooLong)?,
+                }
+                idx += 1;
+            }
+
+            // Every block except the last one and the ones that are maximal
+            // length stands in for a zero:
+            let at_end = data.get(idx).map(|b| *b == 0).unwrap_or(true);
+            if code != 0xFF && !at_end {
+                out.push(0).map_err(|()| CobsError::TooLong)?;
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CobsError {
+    /// A block claimed to be longer than what was left of the message.
+    Truncated,
+    /// The decoded message doesn't fit in a [`Fifo`].
+    TooLong,
+}
+
+impl Display for CobsError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CobsError::Truncated => write!(fmt, "COBS frame ended early"),
+            CobsError::TooLong => write!(fmt, "COBS frame is too long"),
+        }
+    }
+}
+
+/// Everything that can go wrong decoding a message that's been through the
+/// layers in this module (i.e. with
+/// [`postcard_crc_decode`](super::postcard_crc_decode)).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    Cobs(CobsError),
+    Crc(CrcError),
+    Postcard(postcard::Error),
+}
+
+impl Display for FrameError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Cobs(e) => Display::fmt(e, fmt),
+            FrameError::Crc(e) => Display::fmt(e, fmt),
+            FrameError::Postcard(e) => write!(fmt, "failed to deserialize: {}", e),
+        }
+    }
+}
+
+impl From<CobsError> for FrameError {
+    fn from(err: CobsError) -> Self {
+        FrameError::Cobs(err)
+    }
+}
+
+impl From<CrcError> for FrameError {
+    fn from(err: CrcError) -> Self {
+        FrameError::Crc(err)
+    }
+}
+
+impl From<postcard::Error> for FrameError {
+    fn from(err: postcard::Error) -> Self {
+        FrameError::Postcard(err)
+    }
+}
+
+// For `ChainedDecode::new`:
+impl From<Infallible> for FrameError {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
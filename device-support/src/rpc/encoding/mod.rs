@@ -1,6 +1,12 @@
 mod postcard;
+mod framing;
+mod crc;
 
 pub use self::postcard::PostcardEncode;
 pub use self::postcard::PostcardDecode;
 
+pub use self::framing::{CobsFraming, CobsError, FrameError};
+pub use self::crc::{Checksum, Crc, Crc16, Crc32, CrcError};
+pub use self::crc::{PostcardCrcEncode, PostcardCrcDecode, postcard_crc_encode, postcard_crc_decode};
+
 pub use ::postcard::ser_flavors::{Cobs, Slice};
@@ -1,5 +1,6 @@
 //! TODO!
 
+use super::FrameError;
 use crate::util::Fifo;
 
 use lc3_traits::control::rpc::{Encode, Decode};
@@ -8,6 +9,7 @@ use serde::{Serialize, Deserialize};
 use postcard::ser_flavors::{Flavor as SerFlavor, Cobs};
 use postcard::serialize_with_flavor;
 use postcard::take_from_bytes_cobs;
+use postcard::from_bytes;
 
 use core::fmt::Debug;
 use core::marker::PhantomData;
@@ -181,6 +183,21 @@ mod decode {
                 .map(|(m, _)| m)
         }
     }
+
+    // Messages that have already been through another layer (i.e.
+    // `CobsFraming`) and are just plain postcard.
+    impl<Out> Decode<Out> for PostcardDecode<Out, Fifo<u8>>
+    where
+        Out: Debug,
+        for<'de> Out: Deserialize<'de>,
+    {
+        type Encoded = Fifo<u8>;
+        type Err = FrameError;
+
+        fn decode(&mut self, encoded: &Fifo<u8>) -> Result<Out, FrameError> {
+            from_bytes(encoded.as_slice()).map_err(Into::into)
+        }
+    }
 }
 
 impl SerFlavor for Fifo<u8> {
@@ -0,0 +1,577 @@
+//! Recording RPC traffic and playing it back.
+//!
+//! [`RecordingTransport`] wraps another transport and writes every frame that
+//! goes through it, along with which end sent it and when, to a capture file.
+//! [`Capture::print`] decodes a capture back into the
+//! [`RequestMessage`]s and [`ResponseMessage`]s it contains and
+//! [`ReplayTransport`] plays one back against a [`Controller`] or a
+//! [`Device`] (i.e. to reproduce a bug report without the board it happened
+//! on).
+//!
+//! Capture files are plain text with one frame per line:
+//!
+//! ```text
+//! # recorded on the controller side
+//!      0.000125 controller 0a000000dd4f8e1f...
+//!      0.002047 device 0a0000000101dd4f...
+//! ```
+//!
+//! That is: seconds since the recording started, the end that sent the frame,
+//! and the frame's bytes in hex. Lines starting with `#` are comments (feel
+//! free to annotate captures you attach to bug reports).
+//!
+//! Frames are recorded *encoded*, exactly as the transport saw them, so the
+//! capture is only as useful as the encoding is reversible; captures of
+//! transports that carry bytes (i.e. [`Fifo<u8>`]) or strings work, ones of
+//! transports that pass messages around as is don't (see [`Captured`]).
+//!
+//! [`RequestMessage`]: lc3_traits::control::rpc::RequestMessage
+//! [`ResponseMessage`]: lc3_traits::control::rpc::ResponseMessage
+//! [`Controller`]: lc3_traits::control::rpc::Controller
+//! [`Device`]: lc3_traits::control::rpc::Device
+
+use crate::util::Fifo;
+
+use lc3_traits::control::rpc::{Decode, RequestEnvelope, ResponseEnvelope, Transport};
+use lc3_traits::control::{Identifier, Version, version_from_crate};
+
+use std::cell::{Cell, RefCell};
+use std::convert::Infallible;
+use std::fmt::{self, Debug, Display, Write as _};
+use std::io::{self, BufRead, ErrorKind, Write};
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// An end of an RPC session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// The end that sends requests (i.e. a [`Controller`](lc3_traits::control::rpc::Controller)).
+    Controller,
+    /// The end that sends responses (i.e. a [`Device`](lc3_traits::control::rpc::Device)).
+    Device,
+}
+
+impl Side {
+    pub fn other(self) -> Self {
+        match self {
+            Side::Controller => Side::Device,
+            Side::Device => Side::Controller,
+        }
+    }
+}
+
+impl Display for Side {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.pad(match self {
+            Side::Controller => "controller",
+            Side::Device => "device",
+        })
+    }
+}
+
+impl FromStr for Side {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        match s {
+            "controller" => Ok(Side::Controller),
+            "device" => Ok(Side::Device),
+            _ => Err(ParseError::BadSide),
+        }
+    }
+}
+
+/// One encoded message, as it went over the wire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Time since the recording started.
+    pub at: Duration,
+    pub from: Side,
+    pub bytes: Vec<u8>,
+}
+
+impl Display for Frame {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{} {} ", Timestamp(self.at), self.from)?;
+        self.bytes.iter().try_for_each(|b| write!(fmt, "{:02x}", b))
+    }
+}
+
+impl FromStr for Frame {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let mut fields = s.split_whitespace();
+        let mut field = |name| fields.next().ok_or(ParseError::MissingField(name));
+
+        let at = field("timestamp")?
+            .parse::<f64>()
+            .ok()
+            .filter(|t| t.is_finite() && *t >= 0.0)
+            .map(Duration::from_secs_f64)
+            .ok_or(ParseError::BadTimestamp)?;
+        let from = field("side")?.parse()?;
+
+        let hex = field("bytes")?;
+        if hex.len() % 2 != 0 {
+            return Err(ParseError::BadHex);
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| hex.get(i..(i + 2)).and_then(|b| u8::from_str_radix(b, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(ParseError::BadHex)?;
+
+        Ok(Frame { at, from, bytes })
+    }
+}
+
+// Seconds, to the microsecond.
+struct Timestamp(Duration);
+
+impl Display for Timestamp {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{:>6}.{:06}", self.0.as_secs(), self.0.subsec_micros())
+    }
+}
+
+/// Ways in which a line of a capture file can be malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    MissingField(&'static str),
+    BadTimestamp,
+    BadSide,
+    BadHex,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingField(name) => write!(fmt, "missing the {}", name),
+            ParseError::BadTimestamp => write!(fmt, "invalid timestamp"),
+            ParseError::BadSide => write!(fmt, "expected `controller` or `device`"),
+            ParseError::BadHex => write!(fmt, "invalid hex"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError { }
+
+/// Encoded messages that can be put in a capture.
+pub trait Captured: Sized {
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// `None` if `bytes` can't be turned back into this type.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl Captured for Fifo<u8> {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.as_slice().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut fifo = Fifo::new();
+        fifo.push_slice(bytes).ok()?;
+
+        Some(fifo)
+    }
+}
+
+impl Captured for Vec<u8> {
+    fn to_bytes(&self) -> Vec<u8> { self.clone() }
+    fn from_bytes(bytes: &[u8]) -> Option<Self> { Some(bytes.to_vec()) }
+}
+
+impl Captured for String {
+    fn to_bytes(&self) -> Vec<u8> { self.as_bytes().to_vec() }
+    fn from_bytes(bytes: &[u8]) -> Option<Self> { String::from_utf8(bytes.to_vec()).ok() }
+}
+
+/// The frames of a recorded session, in the order they were recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capture {
+    pub frames: Vec<Frame>,
+}
+
+impl Capture {
+    /// Reads a capture file; blank lines and comments are skipped.
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let mut frames = Vec::new();
+
+        for (num, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let frame = line.parse().map_err(|e| io::Error::new(
+                ErrorKind::InvalidData,
+                format!("line {}: {}", num + 1, e),
+            ))?;
+            frames.push(frame);
+        }
+
+        Ok(Self { frames })
+    }
+
+    pub fn write(&self, mut out: impl Write) -> io::Result<()> {
+        self.frames.iter().try_for_each(|f| writeln!(out, "{}", f))
+    }
+
+    /// Writes out the messages in the capture, one per line.
+    ///
+    /// Frames sent by the controller are decoded as requests with `req_dec`;
+    /// frames sent by the device are decoded as responses with `resp_dec`.
+    /// Frames that fail to decode are printed as is, along with the error.
+    pub fn print<RD, PD>(&self, mut out: impl Write, mut req_dec: RD, mut resp_dec: PD) -> io::Result<()>
+    where
+        RD: Decode<RequestEnvelope>,
+        RD::Encoded: Captured,
+        PD: Decode<ResponseEnvelope>,
+        PD::Encoded: Captured,
+    {
+        fn decode<M: Debug, D: Decode<M>>(dec: &mut D, bytes: &[u8]) -> Result<M, String>
+        where
+            D::Encoded: Captured,
+        {
+            let encoded = D::Encoded::from_bytes(bytes).ok_or_else(|| "can't be decoded".to_string())?;
+            dec.decode(&encoded).map_err(|e| format!("{:?}", e))
+        }
+
+        for frame in self.frames.iter() {
+            let message = match frame.from {
+                Side::Controller => decode(&mut req_dec, &frame.bytes).map(|e| (e.seq, format!("{:?}", e.message))),
+                Side::Device => decode(&mut resp_dec, &frame.bytes).map(|e| (e.seq, format!("{:?}", e.message))),
+            };
+
+            write!(out, "{} {:>10} ", Timestamp(frame.at), frame.from)?;
+            match message {
+                Ok((seq, message)) => writeln!(out, "#{:<5} {}", seq, message)?,
+                Err(err) => {
+                    let mut hex = String::with_capacity(frame.bytes.len() * 2);
+                    frame.bytes.iter().for_each(|b| write!(hex, "{:02x}", b).unwrap());
+
+                    writeln!(out, "<{}> {}", err, hex)?
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Wraps a transport and records every frame sent or received through it; see
+/// the [module docs](self).
+///
+/// The frames are written out (and flushed) as they go by so that a capture is
+/// still useful if the program recording it crashes. If writing fails we stop
+/// recording (the transport keeps working); see
+/// [`RecordingTransport::take_error`].
+///
+/// This reports the same [`ID`](Transport::ID) and [`VER`](Transport::VER) as
+/// the transport it wraps so that recording doesn't change what's recorded.
+#[derive(Debug)]
+pub struct RecordingTransport<T, W: Write> {
+    inner: T,
+    side: Side,
+    out: RefCell<W>,
+    start: Instant,
+    error: RefCell<Option<io::Error>>,
+}
+
+impl<T, W: Write> RecordingTransport<T, W> {
+    /// `side` is the end of the session `inner` belongs to.
+    pub fn new(inner: T, side: Side, mut out: W) -> Self {
+        let error = writeln!(out, "# recorded on the {} side", side).err();
+
+        Self {
+            inner,
+            side,
+            out: RefCell::new(out),
+            start: Instant::now(),
+            error: RefCell::new(error),
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> (T, W) {
+        (self.inner, self.out.into_inner())
+    }
+
+    /// The error that made us stop recording, if there was one.
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.error.borrow_mut().take()
+    }
+
+    fn record(&self, from: Side, bytes: Vec<u8>) {
+        if self.error.borrow().is_some() {
+            return;
+        }
+
+        let frame = Frame { at: self.start.elapsed(), from, bytes };
+        let mut out = self.out.borrow_mut();
+
+        if let Err(e) = writeln!(out, "{}", frame).and_then(|()| out.flush()) {
+            *self.error.borrow_mut() = Some(e);
+        }
+    }
+}
+
+impl<T, W, S, R> Transport<S, R> for RecordingTransport<T, W>
+where
+    T: Transport<S, R>,
+    W: Write,
+    S: Captured,
+    R: Captured,
+{
+    type RecvErr = T::RecvErr;
+    type SendErr = T::SendErr;
+
+    const ID: Identifier = T::ID;
+    const VER: Version = T::VER;
+
+    fn send(&self, message: S) -> Result<(), Self::SendErr> {
+        self.record(self.side, message.to_bytes());
+        self.inner.send(message)
+    }
+
+    fn get(&self) -> Result<R, Option<Self::RecvErr>> {
+        let message = self.inner.get()?;
+        self.record(self.side.other(), message.to_bytes());
+
+        Ok(message)
+    }
+
+    fn num_get_errors(&self) -> u64 {
+        self.inner.num_get_errors()
+    }
+
+    fn record_get_error(&self) {
+        self.inner.record_get_error()
+    }
+}
+
+/// A frame sent during a replay that doesn't match the capture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// `None` if the capture didn't have any more frames from this side.
+    pub expected: Option<Frame>,
+    pub actual: Vec<u8>,
+}
+
+/// Plays back a [`Capture`], standing in for one end of the recorded session.
+///
+/// The transport hands out the frames sent by the end it's standing in for
+/// and checks the frames it's sent against the ones the other end sent; i.e.
+/// to replay a session against a [`Device`](lc3_traits::control::rpc::Device),
+/// pass [`Side::Device`] and give the transport to a device: it'll get the
+/// recorded requests and its responses will be compared with the recorded
+/// responses. It doesn't matter which end of the session the capture was
+/// recorded on.
+///
+/// Playback ignores the timestamps and keeps to the recorded order instead: a
+/// frame is only handed out once everything that was sent before it (in the
+/// capture) has been sent again. If the replay goes differently than the
+/// recording did, it stops making progress; check
+/// [`ReplayTransport::mismatches`] to see where things went wrong.
+///
+/// Note that a device's answer to `GetDeviceInfo` includes the ID of the
+/// transport it's using which will differ from the recording (unless the
+/// capture was recorded with another `ReplayTransport`).
+#[derive(Debug)]
+pub struct ReplayTransport<E> {
+    frames: Vec<Frame>,
+    side: Side,
+    // Index of the next frame to hand out and of the next frame we expect to
+    // be sent.
+    next_in: Cell<usize>,
+    next_out: Cell<usize>,
+    mismatches: RefCell<Vec<Mismatch>>,
+    _encoded: PhantomData<E>,
+}
+
+impl<E> ReplayTransport<E> {
+    /// `side` is the end that `capture` is played back against.
+    pub fn new(capture: Capture, side: Side) -> Self {
+        let this = Self {
+            frames: capture.frames,
+            side,
+            next_in: Cell::new(0),
+            next_out: Cell::new(0),
+            mismatches: RefCell::new(Vec::new()),
+            _encoded: PhantomData,
+        };
+
+        this.next_in.set(this.find(0, side.other()));
+        this.next_out.set(this.find(0, side));
+        this
+    }
+
+    // Index of the first frame at or after `from` sent by `side`.
+    fn find(&self, from: usize, side: Side) -> usize {
+        self.frames.iter()
+            .skip(from)
+            .position(|f| f.from == side)
+            .map(|idx| from + idx)
+            .unwrap_or(self.frames.len())
+    }
+
+    /// The frames sent so far that didn't match the capture.
+    pub fn mismatches(&self) -> Vec<Mismatch> {
+        self.mismatches.borrow().clone()
+    }
+
+    /// Whether every frame in the capture has been played back (and sent).
+    pub fn finished(&self) -> bool {
+        self.next_in.get() == self.frames.len() && self.next_out.get() == self.frames.len()
+    }
+}
+
+impl<E: Captured> Transport<E, E> for ReplayTransport<E> {
+    type RecvErr = Infallible;
+    type SendErr = Infallible;
+
+    const ID: Identifier = Identifier::new_from_str_that_crashes_on_invalid_inputs("RPLY");
+    const VER: Version = version_from_crate!();
+
+    fn send(&self, message: E) -> Result<(), Infallible> {
+        let actual = message.to_bytes();
+        let idx = self.next_out.get();
+
+        match self.frames.get(idx) {
+            Some(expected) if expected.bytes == actual => {},
+            expected => self.mismatches.borrow_mut().push(Mismatch {
+                expected: expected.cloned(),
+                actual,
+            }),
+        }
+
+        if idx < self.frames.len() {
+            self.next_out.set(self.find(idx + 1, self.side));
+        }
+
+        Ok(())
+    }
+
+    fn get(&self) -> Result<E, Option<Infallible>> {
+        let idx = self.next_in.get();
+
+        // Not until everything before it has been sent:
+        if idx >= self.frames.len() || self.next_out.get() < idx {
+            return Err(None);
+        }
+
+        self.next_in.set(self.find(idx + 1, self.side.other()));
+
+        // Frames that don't fit `E` get dropped (as a real transport would).
+        E::from_bytes(&self.frames[idx].bytes).ok_or(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn frame(at_micros: u64, from: Side, bytes: &[u8]) -> Frame {
+        Frame { at: Duration::from_micros(at_micros), from, bytes: bytes.to_vec() }
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let f = frame(3_000_042, Side::Device, &[0x00, 0xAB, 0x7F]);
+
+        assert_eq!(f.to_string(), "     3.000042 device 00ab7f");
+        assert_eq!(f.to_string().parse(), Ok(f));
+
+        assert_eq!("1.0 device".parse::<Frame>(), Err(ParseError::MissingField("bytes")));
+        assert_eq!("1.0 board 00".parse::<Frame>(), Err(ParseError::BadSide));
+        assert_eq!("-1 device 00".parse::<Frame>(), Err(ParseError::BadTimestamp));
+        assert_eq!("1.0 device 0g".parse::<Frame>(), Err(ParseError::BadHex));
+        assert_eq!("1.0 device 000".parse::<Frame>(), Err(ParseError::BadHex));
+    }
+
+    #[test]
+    fn captures_round_trip() {
+        let capture = Capture {
+            frames: vec![
+                frame(0, Side::Controller, &[1, 2]),
+                frame(10, Side::Device, &[3]),
+            ],
+        };
+
+        let mut file = b"# a comment\n\n".to_vec();
+        capture.write(&mut file).unwrap();
+
+        assert_eq!(Capture::read(&file[..]).unwrap(), capture);
+
+        let err = Capture::read(&b"# ok\n1.0 device zz\n"[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 2"), "{}", err);
+    }
+
+    #[test]
+    fn replay_keeps_to_the_recorded_order() {
+        let capture = Capture {
+            frames: vec![
+                frame(0, Side::Controller, &[1]),
+                frame(1, Side::Device, &[2]),
+                frame(2, Side::Device, &[3]),
+                frame(3, Side::Controller, &[4]),
+                frame(4, Side::Device, &[5]),
+            ],
+        };
+
+        // Standing in for the controller:
+        let replay = ReplayTransport::<Vec<u8>>::new(capture, Side::Device);
+
+        assert_eq!(replay.get(), Ok(vec![1]));
+        assert_eq!(replay.get(), Err(None)); // waiting on `[2]` and `[3]`
+        replay.send(vec![2]).unwrap();
+        assert_eq!(replay.get(), Err(None)); // still waiting on `[3]`
+        replay.send(vec![3]).unwrap();
+        assert_eq!(replay.get(), Ok(vec![4]));
+        assert_eq!(replay.get(), Err(None));
+        assert!(!replay.finished());
+
+        replay.send(vec![6]).unwrap();
+        replay.send(vec![7]).unwrap();
+        assert!(replay.finished());
+
+        assert_eq!(replay.mismatches(), vec![
+            Mismatch { expected: Some(frame(4, Side::Device, &[5])), actual: vec![6] },
+            Mismatch { expected: None, actual: vec![7] },
+        ]);
+    }
+
+    #[test]
+    fn recording() {
+        use lc3_traits::control::rpc::MpscTransport;
+
+        let (a, b) = MpscTransport::<Vec<u8>, Vec<u8>>::new();
+        let rec = RecordingTransport::new(a, Side::Controller, Vec::new());
+
+        rec.send(vec![0xAA]).unwrap();
+        assert_eq!(b.get(), Ok(vec![0xAA]));
+        b.send(vec![0xBB, 0xCC]).unwrap();
+        assert_eq!(rec.get(), Ok(vec![0xBB, 0xCC]));
+        assert_eq!(rec.get(), Err(None));
+
+        let (_, out) = rec.into_inner();
+        let capture = Capture::read(&out[..]).unwrap();
+
+        assert_eq!(
+            capture.frames.iter().map(|f| (f.from, f.bytes.clone())).collect::<Vec<_>>(),
+            vec![(Side::Controller, vec![0xAA]), (Side::Device, vec![0xBB, 0xCC])],
+        );
+        assert!(capture.frames[0].at <= capture.frames[1].at);
+    }
+}
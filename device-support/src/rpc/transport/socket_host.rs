@@ -0,0 +1,508 @@
+//! TCP and Unix domain socket transport for computers.
+//!
+//! Frames are delimited the same way they are on the UART transports: messages
+//! are expected to be COBS encoded (i.e. with [`PostcardEncode`]) so a zero
+//! byte marks the end of a message.
+//!
+//! One side of the connection [listens](HostSocketTransport::listen) (usually
+//! the [`Device`]) and the other side [connects](HostSocketTransport::connect)
+//! (usually the [`Controller`]). Both sides cope with the connection going
+//! away:
+//!   - listeners go back to waiting for a new connection and discard anything
+//!     they're asked to send in the meantime
+//!   - connectors reconnect (see [`HostSocketTransport::connect`])
+//!
+//! Neither side sends anything again on its own: a message that's lost with
+//! the connection is the [`Controller`]'s to retry (its retry policy knows
+//! which requests are safe to send twice).
+//!
+//! [`PostcardEncode`]: crate::rpc::encoding::PostcardEncode
+//! [`Device`]: lc3_traits::control::rpc::Device
+//! [`Controller`]: lc3_traits::control::rpc::Controller
+
+use crate::util::Fifo;
+
+use lc3_traits::control::rpc::Transport;
+use lc3_traits::control::{version_from_crate, Identifier, Version};
+
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Display};
+use std::io::{
+    BufReader, Error, ErrorKind, Read, Result as IoResult, Write,
+};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+
+/// How long connectors wait between reconnection attempts while waiting on a
+/// message.
+pub const RECONNECT_INTERVAL: Duration = Duration::from_millis(500);
+/// How long a connector waits for a TCP connection to be established.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Where a [`HostSocketTransport`] listens or connects.
+///
+/// Parses from `unix:<path>` (on Unix-like platforms) or from anything that
+/// [resolves](ToSocketAddrs) to a TCP socket address (i.e. `127.0.0.1:7000` or
+/// `lab-server:7000`, optionally prefixed with `tcp:`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SocketAddress {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for SocketAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> IoResult<Self> {
+        #[cfg(unix)]
+        let s = match s.strip_prefix("unix:") {
+            Some(path) => return Ok(SocketAddress::Unix(path.into())),
+            None => s,
+        };
+
+        let s = s.strip_prefix("tcp:").unwrap_or(s);
+        s.to_socket_addrs()?
+            .next()
+            .map(SocketAddress::Tcp)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "no addresses found"))
+    }
+}
+
+impl Display for SocketAddress {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SocketAddress::Tcp(addr) => write!(fmt, "tcp:{}", addr),
+            #[cfg(unix)]
+            SocketAddress::Unix(path) => write!(fmt, "unix:{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+macro_rules! each_stream {
+    ($s:expr, $i:ident => $e:expr) => {
+        match $s {
+            Stream::Tcp($i) => $e,
+            #[cfg(unix)]
+            Stream::Unix($i) => $e,
+        }
+    };
+}
+
+impl Stream {
+    fn connect(addr: &SocketAddress) -> IoResult<Self> {
+        let stream = match addr {
+            SocketAddress::Tcp(addr) => {
+                let s = TcpStream::connect_timeout(addr, CONNECT_TIMEOUT)?;
+                s.set_nodelay(true)?;
+                Stream::Tcp(s)
+            }
+            #[cfg(unix)]
+            SocketAddress::Unix(path) => Stream::Unix(UnixStream::connect(path)?),
+        };
+
+        stream.prepare()
+    }
+
+    // `Transport::get` must not block.
+    fn prepare(self) -> IoResult<Self> {
+        each_stream!(&self, s => s.set_nonblocking(true))?;
+        Ok(self)
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        each_stream!(self, s => s.read(buf))
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        each_stream!(self, s => s.write(buf))
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        each_stream!(self, s => s.flush())
+    }
+}
+
+#[derive(Debug)]
+enum Endpoint {
+    Listen(Listener),
+    Connect(SocketAddress),
+}
+
+#[derive(Debug)]
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    fn accept(&self) -> IoResult<Stream> {
+        let stream = match self {
+            Listener::Tcp(l) => {
+                let (s, _) = l.accept()?;
+                s.set_nodelay(true)?;
+                Stream::Tcp(s)
+            }
+            #[cfg(unix)]
+            Listener::Unix(l) => Stream::Unix(l.accept()?.0),
+        };
+
+        stream.prepare()
+    }
+}
+
+fn is_disconnect(err: &Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::BrokenPipe
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+fn write_all(stream: &mut Stream, mut buf: &[u8]) -> IoResult<()> {
+    // The stream is non-blocking so we have to spin on partial writes:
+    while !buf.is_empty() {
+        match stream.write(buf) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => buf = &buf[n..],
+            Err(e) => match e.kind() {
+                ErrorKind::WouldBlock => std::thread::yield_now(),
+                ErrorKind::Interrupted => {}
+                _ => return Err(e),
+            },
+        }
+    }
+
+    stream.flush()
+}
+
+#[derive(Debug)]
+pub struct HostSocketTransport {
+    endpoint: Endpoint,
+    connection: RefCell<Option<BufReader<Stream>>>,
+    internal_buffer: RefCell<Fifo<u8>>,
+    num_get_errors: Cell<u64>,
+    lost_connection: Cell<bool>,
+
+    // Connectors only:
+    last_attempt: Cell<Option<Instant>>,
+}
+
+impl HostSocketTransport {
+    fn new(endpoint: Endpoint, stream: Option<Stream>) -> Self {
+        Self {
+            endpoint,
+            connection: RefCell::new(stream.map(BufReader::new)),
+            internal_buffer: RefCell::new(Fifo::new_const()),
+            num_get_errors: Cell::new(0),
+            lost_connection: Cell::new(false),
+            last_attempt: Cell::new(None),
+        }
+    }
+
+    /// Connects to a transport that's [listening](HostSocketTransport::listen)
+    /// at `addr`.
+    ///
+    /// If the connection is lost, sending a message reconnects (and returns an
+    /// error if it can't). While waiting on a message, reconnection is
+    /// attempted every [`RECONNECT_INTERVAL`].
+    ///
+    /// Messages that were sent before the connection was lost are *not* sent
+    /// again once reconnected: the other end may well have handled them
+    /// already (and, if other connections were served in the meantime, can't
+    /// tell that it has). The [`Controller`] retries requests whose responses
+    /// don't show up as its [`RetryPolicy`] allows.
+    ///
+    /// [`Controller`]: lc3_traits::control::rpc::Controller
+    /// [`RetryPolicy`]: lc3_traits::control::rpc::RetryPolicy
+    pub fn connect(addr: SocketAddress) -> IoResult<Self> {
+        let stream = Stream::connect(&addr)?;
+
+        Ok(Self::new(Endpoint::Connect(addr), Some(stream)))
+    }
+
+    /// Waits for a transport to [connect](HostSocketTransport::connect) to
+    /// `addr`.
+    ///
+    /// This does not block; connections are accepted when
+    /// [`get`](Transport::get) is called. One connection is served at a time;
+    /// when it goes away, the next connection is accepted.
+    pub fn listen(addr: SocketAddress) -> IoResult<Self> {
+        match addr {
+            SocketAddress::Tcp(addr) => Self::from_tcp_listener(TcpListener::bind(addr)?),
+            #[cfg(unix)]
+            SocketAddress::Unix(path) => Self::listen_unix(path),
+        }
+    }
+
+    /// Like [`listen`](HostSocketTransport::listen) but uses the given TCP
+    /// listener.
+    pub fn from_tcp_listener(listener: TcpListener) -> IoResult<Self> {
+        listener.set_nonblocking(true)?;
+
+        Ok(Self::new(Endpoint::Listen(Listener::Tcp(listener)), None))
+    }
+
+    /// Like [`listen`](HostSocketTransport::listen) but for a Unix domain
+    /// socket at `path`.
+    ///
+    /// Note that this fails if something already exists at `path`.
+    #[cfg(unix)]
+    pub fn listen_unix(path: impl AsRef<Path>) -> IoResult<Self> {
+        Self::from_unix_listener(UnixListener::bind(path)?)
+    }
+
+    /// Like [`listen`](HostSocketTransport::listen) but uses the given Unix
+    /// domain socket listener.
+    #[cfg(unix)]
+    pub fn from_unix_listener(listener: UnixListener) -> IoResult<Self> {
+        listener.set_nonblocking(true)?;
+
+        Ok(Self::new(Endpoint::Listen(Listener::Unix(listener)), None))
+    }
+
+    /// Whether there's currently a connection.
+    pub fn is_connected(&self) -> bool {
+        self.connection.borrow().is_some()
+    }
+
+    /// Whether a connection has gone away since the last time this was
+    /// called.
+    ///
+    /// Listeners can lose a connection and accept the next one within one
+    /// [`get`](Transport::get) call so [`is_connected`] can't be relied on to
+    /// notice.
+    ///
+    /// [`is_connected`]: HostSocketTransport::is_connected
+    pub fn take_lost_connection(&self) -> bool {
+        self.lost_connection.replace(false)
+    }
+
+    fn disconnected(&self, conn: &mut Option<BufReader<Stream>>) {
+        *conn = None;
+        self.lost_connection.set(true);
+
+        // Whatever partial message we had is never going to be finished:
+        *self.internal_buffer.borrow_mut() = Fifo::new_const();
+    }
+
+    // Tries to establish a connection.
+    //
+    // Returns a `WouldBlock` error if a listener has no connections waiting.
+    fn establish(&self, conn: &mut Option<BufReader<Stream>>) -> IoResult<()> {
+        match &self.endpoint {
+            Endpoint::Listen(listener) => {
+                *conn = Some(BufReader::new(listener.accept()?));
+            }
+            Endpoint::Connect(addr) => {
+                self.last_attempt.set(Some(Instant::now()));
+
+                *conn = Some(BufReader::new(Stream::connect(addr)?));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// TODO: on std especially we don't need to pass around buffers; we can be
+// zero-copy...
+impl Transport<Fifo<u8>, Fifo<u8>> for HostSocketTransport {
+    type RecvErr = Error;
+    type SendErr = Error;
+
+    const ID: Identifier =
+        Identifier::new_from_str_that_crashes_on_invalid_inputs("SOCK");
+    const VER: Version = {
+        let ver = version_from_crate!();
+
+        let id =
+            Identifier::new_from_str_that_crashes_on_invalid_inputs("host");
+
+        Version::new(ver.major, ver.minor, ver.patch, Some(id))
+    };
+
+    fn send(&self, message: Fifo<u8>) -> IoResult<()> {
+        let mut conn = self.connection.borrow_mut();
+
+        match self.endpoint {
+            Endpoint::Listen(_) => {
+                // If no one's listening, there's no one to send the message to.
+                let res = match &mut *conn {
+                    Some(stream) => write_all(stream.get_mut(), message.as_slice()),
+                    None => return Ok(()),
+                };
+
+                match res {
+                    Err(ref e) if is_disconnect(e) => {
+                        self.disconnected(&mut conn);
+                        Ok(())
+                    }
+                    res => res,
+                }
+            }
+
+            Endpoint::Connect(_) => {
+                if conn.is_none() {
+                    self.establish(&mut conn)?;
+                }
+
+                let stream = conn.as_mut().unwrap().get_mut();
+                match write_all(stream, message.as_slice()) {
+                    // The message ends with its delimiter so if the connection
+                    // went away before we finished writing it, the other end
+                    // never got all of it; it's safe to send on a new one:
+                    Err(ref e) if is_disconnect(e) => {
+                        self.disconnected(&mut conn);
+                        self.establish(&mut conn)?;
+
+                        write_all(conn.as_mut().unwrap().get_mut(), message.as_slice())
+                    }
+                    res => res,
+                }
+            }
+        }
+    }
+
+    fn get(&self) -> Result<Fifo<u8>, Option<Error>> {
+        let mut conn = self.connection.borrow_mut();
+
+        if conn.is_none() {
+            if let Endpoint::Connect(_) = self.endpoint {
+                let too_soon = self
+                    .last_attempt
+                    .get()
+                    .map(|t| t.elapsed() < RECONNECT_INTERVAL)
+                    .unwrap_or(false);
+
+                if too_soon {
+                    return Err(None);
+                }
+            }
+
+            // Failing to (re)connect isn't an error; we just don't have any
+            // messages yet.
+            if self.establish(&mut conn).is_err() {
+                return Err(None);
+            }
+        }
+
+        let stream = conn.as_mut().unwrap();
+        let mut buf = self.internal_buffer.borrow_mut();
+        let mut byte = [0; 1];
+
+        loop {
+            match stream.read(&mut byte) {
+                Ok(1) => {
+                    if byte[0] == 0 {
+                        return Ok(core::mem::replace(&mut buf, Fifo::new()));
+                    } else if buf.push(byte[0]).is_err() {
+                        // Too big to be one of our messages; drop what we have
+                        // and hope to resynchronize at the next zero byte.
+                        self.num_get_errors.set(self.num_get_errors.get() + 1);
+                        *buf = Fifo::new();
+                    }
+                }
+
+                // End of stream:
+                Ok(_) => {
+                    drop(buf);
+                    self.disconnected(&mut conn);
+                    return Err(None);
+                }
+
+                Err(err) => match err.kind() {
+                    ErrorKind::WouldBlock => return Err(None),
+                    ErrorKind::Interrupted => {}
+                    _ if is_disconnect(&err) => {
+                        drop(buf);
+                        self.disconnected(&mut conn);
+                        return Err(None);
+                    }
+                    _ => return Err(Some(err)),
+                },
+            }
+        }
+    }
+
+    fn num_get_errors(&self) -> u64 {
+        self.num_get_errors.get()
+    }
+
+    fn record_get_error(&self) {
+        self.num_get_errors.set(self.num_get_errors.get() + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn frame(bytes: &[u8]) -> Fifo<u8> {
+        let mut fifo = Fifo::new();
+        fifo.push_slice(bytes).unwrap();
+        fifo
+    }
+
+    #[test]
+    fn reconnecting_does_not_resend() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = SocketAddress::Tcp(listener.local_addr().unwrap());
+
+        let transport = HostSocketTransport::connect(addr).unwrap();
+        transport.send(frame(&[1, 2, 0])).unwrap();
+
+        let (mut first, _) = listener.accept().unwrap();
+        let mut buf = [0; 3];
+        first.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 0]);
+
+        // The other end goes away (having handled the message):
+        drop(first);
+
+        let start = Instant::now();
+        while transport.is_connected() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            assert!(transport.get().is_err());
+        }
+
+        // Reconnecting shouldn't send the message again:
+        assert!(transport.get().is_err());
+        assert!(transport.is_connected());
+
+        let (mut second, _) = listener.accept().unwrap();
+        second.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        let err = second.read(&mut buf).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut));
+
+        // But new messages go out on the new connection:
+        transport.send(frame(&[3, 0])).unwrap();
+        second.set_read_timeout(None).unwrap();
+        second.read_exact(&mut buf[..2]).unwrap();
+        assert_eq!(buf[..2], [3, 0]);
+    }
+}
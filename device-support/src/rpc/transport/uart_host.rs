@@ -12,7 +12,7 @@ use serialport::{
 pub use serialport::SerialPortBuilder;
 
 use std::borrow::Cow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::io::{Error, ErrorKind, Read, Result as IoResult, Write};
 use std::time::Duration;
 
@@ -20,6 +20,7 @@ use std::time::Duration;
 pub struct HostUartTransport {
     serial: RefCell<Box<dyn SerialPort>>,
     internal_buffer: RefCell<Fifo<u8>>,
+    num_get_errors: Cell<u64>,
 }
 
 impl HostUartTransport {
@@ -45,6 +46,7 @@ impl HostUartTransport {
         Ok(Self {
             serial: RefCell::new(serial),
             internal_buffer: RefCell::new(Fifo::new_const()),
+            num_get_errors: Cell::new(0),
         })
     }
 }
@@ -133,4 +135,12 @@ impl Transport<Fifo<u8>, Fifo<u8>> for HostUartTransport {
 
         Err(None)
     }
+
+    fn num_get_errors(&self) -> u64 {
+        self.num_get_errors.get()
+    }
+
+    fn record_get_error(&self) {
+        self.num_get_errors.set(self.num_get_errors.get() + 1);
+    }
 }
@@ -10,7 +10,7 @@ use lc3_traits::control::{Identifier, Version, version_from_crate};
 use embedded_hal::serial::{Read, Write};
 use nb::block;
 
-use core::cell::RefCell;
+use core::cell::{Cell, RefCell};
 use core::fmt::Debug;
 
 #[derive(Debug)]
@@ -22,6 +22,7 @@ where
     read: RefCell<R>,
     write: RefCell<W>,
     internal_buffer: RefCell<Fifo<u8>>,
+    num_get_errors: Cell<u64>,
 }
 
 impl<R: Read<u8>, W: Write<u8>> UartTransport<R, W>
@@ -35,6 +36,7 @@ where
             read: RefCell::new(read),
             write: RefCell::new(write),
             internal_buffer: RefCell::new(Fifo::new_const()),
+            num_get_errors: Cell::new(0),
         }
     }
 }
@@ -93,4 +95,12 @@ where
             }
         }
     }
+
+    fn num_get_errors(&self) -> u64 {
+        self.num_get_errors.get()
+    }
+
+    fn record_get_error(&self) {
+        self.num_get_errors.set(self.num_get_errors.get() + 1);
+    }
 }
@@ -0,0 +1,136 @@
+//! Extensions to the LC-3 ISA.
+//!
+//! The LC-3 leaves one opcode (`1101`) reserved; normally executing an
+//! instruction that uses it raises an illegal opcode exception. An
+//! [`IsaExtension`] can claim some (or all) of the encodings under the
+//! reserved opcode: words it claims decode to [`Instruction::Extended`] and are
+//! formatted using the extension's own syntax.
+//!
+//! The default (no extension, or [`NoExtension`]) is standard LC-3.
+
+use crate::{Instruction, Word};
+
+use core::convert::TryInto;
+use core::fmt::{self, Display, Formatter};
+
+/// The opcode that's reserved in the LC-3 ISA and that extensions can use.
+pub const RESERVED_OPCODE: u8 = 0b1101;
+
+/// Describes the instructions an extension adds under the reserved opcode.
+///
+/// `bits` is always the low 12 bits of the instruction.
+pub trait IsaExtension {
+    /// Whether this extension has an instruction with these bits.
+    fn decodes(&self, bits: u16) -> bool;
+
+    /// Writes out the instruction with these bits in assembly syntax.
+    ///
+    /// Only called with bits that this extension [decodes](IsaExtension::decodes).
+    fn fmt(&self, bits: u16, fmt: &mut Formatter<'_>) -> fmt::Result;
+}
+
+/// Standard LC-3: claims nothing.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct NoExtension;
+
+impl IsaExtension for NoExtension {
+    fn decodes(&self, _bits: u16) -> bool { false }
+
+    fn fmt(&self, bits: u16, fmt: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&Instruction::Extended { bits }, fmt)
+    }
+}
+
+impl<E: IsaExtension + ?Sized> IsaExtension for &E {
+    fn decodes(&self, bits: u16) -> bool { (**self).decodes(bits) }
+    fn fmt(&self, bits: u16, fmt: &mut Formatter<'_>) -> fmt::Result { (**self).fmt(bits, fmt) }
+}
+
+/// Combines two extensions; when both claim an encoding the first one wins.
+impl<A: IsaExtension, B: IsaExtension> IsaExtension for (A, B) {
+    fn decodes(&self, bits: u16) -> bool {
+        self.0.decodes(bits) || self.1.decodes(bits)
+    }
+
+    fn fmt(&self, bits: u16, fmt: &mut Formatter<'_>) -> fmt::Result {
+        if self.0.decodes(bits) { self.0.fmt(bits, fmt) } else { self.1.fmt(bits, fmt) }
+    }
+}
+
+impl Instruction {
+    /// Like `TryFrom<Word>` but words with the reserved opcode that `ext`
+    /// claims decode to [`Instruction::Extended`].
+    pub fn decode_with<E: IsaExtension + ?Sized>(word: Word, ext: &E) -> Result<Self, Word> {
+        let bits = word & 0xFFF;
+        if (word >> 12) as u8 == RESERVED_OPCODE && ext.decodes(bits) {
+            Ok(Instruction::Extended { bits })
+        } else {
+            word.try_into()
+        }
+    }
+
+    /// Returns something that displays this instruction, using `ext` for
+    /// [`Instruction::Extended`] instructions that it claims.
+    pub fn display_with<'a, E: IsaExtension + ?Sized>(&'a self, ext: &'a E) -> WithExtension<'a, E> {
+        WithExtension { insn: self, ext }
+    }
+}
+
+/// See [`Instruction::display_with`].
+#[derive(Debug)]
+pub struct WithExtension<'a, E: ?Sized> {
+    insn: &'a Instruction,
+    ext: &'a E,
+}
+
+impl<E: IsaExtension + ?Sized> Display for WithExtension<'_, E> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match *self.insn {
+            Instruction::Extended { bits } if self.ext.decodes(bits) => self.ext.fmt(bits, fmt),
+            ref insn => Display::fmt(insn, fmt),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::Reg::*;
+    use std::format;
+
+    // Claims `1101 1xxx xxxx xxxx` as `SWAP`.
+    struct Swap;
+
+    impl IsaExtension for Swap {
+        fn decodes(&self, bits: u16) -> bool { bits & 0x800 != 0 }
+        fn fmt(&self, bits: u16, fmt: &mut Formatter<'_>) -> fmt::Result {
+            write!(fmt, "SWAP  x{:03X}", bits & 0x7FF)
+        }
+    }
+
+    #[test]
+    fn standard_by_default() {
+        assert_eq!(Instruction::decode_with(0xD800, &NoExtension), Err(0xD800));
+        assert_eq!(
+            Instruction::decode_with(0x1000, &NoExtension),
+            Ok(Instruction::new_add_reg(R0, R0, R0)),
+        );
+        assert_eq!(format!("{}", Instruction::new_extended(0x800)), ".FILL xD800");
+    }
+
+    #[test]
+    fn claimed_encodings() {
+        assert_eq!(Instruction::decode_with(0xD801, &Swap), Ok(Instruction::new_extended(0x801)));
+        assert_eq!(Instruction::decode_with(0xD001, &Swap), Err(0xD001));
+        assert_eq!(Instruction::decode_with(0xD001, &(NoExtension, Swap)), Err(0xD001));
+        assert_eq!(Instruction::decode_with(0xD801, &(NoExtension, Swap)), Ok(Instruction::new_extended(0x801)));
+
+        let insn = Instruction::new_extended(0x801);
+        assert_eq!(insn.to_word(), 0xD801);
+        assert_eq!(format!("{}", insn.display_with(&Swap)), "SWAP  x001");
+        assert_eq!(format!("{}", insn.display_with(&NoExtension)), ".FILL xD801");
+        assert_eq!(format!("{}", crate::insn!(EXT #0x801)), ".FILL xD801");
+    }
+}
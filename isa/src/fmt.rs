@@ -44,6 +44,8 @@ impl Display for Instruction {
             Sti { sr, offset9 } => write!(fmt, "STI   {}, #{}", sr, offset9),
             Str { sr, base, offset6 } => write!(fmt, "STR   {}, {}, #{}", sr, base, offset6),
             Trap { trapvec } => write!(fmt, "TRAP  x{:X}", trapvec),
+            // Without the extension we don't know what this is called:
+            Extended { .. } => write!(fmt, ".FILL x{:04X}", self.to_word()),
         }
     }
 }
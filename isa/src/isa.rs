@@ -6,7 +6,8 @@ use core::cmp::Ordering;
 use core::convert::{TryFrom, TryInto};
 use core::ops::Range;
 
-use serde::{Deserialize, Serialize};
+use serde::de::{Error as _, Unexpected};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[rustfmt::skip]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -221,7 +222,6 @@ type Sw = SignedWord;
 
 #[rustfmt::skip]
 #[derive(Debug, Copy, Clone, Hash, Serialize, Deserialize)]
-#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 // TODO: docs!
 // Give the full name of the instruction, the pseudo code, whether it sets
 // condition codes, the bit format, and some examples.
@@ -246,6 +246,38 @@ pub enum Instruction {
     Sti { sr: Reg, offset9: Sw },                   // R9
     Str { sr: Reg, base: Reg, offset6: Sw },        // RR6
     Trap { trapvec: u8 },                           // 8
+    /// An instruction that uses the reserved opcode (`1101`); the low 12 bits
+    /// are up to an [ISA extension](crate::ext) to interpret. Words with the
+    /// reserved opcode only decode to this when an extension claims them.
+    Extended {
+        #[serde(deserialize_with = "twelve_bits")]
+        bits: u16,
+    },                                              // 12
+}
+
+// `Extended` only has room for 12 bits.
+fn twelve_bits<'de, D: Deserializer<'de>>(de: D) -> Result<u16, D::Error> {
+    let bits = u16::deserialize(de)?;
+    if bits > 0xFFF {
+        return Err(D::Error::invalid_value(Unexpected::Unsigned(bits.into()), &"at most 12 bits"));
+    }
+
+    Ok(bits)
+}
+
+// A derived impl could make instructions with fields that are out of range
+// (i.e. an `Extended` with more than 12 bits); every instruction that comes from
+// a `Word` is valid.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Instruction {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let word = <Word as arbitrary::Arbitrary>::arbitrary(u)?;
+        Ok(Instruction::try_from(word).unwrap_or_else(|w| Instruction::new_extended(w & 0xFFF)))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <Word as arbitrary::Arbitrary>::size_hint(depth)
+    }
 }
 
 /// We use the bit representation of [`Instruction`] for equality specifically
@@ -585,6 +617,26 @@ impl Instruction {
         // trapvec, an 8 bit value represented by a u8, can't be out of bounds.
         Instruction::Trap { trapvec }
     }
+
+    /// Creates a new [`Instruction::Extended`] with the given 12 bits (`[0,
+    /// 4096)`); see the [`ext`](crate::ext) module.
+    ///
+    /// ```rust
+    /// # use lc3_isa::Instruction;
+    /// assert_eq!(Instruction::new_extended(0x123).to_word(), 0xD123);
+    /// ```
+    ///
+    /// ```rust,should_panic
+    /// # use lc3_isa::Instruction;
+    /// println!("{:?}", Instruction::new_extended(0x1000));
+    /// ```
+    pub const fn new_extended(bits: u16) -> Self {
+        // Until const panics are stable:
+        let canary: [(); 1] = [()];
+        canary[(bits > 0xFFF) as usize];
+
+        Instruction::Extended { bits }
+    }
 }
 
 impl Instruction {
@@ -611,6 +663,8 @@ impl Instruction {
             | Sti { .. }
             | Str { .. }
             | Trap { .. } => false,
+            // Up to the extension:
+            Extended { .. } => false,
         }
     }
 }
@@ -807,6 +861,7 @@ impl Instruction {
             Sti { sr, offset9 }       => Op(0b1011) | Dr(sr)              | O9(offset9),
             Str { sr, base, offset6 } => Op(0b0111) | Dr(sr) | Base(base) | O6(offset6),
             Trap { trapvec }          => Op(0b1111)          | Trapvec(trapvec)        ,
+            Extended { bits }         => Op(0b1101)          | (bits & 0xFFF)          ,
         }
     }
 }
@@ -1030,6 +1085,37 @@ mod instruction_tests {
             assert!(false);
         }
     }
+
+    #[test]
+    fn extended_bits_are_checked_when_deserialized() {
+        use serde::de::{value::Error, IntoDeserializer};
+
+        let bits = |bits: u16| super::twelve_bits(IntoDeserializer::<Error>::into_deserializer(bits));
+        assert_eq!(bits(0xFFF), Ok(0xFFF));
+        assert!(bits(0x1000).is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_instructions_are_valid() {
+        use super::Word;
+        use arbitrary::{Arbitrary, Unstructured};
+        use core::convert::TryFrom;
+
+        for word in 0..=Word::MAX {
+            let bytes = word.to_le_bytes();
+            let insn = Instruction::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+
+            // Field by field, unlike `==`:
+            match insn {
+                Extended { bits } => assert!(bits <= 0xFFF),
+                insn => assert_eq!(
+                    format!("{:?}", Instruction::try_from(insn.to_word()).unwrap()),
+                    format!("{:?}", insn),
+                ),
+            }
+        }
+    }
 }
 
 
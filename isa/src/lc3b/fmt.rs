@@ -0,0 +1,43 @@
+//! Format impls for LC-3b instructions.
+
+use super::{Instruction, Shift};
+use crate::Reg;
+use core::fmt::{self, Display};
+
+impl Display for Instruction {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Instruction::*;
+        match self {
+            AddReg { dr, sr1, sr2 } => write!(fmt, "ADD   {}, {}, {}", dr, sr1, sr2),
+            AddImm { dr, sr1, imm5 } => write!(fmt, "ADD   {}, {}, #{}", dr, sr1, imm5),
+            AndReg { dr, sr1, sr2 } => write!(fmt, "AND   {}, {}, {}", dr, sr1, sr2),
+            AndImm { dr, sr1, imm5 } => write!(fmt, "AND   {}, {}, #{}", dr, sr1, imm5),
+            // Same as the LC-3:
+            Br { n, z, p, offset9 } => {
+                Display::fmt(&crate::Instruction::Br { n: *n, z: *z, p: *p, offset9: *offset9 }, fmt)
+            }
+            Jmp { base: Reg::R7 } => write!(fmt, "RET"),
+            Jmp { base } => write!(fmt, "JMP   {}", base),
+            Jsr { offset11 } => write!(fmt, "JSR   #{}", offset11),
+            Jsrr { base } => write!(fmt, "JSRR  {}", base),
+            Ldb { dr, base, offset6 } => write!(fmt, "LDB   {}, {}, #{}", dr, base, offset6),
+            Ldw { dr, base, offset6 } => write!(fmt, "LDW   {}, {}, #{}", dr, base, offset6),
+            Lea { dr, offset9 } => write!(fmt, "LEA   {}, #{}", dr, offset9),
+            Rti => write!(fmt, "RTI"),
+            Shf { dr, sr, shift, amount4 } => {
+                let name = match shift {
+                    Shift::Left => "LSHF ",
+                    Shift::RightLogical => "RSHFL",
+                    Shift::RightArithmetic => "RSHFA",
+                };
+                write!(fmt, "{} {}, {}, #{}", name, dr, sr, amount4)
+            }
+            Stb { sr, base, offset6 } => write!(fmt, "STB   {}, {}, #{}", sr, base, offset6),
+            Stw { sr, base, offset6 } => write!(fmt, "STW   {}, {}, #{}", sr, base, offset6),
+            Trap { trapvec } => write!(fmt, "TRAP  x{:X}", trapvec),
+            XorReg { dr, sr1, sr2 } => write!(fmt, "XOR   {}, {}, {}", dr, sr1, sr2),
+            XorImm { dr, sr1, imm5: -1 } => write!(fmt, "NOT   {}, {}", dr, sr1),
+            XorImm { dr, sr1, imm5 } => write!(fmt, "XOR   {}, {}, #{}", dr, sr1, imm5),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Macros for the LC-3b; see the LC-3 versions for the general idea.
+
+#[macro_export]
+macro_rules! lc3b_insn {
+    (ADD $dr:ident, $sr1:ident, $sr2:ident $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_add_reg($crate::reg!($dr), $crate::reg!($sr1), $crate::reg!($sr2))
+    };
+    (ADD $dr:ident, $sr1:ident, #$imm5:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_add_imm($crate::reg!($dr), $crate::reg!($sr1), $imm5)
+    };
+
+    (AND $dr:ident, $sr1:ident, $sr2:ident $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_and_reg($crate::reg!($dr), $crate::reg!($sr1), $crate::reg!($sr2))
+    };
+    (AND $dr:ident, $sr1:ident, #$imm5:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_and_imm($crate::reg!($dr), $crate::reg!($sr1), $imm5)
+    };
+
+    (BR #$offset9:expr $(,)? $(=> $($extra:tt)*)?) => { $crate::lc3b_insn!(BRnzp #$offset9) };
+    (BRn #$offset9:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_br(true, false, false, $offset9)
+    };
+    (BRz #$offset9:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_br(false, true, false, $offset9)
+    };
+    (BRp #$offset9:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_br(false, false, true, $offset9)
+    };
+    (BRnz #$offset9:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_br(true, true, false, $offset9)
+    };
+    (BRnp #$offset9:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_br(true, false, true, $offset9)
+    };
+    (BRzp #$offset9:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_br(false, true, true, $offset9)
+    };
+    (BRnzp #$offset9:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_br(true, true, true, $offset9)
+    };
+
+    (JMP $base:ident $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_jmp($crate::reg!($base))
+    };
+
+    (JSR #$offset11:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_jsr($offset11)
+    };
+
+    (JSRR $base:ident $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_jsrr($crate::reg!($base))
+    };
+
+    (LDB $dr:ident, $base:ident, #$offset6:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_ldb($crate::reg!($dr), $crate::reg!($base), $offset6)
+    };
+
+    (LDW $dr:ident, $base:ident, #$offset6:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_ldw($crate::reg!($dr), $crate::reg!($base), $offset6)
+    };
+
+    (LEA $dr:ident, #$offset9:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_lea($crate::reg!($dr), $offset9)
+    };
+
+    (LSHF $dr:ident, $sr:ident, #$amount4:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_lshf($crate::reg!($dr), $crate::reg!($sr), $amount4)
+    };
+
+    (NOT $dr:ident, $sr:ident $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_not($crate::reg!($dr), $crate::reg!($sr))
+    };
+
+    (RET $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_ret()
+    };
+
+    (RSHFL $dr:ident, $sr:ident, #$amount4:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_rshfl($crate::reg!($dr), $crate::reg!($sr), $amount4)
+    };
+
+    (RSHFA $dr:ident, $sr:ident, #$amount4:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_rshfa($crate::reg!($dr), $crate::reg!($sr), $amount4)
+    };
+
+    (RTI $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_rti()
+    };
+
+    (STB $sr:ident, $base:ident, #$offset6:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_stb($crate::reg!($sr), $crate::reg!($base), $offset6)
+    };
+
+    (STW $sr:ident, $base:ident, #$offset6:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_stw($crate::reg!($sr), $crate::reg!($base), $offset6)
+    };
+
+    (TRAP #$trapvec:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_trap($trapvec)
+    };
+
+    (XOR $dr:ident, $sr1:ident, $sr2:ident $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_xor_reg($crate::reg!($dr), $crate::reg!($sr1), $crate::reg!($sr2))
+    };
+    (XOR $dr:ident, $sr1:ident, #$imm5:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::lc3b::Instruction::new_xor_imm($crate::reg!($dr), $crate::reg!($sr1), $imm5)
+    }
+}
+
+#[macro_export]
+macro_rules! lc3b_word {
+    () => { 0 };
+
+    ($(.)? FILL #$word:expr $(=> $($extra:tt)*)?) => {
+        // To mute 'trivial_numeric_casts' lints:
+        #[allow(trivial_numeric_casts)]
+        { $word as $crate::Word }
+    };
+
+    (GETC $(=> $($extra:tt)*)?) => { $crate::lc3b_word!(TRAP #0x20) };
+    (OUT $(=> $($extra:tt)*)?) => { $crate::lc3b_word!(TRAP #0x21) };
+    (PUTS $(=> $($extra:tt)*)?) => { $crate::lc3b_word!(TRAP #0x22) };
+    (IN $(=> $($extra:tt)*)?) => { $crate::lc3b_word!(TRAP #0x23) };
+    (HALT $(=> $($extra:tt)*)?) => { $crate::lc3b_word!(TRAP #0x25) };
+
+    (NOP $(=> $($extra:tt)*)?) => { $crate::lc3b_word!(BR #0) };
+
+    ($($other:tt)*) => {
+        $crate::lc3b_insn!($($other)*).to_word()
+    }
+}
+
+/// Like [`program!`](crate::program) but for the LC-3b.
+///
+/// Addresses (`.ORIG`s and labels) are byte addresses and each line occupies
+/// two bytes; PC relative label operands are turned into word offsets. The
+/// resulting memory image is indexed by
+/// [`memory_index`](crate::lc3b::memory_index) rather than by address.
+#[macro_export]
+macro_rules! lc3b_program {
+    (.ORIG #$orig:expr $(=> $($_oa:ident$($_ob:literal)?)*)?; $($rest:tt)*) => {
+        {
+            #[allow(mutable)]
+            let mut _addr: $crate::Addr;
+
+            #[allow(mutable)]
+            let mut _addr_label: $crate::Addr;
+
+            #[allow(mutable)]
+            let mut _mem: [($crate::Word, bool); $crate::ADDR_SPACE_SIZE_IN_WORDS] = [(0, false); $crate::ADDR_SPACE_SIZE_IN_WORDS];
+
+            $crate::lc3b_program!(%%label_def, _addr_label | .ORIG #$orig; $($rest)*);
+            $crate::lc3b_program!(%%contd, _addr, _mem | .ORIG #$orig; $($rest)*);
+
+            _mem
+        }
+    };
+
+    (%%contd, $addr:ident, $mem:ident | .ORIG #$orig:expr $(=> $($_oa:ident$($_ob:literal)?)*)?; $($rest:tt)*) => {
+        $addr = $orig;
+
+        $crate::lc3b_program!(%%contd, $addr, $mem | $($rest)*);
+    };
+
+    (%%contd, $addr:ident, $mem:ident | $(@$label:ident)? $(.)? FILL $($regs:ident),* $(,)? $(@$label_operand:ident)? $(#$num:expr)? $(=> $($_a:ident$($_b:literal)?)*)?; $($rest:tt)* ) => {
+        let _idx = $crate::lc3b::memory_index($addr) as usize;
+
+        { if $mem[_idx].1 { $crate::overlap!($addr); } }
+        $mem[_idx] = ($crate::lc3b_word!(FILL $($regs,)* $(#((($label_operand))))? $(#$num)*), true);
+
+        $addr += 2;
+
+        $crate::lc3b_program!(%%contd, $addr, $mem | $($rest)*);
+    };
+
+    (%%contd, $addr:ident, $mem:ident | $(@$label:ident)? $(.)? $op:ident $($regs:ident),* $(,)? $(@$label_operand:ident)? $(#$num:expr)? $(=> $($_a:ident$($_b:literal)?)*)?; $($rest:tt)* ) => {
+        let _idx = $crate::lc3b::memory_index($addr) as usize;
+
+        { if $mem[_idx].1 { $crate::overlap!($addr); } }
+        $mem[_idx] = ($crate::lc3b_word!($op $($regs,)* $(#(((($label_operand as i64) - ($addr as i64) - 2) / 2) as $crate::SignedWord))? $(#$num)*), true);
+
+        $addr += 2;
+
+        $crate::lc3b_program!(%%contd, $addr, $mem | $($rest)*);
+    };
+
+    // The end!
+    (%%contd, $addr:ident, $mem:ident |) => {
+
+    };
+
+    // Label definition
+    (%%label_def, $addr:ident | .ORIG #$orig:expr $(=> $($_oa:ident$($_ob:literal)?)*)?; $($rest:tt)*) => {
+        $addr = $orig;
+
+        $crate::lc3b_program!(%%label_def, $addr | $($rest)*);
+    };
+
+    (%%label_def, $addr:ident | $(@$label:ident)? $(.)? $op:ident $($regs:ident),* $(,)? $(@$label_operand:ident)? $(#$num:expr)? $(=> $($_a:ident$($_b:literal)?)*)?; $($rest:tt)* ) => {
+        $(
+            #[allow(non_snake_case)]
+            let $label: $crate::Addr = $addr;
+        )?
+        $addr += 2;
+
+        $crate::lc3b_program!(%%label_def, $addr | $($rest)*);
+    };
+
+    // The end!
+    (%%label_def, $addr:ident |) => {
+
+    };
+}
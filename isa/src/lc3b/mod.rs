@@ -0,0 +1,358 @@
+//! Types for the LC-3b, the byte addressable sibling of the LC-3.
+//!
+//! The LC-3b shares the LC-3's registers, condition codes, privilege model
+//! and device registers but:
+//!   - addresses are byte addresses; memory is still made of 16 bit words
+//!     (little endian: the byte at an even address is the low byte of its word)
+//!     but the PC advances by 2 and PC relative offsets are in words
+//!     (i.e. they're shifted left by one)
+//!   - `LD`/`LDI`/`LDR`/`ST`/`STI`/`STR` are replaced by `LDB`/`LDW` and
+//!     `STB`/`STW` (all base + offset)
+//!   - `NOT` becomes `XOR` (`NOT` is `XOR` with an immediate of `-1`)
+//!   - the reserved opcode is used for `SHF` (shifts)
+//!   - `TRAP` is a call through the trap vector table that links through `R7`
+//!     (like `JSRR`), without changing the privilege level
+//!
+//! [`Instruction`] here is the LC-3b counterpart of [`crate::Instruction`];
+//! [`lc3b_insn!`](crate::lc3b_insn) and [`lc3b_program!`](crate::lc3b_program)
+//! are the counterparts of [`insn!`](crate::insn) and
+//! [`program!`](crate::program).
+
+use crate::{Addr, Bits, Reg, SignedWord, Word, MEM_MAPPED_START_ADDR};
+
+use core::convert::{TryFrom, TryInto};
+
+use serde::{Deserialize, Serialize};
+
+mod fmt;
+mod macros;
+
+/// Byte address of the trap vector table; the entry for trap `n` is at
+/// `n << 1`.
+pub const TRAP_VECTOR_TABLE_START_ADDR: Addr = 0x0000;
+
+/// Byte address of the interrupt (and exception) vector table; the entry for
+/// vector `n` is at this address plus `n << 1`.
+pub const INTERRUPT_VECTOR_TABLE_START_ADDR: Addr = 0x0200;
+
+/// Where execution starts (right after the vector tables).
+pub const OS_START_ADDR: Addr = 0x0400;
+
+/// Maps a byte address to the index of the memory word that holds it.
+///
+/// Memory backed addresses are packed two bytes to a word; device registers
+/// (everything at or above [`MEM_MAPPED_START_ADDR`]) keep their usual
+/// addresses so that they're shared with the LC-3. Program images for the
+/// LC-3b (i.e. from [`lc3b_program!`](crate::lc3b_program)) are laid out
+/// using these indices.
+///
+/// ```rust
+/// # use lc3_isa::lc3b::memory_index;
+/// assert_eq!(memory_index(0x3000), 0x1800);
+/// assert_eq!(memory_index(0x3001), 0x1800);
+/// assert_eq!(memory_index(0xFE04), 0xFE04);
+/// ```
+pub const fn memory_index(addr: Addr) -> Addr {
+    if addr >= MEM_MAPPED_START_ADDR { addr } else { addr >> 1 }
+}
+
+/// The kinds of shift `SHF` can do.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Shift {
+    /// `LSHF`
+    Left,
+    /// `RSHFL`: shifts in zeros.
+    RightLogical,
+    /// `RSHFA`: shifts in copies of the sign bit.
+    RightArithmetic,
+}
+
+type Sw = SignedWord;
+
+/// An LC-3b instruction.
+///
+/// Offsets are in the units the instruction uses: `Ldb`/`Stb` offsets are in
+/// bytes; everything else is in words.
+#[rustfmt::skip]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Instruction {
+    AddReg { dr: Reg, sr1: Reg, sr2: Reg },                 // RRR
+    AddImm { dr: Reg, sr1: Reg, imm5: Sw },                 // RR5
+    AndReg { dr: Reg, sr1: Reg, sr2: Reg },                 // RRR
+    AndImm { dr: Reg, sr1: Reg, imm5: Sw },                 // RR5
+    Br { n: bool, z: bool, p: bool, offset9: Sw },          // nzp9
+    Jmp { base: Reg },                                      // B
+    Jsr { offset11: Sw },                                   // b
+    Jsrr { base: Reg },                                     // B
+    Ldb { dr: Reg, base: Reg, offset6: Sw },                // RR6
+    Ldw { dr: Reg, base: Reg, offset6: Sw },                // RR6
+    Lea { dr: Reg, offset9: Sw },                           // R9
+    Rti,                                                    //
+    Shf { dr: Reg, sr: Reg, shift: Shift, amount4: u8 },    // RRs4
+    Stb { sr: Reg, base: Reg, offset6: Sw },                // RR6
+    Stw { sr: Reg, base: Reg, offset6: Sw },                // RR6
+    Trap { trapvec: u8 },                                   // 8
+    XorReg { dr: Reg, sr1: Reg, sr2: Reg },                 // RRR
+    XorImm { dr: Reg, sr1: Reg, imm5: Sw },                 // RR5
+}
+
+// Until const panics are stable:
+macro_rules! check {
+    ($cond:expr) => {{
+        let canary: [(); 1] = [()];
+        canary[(!$cond) as usize];
+    }};
+}
+
+use crate::check_signed_imm as fits;
+
+impl Instruction {
+    pub const fn new_add_reg(dr: Reg, sr1: Reg, sr2: Reg) -> Self {
+        Instruction::AddReg { dr, sr1, sr2 }
+    }
+
+    pub const fn new_add_imm(dr: Reg, sr1: Reg, imm5: SignedWord) -> Self {
+        check!(fits(imm5, 5));
+        Instruction::AddImm { dr, sr1, imm5 }
+    }
+
+    pub const fn new_and_reg(dr: Reg, sr1: Reg, sr2: Reg) -> Self {
+        Instruction::AndReg { dr, sr1, sr2 }
+    }
+
+    pub const fn new_and_imm(dr: Reg, sr1: Reg, imm5: SignedWord) -> Self {
+        check!(fits(imm5, 5));
+        Instruction::AndImm { dr, sr1, imm5 }
+    }
+
+    /// `offset9` is in words.
+    pub const fn new_br(n: bool, z: bool, p: bool, offset9: SignedWord) -> Self {
+        check!(fits(offset9, 9));
+        Instruction::Br { n, z, p, offset9 }
+    }
+
+    pub const fn new_jmp(base: Reg) -> Self {
+        Instruction::Jmp { base }
+    }
+
+    /// `JMP R7`.
+    pub const fn new_ret() -> Self {
+        Instruction::Jmp { base: Reg::R7 }
+    }
+
+    /// `offset11` is in words.
+    pub const fn new_jsr(offset11: SignedWord) -> Self {
+        check!(fits(offset11, 11));
+        Instruction::Jsr { offset11 }
+    }
+
+    pub const fn new_jsrr(base: Reg) -> Self {
+        Instruction::Jsrr { base }
+    }
+
+    /// `offset6` is in bytes.
+    pub const fn new_ldb(dr: Reg, base: Reg, offset6: SignedWord) -> Self {
+        check!(fits(offset6, 6));
+        Instruction::Ldb { dr, base, offset6 }
+    }
+
+    /// `offset6` is in words.
+    pub const fn new_ldw(dr: Reg, base: Reg, offset6: SignedWord) -> Self {
+        check!(fits(offset6, 6));
+        Instruction::Ldw { dr, base, offset6 }
+    }
+
+    /// `offset9` is in words.
+    pub const fn new_lea(dr: Reg, offset9: SignedWord) -> Self {
+        check!(fits(offset9, 9));
+        Instruction::Lea { dr, offset9 }
+    }
+
+    /// `XOR` with `-1`.
+    pub const fn new_not(dr: Reg, sr: Reg) -> Self {
+        Instruction::XorImm { dr, sr1: sr, imm5: -1 }
+    }
+
+    pub const fn new_rti() -> Self {
+        Instruction::Rti
+    }
+
+    /// Shifts by `[0, 16)` bits.
+    pub const fn new_shf(dr: Reg, sr: Reg, shift: Shift, amount4: u8) -> Self {
+        check!(amount4 < 16);
+        Instruction::Shf { dr, sr, shift, amount4 }
+    }
+
+    pub const fn new_lshf(dr: Reg, sr: Reg, amount4: u8) -> Self {
+        Self::new_shf(dr, sr, Shift::Left, amount4)
+    }
+
+    pub const fn new_rshfl(dr: Reg, sr: Reg, amount4: u8) -> Self {
+        Self::new_shf(dr, sr, Shift::RightLogical, amount4)
+    }
+
+    pub const fn new_rshfa(dr: Reg, sr: Reg, amount4: u8) -> Self {
+        Self::new_shf(dr, sr, Shift::RightArithmetic, amount4)
+    }
+
+    /// `offset6` is in bytes.
+    pub const fn new_stb(sr: Reg, base: Reg, offset6: SignedWord) -> Self {
+        check!(fits(offset6, 6));
+        Instruction::Stb { sr, base, offset6 }
+    }
+
+    /// `offset6` is in words.
+    pub const fn new_stw(sr: Reg, base: Reg, offset6: SignedWord) -> Self {
+        check!(fits(offset6, 6));
+        Instruction::Stw { sr, base, offset6 }
+    }
+
+    pub const fn new_trap(trapvec: u8) -> Self {
+        Instruction::Trap { trapvec }
+    }
+
+    pub const fn new_xor_reg(dr: Reg, sr1: Reg, sr2: Reg) -> Self {
+        Instruction::XorReg { dr, sr1, sr2 }
+    }
+
+    pub const fn new_xor_imm(dr: Reg, sr1: Reg, imm5: SignedWord) -> Self {
+        check!(fits(imm5, 5));
+        Instruction::XorImm { dr, sr1, imm5 }
+    }
+}
+
+impl Instruction {
+    pub fn sets_condition_codes(&self) -> bool {
+        use Instruction::*;
+
+        match self {
+            AddReg { .. }
+            | AddImm { .. }
+            | AndReg { .. }
+            | AndImm { .. }
+            | Ldb { .. }
+            | Ldw { .. }
+            | Shf { .. }
+            | XorReg { .. }
+            | XorImm { .. } => true,
+            // Unlike on the LC-3, `LEA` does not set the condition codes.
+            Br { .. }
+            | Jmp { .. }
+            | Jsr { .. }
+            | Jsrr { .. }
+            | Lea { .. }
+            | Rti
+            | Stb { .. }
+            | Stw { .. }
+            | Trap { .. } => false,
+        }
+    }
+}
+
+impl TryFrom<Word> for Instruction {
+    type Error = Word;
+
+    // Assuming Word = u16; compile error if not.
+    #[rustfmt::skip]
+    fn try_from(w: u16) -> Result<Self, u16> {
+        use Instruction::*;
+
+        let op_code: u8 = (w >> 12).try_into().unwrap();
+
+        Ok(match op_code {
+            0b0000 => Br { n: w.b(11), z: w.b(10), p: w.b(9), offset9: w.i16(0..8) },
+            0b0001 => match w.b(5) {
+                false => AddReg { dr: w.reg(9), sr1: w.reg(6), sr2: w.reg(0) },
+                true => AddImm { dr: w.reg(9), sr1: w.reg(6), imm5: w.i16(0..4) },
+            },
+            0b0010 => Ldb { dr: w.reg(9), base: w.reg(6), offset6: w.i16(0..5) },
+            0b0011 => Stb { sr: w.reg(9), base: w.reg(6), offset6: w.i16(0..5) },
+            0b0100 => match w.bit(11) {
+                true => Jsr { offset11: w.i16(0..10) },
+                false => Jsrr { base: w.reg(6) },
+            },
+            0b0101 => match w.bit(5) {
+                false => AndReg { dr: w.reg(9), sr1: w.reg(6), sr2: w.reg(0) },
+                true => AndImm { dr: w.reg(9), sr1: w.reg(6), imm5: w.i16(0..4) },
+            },
+            0b0110 => Ldw { dr: w.reg(9), base: w.reg(6), offset6: w.i16(0..5) },
+            0b0111 => Stw { sr: w.reg(9), base: w.reg(6), offset6: w.i16(0..5) },
+            0b1000 => Rti,
+            0b1001 => match w.b(5) {
+                false => XorReg { dr: w.reg(9), sr1: w.reg(6), sr2: w.reg(0) },
+                true => XorImm { dr: w.reg(9), sr1: w.reg(6), imm5: w.i16(0..4) },
+            },
+            0b1100 => Jmp { base: w.reg(6) },
+            0b1101 => {
+                let shift = match (w.b(5), w.b(4)) {
+                    (false, false) => Shift::Left,
+                    (false, true) => Shift::RightLogical,
+                    (true, true) => Shift::RightArithmetic,
+                    (true, false) => return Err(w),
+                };
+
+                Shf { dr: w.reg(9), sr: w.reg(6), shift, amount4: w.u8(0..3) }
+            },
+            0b1110 => Lea { dr: w.reg(9), offset9: w.i16(0..8) },
+            0b1111 => Trap { trapvec: w.u8(0..7) },
+            // Reserved:
+            0b1010 | 0b1011 => return Err(w),
+            16..=core::u8::MAX => unreachable!(),
+        })
+    }
+}
+
+impl From<Instruction> for Word {
+    fn from(ins: Instruction) -> u16 {
+        ins.to_word()
+    }
+}
+
+impl Instruction {
+    #[rustfmt::skip]
+    pub const fn to_word(&self) -> u16 {
+        #![allow(non_snake_case)]
+        use Instruction::*;
+
+        const fn Op(op: u8) -> Word { ((op as u16) & 0b1111) << 12 }
+        const fn Dr(dr: Reg) -> Word { ((dr as u16) & 0b111) << 9 }
+        const fn Sr1(sr1: Reg) -> Word { ((sr1 as u16) & 0b111) << 6 }
+        const fn Sr2(sr2: Reg) -> Word { (sr2 as u16) & 0b111 }
+        const fn Imm5(imm5: i16) -> Word { ((imm5 as u16) & 0b11111) | 0b100000 }
+        const fn N(n: bool) -> Word { (n as u16) << 11 }
+        const fn Z(z: bool) -> Word { (z as u16) << 10 }
+        const fn P(p: bool) -> Word { (p as u16) << 9 }
+        const fn O9(offset9: i16) -> Word { (offset9 as u16) & 0b111111111 }
+        const fn O11(offset11: i16) -> Word { (1 << 11) | ((offset11 as u16) & 0x7FF) }
+        const fn Base(base: Reg) -> Word { Sr1(base) }
+        const fn O6(offset6: i16) -> Word { (offset6 as u16) & 0b111111 }
+        const fn Sh(shift: Shift) -> Word {
+            match shift { Shift::Left => 0b00 << 4, Shift::RightLogical => 0b01 << 4, Shift::RightArithmetic => 0b11 << 4 }
+        }
+        const fn Amt(amount4: u8) -> Word { (amount4 as u16) & 0b1111 }
+        const fn Trapvec(trapvec: u8) -> Word { (trapvec as u16) & 0xFF }
+
+        match *self {
+            AddReg { dr, sr1, sr2 }            => Op(0b0001) | Dr(dr) | Sr1(sr1)   | Sr2(sr2)             ,
+            AddImm { dr, sr1, imm5 }           => Op(0b0001) | Dr(dr) | Sr1(sr1)   | Imm5(imm5)           ,
+            AndReg { dr, sr1, sr2 }            => Op(0b0101) | Dr(dr) | Sr1(sr1)   | Sr2(sr2)             ,
+            AndImm { dr, sr1, imm5 }           => Op(0b0101) | Dr(dr) | Sr1(sr1)   | Imm5(imm5)           ,
+            Br { n, z, p, offset9 }            => Op(0b0000) | N(n) | Z(z) | P(p)  | O9(offset9)          ,
+            Jmp { base }                       => Op(0b1100)          | Base(base)                        ,
+            Jsr { offset11 }                   => Op(0b0100)                       | O11(offset11)        ,
+            Jsrr { base }                      => Op(0b0100)          | Base(base)                        ,
+            Ldb { dr, base, offset6 }          => Op(0b0010) | Dr(dr) | Base(base) | O6(offset6)          ,
+            Ldw { dr, base, offset6 }          => Op(0b0110) | Dr(dr) | Base(base) | O6(offset6)          ,
+            Lea { dr, offset9 }                => Op(0b1110) | Dr(dr)              | O9(offset9)          ,
+            Rti                                => Op(0b1000)                                              ,
+            Shf { dr, sr, shift, amount4 }     => Op(0b1101) | Dr(dr) | Sr1(sr)    | Sh(shift) | Amt(amount4),
+            Stb { sr, base, offset6 }          => Op(0b0011) | Dr(sr) | Base(base) | O6(offset6)          ,
+            Stw { sr, base, offset6 }          => Op(0b0111) | Dr(sr) | Base(base) | O6(offset6)          ,
+            Trap { trapvec }                   => Op(0b1111)                       | Trapvec(trapvec)     ,
+            XorReg { dr, sr1, sr2 }            => Op(0b1001) | Dr(dr) | Sr1(sr1)   | Sr2(sr2)             ,
+            XorImm { dr, sr1, imm5 }           => Op(0b1001) | Dr(dr) | Sr1(sr1)   | Imm5(imm5)           ,
+        }
+    }
+}
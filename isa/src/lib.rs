@@ -75,9 +75,15 @@ pub const ADDR_SPACE_SIZE_IN_BYTES: usize = ADDR_SPACE_SIZE_IN_WORDS * size_of::
 
 mod fmt;
 mod isa;
+pub mod ext;
+pub mod lc3b;
 mod macros;
 mod misc;
 
+#[cfg_attr(all(docs, not(doctest)), doc(cfg(feature = "proptest")))]
+#[cfg(feature = "proptest")]
+pub mod strategies;
+
 #[doc(hidden)]
 pub use macros::overlap_error as _macro_support;
 
@@ -98,6 +98,10 @@ macro_rules! insn {
 
     (TRAP #$trapvec:expr $(,)? $(=> $($extra:tt)*)?) => {
         $crate::Instruction::new_trap($trapvec)
+    };
+
+    (EXT #$bits:expr $(,)? $(=> $($extra:tt)*)?) => {
+        $crate::Instruction::new_extended($bits)
     }
 }
 
@@ -0,0 +1,80 @@
+//! [`proptest`] strategies for the types in this crate.
+//!
+//! Instructions are generated through their constructors so that immediates
+//! and offsets are always in range; every [`Instruction`] produced encodes to
+//! a [`Word`] that decodes back to the same [`Instruction`].
+//!
+//! [`Instruction::Extended`] is never produced (without an
+//! [extension](crate::ext) it isn't an instruction at all).
+
+use super::{Addr, Instruction, Reg, SignedWord, Word};
+
+use proptest::prelude::*;
+use proptest::collection::btree_map;
+use proptest::sample::select;
+
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Any of the 8 general purpose registers.
+pub fn reg() -> impl Strategy<Value = Reg> {
+    select(Reg::REGS.to_vec())
+}
+
+/// Any word.
+pub fn word() -> impl Strategy<Value = Word> {
+    any::<Word>()
+}
+
+/// A signed value that fits in `bits` bits.
+pub fn signed_imm(bits: u32) -> impl Strategy<Value = SignedWord> {
+    let max: SignedWord = (1 << (bits - 1)) - 1;
+    (-max - 1)..=max
+}
+
+pub fn imm5() -> impl Strategy<Value = SignedWord> { signed_imm(5) }
+pub fn offset6() -> impl Strategy<Value = SignedWord> { signed_imm(6) }
+pub fn offset9() -> impl Strategy<Value = SignedWord> { signed_imm(9) }
+pub fn offset11() -> impl Strategy<Value = SignedWord> { signed_imm(11) }
+
+/// Any (non-extended) instruction.
+pub fn instruction() -> impl Strategy<Value = Instruction> {
+    use Instruction as I;
+
+    prop_oneof![
+        (reg(), reg(), reg()).prop_map(|(dr, sr1, sr2)| I::new_add_reg(dr, sr1, sr2)),
+        (reg(), reg(), imm5()).prop_map(|(dr, sr1, imm5)| I::new_add_imm(dr, sr1, imm5)),
+        (reg(), reg(), reg()).prop_map(|(dr, sr1, sr2)| I::new_and_reg(dr, sr1, sr2)),
+        (reg(), reg(), imm5()).prop_map(|(dr, sr1, imm5)| I::new_and_imm(dr, sr1, imm5)),
+        // `new_br` insists on at least one condition code but all zeros is a
+        // valid (never taken) branch:
+        (any::<(bool, bool, bool)>(), offset9()).prop_map(|((n, z, p), offset9)| I::Br { n, z, p, offset9 }),
+        // `JMP R7` is `RET`:
+        reg().prop_filter("JMP R7 is RET", |r| *r != Reg::R7).prop_map(I::new_jmp),
+        offset11().prop_map(I::new_jsr),
+        reg().prop_map(I::new_jsrr),
+        (reg(), offset9()).prop_map(|(dr, offset9)| I::new_ld(dr, offset9)),
+        (reg(), offset9()).prop_map(|(dr, offset9)| I::new_ldi(dr, offset9)),
+        (reg(), reg(), offset6()).prop_map(|(dr, base, offset6)| I::new_ldr(dr, base, offset6)),
+        (reg(), offset9()).prop_map(|(dr, offset9)| I::new_lea(dr, offset9)),
+        (reg(), reg()).prop_map(|(dr, sr)| I::new_not(dr, sr)),
+        Just(I::new_ret()),
+        Just(I::new_rti()),
+        (reg(), offset9()).prop_map(|(sr, offset9)| I::new_st(sr, offset9)),
+        (reg(), offset9()).prop_map(|(sr, offset9)| I::new_sti(sr, offset9)),
+        (reg(), reg(), offset6()).prop_map(|(sr, base, offset6)| I::new_str(sr, base, offset6)),
+        any::<u8>().prop_map(I::new_trap),
+    ]
+}
+
+/// Values for the 8 general purpose registers.
+pub fn register_file() -> impl Strategy<Value = [Word; Reg::NUM_REGS]> {
+    any::<[Word; Reg::NUM_REGS]>()
+}
+
+/// Up to `max_len` words at addresses in `addrs`.
+///
+/// Addresses that aren't in the map are meant to hold 0.
+pub fn memory(addrs: RangeInclusive<Addr>, max_len: usize) -> impl Strategy<Value = BTreeMap<Addr, Word>> {
+    btree_map(addrs, word(), 0..=max_len)
+}
@@ -0,0 +1,90 @@
+use lc3_isa::lc3b::{memory_index, Instruction, Shift};
+use lc3_isa::{lc3b_insn, lc3b_program, Reg::*, Word};
+
+use pretty_assertions::assert_eq;
+
+use core::convert::TryFrom;
+
+#[test]
+fn every_word_round_trips() {
+    for w in 0..=Word::max_value() {
+        let insn = match Instruction::try_from(w) {
+            Ok(insn) => insn,
+            Err(e) => {
+                assert_eq!(e, w);
+                continue;
+            }
+        };
+
+        // Decoding ignores "don't care" bits so compare instructions:
+        assert_eq!(Instruction::try_from(insn.to_word()), Ok(insn), "{:#06X}", w);
+    }
+
+    assert_eq!(Instruction::try_from(0xA000), Err(0xA000));
+    assert_eq!(Instruction::try_from(0xB000), Err(0xB000));
+    assert_eq!(Instruction::try_from(0xD020), Err(0xD020));
+}
+
+#[test]
+fn encodings() {
+    fn check(insn: Instruction, word: Word) {
+        assert_eq!(insn.to_word(), word, "{}", insn);
+        assert_eq!(Instruction::try_from(word), Ok(insn));
+    }
+
+    check(lc3b_insn!(LDB R1, R2, #-1), 0b0010_001_010_111111);
+    check(lc3b_insn!(STB R1, R2, #5), 0b0011_001_010_000101);
+    check(lc3b_insn!(LDW R3, R4, #2), 0b0110_011_100_000010);
+    check(lc3b_insn!(STW R3, R4, #-2), 0b0111_011_100_111110);
+    check(lc3b_insn!(LSHF R0, R1, #3), 0b1101_000_001_00_0011);
+    check(lc3b_insn!(RSHFL R0, R1, #15), 0b1101_000_001_01_1111);
+    check(lc3b_insn!(RSHFA R0, R1, #1), 0b1101_000_001_11_0001);
+    check(lc3b_insn!(XOR R5, R6, R7), 0b1001_101_110_000_111);
+    check(lc3b_insn!(XOR R5, R6, #7), 0b1001_101_110_1_00111);
+    check(lc3b_insn!(NOT R5, R6), 0b1001_101_110_1_11111);
+    check(lc3b_insn!(TRAP #0x25), 0xF025);
+    check(lc3b_insn!(RET), 0xC1C0);
+    check(lc3b_insn!(JSR #-1), 0b0100_1_11111111111);
+
+    assert!(!lc3b_insn!(LEA R0, #0).sets_condition_codes());
+    assert!(lc3b_insn!(LDB R0, R0, #0).sets_condition_codes());
+}
+
+#[test]
+fn display() {
+    fn check(insn: Instruction, text: &str) {
+        assert_eq!(format!("{}", insn), text);
+    }
+
+    check(lc3b_insn!(LDB R1, R2, #-1), "LDB   R1, R2, #-1");
+    check(lc3b_insn!(STW R3, R4, #2), "STW   R3, R4, #2");
+    check(lc3b_insn!(LSHF R0, R1, #3), "LSHF  R0, R1, #3");
+    check(lc3b_insn!(RSHFA R0, R1, #3), "RSHFA R0, R1, #3");
+    check(lc3b_insn!(XOR R0, R1, R2), "XOR   R0, R1, R2");
+    check(lc3b_insn!(NOT R0, R1), "NOT   R0, R1");
+    check(lc3b_insn!(JMP R7), "RET");
+    check(lc3b_insn!(BRnz #-3), "BRnz  #-3");
+    check(Instruction::new_shf(R2, R2, Shift::RightLogical, 0), "RSHFL R2, R2, #0");
+}
+
+#[test]
+fn programs_use_byte_addresses() {
+    let prog = lc3b_program! {
+        .ORIG #0x3000;
+        @START
+        LEA R0, @DATA;
+        LDB R1, R0, #1;
+        BRnzp @START;
+        HALT;
+        @DATA .FILL #0xBEEF;
+    };
+
+    let at = |addr| prog[memory_index(addr) as usize];
+
+    assert_eq!(at(0x3000), (lc3b_insn!(LEA R0, #3).into(), true));
+    assert_eq!(at(0x3002), (lc3b_insn!(LDB R1, R0, #1).into(), true));
+    assert_eq!(at(0x3004), (lc3b_insn!(BRnzp #-3).into(), true));
+    assert_eq!(at(0x3006), (lc3b_insn!(TRAP #0x25).into(), true));
+    assert_eq!(at(0x3008), (0xBEEF, true));
+    assert_eq!(at(0x300A), (0, false));
+}
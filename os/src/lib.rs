@@ -28,6 +28,17 @@ pub const OS_STARTING_SP_ADDR: lc3_isa::Addr = 0x0602;
 
 pub const OS_DEFAULT_STARTING_SP: lc3_isa::Word = 0x0700;
 
+/// The supervisor stack the OS sets up (for use with a
+/// [`StackChecker`](lc3_baseline_sim::stack_check::StackChecker)); it sits
+/// just above the OS's settings.
+pub const OS_STACK_BOUNDS: lc3_baseline_sim::stack_check::StackBounds =
+    lc3_baseline_sim::stack_check::StackBounds {
+        base: OS_DEFAULT_STARTING_SP,
+        limit: OS_STARTING_SP_ADDR + 1,
+    };
+
+pub const OS_EXTENDED_START_ADDR: lc3_isa::Addr = 0x0700;
+
 mod os;
 
 pub mod traps;
@@ -1,14 +1,15 @@
 //! (TODO!)
 
 use super::{ERROR_ON_ACV_SETTING_ADDR, USER_PROG_START_ADDR, traps as t};
-use super::{OS_DEFAULT_STARTING_SP, OS_STARTING_SP_ADDR};
+use super::{OS_DEFAULT_STARTING_SP, OS_STARTING_SP_ADDR, OS_EXTENDED_START_ADDR};
 
 use lc3_isa::util::{AssembledProgram, MemoryDump};
 use lc3_isa::{Word, OS_START_ADDR};
 use lc3_baseline_sim::{KBSR_ADDR, KBDR_ADDR, DSR_ADDR, DDR_ADDR};
-use lc3_baseline_sim::{G0CR_ADDR, A0CR_ADDR, P0CR_ADDR, T0CR_ADDR, CLKR_ADDR};
-use lc3_baseline_sim::{GPIO_OFFSET, ADC_OFFSET, PWM_OFFSET, TIMER_OFFSET, MISC_OFFSET};
-use lc3_baseline_sim::{GPIO_BASE_INT_VEC, TIMER_BASE_INT_VEC};
+use lc3_baseline_sim::{G0CR_ADDR, A0CR_ADDR, ADCSPR_ADDR, P0CR_ADDR, P0PR_ADDR, P0IR_ADDR, T0CR_ADDR, T0CCR_ADDR, T0CPR_ADDR, CLKR_ADDR};
+use lc3_baseline_sim::{ICR_ADDR, IMR0_ADDR, IPR0_ADDR};
+use lc3_baseline_sim::{GPIO_OFFSET, ADC_OFFSET, PWM_OFFSET, TIMER_OFFSET, MISC_OFFSET, INTERRUPT_OFFSET};
+use lc3_baseline_sim::{GPIO_BASE_INT_VEC, ADC_BASE_INT_VEC, PWM_BASE_INT_VEC, TIMER_BASE_INT_VEC};
 
 use lazy_static::lazy_static;
 
@@ -92,8 +93,8 @@ const fn os() -> AssembledProgram {
         .ORIG #t::gpio::GET_MODE     as W;  .FILL @TRAP_READ_GPIO_MODE;         // 0x34
         .ORIG #t::gpio::WRITE        as W;  .FILL @TRAP_WRITE_GPIO_DATA;        // 0x35
         .ORIG #t::gpio::READ         as W;  .FILL @TRAP_READ_GPIO_DATA;         // 0x36
-        .FILL @UNKNOWN_TRAP; // 0x37
-        .FILL @UNKNOWN_TRAP; // 0x38
+        .ORIG #t::gpio::SET_TRIGGER  as W;  .FILL @TRAP_SET_GPIO_TRIGGER;       // 0x37
+        .ORIG #t::gpio::SET_PULL     as W;  .FILL @TRAP_SET_GPIO_PULL;          // 0x38
         .FILL @UNKNOWN_TRAP; // 0x39
         .FILL @UNKNOWN_TRAP; // 0x3A
         .FILL @UNKNOWN_TRAP; // 0x3B
@@ -107,10 +108,10 @@ const fn os() -> AssembledProgram {
         .ORIG #t::adc::DISABLE       as W;  .FILL @TRAP_SET_ADC_DISABLE;        // 0x41
         .ORIG #t::adc::GET_MODE      as W;  .FILL @TRAP_READ_ADC_MODE;          // 0x42
         .ORIG #t::adc::READ          as W;  .FILL @TRAP_READ_ADC_DATA;          // 0x43
-        .FILL @UNKNOWN_TRAP; // 0x44
-        .FILL @UNKNOWN_TRAP; // 0x45
-        .FILL @UNKNOWN_TRAP; // 0x46
-        .FILL @UNKNOWN_TRAP; // 0x47
+        .ORIG #t::adc::CONTINUOUS    as W;  .FILL @TRAP_SET_ADC_CONTINUOUS;     // 0x44
+        .ORIG #t::adc::ONE_SHOT      as W;  .FILL @TRAP_SET_ADC_ONE_SHOT;       // 0x45
+        .ORIG #t::adc::INTERRUPT     as W;  .FILL @TRAP_SET_ADC_INTERRUPT;      // 0x46
+        .ORIG #t::adc::SAMPLE_PERIOD as W;  .FILL @TRAP_SET_ADC_SAMPLE_PERIOD;  // 0x47
         .FILL @UNKNOWN_TRAP; // 0x48
         .FILL @UNKNOWN_TRAP; // 0x49
         .FILL @UNKNOWN_TRAP; // 0x4A
@@ -125,11 +126,11 @@ const fn os() -> AssembledProgram {
         .ORIG #t::pwm::DISABLE       as W;  .FILL @TRAP_DISABLE_PWM;            // 0x51
         .ORIG #t::pwm::GET_PERIOD    as W;  .FILL @TRAP_READ_PWM_PERIOD;        // 0x52
         .ORIG #t::pwm::GET_DUTY      as W;  .FILL @TRAP_READ_PWM_DUTY_CYCLE;    // 0x53
-        .FILL @UNKNOWN_TRAP; // 0x54
-        .FILL @UNKNOWN_TRAP; // 0x55
-        .FILL @UNKNOWN_TRAP; // 0x56
-        .FILL @UNKNOWN_TRAP; // 0x57
-        .FILL @UNKNOWN_TRAP; // 0x58
+        .ORIG #t::pwm::INTERRUPT     as W;  .FILL @TRAP_SET_PWM_INTERRUPT;      // 0x54
+        .ORIG #t::pwm::DISABLE_INTERRUPT as W; .FILL @TRAP_DISABLE_PWM_INTERRUPT; // 0x55
+        .ORIG #t::pwm::ENABLE_FINE   as W;  .FILL @TRAP_SET_PWM_FINE;           // 0x56
+        .ORIG #t::pwm::GET_FINE_PERIOD as W; .FILL @TRAP_READ_PWM_FINE_PERIOD;  // 0x57
+        .ORIG #t::pwm::GET_FINE_DUTY as W;  .FILL @TRAP_READ_PWM_FINE_DUTY_CYCLE; // 0x58
         .FILL @UNKNOWN_TRAP; // 0x59
         .FILL @UNKNOWN_TRAP; // 0x5A
         .FILL @UNKNOWN_TRAP; // 0x5B
@@ -144,9 +145,9 @@ const fn os() -> AssembledProgram {
         .ORIG #t::timers::DISABLE    as W;  .FILL @TRAP_SET_TIMER_DISABLE;      // 0x62
         .ORIG #t::timers::GET_MODE   as W;  .FILL @TRAP_READ_TIMER_MODE;        // 0x63
         .ORIG #t::timers::GET_PERIOD as W;  .FILL @TRAP_READ_TIMER_PERIOD;      // 0x64
-        .FILL @UNKNOWN_TRAP; // 0x65
-        .FILL @UNKNOWN_TRAP; // 0x66
-        .FILL @UNKNOWN_TRAP; // 0x67
+        .ORIG #t::timers::FREE_RUNNING as W; .FILL @TRAP_SET_TIMER_FREE_RUNNING; // 0x65
+        .ORIG #t::timers::CAPTURE    as W;  .FILL @TRAP_SET_TIMER_CAPTURE;      // 0x66
+        .ORIG #t::timers::GET_CAPTURE as W; .FILL @TRAP_READ_TIMER_CAPTURE;     // 0x67
         .FILL @UNKNOWN_TRAP; // 0x68
         .FILL @UNKNOWN_TRAP; // 0x69
         .FILL @UNKNOWN_TRAP; // 0x6A
@@ -174,12 +175,13 @@ const fn os() -> AssembledProgram {
         .FILL @UNKNOWN_TRAP; // 0x7E
         .FILL @UNKNOWN_TRAP; // 0x7F
 
-        .FILL @UNKNOWN_TRAP; // 0x80
-        .FILL @UNKNOWN_TRAP; // 0x81
-        .FILL @UNKNOWN_TRAP; // 0x82
-        .FILL @UNKNOWN_TRAP; // 0x83
-        .FILL @UNKNOWN_TRAP; // 0x84
-        .FILL @UNKNOWN_TRAP; // 0x85
+        .ORIG #INTERRUPT_OFFSET as Word;
+        .ORIG #t::interrupts::SET_PRIORITY as W; .FILL @TRAP_SET_INTERRUPT_PRIORITY;  // 0x80
+        .ORIG #t::interrupts::GET_PRIORITY as W; .FILL @TRAP_READ_INTERRUPT_PRIORITY; // 0x81
+        .ORIG #t::interrupts::MASK         as W; .FILL @TRAP_MASK_INTERRUPT;          // 0x82
+        .ORIG #t::interrupts::UNMASK       as W; .FILL @TRAP_UNMASK_INTERRUPT;        // 0x83
+        .ORIG #t::interrupts::ENABLE       as W; .FILL @TRAP_ENABLE_INTERRUPTS;       // 0x84
+        .ORIG #t::interrupts::DISABLE      as W; .FILL @TRAP_DISABLE_INTERRUPTS;      // 0x85
         .FILL @UNKNOWN_TRAP; // 0x86
         .FILL @UNKNOWN_TRAP; // 0x87
         .FILL @UNKNOWN_TRAP; // 0x88
@@ -1063,7 +1065,8 @@ const fn os() -> AssembledProgram {
         // R0 = GPIO pin to enable
         // R1 = mode to set
         @SET_GPIO_MODE
-            ADD R6, R6, #-2;                // Save R4, R7 on stack
+            ADD R6, R6, #-3;                // Save R2, R4, R7 on stack
+            STR R2, R6, #2;
             STR R4, R6, #1;
             STR R7, R6, #0;
 
@@ -1075,11 +1078,15 @@ const fn os() -> AssembledProgram {
             LD R4, @OS_GPIO_BASE_ADDR;      // Load GPIO base address into R2
             ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
             ADD R4, R4, R0;                 // R4 contains control address of pin number in R0
-            STR R1, R4, #0;                 // Write GPIO mode to control register
+            LDR R2, R4, #0;                 // Read the current value of the control register
+            AND R2, R2, #-4;                // Clear the mode bits (keeps the trigger and pull)
+            ADD R2, R2, R1;                 // Set the new mode
+            STR R2, R4, #0;                 // Write GPIO mode to control register
         @SKIP_SET_GPIO_MODE
-            LDR R7, R6, #0;                 // Restore R4, R7
+            LDR R7, R6, #0;                 // Restore R2, R4, R7
             LDR R4, R6, #1;
-            ADD R6, R6, #2;
+            LDR R2, R6, #2;
+            ADD R6, R6, #3;
             RET;
 
         // Sets GPIO pin to input mode
@@ -1133,8 +1140,9 @@ const fn os() -> AssembledProgram {
             LD R4, @OS_GPIO_BASE_ADDR;      // Load GPIO base address into R4
             ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
             ADD R4, R4, R0;                 // R4 contains control address of pin number in R0
-            AND R1, R1, #0;                 // Set R1 to 3 (Interrupt)
-            ADD R1, R1, #3;
+            LDR R1, R4, #0;                 // Read the current value of the control register
+            AND R1, R1, #-4;                // Clear the mode bits (keeps the trigger and pull)
+            ADD R1, R1, #3;                 // Set the mode to 3 (Interrupt)
             STR R1, R4, #0;                 // Write GPIO mode to control register
         @SKIP_SET_GPIO_INTERRUPT
             LDR R7, R6, #0;                 // Restore R1, R4, R7
@@ -1174,6 +1182,7 @@ const fn os() -> AssembledProgram {
             ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
             ADD R4, R4, R0;                 // R3 contains data address of pin number in R0
             LDR R0, R4, #0;                 // Reads mode from pin into R0
+            AND R0, R0, #3;                 // Drop the trigger and pull bits
         @SKIP_READ_GPIO_MODE
             LDR R7, R6, #0;                 // Restore R4, R7
             LDR R4, R6, #1;
@@ -1228,58 +1237,147 @@ const fn os() -> AssembledProgram {
             ADD R6, R6, #2;
             RTI;
 
-        // Sets mode of ADC pin
-        // R0 = ADC pin to set mode of
-        // R1 = mode to set
-        @SET_ADC_MODE
-            ADD R6, R6, #-3;                // Save R1, R4, R7 on stack
+        // Sets a field in a GPIO pin's control register
+        // R0 = GPIO pin to set
+        // R1 = value of the field (already shifted into place)
+        // R2 = mask that clears the field
+        @SET_GPIO_CR_FIELD
+            ADD R6, R6, #-3;                // Save R3, R4, R7 on stack
+            STR R3, R6, #2;
+            STR R4, R6, #1;
+            STR R7, R6, #0;
+
+            AND R4, R4, #0;                 // Set R4 to # of GPIO pins
+            ADD R4, R4, #lc3_traits::peripherals::gpio::GpioPin::NUM_PINS as i16;
+            JSR @CHECK_OUT_OF_BOUNDS;
+            BRn @SKIP_SET_GPIO_CR_FIELD;
+
+            LD R4, @OS_GPIO_BASE_ADDR;      // Load GPIO base address into R4
+            ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
+            ADD R4, R4, R0;                 // R4 contains control address of pin number in R0
+            LDR R3, R4, #0;                 // Read the current value of the control register
+            AND R3, R3, R2;                 // Clear the field
+            ADD R3, R3, R1;                 // Set the new value of the field
+            STR R3, R4, #0;                 // Write the control register
+        @SKIP_SET_GPIO_CR_FIELD
+            LDR R7, R6, #0;                 // Restore R3, R4, R7
+            LDR R4, R6, #1;
+            LDR R3, R6, #2;
+            ADD R6, R6, #3;
+            RET;
+
+        // Sets the interrupt trigger of a GPIO pin
+        // R0 = GPIO pin to set
+        // R1 = trigger to set
+        @TRAP_SET_GPIO_TRIGGER
+            ADD R6, R6, #-3;                // Save R1, R2, R7 on stack
             STR R1, R6, #2;
+            STR R2, R6, #1;
+            STR R7, R6, #0;
+
+            AND R1, R1, #7;                 // Only the lower 3 bits are the trigger
+            ADD R1, R1, R1;                 // Shift the trigger into bits [4:2]
+            ADD R1, R1, R1;
+            LD R2, @OS_GPIO_TRIGGER_MASK;
+            JSR @SET_GPIO_CR_FIELD;
+
+            LDR R7, R6, #0;                 // Restore R1, R2, R7
+            LDR R2, R6, #1;
+            LDR R1, R6, #2;
+            ADD R6, R6, #3;
+            RTI;
+
+        // Sets the pull of a GPIO pin
+        // R0 = GPIO pin to set
+        // R1 = pull to set
+        @TRAP_SET_GPIO_PULL
+            ADD R6, R6, #-3;                // Save R1, R2, R7 on stack
+            STR R1, R6, #2;
+            STR R2, R6, #1;
+            STR R7, R6, #0;
+
+            AND R1, R1, #3;                 // Only the lower 2 bits are the pull
+            ADD R1, R1, R1;                 // Shift the pull into bits [6:5]
+            ADD R1, R1, R1;
+            ADD R1, R1, R1;
+            ADD R1, R1, R1;
+            ADD R1, R1, R1;
+            LD R2, @OS_GPIO_PULL_MASK;
+            JSR @SET_GPIO_CR_FIELD;
+
+            LDR R7, R6, #0;                 // Restore R1, R2, R7
+            LDR R2, R6, #1;
+            LDR R1, R6, #2;
+            ADD R6, R6, #3;
+            RTI;
+
+        @OS_GPIO_TRIGGER_MASK .FILL #0xFFE3;
+        @OS_GPIO_PULL_MASK .FILL #0xFF9F;
+
+        // Sets a field in an ADC pin's control register
+        // R0 = ADC pin to set
+        // R1 = value of the field (already shifted into place)
+        // R2 = mask that clears the field
+        @SET_ADC_CR_FIELD
+            ADD R6, R6, #-3;                // Save R3, R4, R7 on stack
+            STR R3, R6, #2;
             STR R4, R6, #1;
             STR R7, R6, #0;
 
             AND R4, R4, #0;                 // Set R4 to # of ADC pins
             ADD R4, R4, #lc3_traits::peripherals::adc::AdcPin::NUM_PINS as i16;
             JSR @CHECK_OUT_OF_BOUNDS;
-            BRn @SKIP_SET_ADC_MODE;
+            BRn @SKIP_SET_ADC_CR_FIELD;
 
-            LD R4, @OS_ADC_BASE_ADDR;       // Load ADC base address into R2
+            LD R4, @OS_ADC_BASE_ADDR;       // Load ADC base address into R4
             ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
             ADD R4, R4, R0;                 // R4 contains control address of pin number in R0
-            STR R1, R4, #0;                 // Writes ADC mode to control register
-        @SKIP_SET_ADC_MODE
-            LDR R7, R6, #0;                 // Restore R1, R4, R7
+            LDR R3, R4, #0;                 // Read the current value of the control register
+            AND R3, R3, R2;                 // Clear the field
+            ADD R3, R3, R1;                 // Set the new value of the field
+            STR R3, R4, #0;                 // Write the control register
+        @SKIP_SET_ADC_CR_FIELD
+            LDR R7, R6, #0;                 // Restore R3, R4, R7
             LDR R4, R6, #1;
-            LDR R1, R6, #2;
+            LDR R3, R6, #2;
             ADD R6, R6, #3;
             RET;
 
         // Sets mode of ADC pin to Enabled
         // R0 = ADC pin to enable
         @TRAP_SET_ADC_ENABLE
-            ADD R6, R6, #-2;                // Save R1, R7 on stack
-            STR R1, R6, #1;
+            ADD R6, R6, #-3;                // Save R1, R2, R7 on stack
+            STR R1, R6, #2;
+            STR R2, R6, #1;
             STR R7, R6, #0;
 
-            AND R1, R1, #0;                 // Sets mode to 1, to enable ADC
+            AND R1, R1, #0;                 // Sets state bit to 1, to enable ADC
             ADD R1, R1, #1;
-            JSR @SET_ADC_MODE;
-            LDR R7, R6, #0;                 // Restore R1, R7
-            LDR R1, R6, #1;
-            ADD R6, R6, #2;
+            AND R2, R2, #0;                 // Mask that clears the state bit
+            ADD R2, R2, #-2;
+            JSR @SET_ADC_CR_FIELD;
+            LDR R7, R6, #0;                 // Restore R1, R2, R7
+            LDR R2, R6, #1;
+            LDR R1, R6, #2;
+            ADD R6, R6, #3;
             RTI;
 
         // Sets mode of ADC pin to Disabled
         // R0 = ADC pin to disable
         @TRAP_SET_ADC_DISABLE
-            ADD R6, R6, #-2;                // Save R1, R7 on stack
-            STR R1, R6, #1;
+            ADD R6, R6, #-3;                // Save R1, R2, R7 on stack
+            STR R1, R6, #2;
+            STR R2, R6, #1;
             STR R7, R6, #0;
 
-            AND R1, R1, #0;                 // Sets mode to 0, which is mode to disable ADC
-            JSR @SET_ADC_MODE;
-            LDR R7, R6, #0;                 // Restore R1, R7
-            LDR R1, R6, #1;
-            ADD R6, R6, #2;
+            AND R1, R1, #0;                 // Sets state bit to 0, which disables the ADC
+            AND R2, R2, #0;                 // Mask that clears the state bit
+            ADD R2, R2, #-2;
+            JSR @SET_ADC_CR_FIELD;
+            LDR R7, R6, #0;                 // Restore R1, R2, R7
+            LDR R2, R6, #1;
+            LDR R1, R6, #2;
+            ADD R6, R6, #3;
             RTI;
 
         // Reads and returns mode of ADC pin
@@ -1299,6 +1397,7 @@ const fn os() -> AssembledProgram {
             ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
             ADD R4, R4, R0;                 // R3 contains control address of pin number in R0
             LDR R0, R4, #0;                 // Reads mode from pin into R0
+            AND R0, R0, #1;                 // Only the state bit is the mode
         @SKIP_READ_ADC_MODE
             LDR R7, R6, #0;                 // Restore R4, R7
             LDR R4, R6, #1;
@@ -1341,10 +1440,11 @@ const fn os() -> AssembledProgram {
 
         // PWM set
         // R0 = PWM pin to set
-        // R1 = period to set
-        // R2 = duty cycle to set
+        // R1 = period to set (in milliseconds)
+        // R2 = duty cycle to set (out of 255)
         @TRAP_SET_PWM
-            ADD R6, R6, #-2;                // Save R4, R7 on stack
+            ADD R6, R6, #-3;                // Save R3, R4, R7 on stack
+            STR R3, R6, #2;
             STR R4, R6, #1;
             STR R7, R6, #0;
 
@@ -1357,12 +1457,24 @@ const fn os() -> AssembledProgram {
             ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
             ADD R4, R4, R0;                 // R4 contains address of period control register
             STR R1, R4, #0;                 // Write period to PWM
+            LD R7, @OS_PWM_DUTY_MASK;
+            AND R7, R2, R7;                 // Only the low 8 bits of the duty cycle are used
+            ADD R3, R7, R7;                 // Duty cycle register is out of xFFFF, so
+            ADD R3, R3, R3;                 // multiply by x101
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R3, R3, R7;
             ADD R4, R4, #1;                 // R4 contains address of duty cycle register
-            STR R2, R4, #0;                 // Write duty cycle to PWM
+            STR R3, R4, #0;                 // Write duty cycle to PWM
         @SKIP_SET_PWM
-            LDR R7, R6, #0;                 // Restore R4, R7
+            LDR R7, R6, #0;                 // Restore R3, R4, R7
             LDR R4, R6, #1;
-            ADD R6, R6, #2;
+            LDR R3, R6, #2;
+            ADD R6, R6, #3;
             RTI;
 
         // PWM disable
@@ -1390,7 +1502,7 @@ const fn os() -> AssembledProgram {
 
         // Reads and returns period of PWM pin
         // R0 = PWM pin to read from
-        // -> R0 = mode of PWM pin
+        // -> R0 = period of PWM pin (in milliseconds)
         @TRAP_READ_PWM_PERIOD
             ADD R6, R6, #-2;                // Save R4, R7 on stack
             STR R4, R6, #1;
@@ -1403,8 +1515,8 @@ const fn os() -> AssembledProgram {
 
             LD R4, @OS_PWM_BASE_ADDR;
             ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
-            ADD R4, R4, R0;                 // R3 contains control address of pin number in R0
-            LDR R0, R4, #0;                 // Reads mode from pin into R0
+            ADD R4, R4, R0;                 // R4 contains control address of pin number in R0
+            LDR R0, R4, #0;                 // Reads period from pin into R0
             BRnzp @END_READ_PWM_PERIOD;
 
         @SKIP_READ_PWM_PERIOD
@@ -1416,9 +1528,9 @@ const fn os() -> AssembledProgram {
             ADD R6, R6, #2;
             RTI;
 
-        // Reads and returns data from PWM pin
+        // Reads and returns duty cycle of PWM pin
         // R0 = PWM pin to read from
-        // -> R0 = data from PWM pin
+        // -> R0 = duty cycle of PWM pin (out of 255)
         @TRAP_READ_PWM_DUTY_CYCLE
             ADD R6, R6, #-2;                // Save R4, R7 on stack
             STR R4, R6, #1;
@@ -1432,14 +1544,29 @@ const fn os() -> AssembledProgram {
             LD R4, @OS_PWM_BASE_ADDR;
             ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
             ADD R4, R4, R0;                 // and adding 1
-            ADD R4, R4, #1;                 // R3 contains data address of pin number in R0
-            LDR R0, R4, #0;                 // Reads data from pin into R0
+            ADD R4, R4, #1;                 // R4 contains data address of pin number in R0
+            LDR R7, R4, #0;                 // Reads duty cycle (out of xFFFF) from pin
+
+            AND R0, R0, #0;                 // R0 gets the top 8 bits of it, one at a time
+            AND R4, R4, #0;
+            ADD R4, R4, #8;
+        @READ_PWM_DUTY_CYCLE_BIT
+            ADD R0, R0, R0;
+            ADD R7, R7, #0;
+            BRzp @READ_PWM_DUTY_CYCLE_ZERO;
+            ADD R0, R0, #1;
+        @READ_PWM_DUTY_CYCLE_ZERO
+            ADD R7, R7, R7;
+            ADD R4, R4, #-1;
+            BRp @READ_PWM_DUTY_CYCLE_BIT;
         @SKIP_READ_PWM_DUTY_CYCLE
             LDR R7, R6, #0;                 // Restore R4, R7
             LDR R4, R6, #1;
             ADD R6, R6, #2;
             RTI;
 
+        @OS_PWM_DUTY_MASK .FILL #0x00FF;
+
         // Timer Pin Set
         // R0= Timer Pin to set mode of
         // R1= mode to be set
@@ -1617,6 +1744,154 @@ const fn os() -> AssembledProgram {
             LDR R0, R0, #0;                 // Read data from clock
             RTI;
 
+        // Sets ADC pin to continuous mode
+        // R0 = ADC pin to set
+        @TRAP_SET_ADC_CONTINUOUS
+            ADD R6, R6, #-3;                // Save R1, R2, R7 on stack
+            STR R1, R6, #2;
+            STR R2, R6, #1;
+            STR R7, R6, #0;
+
+            AND R1, R1, #0;                 // Sets mode bit to 1 (Continuous)
+            ADD R1, R1, #2;
+            AND R2, R2, #0;                 // Mask that clears the mode bit
+            ADD R2, R2, #-3;
+            JSR @SET_ADC_CR_FIELD;
+            LDR R7, R6, #0;                 // Restore R1, R2, R7
+            LDR R2, R6, #1;
+            LDR R1, R6, #2;
+            ADD R6, R6, #3;
+            RTI;
+
+        // Sets ADC pin to one shot mode
+        // R0 = ADC pin to set
+        @TRAP_SET_ADC_ONE_SHOT
+            ADD R6, R6, #-3;                // Save R1, R2, R7 on stack
+            STR R1, R6, #2;
+            STR R2, R6, #1;
+            STR R7, R6, #0;
+
+            AND R1, R1, #0;                 // Sets mode bit to 0 (One Shot)
+            AND R2, R2, #0;                 // Mask that clears the mode bit
+            ADD R2, R2, #-3;
+            JSR @SET_ADC_CR_FIELD;
+            LDR R7, R6, #0;                 // Restore R1, R2, R7
+            LDR R2, R6, #1;
+            LDR R1, R6, #2;
+            ADD R6, R6, #3;
+            RTI;
+
+        // Sets the interrupt of an ADC pin and its ISR address in the IVT
+        // R0 = ADC pin to set
+        // R1 = Address of interrupt service routine
+        // R2 = interrupt to set
+        // R3 = threshold (for threshold crossing interrupts)
+        @TRAP_SET_ADC_INTERRUPT
+            ADD R6, R6, #-5;                // Save R1, R2, R3, R4, R7 on stack
+            STR R1, R6, #4;
+            STR R2, R6, #3;
+            STR R3, R6, #2;
+            STR R4, R6, #1;
+            STR R7, R6, #0;
+
+            AND R4, R4, #0;                 // Set R4 to # of ADC pins
+            ADD R4, R4, #lc3_traits::peripherals::adc::AdcPin::NUM_PINS as i16;
+            JSR @CHECK_OUT_OF_BOUNDS;
+            BRn @SKIP_SET_ADC_INTERRUPT;
+
+            LD R4, @OS_ADC_BASE_INTVEC;     // Load ADC base interrupt vector address
+            ADD R4, R4, R0;                 // R4 contains address of pin in R0
+            STR R1, R4, #0;                 // Load service routine address into vector table
+
+            AND R1, R2, #3;                 // Only the lower 2 bits are the interrupt
+            ADD R1, R1, R1;                 // Shift the interrupt into bits [3:2]
+            ADD R1, R1, R1;
+            ADD R3, R3, R3;                 // Shift the threshold into bits [15:8]
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R3, R3, R3;
+            ADD R1, R1, R3;
+            AND R2, R2, #0;                 // Mask that keeps only the state and mode bits
+            ADD R2, R2, #3;
+            JSR @SET_ADC_CR_FIELD;
+        @SKIP_SET_ADC_INTERRUPT
+            LDR R7, R6, #0;                 // Restore R1, R2, R3, R4, R7
+            LDR R4, R6, #1;
+            LDR R3, R6, #2;
+            LDR R2, R6, #3;
+            LDR R1, R6, #4;
+            ADD R6, R6, #5;
+            RTI;
+
+        // Sets the sample period used by ADC pins in continuous mode
+        // R0 = sample period to set (in milliseconds)
+        @TRAP_SET_ADC_SAMPLE_PERIOD
+            STI R0, @OS_ADCSPR_ADDR;
+            RTI;
+
+        @OS_ADC_BASE_INTVEC .FILL #ADC_BASE_INT_VEC;
+        @OS_ADCSPR_ADDR .FILL #ADCSPR_ADDR;
+
+        // Turns on the rollover interrupt of a PWM pin and sets its ISR address
+        // in the IVT
+        // R0 = PWM pin to set
+        // R1 = Address of interrupt service routine
+        @TRAP_SET_PWM_INTERRUPT
+            ADD R6, R6, #-3;                // Save R1, R4, R7 on stack
+            STR R1, R6, #2;
+            STR R4, R6, #1;
+            STR R7, R6, #0;
+
+            AND R4, R4, #0;                 // Set R4 to # of PWM pins
+            ADD R4, R4, #lc3_traits::peripherals::pwm::PwmPin::NUM_PINS as i16;
+            JSR @CHECK_OUT_OF_BOUNDS;
+            BRn @SKIP_SET_PWM_INTERRUPT;
+
+            LD R4, @OS_PWM_BASE_INTVEC;     // Load PWM base interrupt vector address
+            ADD R4, R4, R0;                 // R4 contains address of pin in R0
+            STR R1, R4, #0;                 // Load service routine address into vector table
+
+            LD R4, @OS_PWM_IR_BASE_ADDR;
+            ADD R4, R4, R0;                 // R4 contains interrupt address of pin number in R0
+            AND R1, R1, #0;                 // Sets the rollover interrupt bit
+            ADD R1, R1, #1;
+            STR R1, R4, #0;
+        @SKIP_SET_PWM_INTERRUPT
+            LDR R7, R6, #0;                 // Restore R1, R4, R7
+            LDR R4, R6, #1;
+            LDR R1, R6, #2;
+            ADD R6, R6, #3;
+            RTI;
+
+        // Turns off the rollover interrupt of a PWM pin
+        // R0 = PWM pin to set
+        @TRAP_DISABLE_PWM_INTERRUPT
+            ADD R6, R6, #-2;                // Save R4, R7 on stack
+            STR R4, R6, #1;
+            STR R7, R6, #0;
+
+            AND R4, R4, #0;                 // Set R4 to # of PWM pins
+            ADD R4, R4, #lc3_traits::peripherals::pwm::PwmPin::NUM_PINS as i16;
+            JSR @CHECK_OUT_OF_BOUNDS;
+            BRn @SKIP_DISABLE_PWM_INTERRUPT;
+
+            LD R4, @OS_PWM_IR_BASE_ADDR;
+            ADD R4, R4, R0;                 // R4 contains interrupt address of pin number in R0
+            AND R7, R7, #0;
+            STR R7, R4, #0;                 // Clears the rollover interrupt bit
+        @SKIP_DISABLE_PWM_INTERRUPT
+            LDR R7, R6, #0;                 // Restore R4, R7
+            LDR R4, R6, #1;
+            ADD R6, R6, #2;
+            RTI;
+
+        @OS_PWM_BASE_INTVEC .FILL #PWM_BASE_INT_VEC;
+        @OS_PWM_IR_BASE_ADDR .FILL #P0IR_ADDR;
+
         //// Exception Handlers ////
 
         // Triggered when an RTI is called when in user mode.
@@ -1807,6 +2082,389 @@ const fn os() -> AssembledProgram {
 
         .ORIG #OS_STARTING_SP_ADDR;
         .FILL #OS_DEFAULT_STARTING_SP;
+
+        //// Extended routines ////
+        // There's no room left below the configuration variables so newer
+        // routines live in the page above the default stack.
+
+        .ORIG #OS_EXTENDED_START_ADDR;
+
+        // Sets timer to FreeRunning mode with period
+        // R0 = Timer to set
+        // R1 = period to be set
+        @TRAP_SET_TIMER_FREE_RUNNING
+            ADD R6, R6, #-2;                // Save R2, R4 on stack
+            STR R2, R6, #1;
+            STR R4, R6, #0;
+
+            ADD R0, R0, #0;                 // Check that R0 is a valid timer
+            BRn @SKIP_SET_TIMER_FREE_RUNNING;
+            ADD R4, R0, #-(lc3_traits::peripherals::timers::TimerId::NUM_TIMERS as i16);
+            BRzp @SKIP_SET_TIMER_FREE_RUNNING;
+
+            LD R4, @OS_TIMER_CR_BASE_ADDR;
+            ADD R4, R4, R0;                 // Calculate timer address offset by doubling timer number
+            ADD R4, R4, R0;                 // R4 contains control address of timer in R0
+            AND R2, R2, #0;                 // Set the mode to FreeRunning
+            ADD R2, R2, #2;
+            STR R2, R4, #0;
+            STR R1, R4, #1;                 // Then set the period
+        @SKIP_SET_TIMER_FREE_RUNNING
+            LDR R4, R6, #0;                 // Restore R2, R4
+            LDR R2, R6, #1;
+            ADD R6, R6, #2;
+            RTI;
+
+        // Sets the input capture source of a timer
+        // R0 = Timer to set
+        // R1 = GPIO pin to capture on
+        // R2 = edge(s) to capture on (0 disables capture)
+        @TRAP_SET_TIMER_CAPTURE
+            ADD R6, R6, #-3;                // Save R1, R2, R4 on stack
+            STR R1, R6, #2;
+            STR R2, R6, #1;
+            STR R4, R6, #0;
+
+            ADD R0, R0, #0;                 // Check that R0 is a valid timer
+            BRn @SKIP_SET_TIMER_CAPTURE;
+            ADD R4, R0, #-(lc3_traits::peripherals::timers::TimerId::NUM_TIMERS as i16);
+            BRzp @SKIP_SET_TIMER_CAPTURE;
+
+            AND R1, R1, #7;                 // Shift the pin number into bits [4:2]
+            ADD R1, R1, R1;
+            ADD R1, R1, R1;
+            AND R2, R2, #3;                 // and put the edge in bits [1:0]
+            ADD R1, R1, R2;
+
+            LD R4, @OS_TIMER_CCR_BASE_ADDR;
+            ADD R4, R4, R0;                 // R4 contains capture control address of timer in R0
+            STR R1, R4, #0;
+        @SKIP_SET_TIMER_CAPTURE
+            LDR R4, R6, #0;                 // Restore R1, R2, R4
+            LDR R2, R6, #1;
+            LDR R1, R6, #2;
+            ADD R6, R6, #3;
+            RTI;
+
+        // Reads and returns the last count captured by a timer
+        // R0 = Timer to read from
+        // -> R0 = captured count
+        @TRAP_READ_TIMER_CAPTURE
+            ADD R6, R6, #-1;                // Save R4 on stack
+            STR R4, R6, #0;
+
+            ADD R0, R0, #0;                 // Check that R0 is a valid timer
+            BRn @SKIP_READ_TIMER_CAPTURE;
+            ADD R4, R0, #-(lc3_traits::peripherals::timers::TimerId::NUM_TIMERS as i16);
+            BRzp @SKIP_READ_TIMER_CAPTURE;
+
+            LD R4, @OS_TIMER_CPR_BASE_ADDR;
+            ADD R4, R4, R0;                 // R4 contains capture address of timer in R0
+            LDR R0, R4, #0;
+        @SKIP_READ_TIMER_CAPTURE
+            LDR R4, R6, #0;                 // Restore R4
+            ADD R6, R6, #1;
+            RTI;
+
+        @OS_TIMER_CR_BASE_ADDR .FILL #T0CR_ADDR;
+        @OS_TIMER_CCR_BASE_ADDR .FILL #T0CCR_ADDR;
+        @OS_TIMER_CPR_BASE_ADDR .FILL #T0CPR_ADDR;
+
+        // Sets the priority of an interrupt source
+        // R0 = source to set
+        // R1 = priority (only the low 3 bits are used)
+        @TRAP_SET_INTERRUPT_PRIORITY
+            ADD R6, R6, #-5;                // Save R2, R3, R4, R5, R7 on stack
+            STR R2, R6, #4;
+            STR R3, R6, #3;
+            STR R4, R6, #2;
+            STR R5, R6, #1;
+            STR R7, R6, #0;
+
+            ADD R0, R0, #0;                 // Check that R0 is a valid source
+            BRn @SKIP_SET_INTERRUPT_PRIORITY;
+            LD R4, @OS_NEG_NUM_INTERRUPT_SOURCES;
+            ADD R4, R0, R4;
+            BRzp @SKIP_SET_INTERRUPT_PRIORITY;
+
+            JSR @FIND_INTERRUPT_PRIORITY_FIELD;
+            AND R2, R1, #7;                 // R2 = new priority
+            AND R5, R5, #0;                 // R5 = mask for the priority field
+            ADD R5, R5, #7;
+        @SET_INTERRUPT_PRIORITY_SHIFT
+            ADD R3, R3, #-1;                // Shift both into place
+            BRn @SET_INTERRUPT_PRIORITY_WRITE;
+            ADD R2, R2, R2;
+            ADD R5, R5, R5;
+            BRnzp @SET_INTERRUPT_PRIORITY_SHIFT;
+        @SET_INTERRUPT_PRIORITY_WRITE
+            LDR R3, R4, #0;                 // Clear the old priority and add in the new one
+            NOT R5, R5;
+            AND R3, R3, R5;
+            ADD R3, R3, R2;
+            STR R3, R4, #0;
+        @SKIP_SET_INTERRUPT_PRIORITY
+            LDR R7, R6, #0;                 // Restore R2, R3, R4, R5, R7
+            LDR R5, R6, #1;
+            LDR R4, R6, #2;
+            LDR R3, R6, #3;
+            LDR R2, R6, #4;
+            ADD R6, R6, #5;
+            RTI;
+
+        // Reads the priority of an interrupt source
+        // R0 = source to read
+        // -> R0 = priority
+        @TRAP_READ_INTERRUPT_PRIORITY
+            ADD R6, R6, #-5;                // Save R1, R2, R3, R4, R7 on stack
+            STR R1, R6, #4;
+            STR R2, R6, #3;
+            STR R3, R6, #2;
+            STR R4, R6, #1;
+            STR R7, R6, #0;
+
+            ADD R0, R0, #0;                 // Check that R0 is a valid source
+            BRn @SKIP_READ_INTERRUPT_PRIORITY;
+            LD R4, @OS_NEG_NUM_INTERRUPT_SOURCES;
+            ADD R4, R0, R4;
+            BRzp @SKIP_READ_INTERRUPT_PRIORITY;
+
+            JSR @FIND_INTERRUPT_PRIORITY_FIELD;
+            LDR R4, R4, #0;                 // R4 = priority register
+            AND R1, R1, #0;                 // R1 = lowest bit of the priority field
+            ADD R1, R1, #1;
+        @READ_INTERRUPT_PRIORITY_SHIFT
+            ADD R3, R3, #-1;
+            BRn @READ_INTERRUPT_PRIORITY_BITS;
+            ADD R1, R1, R1;
+            BRnzp @READ_INTERRUPT_PRIORITY_SHIFT;
+        @READ_INTERRUPT_PRIORITY_BITS
+            AND R0, R0, #0;                 // Add up the set bits of the field
+            AND R2, R2, #0;                 // R2 = value of the current bit
+            ADD R2, R2, #1;
+        @READ_INTERRUPT_PRIORITY_BIT
+            AND R3, R4, R1;
+            BRz @READ_INTERRUPT_PRIORITY_NEXT_BIT;
+            ADD R0, R0, R2;
+        @READ_INTERRUPT_PRIORITY_NEXT_BIT
+            ADD R1, R1, R1;
+            ADD R2, R2, R2;
+            ADD R3, R2, #-8;
+            BRn @READ_INTERRUPT_PRIORITY_BIT;
+            BRnzp @END_READ_INTERRUPT_PRIORITY;
+        @SKIP_READ_INTERRUPT_PRIORITY
+            AND R0, R0, #0;
+        @END_READ_INTERRUPT_PRIORITY
+            LDR R7, R6, #0;                 // Restore R1, R2, R3, R4, R7
+            LDR R4, R6, #1;
+            LDR R3, R6, #2;
+            LDR R2, R6, #3;
+            LDR R1, R6, #4;
+            ADD R6, R6, #5;
+            RTI;
+
+        // Masks an interrupt source
+        // R0 = source to mask
+        @TRAP_MASK_INTERRUPT
+            ADD R6, R6, #-4;                // Save R2, R3, R4, R7 on stack
+            STR R2, R6, #3;
+            STR R3, R6, #2;
+            STR R4, R6, #1;
+            STR R7, R6, #0;
+
+            ADD R0, R0, #0;                 // Check that R0 is a valid source
+            BRn @SKIP_MASK_INTERRUPT;
+            LD R4, @OS_NEG_NUM_INTERRUPT_SOURCES;
+            ADD R4, R0, R4;
+            BRzp @SKIP_MASK_INTERRUPT;
+
+            JSR @FIND_INTERRUPT_MASK_BIT;
+            LDR R2, R4, #0;                 // Set the bit (R2 = R2 | R3)
+            NOT R2, R2;
+            NOT R3, R3;
+            AND R2, R2, R3;
+            NOT R2, R2;
+            STR R2, R4, #0;
+        @SKIP_MASK_INTERRUPT
+            LDR R7, R6, #0;                 // Restore R2, R3, R4, R7
+            LDR R4, R6, #1;
+            LDR R3, R6, #2;
+            LDR R2, R6, #3;
+            ADD R6, R6, #4;
+            RTI;
+
+        // Unmasks an interrupt source
+        // R0 = source to unmask
+        @TRAP_UNMASK_INTERRUPT
+            ADD R6, R6, #-4;                // Save R2, R3, R4, R7 on stack
+            STR R2, R6, #3;
+            STR R3, R6, #2;
+            STR R4, R6, #1;
+            STR R7, R6, #0;
+
+            ADD R0, R0, #0;                 // Check that R0 is a valid source
+            BRn @SKIP_UNMASK_INTERRUPT;
+            LD R4, @OS_NEG_NUM_INTERRUPT_SOURCES;
+            ADD R4, R0, R4;
+            BRzp @SKIP_UNMASK_INTERRUPT;
+
+            JSR @FIND_INTERRUPT_MASK_BIT;
+            LDR R2, R4, #0;                 // Clear the bit
+            NOT R3, R3;
+            AND R2, R2, R3;
+            STR R2, R4, #0;
+        @SKIP_UNMASK_INTERRUPT
+            LDR R7, R6, #0;                 // Restore R2, R3, R4, R7
+            LDR R4, R6, #1;
+            LDR R3, R6, #2;
+            LDR R2, R6, #3;
+            ADD R6, R6, #4;
+            RTI;
+
+        // Turns on the interrupt controller
+        @TRAP_ENABLE_INTERRUPTS
+            ADD R6, R6, #-1;                // Save R4 on stack
+            STR R4, R6, #0;
+            LD R4, @OS_ICR_ENABLE;
+            STI R4, @OS_ICR_ADDR;
+            LDR R4, R6, #0;                 // Restore R4
+            ADD R6, R6, #1;
+            RTI;
+
+        // Turns off the interrupt controller
+        @TRAP_DISABLE_INTERRUPTS
+            ADD R6, R6, #-1;                // Save R4 on stack
+            STR R4, R6, #0;
+            AND R4, R4, #0;
+            STI R4, @OS_ICR_ADDR;
+            LDR R4, R6, #0;                 // Restore R4
+            ADD R6, R6, #1;
+            RTI;
+
+        // Finds the priority field of an interrupt source
+        // R0 = source (must be valid)
+        // -> R4 = address of the priority register
+        //    R3 = bit offset of the field in the register
+        @FIND_INTERRUPT_PRIORITY_FIELD
+            LD R4, @OS_IPR0_ADDR;           // 4 sources per register
+            ADD R3, R0, #0;
+        @FIND_INTERRUPT_PRIORITY_FIELD_LOOP
+            ADD R3, R3, #-4;
+            BRn @FIND_INTERRUPT_PRIORITY_FIELD_RET;
+            ADD R4, R4, #1;
+            BRnzp @FIND_INTERRUPT_PRIORITY_FIELD_LOOP;
+        @FIND_INTERRUPT_PRIORITY_FIELD_RET
+            ADD R3, R3, #4;                 // R3 = source % 4
+            ADD R3, R3, R3;                 // 4 bits per field
+            ADD R3, R3, R3;
+            RET;
+
+        // Finds the mask bit of an interrupt source
+        // R0 = source (must be valid)
+        // -> R4 = address of the mask register
+        //    R3 = the source's bit
+        // Destroys R2
+        @FIND_INTERRUPT_MASK_BIT
+            LD R4, @OS_IMR0_ADDR;
+            ADD R2, R0, #-16;               // Sources 16 and up are in the second register
+            BRzp @FIND_INTERRUPT_MASK_BIT_HIGH;
+            ADD R2, R0, #0;
+            BRnzp @FIND_INTERRUPT_MASK_BIT_SHIFT;
+        @FIND_INTERRUPT_MASK_BIT_HIGH
+            ADD R4, R4, #1;
+        @FIND_INTERRUPT_MASK_BIT_SHIFT
+            AND R3, R3, #0;
+            ADD R3, R3, #1;
+        @FIND_INTERRUPT_MASK_BIT_LOOP
+            ADD R2, R2, #-1;
+            BRn @FIND_INTERRUPT_MASK_BIT_RET;
+            ADD R3, R3, R3;
+            BRnzp @FIND_INTERRUPT_MASK_BIT_LOOP;
+        @FIND_INTERRUPT_MASK_BIT_RET
+            RET;
+
+        @OS_NEG_NUM_INTERRUPT_SOURCES
+            .FILL #((!(lc3_traits::control::InterruptSource::NUM_SOURCES as Word)).wrapping_add(1));
+        @OS_ICR_ADDR .FILL #ICR_ADDR;
+        @OS_ICR_ENABLE .FILL #0x8000;
+        @OS_IMR0_ADDR .FILL #IMR0_ADDR;
+        @OS_IPR0_ADDR .FILL #IPR0_ADDR;
+
+        // PWM set, with finer units
+        // R0 = PWM pin to set
+        // R1 = period to set (in microseconds)
+        // R2 = duty cycle to set (out of 65535)
+        @TRAP_SET_PWM_FINE
+            ADD R6, R6, #-1;                // Save R4 on stack
+            STR R4, R6, #0;
+
+            ADD R0, R0, #0;                 // Check that R0 is a valid pin
+            BRn @SKIP_SET_PWM_FINE;
+            ADD R4, R0, #-(lc3_traits::peripherals::pwm::PwmPin::NUM_PINS as i16);
+            BRzp @SKIP_SET_PWM_FINE;
+
+            LD R4, @OS_PWM_CR_BASE_ADDR;
+            ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
+            ADD R4, R4, R0;                 // and adding 1
+            ADD R4, R4, #1;                 // R4 contains address of duty cycle register
+            STR R2, R4, #0;                 // Write duty cycle to PWM
+            LD R4, @OS_PWM_PR_BASE_ADDR;
+            ADD R4, R4, R0;                 // R4 contains address of period register
+            STR R1, R4, #0;                 // Write period to PWM (enables it)
+        @SKIP_SET_PWM_FINE
+            LDR R4, R6, #0;                 // Restore R4
+            ADD R6, R6, #1;
+            RTI;
+
+        // Reads and returns period of PWM pin, in microseconds
+        // R0 = PWM pin to read from
+        // -> R0 = period of PWM pin (in microseconds)
+        @TRAP_READ_PWM_FINE_PERIOD
+            ADD R6, R6, #-1;                // Save R4 on stack
+            STR R4, R6, #0;
+
+            ADD R0, R0, #0;                 // Check that R0 is a valid pin
+            BRn @SKIP_READ_PWM_FINE_PERIOD;
+            ADD R4, R0, #-(lc3_traits::peripherals::pwm::PwmPin::NUM_PINS as i16);
+            BRzp @SKIP_READ_PWM_FINE_PERIOD;
+
+            LD R4, @OS_PWM_PR_BASE_ADDR;
+            ADD R4, R4, R0;                 // R4 contains period address of pin number in R0
+            LDR R0, R4, #0;                 // Reads period from pin into R0
+            BRnzp @END_READ_PWM_FINE_PERIOD;
+
+        @SKIP_READ_PWM_FINE_PERIOD
+            AND R0, R0, #0;                 // Return error code 0 (period always nonzero)
+
+        @END_READ_PWM_FINE_PERIOD
+            LDR R4, R6, #0;                 // Restore R4
+            ADD R6, R6, #1;
+            RTI;
+
+        // Reads and returns duty cycle of PWM pin, out of 65535
+        // R0 = PWM pin to read from
+        // -> R0 = duty cycle of PWM pin (out of 65535)
+        @TRAP_READ_PWM_FINE_DUTY_CYCLE
+            ADD R6, R6, #-1;                // Save R4 on stack
+            STR R4, R6, #0;
+
+            ADD R0, R0, #0;                 // Check that R0 is a valid pin
+            BRn @SKIP_READ_PWM_FINE_DUTY_CYCLE;
+            ADD R4, R0, #-(lc3_traits::peripherals::pwm::PwmPin::NUM_PINS as i16);
+            BRzp @SKIP_READ_PWM_FINE_DUTY_CYCLE;
+
+            LD R4, @OS_PWM_CR_BASE_ADDR;
+            ADD R4, R4, R0;                 // Calculate pin address offset by doubling pin number
+            ADD R4, R4, R0;                 // and adding 1
+            ADD R4, R4, #1;                 // R4 contains data address of pin number in R0
+            LDR R0, R4, #0;                 // Reads duty cycle from pin into R0
+        @SKIP_READ_PWM_FINE_DUTY_CYCLE
+            LDR R4, R6, #0;                 // Restore R4
+            ADD R6, R6, #1;
+            RTI;
+
+        @OS_PWM_CR_BASE_ADDR .FILL #P0CR_ADDR;
+        @OS_PWM_PR_BASE_ADDR .FILL #P0PR_ADDR;
     };
 
     AssembledProgram::new(os)
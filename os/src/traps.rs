@@ -16,21 +16,41 @@
 //! | **`0x34`** | [GPIO_GET_MODE]    | [`R0`] - [pin][gpin] #                                                | [`R0`] - [GPIO mode] <br>`n` bit   | Returns the [mode][gmode] of a [GPIO] [pin][gpin].                             |
 //! | **`0x35`** | [GPIO_WRITE]       | [`R0`] - [pin][gpin] # <br>[`R1`] - data to write                     | `n` bit                            | Writes to a [GPIO] [pin][gpin] in [Output mode][gOutput].                      |
 //! | **`0x36`** | [GPIO_READ]        | [`R0`] - [pin][gpin] #                                                | [`R0`] - data from pin <br>`n` bit | Reads data from a [GPIO] [pin][gpin].                                          |
+//! | **`0x37`** | [GPIO_SET_TRIGGER] | [`R0`] - [pin][gpin] # <br>[`R1`] - [trigger][gtrigger]               | `n` bit                            | Sets the [interrupt trigger][gtrigger] of a [GPIO] [pin][gpin].                |
+//! | **`0x38`** | [GPIO_SET_PULL]    | [`R0`] - [pin][gpin] # <br>[`R1`] - [pull][gpull]                     | `n` bit                            | Sets the [pull][gpull] of a [GPIO] [pin][gpin].                                |
 //! | **`0x40`** | [ADC_ENABLE]       | [`R0`] - [pin][apin] #                                                | `n` bit                            | Puts an [ADC] [pin][apin] in [Enabled mode][aEnabled].                         |
 //! | **`0x41`** | [ADC_DISABLE]      | [`R0`] - [pin][apin] #                                                | `n` bit                            | Puts an [ADC] [pin][apin] in [Disabled mode][aDisabled].                       |
 //! | **`0x42`** | [ADC_GET_MODE]     | [`R0`] - [pin][apin] #                                                | [`R0`] - [ADC mode] <br>`n` bit    | Returns the mode of an [ADC] [pin][apin].                                      |
 //! | **`0x43`** | [ADC_READ]         | [`R0`] - [pin][apin] #                                                | [`R0`] - data from pin <br>`n` bit | Reads data from an [ADC] [pin][apin].                                          |
+//! | **`0x44`** | [ADC_CONTINUOUS]   | [`R0`] - [pin][apin] #                                                | `n` bit                            | Puts an [ADC] [pin][apin] in [Continuous mode][aContinuous].                   |
+//! | **`0x45`** | [ADC_ONE_SHOT]     | [`R0`] - [pin][apin] #                                                | `n` bit                            | Puts an [ADC] [pin][apin] in [One Shot mode][aOneShot].                        |
+//! | **`0x46`** | [ADC_INTERRUPT]    | [`R0`] - [pin][apin] # <br>[`R1`] - address of ISR <br>[`R2`] - [interrupt][ainterrupt] <br>[`R3`] - threshold | `n` bit                            | Sets the [interrupt][ainterrupt] of an [ADC] [pin][apin] and sets the ISR.     |
+//! | **`0x47`** | [ADC_SAMPLE_PERIOD] | [`R0`] - sample period                                                | none                               | Sets the [sample period][aperiod] used in [Continuous mode][aContinuous].      |
 //! | **`0x50`** | [PWM_ENABLE]       | [`R0`] - [pin][ppin] # <br>[`R1`] - period <br>[`R2`] - duty cycle    | `n` bit                            | Puts a [PWM] in [Enabled mode][pEnabled], with period and duty cycle.          |
 //! | **`0x51`** | [PWM_DISABLE]      | [`R0`] - [pin][ppin] #                                                | `n` bit                            | Puts a [PWM] [pin][ppin] in [Disabled mode][pDisabled].                        |
 //! | **`0x52`** | [PWM_GET_PERIOD]   | [`R0`] - [pin][ppin] #                                                | [`R0`] - period <br>`n` bit        | Returns the period of a [PWM pin][ppin].                                       |
 //! | **`0x53`** | [PWM_GET_DUTY]     | [`R0`] - [pin][ppin] #                                                | [`R0`] - duty cycle <br>`n` bit    | Returns the duty cycle of a [PWM pin][ppin].                                   |
+//! | **`0x54`** | [PWM_INTERRUPT]    | [`R0`] - [pin][ppin] # <br>[`R1`] - address of ISR                    | `n` bit                            | Turns on the rollover interrupt of a [PWM] [pin][ppin] and sets the ISR.       |
+//! | **`0x55`** | [PWM_DISABLE_INTERRUPT] | [`R0`] - [pin][ppin] #                                           | `n` bit                            | Turns off the rollover interrupt of a [PWM] [pin][ppin].                       |
+//! | **`0x56`** | [PWM_ENABLE_FINE]  | [`R0`] - [pin][ppin] # <br>[`R1`] - period (µs) <br>[`R2`] - duty cycle | `n` bit                          | Like [PWM_ENABLE], with a period in microseconds and a 16-bit duty cycle.      |
+//! | **`0x57`** | [PWM_GET_FINE_PERIOD] | [`R0`] - [pin][ppin] #                                             | [`R0`] - period (µs) <br>`n` bit   | Returns the period of a [PWM pin][ppin] in microseconds.                       |
+//! | **`0x58`** | [PWM_GET_FINE_DUTY] | [`R0`] - [pin][ppin] #                                               | [`R0`] - duty cycle <br>`n` bit    | Returns the 16-bit duty cycle of a [PWM pin][ppin].                            |
 //! | **`0x60`** | [TIMER_SINGLESHOT] | [`R0`] - [id][tid] # <br>[`R1`] - period <br>[`R2`] - address of ISR  | `n` bit                            | Puts a [Timer] in [SingleShot mode][tSingleShot] with period and sets the ISR. |
 //! | **`0x61`** | [TIMER_REPEATED]   | [`R0`] - [id][tid] # <br>[`R1`] - period <br>[`R2`] - address of ISR  | `n` bit                            | Puts a [Timer] in [Repeated mode][tRepeated] with period and sets the ISR.     |
 //! | **`0x62`** | [TIMER_DISABLE]    | [`R0`] - [id][tid] #                                                  | `n` bit                            | Puts a [Timer] in [Disabled mode][tDisabled].                                  |
 //! | **`0x63`** | [TIMER_GET_MODE]   | [`R0`] - [id][tid] #                                                  | [`R0`] - [Timer mode] <br>`n` bit  | Returns the [mode][tMode] of a [Timer].                                        |
-//! | **`0x64`** | [TIMER_GET_PERIOD] | [`R0`] - [id][tid] #                                                  | [`R0`] - period                    | Returns the [period][tState] of a [Timer].                                     |
+//! | **`0x64`** | [TIMER_GET_PERIOD] | [`R0`] - [id][tid] #                                                  | [`R0`] - period                    | Returns the [period][tState] of a [Timer] (or the count, if [FreeRunning][tFreeRunning]). |
+//! | **`0x65`** | [TIMER_FREE_RUNNING] | [`R0`] - [id][tid] # <br>[`R1`] - period                            | `n` bit                            | Puts a [Timer] in [FreeRunning mode][tFreeRunning] with period.                |
+//! | **`0x66`** | [TIMER_CAPTURE]    | [`R0`] - [id][tid] # <br>[`R1`] - [GPIO][gpin] pin # <br>[`R2`] - [edge][tedge] | `n` bit                  | Sets the [input capture source][tcapture] of a [Timer].                        |
+//! | **`0x67`** | [TIMER_GET_CAPTURE] | [`R0`] - [id][tid] #                                                 | [`R0`] - captured count <br>`n` bit | Returns the last count captured by a [Timer].                                 |
 //! | **`0x70`** | [CLOCK_SET]        | [`R0`] - value to set                                                 | none                               | Sets the value of the [Clock].                                                 |
 //! | **`0x71`** | [CLOCK_GET]        | none                                                                  | [`R0`] - value of clock            | Gets the value of the [Clock].                                                 |
+//! | **`0x80`** | [INTERRUPT_SET_PRIORITY] | [`R0`] - [source][isource] # <br>[`R1`] - priority              | `n` bit                            | Sets the priority of an interrupt [source][isource].                           |
+//! | **`0x81`** | [INTERRUPT_GET_PRIORITY] | [`R0`] - [source][isource] #                                    | [`R0`] - priority <br>`n` bit      | Returns the priority of an interrupt [source][isource].                        |
+//! | **`0x82`** | [INTERRUPT_MASK]   | [`R0`] - [source][isource] #                                          | `n` bit                            | Keeps an interrupt [source][isource] from firing.                              |
+//! | **`0x83`** | [INTERRUPT_UNMASK] | [`R0`] - [source][isource] #                                          | `n` bit                            | Lets a masked interrupt [source][isource] fire again.                          |
+//! | **`0x84`** | [INTERRUPT_ENABLE] | none                                                                  | none                               | Turns on the interrupt controller.                                             |
+//! | **`0x85`** | [INTERRUPT_DISABLE] | none                                                                 | none                               | Turns off the interrupt controller; interrupts stay pending.                   |
 //!
 //! [GETC]: builtin::GETC
 //! [OUT]: builtin::OUT
@@ -46,25 +66,46 @@
 //! [GPIO_GET_MODE]: gpio::GET_MODE
 //! [GPIO_WRITE]: gpio::WRITE
 //! [GPIO_READ]: gpio::READ
+//! [GPIO_SET_TRIGGER]: gpio::SET_TRIGGER
+//! [GPIO_SET_PULL]: gpio::SET_PULL
 //! [ADC_ENABLE]: adc::ENABLE
 //! [ADC_DISABLE]: adc::DISABLE
 //! [ADC_GET_MODE]: adc::GET_MODE
 //! [ADC_READ]: adc::READ
+//! [ADC_CONTINUOUS]: adc::CONTINUOUS
+//! [ADC_ONE_SHOT]: adc::ONE_SHOT
+//! [ADC_INTERRUPT]: adc::INTERRUPT
+//! [ADC_SAMPLE_PERIOD]: adc::SAMPLE_PERIOD
 //! [PWM_ENABLE]: pwm::ENABLE
 //! [PWM_DISABLE]: pwm::DISABLE
 //! [PWM_GET_PERIOD]: pwm::GET_PERIOD
 //! [PWM_GET_DUTY]: pwm::GET_DUTY
+//! [PWM_INTERRUPT]: pwm::INTERRUPT
+//! [PWM_DISABLE_INTERRUPT]: pwm::DISABLE_INTERRUPT
+//! [PWM_ENABLE_FINE]: pwm::ENABLE_FINE
+//! [PWM_GET_FINE_PERIOD]: pwm::GET_FINE_PERIOD
+//! [PWM_GET_FINE_DUTY]: pwm::GET_FINE_DUTY
 //! [TIMER_SINGLESHOT]: timers::SINGLESHOT
 //! [TIMER_REPEATED]: timers::REPEATED
 //! [TIMER_DISABLE]: timers::DISABLE
 //! [TIMER_GET_MODE]: timers::GET_MODE
 //! [TIMER_GET_PERIOD]: timers::GET_PERIOD
+//! [TIMER_FREE_RUNNING]: timers::FREE_RUNNING
+//! [TIMER_CAPTURE]: timers::CAPTURE
+//! [TIMER_GET_CAPTURE]: timers::GET_CAPTURE
 //! [CLOCK_SET]: clock::SET
 //! [CLOCK_GET]: clock::GET
+//! [INTERRUPT_SET_PRIORITY]: interrupts::SET_PRIORITY
+//! [INTERRUPT_GET_PRIORITY]: interrupts::GET_PRIORITY
+//! [INTERRUPT_MASK]: interrupts::MASK
+//! [INTERRUPT_UNMASK]: interrupts::UNMASK
+//! [INTERRUPT_ENABLE]: interrupts::ENABLE
+//! [INTERRUPT_DISABLE]: interrupts::DISABLE
 //!
 //! [`R0`]: lc3_isa::Reg::R0
 //! [`R1`]: lc3_isa::Reg::R1
 //! [`R2`]: lc3_isa::Reg::R2
+//! [`R3`]: lc3_isa::Reg::R3
 //!
 //! [GPIO]: lc3_traits::peripherals::gpio::Gpio
 //! [gpin]: lc3_traits::peripherals::gpio::GpioPin
@@ -73,11 +114,17 @@
 //! [gOutput]: lc3_traits::peripherals::gpio::GpioState::Output
 //! [gInterrupt]: lc3_traits::peripherals::gpio::GpioState::Interrupt
 //! [gDisabled]: lc3_traits::peripherals::gpio::GpioState::Disabled
+//! [gtrigger]: lc3_traits::peripherals::gpio::GpioTrigger
+//! [gpull]: lc3_traits::peripherals::gpio::GpioPull
 //!
 //! [ADC]: lc3_traits::peripherals::adc::Adc
 //! [apin]: lc3_traits::peripherals::adc::AdcPin
 //! [aEnabled]: lc3_traits::peripherals::adc::AdcState::Enabled
 //! [aDisabled]: lc3_traits::peripherals::adc::AdcState::Disabled
+//! [aContinuous]: lc3_traits::peripherals::adc::AdcMode::Continuous
+//! [aOneShot]: lc3_traits::peripherals::adc::AdcMode::OneShot
+//! [ainterrupt]: lc3_traits::peripherals::adc::AdcInterrupt
+//! [aperiod]: lc3_traits::peripherals::adc::AdcSamplePeriod
 //!
 //! [PWM]: lc3_traits::peripherals::pwm::Pwm
 //! [ppin]: lc3_traits::peripherals::pwm::PwmPin
@@ -88,12 +135,17 @@
 //! [tid]: lc3_traits::peripherals::timers::TimerId
 //! [tSingleShot]: lc3_traits::peripherals::timers::TimerMode::SingleShot
 //! [tRepeated]: lc3_traits::peripherals::timers::TimerMode::Repeated
+//! [tFreeRunning]: lc3_traits::peripherals::timers::TimerMode::FreeRunning
+//! [tcapture]: lc3_traits::peripherals::timers::CaptureSource
+//! [tedge]: lc3_traits::peripherals::timers::CaptureEdge
 //! [tDisabled]: lc3_traits::peripherals::timers::TimerState::Disabled
 //! [tMode]: lc3_traits::peripherals::timers::TimerMode
 //! [tState]: lc3_traits::peripherals::timers::TimerState
 //!
 //! [Clock]: lc3_traits::peripherals::clock::Clock
 //!
+//! [isource]: lc3_traits::control::InterruptSource
+//!
 //! [GPIO Mode]: lc3_traits::peripherals::gpio::GpioState
 //! [ADC Mode]: lc3_traits::peripherals::adc::AdcState
 //! [Timer Mode]: lc3_traits::peripherals::timers::TimerMode
@@ -489,6 +541,130 @@ pub mod gpio {
       /// [`NUM_GPIO_PINS`]: lc3_traits::peripherals::gpio::GpioPin::NUM_PINS
       /// [`G0`]: lc3_traits::peripherals::gpio::GpioPin::G0
       [0x36] READ,
+      /// Sets the [interrupt trigger][Trigger] of a [GPIO] [Pin].
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [GPIO] [Pin] number.
+      ///  - [`R1`]: A value corresponding to a [trigger][Trigger].
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP sets the condition under which the [GPIO] [Pin] indicated
+      /// by [`R0`] fires interrupts (when in [Interrupt] mode). The values
+      /// are as follows:
+      ///
+      /// | Trigger         | Value |
+      /// | --------------- | ----- |
+      /// | [`RisingEdge`]  | 0     |
+      /// | [`FallingEdge`] | 1     |
+      /// | [`BothEdges`]   | 2     |
+      /// | [`HighLevel`]   | 3     |
+      /// | [`LowLevel`]    | 4     |
+      ///
+      /// Only the lower 3 bits of [`R1`] are used; values 5 through 7 are
+      /// treated as [`RisingEdge`].
+      ///
+      /// The trigger is kept across mode changes; it can be set before or
+      /// after the pin is put into [Interrupt] mode. Pins start out with
+      /// [`RisingEdge`] triggers.
+      ///
+      /// Level triggered interrupts keep firing for as long as the pin is at
+      /// the level in question so ISRs for these should either deal with
+      /// whatever is driving the pin or change the pin's mode.
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_GPIO_PINS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid pin number, the `n` bit is set.
+      ///
+      /// All registers (including [`R0`] and [`R1`]) are preserved.
+      ///
+      /// ## Example
+      /// The below makes [`G0`] fire interrupts on falling edges and then
+      /// puts it in [Interrupt] mode:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// AND R1, R1, #0      ; Sets R1 to 1 (Falling Edge)
+      /// ADD R1, R1, #1
+      /// TRAP 0x37           ; Sets G0's trigger to Falling Edge
+      /// LEA R1, ISR         ; Sets R1 to the address of ISR
+      /// TRAP 0x32           ; Sets G0 to Interrupt w/ ISR
+      /// ```
+      ///
+      /// [GPIO]: lc3_traits::peripherals::Gpio
+      /// [Interrupt]: lc3_traits::peripherals::gpio::GpioState::Interrupt
+      /// [Trigger]: lc3_traits::peripherals::gpio::GpioTrigger
+      /// [`RisingEdge`]: lc3_traits::peripherals::gpio::GpioTrigger::RisingEdge
+      /// [`FallingEdge`]: lc3_traits::peripherals::gpio::GpioTrigger::FallingEdge
+      /// [`BothEdges`]: lc3_traits::peripherals::gpio::GpioTrigger::BothEdges
+      /// [`HighLevel`]: lc3_traits::peripherals::gpio::GpioTrigger::HighLevel
+      /// [`LowLevel`]: lc3_traits::peripherals::gpio::GpioTrigger::LowLevel
+      /// [Pin]: lc3_traits::peripherals::gpio::GpioPin
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`R1`]: lc3_isa::Reg::R1
+      /// [`NUM_GPIO_PINS`]: lc3_traits::peripherals::gpio::GpioPin::NUM_PINS
+      /// [`G0`]: lc3_traits::peripherals::gpio::GpioPin::G0
+      [0x37] SET_TRIGGER,
+      /// Sets the [pull][Pull] of a [GPIO] [Pin].
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [GPIO] [Pin] number.
+      ///  - [`R1`]: A value corresponding to a [pull][Pull].
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP sets the level the [GPIO] [Pin] indicated by [`R0`] sits
+      /// at when it's an [Input] (or in [Interrupt] mode) and nothing is
+      /// driving it. The values are as follows:
+      ///
+      /// | Pull         | Value |
+      /// | ------------ | ----- |
+      /// | [`Floating`] | 0     |
+      /// | [`PullUp`]   | 1     |
+      /// | [`PullDown`] | 2     |
+      ///
+      /// Only the lower 2 bits of [`R1`] are used; 3 is treated as
+      /// [`Floating`].
+      ///
+      /// Like triggers, pulls are kept across mode changes. Pins start out
+      /// [`Floating`].
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_GPIO_PINS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid pin number, the `n` bit is set.
+      ///
+      /// All registers (including [`R0`] and [`R1`]) are preserved.
+      ///
+      /// ## Example
+      /// The below pulls [`G0`] up and then makes it an [Input]:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// AND R1, R1, #0      ; Sets R1 to 1 (Pull Up)
+      /// ADD R1, R1, #1
+      /// TRAP 0x38           ; Pulls G0 up
+      /// TRAP 0x30           ; Sets G0 to Input
+      /// ```
+      ///
+      /// [GPIO]: lc3_traits::peripherals::Gpio
+      /// [Input]: lc3_traits::peripherals::gpio::GpioState::Input
+      /// [Interrupt]: lc3_traits::peripherals::gpio::GpioState::Interrupt
+      /// [Pull]: lc3_traits::peripherals::gpio::GpioPull
+      /// [`Floating`]: lc3_traits::peripherals::gpio::GpioPull::Floating
+      /// [`PullUp`]: lc3_traits::peripherals::gpio::GpioPull::PullUp
+      /// [`PullDown`]: lc3_traits::peripherals::gpio::GpioPull::PullDown
+      /// [Pin]: lc3_traits::peripherals::gpio::GpioPin
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`R1`]: lc3_isa::Reg::R1
+      /// [`NUM_GPIO_PINS`]: lc3_traits::peripherals::gpio::GpioPin::NUM_PINS
+      /// [`G0`]: lc3_traits::peripherals::gpio::GpioPin::G0
+      [0x38] SET_PULL,
   });
 }
 
@@ -650,6 +826,187 @@ pub mod adc {
       /// [`NUM_ADC_PINS`]: lc3_traits::peripherals::adc::AdcPin::NUM_PINS
       /// [`A0`]: lc3_traits::peripherals::adc::AdcPin::A0
       [0x43] READ,
+      /// Puts an [ADC] [Pin] in [Continuous] mode.
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: An [ADC] [Pin] number.
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP puts the [ADC] [Pin] indicated by [`R0`] into [Continuous]
+      /// mode. In this mode the [Pin] is sampled once every sample period (see
+      /// [`SAMPLE_PERIOD`]) and reads return the latest sample. Only pins in
+      /// [Continuous] mode raise interrupts (see [`INTERRUPT`]).
+      ///
+      /// The [Pin] stays [Enabled] or [Disabled]; this TRAP only changes how
+      /// conversions happen once the [Pin] is [Enabled].
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_ADC_PINS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid pin number, the `n` bit is set.
+      ///
+      /// All registers (including [`R0`]) are preserved.
+      ///
+      /// ## Example
+      /// The below enables [`A0`] and puts it in [Continuous] mode:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// TRAP 0x40           ; Sets A0 to Enabled
+      /// TRAP 0x44           ; Sets A0 to Continuous
+      /// ```
+      ///
+      /// [ADC]: lc3_traits::peripherals::adc
+      /// [Continuous]: lc3_traits::peripherals::adc::AdcMode::Continuous
+      /// [Enabled]: lc3_traits::peripherals::adc::AdcState::Enabled
+      /// [Disabled]: lc3_traits::peripherals::adc::AdcState::Disabled
+      /// [Pin]: lc3_traits::peripherals::adc::AdcPin
+      /// [`SAMPLE_PERIOD`]: SAMPLE_PERIOD
+      /// [`INTERRUPT`]: INTERRUPT
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`NUM_ADC_PINS`]: lc3_traits::peripherals::adc::AdcPin::NUM_PINS
+      /// [`A0`]: lc3_traits::peripherals::adc::AdcPin::A0
+      [0x44] CONTINUOUS,
+      /// Puts an [ADC] [Pin] in [One Shot] mode.
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: An [ADC] [Pin] number.
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP puts the [ADC] [Pin] indicated by [`R0`] into [One Shot]
+      /// mode, where every read does a conversion. This is the mode pins start
+      /// out in.
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_ADC_PINS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid pin number, the `n` bit is set.
+      ///
+      /// All registers (including [`R0`]) are preserved.
+      ///
+      /// ## Example
+      /// The below puts [`A0`] back in [One Shot] mode:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// TRAP 0x45           ; Sets A0 to One Shot
+      /// ```
+      ///
+      /// [ADC]: lc3_traits::peripherals::adc
+      /// [One Shot]: lc3_traits::peripherals::adc::AdcMode::OneShot
+      /// [Pin]: lc3_traits::peripherals::adc::AdcPin
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`NUM_ADC_PINS`]: lc3_traits::peripherals::adc::AdcPin::NUM_PINS
+      /// [`A0`]: lc3_traits::peripherals::adc::AdcPin::A0
+      [0x45] ONE_SHOT,
+      /// Sets the [interrupt] of an [ADC] [Pin] and its interrupt service
+      /// routine.
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: An [ADC] [Pin] number.
+      ///  - [`R1`]: The address of the interrupt service routine.
+      ///  - [`R2`]: A value corresponding to an [interrupt].
+      ///  - [`R3`]: The threshold (only used by [`ThresholdCrossing`]).
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP sets the interrupt of the [ADC] [Pin] indicated by [`R0`]
+      /// to the [interrupt] indicated by [`R2`] and sets the [Pin]'s
+      /// interrupt service routine to the address in [`R1`]. The values are as
+      /// follows:
+      ///
+      /// | Interrupt                | Value |
+      /// | ------------------------ | ----- |
+      /// | [`Disabled`]             | 0     |
+      /// | [`ConversionComplete`]   | 1     |
+      /// | [`ThresholdCrossing`]    | 2     |
+      ///
+      /// Only the lower 2 bits of [`R2`] and the lower 8 bits of [`R3`] are
+      /// used. Interrupts only fire for [Pin]s that are [Enabled] and in
+      /// [Continuous] mode.
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_ADC_PINS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid pin number, the `n` bit is set.
+      ///
+      /// All registers (including [`R0`]) are preserved.
+      ///
+      /// ## Example
+      /// The below makes [`A0`] interrupt whenever its readings cross 128,
+      /// using the routine at x4000:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// LD R1, ISR          ; Sets R1 to x4000
+      /// AND R2, R2, #0      ; Sets R2 to 2 (Threshold Crossing)
+      /// ADD R2, R2, #2
+      /// LD R3, THRESHOLD    ; Sets R3 to 128
+      /// TRAP 0x46           ; Sets A0's interrupt
+      /// TRAP 0x40           ; Sets A0 to Enabled
+      /// TRAP 0x44           ; Sets A0 to Continuous
+      /// ...
+      /// ISR .FILL x4000
+      /// THRESHOLD .FILL #128
+      /// ```
+      ///
+      /// [ADC]: lc3_traits::peripherals::adc
+      /// [interrupt]: lc3_traits::peripherals::adc::AdcInterrupt
+      /// [`Disabled`]: lc3_traits::peripherals::adc::AdcInterrupt::Disabled
+      /// [`ConversionComplete`]: lc3_traits::peripherals::adc::AdcInterrupt::ConversionComplete
+      /// [`ThresholdCrossing`]: lc3_traits::peripherals::adc::AdcInterrupt::ThresholdCrossing
+      /// [Continuous]: lc3_traits::peripherals::adc::AdcMode::Continuous
+      /// [Enabled]: lc3_traits::peripherals::adc::AdcState::Enabled
+      /// [Pin]: lc3_traits::peripherals::adc::AdcPin
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`R1`]: lc3_isa::Reg::R1
+      /// [`R2`]: lc3_isa::Reg::R2
+      /// [`R3`]: lc3_isa::Reg::R3
+      /// [`NUM_ADC_PINS`]: lc3_traits::peripherals::adc::AdcPin::NUM_PINS
+      /// [`A0`]: lc3_traits::peripherals::adc::AdcPin::A0
+      [0x46] INTERRUPT,
+      /// Sets the [sample period] used by [ADC] [Pin]s in [Continuous] mode.
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: The sample period, in milliseconds.
+      ///
+      /// ## Outputs
+      ///  - none
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP sets the time between conversions for all the [ADC]
+      /// [Pin]s that are in [Continuous] mode to the number of milliseconds in
+      /// [`R0`]. The sample period is shared by all the [Pin]s.
+      ///
+      /// A sample period of 0 is invalid; the sample period is left unchanged
+      /// and an error is reported.
+      ///
+      /// All registers (including [`R0`]) are preserved.
+      ///
+      /// ## Example
+      /// The below has [Pin]s in [Continuous] mode sample every 100ms:
+      /// ```{ARM Assembly}
+      /// LD R0, PERIOD       ; Sets R0 to 100
+      /// TRAP 0x47           ; Sets the sample period
+      /// ...
+      /// PERIOD .FILL #100
+      /// ```
+      ///
+      /// [ADC]: lc3_traits::peripherals::adc
+      /// [sample period]: lc3_traits::peripherals::adc::AdcSamplePeriod
+      /// [Continuous]: lc3_traits::peripherals::adc::AdcMode::Continuous
+      /// [Pin]: lc3_traits::peripherals::adc::AdcPin
+      /// [`R0`]: lc3_isa::Reg::R0
+      [0x47] SAMPLE_PERIOD,
   });
 }
 
@@ -854,82 +1211,274 @@ pub mod pwm {
       /// [`NUM_PWM_PINS`]: lc3_traits::peripherals::pwm::PwmPin::NUM_PINS
       /// [`P0`]: lc3_traits::peripherals::pwm::PwmPin::P0
       [0x53] GET_DUTY,
-  });
-}
-
-/// Trap vectors for the [`Timers`](lc3_traits::peripherals::Timers)
-/// peripheral.
-pub mod timers {
-  define!([super::mm::TIMER_OFFSET] <- {
-      /// Puts a [Timer] in [SingleShot] mode.
+[0x54] INTERRUPT,
+      /// Stops a [PWM] [Pin] from firing interrupts.
       ///
       /// ## Inputs
-      ///  - [`R0`]: A [Timer] [ID] number.
-      ///  - [`R1`]: The period.
-      ///  - [`R2`]: Address of interrupt service routine.
+      ///  - [`R0`]: A [PWM] [Pin] number.
       ///
       /// ## Outputs
       ///  - `n` bit: set on error, cleared on success.
       ///
       /// ## Usage
       ///
-      /// This TRAP puts the [Timer] indicated by [`R0`] into [SingleShot]
-      /// mode. It also sets the period of the [Timer] as well as the entry
-      /// in the interrupt vector table corresponding to the [Timer]. The
-      /// period is measured in units of milliseconds and uses full 16-bit
-      /// words (i.e. the period will be a value in the range \[0, 65535\]).
-      ///
-      /// In [SingleShot] mode, the interrupt service routine is only
-      /// triggered one time. The [Timer] will then set its state to [Disabled].
-      ///
-      /// Setting the mode or period of a [Timer] will overwrite the previous
-      /// [Timer]. In other words, if this TRAP is called on a [Timer] that is
-      /// currently running, the interrupt service routine will only trigger
-      /// after the newly set period elapses.
+      /// This TRAP turns off the [rollover interrupt] for the [PWM] [Pin]
+      /// indicated by [`R0`]. The [Pin] keeps running.
       ///
       /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
-      /// ∈ \[0, [`NUM_TIMERS`])), this TRAP is _infallible_.
-      ///
-      /// When [`R0`] does not hold a valid timer [ID] number, the `n` bit is
-      /// set.
+      /// ∈ \[0, [`NUM_PWM_PINS`])), this TRAP is _infallible_.
       ///
-      /// All registers (including [`R0`], [`R1`], and [`R2`]) are preserved.
+      /// When [`R0`] does not hold a valid pin number, the `n` bit is set.
       ///
-      /// Be sure to follow the
-      /// [guidelines for writing ISRs](../index.html#guidelines-on-writing-isrs).
+      /// All registers (including [`R0`]) are preserved.
       ///
       /// ## Example
-      /// The below sets [`T0`] to be a [SingleShot] with a period of `3
-      /// seconds` and sets the interrupt service routine to `ISR`. It will then
-      /// spin until [`T0`] fires an interrupt, after three seconds have passed,
-      /// which will call `ISR`, set the `ISR_FLAG` to 1, and allow the main
-      /// program to halt.
+      /// The below stops [`P0`] from firing interrupts:
       /// ```{ARM Assembly}
       /// AND R0, R0, #0      ; Sets R0 to 0
-      /// LD R1, PERIOD       ; Sets R1 to 3000
-      /// LEA R2, ISR         ; Sets R2 to the address of ISR
-      /// TRAP 0x60           ; Sets T0 to SingleShot w/ period of 3000 and ISR
+      /// TRAP 0x55           ; Turns off P0's rollover interrupt
+      /// ```
       ///
-      /// LOOP
-      /// LD R1, ISR_FLAG     ; Loops until flag is set
-      /// BRz LOOP
-      /// HALT
+      /// [PWM]: lc3_traits::peripherals::pwm
+      /// [rollover interrupt]: lc3_traits::peripherals::pwm::Pwm::set_rollover_interrupt
+      /// [Pin]: lc3_traits::peripherals::pwm::PwmPin
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`NUM_PWM_PINS`]: lc3_traits::peripherals::pwm::PwmPin::NUM_PINS
+      /// [`P0`]: lc3_traits::peripherals::pwm::PwmPin::P0
+      [0x55] DISABLE_INTERRUPT,
+      /// Puts a [PWM] [Pin] in [Enabled] mode, with a period in microseconds.
       ///
-      /// PERIOD .FILL #3000
-      /// ISR_FLAG .FILL #0
+      /// ## Inputs
+      ///  - [`R0`]: A [PWM] [Pin] number.
+      ///  - [`R1`]: The period.
+      ///  - [`R2`]: The duty cycle.
       ///
-      /// ISR
-      /// ADD R6, R6, #-1     ; Save R0 onto the supervisor stack
-      /// STR R0, R6, #0
+      /// ## Outputs
+      ///  - `n` bit: set on error, cleared on success.
       ///
-      /// AND R0, R0, #0
-      /// ADD R0, R0, #1
-      /// ST R0, ISR_FLAG
+      /// ## Usage
       ///
-      /// LDR R0, R6, #0      ; Pop R0 off of the supervisor stack
-      /// ADD R6, R6, #1
-      /// RTI
-      /// ```
+      /// This TRAP puts the [PWM] [Pin] indicated by [`R0`] into [Enabled]
+      /// mode. It also sets the corresponding period and duty cycle of that
+      /// [Pin] with [`R1`] and [`R2`] respectively.
+      ///
+      /// The period is measured in units of microseconds and can be any value
+      /// in the range \[1, 65535\]; a period of 0 puts the [Pin] in
+      /// [Disabled] mode instead. Use [`ENABLE`] for periods longer than
+      /// 65.535 ms. The duty cycle is the fraction of the period
+      /// spent high, out of 65535 (e.g. a value of x4000 corresponds to a 25%
+      /// duty cycle).
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_PWM_PINS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid pin number, the `n` bit is set.
+      ///
+      /// All registers (including [`R0`], [`R1`], and [`R2`]) are preserved.
+      ///
+      /// ## Example
+      /// The below sets [`P0`] to be an [Enabled] with a period of *20 ms* and
+      /// a *50%* duty cycle then halts:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// LD R1, PERIOD       ; Sets R1 to 20000
+      /// LD R2, DUTY         ; Sets R2 to x8000
+      /// TRAP 0x56           ; Sets P0 to enabled w/ period of 20 ms and duty
+      ///                     ; of 50%
+      /// HALT
+      ///
+      /// PERIOD .FILL #20000
+      /// DUTY .FILL x8000
+      /// ```
+      ///
+      /// [PWM]: lc3_traits::peripherals::pwm
+      /// [`ENABLE`]: ENABLE
+      /// [Enabled]: lc3_traits::peripherals::pwm::PwmState::Enabled
+      /// [Disabled]: lc3_traits::peripherals::pwm::PwmState::Disabled
+      /// [Pin]: lc3_traits::peripherals::pwm::PwmPin
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`R1`]: lc3_isa::Reg::R1
+      /// [`R2`]: lc3_isa::Reg::R2
+      /// [`NUM_PWM_PINS`]: lc3_traits::peripherals::pwm::PwmPin::NUM_PINS
+      /// [`P0`]: lc3_traits::peripherals::pwm::PwmPin::P0
+      [0x56] ENABLE_FINE,
+      /// Reads the period of a [PWM] [Pin] in [Enabled] mode, in microseconds.
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [PWM] [Pin] number.
+      ///
+      /// ## Outputs
+      ///  - [`R0`]: A period ∈ \[0, 65535\] microseconds.
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP reads the period from the [PWM] [Pin] indicated by [`R0`]
+      /// and returns the period in [`R0`]. The period has units of
+      /// microseconds; periods longer than 65535 microseconds read as 65535.
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_PWM_PINS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid pin number, 0 is returned in [`R0`].
+      ///
+      /// Attempting to read the period from a [PWM] [Pin] that is in [Disabled]
+      /// mode returns 0 in [`R0`].
+      ///
+      /// All registers (**excluding** [`R0`]) are preserved.
+      ///
+      /// ## Example
+      /// The below sets [`P0`] to be an [Enabled] with a period of *20 ms* and
+      /// a *50%* duty cycle. It then reads the period of [`P0`] and results in
+      /// the value 20000 in [`R0`] then halts:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// LD R1, PERIOD       ; Sets R1 to 20000
+      /// LD R2, DUTY         ; Sets R2 to x8000
+      /// TRAP 0x56           ; Sets P0 to Enabled w/ period of 20 ms and duty
+      ///                     ; of 50%
+      /// TRAP 0x57           ; Reads period of P0, sets R0 to 20000
+      /// HALT
+      ///
+      /// PERIOD .FILL #20000
+      /// DUTY .FILL x8000
+      /// ```
+      ///
+      /// [PWM]: lc3_traits::peripherals::pwm
+      /// [Enabled]: lc3_traits::peripherals::pwm::PwmState::Enabled
+      /// [Disabled]: lc3_traits::peripherals::pwm::PwmState::Disabled
+      /// [Pin]: lc3_traits::peripherals::pwm::PwmPin
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`NUM_PWM_PINS`]: lc3_traits::peripherals::pwm::PwmPin::NUM_PINS
+      /// [`P0`]: lc3_traits::peripherals::pwm::PwmPin::P0
+      [0x57] GET_FINE_PERIOD,
+      /// Reads the duty cycle of a [PWM] [Pin] in [Enabled] mode, out of 65535.
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [PWM] [Pin] number.
+      ///
+      /// ## Outputs
+      ///  - [`R0`]: A duty cycle ∈ \[0, 65535\].
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP reads the duty cycle from the [PWM] [Pin] indicated by
+      /// [`R0`] and returns the duty cycle in [`R0`]. The duty cycle is the
+      /// fraction of the period spent high, out of 65535 (e.g. a value of
+      /// x4000 corresponds to a 25% duty cycle).
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_PWM_PINS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid pin number, the `n` bit is set.
+      ///
+      /// Attempting to read the duty cycle from a [PWM] [Pin] that is in
+      /// [Disabled] mode returns -1 in [`R0`].
+      ///
+      /// All registers (**excluding** [`R0`]) are preserved.
+      ///
+      /// ## Example
+      /// The below sets [`P0`] to be an [Enabled] with a period of *20 ms* and
+      /// a *50%* duty cycle. It then reads the duty cycle of [`P0`] and results
+      /// in the value x8000 in [`R0`] then halts:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// LD R1, PERIOD       ; Sets R1 to 20000
+      /// LD R2, DUTY         ; Sets R2 to x8000
+      /// TRAP 0x56           ; Sets P0 to Enabled w/ period of 20 ms and duty
+      ///                     ; of 50%
+      /// TRAP 0x58           ; Reads duty of P0, sets R0 to x8000
+      /// HALT
+      ///
+      /// PERIOD .FILL #20000
+      /// DUTY .FILL x8000
+      /// ```
+      ///
+      /// [PWM]: lc3_traits::peripherals::pwm
+      /// [Enabled]: lc3_traits::peripherals::pwm::PwmState::Enabled
+      /// [Disabled]: lc3_traits::peripherals::pwm::PwmState::Disabled
+      /// [Pin]: lc3_traits::peripherals::pwm::PwmPin
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`NUM_PWM_PINS`]: lc3_traits::peripherals::pwm::PwmPin::NUM_PINS
+      /// [`P0`]: lc3_traits::peripherals::pwm::PwmPin::P0
+      [0x58] GET_FINE_DUTY,
+  });
+}
+
+/// Trap vectors for the [`Timers`](lc3_traits::peripherals::Timers)
+/// peripheral.
+pub mod timers {
+  define!([super::mm::TIMER_OFFSET] <- {
+      /// Puts a [Timer] in [SingleShot] mode.
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [Timer] [ID] number.
+      ///  - [`R1`]: The period.
+      ///  - [`R2`]: Address of interrupt service routine.
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP puts the [Timer] indicated by [`R0`] into [SingleShot]
+      /// mode. It also sets the period of the [Timer] as well as the entry
+      /// in the interrupt vector table corresponding to the [Timer]. The
+      /// period is measured in units of milliseconds and uses full 16-bit
+      /// words (i.e. the period will be a value in the range \[0, 65535\]).
+      ///
+      /// In [SingleShot] mode, the interrupt service routine is only
+      /// triggered one time. The [Timer] will then set its state to [Disabled].
+      ///
+      /// Setting the mode or period of a [Timer] will overwrite the previous
+      /// [Timer]. In other words, if this TRAP is called on a [Timer] that is
+      /// currently running, the interrupt service routine will only trigger
+      /// after the newly set period elapses.
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_TIMERS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid timer [ID] number, the `n` bit is
+      /// set.
+      ///
+      /// All registers (including [`R0`], [`R1`], and [`R2`]) are preserved.
+      ///
+      /// Be sure to follow the
+      /// [guidelines for writing ISRs](../index.html#guidelines-on-writing-isrs).
+      ///
+      /// ## Example
+      /// The below sets [`T0`] to be a [SingleShot] with a period of `3
+      /// seconds` and sets the interrupt service routine to `ISR`. It will then
+      /// spin until [`T0`] fires an interrupt, after three seconds have passed,
+      /// which will call `ISR`, set the `ISR_FLAG` to 1, and allow the main
+      /// program to halt.
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// LD R1, PERIOD       ; Sets R1 to 3000
+      /// LEA R2, ISR         ; Sets R2 to the address of ISR
+      /// TRAP 0x60           ; Sets T0 to SingleShot w/ period of 3000 and ISR
+      ///
+      /// LOOP
+      /// LD R1, ISR_FLAG     ; Loops until flag is set
+      /// BRz LOOP
+      /// HALT
+      ///
+      /// PERIOD .FILL #3000
+      /// ISR_FLAG .FILL #0
+      ///
+      /// ISR
+      /// ADD R6, R6, #-1     ; Save R0 onto the supervisor stack
+      /// STR R0, R6, #0
+      ///
+      /// AND R0, R0, #0
+      /// ADD R0, R0, #1
+      /// ST R0, ISR_FLAG
+      ///
+      /// LDR R0, R6, #0      ; Pop R0 off of the supervisor stack
+      /// ADD R6, R6, #1
+      /// RTI
+      /// ```
       ///
       /// [Timer]: lc3_traits::peripherals::timers
       /// [Disabled]: lc3_traits::peripherals::timers::TimerState::Disabled
@@ -1112,8 +1661,9 @@ pub mod timers {
       ///
       /// | Mode           | Value |
       /// | -------------- | ----- |
-      /// | [`SingleShot`] | 0     |
-      /// | [`Repeated`]   | 1     |
+      /// | [`SingleShot`]  | 0     |
+      /// | [`Repeated`]    | 1     |
+      /// | [`FreeRunning`] | 2     |
       ///
       /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
       /// ∈ \[0, [`NUM_TIMERS`])), this TRAP is _infallible_.
@@ -1144,6 +1694,7 @@ pub mod timers {
       /// [Timer]: lc3_traits::peripherals::timers
       /// [`Repeated`]: lc3_traits::peripherals::timers::TimerMode::Repeated
       /// [`SingleShot`]: lc3_traits::peripherals::timers::TimerMode::SingleShot
+      /// [`FreeRunning`]: lc3_traits::peripherals::timers::TimerMode::FreeRunning
       /// [SingleShot]: lc3_traits::peripherals::timers::TimerMode::SingleShot
       /// [ID]: lc3_traits::peripherals::timers::TimerId
       /// [`R0`]: lc3_isa::Reg::R0
@@ -1171,6 +1722,9 @@ pub mod timers {
       /// Reading the period of a [Timer] that is [Disabled] will return a
       /// value of zero.
       ///
+      /// For a [Timer] in [FreeRunning] mode, this TRAP returns the current
+      /// count of the [Timer] (in milliseconds) instead of its period.
+      ///
       /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
       /// ∈ \[0, [`NUM_TIMERS`])), this TRAP is _infallible_.
       ///
@@ -1208,7 +1762,173 @@ pub mod timers {
       /// [`NUM_TIMERS`]: lc3_traits::peripherals::timers::TimerId::NUM_TIMERS
       /// [`T0`]: lc3_traits::peripherals::timers::TimerId::T0
       /// [mode]: lc3_traits::peripherals::timers::TimerMode
+      /// [FreeRunning]: lc3_traits::peripherals::timers::TimerMode::FreeRunning
       [0x64] GET_PERIOD,
+      /// Puts a [Timer] in [FreeRunning] mode.
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [Timer] [ID] number.
+      ///  - [`R1`]: The period.
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP puts the [Timer] indicated by [`R0`] into [FreeRunning]
+      /// mode and sets its period. In [FreeRunning] mode, the [Timer] counts
+      /// up from zero every millisecond and wraps back to zero once the count
+      /// reaches the period. The count can be read with [`GET_PERIOD`].
+      ///
+      /// A [Timer] in [FreeRunning] mode never triggers interrupts, so no
+      /// interrupt service routine is needed.
+      ///
+      /// As with the other modes, setting the period restarts the count and a
+      /// period of zero disables the [Timer].
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_TIMERS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid timer [ID] number, the `n` bit is
+      /// set.
+      ///
+      /// All registers (including [`R0`] and [`R1`]) are preserved.
+      ///
+      /// ## Example
+      /// The below starts [`T2`] counting, waits a while, and then reads the
+      /// number of milliseconds that have passed into [`R0`].
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0
+      /// ADD R0, R0, #2      ; Sets R0 to 2
+      /// LD R1, PERIOD       ; Sets R1 to 65535
+      /// TRAP 0x65           ; Sets T2 to FreeRunning w/ period of 65535
+      ///
+      /// ...                 ; Do something that takes a while
+      ///
+      /// TRAP 0x64           ; Reads T2's count into R0
+      /// HALT
+      ///
+      /// PERIOD .FILL xFFFF
+      /// ```
+      ///
+      /// [Timer]: lc3_traits::peripherals::timers
+      /// [FreeRunning]: lc3_traits::peripherals::timers::TimerMode::FreeRunning
+      /// [ID]: lc3_traits::peripherals::timers::TimerId
+      /// [`GET_PERIOD`]: GET_PERIOD
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`R1`]: lc3_isa::Reg::R1
+      /// [`NUM_TIMERS`]: lc3_traits::peripherals::timers::TimerId::NUM_TIMERS
+      /// [`T2`]: lc3_traits::peripherals::timers::TimerId::T2
+      [0x65] FREE_RUNNING,
+      /// Sets the input capture source of a [Timer].
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [Timer] [ID] number.
+      ///  - [`R1`]: A [GPIO] [pin] number.
+      ///  - [`R2`]: The [edge(s)][edge] to capture on.
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP makes the [Timer] indicated by [`R0`] latch its current
+      /// count whenever the [GPIO] [pin] indicated by [`R1`] has an edge of
+      /// the kind given in [`R2`]. The latched count can then be read with
+      /// [`GET_CAPTURE`]. The values for [`R2`] are as follows:
+      ///
+      /// | Edge                 | Value |
+      /// | -------------------- | ----- |
+      /// | none (disabled)      | 0     |
+      /// | [`Rising`]           | 1     |
+      /// | [`Falling`]          | 2     |
+      /// | [`Both`]             | 3     |
+      ///
+      /// Only the lowest 3 bits of [`R1`] and the lowest 2 bits of [`R2`] are
+      /// used. The [GPIO] [pin] must be readable (i.e. in [Input] or
+      /// [Interrupt] mode) for edges to be seen.
+      ///
+      /// Setting the capture source clears the previously captured count.
+      /// Capturing does not affect the mode or period of the [Timer]; pairing
+      /// it with a [Timer] in [FreeRunning] mode is a good way to measure the
+      /// width of a pulse.
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_TIMERS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid timer [ID] number, the `n` bit is
+      /// set.
+      ///
+      /// All registers (including [`R0`], [`R1`], and [`R2`]) are preserved.
+      ///
+      /// ## Example
+      /// The below has [`T0`] capture its count on both edges of
+      /// [`G3`]:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// AND R1, R1, #0
+      /// ADD R1, R1, #3      ; Sets R1 to 3
+      /// AND R2, R2, #0
+      /// ADD R2, R2, #3      ; Sets R2 to 3 (both edges)
+      /// TRAP 0x66           ; T0 now captures on both edges of G3
+      /// ```
+      ///
+      /// [Timer]: lc3_traits::peripherals::timers
+      /// [FreeRunning]: lc3_traits::peripherals::timers::TimerMode::FreeRunning
+      /// [ID]: lc3_traits::peripherals::timers::TimerId
+      /// [edge]: lc3_traits::peripherals::timers::CaptureEdge
+      /// [`Rising`]: lc3_traits::peripherals::timers::CaptureEdge::Rising
+      /// [`Falling`]: lc3_traits::peripherals::timers::CaptureEdge::Falling
+      /// [`Both`]: lc3_traits::peripherals::timers::CaptureEdge::Both
+      /// [GPIO]: lc3_traits::peripherals::gpio
+      /// [pin]: lc3_traits::peripherals::gpio::GpioPin
+      /// [Input]: lc3_traits::peripherals::gpio::GpioState::Input
+      /// [Interrupt]: lc3_traits::peripherals::gpio::GpioState::Interrupt
+      /// [`GET_CAPTURE`]: GET_CAPTURE
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`R1`]: lc3_isa::Reg::R1
+      /// [`R2`]: lc3_isa::Reg::R2
+      /// [`NUM_TIMERS`]: lc3_traits::peripherals::timers::TimerId::NUM_TIMERS
+      /// [`T0`]: lc3_traits::peripherals::timers::TimerId::T0
+      /// [`G3`]: lc3_traits::peripherals::gpio::GpioPin::G3
+      [0x66] CAPTURE,
+      /// Returns the last count captured by a [Timer].
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [Timer] [ID] number.
+      ///
+      /// ## Outputs
+      ///  - [`R0`]: The captured count ∈ \[0, 65535\].
+      ///  - `n` bit: set on error, cleared on success.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP returns the count (in milliseconds) that the [Timer]
+      /// indicated by [`R0`] latched on the last edge matching its
+      /// [capture source][`CAPTURE`]. If no edge has been seen since the
+      /// capture source was set, this is zero.
+      ///
+      /// When [`R0`] contains a valid pin number (i.e. when [`R0`] is
+      /// ∈ \[0, [`NUM_TIMERS`])), this TRAP is _infallible_.
+      ///
+      /// When [`R0`] does not hold a valid pin number, the `n` bit is set.
+      ///
+      /// All registers (**excluding** [`R0`]) are preserved.
+      ///
+      /// ## Example
+      /// The below reads the count [`T0`] last captured into [`R0`]:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Sets R0 to 0
+      /// TRAP 0x67           ; Reads T0's captured count into R0
+      /// ```
+      ///
+      /// [Timer]: lc3_traits::peripherals::timers
+      /// [ID]: lc3_traits::peripherals::timers::TimerId
+      /// [`CAPTURE`]: CAPTURE
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`NUM_TIMERS`]: lc3_traits::peripherals::timers::TimerId::NUM_TIMERS
+      /// [`T0`]: lc3_traits::peripherals::timers::TimerId::T0
+      [0x67] GET_CAPTURE,
   });
 
 }
@@ -1271,6 +1991,188 @@ pub mod clock {
   });
 }
 
+/// Trap vectors for the interrupt controller.
+///
+/// Sources are numbered as in
+/// [`INTERRUPT_SOURCES`](lc3_traits::control::INTERRUPT_SOURCES): the keyboard
+/// is 0, the display is 1, [GPIO] pins are 2 through 9, [ADC] pins are 10
+/// through 15, [PWM] pins are 16 and 17 and [Timers][Timer] are 18 through 21.
+///
+/// [GPIO]: lc3_traits::peripherals::gpio
+/// [ADC]: lc3_traits::peripherals::adc
+/// [PWM]: lc3_traits::peripherals::pwm
+/// [Timer]: lc3_traits::peripherals::timers
+pub mod interrupts {
+  define!([super::mm::INTERRUPT_OFFSET] <- {
+      /// Sets the priority of an interrupt [source].
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [source] number.
+      ///  - [`R1`]: The priority (0 - 7).
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set if [`R0`] is invalid, cleared otherwise.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP sets the priority of the interrupt [source] indicated by
+      /// [`R0`] to the value in [`R1`]. A pending interrupt only fires when
+      /// its priority is higher than the priority the processor is currently
+      /// running at; sources with priority 0 never fire. Sources start out at
+      /// priority 4. Only the lowest 3 bits of [`R1`] are used.
+      ///
+      /// If [`R0`] is not a valid [source] number, the `n` bit is set and
+      /// nothing else happens.
+      ///
+      /// All registers (including [`R0`] and [`R1`]) are preserved.
+      ///
+      /// ## Example
+      /// The below makes the keyboard interrupt take priority over everything
+      /// else:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0      ; Keyboard
+      /// AND R1, R1, #0
+      /// ADD R1, R1, #7      ; Highest priority
+      /// TRAP 0x80
+      /// ```
+      ///
+      /// [source]: lc3_traits::control::InterruptSource
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`R1`]: lc3_isa::Reg::R1
+      [0x80] SET_PRIORITY,
+      /// Returns the priority of an interrupt [source].
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [source] number.
+      ///
+      /// ## Outputs
+      ///  - [`R0`]: The priority of the [source] (0 - 7).
+      ///  - `n` bit: set if [`R0`] is invalid, cleared otherwise.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP returns the priority of the interrupt [source] indicated by
+      /// [`R0`] in [`R0`]. If [`R0`] is not a valid [source] number, the `n`
+      /// bit is set and [`R0`] is set to 0.
+      ///
+      /// All registers (**excluding** [`R0`]) are preserved.
+      ///
+      /// ## Example
+      /// The below reads the priority of [`G0`]:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0
+      /// ADD R0, R0, #2      ; G0
+      /// TRAP 0x81
+      /// ```
+      ///
+      /// [source]: lc3_traits::control::InterruptSource
+      /// [`R0`]: lc3_isa::Reg::R0
+      /// [`G0`]: lc3_traits::peripherals::gpio::GpioPin::G0
+      [0x81] GET_PRIORITY,
+      /// Masks an interrupt [source].
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [source] number.
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set if [`R0`] is invalid, cleared otherwise.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP keeps the interrupt [source] indicated by [`R0`] from
+      /// firing. The device itself is left alone: its interrupt stays pending
+      /// until the [source] is [unmasked][`UNMASK`] (or the device's flag is
+      /// cleared).
+      ///
+      /// All registers (including [`R0`]) are preserved.
+      ///
+      /// ## Example
+      /// The below masks the display interrupt:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0
+      /// ADD R0, R0, #1      ; Display
+      /// TRAP 0x82
+      /// ```
+      ///
+      /// [source]: lc3_traits::control::InterruptSource
+      /// [`UNMASK`]: UNMASK
+      /// [`R0`]: lc3_isa::Reg::R0
+      [0x82] MASK,
+      /// Unmasks an interrupt [source].
+      ///
+      /// ## Inputs
+      ///  - [`R0`]: A [source] number.
+      ///
+      /// ## Outputs
+      ///  - `n` bit: set if [`R0`] is invalid, cleared otherwise.
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP undoes [`MASK`] for the interrupt [source] indicated by
+      /// [`R0`]. Sources start out unmasked.
+      ///
+      /// All registers (including [`R0`]) are preserved.
+      ///
+      /// ## Example
+      /// The below unmasks the display interrupt:
+      /// ```{ARM Assembly}
+      /// AND R0, R0, #0
+      /// ADD R0, R0, #1      ; Display
+      /// TRAP 0x83
+      /// ```
+      ///
+      /// [source]: lc3_traits::control::InterruptSource
+      /// [`MASK`]: MASK
+      /// [`R0`]: lc3_isa::Reg::R0
+      [0x83] UNMASK,
+      /// Enables interrupts.
+      ///
+      /// ## Inputs
+      ///  - None
+      ///
+      /// ## Outputs
+      ///  - None
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP turns the interrupt controller back on after [`DISABLE`].
+      /// Interrupts start out enabled.
+      ///
+      /// All registers are preserved.
+      ///
+      /// ## Example
+      /// ```{ARM Assembly}
+      /// TRAP 0x84
+      /// ```
+      ///
+      /// [`DISABLE`]: DISABLE
+      [0x84] ENABLE,
+      /// Disables interrupts.
+      ///
+      /// ## Inputs
+      ///  - None
+      ///
+      /// ## Outputs
+      ///  - None
+      ///
+      /// ## Usage
+      ///
+      /// This TRAP stops all interrupts from firing, regardless of their
+      /// priority or mask, until [`ENABLE`] is used. Interrupts raised in the
+      /// meantime stay pending.
+      ///
+      /// All registers are preserved.
+      ///
+      /// ## Example
+      /// ```{ARM Assembly}
+      /// TRAP 0x85
+      /// ```
+      ///
+      /// [`ENABLE`]: ENABLE
+      [0x85] DISABLE,
+  });
+}
+
 /// Trap vectors for the [`Input`](lc3_traits::peripherals::Input)
 /// peripheral.
 pub mod input {
@@ -8,5 +8,5 @@ use lti::{assert_eq, with_larger_stack};
 
 #[test]
 fn os_size() {
-    with_larger_stack(None, || assert_eq!(OS.into_iter().count(), 0x0535 /*1333*/));
+    with_larger_stack(None, || assert_eq!(OS.into_iter().count(), 0x06FE /*1790*/));
 }
@@ -1,9 +1,10 @@
 use super::*;
 
-use lc3_traits::peripherals::adc::{Adc, AdcPin, AdcState};
+use lc3_traits::peripherals::adc::{Adc, AdcInterrupt, AdcMode, AdcPin, AdcState};
 use lc3_shims::peripherals::AdcShim;
 
 use AdcState::*;
+use AdcMode::*;
 use AdcPin::*;
 
 single_test! {
@@ -79,3 +80,93 @@ single_test! {
     post: |i| { eq!(i.get_word_unchecked(0x3004), 10); },
     with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
 }
+
+single_test! {
+    continuous,
+    insns: [
+        { AND R0, R0, #0 },
+        { TRAP #0x40 },
+        { TRAP #0x44 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        let p = i.get_peripherals();
+        eq!(Adc::get_state(p, A0), Enabled);
+        eq!(Adc::get_mode(p, A0), Continuous);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    one_shot,
+    insns: [
+        { AND R0, R0, #0 },
+        { TRAP #0x45 },
+        { TRAP #0x25 },
+    ],
+    pre: |p| {
+        Adc::set_state(p, A0, Enabled).unwrap();
+        Adc::set_mode(p, A0, Continuous).unwrap();
+    },
+    post: |i| {
+        let p = i.get_peripherals();
+        eq!(Adc::get_state(p, A0), Enabled);
+        eq!(Adc::get_mode(p, A0), OneShot);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    get_mode_continuous,
+    prefill: { 0x3004: 0 },
+    insns: [
+        { AND R0, R0, #0 },
+        { TRAP #0x42 },
+        { ST R0, #1 },
+        { TRAP #0x25 },
+    ],
+    pre: |p| {
+        Adc::set_state(p, A0, Enabled).unwrap();
+        Adc::set_mode(p, A0, Continuous).unwrap();
+    },
+    post: |i| { eq!(i.get_word_unchecked(0x3004), 1); },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    interrupt,
+    prefill: { 0x3008: 0x4000, 0x3009: 200 },
+    insns: [
+        { AND R0, R0, #0 },
+        { ADD R0, R0, #1 },
+        { LD R1, #5 },
+        { AND R2, R2, #0 },
+        { ADD R2, R2, #2 },
+        { LD R3, #3 },
+        { TRAP #0x46 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        let p = i.get_peripherals();
+        eq!(Adc::get_interrupt(p, A1), AdcInterrupt::ThresholdCrossing(200));
+        eq!(Adc::get_state(p, A1), Disabled);
+        eq!(Adc::get_mode(p, A1), OneShot);
+        eq!(i.get_word_unchecked(lc3_baseline_sim::mem_mapped::ADC_BASE_INT_VEC + 1), 0x4000);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    sample_period,
+    prefill: { 0x3003: 25 },
+    insns: [
+        { LD R0, #2 },
+        { TRAP #0x47 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        let p = i.get_peripherals();
+        eq!(Adc::get_sample_period(p).get(), 25);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
@@ -1,6 +1,6 @@
 use super::*;
 
-use lc3_traits::peripherals::gpio::{Gpio, GpioPin, GpioState};
+use lc3_traits::peripherals::gpio::{Gpio, GpioPin, GpioState, GpioTrigger, GpioPull};
 
 use GpioState::*;
 use GpioPin::*;
@@ -66,4 +66,87 @@ mod states {
         post: |i| { eq!(i.get_word_unchecked(0x3004), 1); },
         with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
     }
+
+    single_test! {
+        get_mode_ignores_trigger_and_pull,
+        prefill: { 0x3004: 0 },
+        insns: [
+            { AND R0, R0, #0 },
+            { TRAP #0x34 },
+            { ST R0, #1 },
+            { TRAP #0x25 },
+        ],
+        pre: |p| {
+            Gpio::set_trigger(p, G0, GpioTrigger::LowLevel).unwrap();
+            Gpio::set_pull(p, G0, GpioPull::PullUp).unwrap();
+            Gpio::set_state(p, G0, Interrupt).unwrap();
+        },
+        post: |i| { eq!(i.get_word_unchecked(0x3004), 3); },
+        with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+    }
+}
+
+mod config {
+    use super::*;
+
+    single_test! {
+        set_trigger,
+        insns: [
+            { AND R0, R0, #0 },
+            { ADD R0, R0, #2 },
+            { AND R1, R1, #0 },
+            { ADD R1, R1, #1 },
+            { TRAP #0x37 },
+            { TRAP #0x25 },
+        ],
+        post: |i| {
+            let p = i.get_peripherals();
+            eq!(Gpio::get_trigger(p, G2), GpioTrigger::FallingEdge);
+        },
+        with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+    }
+
+    single_test! {
+        set_pull,
+        insns: [
+            { AND R0, R0, #0 },
+            { ADD R0, R0, #2 },
+            { AND R1, R1, #0 },
+            { ADD R1, R1, #2 },
+            { TRAP #0x38 },
+            { TRAP #0x25 },
+        ],
+        post: |i| {
+            let p = i.get_peripherals();
+            eq!(Gpio::get_pull(p, G2), GpioPull::PullDown);
+        },
+        with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+    }
+
+    // Changing the mode shouldn't clobber the trigger or the pull (and vice
+    // versa).
+    single_test! {
+        mode_and_config_are_independent,
+        insns: [
+            { AND R0, R0, #0 },
+            { AND R1, R1, #0 },
+            { ADD R1, R1, #3 },
+            { TRAP #0x37 },     // G0: High Level
+            { AND R1, R1, #0 },
+            { ADD R1, R1, #1 },
+            { TRAP #0x38 },     // G0: Pull Up
+            { TRAP #0x30 },     // G0: Input
+            { AND R1, R1, #0 },
+            { ADD R1, R1, #2 },
+            { TRAP #0x37 },     // G0: Both Edges
+            { TRAP #0x25 },
+        ],
+        post: |i| {
+            let p = i.get_peripherals();
+            eq!(Gpio::get_state(p, G0), Input);
+            eq!(Gpio::get_trigger(p, G0), GpioTrigger::BothEdges);
+            eq!(Gpio::get_pull(p, G0), GpioPull::PullUp);
+        },
+        with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+    }
 }
@@ -0,0 +1,133 @@
+use super::*;
+
+use lc3_baseline_sim::{ICR_ADDR, IMR0_ADDR, IMR1_ADDR, IPR0_ADDR, IPR5_ADDR};
+
+// T3 is the last source (21); its priority field is bits 4-6 of IPR5 and its
+// mask bit is bit 5 of IMR1.
+
+single_test! {
+    set_priority,
+    prefill: {
+        0x3008: 21,
+        0x3009: 0xFFFF,
+    },
+    insns: [
+        { LD R0, #7 },
+        { AND R1, R1, #0 },
+        { ADD R1, R1, #7 },
+        { TRAP #0x80 },
+        { AND R0, R0, #0 },         // Only the low 3 bits of R1 are used
+        { LD R1, #3 },
+        { TRAP #0x80 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        eq!(i.get_word_unchecked(IPR5_ADDR), 0x0074);
+        eq!(i.get_word_unchecked(IPR0_ADDR), 0x4447);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    get_priority,
+    prefill: {
+        0x300C: 0,
+        0x300D: 0,
+        0x300E: 22,
+    },
+    insns: [
+        { AND R0, R0, #0 },
+        { ADD R0, R0, #6 },         // G4
+        { AND R1, R1, #0 },
+        { ADD R1, R1, #5 },
+        { TRAP #0x80 },
+        { TRAP #0x81 },
+        { ST R0, #5 },
+        { LD R0, #6 },              // Not a source
+        { TRAP #0x81 },
+        { ST R0, #3 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        eq!(i.get_word_unchecked(IPR0_ADDR + 1), 0x4544);
+        eq!(i.get_word_unchecked(0x300C), 5);
+        eq!(i.get_word_unchecked(0x300D), 0);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    set_priority_invalid_source,
+    prefill: { 0x3004: 22 },
+    insns: [
+        { LD R0, #3 },
+        { AND R1, R1, #0 },
+        { TRAP #0x80 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        for addr in IPR0_ADDR..IPR5_ADDR {
+            eq!(i.get_word_unchecked(addr), 0x4444);
+        }
+        eq!(i.get_word_unchecked(IPR5_ADDR), 0x0044);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    mask_and_unmask,
+    prefill: { 0x300A: 21 },
+    insns: [
+        { AND R0, R0, #0 },
+        { ADD R0, R0, #1 },         // Display
+        { TRAP #0x82 },
+        { ADD R0, R0, #1 },         // G0
+        { TRAP #0x82 },
+        { LD R0, #4 },              // T3
+        { TRAP #0x82 },
+        { AND R0, R0, #0 },         // Unmasking an unmasked source does nothing
+        { TRAP #0x83 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        eq!(i.get_word_unchecked(IMR0_ADDR), 0b110);
+        eq!(i.get_word_unchecked(IMR1_ADDR), 1 << 5);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    unmask,
+    insns: [
+        { AND R0, R0, #0 },
+        { ADD R0, R0, #2 },
+        { TRAP #0x82 },
+        { ADD R0, R0, #1 },
+        { TRAP #0x82 },
+        { TRAP #0x83 },
+        { TRAP #0x25 },
+    ],
+    post: |i| { eq!(i.get_word_unchecked(IMR0_ADDR), 0b100); },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    disable,
+    insns: [
+        { TRAP #0x85 },
+        { TRAP #0x25 },
+    ],
+    post: |i| { eq!(i.get_word_unchecked(ICR_ADDR), 0); },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    enable,
+    insns: [
+        { TRAP #0x85 },
+        { TRAP #0x84 },
+        { TRAP #0x25 },
+    ],
+    post: |i| { eq!(i.get_word_unchecked(ICR_ADDR), 0x8000); },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
@@ -17,6 +17,7 @@ use lc3_isa::OS_START_ADDR;
 mod adc;
 mod clock;
 mod gpio;
+mod interrupts;
 mod pwm;
 mod timers;
 
@@ -1,7 +1,6 @@
 use super::*;
 
-use core::num::NonZeroU8;
-use lc3_traits::peripherals::pwm::{Pwm, PwmPin, PwmState};
+use lc3_traits::peripherals::pwm::{Pwm, PwmPeriod, PwmPin, PwmState};
 use PwmState::*;
 use PwmPin::*;
 
@@ -20,8 +19,30 @@ single_test! {
     ],
     post: |i| {
         let p = i.get_peripherals();
-        eq!(Pwm::get_state(p, P0), Enabled(NonZeroU8::new(20).unwrap()));
-        eq!(Pwm::get_duty_cycle(p, P0), 128);
+        eq!(Pwm::get_state(p, P0), Enabled(PwmPeriod::from_millis(20).unwrap()));
+        eq!(Pwm::get_duty_cycle(p, P0), 0x8080);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+// The longest period the old units can express:
+single_test! {
+    enable_longest_period,
+    prefill: {
+        0x3005: 255,
+        0x3006: 0xFFFF
+    },
+    insns: [
+        { AND R0, R0, #0 },
+        { LD R1, #3 },
+        { LD R2, #3 },
+        { TRAP #0x50 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        let p = i.get_peripherals();
+        eq!(Pwm::get_state(p, P0), Enabled(PwmPeriod::MAX));
+        eq!(Pwm::get_duty_cycle(p, P0), 0xFFFF);
     },
     with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
 }
@@ -34,13 +55,13 @@ single_test! {
         { TRAP #0x25 },
     ],
     pre: |p| {
-        Pwm::set_state(p, P0, Enabled(NonZeroU8::new(20).unwrap()));
-        Pwm::set_duty_cycle(p, P0, 128);
+        Pwm::set_state(p, P0, Enabled(PwmPeriod::from_millis(20).unwrap()));
+        Pwm::set_duty_cycle(p, P0, 0x8080);
     },
     post: |i| {
         let p = i.get_peripherals();
         eq!(Pwm::get_state(p, P0), Disabled);
-        eq!(Pwm::get_duty_cycle(p, P0), 128);
+        eq!(Pwm::get_duty_cycle(p, P0), 0x8080);
     },
     with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
 }
@@ -55,8 +76,8 @@ single_test! {
         { TRAP #0x25 },
     ],
     pre: |p| {
-        Pwm::set_state(p, P0, Enabled(NonZeroU8::new(20).unwrap()));
-        Pwm::set_duty_cycle(p, P0, 128);
+        Pwm::set_state(p, P0, Enabled(PwmPeriod::from_millis(20).unwrap()));
+        Pwm::set_duty_cycle(p, P0, 0x8080);
     },
     post: |i| {
         eq!(i.get_word_unchecked(0x3004), 20);
@@ -105,11 +126,101 @@ single_test! {
         { TRAP #0x25 },
     ],
     pre: |p| {
-        Pwm::set_state(p, P0, Enabled(NonZeroU8::new(20).unwrap()));
-        Pwm::set_duty_cycle(p, P0, 128);
+        Pwm::set_state(p, P0, Enabled(PwmPeriod::from_millis(20).unwrap()));
+        Pwm::set_duty_cycle(p, P0, 0x8080);
     },
     post: |i| {
         eq!(i.get_word_unchecked(0x3004), 128);
     },
     with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
 }
+
+single_test! {
+    interrupt,
+    prefill: { 0x3005: 0x4000 },
+    insns: [
+        { AND R0, R0, #0 },
+        { ADD R0, R0, #1 },
+        { LD R1, #2 },
+        { TRAP #0x54 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        let p = i.get_peripherals();
+        eq!(Pwm::get_rollover_interrupt(p, P1), true);
+        eq!(Pwm::get_rollover_interrupt(p, P0), false);
+        eq!(i.get_word_unchecked(lc3_baseline_sim::mem_mapped::PWM_BASE_INT_VEC + 1), 0x4000);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    disable_interrupt,
+    insns: [
+        { AND R0, R0, #0 },
+        { TRAP #0x55 },
+        { TRAP #0x25 },
+    ],
+    pre: |p| { Pwm::set_rollover_interrupt(p, P0, true); },
+    post: |i| {
+        let p = i.get_peripherals();
+        eq!(Pwm::get_rollover_interrupt(p, P0), false);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    enable_fine,
+    prefill: {
+        0x3005: 20500,
+        0x3006: 0x8000
+    },
+    insns: [
+        { AND R0, R0, #0 },
+        { LD R1, #3 },
+        { LD R2, #3 },
+        { TRAP #0x56 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        let p = i.get_peripherals();
+        eq!(Pwm::get_state(p, P0), Enabled(PwmPeriod::from_micros(20500).unwrap()));
+        eq!(Pwm::get_duty_cycle(p, P0), 0x8000);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    get_fine_period,
+    prefill: { 0x3004: 0 },
+    insns: [
+        { AND R0, R0, #0 },
+        { TRAP #0x57 },
+        { ST R0, #1 },
+        { TRAP #0x25 },
+    ],
+    pre: |p| { Pwm::set_state(p, P0, Enabled(PwmPeriod::from_micros(20500).unwrap())); },
+    post: |i| {
+        eq!(i.get_word_unchecked(0x3004), 20500);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    get_fine_duty,
+    prefill: { 0x3004: 0 },
+    insns: [
+        { AND R0, R0, #0 },
+        { TRAP #0x58 },
+        { ST R0, #1 },
+        { TRAP #0x25 },
+    ],
+    pre: |p| {
+        Pwm::set_state(p, P0, Enabled(PwmPeriod::from_micros(20500).unwrap()));
+        Pwm::set_duty_cycle(p, P0, 0x1234);
+    },
+    post: |i| {
+        eq!(i.get_word_unchecked(0x3004), 0x1234);
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
@@ -157,3 +157,59 @@ single_test! {
     },
     with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
 }
+
+single_test! {
+    free_running,
+    prefill: {
+        0x3007: 1000,
+        0x3008: 0,
+    },
+    insns: [
+        { AND R0, R0, #0 },
+        { ADD R0, R0, #3 },
+        { LD R1, #4 },
+        { TRAP #0x65 },
+        { TRAP #0x63 },
+        { ST R0, #2 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        use lc3_traits::peripherals::timers::{Period, TimerMode};
+        let p = i.get_peripherals();
+
+        eq!(i.get_word_unchecked(0x3008), 2);
+        eq!(Timers::get_mode(p, T3), TimerMode::FreeRunning);
+        eq!(Timers::get_state(p, T3), WithPeriod(Period::new(1000).unwrap()));
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
+
+single_test! {
+    capture,
+    prefill: {
+        0x300A: 0xFFFF,
+    },
+    insns: [
+        { AND R0, R0, #0 },
+        { ADD R0, R0, #2 },
+        { AND R1, R1, #0 },
+        { ADD R1, R1, #5 },
+        { AND R2, R2, #0 },
+        { ADD R2, R2, #3 },
+        { TRAP #0x66 },
+        { TRAP #0x67 },
+        { ST R0, #1 },
+        { TRAP #0x25 },
+    ],
+    post: |i| {
+        use lc3_traits::peripherals::{gpio::GpioPin, timers::{CaptureEdge, CaptureSource}};
+        let p = i.get_peripherals();
+
+        eq!(i.get_word_unchecked(0x300A), 0);
+        eq!(
+            Timers::get_capture_source(p, T2),
+            Some(CaptureSource { pin: GpioPin::G5, edge: CaptureEdge::Both })
+        );
+    },
+    with os { MemoryShim::new(**OS_IMAGE) } @ OS_START_ADDR
+}
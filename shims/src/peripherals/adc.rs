@@ -1,35 +1,61 @@
 use lc3_traits::peripherals::adc::{
-    Adc, AdcMiscError, AdcPin as Pin, AdcPinArr as PinArr, AdcReadError as ReadError, AdcState,
-    AdcStateMismatch as StateMismatch,
+    Adc, AdcInterrupt, AdcMiscError, AdcMode, AdcPin as Pin, AdcPinArr as PinArr,
+    AdcReadError as ReadError, AdcSamplePeriod, AdcState, AdcStateMismatch as StateMismatch,
+    ADC_DEFAULT_SAMPLE_PERIOD, ADC_PINS,
 };
 
-#[derive(Debug, Clone)]
-pub struct AdcShim {
-    states: PinArr<State>,
-}
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Arc;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum State {
-    Enabled(u8),
-    Disabled,
-}
+/// A simulated ADC.
+///
+/// Some implementation details:
+///   - The value a pin is at (set with [`set_value`](AdcShim::set_value)) goes
+///     back to 0 when the pin is enabled.
+///   - Pins in [one shot](AdcMode::OneShot) mode read whatever value the pin
+///     is currently at.
+///   - Pins in [continuous](AdcMode::Continuous) mode are sampled on a
+///     separate thread (not available on wasm); reads return the latest
+///     sample. Shims made with [`manual`](AdcShim::manual) don't start this
+///     thread; conversions only happen when [`sample`](AdcShim::sample) is
+///     called.
+///   - Modes and interrupt settings are retained across state changes.
+pub struct AdcShim<'aint> {
+    states: PinArr<AdcState>,
+    modes: PinArr<AdcMode>,
+    interrupts: PinArr<AdcInterrupt>,
+    sample_period: AdcSamplePeriod,
 
-impl From<State> for AdcState {
-    fn from(state: State) -> AdcState {
-        use AdcState::*;
-        match state {
-            State::Enabled(_) => Enabled,
-            State::Disabled => Disabled,
-        }
-    }
+    values: Arc<PinArr<AtomicU8>>,
+    samples: Arc<PinArr<AtomicU8>>,
+
+    external_flags: Option<&'aint PinArr<AtomicBool>>,
+    internal_flags: Arc<PinArr<AtomicBool>>,
+
+    sampler: Sampler,
+    manual: bool,
 }
 
 const INIT_VALUE: u8 = 0;
 
-impl Default for AdcShim {
+macro_rules! arr { ($v:expr) => { PinArr([$v, $v, $v, $v, $v, $v]) }; }
+
+impl Default for AdcShim<'_> {
     fn default() -> Self {
         Self {
-            states: PinArr([State::Disabled; Pin::NUM_PINS]),
+            states: arr!(AdcState::Disabled),
+            modes: arr!(AdcMode::OneShot),
+            interrupts: arr!(AdcInterrupt::Disabled),
+            sample_period: ADC_DEFAULT_SAMPLE_PERIOD,
+
+            values: Arc::new(arr!(AtomicU8::new(INIT_VALUE))),
+            samples: Arc::new(arr!(AtomicU8::new(INIT_VALUE))),
+
+            external_flags: None,
+            internal_flags: Arc::new(arr!(AtomicBool::new(false))),
+
+            sampler: Sampler::new(),
+            manual: false,
         }
     }
 }
@@ -37,44 +63,230 @@ impl Default for AdcShim {
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct SetError(StateMismatch);
 
-impl AdcShim {
+impl AdcShim<'_> {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// A shim whose pins are only sampled when [`sample`](AdcShim::sample) is
+    /// called.
+    pub fn manual() -> Self {
+        Self { manual: true, ..Self::default() }
+    }
+
+    /// Does one conversion for `pin` right away, if it's enabled and in
+    /// continuous mode.
+    ///
+    /// -> true if a conversion happened
+    pub fn sample(&mut self, pin: Pin) -> bool {
+        match (self.states[pin], self.modes[pin]) {
+            (AdcState::Enabled, AdcMode::Continuous) => {
+                Self::convert(pin, self.interrupts[pin], &self.values, &self.samples, &self.internal_flags);
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn set_value(&mut self, pin: Pin, value: u8) -> Result<(), SetError> {
-        use State::*;
-        self.states[pin] = match self.states[pin] {
-            Enabled(_) => Enabled(value),
-            Disabled => return Err(SetError((pin, self.get_state(pin)))),
+        match self.states[pin] {
+            AdcState::Enabled => self.values[pin].store(value, Ordering::SeqCst),
+            AdcState::Disabled => return Err(SetError((pin, self.get_state(pin)))),
         };
         Ok(())
     }
+
+    /// Does one conversion for a pin in continuous mode: the pin's current
+    /// value becomes the latest sample and the pin's interrupt flag is raised
+    /// if the sample is one the pin's interrupt setting cares about.
+    fn convert(
+        pin: Pin,
+        interrupt: AdcInterrupt,
+        values: &PinArr<AtomicU8>,
+        samples: &PinArr<AtomicU8>,
+        flags: &PinArr<AtomicBool>,
+    ) {
+        use Ordering::SeqCst;
+
+        let sample = values[pin].load(SeqCst);
+        let prev = samples[pin].swap(sample, SeqCst);
+
+        let fire = match interrupt {
+            AdcInterrupt::Disabled => false,
+            AdcInterrupt::ConversionComplete => true,
+            AdcInterrupt::ThresholdCrossing(t) => (prev >= t) != (sample >= t),
+        };
+
+        if fire {
+            flags[pin].store(true, SeqCst);
+        }
+    }
+
+    // (Re)starts or stops sampling for a pin to match its current state, mode,
+    // and interrupt setting and the current sample period.
+    fn update_sampler(&mut self, pin: Pin) -> Result<(), AdcMiscError> {
+        self.sampler.stop(pin);
+
+        if self.manual {
+            return Ok(());
+        }
+
+        if let (AdcState::Enabled, AdcMode::Continuous) = (self.states[pin], self.modes[pin]) {
+            let interrupt = self.interrupts[pin];
+            let values = self.values.clone();
+            let samples = self.samples.clone();
+            let flags = self.internal_flags.clone();
+
+            self.sampler.start(pin, self.sample_period, move || {
+                Self::convert(pin, interrupt, &values, &samples, &flags)
+            })
+        } else {
+            Ok(())
+        }
+    }
 }
 
-impl Adc for AdcShim {
+impl<'a> Adc<'a> for AdcShim<'a> {
     fn set_state(&mut self, pin: Pin, state: AdcState) -> Result<(), AdcMiscError> {
-        use AdcState::*;
-        self.states[pin] = match state {
-            Enabled => State::Enabled(INIT_VALUE),
-            Disabled => State::Disabled,
-        };
-        Ok(())
+        if let AdcState::Enabled = state {
+            self.values[pin].store(INIT_VALUE, Ordering::SeqCst);
+            self.samples[pin].store(INIT_VALUE, Ordering::SeqCst);
+        }
+
+        self.states[pin] = state;
+        self.update_sampler(pin)
     }
 
     fn get_state(&self, pin: Pin) -> AdcState {
-        self.states[pin].into()
+        self.states[pin]
     }
 
     fn read(&self, pin: Pin) -> Result<u8, ReadError> {
-        use State::*;
-        match self.states[pin] {
-            Enabled(value) => Ok(value),
-            valueless => Err(ReadError((pin, valueless.into()))),
+        use AdcMode::*;
+        match (self.states[pin], self.modes[pin]) {
+            (AdcState::Enabled, OneShot) => Ok(self.values[pin].load(Ordering::SeqCst)),
+            (AdcState::Enabled, Continuous) => Ok(self.samples[pin].load(Ordering::SeqCst)),
+            (state, _) => Err(ReadError((pin, state))),
+        }
+    }
+
+    fn set_mode(&mut self, pin: Pin, mode: AdcMode) -> Result<(), AdcMiscError> {
+        // Start the samples off at the current value so that a threshold
+        // crossing isn't reported for the first conversion:
+        self.samples[pin].store(self.values[pin].load(Ordering::SeqCst), Ordering::SeqCst);
+
+        self.modes[pin] = mode;
+        self.update_sampler(pin)
+    }
+
+    fn get_mode(&self, pin: Pin) -> AdcMode {
+        self.modes[pin]
+    }
+
+    fn set_sample_period(&mut self, period: AdcSamplePeriod) -> Result<(), AdcMiscError> {
+        self.sample_period = period;
+
+        ADC_PINS
+            .iter()
+            .try_for_each(|p| self.update_sampler(*p))
+    }
+
+    fn get_sample_period(&self) -> AdcSamplePeriod {
+        self.sample_period
+    }
+
+    fn set_interrupt(&mut self, pin: Pin, interrupt: AdcInterrupt) -> Result<(), AdcMiscError> {
+        self.interrupts[pin] = interrupt;
+        self.update_sampler(pin)
+    }
+
+    fn get_interrupt(&self, pin: Pin) -> AdcInterrupt {
+        self.interrupts[pin]
+    }
+
+    fn register_interrupt_flags(&mut self, flags: &'a PinArr<AtomicBool>) {
+        self.external_flags = match self.external_flags {
+            None => Some(flags),
+            Some(_) => {
+                // warn!("re-registering interrupt flags!");
+                Some(flags)
+            }
+        }
+    }
+
+    // As with the timers, the external flags are updated whenever we're polled.
+    fn interrupt_occurred(&self, pin: Pin) -> bool {
+        use Ordering::SeqCst;
+
+        let occurred = self.internal_flags[pin].load(SeqCst);
+        self.external_flags.unwrap()[pin].store(occurred, SeqCst);
+
+        self.interrupts_enabled(pin) && occurred
+    }
+
+    fn reset_interrupt_flag(&mut self, pin: Pin) {
+        use Ordering::SeqCst;
+
+        self.external_flags.unwrap()[pin].store(false, SeqCst);
+        self.internal_flags[pin].store(false, SeqCst);
+    }
+}
+
+not_wasm! {
+    use std::sync::Mutex;
+
+    /// Runs the conversions for pins in continuous mode.
+    ///
+    /// `timer::Timer` isn't `Sync` so it lives behind a `Mutex`; this lets
+    /// `AdcShim` be shared through an `Arc<RwLock<_>>`.
+    struct Sampler {
+        timer: Mutex<timer::Timer>,
+        guards: PinArr<Option<timer::Guard>>,
+    }
+
+    impl Sampler {
+        fn new() -> Self {
+            Self {
+                timer: Mutex::new(timer::Timer::new()),
+                guards: PinArr([None, None, None, None, None, None]),
+            }
+        }
+
+        fn start<F>(&mut self, pin: Pin, period: AdcSamplePeriod, conversion: F) -> Result<(), AdcMiscError>
+        where
+            F: 'static + FnMut() + Send,
+        {
+            let duration = chrono::Duration::milliseconds(period.get() as i64);
+            let guard = self.timer.lock().unwrap().schedule_repeating(duration, conversion);
+
+            self.guards[pin] = Some(guard);
+            Ok(())
+        }
+
+        fn stop(&mut self, pin: Pin) {
+            drop(self.guards[pin].take())
         }
     }
 }
 
+wasm! {
+    /// No threads on wasm; continuous mode isn't supported.
+    struct Sampler;
+
+    impl Sampler {
+        fn new() -> Self { Sampler }
+
+        fn start<F>(&mut self, _pin: Pin, _period: AdcSamplePeriod, _conversion: F) -> Result<(), AdcMiscError>
+        where
+            F: 'static + FnMut() + Send,
+        {
+            Err(AdcMiscError)
+        }
+
+        fn stop(&mut self, _pin: Pin) { }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,4 +330,103 @@ mod tests {
         let val = shim.read(A0);
         assert_eq!(val, Err(ReadError((A0, AdcState::Disabled))))
     }
+
+    fn new_flags() -> PinArr<AtomicBool> {
+        arr!(AtomicBool::new(false))
+    }
+
+    fn continuous_shim(flags: &PinArr<AtomicBool>, interrupt: AdcInterrupt) -> AdcShim<'_> {
+        let mut shim = AdcShim::manual();
+        shim.register_interrupt_flags(flags);
+        shim.set_interrupt(A0, interrupt).unwrap();
+        shim.set_state(A0, AdcState::Enabled).unwrap();
+        shim.set_mode(A0, AdcMode::Continuous).unwrap();
+        shim
+    }
+
+    #[test]
+    fn continuous_reads_latest_sample() {
+        let flags = new_flags();
+        let mut shim = continuous_shim(&flags, AdcInterrupt::Disabled);
+
+        shim.set_value(A0, 200).unwrap();
+        assert_eq!(shim.read(A0), Ok(INIT_VALUE));
+
+        assert!(shim.sample(A0));
+        assert_eq!(shim.read(A0), Ok(200));
+        assert!(!shim.interrupt_occurred(A0));
+    }
+
+    #[test]
+    fn conversion_complete() {
+        let flags = new_flags();
+        let mut shim = continuous_shim(&flags, AdcInterrupt::ConversionComplete);
+        assert!(!shim.interrupt_occurred(A0));
+
+        assert!(shim.sample(A0));
+        assert!(shim.interrupt_occurred(A0));
+
+        // One shot pins aren't sampled:
+        shim.set_mode(A0, AdcMode::OneShot).unwrap();
+        shim.reset_interrupt_flag(A0);
+        assert!(!shim.sample(A0));
+        assert!(!shim.interrupt_occurred(A0));
+
+        shim.set_mode(A0, AdcMode::Continuous).unwrap();
+        assert!(shim.sample(A0));
+        assert!(shim.interrupt_occurred(A0));
+    }
+
+    #[test]
+    fn threshold_crossing() {
+        let flags = new_flags();
+        let mut shim = continuous_shim(&flags, AdcInterrupt::ThresholdCrossing(100));
+
+        let mut sample_at = |shim: &mut AdcShim, value| {
+            shim.set_value(A0, value).unwrap();
+            shim.sample(A0);
+            let occurred = shim.interrupt_occurred(A0);
+            shim.reset_interrupt_flag(A0);
+            occurred
+        };
+
+        assert!(!sample_at(&mut shim, 99));
+        assert!(sample_at(&mut shim, 100));
+        assert!(!sample_at(&mut shim, 150));
+        assert!(sample_at(&mut shim, 10));
+        assert!(!sample_at(&mut shim, 10));
+    }
+
+    #[test]
+    fn sampled_on_a_thread() {
+        let flags = new_flags();
+        let mut shim = AdcShim::new();
+        shim.register_interrupt_flags(&flags);
+        shim.set_sample_period(AdcSamplePeriod::new(1).unwrap()).unwrap();
+        shim.set_interrupt(A0, AdcInterrupt::ConversionComplete).unwrap();
+        shim.set_state(A0, AdcState::Enabled).unwrap();
+        assert!(!shim.interrupt_occurred(A0));
+
+        shim.set_mode(A0, AdcMode::Continuous).unwrap();
+
+        // Wait (for up to a second) for the first conversion:
+        let converted = (0..1000).any(|_| {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            shim.interrupt_occurred(A0)
+        });
+        assert!(converted);
+    }
+
+    #[test]
+    fn settings_survive_state_changes() {
+        let mut shim = AdcShim::new();
+        shim.set_mode(A1, AdcMode::Continuous).unwrap();
+        shim.set_interrupt(A1, AdcInterrupt::ThresholdCrossing(7)).unwrap();
+
+        shim.set_state(A1, AdcState::Enabled).unwrap();
+        shim.set_state(A1, AdcState::Disabled).unwrap();
+
+        assert_eq!(shim.get_mode(A1), AdcMode::Continuous);
+        assert_eq!(shim.get_interrupt(A1), AdcInterrupt::ThresholdCrossing(7));
+    }
 }
@@ -2,7 +2,8 @@ use core::ops::{Index, IndexMut};
 use core::sync::atomic::{AtomicBool, Ordering};
 use lc3_traits::peripherals::gpio::GpioState::Interrupt;
 use lc3_traits::peripherals::gpio::{
-    Gpio, GpioMiscError, GpioPin, GpioPinArr, GpioReadError, GpioState, GpioWriteError,
+    Gpio, GpioMiscError, GpioPin, GpioPinArr, GpioPull, GpioReadError, GpioState, GpioTrigger,
+    GpioWriteError,
 };
 use std::sync::{Arc, RwLock};
 
@@ -32,15 +33,21 @@ impl From<State> for GpioState {
 ///
 /// Some implementation details:
 ///   - The value of a pin is set to 0 when the pin is switched to input,
-///     output, or interrupt mode.
+///     output, or interrupt mode (or to 1 when switched to input or interrupt
+///     mode with a [pull-up](GpioPull::PullUp)).
 ///   - The value of a pin can be read when the pin is in input, output, or
 ///     interrupt mode (anything but disabled).
 ///   - The value of a pin can be _set_ when the pin is in input or interrupt
 ///     mode.
 ///   - The state of a pin (input, output, interrupt, or disabled) can be
 ///     retrieved at any time.
+///   - All [interrupt triggers](GpioTrigger) and [pulls](GpioPull) are
+///     supported. Pulls take effect when a pin enters input or interrupt mode
+///     and when a pin is [released](GpioShim::release_pin).
 pub struct GpioShim<'gint> {
     states: GpioPinArr<State>,
+    triggers: GpioPinArr<GpioTrigger>,
+    pulls: GpioPinArr<GpioPull>,
     flags: Option<&'gint GpioPinArr<AtomicBool>>,
 }
 
@@ -62,6 +69,8 @@ impl Default for GpioShim<'_> {
     fn default() -> Self {
         Self {
             states: GpioPinArr([State::Disabled; GpioPin::NUM_PINS]),
+            triggers: GpioPinArr([GpioTrigger::default(); GpioPin::NUM_PINS]),
+            pulls: GpioPinArr([GpioPull::default(); GpioPin::NUM_PINS]),
             flags: None,
         }
     }
@@ -85,8 +94,17 @@ impl GpioShim<'_> {
         self[pin] = match self[pin] {
             Input(_) => Input(bit),
             Interrupt(prev) => {
-                // Rising edge!
-                if bit && !prev {
+                use GpioTrigger::*;
+
+                let fire = match self.triggers[pin] {
+                    RisingEdge => bit && !prev,
+                    FallingEdge => !bit && prev,
+                    BothEdges => bit != prev,
+                    HighLevel => bit,
+                    LowLevel => !bit,
+                };
+
+                if fire {
                     self.raise_interrupt(pin)
                 }
 
@@ -98,6 +116,37 @@ impl GpioShim<'_> {
         Some(())
     }
 
+    /// Stops driving a pin that's in input or interrupt mode; the pin then
+    /// goes to the level its [pull](GpioPull) dictates. Floating pins keep
+    /// their current value.
+    ///
+    /// Returns `Some(())` on success and `None` on failure.
+    pub fn release_pin(&mut self, pin: GpioPin) -> Option<()> {
+        match (self[pin], self.pull_level(pin)) {
+            (State::Output(_), _) | (State::Disabled, _) => None,
+            (_, Some(level)) => self.set_pin(pin, level),
+            (_, None) => Some(()),
+        }
+    }
+
+    fn pull_level(&self, pin: GpioPin) -> Option<bool> {
+        match self.pulls[pin] {
+            GpioPull::Floating => None,
+            GpioPull::PullUp => Some(true),
+            GpioPull::PullDown => Some(false),
+        }
+    }
+
+    /// Returns true if the pin is currently at the level its (level)
+    /// [trigger](GpioTrigger) fires on.
+    fn at_trigger_level(&self, pin: GpioPin) -> bool {
+        match (self[pin], self.triggers[pin]) {
+            (State::Interrupt(b), GpioTrigger::HighLevel) => b,
+            (State::Interrupt(b), GpioTrigger::LowLevel) => !b,
+            _ => false,
+        }
+    }
+
     fn raise_interrupt(&self, pin: GpioPin) {
         match self.flags {
             Some(flags) => flags[pin].store(true, Ordering::SeqCst),
@@ -127,23 +176,27 @@ impl<'a> Gpio<'a> for GpioShim<'a> {
     fn set_state(&mut self, pin: GpioPin, state: GpioState) -> Result<(), GpioMiscError> {
         use GpioState::*;
 
+        // Inputs that aren't carrying over a value start at the level their
+        // pull dictates:
+        let initial = self.pull_level(pin).unwrap_or(false);
+
         // Retain the previous value when switching between input and interrupt
         // modes and when switching to the mode we're already in:
         self[pin] = match (self[pin], state) {
             (State::Input(v), Input) |
             (State::Interrupt(v), Input) => State::Input(v),
             (State::Disabled, Input) |
-            (State::Output(_), Input) => State::Input(false),
+            (State::Output(_), Input) => State::Input(initial),
 
             (State::Output(v), Output) => State::Output(v),
             (State::Disabled, Output) |
             (State::Input(_), Output) |
             (State::Interrupt(_), Output) => State::Output(false),
 
-            (State::Input(_), Interrupt) |
-            (State::Interrupt(_), Interrupt) => State::Interrupt(false),
+            (State::Input(v), Interrupt) |
+            (State::Interrupt(v), Interrupt) => State::Interrupt(v),
             (State::Disabled, Interrupt) |
-            (State::Output(_), Interrupt) => State::Interrupt(false),
+            (State::Output(_), Interrupt) => State::Interrupt(initial),
 
             (_, Disabled) => State::Disabled,
         };
@@ -191,7 +244,9 @@ impl<'a> Gpio<'a> for GpioShim<'a> {
     fn interrupt_occurred(&self, pin: GpioPin) -> bool {
         match self.flags {
             Some(flag) => {
-                let occurred = flag[pin].load(Ordering::SeqCst);
+                // Level triggered interrupts stay pending for as long as the
+                // pin is at the level in question:
+                let occurred = flag[pin].load(Ordering::SeqCst) || self.at_trigger_level(pin);
                 self.interrupts_enabled(pin) && occurred
             }
             None => unreachable!(),
@@ -210,6 +265,24 @@ impl<'a> Gpio<'a> for GpioShim<'a> {
     fn interrupts_enabled(&self, pin: GpioPin) -> bool {
         self.get_state(pin) == Interrupt
     }
+
+    fn set_trigger(&mut self, pin: GpioPin, trigger: GpioTrigger) -> Result<(), GpioMiscError> {
+        self.triggers[pin] = trigger;
+        Ok(())
+    }
+
+    fn get_trigger(&self, pin: GpioPin) -> GpioTrigger {
+        self.triggers[pin]
+    }
+
+    fn set_pull(&mut self, pin: GpioPin, pull: GpioPull) -> Result<(), GpioMiscError> {
+        self.pulls[pin] = pull;
+        Ok(())
+    }
+
+    fn get_pull(&self, pin: GpioPin) -> GpioPull {
+        self.pulls[pin]
+    }
 }
 
 #[cfg(test)]
@@ -270,4 +343,113 @@ mod tests {
         let result = shim.write(G0, true);
         assert_eq!(result, Err(GpioWriteError((G0, gpio::GpioState::Input))));
     }
+
+    fn interrupt_shim(flags: &GpioPinArr<AtomicBool>, trigger: GpioTrigger) -> GpioShim<'_> {
+        let mut shim = GpioShim::new();
+        shim.register_interrupt_flags(flags);
+        shim.set_trigger(G0, trigger).unwrap();
+        shim.set_state(G0, gpio::GpioState::Interrupt).unwrap();
+        shim
+    }
+
+    fn new_flags() -> GpioPinArr<AtomicBool> {
+        GpioPinArr([(); GpioPin::NUM_PINS].map(|_| AtomicBool::new(false)))
+    }
+
+    #[test]
+    fn falling_edge() {
+        let flags = new_flags();
+        let mut shim = interrupt_shim(&flags, GpioTrigger::FallingEdge);
+
+        shim.set_pin(G0, true).unwrap();
+        assert!(!shim.interrupt_occurred(G0));
+
+        shim.set_pin(G0, false).unwrap();
+        assert!(shim.interrupt_occurred(G0));
+    }
+
+    #[test]
+    fn both_edges() {
+        let flags = new_flags();
+        let mut shim = interrupt_shim(&flags, GpioTrigger::BothEdges);
+
+        shim.set_pin(G0, true).unwrap();
+        assert!(shim.interrupt_occurred(G0));
+        shim.reset_interrupt_flag(G0);
+
+        shim.set_pin(G0, true).unwrap();
+        assert!(!shim.interrupt_occurred(G0));
+
+        shim.set_pin(G0, false).unwrap();
+        assert!(shim.interrupt_occurred(G0));
+    }
+
+    #[test]
+    fn high_level_stays_pending() {
+        let flags = new_flags();
+        let mut shim = interrupt_shim(&flags, GpioTrigger::HighLevel);
+        assert!(!shim.interrupt_occurred(G0));
+
+        shim.set_pin(G0, true).unwrap();
+        shim.reset_interrupt_flag(G0);
+        assert!(shim.interrupt_occurred(G0));
+
+        shim.set_pin(G0, false).unwrap();
+        shim.reset_interrupt_flag(G0);
+        assert!(!shim.interrupt_occurred(G0));
+    }
+
+    #[test]
+    fn pull_up_input() {
+        let mut shim = GpioShim::new();
+        shim.set_pull(G0, GpioPull::PullUp).unwrap();
+        shim.set_state(G0, gpio::GpioState::Input).unwrap();
+        assert_eq!(shim.read(G0), Ok(true));
+
+        shim.set_pin(G0, false).unwrap();
+        assert_eq!(shim.read(G0), Ok(false));
+
+        shim.release_pin(G0).unwrap();
+        assert_eq!(shim.read(G0), Ok(true));
+    }
+
+    #[test]
+    fn pull_down_release_fires_falling_edge() {
+        let flags = new_flags();
+        let mut shim = interrupt_shim(&flags, GpioTrigger::FallingEdge);
+        shim.set_pull(G0, GpioPull::PullDown).unwrap();
+
+        shim.set_pin(G0, true).unwrap();
+        shim.release_pin(G0).unwrap();
+        assert_eq!(shim.read(G0), Ok(false));
+        assert!(shim.interrupt_occurred(G0));
+    }
+
+    #[test]
+    fn interrupt_pins_keep_their_value() {
+        let flags = new_flags();
+        let mut shim = interrupt_shim(&flags, GpioTrigger::RisingEdge);
+        shim.set_pull(G0, GpioPull::PullDown).unwrap();
+
+        shim.set_pin(G0, true).unwrap();
+        shim.set_state(G0, gpio::GpioState::Interrupt).unwrap();
+        assert_eq!(shim.read(G0), Ok(true));
+
+        shim.set_state(G0, gpio::GpioState::Input).unwrap();
+        shim.set_state(G0, gpio::GpioState::Interrupt).unwrap();
+        assert_eq!(shim.read(G0), Ok(true));
+    }
+
+    #[test]
+    fn triggers_and_pulls_survive_state_changes() {
+        let mut shim = GpioShim::new();
+        shim.set_trigger(G3, GpioTrigger::LowLevel).unwrap();
+        shim.set_pull(G3, GpioPull::PullDown).unwrap();
+
+        shim.set_state(G3, gpio::GpioState::Output).unwrap();
+        shim.set_state(G3, gpio::GpioState::Disabled).unwrap();
+
+        assert_eq!(shim.get_trigger(G3), GpioTrigger::LowLevel);
+        assert_eq!(shim.get_pull(G3), GpioPull::PullDown);
+    }
 }
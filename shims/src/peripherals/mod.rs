@@ -27,8 +27,8 @@ use std::sync::{Arc, RwLock, Mutex};
 pub type ShareablePeripheralsShim<'int, 'io> = PeripheralSet<
     'int,
     Arc<RwLock<GpioShim<'int>>>,
-    Arc<RwLock<AdcShim>>,
-    Arc<Mutex<PwmShim>>,
+    Arc<RwLock<AdcShim<'int>>>,
+    Arc<Mutex<PwmShim<'int>>>,
     Arc<Mutex<TimersShim<'int>>>,
     Arc<RwLock<ClockShim>>,
     Arc<Mutex<InputShim<'io, 'int>>>,
@@ -46,8 +46,8 @@ sa::assert_impl_all!(ShareablePeripheralsShim<'_, '_>: Sync, Send);
 pub type PeripheralsShim<'s> = PeripheralSet<
     's,
     GpioShim<'s>,
-    AdcShim,
-    PwmShim,
+    AdcShim<'s>,
+    PwmShim<'s>,
     TimersShim<'s>,
     ClockShim,
     InputShim<'s, 's>,
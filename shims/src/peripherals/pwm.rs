@@ -1,28 +1,31 @@
 not_wasm! {
-use core::num::NonZeroU8;
 use lc3_traits::peripherals::pwm::{
-    Pwm, PwmPin, PwmPinArr, PwmState, PwmDutyCycle,
+    Pwm, PwmPin, PwmPinArr, PwmState, PwmDutyCycle, PwmPeriod, MAX_DUTY_CYCLE,
 };
 use std::sync::Arc;
-use std::time::Duration;
 use timer;
 use chrono;
 use core::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering::SeqCst;
-use std::thread::sleep;
 
-const MAX_DUTY_CYCLE: PwmDutyCycle = PwmDutyCycle::max_value();
-
-pub struct PwmShim {
+/// A simulated PWM peripheral.
+///
+/// Each enabled pin's wave is driven by a separate thread. Rollover interrupts
+/// are raised on every rising edge of the wave.
+pub struct PwmShim<'int> {
     states: PwmPinArr<PwmState>,
     duty_cycle: PwmPinArr<PwmDutyCycle>,
     rising_edge_guards: PwmPinArr<Option<timer::Guard>>,
     falling_edge_guards: PwmPinArr<Option<timer::Guard>>,
     bit_states: Arc<PwmPinArr<AtomicBool>>,
     timers: PwmPinArr<timer::Timer>,
+
+    rollover_interrupts: Arc<PwmPinArr<AtomicBool>>,
+    external_flags: Option<&'int PwmPinArr<AtomicBool>>,
+    internal_flags: Arc<PwmPinArr<AtomicBool>>,
 }
 
-impl Default for PwmShim {
+impl Default for PwmShim<'_> {
     fn default() -> Self {
         //let pins = [Arc::new(Mutex::new(false)), Arc::new(Mutex::new(false))];
 
@@ -32,12 +35,16 @@ impl Default for PwmShim {
             rising_edge_guards: PwmPinArr([None, None]),
             falling_edge_guards: PwmPinArr([None, None]),
             bit_states: Arc::new(PwmPinArr([AtomicBool::new(false), AtomicBool::new(false)])),
-            timers: PwmPinArr([timer::Timer::new(), timer::Timer::new()])
+            timers: PwmPinArr([timer::Timer::new(), timer::Timer::new()]),
+
+            rollover_interrupts: Arc::new(PwmPinArr([AtomicBool::new(false), AtomicBool::new(false)])),
+            external_flags: None,
+            internal_flags: Arc::new(PwmPinArr([AtomicBool::new(false), AtomicBool::new(false)])),
         }
     }
 }
 
-impl PwmShim {
+impl PwmShim<'_> {
     pub fn new() -> Self {
         Self::default()
     }
@@ -47,29 +54,35 @@ impl PwmShim {
         self.states[pin].into()
     }
 
-    fn start_wave(&mut self, pin: PwmPin, period: NonZeroU8) {
+    fn start_wave(&mut self, pin: PwmPin, period: PwmPeriod) {
         self.stop_wave(pin);
 
-        let period = period.get();
-        let duration = chrono::Duration::milliseconds(period as i64);
+        let period = period.as_micros();
+        let duration = chrono::Duration::microseconds(period as i64);
+        let high_time = (period as i64) * (self.duty_cycle[pin] as i64) / (MAX_DUTY_CYCLE as i64);
 
         self.bit_states[pin].store(true, SeqCst); // start with rising edge
 
-        // Schedule future rising edges
+        // Both edges are scheduled off of the same start time so the timer
+        // thread does the waiting for the falling edge, not the caller.
+        let start = chrono::Utc::now();
+
+        // Schedule future rising edges; each one starts a new period.
         let pin_clone = self.bit_states.clone();
-        let rising_edge_guard = self.timers[pin].schedule_repeating(duration, move | | {
+        let rollover_interrupts = self.rollover_interrupts.clone();
+        let flags = self.internal_flags.clone();
+        let rising_edge_guard = self.timers[pin].schedule(start + duration, Some(duration), move | | {
             pin_clone[pin].store(true, SeqCst);
-        });
-
-        // Wait to schedule falling edges
-        let high_time = (period as u64) * (self.duty_cycle[pin] as u64) / (MAX_DUTY_CYCLE as u64);
-        sleep(Duration::from_millis(high_time));
 
-        self.bit_states[pin].store(true, SeqCst); // make a falling edge
+            if rollover_interrupts[pin].load(SeqCst) {
+                flags[pin].store(true, SeqCst);
+            }
+        });
 
-        // Schedule future falling edges
+        // And the falling edges, starting with this period's:
         let pin_clone = self.bit_states.clone();
-        let falling_edge_guard = self.timers[pin].schedule_repeating(duration, move | | {
+        let first_fall = start + chrono::Duration::microseconds(high_time);
+        let falling_edge_guard = self.timers[pin].schedule(first_fall, Some(duration), move | | {
             pin_clone[pin].store(false, SeqCst);
         });
 
@@ -90,7 +103,7 @@ impl PwmShim {
 
 }
 
-impl Pwm for PwmShim {
+impl<'a> Pwm<'a> for PwmShim<'a> {
     fn set_state(&mut self, pin: PwmPin, state: PwmState)  {
         use PwmState::*;
         match state {
@@ -118,17 +131,55 @@ impl Pwm for PwmShim {
     fn get_duty_cycle(&self, pin: PwmPin) -> PwmDutyCycle {
         self.duty_cycle[pin]
     }
+
+    fn set_rollover_interrupt(&mut self, pin: PwmPin, enabled: bool) {
+        self.rollover_interrupts[pin].store(enabled, SeqCst);
+
+        // Don't leave a stale rollover around for when interrupts are turned
+        // back on:
+        if !enabled {
+            self.internal_flags[pin].store(false, SeqCst);
+        }
+    }
+
+    fn get_rollover_interrupt(&self, pin: PwmPin) -> bool {
+        self.rollover_interrupts[pin].load(SeqCst)
+    }
+
+    fn register_interrupt_flags(&mut self, flags: &'a PwmPinArr<AtomicBool>) {
+        self.external_flags = match self.external_flags {
+            None => Some(flags),
+            Some(_) => {
+                // warn!("re-registering interrupt flags!");
+                Some(flags)
+            }
+        }
+    }
+
+    // As with the timers, the external flags are updated whenever we're polled.
+    fn interrupt_occurred(&self, pin: PwmPin) -> bool {
+        let occurred = self.internal_flags[pin].load(SeqCst);
+        self.external_flags.unwrap()[pin].store(occurred, SeqCst);
+
+        self.interrupts_enabled(pin) && occurred
+    }
+
+    fn reset_interrupt_flag(&mut self, pin: PwmPin) {
+        self.external_flags.unwrap()[pin].store(false, SeqCst);
+        self.internal_flags[pin].store(false, SeqCst);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use std::{thread, sync::Mutex};
+    use std::{thread, sync::Mutex, time::Duration};
 
     use lc3_traits::peripherals::pwm::{self, Pwm, PwmPin::*, PwmState};
 
-    const MAX_PERIOD: u8 = u8::max_value();
+    const MAX_PERIOD: u32 = u16::max_value() as u32;
+    const PERIOD_MS: u16 = 60;
 
     use lc3_test_infrastructure::assert_eq;
     #[test]
@@ -143,15 +194,15 @@ mod tests {
     #[test]
     fn get_enabled() {
         let mut shim = PwmShim::new();
-        shim.set_state(P0, pwm::PwmState::Enabled(NonZeroU8::new(MAX_PERIOD).unwrap()));
+        shim.set_state(P0, pwm::PwmState::Enabled(PwmPeriod::from_micros(MAX_PERIOD).unwrap()));
         let val = shim.get_state(P0);
-        assert_eq!(val, pwm::PwmState::Enabled((NonZeroU8::new(MAX_PERIOD)).unwrap()));
+        assert_eq!(val, pwm::PwmState::Enabled((PwmPeriod::from_micros(MAX_PERIOD)).unwrap()));
     }
 
     #[test]
     fn get_duty() {
         let mut shim = PwmShim::new();
-        shim.set_state(P0, pwm::PwmState::Enabled(NonZeroU8::new(MAX_PERIOD).unwrap()));
+        shim.set_state(P0, pwm::PwmState::Enabled(PwmPeriod::from_micros(MAX_PERIOD).unwrap()));
         shim.set_duty_cycle(P0, 100);
         assert_eq!(shim.get_duty_cycle(P0), 100);
         shim.set_state(P0, pwm::PwmState::Disabled);
@@ -160,7 +211,7 @@ mod tests {
     #[test]
     fn get_pin_initial() {
         let mut shim = PwmShim::new();
-        shim.set_state(P0, pwm::PwmState::Enabled(NonZeroU8::new(MAX_PERIOD).unwrap()));
+        shim.set_state(P0, pwm::PwmState::Enabled(PwmPeriod::from_micros(MAX_PERIOD).unwrap()));
 
         let b = shim.get_pin(P0);
         assert_eq!(b, true);
@@ -169,7 +220,7 @@ mod tests {
     #[test]
     fn get_pin_on() {
         let mut shim = PwmShim::new();
-        shim.set_state(P0, pwm::PwmState::Enabled(NonZeroU8::new(MAX_PERIOD).unwrap()));
+        shim.set_state(P0, pwm::PwmState::Enabled(PwmPeriod::from_micros(MAX_PERIOD).unwrap()));
 
         shim.set_duty_cycle(P0, MAX_DUTY_CYCLE); // should always be on
         thread::sleep(Duration::from_millis(10));
@@ -180,8 +231,8 @@ mod tests {
     #[test]
     fn start_pwm() {
         let mut shim = PwmShim::new();
-        shim.set_state(P0, pwm::PwmState::Enabled(NonZeroU8::new(255).unwrap()));
-        shim.set_duty_cycle(P0, 100); // this starts pwm
+        shim.set_state(P0, pwm::PwmState::Enabled(PwmPeriod::from_micros(MAX_PERIOD).unwrap()));
+        shim.set_duty_cycle(P0, MAX_DUTY_CYCLE / 4 * 3); // this starts pwm
 
         let b = shim.get_pin(P0);
         thread::sleep(Duration::from_millis(100));
@@ -194,7 +245,7 @@ mod tests {
     fn p0_toggle_once_check() {
         let mut shim = PwmShim::new();
 
-        shim.set_state(P0, pwm::PwmState::Enabled((NonZeroU8::new(MAX_PERIOD)).unwrap()));
+        shim.set_state(P0, pwm::PwmState::Enabled((PwmPeriod::from_micros(MAX_PERIOD)).unwrap()));
 
         shim.set_duty_cycle(P0, MAX_DUTY_CYCLE / 2);
         let pin_state = shim.get_pin(P0);
@@ -204,7 +255,7 @@ mod tests {
             if shim.get_pin(P0) != pin_state {
                 toggle_flag = 1;
             }
-            thread::sleep(Duration::from_millis((MAX_DUTY_CYCLE/2) as u64));
+            thread::sleep(Duration::from_micros((MAX_PERIOD/2) as u64));
         }
         assert_eq!(toggle_flag,  1);
         shim.set_state(P0, pwm::PwmState::Disabled);
@@ -215,7 +266,7 @@ mod tests {
     fn p1_toggle_once_check() {
         let mut shim = PwmShim::new();
 
-        shim.set_state(P1, pwm::PwmState::Enabled((NonZeroU8::new(MAX_PERIOD)).unwrap()));
+        shim.set_state(P1, pwm::PwmState::Enabled((PwmPeriod::from_micros(MAX_PERIOD)).unwrap()));
 
         shim.set_duty_cycle(P1, MAX_DUTY_CYCLE / 2);
         let pin_state = shim.get_pin(P1);
@@ -226,7 +277,7 @@ mod tests {
             if shim.get_pin(P1) != pin_state {
                 toggle_flag = 1;
             }
-            thread::sleep(Duration::from_millis((MAX_DUTY_CYCLE/2) as u64));
+            thread::sleep(Duration::from_micros((MAX_PERIOD/2) as u64));
         }
         assert_eq!(toggle_flag,  1);
 
@@ -239,17 +290,17 @@ mod tests {
 
         let mut shim = PwmShim::new();
 
-        shim.set_state(P0, pwm::PwmState::Enabled((NonZeroU8::new(MAX_DUTY_CYCLE)).unwrap()));
+        shim.set_state(P0, pwm::PwmState::Enabled((PwmPeriod::from_millis(PERIOD_MS)).unwrap()));
 
         let num_cycles = 5;
-        let duty_cycle_ratio: u8 = 5;
+        let duty_cycle_ratio: u16 = 5;
 
 
 
         let mut p0_count = 0;
 
-        let oncycle = (MAX_DUTY_CYCLE/duty_cycle_ratio) as u64;
-        let offcycle = (MAX_DUTY_CYCLE - MAX_DUTY_CYCLE/duty_cycle_ratio) as u64;
+        let oncycle = (PERIOD_MS/duty_cycle_ratio) as u64;
+        let offcycle = (PERIOD_MS - PERIOD_MS/duty_cycle_ratio) as u64;
 
         shim.set_duty_cycle(P0, MAX_DUTY_CYCLE/duty_cycle_ratio);
         thread::sleep(Duration::from_millis(1 as u64));  // give wiggle room
@@ -257,7 +308,7 @@ mod tests {
         let actual_cycles = Arc::new(Mutex::new(0));
 
         let timer = timer::Timer::new();
-        let duration = chrono::Duration::milliseconds(MAX_DUTY_CYCLE as i64);
+        let duration = chrono::Duration::milliseconds(PERIOD_MS as i64);
 
 
 
@@ -305,14 +356,14 @@ mod tests {
 
         let mut shim = PwmShim::new();
 
-        shim.set_state(P1, pwm::PwmState::Enabled((NonZeroU8::new(MAX_DUTY_CYCLE)).unwrap()));
+        shim.set_state(P1, pwm::PwmState::Enabled((PwmPeriod::from_millis(PERIOD_MS)).unwrap()));
 
         let num_cycles = 5;
-        let duty_cycle_ratio: u8 = 5;
+        let duty_cycle_ratio: u16 = 5;
         let mut p1_count = 0;
 
-        let oncycle = (MAX_DUTY_CYCLE/duty_cycle_ratio) as u64;
-        let offcycle = (MAX_DUTY_CYCLE - MAX_DUTY_CYCLE/duty_cycle_ratio) as u64;
+        let oncycle = (PERIOD_MS/duty_cycle_ratio) as u64;
+        let offcycle = (PERIOD_MS - PERIOD_MS/duty_cycle_ratio) as u64;
 
         shim.set_duty_cycle(P1, MAX_DUTY_CYCLE/duty_cycle_ratio);
         thread::sleep(Duration::from_millis(1 as u64));  // give wiggle room
@@ -320,7 +371,7 @@ mod tests {
         let actual_cycles = Arc::new(Mutex::new(0));
 
         let timer = timer::Timer::new();
-        let duration = chrono::Duration::milliseconds(MAX_DUTY_CYCLE as i64);
+        let duration = chrono::Duration::milliseconds(PERIOD_MS as i64);
 
 
 
@@ -362,23 +413,46 @@ mod tests {
 
 
     }
+    #[test]
+    fn rollover_interrupt() {
+        let flags = PwmPinArr([AtomicBool::new(false), AtomicBool::new(false)]);
+        let mut shim = PwmShim::new();
+        shim.register_interrupt_flags(&flags);
+
+        shim.set_rollover_interrupt(P0, true);
+        shim.set_duty_cycle(P0, MAX_DUTY_CYCLE / 2);
+        shim.set_state(P0, PwmState::Enabled(PwmPeriod::from_micros(1000).unwrap()));
+        assert_eq!(shim.interrupts_enabled(P0), true);
+
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(shim.interrupt_occurred(P0), true);
+        assert_eq!(flags[P0].load(SeqCst), true);
+
+        shim.reset_interrupt_flag(P0);
+        shim.set_rollover_interrupt(P0, false);
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(shim.interrupt_occurred(P0), false);
+
+        shim.set_state(P0, PwmState::Disabled);
+    }
+
         // #[test]
         // fn test_multi_duty_cycle() {
 
         //     let mut shim = PwmShim::new();
 
-        //     shim.set_state(P0, pwm::PwmState::Enabled((NonZeroU8::new(MAX_DUTY_CYCLE)).unwrap()));
-        //     shim.set_state(P1, pwm::PwmState::Enabled((NonZeroU8::new(MAX_DUTY_CYCLE)).unwrap()));
+        //     shim.set_state(P0, pwm::PwmState::Enabled((PwmPeriod::from_millis(PERIOD_MS)).unwrap()));
+        //     shim.set_state(P1, pwm::PwmState::Enabled((PwmPeriod::from_millis(PERIOD_MS)).unwrap()));
 
         //     let num_cycles = 5;
-        //     let duty_cycle_ratio: u8 = 5;
+        //     let duty_cycle_ratio: u16 = 5;
 
 
 
         //     let mut p0_count = 0;
         //     let mut p1_count = 0;
-        //     let oncycle = (MAX_DUTY_CYCLE/duty_cycle_ratio) as u64;
-        //     let offcycle = (MAX_DUTY_CYCLE - MAX_DUTY_CYCLE/duty_cycle_ratio) as u64;
+        //     let oncycle = (PERIOD_MS/duty_cycle_ratio) as u64;
+        //     let offcycle = (PERIOD_MS - PERIOD_MS/duty_cycle_ratio) as u64;
         //     shim.set_duty_cycle(P0, MAX_DUTY_CYCLE/duty_cycle_ratio);
         //     shim.set_duty_cycle(P1, MAX_DUTY_CYCLE/duty_cycle_ratio);
 
@@ -389,7 +463,7 @@ mod tests {
         //     let mut actual_cycles = Arc::new(Mutex::new(0));
 
         //     let timer = timer::Timer::new();
-        //     let duration = chrono::Duration::milliseconds(MAX_DUTY_CYCLE as i64);
+        //     let duration = chrono::Duration::milliseconds(PERIOD_MS as i64);
 
 
 
@@ -447,5 +521,5 @@ mod tests {
 }
 
 wasm! {
-    pub use lc3_traits::peripherals::stubs::PwmStub as PwmShim;
+    pub type PwmShim<'p> = lc3_traits::peripherals::stubs::PwmStub;
 }
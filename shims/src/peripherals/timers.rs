@@ -1,8 +1,9 @@
 not_wasm!{
 use lc3_traits::peripherals::timers::{
-    Timers, TimerArr, TimerId, TimerMode, TimerState, Period, TIMERS
+    Timers, TimerArr, TimerId, TimerMode, TimerState, Period, CaptureSource, TIMERS
 };
 use lc3_traits::control::Snapshot;
+use lc3_isa::Word;
 
 use timer;
 
@@ -21,9 +22,13 @@ pub struct TimersShim<'tint> {
     timers: TimerArr<timer::Timer>,
 
     start_times: TimerArr<Option<Instant>>,
+
+    capture_sources: TimerArr<Option<CaptureSource>>,
+    capture_levels: TimerArr<Option<bool>>,
+    captures: TimerArr<Word>,
 }
 
-macro_rules! arr { ($v:expr) => { TimerArr([$v, $v]) }; }
+macro_rules! arr { ($v:expr) => { TimerArr([$v, $v, $v, $v]) }; }
 
 impl Default for TimersShim<'_> {
     fn default() -> Self {
@@ -38,6 +43,10 @@ impl Default for TimersShim<'_> {
             timers: arr!(timer::Timer::new()),
 
             start_times: arr!(None),
+
+            capture_sources: arr!(None),
+            capture_levels: arr!(None),
+            captures: arr!(0),
         }
     }
 }
@@ -63,6 +72,9 @@ impl TimersShim<'_> {
                     flags[timer].store(true, Ordering::SeqCst)
                 })
             },
+            // Free running timers never fire; their count is derived from the
+            // start time alone.
+            FreeRunning => return,
             SingleShot => {
                 let states = self.states.clone();
 
@@ -113,6 +125,48 @@ impl<'a> Timers<'a> for TimersShim<'a> {
         *self.states[timer].lock().unwrap()
     }
 
+    fn get_count(&self, timer: TimerId) -> Word {
+        use TimerMode::*;
+
+        let period = match self.get_state(timer) {
+            TimerState::Disabled => return 0,
+            TimerState::WithPeriod(period) => period.get() as u128,
+        };
+
+        let elapsed = self.start_times[timer]
+            .map(|start| start.elapsed().as_millis())
+            .unwrap_or(0);
+
+        (match self.get_mode(timer) {
+            SingleShot => elapsed.min(period),
+            Repeated | FreeRunning => elapsed % period,
+        }) as Word
+    }
+
+    fn set_capture_source(&mut self, timer: TimerId, source: Option<CaptureSource>) {
+        self.capture_sources[timer] = source;
+        self.capture_levels[timer] = None;
+        self.captures[timer] = 0;
+    }
+
+    fn get_capture_source(&self, timer: TimerId) -> Option<CaptureSource> {
+        self.capture_sources[timer]
+    }
+
+    fn capture_input(&mut self, timer: TimerId, level: bool) {
+        if let (Some(source), Some(previous)) = (self.capture_sources[timer], self.capture_levels[timer]) {
+            if source.edge.matches(previous, level) {
+                self.captures[timer] = self.get_count(timer);
+            }
+        }
+
+        self.capture_levels[timer] = Some(level);
+    }
+
+    fn get_capture(&self, timer: TimerId) -> Word {
+        self.captures[timer]
+    }
+
     fn register_interrupt_flags(&mut self, flags: &'a TimerArr<AtomicBool>) {
         self.external_flags = match self.external_flags {
             None => Some(flags),
@@ -151,6 +205,10 @@ pub struct TimersSnapshot {
     flags: TimerArr<bool>,
     start_times: TimerArr<Option<Instant>>,
     snapshot_time: Instant,
+
+    capture_sources: TimerArr<Option<CaptureSource>>,
+    capture_levels: TimerArr<Option<bool>>,
+    captures: TimerArr<Word>,
 }
 
 impl<'a> Snapshot for TimersShim<'a> {
@@ -158,19 +216,25 @@ impl<'a> Snapshot for TimersShim<'a> {
     type Err = core::convert::Infallible;
 
     fn record(&self) -> Result<Self::Snap, Self::Err> {
+        let mut states = arr!(TimerState::Disabled);
+        let mut flags = arr!(false);
+
+        TIMERS.iter().for_each(|t| {
+            states[*t] = *self.states[*t].lock().unwrap();
+            flags[*t] = self.internal_flags[*t].load(Ordering::SeqCst);
+        });
+
         Ok(TimersSnapshot {
-            states: TimerArr([
-                *self.states[TimerId::T0].lock().unwrap(),
-                *self.states[TimerId::T1].lock().unwrap(),
-            ]),
+            states,
             modes: self.modes.clone(),
 
-            flags: TimerArr([
-                self.internal_flags[TimerId::T0].load(Ordering::SeqCst),
-                self.internal_flags[TimerId::T1].load(Ordering::SeqCst),
-            ]),
+            flags,
             start_times: self.start_times.clone(),
             snapshot_time: Instant::now(),
+
+            capture_sources: self.capture_sources.clone(),
+            capture_levels: self.capture_levels.clone(),
+            captures: self.captures.clone(),
         })
     }
 
@@ -188,6 +252,10 @@ impl<'a> Snapshot for TimersShim<'a> {
 
         self.start_times = snap.start_times;
 
+        self.capture_sources = snap.capture_sources;
+        self.capture_levels = snap.capture_levels;
+        self.captures = snap.captures;
+
         for t in TIMERS.iter() {
             self.internal_flags[*t].store(snap.flags[*t], Ordering::SeqCst);
             self.external_flags.unwrap()[*t].store(snap.flags[*t], Ordering::SeqCst);
@@ -258,6 +326,16 @@ impl<'a> Snapshot for TimersShim<'a> {
                     self.start_times[*t] = Some(Instant::now() - elapsed);
                 },
 
+                // Free running timers don't have anything scheduled; we just
+                // need to carry over the count they had reached.
+                (WithPeriod(_), FreeRunning) => {
+                    let start_time = self.start_times[*t]
+                        .expect("running timers should have a start time");
+                    let elapsed = snap.snapshot_time.duration_since(start_time);
+
+                    self.start_times[*t] = Some(Instant::now() - elapsed);
+                },
+
                 (Disabled, _) => {},
             }
         }
@@ -501,6 +579,81 @@ mod tests {
 
         assert_eq!(count, 5);
     }
+
+    // The timer was started somewhere in `started`; checks `count` (read in
+    // `read`) against the time that actually passed rather than how long the
+    // test meant to sleep for, which the scheduler doesn't promise.
+    fn assert_count_fits(started: (Instant, Instant), read: impl FnOnce() -> Word, period: u16) -> Word {
+        let (before, after) = started;
+
+        let min = after.elapsed().as_millis();
+        let count = read();
+        let max = before.elapsed().as_millis();
+
+        assert!(
+            (min..=max).any(|ms| ms % period as u128 == count as u128),
+            "count of {} doesn't fit {}..={} ms passing (with a period of {})", count, min, max, period,
+        );
+
+        count
+    }
+
+    #[test]
+    fn free_running_count() {
+        let mut shim = shim!();
+
+        shim.set_mode(T3, FreeRunning);
+        let before = Instant::now();
+        shim.set_state(T3, p!(100));
+        let started = (before, Instant::now());
+        assert_eq!(shim.get_mode(T3), FreeRunning);
+        assert_eq!(shim.interrupts_enabled(T3), false);
+
+        sleep(Duration::from_millis(30));
+        assert_count_fits(started, || shim.get_count(T3), 100);
+
+        // The count wraps at the period and never raises an interrupt:
+        sleep(Duration::from_millis(100));
+        assert_count_fits(started, || shim.get_count(T3), 100);
+        assert_eq!(shim.interrupt_occurred(T3), false);
+
+        shim.set_state(T3, Disabled);
+        assert_eq!(shim.get_count(T3), 0);
+    }
+
+    #[test]
+    fn input_capture() {
+        use lc3_traits::peripherals::{gpio::GpioPin::G2, timers::{CaptureEdge, CaptureSource}};
+        let mut shim = shim!();
+
+        shim.set_mode(T2, FreeRunning);
+        let before = Instant::now();
+        shim.set_state(T2, p!(u16::MAX));
+        let started = (before, Instant::now());
+        shim.set_capture_source(T2, Some(CaptureSource { pin: G2, edge: CaptureEdge::Falling }));
+
+        // The first level seen is never an edge:
+        shim.capture_input(T2, true);
+        sleep(Duration::from_millis(40));
+        assert_eq!(shim.get_capture(T2), 0);
+
+        // A falling edge latches the count:
+        let captured = assert_count_fits(started, || {
+            shim.capture_input(T2, false);
+            shim.get_capture(T2)
+        }, u16::MAX);
+        assert!(captured >= 40);
+
+        // Rising edges are ignored:
+        sleep(Duration::from_millis(20));
+        shim.capture_input(T2, true);
+        assert_eq!(shim.get_capture(T2), captured);
+
+        // Changing the source clears the latched count:
+        shim.set_capture_source(T2, None);
+        shim.capture_input(T2, false);
+        assert_eq!(shim.get_capture(T2), 0);
+    }
 }
 }
 
@@ -0,0 +1,342 @@
+//! Differential testing: running randomly generated programs on two [`Control`]
+//! implementations in lockstep and reporting the first step at which they
+//! disagree.
+//!
+//! Generated programs stick to the instructions that behave the same way on
+//! a bare machine (no OS, no devices): loads and stores are confined to the
+//! program's own data and branches and calls only go to instructions within
+//! the program. Every program ends by spinning in place so running for longer
+//! than the program is fine.
+//!
+//! When the two machines disagree, the program is minimized (instructions are
+//! replaced with `NOP`s and register and data values are zeroed for as long as
+//! the machines still disagree) and the resulting [`Divergence`] shows the
+//! reproducer in the form [`program!`](lc3_isa::program) takes.
+//!
+//! Note that lc3tools can't be stepped through from here (its bindings only
+//! run whole programs) so comparisons against it still go through
+//! [`lc3tools_tester`](crate::lc3tools::lc3tools_tester).
+
+use lc3_isa::{Addr, Instruction, Reg, SignedWord, Word, USER_PROGRAM_START_ADDR};
+use lc3_traits::control::Control;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+
+/// Knobs for [`Program::generate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenConfig {
+    /// How many random instructions to generate (at most 200).
+    pub num_insns: usize,
+    /// How many words of data loads and stores can use (at most 32).
+    pub data_words: usize,
+    /// Whether to generate branches and subroutine calls.
+    pub control_flow: bool,
+}
+
+impl Default for GenConfig {
+    fn default() -> Self {
+        Self { num_insns: 50, data_words: 16, control_flow: true }
+    }
+}
+
+// The number of pointers (into the data) available to LDI and STI.
+const NUM_POINTERS: usize = 4;
+
+// Loads and stores go through this register, which points at the data.
+const DATA_REG: Reg = Reg::R5;
+
+const NOP: Word = 0;
+
+/// A generated program.
+///
+/// The first instruction points [`R5`](Reg::R5) at the data and the last
+/// instruction spins in place; the data is made up of a few pointers (into
+/// the data, for LDI and STI) followed by the data that loads and stores use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    pub orig: Addr,
+    pub code: Vec<Word>,
+    pub pointers: Vec<Word>,
+    pub data: Vec<Word>,
+    /// Register values to start with.
+    pub regs: [Word; Reg::NUM_REGS],
+}
+
+impl Program {
+    pub fn generate(config: &GenConfig, rng: &mut impl Rng) -> Self {
+        assert!(config.num_insns <= 200, "programs must stay within PC offset range of their data");
+        assert!(config.data_words <= 32, "data must stay within base + offset range");
+
+        let orig = USER_PROGRAM_START_ADDR;
+        let code_len = config.num_insns + 2;
+        let pointers_start = orig + code_len as Addr;
+        let data_start = pointers_start + NUM_POINTERS as Addr;
+        let data_words = config.data_words.max(1);
+
+        let offset = |from: Addr, to: Addr| (to as SignedWord).wrapping_sub(from as SignedWord + 1);
+        let data_addr = |rng: &mut dyn rand::RngCore| data_start + rng.gen_range(0..data_words) as Addr;
+
+        let dests = [Reg::R0, Reg::R1, Reg::R2, Reg::R3, Reg::R4, Reg::R6, Reg::R7];
+
+        let mut code = Vec::with_capacity(code_len);
+        code.push(Instruction::new_lea(DATA_REG, offset(orig, data_start)).into());
+
+        for idx in 1..=config.num_insns {
+            let addr = orig + idx as Addr;
+
+            let dr = dests[rng.gen_range(0..dests.len())];
+            let sr1 = Reg::REGS[rng.gen_range(0..Reg::NUM_REGS)];
+            let sr2 = Reg::REGS[rng.gen_range(0..Reg::NUM_REGS)];
+            let imm5 = rng.gen_range(-16..=15);
+
+            let num_kinds = if config.control_flow { 14 } else { 12 };
+            let insn = match rng.gen_range(0..num_kinds) {
+                0 => Instruction::new_add_reg(dr, sr1, sr2),
+                1 => Instruction::new_add_imm(dr, sr1, imm5),
+                2 => Instruction::new_and_reg(dr, sr1, sr2),
+                3 => Instruction::new_and_imm(dr, sr1, imm5),
+                4 => Instruction::new_not(dr, sr1),
+                5 => Instruction::new_lea(dr, offset(addr, orig + rng.gen_range(0..code_len) as Addr)),
+                6 => Instruction::new_ld(dr, offset(addr, rng.gen_range(pointers_start..(data_start + data_words as Addr)))),
+                7 => Instruction::new_st(sr1, offset(addr, data_addr(rng))),
+                8 => Instruction::new_ldr(dr, DATA_REG, rng.gen_range(0..data_words) as SignedWord),
+                9 => Instruction::new_str(sr1, DATA_REG, rng.gen_range(0..data_words) as SignedWord),
+                10 => Instruction::new_ldi(dr, offset(addr, pointers_start + rng.gen_range(0..NUM_POINTERS) as Addr)),
+                11 => Instruction::new_sti(sr1, offset(addr, pointers_start + rng.gen_range(0..NUM_POINTERS) as Addr)),
+                // Branches and calls go anywhere after the first instruction:
+                12 => {
+                    let (n, z, p) = loop {
+                        let (n, z, p) = (rng.gen(), rng.gen(), rng.gen());
+                        if n || z || p { break (n, z, p) }
+                    };
+
+                    Instruction::new_br(n, z, p, offset(addr, orig + rng.gen_range(1..code_len) as Addr))
+                }
+                13 => Instruction::new_jsr(offset(addr, orig + rng.gen_range(1..code_len) as Addr)),
+                _ => unreachable!(),
+            };
+
+            code.push(insn.into());
+        }
+
+        code.push(Instruction::new_br(true, true, true, -1).into());
+
+        let pointers = (0..NUM_POINTERS).map(|_| data_addr(rng)).collect();
+        let data = (0..data_words).map(|_| rng.gen()).collect();
+
+        let mut regs = [0; Reg::NUM_REGS];
+        regs.iter_mut().for_each(|r| *r = rng.gen());
+
+        Self { orig, code, pointers, data, regs }
+    }
+
+    pub fn from_seed(config: &GenConfig, seed: u64) -> Self {
+        Self::generate(config, &mut StdRng::seed_from_u64(seed))
+    }
+
+    /// The address of the first word of data (that [`R5`](Reg::R5) points
+    /// to).
+    pub fn data_start(&self) -> Addr {
+        self.orig + (self.code.len() + self.pointers.len()) as Addr
+    }
+
+    fn words(&self) -> impl Iterator<Item = (Addr, Word)> + '_ {
+        self.code.iter()
+            .chain(self.pointers.iter())
+            .chain(self.data.iter())
+            .enumerate()
+            .map(move |(offset, w)| (self.orig + offset as Addr, *w))
+    }
+
+    /// Resets the machine and loads the program (and its register values)
+    /// into it.
+    pub fn load<C: Control>(&self, machine: &mut C) {
+        machine.reset();
+
+        self.words().for_each(|(addr, word)| machine.write_word(addr, word));
+        Reg::REGS.iter().zip(self.regs.iter()).for_each(|(r, w)| machine.set_register(*r, *w));
+        machine.set_pc(self.orig);
+    }
+}
+
+/// Lists the program as something [`program!`](lc3_isa::program) accepts,
+/// preceded by the register values it starts with.
+impl Display for Program {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (r, w) in Reg::REGS.iter().zip(self.regs.iter()) {
+            writeln!(fmt, "// {}: {:#06X}", r, w)?;
+        }
+
+        writeln!(fmt, ".ORIG #{:#06X};", self.orig)?;
+        for (addr, word) in self.words() {
+            match Instruction::try_from(word) {
+                _ if addr >= self.orig + self.code.len() as Addr => {
+                    writeln!(fmt, ".FILL #{:#06X}; // {:#06X}", word, addr)
+                }
+                Ok(_) if word == NOP => writeln!(fmt, "NOP; // {:#06X}", addr),
+                Ok(insn) => writeln!(fmt, "{}; // {:#06X}", insn, addr),
+                Err(_) => writeln!(fmt, ".FILL #{:#06X}; // {:#06X}", word, addr),
+            }?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The parts of a machine's state that are compared at every step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub pc: Addr,
+    pub psr: Word,
+    pub regs: [Word; Reg::NUM_REGS],
+    /// The program's data.
+    pub data: Vec<Word>,
+}
+
+impl Snapshot {
+    pub fn take<C: Control>(machine: &C, program: &Program) -> Self {
+        let (regs, psr, pc) = machine.get_registers_psr_and_pc();
+
+        let mut data = vec![0; program.data.len()];
+        machine.read_words(program.data_start(), &mut data);
+
+        Self { pc, psr, regs, data }
+    }
+}
+
+/// Where two machines running the same program first disagreed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// How many steps the machines had taken.
+    pub step: usize,
+    pub left: Snapshot,
+    pub right: Snapshot,
+    pub program: Program,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (l, r) = (&self.left, &self.right);
+        writeln!(fmt, "Machines diverged after {} steps:", self.step)?;
+
+        macro_rules! diff {
+            ($name:expr, $l:expr, $r:expr) => {
+                if $l != $r {
+                    writeln!(fmt, "  {}: {:#06X} vs {:#06X}", $name, $l, $r)?;
+                }
+            };
+        }
+
+        diff!("PC", l.pc, r.pc);
+        diff!("PSR", l.psr, r.psr);
+        for (idx, reg) in Reg::REGS.iter().enumerate() {
+            diff!(reg, l.regs[idx], r.regs[idx]);
+        }
+        for (idx, (lw, rw)) in l.data.iter().zip(r.data.iter()).enumerate() {
+            diff!(format!("{:#06X}", self.program.data_start() + idx as Addr), lw, rw);
+        }
+
+        writeln!(fmt, "\nReproducer:\n{}", self.program)
+    }
+}
+
+/// Steps two machines that have had `program` loaded into them until they
+/// disagree or `max_steps` steps have been taken.
+pub fn run_loaded_in_lockstep<L: Control, R: Control>(
+    left: &mut L,
+    right: &mut R,
+    program: &Program,
+    max_steps: usize,
+) -> Result<(), Divergence> {
+    for step in 0..=max_steps {
+        if step != 0 {
+            let _ = left.step();
+            let _ = right.step();
+        }
+
+        let (l, r) = (Snapshot::take(left, program), Snapshot::take(right, program));
+        if l != r {
+            return Err(Divergence { step, left: l, right: r, program: program.clone() });
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads `program` into both machines and then runs them in lockstep; see
+/// [`run_loaded_in_lockstep`].
+pub fn run_in_lockstep<L: Control, R: Control>(
+    left: &mut L,
+    right: &mut R,
+    program: &Program,
+    max_steps: usize,
+) -> Result<(), Divergence> {
+    program.load(left);
+    program.load(right);
+
+    run_loaded_in_lockstep(left, right, program, max_steps)
+}
+
+/// Simplifies `program` for as long as `still_fails` says the simplified
+/// program still shows the problem.
+///
+/// Code (other than the first and last instructions) is replaced with `NOP`s
+/// and register values and data are zeroed, one at a time; the program's
+/// layout is left as is so the remaining instructions keep their meaning.
+pub fn minimize(program: &Program, mut still_fails: impl FnMut(&Program) -> bool) -> Program {
+    let mut program = program.clone();
+
+    loop {
+        let mut changed = false;
+        let mut attempt = |program: &mut Program, get: fn(&mut Program) -> &mut [Word], idx: usize, simpler: Word| {
+            let old = get(program)[idx];
+            if old == simpler { return }
+
+            get(program)[idx] = simpler;
+            if still_fails(program) {
+                changed = true;
+            } else {
+                get(program)[idx] = old;
+            }
+        };
+
+        for idx in 1..(program.code.len() - 1) {
+            attempt(&mut program, |p| &mut p.code, idx, NOP);
+        }
+        for idx in 0..Reg::NUM_REGS {
+            attempt(&mut program, |p| &mut p.regs, idx, 0);
+        }
+        for idx in 0..program.data.len() {
+            attempt(&mut program, |p| &mut p.data, idx, 0);
+        }
+
+        if !changed { return program }
+    }
+}
+
+/// Runs `num_programs` programs (generated from `seed` onwards) on both
+/// machines, returning the minimized [`Divergence`] for the first program
+/// they disagree on.
+pub fn differential_test<L: Control, R: Control>(
+    left: &mut L,
+    right: &mut R,
+    config: &GenConfig,
+    seed: u64,
+    num_programs: usize,
+    max_steps: usize,
+) -> Result<(), Divergence> {
+    for seed in seed..(seed + num_programs as u64) {
+        let program = Program::from_seed(config, seed);
+
+        if run_in_lockstep(left, right, &program, max_steps).is_err() {
+            let program = minimize(&program, |p| run_in_lockstep(left, right, p, max_steps).is_err());
+            return run_in_lockstep(left, right, &program, max_steps);
+        }
+    }
+
+    Ok(())
+}
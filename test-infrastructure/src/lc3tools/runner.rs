@@ -8,6 +8,7 @@ use crate::{
     InstructionInterpreter, Addr, Word, Reg,
 };
 
+use lc3_baseline_sim::block::BlockInterpreter;
 use lc3_baseline_sim::interp::InterpreterBuilder;
 use lc3_traits::memory::Memory;
 use lc3_traits::peripherals::Peripherals;
@@ -175,7 +176,24 @@ pub fn lc3tools_tester<'flags, M: Memory + Default + Clone, P: Peripherals<'flag
         }
     }
 
-    // Run the program on the UTP simulator:
+    // Run the program on the UTP simulator; both the regular interpreter and
+    // the block interpreter should match lc3tools:
+    check_utp_interpreter::<Interpreter<'flags, M, P>, M, P>(&insns, flags, alt_memory.clone(), &regs, &memory);
+    check_utp_interpreter::<BlockInterpreter<'flags, M, P>, M, P>(&insns, flags, alt_memory, &regs, &memory);
+
+    Ok(())
+}
+
+fn check_utp_interpreter<'flags, I, M: Memory + Default + Clone, P: Peripherals<'flags>>(
+    insns: &[Instruction],
+    flags: &'flags PeripheralInterruptFlags,
+    alt_memory: Option<(M, Addr)>,
+    regs: &[Option<Word>; Reg::NUM_REGS],
+    memory: &[MemEntry],
+)
+where
+    I: InstructionInterpreter + From<Interpreter<'flags, M, P>>,
+{
     let mut addr = 0x3000;
 
     let mut interp: Interpreter<'flags, M, P> = if let Some((mem, addr)) = alt_memory {
@@ -199,12 +217,14 @@ pub fn lc3tools_tester<'flags, M: Memory + Default + Clone, P: Peripherals<'flag
 
     interp.init(flags);
 
+    let mut interp: I = interp.into();
+
     // TODO: we assume that there are no branches or control flow instructions!
     // This is very limiting!
     let steps = insns.len();
 
     for insn in insns {
-        interp.set_word_unchecked(addr, insn.into());
+        interp.set_word_unchecked(addr, (*insn).into());
         addr += 1;
     }
 
@@ -242,5 +262,4 @@ pub fn lc3tools_tester<'flags, M: Memory + Default + Clone, P: Peripherals<'flag
         }
     }
 
-    Ok(())
 }
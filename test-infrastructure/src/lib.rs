@@ -29,7 +29,9 @@ pub use pretty_assertions::*;
 
 mod runner;
 #[macro_use] pub mod macros;
+pub mod diff;
 mod misc;
+pub mod model;
 
 // The bash script will not work on Windows.
 #[cfg_attr(all(docs, not(doctest)), doc(cfg(target_family = "unix")))]
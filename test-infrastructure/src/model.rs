@@ -0,0 +1,285 @@
+//! A reference model of the LC-3's instruction semantics, for checking
+//! interpreters against.
+//!
+//! The model is deliberately simple: a [`Machine`] is registers, the PC, the
+//! PSR, the saved stack pointer and a sparse memory, and [`Machine::step`] is
+//! a direct transcription of the ISA (including traps, `RTI` and the
+//! privilege mode, illegal opcode and access control violation exceptions).
+//!
+//! Devices are not modeled. Steps that would touch memory mapped I/O or
+//! overflow the system stack are reported as [`Unmodeled`] rather than
+//! guessed at; property tests should discard those cases.
+//!
+//! [`machine`] is a [`proptest`] strategy for machine states to start from;
+//! see [`lc3_isa::strategies`] for instructions.
+
+use lc3_isa::{
+    Addr, Instruction, Reg, Word, ACCESS_CONTROL_VIOLATION_EXCEPTION_VECTOR,
+    ILLEGAL_OPCODE_EXCEPTION_VECTOR, INTERRUPT_VECTOR_TABLE_START_ADDR, MEM_MAPPED_START_ADDR,
+    OS_START_ADDR, PRIVILEGE_MODE_VIOLATION_EXCEPTION_VECTOR, TRAP_VECTOR_TABLE_START_ADDR,
+    USER_PROGRAM_START_ADDR,
+};
+use lc3_isa::strategies::{memory, register_file, word};
+
+use proptest::prelude::*;
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+/// Why a step couldn't be modeled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unmodeled {
+    /// A device register (memory mapped I/O) was accessed.
+    DeviceAccess { addr: Addr },
+    /// A push would have gone past the bottom of the system stack.
+    SystemStackOverflow,
+}
+
+// Things that stop an instruction partway through.
+enum Stop {
+    Exception(u8),
+    Unmodeled(Unmodeled),
+}
+
+impl From<Unmodeled> for Stop {
+    fn from(u: Unmodeled) -> Self {
+        Stop::Unmodeled(u)
+    }
+}
+
+const USER_MODE: Word = 1 << 15;
+const CC_MASK: Word = 0b111;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    pub regs: [Word; Reg::NUM_REGS],
+    pub pc: Addr,
+    pub psr: Word,
+    /// The stack pointer of the mode we're not in (R6 has the other one).
+    pub saved_sp: Word,
+    /// Memory below the memory mapped I/O region; missing addresses hold 0.
+    pub memory: BTreeMap<Addr, Word>,
+}
+
+impl Machine {
+    pub fn in_user_mode(&self) -> bool {
+        self.psr & USER_MODE != 0
+    }
+
+    /// The condition codes, as `(n, z, p)`.
+    pub fn cc(&self) -> (bool, bool, bool) {
+        (self.psr & 0b100 != 0, self.psr & 0b010 != 0, self.psr & 0b001 != 0)
+    }
+
+    pub fn word(&self, addr: Addr) -> Word {
+        self.memory.get(&addr).copied().unwrap_or(0)
+    }
+
+    /// Runs one instruction (or takes one exception).
+    ///
+    /// On `Err` the machine is left partway through the step.
+    pub fn step(&mut self) -> Result<(), Unmodeled> {
+        let pc = self.pc;
+        self.pc = pc.wrapping_add(1);
+
+        match self.execute(pc) {
+            Ok(()) => Ok(()),
+            Err(Stop::Exception(vec)) => self.exception(INTERRUPT_VECTOR_TABLE_START_ADDR | vec as Addr),
+            Err(Stop::Unmodeled(u)) => Err(u),
+        }
+    }
+
+    fn execute(&mut self, pc: Addr) -> Result<(), Stop> {
+        use Instruction::*;
+
+        let word = self.load(pc)?;
+        let insn = Instruction::try_from(word).map_err(|_| Stop::Exception(ILLEGAL_OPCODE_EXCEPTION_VECTOR))?;
+
+        let pc = self.pc;
+        let rel = |offset| pc.wrapping_add(offset as Word);
+
+        match insn {
+            AddReg { dr, sr1, sr2 } => self.set_reg(dr, self.reg(sr1).wrapping_add(self.reg(sr2))),
+            AddImm { dr, sr1, imm5 } => self.set_reg(dr, self.reg(sr1).wrapping_add(imm5 as Word)),
+            AndReg { dr, sr1, sr2 } => self.set_reg(dr, self.reg(sr1) & self.reg(sr2)),
+            AndImm { dr, sr1, imm5 } => self.set_reg(dr, self.reg(sr1) & imm5 as Word),
+            Not { dr, sr } => self.set_reg(dr, !self.reg(sr)),
+            Br { n, z, p, offset9 } => {
+                let (cc_n, cc_z, cc_p) = self.cc();
+                if n && cc_n || z && cc_z || p && cc_p {
+                    self.pc = rel(offset9);
+                }
+            }
+            Jmp { base } => self.pc = self.reg(base),
+            Ret => self.pc = self.reg(Reg::R7),
+            Jsr { offset11 } => {
+                self.regs[Reg::R7 as usize] = pc;
+                self.pc = rel(offset11);
+            }
+            Jsrr { base } => {
+                self.pc = self.reg(base);
+                self.regs[Reg::R7 as usize] = pc;
+            }
+            Ld { dr, offset9 } => {
+                let word = self.load(rel(offset9))?;
+                self.set_reg(dr, word)
+            }
+            Ldi { dr, offset9 } => {
+                let addr = self.load(rel(offset9))?;
+                let word = self.load(addr)?;
+                self.set_reg(dr, word)
+            }
+            Ldr { dr, base, offset6 } => {
+                let word = self.load(self.reg(base).wrapping_add(offset6 as Word))?;
+                self.set_reg(dr, word)
+            }
+            // LEA does not set the condition codes.
+            Lea { dr, offset9 } => self.regs[dr as usize] = rel(offset9),
+            St { sr, offset9 } => self.store(rel(offset9), self.reg(sr))?,
+            Sti { sr, offset9 } => {
+                let addr = self.load(rel(offset9))?;
+                self.store(addr, self.reg(sr))?
+            }
+            Str { sr, base, offset6 } => self.store(self.reg(base).wrapping_add(offset6 as Word), self.reg(sr))?,
+            Trap { trapvec } => self.exception(TRAP_VECTOR_TABLE_START_ADDR | trapvec as Addr)?,
+            Rti => {
+                if self.in_user_mode() {
+                    return Err(Stop::Exception(PRIVILEGE_MODE_VIOLATION_EXCEPTION_VECTOR));
+                }
+
+                self.pc = self.pop()?;
+                self.psr = self.pop()?;
+
+                if self.in_user_mode() {
+                    self.swap_stacks();
+                }
+            }
+            Extended { .. } => unreachable!("extended instructions aren't decoded"),
+        }
+
+        Ok(())
+    }
+
+    fn reg(&self, reg: Reg) -> Word {
+        self.regs[reg as usize]
+    }
+
+    // Sets a register and the condition codes.
+    fn set_reg(&mut self, reg: Reg, word: Word) {
+        self.regs[reg as usize] = word;
+
+        let cc = match word as i16 {
+            w if w < 0 => 0b100,
+            0 => 0b010,
+            _ => 0b001,
+        };
+        self.psr = (self.psr & !CC_MASK) | cc;
+    }
+
+    fn check(&self, addr: Addr) -> Result<(), Stop> {
+        if self.in_user_mode() && !(USER_PROGRAM_START_ADDR..MEM_MAPPED_START_ADDR).contains(&addr) {
+            Err(Stop::Exception(ACCESS_CONTROL_VIOLATION_EXCEPTION_VECTOR))
+        } else if addr >= MEM_MAPPED_START_ADDR {
+            Err(Unmodeled::DeviceAccess { addr }.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn load(&self, addr: Addr) -> Result<Word, Stop> {
+        self.check(addr).map(|()| self.word(addr))
+    }
+
+    fn store(&mut self, addr: Addr, word: Word) -> Result<(), Stop> {
+        self.check(addr).map(|()| { let _ = self.memory.insert(addr, word); })
+    }
+
+    fn swap_stacks(&mut self) {
+        core::mem::swap(&mut self.regs[Reg::R6 as usize], &mut self.saved_sp);
+    }
+
+    // Only ever used in supervisor mode.
+    fn push(&mut self, word: Word) -> Result<(), Unmodeled> {
+        let sp = self.reg(Reg::R6);
+        if sp <= OS_START_ADDR {
+            return Err(Unmodeled::SystemStackOverflow);
+        }
+
+        self.regs[Reg::R6 as usize] = sp - 1;
+        match self.store(sp - 1, word) {
+            Err(Stop::Unmodeled(u)) => Err(u),
+            _ => Ok(()),
+        }
+    }
+
+    fn pop(&mut self) -> Result<Word, Unmodeled> {
+        let sp = self.reg(Reg::R6);
+        let word = match self.load(sp) {
+            Ok(w) => w,
+            Err(Stop::Unmodeled(u)) => return Err(u),
+            Err(Stop::Exception(_)) => unreachable!("pops happen in supervisor mode"),
+        };
+
+        self.regs[Reg::R6 as usize] = sp.wrapping_add(1);
+        Ok(word)
+    }
+
+    // Switches to supervisor mode (and the system stack), saves the PSR and PC
+    // and goes to the routine in the given vector table entry.
+    fn exception(&mut self, table_entry: Addr) -> Result<(), Unmodeled> {
+        let saved_psr = self.psr;
+        if self.in_user_mode() {
+            self.psr &= !USER_MODE;
+            self.swap_stacks();
+        }
+
+        self.push(saved_psr)?;
+        self.push(self.pc)?;
+
+        self.pc = self.word(table_entry);
+        Ok(())
+    }
+}
+
+/// A PSR with exactly one condition code set, any priority and either mode.
+pub fn psr() -> impl Strategy<Value = Word> {
+    (any::<bool>(), 0..8u16, 0..3u16).prop_map(|(user, priority, cc)| {
+        (if user { USER_MODE } else { 0 }) | (priority << 8) | (1 << cc)
+    })
+}
+
+/// A machine about to run `insn` (which need not be a valid instruction).
+///
+/// The PC is in user space, the stack pointers are somewhere plausible (with
+/// room to push) and memory has random vector table entries and random words
+/// around the PC (and anywhere else loads might go).
+pub fn machine(insn: impl Strategy<Value = Word>) -> impl Strategy<Value = Machine> {
+    let stack_pointer = || prop_oneof![(OS_START_ADDR + 2)..USER_PROGRAM_START_ADDR, word()];
+
+    (
+        insn,
+        register_file(),
+        USER_PROGRAM_START_ADDR..(MEM_MAPPED_START_ADDR - 1),
+        psr(),
+        stack_pointer(),
+        stack_pointer(),
+        memory(0..=(OS_START_ADDR - 1), 32),
+        memory(0..=(MEM_MAPPED_START_ADDR - 1), 64),
+    )
+        .prop_flat_map(|(insn, regs, pc, psr, sp, saved_sp, vectors, random)| {
+            // Put a few words (which are often pointers) right around the PC:
+            let near_pc = memory(pc.saturating_sub(256)..=pc.saturating_add(256).min(MEM_MAPPED_START_ADDR - 1), 16);
+
+            near_pc.prop_map(move |near_pc| {
+                let mut regs = regs;
+                regs[Reg::R6 as usize] = sp;
+
+                let mut memory = BTreeMap::new();
+                memory.extend(random.iter().chain(vectors.iter()));
+                memory.extend(near_pc);
+                let _ = memory.insert(pc, insn);
+
+                Machine { regs, pc, psr, saved_sp, memory }
+            })
+        })
+}
@@ -1,12 +1,20 @@
 //! Home of the workhorse of this crate: `interp_test_runner`; the thing that
 //! actually runs the interpreter.
+//!
+//! Also has helpers for tests that drive an interpreter or simulator by hand
+//! with a program at 0x3000.
 
-use lc3_isa::{Addr, Instruction, Word};
+use lc3_isa::{Addr, Instruction, Word, ADDR_SPACE_SIZE_IN_WORDS};
+use lc3_isa::util::MemoryDump;
+use lc3_traits::control::{Control, Event};
 use lc3_traits::memory::Memory;
 use lc3_traits::peripherals::Peripherals;
 use lc3_baseline_sim::interp::{PeripheralInterruptFlags, InstructionInterpreter,
     Interpreter, InterpreterBuilder, MachineState
 };
+use lc3_baseline_sim::sim::Simulator;
+use lc3_shims::memory::MemoryShim;
+use lc3_shims::peripherals::PeripheralsShim;
 use core::convert::TryInto;
 
 use pretty_assertions::assert_eq;
@@ -31,6 +39,43 @@ where
     for<'i> TF: FnOnce(&'i Interpreter<'flags, M, P>), // Note: we could pass by value
                                                        // since this is the last thing
                                                        // we do.
+{
+    interp_test_runner_for::<Interpreter<'flags, M, P>, M, P, PF, TF>(
+        prefilled_memory_locations,
+        insns,
+        num_steps,
+        regs,
+        pc,
+        memory_locations,
+        setup_func,
+        teardown_func,
+        flags,
+        alt_memory,
+        alt_peripherals,
+    )
+}
+
+/// Like [`interp_test_runner`] but runs the instructions on any interpreter
+/// that can be made from an [`Interpreter`] (i.e. the
+/// [`BlockInterpreter`](lc3_baseline_sim::block::BlockInterpreter)).
+pub fn interp_test_runner_for<'flags, I, M: Memory + Default + Clone, P: Peripherals<'flags>, PF, TF>
+(
+    prefilled_memory_locations: Vec<(Addr, Word)>,
+    insns: Vec<Instruction>,
+    num_steps: Option<usize>,
+    regs: [Option<Word>; 8],
+    pc: Option<Addr>,
+    memory_locations: Vec<(Addr, Word)>,
+    setup_func: PF,
+    teardown_func: TF,
+    flags: &'flags PeripheralInterruptFlags,
+    alt_memory: Option<(M, Addr)>,
+    alt_peripherals: Option<P>,
+)
+where
+    I: InstructionInterpreter + From<Interpreter<'flags, M, P>>,
+    for<'p> PF: FnOnce(&'p mut P),
+    for<'i> TF: FnOnce(&'i I),
 {
     let mut addr = 0x3000;
 
@@ -65,6 +110,8 @@ where
     // Run the setup func:
     setup_func(&mut *interp);
 
+    let mut interp: I = interp.into();
+
     // Prefill the memory locations:
     for (addr, word) in prefilled_memory_locations.iter() {
         // Crashes on ACVs! (they should not happen at this point)
@@ -90,7 +137,7 @@ where
             let _ = interp.step();
         }
     } else {
-        while let MachineState::Running = interp.step() { }
+        while let (_, MachineState::Running) = interp.step_many(usize::MAX) { }
     }
 
     // Check PC:
@@ -134,3 +181,65 @@ where
     // Run the teardown func:
     teardown_func(&interp);
 }
+
+/// An [`Interpreter`] backed by the shims.
+pub type ShimInterp<'a> = Interpreter<'a, MemoryShim, PeripheralsShim<'a>>;
+
+/// A [`Simulator`] around a [`ShimInterp`].
+pub type ShimSim<'a> = Simulator<'a, 'static, ShimInterp<'a>>;
+
+/// An interpreter with `program` written at 0x3000 and its PC pointing there.
+pub fn interp_with_program<'a>(program: &[Word]) -> ShimInterp<'a> {
+    let mut interp = ShimInterp::default();
+
+    for (addr, word) in (0x3000..).zip(program.iter()) {
+        interp.set_word_unchecked(addr, *word);
+    }
+
+    interp.set_pc(0x3000);
+    interp
+}
+
+/// Any interpreter that can be made from a [`ShimInterp`], with `memory` (i.e.
+/// what `program!` produces) loaded and its PC at 0x3000.
+pub fn interp_with_memory<'a, I>(memory: [(Word, bool); ADDR_SPACE_SIZE_IN_WORDS]) -> I
+where
+    I: InstructionInterpreter + From<ShimInterp<'a>>,
+{
+    let interp: ShimInterp<'a> = InterpreterBuilder::<_, PeripheralsShim<'a>>::new()
+        .with_defaults()
+        .with_memory(MemoryShim::new(MemoryDump::from(memory).0))
+        .build();
+
+    let mut interp: I = interp.into();
+    interp.set_pc(0x3000);
+    interp
+}
+
+/// A simulator with `program` written at 0x3000 and its PC pointing there.
+///
+/// `setup` gets the simulator before the program is written.
+pub fn sim_with_program<'a>(program: &[Word], setup: impl FnOnce(&mut ShimSim<'a>)) -> ShimSim<'a> {
+    let mut sim = ShimSim::default();
+    setup(&mut sim);
+
+    for (addr, word) in (0x3000..).zip(program.iter()) {
+        sim.write_word(addr, *word);
+    }
+
+    sim.set_pc(0x3000);
+    sim
+}
+
+/// Steps the interpreter `num` times.
+pub fn interp_steps<I: InstructionInterpreter + ?Sized>(interp: &mut I, num: usize) {
+    for _ in 0..num {
+        let _ = interp.step();
+    }
+}
+
+/// Steps `sim` `num` times and collects the event each step ended with (if
+/// any).
+pub fn sim_steps<C: Control + ?Sized>(sim: &mut C, num: usize) -> Vec<Option<Event>> {
+    (0..num).map(|_| sim.step()).collect()
+}
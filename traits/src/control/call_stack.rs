@@ -0,0 +1,96 @@
+//! Types for inspecting the call stack through
+//! [`Control`](crate::control::Control).
+//!
+//! Frames are numbered from the bottom of the stack: frame 0 is the outermost
+//! call. [`Control::get_call_stack`](crate::control::Control::get_call_stack)
+//! hands frames out [`CALL_STACK_PAGE_SIZE`] at a time; page `n` holds frames
+//! `n * CALL_STACK_PAGE_SIZE` up to (but not including)
+//! `(n + 1) * CALL_STACK_PAGE_SIZE`.
+
+use super::control::ProcessorMode;
+
+use lc3_isa::{Addr, Reg, Word};
+
+use serde::{Deserialize, Serialize};
+
+/// Number of frames in a page of the call stack.
+pub const CALL_STACK_PAGE_SIZE: usize = 4;
+
+pub type CallStackPage = [Option<CallStackFrame>; CALL_STACK_PAGE_SIZE];
+
+/// How a frame was entered.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FrameKind {
+    Jsr,
+    Jsrr,
+    Trap { vector: u8 },
+    Interrupt { vector: u8 },
+    Exception { vector: u8 },
+}
+
+impl FrameKind {
+    /// Whether this frame was entered through the trap, interrupt or exception
+    /// vector tables (and so saved the PSR and PC on the system stack).
+    pub fn is_vectored(&self) -> bool {
+        !matches!(self, FrameKind::Jsr | FrameKind::Jsrr)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CallStackFrame {
+    pub kind: FrameKind,
+    /// The address of the routine that was entered.
+    pub target: Addr,
+    /// Where the routine should return to.
+    pub return_addr: Addr,
+    /// The PSR just before the call.
+    pub saved_psr: Word,
+    /// R6 when the routine was entered (for vectored frames, this is after the
+    /// PSR and PC were pushed).
+    pub stack_pointer: Word,
+}
+
+impl CallStackFrame {
+    /// The mode the routine runs in.
+    pub fn mode(&self) -> ProcessorMode {
+        if !self.kind.is_vectored() && self.saved_psr & 0x8000 != 0 {
+            ProcessorMode::User
+        } else {
+            ProcessorMode::Supervisor
+        }
+    }
+}
+
+/// A misuse of a stack or of the calling convention (see
+/// [`Event::StackViolation`](crate::control::Event::StackViolation)).
+///
+/// `stack` is the stack in question: the user stack or the supervisor stack.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StackViolation {
+    /// The stack pointer went below the stack's limit.
+    Overflow { stack: ProcessorMode, sp: Word },
+    /// The stack pointer went above the stack's base.
+    Underflow { stack: ProcessorMode, sp: Word },
+    /// A store through R6 to an address below the stack's limit.
+    WriteBelowLimit { stack: ProcessorMode, addr: Addr },
+    /// A subroutine returned without restoring a register it was supposed to
+    /// preserve.
+    CalleeSavedRegister { reg: Reg, expected: Word, found: Word },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_modes() {
+        let frame = |kind, saved_psr| CallStackFrame {
+            kind, target: 0x3100, return_addr: 0x3001, saved_psr, stack_pointer: 0xFE00,
+        };
+
+        assert_eq!(frame(FrameKind::Jsr, 0x8002).mode(), ProcessorMode::User);
+        assert_eq!(frame(FrameKind::Jsrr, 0x0002).mode(), ProcessorMode::Supervisor);
+        assert_eq!(frame(FrameKind::Trap { vector: 0x25 }, 0x8002).mode(), ProcessorMode::Supervisor);
+        assert_eq!(frame(FrameKind::Interrupt { vector: 0x80 }, 0x8002).mode(), ProcessorMode::Supervisor);
+    }
+}
@@ -10,9 +10,11 @@
 use crate::error::Error;
 use crate::peripherals::adc::{AdcPinArr, AdcReadError, AdcState};
 use crate::peripherals::gpio::{GpioPinArr, GpioReadError, GpioState};
-use crate::peripherals::pwm::{PwmPinArr, PwmState};
+use crate::peripherals::pwm::{PwmConfig, PwmPinArr, PwmState};
 use crate::peripherals::timers::{TimerArr, TimerState, TimerMode};
 use super::{Capabilities, DeviceInfo, ProgramMetadata, Identifier};
+use super::call_stack::{CallStackPage, StackViolation};
+use super::interrupts::InterruptControllerState;
 use super::UnifiedRange;
 use super::load::{
     PageIndex, PageWriteStart, StartPageWriteError, PageChunkError,
@@ -62,11 +64,29 @@ pub enum Event {
     Breakpoint { addr: Addr },
     MemoryWatch { addr: Addr, data: Word },
     DepthReached { current_depth: u64 },
+    /// A return (`RET` or `RTI`) that didn't go back to where the innermost
+    /// call on the call stack expected it to.
+    UnbalancedReturn { to: Addr, expected: Addr },
+    /// Reported by simulators with stack checking turned on; `pc` is the
+    /// address of the instruction responsible.
+    StackViolation { pc: Addr, violation: StackViolation },
+    /// Reported by simulators that track which registers and memory locations
+    /// have been written; `pc` is the address of the instruction that read the
+    /// uninitialized value.
+    UninitializedRead { pc: Addr, read: UninitializedRead },
     Error { err: Error },
     Interrupted, // If we get paused or stepped, this is returned. (TODO: we currently only return this if we're paused!! not sure if stopping on a step is reasonable behavior)
     Halted,
 }
 
+/// A value an instruction used before anything wrote to it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UninitializedRead {
+    Register(Reg),
+    /// Includes fetching an instruction from memory that was never written.
+    Memory(Addr),
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum State {
     Paused,
@@ -74,6 +94,23 @@ pub enum State {
     Halted,
 }
 
+/// What a simulator does when an instruction faults (access control
+/// violations, illegal opcodes, and privilege mode violations).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FaultHandling {
+    /// Vector into the OS's handler for the fault, like the hardware does.
+    Vector,
+    /// Leave the PC on the faulting instruction and report the fault as an
+    /// error (which stops the simulator with an [`Event::Error`]).
+    Stop,
+}
+
+impl Default for FaultHandling {
+    fn default() -> Self {
+        FaultHandling::Vector
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ProcessorMode {
     /// Privileged Mode
@@ -115,6 +152,17 @@ pub trait Control {
     fn read_word(&self, addr: Addr) -> Word;
     fn write_word(&mut self, addr: Addr, word: Word);
 
+    /// Reads `out.len()` consecutive words starting at `start` (wrapping
+    /// around at the end of the address space).
+    ///
+    /// Implementors for whom each call is expensive (i.e. the RPC
+    /// [`Controller`](super::rpc::Controller)) should override this.
+    fn read_words(&self, start: Addr, out: &mut [Word]) {
+        out.iter_mut()
+            .enumerate()
+            .for_each(|(offset, w)| *w = self.read_word(start.wrapping_add(offset as Addr)));
+    }
+
     /// The start function for a Load API Session.
     ///
     /// Calling this is effectively unsafe since you need to call [an unsafe
@@ -222,11 +270,16 @@ pub trait Control {
     /// [`set_depth_condition`]: Control::set_depth_condition
     fn get_depth(&self) -> Result<u64, ()>;
 
-    /// Gets the first [`MAX_CALL_STACK_DEPTH`] frames of the call stack.
+    /// Gets a page of the call stack (see the [`call_stack`] module).
+    ///
+    /// Frames that are `None` are past the top of the stack or weren't
+    /// recorded; implementations may only record a limited number of frames
+    /// (no_std ones will generally record [`MAX_CALL_STACK_DEPTH`]) but
+    /// [`get_depth`] always has the true depth.
     ///
-    /// Frames that are `Some` exist; other's are not present. The number of
-    /// frames returned should be (MAX_CALL_STACK_DEPTH).min()
-    fn get_call_stack(&self) -> [Option<(Addr, ProcessorMode)>; MAX_CALL_STACK_DEPTH];
+    /// [`call_stack`]: super::call_stack
+    /// [`get_depth`]: Control::get_depth
+    fn get_call_stack(&self, page: u16) -> CallStackPage;
 
     // Execution control functions:
     fn run_until_event(&mut self) -> Self::EventFuture; // Can be interrupted by step or pause.
@@ -261,6 +314,11 @@ pub trait Control {
     // Leaning towards it being the error in the last step though.
     fn get_error(&self) -> Option<Error>;
 
+    /// Whether faults (ACVs, illegal opcodes, privilege mode violations) are
+    /// handed to the OS or stop the device with an [`Event::Error`].
+    fn set_fault_handling(&mut self, handling: FaultHandling);
+    fn get_fault_handling(&self) -> FaultHandling;
+
     // I/O Access:
     // TODO!! Does the state/reading separation make sense?
     fn get_gpio_states(&self) -> GpioPinArr<GpioState>;
@@ -270,9 +328,13 @@ pub trait Control {
     fn get_timer_modes(&self) -> TimerArr<TimerMode>;
     fn get_timer_states(&self) -> TimerArr<TimerState>;
     fn get_pwm_states(&self) -> PwmPinArr<PwmState>;
-    fn get_pwm_config(&self) -> PwmPinArr<u8>; // TODO: ditto with using u8 here; probably should be some kind of enum (the conflict is then we're kinda pushing implementors to represent state a certain way.. or at least to have to translate it to our enum).
+    fn get_pwm_config(&self) -> PwmPinArr<PwmConfig>;
     fn get_clock(&self) -> Word;
 
+    /// Gets the state of the interrupt controller (which interrupts are
+    /// pending, which are masked and what their priorities are).
+    fn get_interrupt_controller_state(&self) -> InterruptControllerState;
+
     // So with some of these functions that are basically straight wrappers over their Memory/Peripheral trait counterparts,
     // we have a bit of a choice. We can make Control a super trait of those traits so that we can have default impls of said
     // functions or we can make the implementor of Control manually wrap those functions.
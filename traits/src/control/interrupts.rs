@@ -0,0 +1,139 @@
+//! Types for inspecting the interrupt controller through
+//! [`Control`](crate::control::Control).
+//!
+//! Every device that can raise an interrupt is an [`InterruptSource`]; sources
+//! are numbered (see [`INTERRUPT_SOURCES`]) and that number is the source's bit
+//! in the controller's pending and mask registers.
+
+use crate::peripherals::adc::AdcPin;
+use crate::peripherals::gpio::GpioPin;
+use crate::peripherals::pwm::PwmPin;
+use crate::peripherals::timers::TimerId;
+
+use lc3_macros::DisplayUsingDebug;
+
+use core::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(DisplayUsingDebug)]
+pub enum InterruptSource {
+    Keyboard,
+    Display,
+    Gpio(GpioPin),
+    Adc(AdcPin),
+    Pwm(PwmPin),
+    Timer(TimerId),
+}
+
+impl InterruptSource {
+    pub const NUM_SOURCES: usize = 2
+        + GpioPin::NUM_PINS
+        + AdcPin::NUM_PINS
+        + PwmPin::NUM_PINS
+        + TimerId::NUM_TIMERS;
+}
+
+/// All the interrupt sources, in order.
+pub const INTERRUPT_SOURCES: [InterruptSource; InterruptSource::NUM_SOURCES] = {
+    use InterruptSource::*;
+    use GpioPin::*; use AdcPin::*; use PwmPin::*; use TimerId::*;
+
+    [
+        Keyboard, Display,
+        Gpio(G0), Gpio(G1), Gpio(G2), Gpio(G3), Gpio(G4), Gpio(G5), Gpio(G6), Gpio(G7),
+        Adc(A0), Adc(A1), Adc(A2), Adc(A3), Adc(A4), Adc(A5),
+        Pwm(P0), Pwm(P1),
+        Timer(T0), Timer(T1), Timer(T2), Timer(T3),
+    ]
+};
+
+impl From<InterruptSource> for usize {
+    fn from(source: InterruptSource) -> usize {
+        use InterruptSource::*;
+
+        match source {
+            Keyboard => 0,
+            Display => 1,
+            Gpio(pin) => 2 + usize::from(pin),
+            Adc(pin) => 2 + GpioPin::NUM_PINS + usize::from(pin),
+            Pwm(pin) => 2 + GpioPin::NUM_PINS + AdcPin::NUM_PINS + usize::from(pin),
+            Timer(id) => 2 + GpioPin::NUM_PINS + AdcPin::NUM_PINS + PwmPin::NUM_PINS + usize::from(id),
+        }
+    }
+}
+
+impl TryFrom<usize> for InterruptSource {
+    type Error = ();
+
+    fn try_from(idx: usize) -> Result<Self, ()> {
+        INTERRUPT_SOURCES.get(idx).copied().ok_or(())
+    }
+}
+
+/// A snapshot of the interrupt controller's registers.
+///
+/// `pending` and `masked` are bitmaps indexed by source number. A source is
+/// pending when its device has raised an interrupt (and has interrupts
+/// enabled), whether or not it's masked or has a high enough priority to
+/// actually fire.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct InterruptControllerState {
+    pub enabled: bool,
+    pub pending: u32,
+    pub masked: u32,
+    pub priorities: [u8; InterruptSource::NUM_SOURCES],
+}
+
+impl InterruptControllerState {
+    pub fn is_pending(&self, source: InterruptSource) -> bool {
+        self.pending & (1 << usize::from(source)) != 0
+    }
+
+    pub fn is_masked(&self, source: InterruptSource) -> bool {
+        self.masked & (1 << usize::from(source)) != 0
+    }
+
+    pub fn priority(&self, source: InterruptSource) -> u8 {
+        self.priorities[usize::from(source)]
+    }
+
+    /// Sources that are pending and not masked, in order.
+    pub fn unmasked_pending(&self) -> impl Iterator<Item = InterruptSource> + '_ {
+        INTERRUPT_SOURCES
+            .iter()
+            .copied()
+            .filter(move |s| self.is_pending(*s) && !self.is_masked(*s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_numbers_round_trip() {
+        for (idx, source) in INTERRUPT_SOURCES.iter().enumerate() {
+            assert_eq!(usize::from(*source), idx);
+            assert_eq!(InterruptSource::try_from(idx), Ok(*source));
+        }
+
+        assert_eq!(InterruptSource::try_from(InterruptSource::NUM_SOURCES), Err(()));
+    }
+
+    #[test]
+    fn unmasked_pending() {
+        use InterruptSource::*;
+
+        let state = InterruptControllerState {
+            enabled: true,
+            pending: 0b1011,
+            masked: 0b0010,
+            priorities: [4; InterruptSource::NUM_SOURCES],
+        };
+
+        assert!(state.is_pending(Display) && state.is_masked(Display));
+        assert!(state.unmasked_pending().eq([Keyboard, Gpio(GpioPin::G1)].iter().copied()));
+    }
+}
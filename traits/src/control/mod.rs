@@ -8,7 +8,7 @@ pub use metadata::{
 };
 
 pub mod control;
-pub use control::{Control, Event, State, ProcessorMode, Idx};
+pub use control::{Control, Event, State, ProcessorMode, FaultHandling, Idx, UninitializedRead};
 
 pub mod ext;
 pub use ext::StepControl;
@@ -19,6 +19,12 @@ pub use load::{load_memory_dump, Progress};
 pub mod snapshot;
 pub use snapshot::{Snapshot, SnapshotError};
 
+pub mod call_stack;
+pub use call_stack::{CallStackFrame, CallStackPage, FrameKind, StackViolation, CALL_STACK_PAGE_SIZE};
+
+pub mod interrupts;
+pub use interrupts::{InterruptControllerState, InterruptSource, INTERRUPT_SOURCES};
+
 pub mod ranges;
 pub use ranges::UnifiedRange;
 
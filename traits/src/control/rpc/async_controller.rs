@@ -0,0 +1,671 @@
+//! A [`Controller`](super::Controller) whose operations return futures.
+//!
+//! [`Controller`](super::Controller) blocks on the response to every request
+//! which means something has to keep calling
+//! [`Control::tick`](crate::control::Control::tick) (or block a
+//! thread) while we wait for the device. [`AsyncController`] instead hands out
+//! a future for every operation; polling these futures is what moves things
+//! along. This makes it a good fit for single-threaded executors and for wasm,
+//! where there aren't any threads to block.
+//!
+//! Any [`Transport`] that doesn't block in [`get`](Transport::get) works.
+//! Several operations can be in flight at once (i.e. joined futures). Each one
+//! holds one of [`MAX_PIPELINE_DEPTH`] slots. Responses are matched up with
+//! their requests by sequence number as they arrive, no matter which future
+//! happens to be polled. Requests are retried as the [retry policy](RetryPolicy)
+//! allows, same as with `Controller`. Note that the device only remembers the
+//! last request it handled so only [idempotent](RequestMessage::is_idempotent)
+//! requests are safe to retry while other requests are in flight. Requests
+//! that aren't (if the policy retries them at all) are sent on their own: they
+//! wait for the requests in flight to finish and hold off new ones until
+//! they're done.
+//!
+//! Transports generally have no way to tell us when a message shows up. When
+//! a future has nothing to do yet, it hands its [`Waker`] to the controller's
+//! [`Wait`] strategy, which decides when the future is polled again. The
+//! default ([`Spin`]) wakes it right away. That's fine for executors that let
+//! other tasks run in between but on the web it'd keep the browser from ever
+//! delivering the message. There, use a strategy that wakes the future from a
+//! timer instead:
+//!
+//! ```rust,ignore
+//! let cont = AsyncController::new(enc, dec, transport)
+//!     .with_wait(|waker: &Waker| {
+//!         let waker = waker.clone();
+//!         set_timeout(move || waker.wake(), 1);
+//!     });
+//! ```
+//!
+//! [Notifications](super::notifications) that arrive are queued (up to
+//! [`NOTIFICATION_QUEUE_LEN`] of them; past that the oldest are dropped) until
+//! they're [taken](AsyncController::take_notification). Unlike `Controller`,
+//! `AsyncController` doesn't answer operations from them.
+
+use super::{Event, State, Transport};
+use super::controller::{Deadline, RetryPolicy, RpcError, MAX_PIPELINE_DEPTH};
+use super::messages::{RequestMessage, ResponseMessage, Envelope, SeqNum, RequestEnvelope, ResponseEnvelope, READ_WORDS_CHUNK_LEN};
+use super::encoding::{Encode, Decode, Transparent};
+use super::handshake::{self, Hello, Features, HandshakeError};
+use super::hub::SessionInfo;
+use super::notifications::{Notification, Topics};
+use crate::control::control::{
+    MAX_BREAKPOINTS, MAX_MEMORY_WATCHPOINTS, Idx
+};
+use crate::control::call_stack::CallStackPage;
+use crate::control::load::{
+    LoadApiSession, CHUNK_SIZE_IN_WORDS, PageWriteStart, PageIndex, Offset,
+    StartPageWriteError, PageChunkError, FinishPageWriteError
+};
+use crate::control::{ProgramMetadata, DeviceInfo, FaultHandling, UnifiedRange, InterruptControllerState};
+use crate::error::Error as Lc3Error;
+use crate::peripherals::{
+    adc::{AdcPinArr, AdcState, AdcReadError},
+    gpio::{GpioPinArr, GpioState, GpioReadError},
+    pwm::{PwmConfig, PwmPinArr, PwmState},
+    timers::{TimerArr, TimerMode, TimerState},
+};
+
+use lc3_isa::{Reg, Addr, Word};
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug};
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+/// Decides when a future handed out by an [`AsyncController`] that's waiting
+/// on the device gets polled again.
+pub trait Wait {
+    /// Called with the future's waker each time it returns `Pending`; the
+    /// waker must be woken at some point (the sooner, the more responsive).
+    fn wait(&self, waker: &Waker);
+}
+
+/// Wakes waiting futures right away.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Spin;
+
+impl Wait for Spin {
+    fn wait(&self, waker: &Waker) {
+        waker.wake_by_ref()
+    }
+}
+
+impl<F: Fn(&Waker)> Wait for F {
+    fn wait(&self, waker: &Waker) {
+        self(waker)
+    }
+}
+
+// `core::future::poll_fn` isn't stable on our toolchain yet.
+struct PollFn<F>(F);
+
+impl<T, F: FnMut(&mut Context<'_>) -> Poll<T> + Unpin> Future for PollFn<F> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        (self.0)(cx)
+    }
+}
+
+// A request that's in flight and (once it arrives) its response.
+#[derive(Debug)]
+struct Slot {
+    seq: SeqNum,
+    // Whether the request has to be the only one in flight; see
+    // `AsyncController::claim_slot`.
+    exclusive: bool,
+    response: Option<ResponseMessage>,
+}
+
+type Slots = RefCell<[Option<Slot>; MAX_PIPELINE_DEPTH]>;
+
+// Frees up a slot when the request it belongs to finishes (or is dropped).
+struct SlotGuard<'s>(&'s Slots, usize);
+
+impl Drop for SlotGuard<'_> {
+    fn drop(&mut self) {
+        self.0.borrow_mut()[self.1] = None;
+    }
+}
+
+/// How many [notifications](super::notifications) an [`AsyncController`]
+/// holds on to until they're [taken](AsyncController::take_notification).
+pub const NOTIFICATION_QUEUE_LEN: usize = 16;
+
+// Notifications that have arrived but haven't been taken yet.
+#[derive(Debug, Default)]
+struct NotificationQueue {
+    buf: [Option<Notification>; NOTIFICATION_QUEUE_LEN],
+    head: usize,
+    len: usize,
+}
+
+impl NotificationQueue {
+    fn push(&mut self, n: Notification) {
+        if self.len == NOTIFICATION_QUEUE_LEN {
+            let dropped = self.pop();
+            log::warn!("Notification queue is full; dropping `{:?}`", dropped);
+        }
+
+        self.buf[(self.head + self.len) % NOTIFICATION_QUEUE_LEN] = Some(n);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<Notification> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let n = self.buf[self.head].take();
+        self.head = (self.head + 1) % NOTIFICATION_QUEUE_LEN;
+        self.len -= 1;
+        n
+    }
+}
+
+// The device only runs until one event at a time; every `run_until_event`
+// future that's started while it's running waits on the same event.
+#[derive(Debug, Default)]
+struct Run {
+    seq: Option<SeqNum>,
+    event: Option<Event>,
+    waiters: usize,
+}
+
+struct RunGuard<'r>(&'r RefCell<Run>);
+
+impl Drop for RunGuard<'_> {
+    fn drop(&mut self) {
+        let mut run = self.0.borrow_mut();
+        run.waiters -= 1;
+
+        // Once everyone's seen the event, the next call starts a new run. If
+        // the event hasn't come yet the device is still running, so we hold
+        // on to the run for whoever asks next.
+        if run.waiters == 0 && run.event.is_some() {
+            *run = Run::default();
+        }
+    }
+}
+
+/// Talks to a [`Device`](super::Device) like [`Controller`](super::Controller)
+/// does but returns a future from every operation instead of blocking; see the
+/// [module docs](self).
+pub struct AsyncController<
+    T,
+    Req = RequestMessage,
+    Resp = ResponseMessage,
+    ReqEnc = Transparent<RequestEnvelope>,
+    RespDec = Transparent<ResponseEnvelope>,
+    W = Spin,
+>
+where
+    Req: Debug,
+    Resp: Debug,
+    RequestMessage: Into<Req>,
+    Resp: Into<ResponseMessage>,
+    ReqEnc: Encode<Envelope<Req>>,
+    RespDec: Decode<Envelope<Resp>>,
+    T: Transport<<ReqEnc as Encode<Envelope<Req>>>::Encoded, <RespDec as Decode<Envelope<Resp>>>::Encoded>,
+    W: Wait,
+{
+    _encoded_formats: PhantomData<(Req, Resp)>,
+    pub transport: T,
+    enc: RefCell<ReqEnc>,
+    dec: RefCell<RespDec>,
+    next_seq: Cell<SeqNum>,
+    pub retry_policy: RetryPolicy,
+    slots: Slots,
+    run: RefCell<Run>,
+    notifications: RefCell<NotificationQueue>,
+    wait: W,
+}
+
+impl<Req, Resp, E, D, T, W> Debug for AsyncController<T, Req, Resp, E, D, W>
+where
+    Req: Debug,
+    Resp: Debug,
+    RequestMessage: Into<Req>,
+    Resp: Into<ResponseMessage>,
+    E: Encode<Envelope<Req>>,
+    D: Decode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Req>>>::Encoded, <D as Decode<Envelope<Resp>>>::Encoded> + Debug,
+    W: Wait,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("AsyncController")
+            .field("transport", &self.transport)
+            .field("next_seq", &self.next_seq)
+            .field("retry_policy", &self.retry_policy)
+            .field("slots", &self.slots)
+            .field("run", &self.run)
+            .field("notifications", &self.notifications)
+            .finish()
+    }
+}
+
+impl<Req, Resp, E, D, T> AsyncController<T, Req, Resp, E, D, Spin>
+where
+    Req: Debug,
+    Resp: Debug,
+    RequestMessage: Into<Req>,
+    Resp: Into<ResponseMessage>,
+    E: Encode<Envelope<Req>>,
+    D: Decode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Req>>>::Encoded, <D as Decode<Envelope<Resp>>>::Encoded>,
+{
+    pub fn new(enc: E, dec: D, transport: T) -> Self {
+        Self {
+            _encoded_formats: PhantomData,
+            transport,
+            enc: RefCell::new(enc),
+            dec: RefCell::new(dec),
+            next_seq: Cell::new(0),
+            retry_policy: RetryPolicy::NEVER_GIVE_UP,
+            slots: RefCell::new(Default::default()),
+            run: RefCell::new(Run::default()),
+            notifications: RefCell::new(NotificationQueue::default()),
+            wait: Spin,
+        }
+    }
+}
+
+impl<Req, Resp, E, D, T, W> AsyncController<T, Req, Resp, E, D, W>
+where
+    Req: Debug,
+    Resp: Debug,
+    RequestMessage: Into<Req>,
+    Resp: Into<ResponseMessage>,
+    E: Encode<Envelope<Req>>,
+    D: Decode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Req>>>::Encoded, <D as Decode<Envelope<Resp>>>::Encoded>,
+    W: Wait,
+{
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// How futures that are waiting on the device get woken.
+    pub fn with_wait<W2: Wait>(self, wait: W2) -> AsyncController<T, Req, Resp, E, D, W2> {
+        AsyncController {
+            _encoded_formats: PhantomData,
+            transport: self.transport,
+            enc: self.enc,
+            dec: self.dec,
+            next_seq: self.next_seq,
+            retry_policy: self.retry_policy,
+            slots: self.slots,
+            run: self.run,
+            notifications: self.notifications,
+            wait,
+        }
+    }
+
+    // Takes everything the transport has for us and puts it where it belongs.
+    fn drain(&self) -> Result<(), RpcError<T::SendErr, T::RecvErr>> {
+        loop {
+            let encoded_message = match self.transport.get() {
+                Ok(m) => m,
+                Err(None) => return Ok(()),
+                Err(Some(e)) => return Err(RpcError::RecvError(e)),
+            };
+
+            let Envelope { seq, message } = match self.dec.borrow_mut().decode(&encoded_message) {
+                Ok(env) => env,
+                // We can't tell which request this was a response to so
+                // we wait for the timeout to resend it.
+                Err(e) => {
+                    self.transport.record_get_error();
+                    log::trace!("Decode Error: `{:?}`", e);
+                    continue;
+                },
+            };
+
+            match message.into() {
+                ResponseMessage::RunUntilEvent(event) => {
+                    let mut run = self.run.borrow_mut();
+                    if run.seq == Some(seq) && run.event.is_none() {
+                        run.event = Some(event);
+                    } else {
+                        log::warn!("Ignoring unrequested event (#{}): `{:?}`", seq, event);
+                    }
+                },
+                ResponseMessage::Notification(n) => self.notifications.borrow_mut().push(n),
+                message => {
+                    let mut slots = self.slots.borrow_mut();
+                    match slots.iter_mut().flatten().find(|s| s.seq == seq && s.response.is_none()) {
+                        Some(slot) => slot.response = Some(message),
+                        None => log::trace!("Ignoring stale response (#{}): `{:?}`", seq, message),
+                    }
+                },
+            }
+        }
+    }
+
+    // Nothing else can claim a slot while an exclusive request (one that may be
+    // retried but isn't idempotent) holds one; see `wait_alone`.
+    fn claim_slot(&self, exclusive: bool, cx: &mut Context<'_>) -> Poll<(usize, SeqNum)> {
+        let mut slots = self.slots.borrow_mut();
+        let blocked = slots.iter().flatten().any(|s| s.exclusive);
+
+        match slots.iter().position(Option::is_none) {
+            Some(idx) if !blocked => {
+                let seq = self.next_seq.get();
+                self.next_seq.set(seq.wrapping_add(1));
+
+                slots[idx] = Some(Slot { seq, exclusive, response: None });
+                Poll::Ready((idx, seq))
+            },
+            _ => {
+                self.wait.wait(cx.waker());
+                Poll::Pending
+            },
+        }
+    }
+
+    // Exclusive requests are only sent once everything that was in flight when
+    // they claimed their slot is done. Otherwise a retry could reach the
+    // device after some other request, by which point the device has
+    // forgotten that it already handled it.
+    fn wait_alone(&self, cx: &mut Context<'_>) -> Poll<Result<(), RpcError<T::SendErr, T::RecvErr>>> {
+        if let Err(e) = self.drain() {
+            return Poll::Ready(Err(e));
+        }
+
+        if self.slots.borrow().iter().flatten().count() == 1 {
+            Poll::Ready(Ok(()))
+        } else {
+            self.wait.wait(cx.waker());
+            Poll::Pending
+        }
+    }
+
+    // `None` if the deadline passed first.
+    fn poll_slot(
+        &self,
+        idx: usize,
+        deadline: &mut Deadline,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<ResponseMessage>, RpcError<T::SendErr, T::RecvErr>>> {
+        if let Err(e) = self.drain() {
+            return Poll::Ready(Err(e));
+        }
+
+        let response = self.slots.borrow_mut()[idx].as_mut().and_then(|s| s.response.take());
+        if response.is_some() {
+            return Poll::Ready(Ok(response));
+        }
+
+        if deadline.expired() {
+            return Poll::Ready(Ok(None));
+        }
+
+        self.wait.wait(cx.waker());
+        Poll::Pending
+    }
+
+    async fn request_inner(
+        &self,
+        message: RequestMessage,
+        on_seq: impl FnOnce(SeqNum),
+    ) -> Result<ResponseMessage, RpcError<T::SendErr, T::RecvErr>> {
+        let policy = self.retry_policy;
+        let retryable = policy.retry_non_idempotent || message.is_idempotent();
+        let exclusive = retryable && !message.is_idempotent();
+
+        let (idx, seq) = PollFn(|cx: &mut Context<'_>| self.claim_slot(exclusive, cx)).await;
+        let _slot = SlotGuard(&self.slots, idx);
+        if exclusive {
+            PollFn(|cx: &mut Context<'_>| self.wait_alone(cx)).await?;
+        }
+        on_seq(seq);
+
+        let envelope = Envelope::new(seq, message.into());
+
+        let mut attempts: u16 = 0;
+        loop {
+            self.transport.send(self.enc.borrow_mut().encode(&envelope))
+                .map_err(RpcError::SendError)?;
+            attempts += 1;
+
+            let mut deadline = Deadline::start(policy.timeout);
+            let response = PollFn(|cx: &mut Context<'_>| self.poll_slot(idx, &mut deadline, cx)).await?;
+
+            if let Some(response) = response {
+                return Ok(response);
+            }
+
+            if !retryable || attempts > policy.max_retries as u16 {
+                return Err(RpcError::NoResponse { seq, attempts });
+            }
+
+            log::debug!("Retrying request #{} (attempt {})", seq, attempts + 1);
+        }
+    }
+
+    /// Sends a request to the device and resolves to its response, retrying
+    /// as the [retry policy](RetryPolicy) allows.
+    pub async fn request(&self, message: RequestMessage) -> Result<ResponseMessage, RpcError<T::SendErr, T::RecvErr>> {
+        self.request_inner(message, |_| ()).await
+    }
+
+    /// The oldest [notification](super::notifications) that's arrived and
+    /// hasn't been taken yet, if any.
+    pub fn take_notification(&self) -> Result<Option<Notification>, RpcError<T::SendErr, T::RecvErr>> {
+        self.drain()?;
+        Ok(self.notifications.borrow_mut().pop())
+    }
+
+    /// Checks that the device speaks a compatible version of the protocol; see
+    /// [`Controller::handshake`](super::Controller::handshake).
+    pub async fn handshake(&mut self) -> Result<Features, HandshakeError<T::SendErr, T::RecvErr>> {
+        // Devices that predate the handshake never answer it so we can't wait
+        // on it forever:
+        let policy = self.retry_policy;
+        self.retry_policy = policy.bounded();
+        let answer = self.request(RequestMessage::Handshake { hello: Hello::OURS }).await;
+        self.retry_policy = policy;
+
+        let features = handshake::check_answer(answer)?;
+
+        if !features.contains(Features::DUPLICATE_DETECTION) {
+            self.retry_policy.retry_non_idempotent = false;
+        }
+
+        Ok(features)
+    }
+
+    /// Starts the device running (unless it's already running because of an
+    /// earlier call) and resolves to the event that stops it.
+    pub async fn run_until_event(&self) -> Result<Event, RpcError<T::SendErr, T::RecvErr>> {
+        // Everyone waiting on the last run has to have seen its event before
+        // we can start the next one:
+        PollFn(|cx: &mut Context<'_>| {
+            if self.run.borrow().event.is_none() {
+                Poll::Ready(())
+            } else {
+                self.wait.wait(cx.waker());
+                Poll::Pending
+            }
+        }).await;
+
+        let running = {
+            let mut run = self.run.borrow_mut();
+            run.waiters += 1;
+            run.seq.is_some()
+        };
+        let _run = RunGuard(&self.run);
+
+        if !running {
+            // Note that we start waiting *before* the request is acknowledged;
+            // if the acknowledgement gets lost, the event may very well arrive
+            // before the acknowledgement for our retried request does.
+            let ack = self.request_inner(RequestMessage::RunUntilEvent, |seq| {
+                self.run.borrow_mut().seq = Some(seq);
+            });
+
+            match ack.await {
+                Ok(ResponseMessage::RunUntilEventAck) => {},
+                Ok(ResponseMessage::NotPermitted) => {
+                    self.run.borrow_mut().seq = None;
+                    return Err(RpcError::NotPermitted);
+                },
+                Ok(other) => panic!("Incorrect response for message! `{:?}`", other),
+                Err(e) => {
+                    self.run.borrow_mut().seq = None;
+                    return Err(e);
+                },
+            }
+        }
+
+        PollFn(|cx: &mut Context<'_>| {
+            if let Err(e) = self.drain() {
+                return Poll::Ready(Err(e));
+            }
+
+            match self.run.borrow().event {
+                Some(event) => Poll::Ready(Ok(event)),
+                None => {
+                    self.wait.wait(cx.waker());
+                    Poll::Pending
+                },
+            }
+        }).await
+    }
+
+    /// Reads `out.len()` words starting at `start`, one
+    /// [`ReadWords`](RequestMessage::ReadWords) request per chunk.
+    pub async fn read_words(&self, start: Addr, out: &mut [Word]) -> Result<(), RpcError<T::SendErr, T::RecvErr>> {
+        for (idx, chunk) in out.chunks_mut(READ_WORDS_CHUNK_LEN).enumerate() {
+            let message = RequestMessage::ReadWords {
+                start: start.wrapping_add((idx * READ_WORDS_CHUNK_LEN) as Addr),
+                len: chunk.len() as u8,
+            };
+
+            match self.request(message).await? {
+                ResponseMessage::ReadWords(words) => chunk.copy_from_slice(&words[..chunk.len()]),
+                ResponseMessage::NotPermitted => return Err(RpcError::NotPermitted),
+                other => panic!("Incorrect response for message! `{:?}`", other),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Asks the device what this session is allowed to do; see
+    /// [`Controller::session_info`](super::Controller::session_info).
+    pub async fn session_info(&self) -> Result<SessionInfo, RpcError<T::SendErr, T::RecvErr>> {
+        match self.request(RequestMessage::GetSessionInfo).await? {
+            ResponseMessage::GetSessionInfo(info) => Ok(info),
+            other => panic!("Incorrect response for message! `{:?}`", other),
+        }
+    }
+}
+
+// Like `try_ctrl!` in the `controller` module but for async fns.
+macro_rules! ops {
+    ($(
+        fn $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty { $req:expr => $resp:pat => $out:expr }
+    )*) => {$(
+        pub async fn $name(&self $(, $arg: $ty)*) -> Result<$ret, RpcError<T::SendErr, T::RecvErr>> {
+            #[allow(unused_imports)]
+            use RequestMessage::*;
+            use ResponseMessage as R;
+
+            match self.request($req).await? {
+                $resp => Ok($out),
+                R::NotPermitted => Err(RpcError::NotPermitted),
+                other => panic!("Incorrect response for message! `{:?}`", other),
+            }
+        }
+    )*};
+}
+
+/// The [`Control`](crate::control::Control) operations.
+impl<Req, Resp, E, D, T, W> AsyncController<T, Req, Resp, E, D, W>
+where
+    Req: Debug,
+    Resp: Debug,
+    RequestMessage: Into<Req>,
+    Resp: Into<ResponseMessage>,
+    E: Encode<Envelope<Req>>,
+    D: Decode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Req>>>::Encoded, <D as Decode<Envelope<Resp>>>::Encoded>,
+    W: Wait,
+{
+    ops! {
+        fn get_pc() -> Addr { GetPc => R::GetPc(addr) => addr }
+        fn set_pc(addr: Addr) -> () { SetPc { addr } => R::SetPc => () }
+
+        fn get_register(reg: Reg) -> Word { GetRegister { reg } => R::GetRegister(word) => word }
+        fn set_register(reg: Reg, data: Word) -> () { SetRegister { reg, data } => R::SetRegister => () }
+
+        fn get_registers_psr_and_pc() -> ([Word; Reg::NUM_REGS], Word, Word) {
+            GetRegistersPsrAndPc => R::GetRegistersPsrAndPc(r) => r
+        }
+
+        fn read_word(addr: Addr) -> Word { ReadWord { addr } => R::ReadWord(w) => w }
+        fn write_word(addr: Addr, word: Word) -> () { WriteWord { addr, word } => R::WriteWord => () }
+
+        fn start_page_write(page: LoadApiSession<PageWriteStart>, checksum: u64) -> Result<LoadApiSession<u8>, StartPageWriteError> {
+            StartPageWrite { page, checksum } => R::StartPageWrite(r) => r
+        }
+        fn send_page_chunk(offset: LoadApiSession<Offset>, chunk: [Word; CHUNK_SIZE_IN_WORDS as usize]) -> Result<(), PageChunkError> {
+            SendPageChunk { offset, chunk } => R::SendPageChunk(r) => r
+        }
+        fn finish_page_write(page: LoadApiSession<PageIndex>) -> Result<(), FinishPageWriteError> {
+            FinishPageWrite { page } => R::FinishPageWrite(r) => r
+        }
+
+        fn set_breakpoint(addr: Addr) -> Result<Idx, ()> { SetBreakpoint { addr } => R::SetBreakpoint(r) => r }
+        fn unset_breakpoint(idx: Idx) -> Result<(), ()> { UnsetBreakpoint { idx } => R::UnsetBreakpoint(r) => r }
+        fn get_breakpoints() -> [Option<Addr>; MAX_BREAKPOINTS] { GetBreakpoints => R::GetBreakpoints(r) => r }
+        fn get_max_breakpoints() -> Idx { GetMaxBreakpoints => R::GetMaxBreakpoints(r) => r }
+
+        fn set_memory_watchpoint(addr: Addr) -> Result<Idx, ()> { SetMemoryWatchpoint { addr } => R::SetMemoryWatchpoint(r) => r }
+        fn unset_memory_watchpoint(idx: Idx) -> Result<(), ()> { UnsetMemoryWatchpoint { idx } => R::UnsetMemoryWatchpoint(r) => r }
+        fn get_memory_watchpoints() -> [Option<(Addr, Word)>; MAX_MEMORY_WATCHPOINTS] {
+            GetMemoryWatchpoints => R::GetMemoryWatchpoints(r) => r
+        }
+        fn get_max_memory_watchpoints() -> Idx { GetMaxMemoryWatchpoints => R::GetMaxMemoryWatchpoints(r) => r }
+
+        fn set_depth_condition(condition: UnifiedRange<u64>) -> Result<Option<UnifiedRange<u64>>, ()> {
+            SetDepthCondition { condition } => R::SetDepthCondition(r) => r
+        }
+        fn unset_depth_condition() -> Option<UnifiedRange<u64>> { UnsetDepthCondition => R::UnsetDepthCondition(r) => r }
+        fn get_depth() -> Result<u64, ()> { GetDepth => R::GetDepth(r) => r }
+        fn get_call_stack(page: u16) -> CallStackPage { GetCallStack { page } => R::GetCallStack(r) => r }
+
+        fn step() -> Option<Event> { Step => R::Step(r) => r }
+        fn pause() -> () { Pause => R::Pause => () }
+        fn get_state() -> State { GetState => R::GetState(r) => r }
+        fn reset() -> () { Reset => R::Reset => () }
+        fn get_error() -> Option<Lc3Error> { GetError => R::GetError(r) => r }
+
+        fn set_fault_handling(handling: FaultHandling) -> () { SetFaultHandling { handling } => R::SetFaultHandling => () }
+        fn get_fault_handling() -> FaultHandling { GetFaultHandling => R::GetFaultHandling(r) => r }
+
+        fn get_gpio_states() -> GpioPinArr<GpioState> { GetGpioStates => R::GetGpioStates(r) => r }
+        fn get_gpio_readings() -> GpioPinArr<Result<bool, GpioReadError>> { GetGpioReadings => R::GetGpioReadings(r) => r }
+        fn get_adc_states() -> AdcPinArr<AdcState> { GetAdcStates => R::GetAdcStates(r) => r }
+        fn get_adc_readings() -> AdcPinArr<Result<u8, AdcReadError>> { GetAdcReadings => R::GetAdcReadings(r) => r }
+        fn get_timer_modes() -> TimerArr<TimerMode> { GetTimerModes => R::GetTimerModes(r) => r }
+        fn get_timer_states() -> TimerArr<TimerState> { GetTimerStates => R::GetTimerStates(r) => r }
+        fn get_pwm_states() -> PwmPinArr<PwmState> { GetPwmStates => R::GetPwmStates(r) => r }
+        fn get_pwm_config() -> PwmPinArr<PwmConfig> { GetPwmConfig => R::GetPwmConfig(r) => r }
+        fn get_clock() -> Word { GetClock => R::GetClock(r) => r }
+        fn get_interrupt_controller_state() -> InterruptControllerState {
+            GetInterruptControllerState => R::GetInterruptControllerState(r) => r
+        }
+
+        fn get_device_info() -> DeviceInfo { GetDeviceInfo => R::GetDeviceInfo(r) => r }
+
+        fn subscribe(topics: Topics) -> Topics { Subscribe { topics } => R::Subscribe(t) => t }
+
+        fn get_program_metadata() -> ProgramMetadata { GetProgramMetadata => R::GetProgramMetadata(r) => r }
+        fn set_program_metadata(metadata: ProgramMetadata) -> () { SetProgramMetadata { metadata } => R::SetProgramMetadata => () }
+    }
+}
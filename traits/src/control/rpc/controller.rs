@@ -6,32 +6,189 @@
 // trait.
 
 use super::{State, Event, Control, Transport};
-use super::messages::{RequestMessage, ResponseMessage};
+use super::messages::{RequestMessage, ResponseMessage, Envelope, SeqNum, RequestEnvelope, ResponseEnvelope, READ_WORDS_CHUNK_LEN};
 use super::encoding::{Encode, Decode, Transparent};
 use super::futures::{EventFutureSharedStatePorcelain, EventFuture};
+use super::handshake::{self, Hello, Features, HandshakeError};
+use super::hub::SessionInfo;
+use super::notifications::{self, Notification, Snapshot, Topics, CONSOLE_CHUNK_SIZE};
 use crate::control::control::{
-    MAX_BREAKPOINTS, MAX_MEMORY_WATCHPOINTS, MAX_CALL_STACK_DEPTH,
-    ProcessorMode, Idx
+    MAX_BREAKPOINTS, MAX_MEMORY_WATCHPOINTS, Idx
 };
+use crate::control::call_stack::CallStackPage;
 use crate::control::load::{
     LoadApiSession, CHUNK_SIZE_IN_WORDS, PageWriteStart, PageIndex, Offset,
     StartPageWriteError, PageChunkError, FinishPageWriteError
 };
-use crate::control::{ProgramMetadata, DeviceInfo, UnifiedRange};
+use crate::control::{ProgramMetadata, DeviceInfo, FaultHandling, UnifiedRange, InterruptControllerState};
 use crate::error::Error as Lc3Error;
 use crate::peripherals::{
     adc::{AdcPinArr, AdcState, AdcReadError},
     gpio::{GpioPinArr, GpioState, GpioReadError},
-    pwm::{PwmPinArr, PwmState},
+    pwm::{PwmConfig, PwmPinArr, PwmState},
     timers::{TimerArr, TimerMode, TimerState},
 };
 
 use lc3_isa::{Reg, Addr, Word};
 
-use core::cell::RefCell;
-use core::fmt::Debug;
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Display};
 use core::marker::PhantomData;
 use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+use core::time::Duration;
+
+/// How long a [`Controller`] waits for the response to a request before it
+/// gives up on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timeout {
+    /// Wait forever.
+    Never,
+    /// Give up after checking the transport this many times in a row without
+    /// getting a response.
+    ///
+    /// This is the only option on platforms without a clock; how long this
+    /// actually is depends on how fast the transport can be polled.
+    Polls(u32),
+    /// Give up once this much time has passed without getting a response.
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    After(Duration),
+}
+
+/// What a [`Controller`] does when the response to a request doesn't show up.
+///
+/// Requests that time out (or whose responses arrive garbled) are sent again,
+/// with the same sequence number, up to `max_retries` times; after that the
+/// request fails with [`RpcError::NoResponse`].
+///
+/// Sending an [idempotent](RequestMessage::is_idempotent) request again is
+/// always fine. For other requests we rely on the [`Device`](super::Device)
+/// noticing that it's already handled the request (it remembers the sequence
+/// number of the last request it handled and the response it sent) and just
+/// sending the response again. This doesn't hold if the device was restarted
+/// in the meantime so `retry_non_idempotent` has to be set to have such
+/// requests retried too; otherwise they fail once they time out.
+///
+/// A [`Controller`] starts out with [`RetryPolicy::NEVER_GIVE_UP`]; use
+/// [`Controller::with_retry_policy`] to opt into timeouts and retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub timeout: Timeout,
+    pub max_retries: u8,
+    pub retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    /// Waits forever on every request and never sends one twice.
+    pub const NEVER_GIVE_UP: Self = Self {
+        timeout: Timeout::Never,
+        max_retries: 0,
+        retry_non_idempotent: false,
+    };
+
+    // This policy, but with the default timeout if it would otherwise wait
+    // forever.
+    pub(super) fn bounded(self) -> Self {
+        match self.timeout {
+            Timeout::Never => Self { timeout: Self::default().timeout, ..self },
+            _ => self,
+        }
+    }
+}
+
+/// Gives up on a request after a second (or, without a clock, after polling the
+/// transport 100,000 times) and resends it up to 5 times, if it's idempotent.
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+        let timeout = Timeout::After(Duration::from_secs(1));
+        #[cfg(not(all(feature = "std", not(target_arch = "wasm32"))))]
+        let timeout = Timeout::Polls(100_000);
+
+        Self {
+            timeout,
+            max_retries: 5,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+/// The most requests a [`Controller`] will have in flight at once; see
+/// [`Controller::request_batch`].
+pub const MAX_PIPELINE_DEPTH: usize = 16;
+
+// Tracks how much of a `Timeout` is left.
+pub(super) enum Deadline {
+    Never,
+    Polls(u32),
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    At(std::time::Instant),
+}
+
+impl Deadline {
+    pub(super) fn start(timeout: Timeout) -> Self {
+        match timeout {
+            Timeout::Never => Deadline::Never,
+            Timeout::Polls(n) => Deadline::Polls(n),
+            #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+            Timeout::After(d) => Deadline::At(std::time::Instant::now() + d),
+        }
+    }
+
+    // Called each time the transport comes up empty.
+    pub(super) fn expired(&mut self) -> bool {
+        match self {
+            Deadline::Never => false,
+            Deadline::Polls(0) => true,
+            Deadline::Polls(n) => { *n -= 1; false },
+            #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+            Deadline::At(at) => std::time::Instant::now() >= *at,
+        }
+    }
+}
+
+/// Ways in which a request made through a [`Controller`] can fail.
+///
+/// The [`Control`] impl on `Controller` panics with one of these (it has no way
+/// to report errors); use the `try_*` counterparts of its methods (i.e.
+/// [`Controller::try_get_pc`]) or [`Controller::request`] to handle them
+/// instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcError<SendErr, RecvErr> {
+    /// The transport couldn't send the request.
+    SendError(SendErr),
+    /// The transport reported an error while we were waiting for a response.
+    RecvError(RecvErr),
+    /// No (intact) response came back, even after sending the request
+    /// `attempts` times.
+    NoResponse { seq: SeqNum, attempts: u16 },
+    /// This session isn't allowed to make the request; see the
+    /// [`hub`](super::hub) module.
+    NotPermitted,
+}
+
+impl<S: Debug, R: Debug> Display for RpcError<S, R> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use RpcError::*;
+
+        match self {
+            SendError(e) => write!(fmt, "failed to send request: `{:?}`", e),
+            RecvError(e) => write!(fmt, "failed to receive response: `{:?}`", e),
+            NoResponse { seq, attempts } => write!(
+                fmt,
+                "no response to request #{} (sent {} time{})",
+                seq,
+                attempts,
+                if *attempts == 1 { "" } else { "s" },
+            ),
+            NotPermitted => write!(fmt, "this session doesn't own the device"),
+        }
+    }
+}
+
+using_std! {
+    impl<S: Debug, R: Debug> std::error::Error for RpcError<S, R> { }
+}
 
 // Converts calls on the control interface to messages and sends said messages.
 //
@@ -43,6 +200,9 @@ use core::sync::atomic::{AtomicBool, Ordering};
 // you can already do this by defining an encoding layer that does the
 // conversion for you. The only thing the below buys you is being able to use
 // message types that don't implement Debug. Update: this is no longer true. (TODO)
+//
+// Messages are sent and received in `Envelope`s that carry sequence numbers;
+// see `RetryPolicy` for how these are used.
 #[derive(Debug)]
 pub struct Controller<
     'a,
@@ -50,17 +210,17 @@ pub struct Controller<
     S,
     Req = RequestMessage,
     Resp = ResponseMessage,
-    ReqEnc = Transparent<RequestMessage>,
-    RespDec = Transparent<ResponseMessage>,
+    ReqEnc = Transparent<RequestEnvelope>,
+    RespDec = Transparent<ResponseEnvelope>,
 >
 where
     Req: Debug,
     Resp: Debug,
     RequestMessage: Into<Req>,
     Resp: Into<ResponseMessage>,
-    ReqEnc: Encode<Req>,
-    RespDec: Decode<Resp>,
-    T: Transport<<ReqEnc as Encode<Req>>::Encoded, <RespDec as Decode<Resp>>::Encoded>,
+    ReqEnc: Encode<Envelope<Req>>,
+    RespDec: Decode<Envelope<Resp>>,
+    T: Transport<<ReqEnc as Encode<Envelope<Req>>>::Encoded, <RespDec as Decode<Envelope<Resp>>>::Encoded>,
     S: EventFutureSharedStatePorcelain,
 {
     _encoded_formats: PhantomData<(Req, Resp)>,
@@ -72,6 +232,40 @@ where
     shared_state: &'a S,
     waiting_for_event: AtomicBool, // TODO: no reason for this to be Atomic // Note: it's atomic so we can maintain interior mutability?
     // waiting_for_event: bool,
+    next_seq: Cell<SeqNum>,
+    pub retry_policy: RetryPolicy,
+    /// How many requests [`Controller::request_batch`] sends before waiting
+    /// for responses; capped at [`MAX_PIPELINE_DEPTH`].
+    pub pipeline_depth: usize,
+    // See the `notifications` module.
+    subscriptions: Cell<Topics>,
+    pushed: RefCell<Snapshot>,
+    // The sequence number of the last notification we got (0 for none).
+    last_notification: Cell<SeqNum>,
+    console_sink: Option<ConsoleSink<'a>>,
+    console_source: Option<ConsoleSource<'a>>,
+    // Input we've taken from the console source that the device hasn't taken
+    // yet: (len, data).
+    pending_input: Cell<(usize, [u8; CONSOLE_CHUNK_SIZE])>,
+}
+
+// Just so that `Controller` can still derive `Debug`.
+#[derive(Clone, Copy)]
+struct ConsoleSink<'a>(&'a (dyn Fn(&[u8]) + Send + Sync));
+
+impl Debug for ConsoleSink<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "ConsoleSink")
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ConsoleSource<'a>(&'a (dyn Fn(&mut [u8]) -> usize + Send + Sync));
+
+impl Debug for ConsoleSource<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "ConsoleSource")
+    }
 }
 
 // TODO: make a builder!
@@ -82,9 +276,9 @@ where
     Resp: Debug,
     RequestMessage: Into<Req>,
     Resp: Into<ResponseMessage>,
-    E: Encode<Req>,
-    D: Decode<Resp>,
-    T: Transport<<E as Encode<Req>>::Encoded, <D as Decode<Resp>>::Encoded>,
+    E: Encode<Envelope<Req>>,
+    D: Decode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Req>>>::Encoded, <D as Decode<Envelope<Resp>>>::Encoded>,
     S: EventFutureSharedStatePorcelain,
 {
     // When const functions can be in blanket impls, this can be made `const`.
@@ -104,8 +298,44 @@ where
             shared_state,
             waiting_for_event: AtomicBool::new(false),
             // waiting_for_event: false,
+            next_seq: Cell::new(0),
+            retry_policy: RetryPolicy::NEVER_GIVE_UP,
+            pipeline_depth: 8,
+            subscriptions: Cell::new(Topics::NONE),
+            pushed: RefCell::new(Snapshot::default()),
+            last_notification: Cell::new(0),
+            console_sink: None,
+            console_source: None,
+            pending_input: Cell::new((0, [0; CONSOLE_CHUNK_SIZE])),
         }
     }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_pipeline_depth(mut self, pipeline_depth: usize) -> Self {
+        self.pipeline_depth = pipeline_depth;
+        self
+    }
+
+    /// Where console output [pushed](super::notifications) by the device goes.
+    pub fn with_console_sink(mut self, sink: &'a (dyn Fn(&[u8]) + Send + Sync)) -> Self {
+        self.console_sink = Some(ConsoleSink(sink));
+        self
+    }
+
+    /// Where console input to [send](Controller::send_console_input) to the
+    /// device comes from.
+    ///
+    /// `source` is called from [`Control::tick`] and should fill the buffer
+    /// it's given with whatever input is waiting (if any) and return how many
+    /// bytes that was.
+    pub fn with_console_source(mut self, source: &'a (dyn Fn(&mut [u8]) -> usize + Send + Sync)) -> Self {
+        self.console_source = Some(ConsoleSource(source));
+        self
+    }
 }
 
 // TODO: this is a stopgap; eventually we should have an error variant on the
@@ -135,247 +365,714 @@ where
     Resp: Debug,
     RequestMessage: Into<Req>,
     Resp: Into<ResponseMessage>,
-    E: Encode<Req>,
-    D: Decode<Resp>,
-    T: Transport<<E as Encode<Req>>::Encoded, <D as Decode<Resp>>::Encoded>,
+    E: Encode<Envelope<Req>>,
+    D: Decode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Req>>>::Encoded, <D as Decode<Envelope<Resp>>>::Encoded>,
     S: EventFutureSharedStatePorcelain,
 {
-    // For now, we're going to assume sequential consistency (we receive
-    // responses to messages in the same order we filed the requests). (TODO)
+    // We only ever have one request in flight so responses either belong to
+    // the current request or are stale (responses to earlier attempts).
     //
     // Responses to our one non-blocking call (`run_until_event`) are the only
     // thing that could interrupt this.
-    fn tick(&self) -> TickAttempt<ResponseMessage, D::Err, T::RecvErr> {
+    fn tick(&self) -> TickAttempt<Envelope<ResponseMessage>, D::Err, T::RecvErr> {
         let encoded_message = self.transport.get()
             .map_err(|e| e.map(|inner| TickError::TransportError(inner)))?;
 
-        let message = self.dec.borrow_mut()
+        let Envelope { seq, message } = self.dec.borrow_mut()
             .decode(&encoded_message)
-            .map_err(|d| Some(TickError::DecodeError(d)))?; // TODO: do better?
+            .map_err(|d| {
+                self.transport.record_get_error();
+
+                // This may have been a notification:
+                self.pushed.borrow_mut().forget(Topics::ALL);
+
+                Some(TickError::DecodeError(d))
+            })?; // TODO: do better?
 
         let message = message.into();
 
-        if let ResponseMessage::RunUntilEvent(event) = message {
-            if self.waiting_for_event.load(Ordering::SeqCst) {
-                // println!("resolving the rpc future"); // TODO: logging w/feature flag
-                self.shared_state.resolve_all(event).unwrap();
-                self.waiting_for_event.store(false, Ordering::SeqCst);
+        match message {
+            ResponseMessage::RunUntilEvent(event) => {
+                if self.waiting_for_event.load(Ordering::SeqCst) {
+                    // println!("resolving the rpc future"); // TODO: logging w/feature flag
+                    self.shared_state.resolve_all(event).unwrap();
+                    self.waiting_for_event.store(false, Ordering::SeqCst);
+                } else {
+                    // We were told an event happened but we never asked (or
+                    // already heard about it).
+                    log::warn!("Ignoring unrequested event (#{}): `{:?}`", seq, event);
+                }
 
                 /*NoMessage*/ Err(None)
-            } else {
-                // Something has gone very wrong.
-                // We were told an event happened but we never asked.
-                unreachable!()
+            },
+            ResponseMessage::Notification(n) => {
+                self.check_notification_seq(seq);
+                self.handle_notification(n);
+
+                /*NoMessage*/ Err(None)
+            },
+            message => Ok(Envelope { seq, message }),
+        }
+    }
+
+    // If we've missed notifications, the pushed values can't be trusted until
+    // they're pushed again; until then we ask the device.
+    fn check_notification_seq(&self, seq: SeqNum) {
+        let last = self.last_notification.replace(seq);
+
+        if seq != 0 && last != 0 && seq != notifications::next_seq(last) {
+            log::warn!("Missed notifications (got #{} after #{}); forgetting pushed values", seq, last);
+            self.pushed.borrow_mut().forget(Topics::ALL);
+        }
+    }
+
+    fn handle_notification(&self, n: Notification) {
+        if !self.subscriptions.get().contains(n.topic()) {
+            log::trace!("Ignoring notification we didn't subscribe to: `{:?}`", n);
+            return;
+        }
+
+        match n {
+            Notification::Console { len, data } => match self.console_sink {
+                Some(ConsoleSink(sink)) => sink(&data[..(len as usize)]),
+                None => log::warn!("Dropping console output; no console sink"),
+            },
+            n => self.pushed.borrow_mut().update(&n),
+        }
+    }
+
+    // Handles whatever's come in (i.e. notifications) without blocking.
+    fn drain(&self) -> Result<(), RpcError<T::SendErr, T::RecvErr>> {
+        loop {
+            match self.tick() {
+                Err(None) => break Ok(()),
+                Ok(Envelope { seq, message }) => {
+                    log::trace!("Ignoring stale response (#{}): `{:?}`", seq, message);
+                },
+                Err(Some(TickError::DecodeError(e))) => log::trace!("Decode Error: `{:?}`", e),
+                Err(Some(TickError::TransportError(e))) => break Err(RpcError::RecvError(e)),
             }
-        } else {
-            Ok(message)
         }
     }
-}
 
+    // The latest pushed value for `topic` (if we're subscribed to it and we
+    // have one).
+    fn pushed<R>(
+        &self,
+        topic: Topics,
+        func: impl FnOnce(&Snapshot) -> Option<R>,
+    ) -> Result<Option<R>, RpcError<T::SendErr, T::RecvErr>> {
+        if !self.subscriptions.get().contains(topic) {
+            return Ok(None);
+        }
 
-macro_rules! ctrl {
-    ($s:ident, $req:expr, $resp:pat$(, $ret:expr)?) => {{
-        use RequestMessage::*;
-        use ResponseMessage as R;
-        let m = $req.into();
+        self.drain()?;
+        Ok(func(&self.pushed.borrow()))
+    }
 
-        $s.transport.send($s.enc.borrow_mut().encode(&m)).unwrap(); // TODO: don't panic? not sure how we'd realistically deal with any transport errors..
+    /// Sends a request to the device and waits for the response, retrying as
+    /// the [retry policy](RetryPolicy) allows.
+    ///
+    /// Unlike the [`Control`] impl, this reports failures instead of panicking.
+    pub fn request(&self, message: RequestMessage) -> Result<ResponseMessage, RpcError<T::SendErr, T::RecvErr>> {
+        let seq = self.next_seq.get();
+        self.next_seq.set(seq.wrapping_add(1));
 
+        let policy = self.retry_policy;
+        let retryable = policy.retry_non_idempotent || message.is_idempotent();
+        let envelope = Envelope::new(seq, message.into());
+
+        let mut attempts: u16 = 0;
         loop {
-            match Controller::tick($s) {
-                // If we got a message, process it:
-                Ok(m) => {
-                    if let $resp = m {
-                        break $($ret)?
-                    } else {
-                        panic!("Incorrect response for message!")
+            // TODO: because send _consumes_ the message we have to do the
+            // encode here. On the one hand having the transport consume the
+            // message should allow for good zero-copy impls but on the other
+            // hand it means we can't cache the encode in situations like
+            // these... Not sure what the right tradeoff is.
+            self.transport.send(self.enc.borrow_mut().encode(&envelope))
+                .map_err(RpcError::SendError)?;
+            attempts += 1;
+
+            let mut deadline = Deadline::start(policy.timeout);
+            loop {
+                match Controller::tick(self) {
+                    Ok(Envelope { seq: s, message }) if s == seq => return Ok(message),
+
+                    // A response to a request we've already given up on or a
+                    // duplicate response to this request; either way, skip it:
+                    Ok(Envelope { seq: s, message }) => {
+                        log::trace!("Ignoring stale response (#{}, expected #{}): `{:?}`", s, seq, message);
+                    },
+
+                    // If we got no message, try, try again (for a while):
+                    Err(None) => if deadline.expired() { break },
+
+                    // If we got a transport error, bail:
+                    Err(Some(TickError::TransportError(e))) => return Err(RpcError::RecvError(e)),
+
+                    // If we got a decode error, assume a problem in
+                    // transmission and (if we can) ask again right away.
+                    Err(Some(TickError::DecodeError(e))) => {
+                        log::trace!("Decode Error: `{:?}`", e);
+                        if retryable { break }
                     }
-                },
+                }
+            }
+
+            if !retryable || attempts > policy.max_retries as u16 {
+                return Err(RpcError::NoResponse { seq, attempts });
+            }
+
+            log::debug!("Retrying request #{} (attempt {})", seq, attempts + 1);
+        }
+    }
 
-                // If we got no message, try, try again:
-                Err(None) => { },
+    /// Sends a batch of requests, keeping up to [`pipeline_depth`] of them in
+    /// flight at once, and calls `on_response` with the index (in `requests`)
+    /// and response of each as it comes in.
+    ///
+    /// Responses can arrive in any order. Requests that time out are sent
+    /// again as the [retry policy](RetryPolicy) allows; if any request runs
+    /// out of retries the batch stops there (`on_response` won't be called for
+    /// the requests that were still in flight or that weren't sent yet).
+    ///
+    /// The device only remembers the *last* request it handled so retrying a
+    /// request that [isn't idempotent](RequestMessage::is_idempotent) is only
+    /// safe when it's the only one in flight: such requests wait for the
+    /// requests ahead of them to finish and are then sent on their own.
+    ///
+    /// [`pipeline_depth`]: Controller::pipeline_depth
+    pub fn request_batch<I>(
+        &self,
+        requests: I,
+        mut on_response: impl FnMut(usize, ResponseMessage),
+    ) -> Result<(), RpcError<T::SendErr, T::RecvErr>>
+    where
+        I: IntoIterator<Item = RequestMessage>,
+    {
+        let depth = self.pipeline_depth.max(1).min(MAX_PIPELINE_DEPTH);
+        let policy = self.retry_policy;
 
-                // If we got a transport error, bail:
-                Err(Some(TickError::TransportError(e))) => panic!("Transport error! `{:?}`", e),
+        let mut requests = requests.into_iter().enumerate().peekable();
 
-                // If we got a decode error, assume a problem in transmission
-                // and try again.
-                Err(Some(TickError::DecodeError(e))) => {
-                    log::trace!("Decode Error: `{:?}`", e);
+        // (index in the batch, the request, times sent)
+        let mut in_flight: [Option<(usize, Envelope<Req>, u16)>; MAX_PIPELINE_DEPTH] = Default::default();
+        let mut num_in_flight = 0;
 
-                    // TODO: because send _consumes_ the message we have to do
-                    // the encode here. On the one hand having the transport
-                    // consume the message should allow for good zero-copy
-                    // impls but on the other hand it means we can't cache the
-                    // encode in situations like these... Not sure what the
-                    // right tradeoff is.
-                    $s.transport.send($s.enc.borrow_mut().encode(&m)).unwrap(); // TODO: don't panic?
+        loop {
+            // Fill up the pipeline:
+            while num_in_flight < depth {
+                let (idx, message) = match requests.peek() {
+                    None => break,
+                    Some((_, m)) if !m.is_idempotent() => {
+                        if num_in_flight > 0 { break }
+
+                        let (idx, message) = requests.next().unwrap();
+                        on_response(idx, self.request(message)?);
+                        continue;
+                    },
+                    Some(_) => requests.next().unwrap(),
+                };
+
+                let seq = self.next_seq.get();
+                self.next_seq.set(seq.wrapping_add(1));
+
+                let envelope = Envelope::new(seq, message.into());
+                self.transport.send(self.enc.borrow_mut().encode(&envelope))
+                    .map_err(RpcError::SendError)?;
+
+                let slot = in_flight.iter_mut().find(|s| s.is_none()).unwrap();
+                *slot = Some((idx, envelope, 1));
+                num_in_flight += 1;
+            }
+
+            if num_in_flight == 0 {
+                // Nothing in flight and nothing left to send.
+                return Ok(());
+            }
+
+            // Wait for (at least) one response:
+            let mut deadline = Deadline::start(policy.timeout);
+            loop {
+                match Controller::tick(self) {
+                    Ok(Envelope { seq, message }) => {
+                        let slot = in_flight.iter_mut()
+                            .find(|s| matches!(s, Some((_, e, _)) if e.seq == seq));
+
+                        if let Some(slot) = slot {
+                            let (idx, _, _) = slot.take().unwrap();
+                            num_in_flight -= 1;
+
+                            on_response(idx, message);
+                            break;
+                        } else {
+                            log::trace!("Ignoring stale response (#{}): `{:?}`", seq, message);
+                        }
+                    },
+
+                    // Everything that's still in flight is idempotent so we
+                    // can just send it all again:
+                    Err(None) => if deadline.expired() {
+                        for (_, envelope, attempts) in in_flight.iter_mut().flatten() {
+                            if *attempts > policy.max_retries as u16 {
+                                return Err(RpcError::NoResponse { seq: envelope.seq, attempts: *attempts });
+                            }
+
+                            log::debug!("Retrying request #{} (attempt {})", envelope.seq, *attempts + 1);
+                            self.transport.send(self.enc.borrow_mut().encode(envelope))
+                                .map_err(RpcError::SendError)?;
+                            *attempts += 1;
+                        }
+
+                        deadline = Deadline::start(policy.timeout);
+                    },
+
+                    Err(Some(TickError::TransportError(e))) => return Err(RpcError::RecvError(e)),
+
+                    // We can't tell which request this was a response to so
+                    // we wait for the timeout to resend it.
+                    Err(Some(TickError::DecodeError(e))) => log::trace!("Decode Error: `{:?}`", e),
                 }
             }
         }
+    }
+
+    /// Checks that the device speaks a compatible version of the protocol and
+    /// agrees on which optional [features](Features) to use.
+    ///
+    /// This should be the first thing sent in a session. If the device turns
+    /// out not to support [duplicate detection](Features::DUPLICATE_DETECTION),
+    /// the [retry policy](RetryPolicy) is changed to not retry requests that
+    /// aren't idempotent.
+    ///
+    /// Devices that don't answer at all are assumed to predate the handshake
+    /// and to support none of the optional features; see the
+    /// [`handshake`](super::handshake) module.
+    pub fn handshake(&mut self) -> Result<Features, HandshakeError<T::SendErr, T::RecvErr>> {
+        // Devices that predate the handshake never answer it so we can't wait
+        // on it forever:
+        let policy = self.retry_policy;
+        self.retry_policy = policy.bounded();
+        let answer = self.request(RequestMessage::Handshake { hello: Hello::OURS });
+        self.retry_policy = policy;
+
+        let features = handshake::check_answer(answer)?;
+
+        if !features.contains(Features::DUPLICATE_DETECTION) {
+            self.retry_policy.retry_non_idempotent = false;
+        }
+
+        Ok(features)
+    }
+
+    /// Asks the device what this session is allowed to do and how many
+    /// changes have been made to the device so far; see the [`hub`](super::hub)
+    /// module.
+    pub fn session_info(&self) -> Result<SessionInfo, RpcError<T::SendErr, T::RecvErr>> {
+        match self.request(RequestMessage::GetSessionInfo)? {
+            ResponseMessage::GetSessionInfo(info) => Ok(info),
+            other => panic!("Incorrect response for message! `{:?}`", other),
+        }
+    }
+
+    /// Asks the device to push [notifications](super::notifications) for the
+    /// given topics (and to stop pushing the rest).
+    ///
+    /// Once subscribed, the corresponding [`Control`] calls (`get_state`,
+    /// `get_gpio_states`, etc.) are answered with the pushed values instead of
+    /// going to the device. Console output goes to the [console
+    /// sink](Controller::with_console_sink).
+    pub fn subscribe(&self, topics: Topics) -> Result<Topics, RpcError<T::SendErr, T::RecvErr>> {
+        // The current values for the new topics arrive before the response
+        // does so we have to be ready for them:
+        let previous = self.subscriptions.get();
+        self.subscriptions.set(previous.union(topics));
+
+        let granted = match self.request(RequestMessage::Subscribe { topics }) {
+            Ok(ResponseMessage::Subscribe(granted)) => granted,
+            Ok(other) => panic!("Incorrect response for message! `{:?}`", other),
+            Err(e) => {
+                self.subscriptions.set(previous);
+                return Err(e);
+            },
+        };
+
+        self.subscriptions.set(granted);
+        self.pushed.borrow_mut().forget(Topics::ALL.difference(granted));
+
+        Ok(granted)
+    }
+
+    /// Sends console input to the device, which holds on to it until it's
+    /// handed to the input peripheral (see [`Device::take_console_input`]).
+    ///
+    /// Returns how many bytes the device took; devices only hold on to so much
+    /// input so the rest has to be sent again later.
+    ///
+    /// [`Device::take_console_input`]: super::Device::take_console_input
+    pub fn send_console_input(&self, bytes: &[u8]) -> Result<usize, RpcError<T::SendErr, T::RecvErr>> {
+        let mut sent = 0;
+
+        for chunk in bytes.chunks(CONSOLE_CHUNK_SIZE) {
+            let mut data = [0; CONSOLE_CHUNK_SIZE];
+            data[..chunk.len()].copy_from_slice(chunk);
+
+            let taken = match self.request(RequestMessage::ConsoleInput { len: chunk.len() as u8, data })? {
+                ResponseMessage::ConsoleInput(taken) => taken as usize,
+                ResponseMessage::NotPermitted => return Err(RpcError::NotPermitted),
+                other => panic!("Incorrect response for message! `{:?}`", other),
+            };
+
+            sent += taken;
+            if taken < chunk.len() {
+                break;
+            }
+        }
+
+        Ok(sent)
+    }
+
+    // Sends along whatever the console source has for us.
+    fn forward_console_input(&self) {
+        let source = if let Some(ConsoleSource(s)) = self.console_source { s } else { return };
+
+        let (mut len, mut data) = self.pending_input.get();
+        len += source(&mut data[len..]).min(CONSOLE_CHUNK_SIZE - len);
+
+        if len != 0 {
+            match self.send_console_input(&data[..len]) {
+                Ok(taken) => {
+                    data.copy_within(taken..len, 0);
+                    len -= taken;
+                },
+                // We'll try again on the next tick.
+                Err(e) => log::warn!("Failed to send console input: {}", e),
+            }
+        }
+
+        self.pending_input.set((len, data));
+    }
+}
+
+
+macro_rules! try_ctrl {
+    ($s:ident, $req:expr, $resp:pat$(, $ret:expr)?) => {{
+        use RequestMessage::*;
+        use ResponseMessage as R;
+
+        match $s.request($req)? {
+            $resp => Ok({ $($ret)? }),
+            R::NotPermitted => Err(RpcError::NotPermitted),
+            other => panic!("Incorrect response for message! `{:?}`", other),
+        }
     }};
 }
 
+// Fallible counterparts to the `Control` impl (which panics when requests
+// fail).
 #[forbid(irrefutable_let_patterns)]
-impl<'a, Req, Resp, E, D, T, S> Control for Controller<'a, T, S, Req, Resp, E, D>
+impl<'a, Req, Resp, E, D, T, S> Controller<'a, T, S, Req, Resp, E, D>
 where
     Req: Debug,
     Resp: Debug,
     RequestMessage: Into<Req>,
     Resp: Into<ResponseMessage>,
-    E: Encode<Req>,
-    D: Decode<Resp>,
-    T: Transport<<E as Encode<Req>>::Encoded, <D as Decode<Resp>>::Encoded>,
+    E: Encode<Envelope<Req>>,
+    D: Decode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Req>>>::Encoded, <D as Decode<Envelope<Resp>>>::Encoded>,
     S: EventFutureSharedStatePorcelain,
 {
-    type EventFuture = EventFuture<'a, S>;
+    pub fn try_get_pc(&self) -> Result<Addr, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetPc, R::GetPc(addr), addr) }
+    pub fn try_set_pc(&mut self, addr: Addr) -> Result<(), RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, SetPc { addr }, R::SetPc) }
 
-    fn get_pc(&self) -> Addr { ctrl!(self, GetPc, R::GetPc(addr), addr) }
-    fn set_pc(&mut self, addr: Addr) { ctrl!(self, SetPc { addr }, R::SetPc) }
+    pub fn try_get_register(&self, reg: Reg) -> Result<Word, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetRegister { reg }, R::GetRegister(word), word) }
+    pub fn try_set_register(&mut self, reg: Reg, data: Word) -> Result<(), RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, SetRegister { reg, data }, R::SetRegister) }
 
-    fn get_register(&self, reg: Reg) -> Word { ctrl!(self, GetRegister { reg }, R::GetRegister(word), word) }
-    fn set_register(&mut self, reg: Reg, data: Word) { ctrl!(self, SetRegister { reg, data }, R::SetRegister) }
-
-    fn get_registers_psr_and_pc(&self) -> ([Word; Reg::NUM_REGS], Word, Word) {
-        ctrl!(self, GetRegistersPsrAndPc, R::GetRegistersPsrAndPc(r), r)
+    pub fn try_get_registers_psr_and_pc(&self) -> Result<([Word; Reg::NUM_REGS], Word, Word), RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, GetRegistersPsrAndPc, R::GetRegistersPsrAndPc(r), r)
     }
 
-    fn read_word(&self, addr: Addr) -> Word { ctrl!(self, ReadWord { addr }, R::ReadWord(w), w) }
-    fn write_word(&mut self, addr: Addr, word: Word) { ctrl!(self, WriteWord { addr, word }, R::WriteWord) }
+    pub fn try_read_word(&self, addr: Addr) -> Result<Word, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, ReadWord { addr }, R::ReadWord(w), w) }
+    pub fn try_write_word(&mut self, addr: Addr, word: Word) -> Result<(), RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, WriteWord { addr, word }, R::WriteWord) }
 
-    fn start_page_write(&mut self, page: LoadApiSession<PageWriteStart>, checksum: u64) -> Result<LoadApiSession<u8>, StartPageWriteError> {
-        ctrl!(self, StartPageWrite { page, checksum }, R::StartPageWrite(r), r)
+    // One `ReadWords` request per chunk, all pipelined.
+    pub fn try_read_words(&self, start: Addr, out: &mut [Word]) -> Result<(), RpcError<T::SendErr, T::RecvErr>> {
+        let len = out.len();
+        let chunk_len = |offset: usize| (len - offset).min(READ_WORDS_CHUNK_LEN);
+
+        let requests = (0..len).step_by(READ_WORDS_CHUNK_LEN).map(|offset| RequestMessage::ReadWords {
+            start: start.wrapping_add(offset as Addr),
+            len: chunk_len(offset) as u8,
+        });
+
+        let mut permitted = true;
+        self.request_batch(requests, |idx, resp| match resp {
+            ResponseMessage::ReadWords(words) => {
+                let offset = idx * READ_WORDS_CHUNK_LEN;
+                let n = chunk_len(offset);
+
+                out[offset..(offset + n)].copy_from_slice(&words[..n]);
+            },
+            ResponseMessage::NotPermitted => permitted = false,
+            other => panic!("Incorrect response for message! `{:?}`", other),
+        })?;
+
+        if permitted { Ok(()) } else { Err(RpcError::NotPermitted) }
     }
-    fn send_page_chunk(&mut self, offset: LoadApiSession<Offset>, chunk: [Word; CHUNK_SIZE_IN_WORDS as usize]) -> Result<(), PageChunkError> {
-        ctrl!(self, SendPageChunk { offset, chunk }, R::SendPageChunk(r), r)
+
+    pub fn try_start_page_write(&mut self, page: LoadApiSession<PageWriteStart>, checksum: u64) -> Result<Result<LoadApiSession<u8>, StartPageWriteError>, RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, StartPageWrite { page, checksum }, R::StartPageWrite(r), r)
     }
-    fn finish_page_write(&mut self, page: LoadApiSession<PageIndex>) -> Result<(), FinishPageWriteError> {
-        ctrl!(self, FinishPageWrite { page }, R::FinishPageWrite(r), r)
+    pub fn try_send_page_chunk(&mut self, offset: LoadApiSession<Offset>, chunk: [Word; CHUNK_SIZE_IN_WORDS as usize]) -> Result<Result<(), PageChunkError>, RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, SendPageChunk { offset, chunk }, R::SendPageChunk(r), r)
+    }
+    pub fn try_finish_page_write(&mut self, page: LoadApiSession<PageIndex>) -> Result<Result<(), FinishPageWriteError>, RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, FinishPageWrite { page }, R::FinishPageWrite(r), r)
     }
 
-    fn set_breakpoint(&mut self, addr: Addr) -> Result<Idx, ()> {
-        ctrl!(self, SetBreakpoint { addr }, R::SetBreakpoint(r), r)
+    pub fn try_set_breakpoint(&mut self, addr: Addr) -> Result<Result<Idx, ()>, RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, SetBreakpoint { addr }, R::SetBreakpoint(r), r)
     }
-    fn unset_breakpoint(&mut self, idx: Idx) -> Result<(), ()> {
-        ctrl!(self, UnsetBreakpoint { idx }, R::UnsetBreakpoint(r), r)
+    pub fn try_unset_breakpoint(&mut self, idx: Idx) -> Result<Result<(), ()>, RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, UnsetBreakpoint { idx }, R::UnsetBreakpoint(r), r)
     }
-    fn get_breakpoints(&self) -> [Option<Addr>; MAX_BREAKPOINTS] { ctrl!(self, GetBreakpoints, R::GetBreakpoints(r), r) }
-    fn get_max_breakpoints(&self) -> Idx { ctrl!(self, GetMaxBreakpoints, R::GetMaxBreakpoints(r), r) }
+    pub fn try_get_breakpoints(&self) -> Result<[Option<Addr>; MAX_BREAKPOINTS], RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetBreakpoints, R::GetBreakpoints(r), r) }
+    pub fn try_get_max_breakpoints(&self) -> Result<Idx, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetMaxBreakpoints, R::GetMaxBreakpoints(r), r) }
 
-    fn set_memory_watchpoint(&mut self, addr: Addr) -> Result<Idx, ()> {
-        ctrl!(self, SetMemoryWatchpoint { addr }, R::SetMemoryWatchpoint(r), r)
+    pub fn try_set_memory_watchpoint(&mut self, addr: Addr) -> Result<Result<Idx, ()>, RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, SetMemoryWatchpoint { addr }, R::SetMemoryWatchpoint(r), r)
     }
-    fn unset_memory_watchpoint(&mut self, idx: Idx) -> Result<(), ()> {
-        ctrl!(self, UnsetMemoryWatchpoint { idx }, R::UnsetMemoryWatchpoint(r), r)
+    pub fn try_unset_memory_watchpoint(&mut self, idx: Idx) -> Result<Result<(), ()>, RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, UnsetMemoryWatchpoint { idx }, R::UnsetMemoryWatchpoint(r), r)
     }
-    fn get_memory_watchpoints(&self) -> [Option<(Addr, Word)>; MAX_MEMORY_WATCHPOINTS] { ctrl!(self, GetMemoryWatchpoints, R::GetMemoryWatchpoints(r), r) }
-    fn get_max_memory_watchpoints(&self) -> Idx { ctrl!(self, GetMaxMemoryWatchpoints, R::GetMaxMemoryWatchpoints(r), r) }
+    pub fn try_get_memory_watchpoints(&self) -> Result<[Option<(Addr, Word)>; MAX_MEMORY_WATCHPOINTS], RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetMemoryWatchpoints, R::GetMemoryWatchpoints(r), r) }
+    pub fn try_get_max_memory_watchpoints(&self) -> Result<Idx, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetMaxMemoryWatchpoints, R::GetMaxMemoryWatchpoints(r), r) }
 
-    fn set_depth_condition(&mut self, condition: UnifiedRange<u64>) -> Result<Option<UnifiedRange<u64>>, ()> {
-        ctrl!(self, SetDepthCondition { condition }, R::SetDepthCondition(r), r)
+    pub fn try_set_depth_condition(&mut self, condition: UnifiedRange<u64>) -> Result<Result<Option<UnifiedRange<u64>>, ()>, RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, SetDepthCondition { condition }, R::SetDepthCondition(r), r)
     }
-    fn unset_depth_condition(&mut self) -> Option<UnifiedRange<u64>> { ctrl!(self, UnsetDepthCondition, R::UnsetDepthCondition(r), r) }
-    fn get_depth(&self) -> Result<u64, ()> { ctrl!(self, GetDepth, R::GetDepth(r), r) }
-    fn get_call_stack(&self) -> [Option<(Addr, ProcessorMode)>; MAX_CALL_STACK_DEPTH] {
-        ctrl!(self, GetCallStack, R::GetCallStack(r), r)
+    pub fn try_unset_depth_condition(&mut self) -> Result<Option<UnifiedRange<u64>>, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, UnsetDepthCondition, R::UnsetDepthCondition(r), r) }
+    pub fn try_get_depth(&self) -> Result<Result<u64, ()>, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetDepth, R::GetDepth(r), r) }
+    pub fn try_get_call_stack(&self, page: u16) -> Result<CallStackPage, RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, GetCallStack { page }, R::GetCallStack(r), r)
     }
 
-    // Execution control functions:
-    fn run_until_event(&mut self) -> Self::EventFuture {
+    /// Note that the future is counted (in the shared state) before the
+    /// request is sent; if this fails, futures in the same batch won't resolve.
+    pub fn try_run_until_event(&mut self) -> Result<EventFuture<'a, S>, RpcError<T::SendErr, T::RecvErr>> {
         // If we're in a sealed batch with pending futures, just crash.
         self.shared_state.add_new_future().expect("no new futures once a batch starts to resolve");
 
-        // TODO: factor out this code; it's a copy of that in the ctrl! macro.
-
-        let m = RequestMessage::RunUntilEvent.into();
-
         // If we're already waiting for an event, don't bother sending the
         // request along again:
         if !self.waiting_for_event.load(Ordering::SeqCst) {
-            self.transport.send(self.enc.borrow_mut().encode(&m)).unwrap();
-
-            // Wait for the acknowledge:
-            loop {
-                match Controller::tick(self) {
-                    Ok(m) => if let ResponseMessage::RunUntilEventAck = m {
-                        break;
-                    } else {
-                        panic!("Incorrect response for message!")
-                    }
-
-                    Err(None) => {},
-
-                    Err(Some(TickError::TransportError(e))) => {
-                        panic!("Transport Error! {:?}", e)
-                    },
+            // Note that we start waiting *before* the request is acknowledged;
+            // if the acknowledgement gets lost, the event may very well arrive
+            // before the acknowledgement for our retried request does.
+            self.waiting_for_event.store(true, Ordering::SeqCst);
 
-                    Err(Some(TickError::DecodeError(e))) => {
-                        log::trace!("Decode Error: {:?}", e);
-                        self.transport.send(self.enc.borrow_mut().encode(&m)).unwrap();
-                    },
-                }
+            let res = try_ctrl!(self, RunUntilEvent, R::RunUntilEventAck);
+            if res.is_err() {
+                self.waiting_for_event.store(false, Ordering::SeqCst);
             }
-
-            self.waiting_for_event.store(true, Ordering::SeqCst);
+            res?
         }
 
-        // println!("new rpc future");
-
-        EventFuture(self.shared_state)
+        Ok(EventFuture(self.shared_state))
     }
 
-    fn tick(&mut self) -> usize {
+    pub fn try_tick(&mut self) -> Result<(), RpcError<T::SendErr, T::RecvErr>> {
         // Because we basically call tick() on every other function call here, we could
         // probably get away with just doing nothing here in practice.
         // But, checking here as well doesn't hurt.
         //
-        // We should never actually get a message here (run until event responses are
-        // handled within `Self::tick()`) though.
-        // Self::tick(self).unwrap_none(); // when this goes stable, use this, maybe (TODO)
-        if let Err(None) = Self::tick(self) {
-            /* We expect to get nothing here. */
-        } else {
-            panic!("Controller received a message in tick!")
-        }
+        // The only messages we should get here are run until event responses
+        // (handled within `Self::tick()`) and stale responses to requests that
+        // we've already given up on.
+        self.drain()?;
+        self.forward_console_input();
 
-        // This function can (probably, TODO) be safely _not_ called so we
-        // return 0:
-        0
+        Ok(())
     }
 
-    fn step(&mut self) -> Option<Event> { ctrl!(self, Step, R::Step(r), r) }
-    fn pause(&mut self) { ctrl!(self, Pause, R::Pause) }
+    pub fn try_step(&mut self) -> Result<Option<Event>, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, Step, R::Step(r), r) }
+    pub fn try_pause(&mut self) -> Result<(), RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, Pause, R::Pause) }
 
-    fn get_state(&self) -> State { ctrl!(self, GetState, R::GetState(r), r) }
+    pub fn try_get_state(&self) -> Result<State, RpcError<T::SendErr, T::RecvErr>> {
+        if let Some(s) = self.pushed(Topics::STATE, |p| p.state)? { return Ok(s); }
+        try_ctrl!(self, GetState, R::GetState(r), r)
+    }
 
-    fn reset(&mut self) {
+    pub fn try_reset(&mut self) -> Result<(), RpcError<T::SendErr, T::RecvErr>> {
         // For now, we won't force all futures to have resolved on a reset.
         // We're still calling reset here (currently a no-op) because eventually
         // this should advance the batch counter (though that may happen on
         // set_event, rendering this function entirely unnecessary).
         self.shared_state.reset();
 
-        ctrl!(self, Reset, R::Reset)
+        try_ctrl!(self, Reset, R::Reset)
+    }
+
+    pub fn try_get_error(&self) -> Result<Option<Lc3Error>, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetError, R::GetError(r), r) }
+
+    pub fn try_set_fault_handling(&mut self, handling: FaultHandling) -> Result<(), RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, SetFaultHandling { handling }, R::SetFaultHandling) }
+    pub fn try_get_fault_handling(&self) -> Result<FaultHandling, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetFaultHandling, R::GetFaultHandling(r), r) }
+
+    pub fn try_get_gpio_states(&self) -> Result<GpioPinArr<GpioState>, RpcError<T::SendErr, T::RecvErr>> {
+        if let Some(s) = self.pushed(Topics::GPIO, |p| p.gpio_states.clone())? { return Ok(s); }
+        try_ctrl!(self, GetGpioStates, R::GetGpioStates(r), r)
+    }
+    pub fn try_get_gpio_readings(&self) -> Result<GpioPinArr<Result<bool, GpioReadError>>, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetGpioReadings, R::GetGpioReadings(r), r) }
+    pub fn try_get_adc_states(&self) -> Result<AdcPinArr<AdcState>, RpcError<T::SendErr, T::RecvErr>> {
+        if let Some(s) = self.pushed(Topics::ADC, |p| p.adc_states.clone())? { return Ok(s); }
+        try_ctrl!(self, GetAdcStates, R::GetAdcStates(r), r)
+    }
+    pub fn try_get_adc_readings(&self) -> Result<AdcPinArr<Result<u8, AdcReadError>>, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetAdcReadings, R::GetAdcReadings(r), r) }
+    pub fn try_get_timer_modes(&self) -> Result<TimerArr<TimerMode>, RpcError<T::SendErr, T::RecvErr>> {
+        if let Some(m) = self.pushed(Topics::TIMERS, |p| p.timer_modes.clone())? { return Ok(m); }
+        try_ctrl!(self, GetTimerModes, R::GetTimerModes(r), r)
+    }
+    pub fn try_get_timer_states(&self) -> Result<TimerArr<TimerState>, RpcError<T::SendErr, T::RecvErr>> {
+        if let Some(s) = self.pushed(Topics::TIMERS, |p| p.timer_states.clone())? { return Ok(s); }
+        try_ctrl!(self, GetTimerStates, R::GetTimerStates(r), r)
+    }
+    pub fn try_get_pwm_states(&self) -> Result<PwmPinArr<PwmState>, RpcError<T::SendErr, T::RecvErr>> {
+        let states = |c: &PwmPinArr<PwmConfig>| PwmPinArr(c.0.map(|c| c.state));
+        if let Some(s) = self.pushed(Topics::PWM, |p| p.pwm_config.as_ref().map(states))? { return Ok(s); }
+        try_ctrl!(self, GetPwmStates, R::GetPwmStates(r), r)
+    }
+    pub fn try_get_pwm_config(&self) -> Result<PwmPinArr<PwmConfig>, RpcError<T::SendErr, T::RecvErr>> {
+        if let Some(c) = self.pushed(Topics::PWM, |p| p.pwm_config.clone())? { return Ok(c); }
+        try_ctrl!(self, GetPwmConfig, R::GetPwmConfig(r), r)
+    }
+    pub fn try_get_clock(&self) -> Result<Word, RpcError<T::SendErr, T::RecvErr>> {
+        if let Some(c) = self.pushed(Topics::CLOCK, |p| p.clock)? { return Ok(c); }
+        try_ctrl!(self, GetClock, R::GetClock(r), r)
+    }
+    pub fn try_get_interrupt_controller_state(&self) -> Result<InterruptControllerState, RpcError<T::SendErr, T::RecvErr>> {
+        try_ctrl!(self, GetInterruptControllerState, R::GetInterruptControllerState(r), r)
+    }
+
+    pub fn try_get_device_info(&self) -> Result<DeviceInfo, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetDeviceInfo, R::GetDeviceInfo(r), r) }
+
+    pub fn try_get_program_metadata(&self) -> Result<ProgramMetadata, RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, GetProgramMetadata, R::GetProgramMetadata(r), r) }
+    pub fn try_set_program_metadata(&mut self, metadata: ProgramMetadata) -> Result<(), RpcError<T::SendErr, T::RecvErr>> { try_ctrl!(self, SetProgramMetadata { metadata }, R::SetProgramMetadata) }
+}
+
+// `Control` has no way to report errors so the impl below panics with them;
+// use the `try_*` methods above to handle them instead.
+#[track_caller]
+fn ctrl<R, S: Debug, E: Debug>(res: Result<R, RpcError<S, E>>) -> R {
+    match res {
+        Ok(r) => r,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+// Requests that change the device can be turned down when this session doesn't
+// own it (see `Hub`). That's expected of viewers so rather than panicking we
+// log it and return `denied`; the `try_*` methods report it properly.
+#[track_caller]
+fn ctrl_or<R, S: Debug, E: Debug>(res: Result<R, RpcError<S, E>>, denied: R) -> R {
+    match res {
+        Err(RpcError::NotPermitted) => {
+            log::warn!("request ignored; this session doesn't own the device (see `Hub`)");
+            denied
+        },
+        res => ctrl(res),
+    }
+}
+
+impl<'a, Req, Resp, E, D, T, S> Control for Controller<'a, T, S, Req, Resp, E, D>
+where
+    Req: Debug,
+    Resp: Debug,
+    RequestMessage: Into<Req>,
+    Resp: Into<ResponseMessage>,
+    E: Encode<Envelope<Req>>,
+    D: Decode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Req>>>::Encoded, <D as Decode<Envelope<Resp>>>::Encoded>,
+    S: EventFutureSharedStatePorcelain,
+{
+    type EventFuture = EventFuture<'a, S>;
+
+    fn get_pc(&self) -> Addr { ctrl(self.try_get_pc()) }
+    fn set_pc(&mut self, addr: Addr) { ctrl_or(self.try_set_pc(addr), ()) }
+
+    fn get_register(&self, reg: Reg) -> Word { ctrl(self.try_get_register(reg)) }
+    fn set_register(&mut self, reg: Reg, data: Word) { ctrl_or(self.try_set_register(reg, data), ()) }
+
+    fn get_registers_psr_and_pc(&self) -> ([Word; Reg::NUM_REGS], Word, Word) { ctrl(self.try_get_registers_psr_and_pc()) }
+
+    fn read_word(&self, addr: Addr) -> Word { ctrl(self.try_read_word(addr)) }
+    fn write_word(&mut self, addr: Addr, word: Word) { ctrl_or(self.try_write_word(addr, word), ()) }
+    fn read_words(&self, start: Addr, out: &mut [Word]) { ctrl(self.try_read_words(start, out)) }
+
+    fn start_page_write(&mut self, page: LoadApiSession<PageWriteStart>, checksum: u64) -> Result<LoadApiSession<u8>, StartPageWriteError> {
+        let denied = Err(StartPageWriteError::InvalidPage { page: page.get().0 });
+        ctrl_or(self.try_start_page_write(page, checksum), denied)
+    }
+    fn send_page_chunk(&mut self, offset: LoadApiSession<Offset>, chunk: [Word; CHUNK_SIZE_IN_WORDS as usize]) -> Result<(), PageChunkError> {
+        ctrl_or(self.try_send_page_chunk(offset, chunk), Err(PageChunkError::NoCurrentSession))
+    }
+    fn finish_page_write(&mut self, page: LoadApiSession<PageIndex>) -> Result<(), FinishPageWriteError> {
+        ctrl_or(self.try_finish_page_write(page), Err(FinishPageWriteError::NoCurrentSession))
     }
 
-    fn get_error(&self) -> Option<Lc3Error> { ctrl!(self, GetError, R::GetError(r), r) }
+    fn set_breakpoint(&mut self, addr: Addr) -> Result<Idx, ()> { ctrl_or(self.try_set_breakpoint(addr), Err(())) }
+    fn unset_breakpoint(&mut self, idx: Idx) -> Result<(), ()> { ctrl_or(self.try_unset_breakpoint(idx), Err(())) }
+    fn get_breakpoints(&self) -> [Option<Addr>; MAX_BREAKPOINTS] { ctrl(self.try_get_breakpoints()) }
+    fn get_max_breakpoints(&self) -> Idx { ctrl(self.try_get_max_breakpoints()) }
+
+    fn set_memory_watchpoint(&mut self, addr: Addr) -> Result<Idx, ()> { ctrl_or(self.try_set_memory_watchpoint(addr), Err(())) }
+    fn unset_memory_watchpoint(&mut self, idx: Idx) -> Result<(), ()> { ctrl_or(self.try_unset_memory_watchpoint(idx), Err(())) }
+    fn get_memory_watchpoints(&self) -> [Option<(Addr, Word)>; MAX_MEMORY_WATCHPOINTS] { ctrl(self.try_get_memory_watchpoints()) }
+    fn get_max_memory_watchpoints(&self) -> Idx { ctrl(self.try_get_max_memory_watchpoints()) }
+
+    fn set_depth_condition(&mut self, condition: UnifiedRange<u64>) -> Result<Option<UnifiedRange<u64>>, ()> {
+        ctrl_or(self.try_set_depth_condition(condition), Err(()))
+    }
+    fn unset_depth_condition(&mut self) -> Option<UnifiedRange<u64>> { ctrl_or(self.try_unset_depth_condition(), None) }
+    fn get_depth(&self) -> Result<u64, ()> { ctrl(self.try_get_depth()) }
+    fn get_call_stack(&self, page: u16) -> CallStackPage { ctrl(self.try_get_call_stack(page)) }
+
+    // Execution control functions:
+    fn run_until_event(&mut self) -> Self::EventFuture { ctrl(self.try_run_until_event()) }
+
+    fn tick(&mut self) -> usize {
+        ctrl(self.try_tick());
+
+        // This function can (probably, TODO) be safely _not_ called so we
+        // return 0:
+        0
+    }
+
+    fn step(&mut self) -> Option<Event> { ctrl_or(self.try_step(), None) }
+    fn pause(&mut self) { ctrl_or(self.try_pause(), ()) }
+    fn get_state(&self) -> State { ctrl(self.try_get_state()) }
+    fn reset(&mut self) { ctrl_or(self.try_reset(), ()) }
+    fn get_error(&self) -> Option<Lc3Error> { ctrl(self.try_get_error()) }
+
+    fn set_fault_handling(&mut self, handling: FaultHandling) { ctrl_or(self.try_set_fault_handling(handling), ()) }
+    fn get_fault_handling(&self) -> FaultHandling { ctrl(self.try_get_fault_handling()) }
 
     // I/O Access:
-    fn get_gpio_states(&self) -> GpioPinArr<GpioState> { ctrl!(self, GetGpioStates, R::GetGpioStates(r), r) }
-    fn get_gpio_readings(&self) -> GpioPinArr<Result<bool, GpioReadError>> { ctrl!(self, GetGpioReadings, R::GetGpioReadings(r), r) }
-    fn get_adc_states(&self) -> AdcPinArr<AdcState> { ctrl!(self, GetAdcStates, R::GetAdcStates(r), r) }
-    fn get_adc_readings(&self) -> AdcPinArr<Result<u8, AdcReadError>> { ctrl!(self, GetAdcReadings, R::GetAdcReadings(r), r) }
-    fn get_timer_modes(&self) -> TimerArr<TimerMode> { ctrl!(self, GetTimerModes, R::GetTimerModes(r), r) }
-    fn get_timer_states(&self) -> TimerArr<TimerState> { ctrl!(self, GetTimerStates, R::GetTimerStates(r), r) }
-    fn get_pwm_states(&self) -> PwmPinArr<PwmState> { ctrl!(self, GetPwmStates, R::GetPwmStates(r), r) }
-    fn get_pwm_config(&self) -> PwmPinArr<u8> { ctrl!(self, GetPwmConfig, R::GetPwmConfig(r), r) }
-    fn get_clock(&self) -> Word { ctrl!(self, GetClock, R::GetClock(r), r) }
-
-    fn get_device_info(&self) -> DeviceInfo { ctrl!(self, GetDeviceInfo, R::GetDeviceInfo(r), r) }
-
-    fn get_program_metadata(&self) -> ProgramMetadata { ctrl!(self, GetProgramMetadata, R::GetProgramMetadata(r), r) }
-    fn set_program_metadata(&mut self, metadata: ProgramMetadata) { ctrl!(self, SetProgramMetadata { metadata }, R::SetProgramMetadata) }
+    fn get_gpio_states(&self) -> GpioPinArr<GpioState> { ctrl(self.try_get_gpio_states()) }
+    fn get_gpio_readings(&self) -> GpioPinArr<Result<bool, GpioReadError>> { ctrl(self.try_get_gpio_readings()) }
+    fn get_adc_states(&self) -> AdcPinArr<AdcState> { ctrl(self.try_get_adc_states()) }
+    fn get_adc_readings(&self) -> AdcPinArr<Result<u8, AdcReadError>> { ctrl(self.try_get_adc_readings()) }
+    fn get_timer_modes(&self) -> TimerArr<TimerMode> { ctrl(self.try_get_timer_modes()) }
+    fn get_timer_states(&self) -> TimerArr<TimerState> { ctrl(self.try_get_timer_states()) }
+    fn get_pwm_states(&self) -> PwmPinArr<PwmState> { ctrl(self.try_get_pwm_states()) }
+    fn get_pwm_config(&self) -> PwmPinArr<PwmConfig> { ctrl(self.try_get_pwm_config()) }
+    fn get_clock(&self) -> Word { ctrl(self.try_get_clock()) }
+    fn get_interrupt_controller_state(&self) -> InterruptControllerState { ctrl(self.try_get_interrupt_controller_state()) }
+
+    fn get_device_info(&self) -> DeviceInfo { ctrl(self.try_get_device_info()) }
+
+    fn get_program_metadata(&self) -> ProgramMetadata { ctrl(self.try_get_program_metadata()) }
+    fn set_program_metadata(&mut self, metadata: ProgramMetadata) { ctrl_or(self.try_set_program_metadata(metadata), ()) }
 
     fn id(&self) -> crate::control::metadata::Identifier {
         crate::control::metadata::Identifier::new_from_str_that_crashes_on_invalid_inputs("PROX")
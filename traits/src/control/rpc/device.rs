@@ -135,8 +135,14 @@
 
 use super::{Encode, Decode, Transport};
 use super::{Control, RequestMessage, ResponseMessage};
+use super::messages::{Envelope, SeqNum, RequestEnvelope, ResponseEnvelope, READ_WORDS_CHUNK_LEN};
+use super::handshake::Hello;
+use super::hub::{Role, SessionInfo};
+use super::notifications::{Console, InputBuffer, Notification, Subscription, Topics, CONSOLE_CHUNK_SIZE};
 use super::encoding::Transparent;
 
+use crate::control::{Identifier, Version};
+
 use core::marker::PhantomData;
 use core::task::{Context, Poll, Waker, RawWaker, RawWakerVTable};
 use core::future::Future;
@@ -152,23 +158,32 @@ use core::fmt::Debug;
 /// `RequestMessage`/`ResponseMessage` and the types used here so that users can
 /// experiment with their own messages. This may, however, be moot (TODO). See
 /// the docs in `controller.rs` for more info.
+///
+/// Responses carry the [sequence number](Envelope) of the request they answer.
+/// The device remembers the last request it handled and the response it sent;
+/// if that request shows up again (i.e. because the response was lost and the
+/// [`Controller`](super::Controller) retried) the response is sent again and
+/// the request is *not* handled a second time.
+///
+/// A `Device` serves a single controller; to share a device between several,
+/// use a [`Hub`](super::hub::Hub).
 #[derive(Debug, Default)]
 pub struct Device<
     T,
     C,
     Req = RequestMessage,
     Resp = ResponseMessage,
-    ReqDec = Transparent<RequestMessage>,
-    RespEnc = Transparent<ResponseMessage>,
+    ReqDec = Transparent<RequestEnvelope>,
+    RespEnc = Transparent<ResponseEnvelope>,
 >
 where
     Req: Debug,
     Resp: Debug,
     Req: Into<RequestMessage>,
     ResponseMessage: Into<Resp>,
-    ReqDec: Decode<Req>,
-    RespEnc: Encode<Resp>,
-    T: Transport<<RespEnc as Encode<Resp>>::Encoded, <ReqDec as Decode<Req>>::Encoded>,
+    ReqDec: Decode<Envelope<Req>>,
+    RespEnc: Encode<Envelope<Resp>>,
+    T: Transport<<RespEnc as Encode<Envelope<Resp>>>::Encoded, <ReqDec as Decode<Envelope<Req>>>::Encoded>,
     C: Control,
     // <C as Control>::EventFuture: Unpin,
 {
@@ -178,7 +193,13 @@ where
     enc: RespEnc,
     dec: ReqDec,
     // pending_event_future: Option<Pin<C::EventFuture>>,
-    pending_event_future: Option<C::EventFuture>,
+    // The sequence number of the `RunUntilEvent` request goes along with the
+    // future.
+    pending_event_future: Option<(SeqNum, C::EventFuture)>,
+    last_handled: Option<(SeqNum, RequestMessage, ResponseMessage)>,
+    changes: u64,
+    subscription: Subscription,
+    console_input: InputBuffer,
 }
 
 // TODO: make a builder!
@@ -189,9 +210,9 @@ where
     Resp: Debug,
     Req: Into<RequestMessage>,
     ResponseMessage: Into<Resp>,
-    D: Decode<Req>,
-    E: Encode<Resp>,
-    T: Transport<<E as Encode<Resp>>::Encoded, <D as Decode<Req>>::Encoded>,
+    D: Decode<Envelope<Req>>,
+    E: Encode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Resp>>>::Encoded, <D as Decode<Envelope<Req>>>::Encoded>,
     C: Control,
     // <C as Control>::EventFuture: Unpin,
 {
@@ -209,6 +230,10 @@ where
             enc,
             dec,
             pending_event_future: None,
+            last_handled: None,
+            changes: 0,
+            subscription: Subscription::default(),
+            console_input: InputBuffer::default(),
         }
     }
 }
@@ -245,9 +270,9 @@ where
     Resp: Debug,
     Req: Into<RequestMessage>,
     ResponseMessage: Into<Resp>,
-    D: Decode<Req>,
-    E: Encode<Resp>,
-    T: Transport<<E as Encode<Resp>>::Encoded, <D as Decode<Req>>::Encoded>,
+    D: Decode<Envelope<Req>>,
+    E: Encode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Resp>>>::Encoded, <D as Decode<Envelope<Req>>>::Encoded>,
     C: Control,
     <C as Control>::EventFuture: Unpin, // TODO: use `pin_utils::pin_mut!` and relax this requirement.
     // <C as Control>::EventFuture: Deref<Target = <C as Control>::EventFuture>,
@@ -267,7 +292,7 @@ where
         // Make some progress:
         num_executed_instructions = c.tick();
 
-        if let Some(ref mut f) = self.pending_event_future {
+        if let Some((seq, ref mut f)) = self.pending_event_future {
             // println!("polling the device future");
 
             // TODO: we opt to poll here because we assume that the underlying future is
@@ -287,99 +312,262 @@ where
                 // println!("device future is done!");
                 self.pending_event_future = None;
 
-                let enc = self.enc.encode(&R::RunUntilEvent(event).into());
+                // Subscribers hear about the state change first:
+                self.notify(c);
+
+                let enc = self.enc.encode(&Envelope::new(seq, R::RunUntilEvent(event).into()));
                 self.transport.send(enc).unwrap(); // TODO: don't panic?
             }
         }
 
+        self.notify(c);
+
         // TODO: we don't panic on decode failures here, but this is only a stopgap,
         // first pass solution.
-        while let Ok(m) = self.transport.get().and_then(|enc| self.dec.decode(&enc).map_err(|_| None).map(Into::into)) {
+        //
+        // Messages that fail to decode are dropped; the controller will send
+        // them again once it realizes we're not going to answer.
+        while let Ok(encoded) = self.transport.get() {
+            let Envelope { seq, message } = match self.dec.decode(&encoded) {
+                Ok(m) => m,
+                Err(e) => {
+                    log::trace!("Decode Error: `{:?}`", e);
+                    self.transport.record_get_error();
+
+                    continue;
+                }
+            };
+
             num_processed_messages += 1;
 
-            macro_rules! dev {
-                ($(($req:pat => $($resp:tt)+) with $r:tt = $resp_expr:expr;)*) => {
-                    #[forbid(unreachable_patterns)]
-                    match m {
-                        RunUntilEvent => {
-                            if self.pending_event_future.is_some() {
-                                panic!() // TODO: write a message // already have a run until event pending!
-                            } else {
-                                // self.pending_event_future = Some(Pin::new(c.run_until_event()));
-                                self.pending_event_future = Some(c.run_until_event());
-                                self.transport.send(self.enc.encode(&R::RunUntilEventAck.into())).unwrap()
-                            }
-                        },
-                        $(
-                            $req => self.transport.send(self.enc.encode(&{
-                                let $r = $resp_expr;
-                                $($resp)+
-                            }.into())).unwrap(),
-                        )*
+            let m: RequestMessage = message.into();
+
+            // If this is the last request we handled again, the controller
+            // didn't get our response; send it again (but don't do the
+            // request again!):
+            if let Some((last_seq, ref last_req, ref last_resp)) = self.last_handled {
+                if last_seq == seq && *last_req == m {
+                    let enc = self.enc.encode(&Envelope::new(seq, last_resp.clone().into()));
+                    self.transport.send(enc).unwrap(); // TODO: don't panic?
+
+                    continue;
+                }
+            }
+
+            let req = m.clone();
+
+            if req.is_mutating() {
+                self.changes = self.changes.wrapping_add(1);
+            }
+
+            let resp = match dispatch(c, m, (T::ID, T::VER)) {
+                Ok(resp) => resp,
+                Err(Handshake { hello }) => R::Handshake(Hello::respond_to(&hello)),
+                Err(RunUntilEvent) => {
+                    if self.pending_event_future.is_some() {
+                        panic!() // TODO: write a message // already have a run until event pending!
+                    } else {
+                        // self.pending_event_future = Some(Pin::new(c.run_until_event()));
+                        self.pending_event_future = Some((seq, c.run_until_event()));
+                        R::RunUntilEventAck
                     }
+                },
+                // There's only one session and it's ours:
+                Err(GetSessionInfo) => R::GetSessionInfo(SessionInfo {
+                    role: Role::Owner,
+                    changes: self.changes,
+                }),
+                Err(Subscribe { topics }) => {
+                    self.subscription.set(topics);
+                    R::Subscribe(topics)
+                },
+                Err(ConsoleInput { len, data }) => {
+                    let len = (len as usize).min(CONSOLE_CHUNK_SIZE);
+                    R::ConsoleInput(self.console_input.put(&data[..len]) as u8)
+                },
+                Err(other) => unreachable!("`dispatch` only hands back session requests, not `{:?}`", other),
+            };
+
+            // Notifications for whatever this request changed go out before
+            // the response does; see the `notifications` module.
+            self.notify(c);
 
-                };
+            let enc = self.enc.encode(&Envelope::new(seq, resp.clone().into()));
+            self.transport.send(enc).unwrap(); // TODO: don't panic?
+
+            self.last_handled = Some((seq, req, resp));
+        }
+
+        (num_processed_messages, num_executed_instructions)
+    }
+}
+
+impl<Req, Resp, D, E, T, C> Device<T, C, Req, Resp, D, E>
+where
+    Req: Debug,
+    Resp: Debug,
+    Req: Into<RequestMessage>,
+    ResponseMessage: Into<Resp>,
+    D: Decode<Envelope<Req>>,
+    E: Encode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Resp>>>::Encoded, <D as Decode<Envelope<Req>>>::Encoded>,
+    C: Control,
+{
+    /// Sends console output to the controller, if it has subscribed to it.
+    ///
+    /// The [`Control`] interface doesn't know about console output so whatever
+    /// the output peripheral writes to has to be handed to us here.
+    pub fn push_console(&mut self, bytes: &[u8]) {
+        if self.subscription.topics.contains(Topics::CONSOLE) {
+            for n in Notification::console(bytes) {
+                let envelope = self.subscription.envelope(n);
+                self.send_notification(envelope);
             }
+        }
+    }
+
+    /// Takes the oldest byte of console input that the controller has sent.
+    ///
+    /// Like [`push_console`](Device::push_console), this is for whatever owns
+    /// the input peripheral to call (i.e. between steps, whenever the input
+    /// peripheral is ready for another character). Only so much input is held
+    /// on to; controllers send the rest again later.
+    pub fn take_console_input(&mut self) -> Option<u8> {
+        self.console_input.pop()
+    }
+
+    // Pushes whatever has changed since we last checked.
+    fn notify(&mut self, c: &C) {
+        let mut sub = core::mem::take(&mut self.subscription);
+        sub.check(c, |n| self.send_notification(n));
+
+        self.subscription = sub;
+    }
+
+    // Notifications don't answer a request; their sequence number is their
+    // own (see the `notifications` module).
+    fn send_notification(&mut self, Envelope { seq, message }: ResponseEnvelope) {
+        let enc = self.enc.encode(&Envelope::new(seq, message.into()));
+        self.transport.send(enc).unwrap(); // TODO: don't panic?
+    }
+}
+
+impl<Req, Resp, D, E, T, C> Console for Device<T, C, Req, Resp, D, E>
+where
+    Req: Debug,
+    Resp: Debug,
+    Req: Into<RequestMessage>,
+    ResponseMessage: Into<Resp>,
+    D: Decode<Envelope<Req>>,
+    E: Encode<Envelope<Resp>>,
+    T: Transport<<E as Encode<Envelope<Resp>>>::Encoded, <D as Decode<Envelope<Req>>>::Encoded>,
+    C: Control,
+{
+    fn push_console(&mut self, bytes: &[u8]) { Device::push_console(self, bytes) }
+    fn take_console_input(&mut self) -> Option<u8> { Device::take_console_input(self) }
+}
 
-            dev! {
-                (GetPc => R::GetPc(r)) with r = c.get_pc();
-                (SetPc { addr } => R::SetPc) with _ = c.set_pc(addr);
 
-                (GetRegister { reg } => R::GetRegister(r)) with r = c.get_register(reg);
-                (SetRegister { reg, data } => R::SetRegister) with _ = c.set_register(reg, data);
+/// Runs a request on `c` and produces the response.
+///
+/// Requests whose handling depends on the session they came from (the
+/// handshake, `RunUntilEvent`, `GetSessionInfo` and `Subscribe`) or that
+/// aren't for the [`Control`] impl (`ConsoleInput`) are handed back instead;
+/// this is shared with the [`Hub`](super::hub::Hub).
+///
+/// `proxy` is the transport that the request came in on; it's added to the
+/// [`DeviceInfo`](crate::control::DeviceInfo) that we hand out.
+pub(super) fn dispatch<C: Control>(
+    c: &mut C,
+    m: RequestMessage,
+    proxy: (Identifier, Version),
+) -> Result<ResponseMessage, RequestMessage> {
+    use RequestMessage::*;
+    use ResponseMessage as R;
+
+    macro_rules! dev {
+        ($(($req:pat => $($resp:tt)+) with $r:tt = $resp_expr:expr;)*) => {{
+            #[forbid(unreachable_patterns)]
+            let resp = match m {
+                m @ (Handshake { .. } | RunUntilEvent | GetSessionInfo | Subscribe { .. } | ConsoleInput { .. }) => return Err(m),
+                $(
+                    $req => {
+                        let $r = $resp_expr;
+                        $($resp)+
+                    },
+                )*
+            };
 
-                (GetRegistersPsrAndPc => R::GetRegistersPsrAndPc(r)) with r = c.get_registers_psr_and_pc();
+            Ok(resp)
+        }};
+    }
 
-                (ReadWord { addr } => R::ReadWord(r)) with r = c.read_word(addr);
-                (WriteWord { addr, word } => R::WriteWord) with _ = c.write_word(addr, word);
+    dev! {
+        (GetPc => R::GetPc(r)) with r = c.get_pc();
+        (SetPc { addr } => R::SetPc) with _ = c.set_pc(addr);
 
-                (StartPageWrite { page, checksum } => R::StartPageWrite(r)) with r = c.start_page_write(page, checksum);
-                (SendPageChunk { offset, chunk } => R::SendPageChunk(r)) with r = c.send_page_chunk(offset, chunk);
-                (FinishPageWrite { page } => R::FinishPageWrite(r)) with r = c.finish_page_write(page);
+        (GetRegister { reg } => R::GetRegister(r)) with r = c.get_register(reg);
+        (SetRegister { reg, data } => R::SetRegister) with _ = c.set_register(reg, data);
 
-                (SetBreakpoint { addr } => R::SetBreakpoint(r)) with r= c.set_breakpoint(addr);
-                (UnsetBreakpoint { idx } => R::UnsetBreakpoint(r)) with r = c.unset_breakpoint(idx);
-                (GetBreakpoints => R::GetBreakpoints(r)) with r = c.get_breakpoints();
-                (GetMaxBreakpoints => R::GetMaxBreakpoints(r)) with r = c.get_max_breakpoints();
+        (GetRegistersPsrAndPc => R::GetRegistersPsrAndPc(r)) with r = c.get_registers_psr_and_pc();
 
-                (SetMemoryWatchpoint { addr } => R::SetMemoryWatchpoint(r)) with r = c.set_memory_watchpoint(addr);
-                (UnsetMemoryWatchpoint { idx } => R::UnsetMemoryWatchpoint(r)) with r = c.unset_memory_watchpoint(idx);
-                (GetMemoryWatchpoints => R::GetMemoryWatchpoints(r)) with r = c.get_memory_watchpoints();
-                (GetMaxMemoryWatchpoints => R::GetMaxMemoryWatchpoints(r)) with r = c.get_max_memory_watchpoints();
+        (ReadWord { addr } => R::ReadWord(r)) with r = c.read_word(addr);
+        (WriteWord { addr, word } => R::WriteWord) with _ = c.write_word(addr, word);
+        (ReadWords { start, len } => R::ReadWords(r)) with r = {
+            let mut words = [0; READ_WORDS_CHUNK_LEN];
+            let len = (len as usize).min(READ_WORDS_CHUNK_LEN);
+            c.read_words(start, &mut words[..len]);
 
-                (SetDepthCondition { condition } => R::SetDepthCondition(r)) with r = c.set_depth_condition(condition);
-                (UnsetDepthCondition => R::UnsetDepthCondition(r)) with r = c.unset_depth_condition();
-                (GetDepth => R::GetDepth(r)) with r = c.get_depth();
-                (GetCallStack => R::GetCallStack(r)) with r = c.get_call_stack();
+            words
+        };
 
-                (Step => R::Step(r)) with r = c.step();
-                (Pause => R::Pause) with _ = c.pause();
-                (GetState => R::GetState(r)) with r = c.get_state();
-                (Reset => R::Reset) with _ = c.reset();
+        (StartPageWrite { page, checksum } => R::StartPageWrite(r)) with r = c.start_page_write(page, checksum);
+        (SendPageChunk { offset, chunk } => R::SendPageChunk(r)) with r = c.send_page_chunk(offset, chunk);
+        (FinishPageWrite { page } => R::FinishPageWrite(r)) with r = c.finish_page_write(page);
 
-                (GetError => R::GetError(r)) with r = c.get_error();
+        (SetBreakpoint { addr } => R::SetBreakpoint(r)) with r= c.set_breakpoint(addr);
+        (UnsetBreakpoint { idx } => R::UnsetBreakpoint(r)) with r = c.unset_breakpoint(idx);
+        (GetBreakpoints => R::GetBreakpoints(r)) with r = c.get_breakpoints();
+        (GetMaxBreakpoints => R::GetMaxBreakpoints(r)) with r = c.get_max_breakpoints();
 
-                (GetGpioStates => R::GetGpioStates(r)) with r = c.get_gpio_states();
-                (GetGpioReadings => R::GetGpioReadings(r)) with r = c.get_gpio_readings();
+        (SetMemoryWatchpoint { addr } => R::SetMemoryWatchpoint(r)) with r = c.set_memory_watchpoint(addr);
+        (UnsetMemoryWatchpoint { idx } => R::UnsetMemoryWatchpoint(r)) with r = c.unset_memory_watchpoint(idx);
+        (GetMemoryWatchpoints => R::GetMemoryWatchpoints(r)) with r = c.get_memory_watchpoints();
+        (GetMaxMemoryWatchpoints => R::GetMaxMemoryWatchpoints(r)) with r = c.get_max_memory_watchpoints();
 
-                (GetAdcStates => R::GetAdcStates(r)) with r = c.get_adc_states();
-                (GetAdcReadings => R::GetAdcReadings(r)) with r = c.get_adc_readings();
+        (SetDepthCondition { condition } => R::SetDepthCondition(r)) with r = c.set_depth_condition(condition);
+        (UnsetDepthCondition => R::UnsetDepthCondition(r)) with r = c.unset_depth_condition();
+        (GetDepth => R::GetDepth(r)) with r = c.get_depth();
+        (GetCallStack { page } => R::GetCallStack(r)) with r = c.get_call_stack(page);
 
-                (GetTimerModes => R::GetTimerModes(r)) with r = c.get_timer_modes();
-                (GetTimerStates => R::GetTimerStates(r)) with r = c.get_timer_states();
+        (Step => R::Step(r)) with r = c.step();
+        (Pause => R::Pause) with _ = c.pause();
+        (GetState => R::GetState(r)) with r = c.get_state();
+        (Reset => R::Reset) with _ = c.reset();
 
-                (GetPwmStates => R::GetPwmStates(r)) with r = c.get_pwm_states();
-                (GetPwmConfig => R::GetPwmConfig(r)) with r = c.get_pwm_config();
+        (GetError => R::GetError(r)) with r = c.get_error();
 
-                (GetClock => R::GetClock(r)) with r = c.get_clock();
+        (SetFaultHandling { handling } => R::SetFaultHandling) with _ = c.set_fault_handling(handling);
+        (GetFaultHandling => R::GetFaultHandling(r)) with r = c.get_fault_handling();
 
-                (GetDeviceInfo => R::GetDeviceInfo(r)) with r = c.get_device_info().add_proxy(T::ID, T::VER).expect("too many proxies");
+        (GetGpioStates => R::GetGpioStates(r)) with r = c.get_gpio_states();
+        (GetGpioReadings => R::GetGpioReadings(r)) with r = c.get_gpio_readings();
 
-                (GetProgramMetadata => R::GetProgramMetadata(r)) with r = c.get_program_metadata();
-                (SetProgramMetadata { metadata } => R::SetProgramMetadata) with _ = c.set_program_metadata(metadata);
-            };
-        }
+        (GetAdcStates => R::GetAdcStates(r)) with r = c.get_adc_states();
+        (GetAdcReadings => R::GetAdcReadings(r)) with r = c.get_adc_readings();
 
-        (num_processed_messages, num_executed_instructions)
+        (GetTimerModes => R::GetTimerModes(r)) with r = c.get_timer_modes();
+        (GetTimerStates => R::GetTimerStates(r)) with r = c.get_timer_states();
+
+        (GetPwmStates => R::GetPwmStates(r)) with r = c.get_pwm_states();
+        (GetPwmConfig => R::GetPwmConfig(r)) with r = c.get_pwm_config();
+
+        (GetClock => R::GetClock(r)) with r = c.get_clock();
+        (GetInterruptControllerState => R::GetInterruptControllerState(r)) with r = c.get_interrupt_controller_state();
+
+        (GetDeviceInfo => R::GetDeviceInfo(r)) with r = c.get_device_info().add_proxy(proxy.0, proxy.1).expect("too many proxies");
+
+        (GetProgramMetadata => R::GetProgramMetadata(r)) with r = c.get_program_metadata();
+        (SetProgramMetadata { metadata } => R::SetProgramMetadata) with _ = c.set_program_metadata(metadata);
     }
 }
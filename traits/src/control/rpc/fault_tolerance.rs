@@ -139,3 +139,23 @@
 //! of granularity is a byte, which is very helpful.
 //!
 //! [COBS]: (https://en.wikipedia.org/wiki/Consistent_Overhead_Byte_Stuffing)
+
+//! #### What we actually do:
+//!
+//! The "borked" flow above, more or less: requests and responses are wrapped
+//! in [`Envelope`](super::Envelope)s with sequence numbers, the
+//! [`Controller`](super::Controller) times out and resends requests as its
+//! [`RetryPolicy`](super::RetryPolicy) allows, and the
+//! [`Device`](super::Device) answers a request it has just handled with the
+//! response it already sent instead of handling it again (this is what makes
+//! retrying the requests that *aren't* idempotent okay).
+//!
+//! Failures are not communicated; garbled messages are dropped on the floor
+//! and the controller finds out via the timeout (or, for garbled responses,
+//! right away).
+//!
+//! Garbled messages are only dropped if they fail to decode; encodings that
+//! can't tell on their own (postcard, for example) should be paired with a
+//! checksum layer (`lc3_device_support::rpc::encoding::Crc`). Either way, both
+//! ends tell their [`Transport`](super::Transport) about the messages they
+//! drop (see [`Transport::num_get_errors`](super::Transport::num_get_errors)).
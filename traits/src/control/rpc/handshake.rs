@@ -0,0 +1,252 @@
+//! Checks that the two ends of an RPC session actually speak the same protocol.
+//!
+//! [`DeviceInfo`](crate::control::DeviceInfo) tells you what's on the other
+//! end of a connection but by the time you can ask for it you're already
+//! trusting that both ends agree on what the messages look like. Instead, at
+//! the start of a session the [`Controller`](super::Controller) sends a
+//! [`Hello`] describing its end of the protocol and the
+//! [`Device`](super::Device) answers with its own; see
+//! [`Controller::handshake`](super::Controller::handshake).
+//!
+//! The two are compatible when they have the same [major
+//! version](PROTOCOL_VERSION) and the same [message hash](MESSAGE_HASH).
+//! Differences in the minor version are fine: the [`Features`] that only one
+//! end supports just go unused.
+//!
+//! The handshake messages are the first variants of
+//! [`RequestMessage`](super::RequestMessage) and
+//! [`ResponseMessage`](super::ResponseMessage) and [`Hello`] must not change
+//! shape so that the handshake itself decodes correctly even when nothing else
+//! does.
+//!
+//! Devices that predate the handshake can't decode it and so never answer it;
+//! when the handshake goes unanswered the controller assumes that it's talking
+//! to such a device and carries on without any of the optional [`Features`].
+
+use super::{ResponseMessage, RpcError};
+
+use serde::{Deserialize, Serialize};
+
+use core::fmt::{self, Debug, Display};
+
+pub use super::messages::MESSAGE_HASH;
+
+/// Version of the RPC wire format (the message types and the [`Envelope`] they
+/// travel in).
+///
+/// Bump the major version for changes that old peers can't cope with (this
+/// includes changes to the types used *inside* messages; the [message
+/// hash](MESSAGE_HASH) only covers the message enums themselves). Bump the
+/// minor version when adding [`Features`].
+///
+/// [`Envelope`]: super::Envelope
+pub const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion { major: 1, minor: 1 };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ProtocolVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl Display for ProtocolVersion {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Optional parts of the protocol that both ends of a session must support
+/// before they get used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Features(u32);
+
+impl Features {
+    pub const NONE: Self = Self(0);
+
+    /// The device answers retried requests with the response it already sent
+    /// instead of handling them again; without this, retrying requests that
+    /// aren't idempotent isn't safe (see [`RetryPolicy`](super::RetryPolicy)).
+    pub const DUPLICATE_DETECTION: Self = Self(1 << 0);
+
+    /// The device pushes [notifications](super::notifications) to controllers
+    /// that subscribe to them.
+    pub const NOTIFICATIONS: Self = Self(1 << 1);
+
+    /// Everything this version of the protocol supports.
+    pub const SUPPORTED: Self = Self::DUPLICATE_DETECTION.union(Self::NOTIFICATIONS);
+
+    pub const fn bits(self) -> u32 { self.0 }
+
+    /// Features we don't know about are dropped.
+    pub const fn from_bits_truncate(bits: u32) -> Self {
+        Self(bits & Self::SUPPORTED.0)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    pub const fn union(self, other: Self) -> Self { Self(self.0 | other.0) }
+    pub const fn intersection(self, other: Self) -> Self { Self(self.0 & other.0) }
+}
+
+/// One end of a session's description of the protocol it speaks.
+///
+/// The device's answer lists only the features that both ends support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Hello {
+    pub version: ProtocolVersion,
+    pub message_hash: u64,
+    pub features: Features,
+}
+
+impl Hello {
+    /// What this build of the crate speaks.
+    pub const OURS: Self = Self {
+        version: PROTOCOL_VERSION,
+        message_hash: MESSAGE_HASH,
+        features: Features::SUPPORTED,
+    };
+
+    /// The device side of the handshake: what to answer `theirs` with.
+    pub const fn respond_to(theirs: &Hello) -> Self {
+        Self {
+            features: Self::OURS.features.intersection(theirs.features),
+            ..Self::OURS
+        }
+    }
+
+    /// Checks that `theirs` is compatible with us.
+    ///
+    /// Returns the features that both ends support.
+    pub fn check(&self, theirs: &Hello) -> Result<Features, Mismatch> {
+        if self.version.major != theirs.version.major {
+            Err(Mismatch::Version { ours: self.version, theirs: theirs.version })
+        } else if self.message_hash != theirs.message_hash {
+            Err(Mismatch::Messages { ours: self.message_hash, theirs: theirs.message_hash })
+        } else {
+            Ok(self.features.intersection(theirs.features))
+        }
+    }
+}
+
+/// Ways in which the two ends of a session can disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    Version { ours: ProtocolVersion, theirs: ProtocolVersion },
+    Messages { ours: u64, theirs: u64 },
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Mismatch::*;
+
+        match self {
+            Version { ours, theirs } => write!(fmt,
+                "the device speaks version {} of the protocol but we speak version {}",
+                theirs, ours,
+            ),
+            Messages { ours, theirs } => write!(fmt,
+                "the device's message types (hash: {:#018X}) don't match ours (hash: {:#018X})",
+                theirs, ours,
+            ),
+        }
+    }
+}
+
+/// Why [`Controller::handshake`](super::Controller::handshake) failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandshakeError<SendErr, RecvErr> {
+    /// The transport failed while sending the handshake or waiting for the
+    /// answer.
+    ///
+    /// This is never [`RpcError::NoResponse`]; see the [module
+    /// docs](self).
+    Rpc(RpcError<SendErr, RecvErr>),
+    /// The device answered with something other than a handshake; it's
+    /// probably running something that predates the handshake.
+    NotUnderstood,
+    /// The device speaks an incompatible version of the protocol.
+    Incompatible(Mismatch),
+}
+
+impl<S: Debug, R: Debug> Display for HandshakeError<S, R> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use HandshakeError::*;
+
+        match self {
+            Rpc(e) => write!(fmt, "handshake failed: {}", e),
+            NotUnderstood => write!(fmt,
+                "the device didn't understand the handshake; it's probably running an older version of the protocol",
+            ),
+            Incompatible(m) => write!(fmt, "incompatible device: {}", m),
+        }
+    }
+}
+
+using_std! {
+    impl<S: Debug, R: Debug> std::error::Error for HandshakeError<S, R> { }
+}
+
+// What to make of the device's answer to our `Hello`; shared by the
+// controllers.
+pub(super) fn check_answer<S, R>(
+    answer: Result<ResponseMessage, RpcError<S, R>>,
+) -> Result<Features, HandshakeError<S, R>> {
+    match answer {
+        Ok(ResponseMessage::Handshake(theirs)) => {
+            Hello::OURS.check(&theirs).map_err(HandshakeError::Incompatible)
+        },
+        Ok(other) => {
+            log::debug!("Got `{:?}` in response to a handshake", other);
+            Err(HandshakeError::NotUnderstood)
+        },
+        Err(RpcError::NoResponse { .. }) => {
+            log::warn!("The device didn't answer the handshake; assuming it predates the handshake");
+            Ok(Features::NONE)
+        },
+        Err(e) => Err(HandshakeError::Rpc(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    const fn hello(major: u8, minor: u8, message_hash: u64, features: Features) -> Hello {
+        Hello { version: ProtocolVersion { major, minor }, message_hash, features }
+    }
+
+    #[test]
+    fn compatible() {
+        let ours = hello(1, 0, 0xABCD, Features::DUPLICATE_DETECTION);
+
+        assert_eq!(ours.check(&ours), Ok(Features::DUPLICATE_DETECTION));
+        assert_eq!(ours.check(&hello(1, 3, 0xABCD, Features::SUPPORTED)), Ok(Features::DUPLICATE_DETECTION));
+        assert_eq!(ours.check(&hello(1, 0, 0xABCD, Features::NONE)), Ok(Features::NONE));
+    }
+
+    #[test]
+    fn incompatible() {
+        let ours = hello(1, 0, 0xABCD, Features::SUPPORTED);
+
+        assert_eq!(
+            ours.check(&hello(2, 0, 0xABCD, Features::SUPPORTED)),
+            Err(Mismatch::Version { ours: ours.version, theirs: ProtocolVersion { major: 2, minor: 0 } }),
+        );
+        assert_eq!(
+            ours.check(&hello(1, 0, 0xDCBA, Features::SUPPORTED)),
+            Err(Mismatch::Messages { ours: 0xABCD, theirs: 0xDCBA }),
+        );
+    }
+
+    #[test]
+    fn device_answers_with_common_features() {
+        let theirs = hello(1, 7, MESSAGE_HASH, Features::from_bits_truncate(u32::MAX));
+        assert_eq!(Hello::respond_to(&theirs), Hello::OURS);
+
+        let theirs = hello(1, 0, MESSAGE_HASH, Features::NONE);
+        assert_eq!(Hello::respond_to(&theirs).features, Features::NONE);
+    }
+}
@@ -0,0 +1,365 @@
+//! Lets several [`Controller`](super::Controller)s share one device.
+//!
+//! A [`Device`](super::Device) serves exactly one controller. A [`Hub`] takes
+//! the device's place and serves many, each over its own transport (i.e. an
+//! instructor's GUI and a handful of students following along).
+//!
+//! Each connection gets its own session: sequence numbers, duplicate
+//! detection and pending `run_until_event` calls are all tracked per session.
+//!
+//! ## Ownership
+//!
+//! Exactly one session (or none) *owns* the device; the rest are viewers. Only
+//! the owner may make requests that [change the
+//! device](super::RequestMessage::is_mutating); viewers that try get a
+//! [`NotPermitted`](super::ResponseMessage::NotPermitted) response instead.
+//! The `Control` impl on [`Controller`](super::Controller) logs and ignores
+//! these (`set_breakpoint` and friends return their usual errors, `step`
+//! returns `None`); viewers that want to know should use the `try_*` methods
+//! on `Controller`, which return [`RpcError::NotPermitted`](super::RpcError).
+//! Who owns the device is decided on the device side; see [`Hub::set_owner`].
+//!
+//! ## Broadcasts
+//!
+//! `run_until_event` from a viewer doesn't start the device; it just waits for
+//! the next event. When the device does hit an event (i.e. because the owner
+//! ran it and it hit a breakpoint), every session that's waiting gets told.
+//!
+//! Other changes aren't pushed to the viewers: instead every session can ask
+//! for its [`SessionInfo`] which has a count of the changes made to the device
+//! so far. Viewers can poll this cheaply and only fetch the rest of the
+//! device's state when it has changed.
+
+use serde::{Deserialize, Serialize};
+
+/// What a session is allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    /// May make any request.
+    Owner,
+    /// May only make requests that don't change the device.
+    Viewer,
+}
+
+/// A session's view of the [`Hub`] (or [`Device`](super::Device)) it's talking
+/// to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub role: Role,
+    /// Number of changes made to the device (by any session) plus the number
+    /// of events it has hit. Wraps around.
+    pub changes: u64,
+}
+
+using_std! {
+    use super::{Encode, Decode, Transport};
+    use super::{Control, RequestMessage, ResponseMessage};
+    use super::messages::{Envelope, SeqNum, RequestEnvelope, ResponseEnvelope};
+    use super::handshake::Hello;
+    use super::notifications::{Console, InputBuffer, Notification, Subscription, Topics, CONSOLE_CHUNK_SIZE};
+    use super::encoding::Transparent;
+    use super::device::{dispatch, RW_CLONE};
+
+    use std::fmt::Debug;
+    use std::future::Future;
+    use std::marker::PhantomData;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Waker};
+    use std::vec::Vec;
+
+    /// Identifies a session on a [`Hub`].
+    ///
+    /// Ids aren't reused once a session is removed.
+    pub type SessionId = usize;
+
+    #[derive(Debug)]
+    struct Session<T> {
+        transport: T,
+        last_handled: Option<(SeqNum, RequestMessage, ResponseMessage)>,
+        // The sequence number of the `RunUntilEvent` request this session is
+        // waiting on the answer to.
+        waiting_for_event: Option<SeqNum>,
+        subscription: Subscription,
+    }
+
+    /// A [`Device`](super::Device) for several controllers at once; see the
+    /// [module docs](self).
+    #[derive(Debug)]
+    pub struct Hub<
+        T,
+        C,
+        ReqDec = Transparent<RequestEnvelope>,
+        RespEnc = Transparent<ResponseEnvelope>,
+    >
+    where
+        ReqDec: Decode<RequestEnvelope>,
+        RespEnc: Encode<ResponseEnvelope>,
+        T: Transport<<RespEnc as Encode<ResponseEnvelope>>::Encoded, <ReqDec as Decode<RequestEnvelope>>::Encoded>,
+        C: Control,
+    {
+        _control_impl: PhantomData<C>,
+        enc: RespEnc,
+        dec: ReqDec,
+        sessions: Vec<Option<Session<T>>>,
+        owner: Option<SessionId>,
+        event_future: Option<C::EventFuture>,
+        changes: u64,
+        console_input: InputBuffer,
+    }
+
+    impl<T, C, D, E> Hub<T, C, D, E>
+    where
+        D: Decode<RequestEnvelope>,
+        E: Encode<ResponseEnvelope>,
+        T: Transport<<E as Encode<ResponseEnvelope>>::Encoded, <D as Decode<RequestEnvelope>>::Encoded>,
+        C: Control,
+    {
+        pub fn new(enc: E, dec: D) -> Self {
+            Self {
+                _control_impl: PhantomData,
+                enc,
+                dec,
+                sessions: Vec::new(),
+                owner: None,
+                event_future: None,
+                changes: 0,
+                console_input: InputBuffer::default(),
+            }
+        }
+
+        /// Starts a session (as a viewer) on the given transport.
+        pub fn add_session(&mut self, transport: T) -> SessionId {
+            self.sessions.push(Some(Session {
+                transport,
+                last_handled: None,
+                waiting_for_event: None,
+                subscription: Subscription::default(),
+            }));
+
+            self.sessions.len() - 1
+        }
+
+        /// Ends a session, handing back its transport.
+        ///
+        /// If the session owned the device, nobody does now.
+        pub fn remove_session(&mut self, id: SessionId) -> Option<T> {
+            let session = self.sessions.get_mut(id)?.take()?;
+
+            if self.owner == Some(id) {
+                self.owner = None;
+            }
+
+            Some(session.transport)
+        }
+
+        /// Makes `id` the owner of the device; the current owner (if any)
+        /// becomes a viewer.
+        ///
+        /// Passing `None` makes every session a viewer. Returns `false` (and
+        /// changes nothing) if there's no session with the given id.
+        pub fn set_owner(&mut self, id: Option<SessionId>) -> bool {
+            if let Some(id) = id {
+                if self.transport(id).is_none() {
+                    return false;
+                }
+            }
+
+            self.owner = id;
+            true
+        }
+
+        pub fn owner(&self) -> Option<SessionId> {
+            self.owner
+        }
+
+        pub fn role(&self, id: SessionId) -> Option<Role> {
+            self.transport(id)?;
+
+            Some(if self.owner == Some(id) { Role::Owner } else { Role::Viewer })
+        }
+
+        pub fn transport(&self, id: SessionId) -> Option<&T> {
+            self.sessions.get(id)?.as_ref().map(|s| &s.transport)
+        }
+
+        pub fn num_sessions(&self) -> usize {
+            self.sessions.iter().flatten().count()
+        }
+    }
+
+    impl<T, C, D, E> Hub<T, C, D, E>
+    where
+        D: Decode<RequestEnvelope>,
+        E: Encode<ResponseEnvelope>,
+        T: Transport<<E as Encode<ResponseEnvelope>>::Encoded, <D as Decode<RequestEnvelope>>::Encoded>,
+        C: Control,
+        <C as Control>::EventFuture: Unpin,
+    {
+        /// Like [`Device::step`](super::Device::step): makes progress on `c`
+        /// and handles whatever requests have come in on all the sessions.
+        ///
+        /// Returns the number of messages processed and the number of
+        /// instructions executed.
+        #[allow(unsafe_code)]
+        pub fn step(&mut self, c: &mut C) -> (usize, usize) {
+            use RequestMessage::*;
+            use ResponseMessage as R;
+
+            let Self { enc, dec, sessions, owner, event_future, changes, console_input, .. } = self;
+            let mut num_processed_messages = 0;
+
+            let num_executed_instructions = c.tick();
+
+            // See `Device::step` for why we poll with a no-op waker.
+            let mut event = None;
+            if let Some(ref mut f) = event_future {
+                if let Poll::Ready(e) = Pin::new(f).poll(&mut Context::from_waker(&unsafe { Waker::from_raw(RW_CLONE(&())) })) {
+                    *event_future = None;
+                    *changes = changes.wrapping_add(1);
+                    event = Some(e);
+                }
+            }
+
+            for session in sessions.iter_mut().flatten() {
+                notify(enc, session, c);
+
+                if let Some(event) = event {
+                    if let Some(seq) = session.waiting_for_event.take() {
+                        send(enc, &session.transport, Envelope::new(seq, R::RunUntilEvent(event)));
+                    }
+                }
+            }
+
+            for (id, session) in sessions.iter_mut().enumerate() {
+                let session = if let Some(s) = session { s } else { continue };
+                let role = if *owner == Some(id) { Role::Owner } else { Role::Viewer };
+
+                while let Ok(encoded) = session.transport.get() {
+                    let Envelope { seq, message: m } = match dec.decode(&encoded) {
+                        Ok(m) => m,
+                        Err(e) => {
+                            log::trace!("Decode Error on session {}: `{:?}`", id, e);
+                            session.transport.record_get_error();
+
+                            continue;
+                        }
+                    };
+
+                    num_processed_messages += 1;
+
+                    if let Some((last_seq, ref last_req, ref last_resp)) = session.last_handled {
+                        if last_seq == seq && *last_req == m {
+                            send(enc, &session.transport, Envelope::new(seq, last_resp.clone()));
+                            continue;
+                        }
+                    }
+
+                    let req = m.clone();
+
+                    let resp = match (m, role) {
+                        // Viewers can wait for events but can't start the
+                        // device:
+                        (RunUntilEvent, role) => {
+                            if role == Role::Owner && event_future.is_none() {
+                                *event_future = Some(c.run_until_event());
+                                *changes = changes.wrapping_add(1);
+                            }
+
+                            session.waiting_for_event = Some(seq);
+                            R::RunUntilEventAck
+                        },
+                        (m, Role::Viewer) if m.is_mutating() => R::NotPermitted,
+                        (m, _) => {
+                            if m.is_mutating() {
+                                *changes = changes.wrapping_add(1);
+                            }
+
+                            match dispatch(c, m, (T::ID, T::VER)) {
+                                Ok(resp) => resp,
+                                Err(Handshake { hello }) => R::Handshake(Hello::respond_to(&hello)),
+                                Err(GetSessionInfo) => R::GetSessionInfo(SessionInfo { role, changes: *changes }),
+                                Err(Subscribe { topics }) => {
+                                    session.subscription.set(topics);
+                                    R::Subscribe(topics)
+                                },
+                                // Only the owner gets this far:
+                                Err(ConsoleInput { len, data }) => {
+                                    let len = (len as usize).min(CONSOLE_CHUNK_SIZE);
+                                    R::ConsoleInput(console_input.put(&data[..len]) as u8)
+                                },
+                                Err(other) => unreachable!("`dispatch` only hands back session requests, not `{:?}`", other),
+                            }
+                        },
+                    };
+
+                    notify(enc, session, c);
+
+                    send(enc, &session.transport, Envelope::new(seq, resp.clone()));
+                    session.last_handled = Some((seq, req, resp));
+                }
+            }
+
+            // Everyone else hears about what the sessions above changed:
+            for session in sessions.iter_mut().flatten() {
+                notify(enc, session, c);
+            }
+
+            (num_processed_messages, num_executed_instructions)
+        }
+
+        /// Sends console output to every session that has subscribed to it;
+        /// see [`Device::push_console`](super::Device::push_console).
+        pub fn push_console(&mut self, bytes: &[u8]) {
+            let Self { enc, sessions, .. } = self;
+
+            for session in sessions.iter_mut().flatten() {
+                if session.subscription.topics.contains(Topics::CONSOLE) {
+                    for n in Notification::console(bytes) {
+                        send(enc, &session.transport, session.subscription.envelope(n));
+                    }
+                }
+            }
+        }
+
+        /// Takes the oldest byte of console input that the owner has sent;
+        /// see [`Device::take_console_input`](super::Device::take_console_input).
+        pub fn take_console_input(&mut self) -> Option<u8> {
+            self.console_input.pop()
+        }
+    }
+
+    impl<T, C, D, E> Console for Hub<T, C, D, E>
+    where
+        D: Decode<RequestEnvelope>,
+        E: Encode<ResponseEnvelope>,
+        T: Transport<<E as Encode<ResponseEnvelope>>::Encoded, <D as Decode<RequestEnvelope>>::Encoded>,
+        C: Control,
+        <C as Control>::EventFuture: Unpin,
+    {
+        fn push_console(&mut self, bytes: &[u8]) { Hub::push_console(self, bytes) }
+        fn take_console_input(&mut self) -> Option<u8> { Hub::take_console_input(self) }
+    }
+
+    fn notify<E, T, R, C>(enc: &mut E, session: &mut Session<T>, c: &C)
+    where
+        E: Encode<ResponseEnvelope>,
+        T: Transport<E::Encoded, R>,
+        C: Control,
+    {
+        let Session { subscription, transport, .. } = session;
+
+        subscription.check(c, |n| send(enc, transport, n));
+    }
+
+    // One misbehaving client shouldn't take the others down with it so we
+    // don't panic here.
+    fn send<E, T, R>(enc: &mut E, transport: &T, message: ResponseEnvelope)
+    where
+        E: Encode<ResponseEnvelope>,
+        T: Transport<E::Encoded, R>,
+    {
+        if let Err(e) = transport.send(enc.encode(&message)) {
+            log::warn!("Failed to send a response: `{:?}`", e);
+        }
+    }
+}
@@ -1,24 +1,33 @@
 //! Messages used for proxying [Control trait](super::Control) functions.
 
 use super::{State, Event};
+use super::handshake::Hello;
+use super::hub::SessionInfo;
+use super::notifications::{Notification, Topics, CONSOLE_CHUNK_SIZE};
 use crate::control::control::{
-    MAX_BREAKPOINTS, MAX_MEMORY_WATCHPOINTS, MAX_CALL_STACK_DEPTH
+    MAX_BREAKPOINTS, MAX_MEMORY_WATCHPOINTS
 };
+use crate::control::call_stack::CallStackPage;
 use crate::control::load::{
     LoadApiSession, CHUNK_SIZE_IN_WORDS, PageWriteStart, PageIndex, Offset,
     StartPageWriteError, PageChunkError, FinishPageWriteError
 };
-use crate::control::{ProgramMetadata, DeviceInfo, UnifiedRange, ProcessorMode, Idx};
+use crate::control::{ProgramMetadata, DeviceInfo, FaultHandling, UnifiedRange, Idx};
+use crate::control::interrupts::InterruptControllerState;
 use crate::error::Error as Lc3Error;
 use crate::peripherals::{
     adc::{AdcPinArr, AdcState, AdcReadError},
     gpio::{GpioPinArr, GpioState, GpioReadError},
-    pwm::{PwmPinArr, PwmState},
+    pwm::{PwmConfig, PwmPinArr, PwmState},
     timers::{TimerArr, TimerMode, TimerState},
 };
 
 use lc3_isa::{Addr, Reg, Word};
 
+/// The most words a single [`ReadWords`](RequestMessage::ReadWords) request
+/// can ask for.
+pub const READ_WORDS_CHUNK_LEN: usize = 16;
+
 use serde::{Serialize, Deserialize};
 
 // TODO: auto gen (proc macro, probably) the types below from and the `Control`
@@ -44,14 +53,63 @@ use serde::{Serialize, Deserialize};
 static __REQ_SIZE_CHECK: () = {
     let canary = [()];
 
-    canary[REQUEST_MESSAGE_SIZE - 40] // panic if the size of RequestMessage changes
+    canary[REQUEST_ENVELOPE_SIZE - 48] // panic if the size of RequestEnvelope changes
 };
 
 pub const REQUEST_MESSAGE_SIZE: usize = core::mem::size_of::<RequestMessage>();
+/// The size of a [`RequestMessage`] with its [sequence number](SeqNum); this is
+/// what actually gets sent.
+pub const REQUEST_ENVELOPE_SIZE: usize = core::mem::size_of::<RequestEnvelope>();
+
+#[allow(dead_code)]
+static __RESP_SIZE_CHECK: () = {
+    let canary = [()];
+
+    canary[RESPONSE_ENVELOPE_SIZE - 72] // panic if the size of ResponseEnvelope changes
+};
+
+pub const RESPONSE_MESSAGE_SIZE: usize = core::mem::size_of::<ResponseMessage>();
+/// The size of a [`ResponseMessage`] with its [sequence number](SeqNum).
+pub const RESPONSE_ENVELOPE_SIZE: usize = core::mem::size_of::<ResponseEnvelope>();
+
+// Hashes the definitions of the message types (whitespace aside) so that the
+// two ends of a session can tell whether they agree on what the messages look
+// like; see the `handshake` module.
+macro_rules! hashed {
+    ($($item:item)*) => {
+        $($item)*
+
+        /// A hash of the definitions of [`RequestMessage`] and
+        /// [`ResponseMessage`].
+        pub const MESSAGE_HASH: u64 = fnv1a_ignoring_whitespace(
+            concat!($(stringify!($item)),*).as_bytes()
+        );
+    };
+}
+
+const fn fnv1a_ignoring_whitespace(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_whitespace() {
+            hash ^= bytes[i] as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+        }
 
+        i += 1;
+    }
+
+    hash
+}
+
+hashed! {
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[deny(clippy::large_enum_variant)]
 pub enum RequestMessage { // messages for everything but tick()
+    // This must stay the first variant; see the `handshake` module.
+    Handshake { hello: Hello },
+
     GetPc,
     SetPc { addr: Addr },
 
@@ -64,6 +122,8 @@ pub enum RequestMessage { // messages for everything but tick()
 
     ReadWord { addr: Addr },
     WriteWord { addr: Addr, word: Word },
+    // `len` is at most `READ_WORDS_CHUNK_LEN`.
+    ReadWords { start: Addr, len: u8 },
 
     StartPageWrite { page: LoadApiSession<PageWriteStart>, checksum: u64 },
     SendPageChunk { offset: LoadApiSession<Offset>, chunk: [Word; CHUNK_SIZE_IN_WORDS as usize] },
@@ -82,7 +142,7 @@ pub enum RequestMessage { // messages for everything but tick()
     SetDepthCondition { condition: UnifiedRange<u64> },
     UnsetDepthCondition,
     GetDepth,
-    GetCallStack,
+    GetCallStack { page: u16 },
 
     // no tick!
     RunUntilEvent,
@@ -96,6 +156,9 @@ pub enum RequestMessage { // messages for everything but tick()
 
     GetError,
 
+    SetFaultHandling { handling: FaultHandling },
+    GetFaultHandling,
+
     GetGpioStates,
     GetGpioReadings,
     GetAdcStates,
@@ -105,27 +168,29 @@ pub enum RequestMessage { // messages for everything but tick()
     GetPwmStates,
     GetPwmConfig,
     GetClock,
+    GetInterruptControllerState,
 
     GetDeviceInfo,
 
     GetProgramMetadata,
     SetProgramMetadata { metadata: ProgramMetadata },
 
-    // no id!
-}
-
-#[allow(dead_code)]
-static __RESP_SIZE_CHECK: () = {
-    let canary = [()];
+    // See the `hub` module.
+    GetSessionInfo,
 
-    canary[RESPONSE_MESSAGE_SIZE - 72] // panic if the size of ResponseMessage changes
-};
+    // See the `notifications` module.
+    Subscribe { topics: Topics },
+    ConsoleInput { len: u8, data: [u8; CONSOLE_CHUNK_SIZE] }, // Only the first `len` bytes are valid.
 
-pub const RESPONSE_MESSAGE_SIZE: usize = core::mem::size_of::<ResponseMessage>();
+    // no id!
+}
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[deny(clippy::large_enum_variant)]
 pub enum ResponseMessage { // messages for everything but tick()
+    // This must stay the first variant; see the `handshake` module.
+    Handshake(Hello),
+
     GetPc(Addr),
     SetPc,
 
@@ -138,6 +203,7 @@ pub enum ResponseMessage { // messages for everything but tick()
 
     ReadWord(Word),
     WriteWord,
+    ReadWords([Word; READ_WORDS_CHUNK_LEN]), // Only the first `len` words are valid.
 
     StartPageWrite(Result<LoadApiSession<PageIndex>, StartPageWriteError>),
     SendPageChunk(Result<(), PageChunkError>),
@@ -156,7 +222,7 @@ pub enum ResponseMessage { // messages for everything but tick()
     SetDepthCondition(Result<Option<UnifiedRange<u64>>, ()>),
     UnsetDepthCondition(Option<UnifiedRange<u64>>),
     GetDepth(Result<u64, ()>),
-    GetCallStack([Option<(Addr, ProcessorMode)>; MAX_CALL_STACK_DEPTH]),
+    GetCallStack(CallStackPage),
 
     // no tick!
     RunUntilEventAck, // Special acknowledge message for run until event.
@@ -170,6 +236,9 @@ pub enum ResponseMessage { // messages for everything but tick()
 
     GetError(Option<Lc3Error>),
 
+    SetFaultHandling,
+    GetFaultHandling(FaultHandling),
+
     GetGpioStates(GpioPinArr<GpioState>),
     GetGpioReadings(GpioPinArr<Result<bool, GpioReadError>>),
     GetAdcStates(AdcPinArr<AdcState>),
@@ -177,16 +246,27 @@ pub enum ResponseMessage { // messages for everything but tick()
     GetTimerModes(TimerArr<TimerMode>),
     GetTimerStates(TimerArr<TimerState>),
     GetPwmStates(PwmPinArr<PwmState>),
-    GetPwmConfig(PwmPinArr<u8>), // TODO
+    GetPwmConfig(PwmPinArr<PwmConfig>),
     GetClock(Word),
+    GetInterruptControllerState(InterruptControllerState),
 
     GetDeviceInfo(DeviceInfo),
 
     GetProgramMetadata(ProgramMetadata),
     SetProgramMetadata,
 
+    // See the `hub` module.
+    GetSessionInfo(SessionInfo),
+    NotPermitted, // This session doesn't own the device.
+
+    // See the `notifications` module.
+    Subscribe(Topics),
+    Notification(Notification), // Unsolicited!
+    ConsoleInput(u8), // How many bytes the device took.
+
     // no id!
 }
+}
 
 
 // This workaround allows us to avoid having a Clone impl on RequestMessage and
@@ -266,6 +346,7 @@ impl Clone for RequestMessage {
         }
 
         variants! {
+            Handshake { hello },
             GetPc,
             SetPc { addr },
             GetRegister { reg },
@@ -273,6 +354,7 @@ impl Clone for RequestMessage {
             GetRegistersPsrAndPc,
             ReadWord { addr },
             WriteWord { addr, word },
+            ReadWords { start, len },
             SetBreakpoint { addr },
             UnsetBreakpoint { idx },
             GetBreakpoints,
@@ -284,13 +366,15 @@ impl Clone for RequestMessage {
             SetDepthCondition { condition },
             UnsetDepthCondition,
             GetDepth,
-            GetCallStack,
+            GetCallStack { page },
             RunUntilEvent,
             Step,
             Pause,
             GetState,
             Reset,
             GetError,
+            SetFaultHandling { handling },
+            GetFaultHandling,
             GetGpioStates,
             GetGpioReadings,
             GetAdcStates,
@@ -300,9 +384,13 @@ impl Clone for RequestMessage {
             GetPwmStates,
             GetPwmConfig,
             GetClock,
+            GetInterruptControllerState,
             GetDeviceInfo,
             GetProgramMetadata,
-            SetProgramMetadata { metadata }
+            SetProgramMetadata { metadata },
+            GetSessionInfo,
+            Subscribe { topics },
+            ConsoleInput { len, data }
         }
     }
 }
@@ -329,6 +417,7 @@ impl Clone for ResponseMessage {
         }
 
         variants! {
+            Handshake(h),
             GetPc(a),
             SetPc,
             GetRegister(w),
@@ -336,6 +425,7 @@ impl Clone for ResponseMessage {
             GetRegistersPsrAndPc(t),
             ReadWord(w),
             WriteWord,
+            ReadWords(w),
             SetBreakpoint(r),
             UnsetBreakpoint(r),
             GetBreakpoints(bps),
@@ -355,6 +445,8 @@ impl Clone for ResponseMessage {
             GetState(s),
             Reset,
             GetError(e),
+            SetFaultHandling,
+            GetFaultHandling(h),
             GetGpioStates(s),
             GetGpioReadings(r),
             GetAdcStates(s),
@@ -364,12 +456,121 @@ impl Clone for ResponseMessage {
             GetPwmStates(s),
             GetPwmConfig(c),
             GetClock(w),
+            GetInterruptControllerState(s),
             GetDeviceInfo(i),
             GetProgramMetadata(m),
             SetProgramMetadata,
+            GetSessionInfo(i),
+            NotPermitted,
+            Subscribe(t),
+            Notification(n),
+            ConsoleInput(n),
 
             SendPageChunk(r),
             FinishPageWrite(r)
         }
     }
 }
+
+impl RequestMessage {
+    /// Whether handling this request twice has the same effect as handling it
+    /// once.
+    ///
+    /// The [`Controller`](super::Controller) uses this to decide which requests
+    /// it's always safe to send again (see [`RetryPolicy`](super::RetryPolicy)).
+    pub fn is_idempotent(&self) -> bool {
+        use RequestMessage::*;
+
+        match self {
+            Handshake { .. } |
+            GetPc | SetPc { .. } |
+            GetRegister { .. } | SetRegister { .. } | GetRegistersPsrAndPc |
+            ReadWord { .. } | WriteWord { .. } | ReadWords { .. } |
+            GetBreakpoints | GetMaxBreakpoints |
+            GetMemoryWatchpoints | GetMaxMemoryWatchpoints |
+            GetDepth | GetCallStack { .. } |
+            Pause | GetState | Reset | GetError |
+            SetFaultHandling { .. } | GetFaultHandling |
+            GetGpioStates | GetGpioReadings | GetAdcStates | GetAdcReadings |
+            GetTimerModes | GetTimerStates | GetPwmStates | GetPwmConfig |
+            GetClock | GetInterruptControllerState | GetDeviceInfo |
+            GetProgramMetadata | SetProgramMetadata { .. } |
+            GetSessionInfo | Subscribe { .. } => true,
+
+            // These either hand out or consume something (a load API session,
+            // a breakpoint index, etc.) or make the device do something:
+            StartPageWrite { .. } | SendPageChunk { .. } | FinishPageWrite { .. } |
+            SetBreakpoint { .. } | UnsetBreakpoint { .. } |
+            SetMemoryWatchpoint { .. } | UnsetMemoryWatchpoint { .. } |
+            SetDepthCondition { .. } | UnsetDepthCondition |
+            RunUntilEvent | Step | ConsoleInput { .. } => false,
+        }
+    }
+
+    /// Whether this request changes the state of the device.
+    ///
+    /// On a [`Hub`](super::hub::Hub) only the session that owns the device is
+    /// allowed to make these requests.
+    pub fn is_mutating(&self) -> bool {
+        use RequestMessage::*;
+
+        match self {
+            Handshake { .. } |
+            GetPc | GetRegister { .. } | GetRegistersPsrAndPc |
+            ReadWord { .. } | ReadWords { .. } |
+            GetBreakpoints | GetMaxBreakpoints |
+            GetMemoryWatchpoints | GetMaxMemoryWatchpoints |
+            GetDepth | GetCallStack { .. } |
+            GetState | GetError | GetFaultHandling |
+            GetGpioStates | GetGpioReadings | GetAdcStates | GetAdcReadings |
+            GetTimerModes | GetTimerStates | GetPwmStates | GetPwmConfig |
+            GetClock | GetInterruptControllerState | GetDeviceInfo |
+            GetProgramMetadata | GetSessionInfo |
+            Subscribe { .. } => false,
+
+            SetPc { .. } | SetRegister { .. } | WriteWord { .. } |
+            StartPageWrite { .. } | SendPageChunk { .. } | FinishPageWrite { .. } |
+            SetBreakpoint { .. } | UnsetBreakpoint { .. } |
+            SetMemoryWatchpoint { .. } | UnsetMemoryWatchpoint { .. } |
+            SetDepthCondition { .. } | UnsetDepthCondition |
+            RunUntilEvent | Step | Pause | Reset | SetFaultHandling { .. } |
+            SetProgramMetadata { .. } | ConsoleInput { .. } => true,
+        }
+    }
+}
+
+/// Sequence numbers tag each request and the response to it.
+///
+/// These wrap around; all that matters is that consecutive requests from a
+/// [`Controller`](super::Controller) have different sequence numbers.
+pub type SeqNum = u16;
+
+/// What actually goes over the wire: a message and its [sequence number].
+///
+/// Requests are numbered by the [`Controller`](super::Controller) and the
+/// [`Device`](super::Device) echoes the number back on its response; this lets
+/// the controller match responses to requests (and ignore stale responses to
+/// requests it has already given up on) and lets the device spot requests that
+/// it has already handled (i.e. when a response gets lost and the controller
+/// sends the request again).
+///
+/// The exception is the [`RunUntilEvent`](ResponseMessage::RunUntilEvent)
+/// response which is sent whenever the event actually happens; it carries the
+/// sequence number of the [`RunUntilEvent`](RequestMessage::RunUntilEvent)
+/// request that asked for it.
+///
+/// [sequence number]: SeqNum
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope<M> {
+    pub seq: SeqNum,
+    pub message: M,
+}
+
+impl<M> Envelope<M> {
+    pub const fn new(seq: SeqNum, message: M) -> Self {
+        Self { seq, message }
+    }
+}
+
+pub type RequestEnvelope = Envelope<RequestMessage>;
+pub type ResponseEnvelope = Envelope<ResponseMessage>;
@@ -54,7 +54,10 @@ use super::{Event, State, Control};
 // log crate.
 
 mod messages;
-pub use messages::{RequestMessage, ResponseMessage};
+pub use messages::{
+    RequestMessage, ResponseMessage, Envelope, SeqNum, RequestEnvelope,
+    ResponseEnvelope, READ_WORDS_CHUNK_LEN,
+};
 
 pub mod encoding;
 pub use encoding::{Encode, Decode};
@@ -65,12 +68,24 @@ pub use transport::Transport;
 pub mod futures;
 pub use futures::{EventFutureSharedState, EventFutureSharedStatePorcelain, SimpleEventFutureSharedState, EventFuture};
 
+pub mod handshake;
+pub use handshake::{Hello, HandshakeError, PROTOCOL_VERSION};
+
 pub mod controller;
-pub use controller::Controller;
+pub use controller::{Controller, RetryPolicy, Timeout, RpcError, MAX_PIPELINE_DEPTH};
+
+pub mod async_controller;
+pub use async_controller::{AsyncController, Wait, Spin};
 
 pub mod device;
 pub use device::Device;
 
+pub mod hub;
+pub use hub::{Role, SessionInfo};
+
+pub mod notifications;
+pub use notifications::{Console, Notification, Topics};
+
 mod fault_tolerance; // Just for the docs (when built with private docs).
 
 use core::fmt::Debug;
@@ -82,17 +97,17 @@ pub fn new_pair<
     Resp: Debug,
 
     // Controller:
-    ReqEnc: Default + Encode<Req>, // = Transparent,
-    RespDec: Default + Decode<Resp>, // = Transparent,
+    ReqEnc: Default + Encode<Envelope<Req>>, // = Transparent,
+    RespDec: Default + Decode<Envelope<Resp>>, // = Transparent,
     // Sends Requests, Receives Responses:
-    ContTrans: Transport<<ReqEnc as Encode<Req>>::Encoded, <RespDec as Decode<Resp>>::Encoded>,
+    ContTrans: Transport<<ReqEnc as Encode<Envelope<Req>>>::Encoded, <RespDec as Decode<Envelope<Resp>>>::Encoded>,
     S: EventFutureSharedStatePorcelain,
 
     // Device:
-    ReqDec: Default + Decode<Req>, // = Transparent,
-    RespEnc: Default + Encode<Resp>, // = Transparent,
+    ReqDec: Default + Decode<Envelope<Req>>, // = Transparent,
+    RespEnc: Default + Encode<Envelope<Resp>>, // = Transparent,
     // Sends Responses, Receives Requests:
-    DevTrans: Transport<<RespEnc as Encode<Resp>>::Encoded, <ReqDec as Decode<Req>>::Encoded>,
+    DevTrans: Transport<<RespEnc as Encode<Envelope<Resp>>>::Encoded, <ReqDec as Decode<Envelope<Req>>>::Encoded>,
     C: Control,
 >(
         cont_trans: ContTrans,
@@ -127,10 +142,10 @@ where
     ResponseMessage: Into<Resp>,
 
     // Controller's inputs (encoded responses) must = Device's outputs (encoded responses)
-    RespDec: Decode<Resp, Encoded = <RespEnc as Encode<Resp>>::Encoded>,
+    RespDec: Decode<Envelope<Resp>, Encoded = <RespEnc as Encode<Envelope<Resp>>>::Encoded>,
 
     // Devices's inputs (encoded requests) must = Controller's outputs (encoded requests)
-    ReqDec: Decode<Req, Encoded = <ReqEnc as Encode<Req>>::Encoded>,
+    ReqDec: Decode<Envelope<Req>, Encoded = <ReqEnc as Encode<Envelope<Req>>>::Encoded>,
 {
     macro_rules! d { () => {Default::default()}; }
 
@@ -143,6 +158,7 @@ where
 using_std! {
     pub use transport::MpscTransport;
     pub use futures::SyncEventFutureSharedState;
+    pub use hub::{Hub, SessionId};
 
 
     pub fn mpsc_pair<
@@ -151,13 +167,13 @@ using_std! {
         Resp: Debug,
 
         // Controller:
-        ReqEnc: Default + Encode<Req>, // = Transparent,
-        RespDec: Default + Decode<Resp>, // = Transparent,
+        ReqEnc: Default + Encode<Envelope<Req>>, // = Transparent,
+        RespDec: Default + Decode<Envelope<Resp>>, // = Transparent,
         S: EventFutureSharedStatePorcelain,
 
         // Device:
-        ReqDec: Default + Decode<Req>, // = Transparent,
-        RespEnc: Default + Encode<Resp>, // = Transparent,
+        ReqDec: Default + Decode<Envelope<Req>>, // = Transparent,
+        RespEnc: Default + Encode<Envelope<Resp>>, // = Transparent,
         C: Control,
     >(
         state: &'a S,
@@ -165,7 +181,7 @@ using_std! {
         Controller<
             'a,
             // Sends Requests, Receives Responses:
-            MpscTransport<<ReqEnc as Encode<Req>>::Encoded, <RespDec as Decode<Resp>>::Encoded>,
+            MpscTransport<<ReqEnc as Encode<Envelope<Req>>>::Encoded, <RespDec as Decode<Envelope<Resp>>>::Encoded>,
             S,
             Req,
             Resp,
@@ -174,7 +190,7 @@ using_std! {
         >,
         Device<
             // Sends Responses, Receives Requests:
-            MpscTransport<<RespEnc as Encode<Resp>>::Encoded, <ReqDec as Decode<Req>>::Encoded>,
+            MpscTransport<<RespEnc as Encode<Envelope<Resp>>>::Encoded, <ReqDec as Decode<Envelope<Req>>>::Encoded>,
             C,
             Req,
             Resp,
@@ -190,10 +206,10 @@ using_std! {
         ResponseMessage: Into<Resp>,
 
         // Controller's inputs (encoded responses) must = Device's outputs (encoded responses)
-        RespDec: Decode<Resp, Encoded = <RespEnc as Encode<Resp>>::Encoded>,
+        RespDec: Decode<Envelope<Resp>, Encoded = <RespEnc as Encode<Envelope<Resp>>>::Encoded>,
 
         // Devices's inputs (encoded requests) must = Controller's outputs (encoded requests)
-        ReqDec: Decode<Req, Encoded = <ReqEnc as Encode<Req>>::Encoded>,
+        ReqDec: Decode<Envelope<Req>, Encoded = <ReqEnc as Encode<Envelope<Req>>>::Encoded>,
     {
         let (controller, device) = MpscTransport::new();
 
@@ -206,12 +222,12 @@ using_std! {
         Resp: Debug,
 
         // Controller:
-        ReqEnc: Default + Encode<Req>, // = Transparent,
-        RespDec: Default + Decode<Resp>, // = Transparent,
+        ReqEnc: Default + Encode<Envelope<Req>>, // = Transparent,
+        RespDec: Default + Decode<Envelope<Resp>>, // = Transparent,
 
         // Device:
-        ReqDec: Default + Decode<Req>, // = Transparent,
-        RespEnc: Default + Encode<Resp>, // = Transparent,
+        ReqDec: Default + Decode<Envelope<Req>>, // = Transparent,
+        RespEnc: Default + Encode<Envelope<Resp>>, // = Transparent,
         C: Control,
     >(
             state: &'a SyncEventFutureSharedState
@@ -219,7 +235,7 @@ using_std! {
         Controller<
             'a,
             // Sends Requests, Receives Responses:
-            MpscTransport<<ReqEnc as Encode<Req>>::Encoded, <RespDec as Decode<Resp>>::Encoded>,
+            MpscTransport<<ReqEnc as Encode<Envelope<Req>>>::Encoded, <RespDec as Decode<Envelope<Resp>>>::Encoded>,
             SyncEventFutureSharedState,
             Req,
             Resp,
@@ -228,7 +244,7 @@ using_std! {
         >,
         Device<
             // Sends Responses, Receives Requests:
-            MpscTransport<<RespEnc as Encode<Resp>>::Encoded, <ReqDec as Decode<Req>>::Encoded>,
+            MpscTransport<<RespEnc as Encode<Envelope<Resp>>>::Encoded, <ReqDec as Decode<Envelope<Req>>>::Encoded>,
             C,
             Req,
             Resp,
@@ -244,10 +260,10 @@ using_std! {
         ResponseMessage: Into<Resp>,
 
         // Controller's inputs (encoded responses) must = Device's outputs (encoded responses)
-        RespDec: Decode<Resp, Encoded = <RespEnc as Encode<Resp>>::Encoded>,
+        RespDec: Decode<Envelope<Resp>, Encoded = <RespEnc as Encode<Envelope<Resp>>>::Encoded>,
 
         // Devices's inputs (encoded requests) must = Controller's outputs (encoded requests)
-        ReqDec: Decode<Req, Encoded = <ReqEnc as Encode<Req>>::Encoded>,
+        ReqDec: Decode<Envelope<Req>, Encoded = <ReqEnc as Encode<Envelope<Req>>>::Encoded>,
     {
         mpsc_pair(state)
     }
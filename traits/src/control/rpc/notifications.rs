@@ -0,0 +1,371 @@
+//! Messages that the device sends without being asked.
+//!
+//! Controllers that want to keep a UI up to date would otherwise have to poll
+//! (`get_state`, `get_gpio_states`, `get_clock`, etc.) which, over a slow
+//! link like UART, eats up most of the bandwidth. Instead, a controller can
+//! [subscribe](super::Controller::subscribe) to the [`Topics`] it cares about;
+//! the device then pushes a [`Notification`] whenever something in those
+//! topics changes (and once, with the current values, when the subscription
+//! starts).
+//!
+//! The [`Controller`](super::Controller) keeps the latest pushed values around
+//! and answers the corresponding [`Control`] calls from them instead of
+//! asking the device. Console output (which the [`Control`] interface doesn't
+//! otherwise have a way to get at) goes to the controller's [console
+//! sink](super::Controller::with_console_sink).
+//!
+//! Notifications are sent right before the response to the request that
+//! caused them so a controller that's just made a change sees it reflected in
+//! the pushed values by the time the request returns. Changes that the device
+//! makes on its own (i.e. halting while running) are picked up the next time
+//! the device is [stepped](super::Device::step).
+//!
+//! Notifications aren't retried. Instead, they're numbered (in the sequence
+//! number of their [`Envelope`]; from 1 up, wrapping around to 1, per
+//! connection) so that a controller can tell when it has missed some. When it
+//! has (or when a message arrives garbled, since it may have been a
+//! notification) the controller forgets all the pushed values and goes back to
+//! asking the device until they're pushed again. Notifications sent with a
+//! sequence number of 0 aren't numbered.
+//!
+//! Lost console output is gone for good.
+//!
+//! Console input goes the other way, as a regular request: the controller
+//! [sends](super::Controller::send_console_input) it (or forwards it from its
+//! [console source](super::Controller::with_console_source)) and the device
+//! holds on to it until it's [taken](super::Device::take_console_input) and
+//! handed to the input peripheral.
+
+use super::messages::{Envelope, ResponseEnvelope, ResponseMessage, SeqNum};
+use super::State;
+use crate::control::Control;
+use crate::peripherals::{
+    adc::{AdcPinArr, AdcState},
+    gpio::{GpioPinArr, GpioState},
+    pwm::{PwmConfig, PwmPinArr},
+    timers::{TimerArr, TimerMode, TimerState},
+};
+
+use lc3_isa::Word;
+
+use serde::{Deserialize, Serialize};
+
+/// Groups of values that a controller can ask to be told about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Topics(u32);
+
+impl Topics {
+    pub const NONE: Self = Self(0);
+
+    /// Transitions between [`State`]s.
+    pub const STATE: Self = Self(1 << 0);
+    /// Output that's been [pushed](super::Device::push_console) to the device.
+    pub const CONSOLE: Self = Self(1 << 1);
+    /// [`GpioState`]s.
+    pub const GPIO: Self = Self(1 << 2);
+    /// [`AdcState`]s.
+    pub const ADC: Self = Self(1 << 3);
+    /// [`TimerMode`]s and [`TimerState`]s.
+    pub const TIMERS: Self = Self(1 << 4);
+    /// [`PwmConfig`]s (which include the `PwmState`s).
+    pub const PWM: Self = Self(1 << 5);
+    /// The clock; this is only pushed once it has moved by at least
+    /// [`CLOCK_INTERVAL_MS`].
+    pub const CLOCK: Self = Self(1 << 6);
+
+    pub const ALL: Self = Self((1 << 7) - 1);
+
+    pub const fn bits(self) -> u32 { self.0 }
+
+    /// Topics we don't know about are dropped.
+    pub const fn from_bits_truncate(bits: u32) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    pub const fn union(self, other: Self) -> Self { Self(self.0 | other.0) }
+    pub const fn intersection(self, other: Self) -> Self { Self(self.0 & other.0) }
+    pub const fn difference(self, other: Self) -> Self { Self(self.0 & !other.0) }
+}
+
+/// How far the clock has to move before it's pushed again.
+pub const CLOCK_INTERVAL_MS: Word = 100;
+
+/// Number of bytes of console output that fit in one [`Notification`].
+pub const CONSOLE_CHUNK_SIZE: usize = 16;
+
+/// Number of bytes of console input a device holds on to until it's taken.
+pub const CONSOLE_INPUT_BUFFER_SIZE: usize = 4 * CONSOLE_CHUNK_SIZE;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Notification {
+    State(State),
+    /// Only the first `len` bytes of `data` are valid.
+    Console { len: u8, data: [u8; CONSOLE_CHUNK_SIZE] },
+    GpioStates(GpioPinArr<GpioState>),
+    AdcStates(AdcPinArr<AdcState>),
+    TimerModes(TimerArr<TimerMode>),
+    TimerStates(TimerArr<TimerState>),
+    PwmConfig(PwmPinArr<PwmConfig>),
+    Clock(Word),
+}
+
+impl Notification {
+    pub fn topic(&self) -> Topics {
+        use Notification::*;
+
+        match self {
+            State(_) => Topics::STATE,
+            Console { .. } => Topics::CONSOLE,
+            GpioStates(_) => Topics::GPIO,
+            AdcStates(_) => Topics::ADC,
+            TimerModes(_) | TimerStates(_) => Topics::TIMERS,
+            PwmConfig(_) => Topics::PWM,
+            Clock(_) => Topics::CLOCK,
+        }
+    }
+
+    /// Splits `bytes` into [`Console`](Notification::Console) notifications.
+    pub fn console(bytes: &[u8]) -> impl Iterator<Item = Notification> + '_ {
+        bytes.chunks(CONSOLE_CHUNK_SIZE).map(|chunk| {
+            let mut data = [0; CONSOLE_CHUNK_SIZE];
+            data[..chunk.len()].copy_from_slice(chunk);
+
+            Notification::Console { len: chunk.len() as u8, data }
+        })
+    }
+}
+
+/// The console side of a [`Device`](super::Device) or a
+/// [`Hub`](super::hub::Hub); whatever owns the I/O peripherals uses this to
+/// relay the console to and from controllers.
+pub trait Console {
+    /// Sends console output to the controllers that have subscribed to it.
+    fn push_console(&mut self, bytes: &[u8]);
+
+    /// Takes the oldest byte of console input that's been sent by a
+    /// controller.
+    fn take_console_input(&mut self) -> Option<u8>;
+}
+
+/// Console input that a device has been sent but hasn't handed off yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct InputBuffer {
+    len: usize,
+    data: [u8; CONSOLE_INPUT_BUFFER_SIZE],
+}
+
+impl Default for InputBuffer {
+    fn default() -> Self {
+        Self { len: 0, data: [0; CONSOLE_INPUT_BUFFER_SIZE] }
+    }
+}
+
+impl InputBuffer {
+    /// Takes as much of `bytes` as fits and returns how much that was.
+    pub(super) fn put(&mut self, bytes: &[u8]) -> usize {
+        let num = bytes.len().min(CONSOLE_INPUT_BUFFER_SIZE - self.len);
+        self.data[self.len..(self.len + num)].copy_from_slice(&bytes[..num]);
+        self.len += num;
+
+        num
+    }
+
+    /// Takes the oldest byte out of the buffer.
+    pub(super) fn pop(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let byte = self.data[0];
+        self.data.copy_within(1..self.len, 0);
+        self.len -= 1;
+
+        Some(byte)
+    }
+}
+
+/// The last values pushed for each topic.
+///
+/// The device keeps one of these per subscriber so it knows what's changed;
+/// the controller keeps one to answer `Control` calls with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct Snapshot {
+    pub(super) state: Option<State>,
+    pub(super) gpio_states: Option<GpioPinArr<GpioState>>,
+    pub(super) adc_states: Option<AdcPinArr<AdcState>>,
+    pub(super) timer_modes: Option<TimerArr<TimerMode>>,
+    pub(super) timer_states: Option<TimerArr<TimerState>>,
+    pub(super) pwm_config: Option<PwmPinArr<PwmConfig>>,
+    pub(super) clock: Option<Word>,
+}
+
+impl Snapshot {
+    /// Records a pushed value.
+    pub(super) fn update(&mut self, notification: &Notification) {
+        use Notification::*;
+
+        match notification {
+            State(s) => self.state = Some(*s),
+            Console { .. } => {},
+            GpioStates(s) => self.gpio_states = Some(s.clone()),
+            AdcStates(s) => self.adc_states = Some(s.clone()),
+            TimerModes(m) => self.timer_modes = Some(m.clone()),
+            TimerStates(s) => self.timer_states = Some(s.clone()),
+            PwmConfig(c) => self.pwm_config = Some(c.clone()),
+            Clock(c) => self.clock = Some(*c),
+        }
+    }
+
+    /// Forgets the values for the given topics (so that they get pushed again
+    /// or, on the controller, so that we go back to asking the device).
+    pub(super) fn forget(&mut self, topics: Topics) {
+        if topics.contains(Topics::STATE) { self.state = None; }
+        if topics.contains(Topics::GPIO) { self.gpio_states = None; }
+        if topics.contains(Topics::ADC) { self.adc_states = None; }
+        if topics.contains(Topics::TIMERS) { self.timer_modes = None; self.timer_states = None; }
+        if topics.contains(Topics::PWM) { self.pwm_config = None; }
+        if topics.contains(Topics::CLOCK) { self.clock = None; }
+    }
+}
+
+/// The sequence number of the notification after the one numbered `seq`.
+pub(super) const fn next_seq(seq: SeqNum) -> SeqNum {
+    match seq.wrapping_add(1) {
+        0 => 1,
+        n => n,
+    }
+}
+
+/// A subscriber, on the device side.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct Subscription {
+    pub(super) topics: Topics,
+    sent: Snapshot,
+    // The sequence number of the last notification we sent (0 for none).
+    last_seq: SeqNum,
+}
+
+impl Subscription {
+    /// Changes what we're subscribed to; everything in the new set of topics
+    /// gets sent again.
+    pub(super) fn set(&mut self, topics: Topics) {
+        self.topics = topics;
+        self.sent = Snapshot::default();
+    }
+
+    /// Numbers a notification for sending.
+    pub(super) fn envelope(&mut self, notification: Notification) -> ResponseEnvelope {
+        self.last_seq = next_seq(self.last_seq);
+        Envelope::new(self.last_seq, ResponseMessage::Notification(notification))
+    }
+
+    /// Calls `send` with a notification for each value that's changed since
+    /// we last sent it.
+    pub(super) fn check<C: Control + ?Sized>(&mut self, c: &C, mut send: impl FnMut(ResponseEnvelope)) {
+        let topics = self.topics;
+        let sent = &mut self.sent;
+        let last_seq = &mut self.last_seq;
+
+        macro_rules! check {
+            ($topic:ident: $field:ident = $get:expr => $variant:ident $(if $cond:expr)?) => {
+                if topics.contains(Topics::$topic) {
+                    let current = $get;
+                    let changed = match sent.$field {
+                        Some(ref prev) => *prev != current $(&& $cond(prev, &current))?,
+                        None => true,
+                    };
+
+                    if changed {
+                        sent.$field = Some(current.clone());
+
+                        *last_seq = next_seq(*last_seq);
+                        send(Envelope::new(*last_seq, ResponseMessage::Notification(Notification::$variant(current))));
+                    }
+                }
+            };
+        }
+
+        check!(STATE: state = c.get_state() => State);
+        check!(GPIO: gpio_states = c.get_gpio_states() => GpioStates);
+        check!(ADC: adc_states = c.get_adc_states() => AdcStates);
+        check!(TIMERS: timer_modes = c.get_timer_modes() => TimerModes);
+        check!(TIMERS: timer_states = c.get_timer_states() => TimerStates);
+        check!(PWM: pwm_config = c.get_pwm_config() => PwmConfig);
+        check!(CLOCK: clock = c.get_clock() => Clock
+            if |prev: &Word, current: &Word| current.wrapping_sub(*prev) >= CLOCK_INTERVAL_MS
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn console_chunks() {
+        let mut bytes = [0u8; 40];
+        for (i, b) in bytes.iter_mut().enumerate() { *b = i as u8; }
+        let chunks: [Notification; 3] = {
+            let mut iter = Notification::console(&bytes);
+            [iter.next().unwrap(), iter.next().unwrap(), iter.next().unwrap()]
+        };
+
+        assert_eq!(Notification::console(&bytes).count(), 3);
+
+        let mut reassembled = [0u8; 40];
+        let mut idx = 0;
+        for chunk in chunks.iter() {
+            if let Notification::Console { len, data } = chunk {
+                reassembled[idx..(idx + *len as usize)].copy_from_slice(&data[..*len as usize]);
+                idx += *len as usize;
+            } else {
+                panic!()
+            }
+        }
+
+        assert_eq!(idx, 40);
+        assert_eq!(reassembled, bytes);
+    }
+
+    #[test]
+    fn input_buffer() {
+        let mut buf = InputBuffer::default();
+        let bytes = [7u8; CONSOLE_INPUT_BUFFER_SIZE - 1];
+
+        assert_eq!(buf.put(&bytes), CONSOLE_INPUT_BUFFER_SIZE - 1);
+        assert_eq!(buf.put(&[1, 2, 3]), 1);
+        assert_eq!(buf.put(&[4]), 0);
+
+        assert_eq!(buf.pop(), Some(7));
+        assert_eq!(buf.put(&[4, 5]), 1);
+
+        let rest: Vec<u8> = core::iter::from_fn(|| buf.pop()).collect();
+        assert_eq!(rest.len(), CONSOLE_INPUT_BUFFER_SIZE);
+        assert_eq!(rest[CONSOLE_INPUT_BUFFER_SIZE - 2..], [1, 4]);
+        assert_eq!(buf.pop(), None);
+    }
+
+    #[test]
+    fn sequence_numbers_skip_zero() {
+        let mut sub = Subscription::default();
+
+        assert_eq!(sub.envelope(Notification::Clock(0)).seq, 1);
+        assert_eq!(sub.envelope(Notification::Clock(0)).seq, 2);
+
+        assert_eq!(next_seq(SeqNum::MAX), 1);
+    }
+
+    #[test]
+    fn topics() {
+        assert_eq!(Topics::from_bits_truncate(u32::MAX), Topics::ALL);
+        assert!(Topics::ALL.contains(Topics::CLOCK));
+        assert_eq!(Topics::ALL.difference(Topics::CLOCK).contains(Topics::CLOCK), false);
+        assert_eq!(Topics::STATE.union(Topics::GPIO).intersection(Topics::GPIO), Topics::GPIO);
+    }
+}
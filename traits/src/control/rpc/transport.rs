@@ -32,6 +32,14 @@ pub trait Transport<SendFormat, RecvFormat> {
 
     // Number of invalid/discarded messages.
     fn num_get_errors(&self) -> u64 { 0 }
+
+    /// Called when a message that [`get`](Transport::get) returned turns out to
+    /// be invalid (i.e. it fails to decode or fails a checksum check in the
+    /// encoding layer).
+    ///
+    /// Transports that count invalid messages should count these too (in
+    /// [`num_get_errors`](Transport::num_get_errors)).
+    fn record_get_error(&self) { }
 }
 
 using_std! {
@@ -5,9 +5,12 @@ use super::peripherals::gpio::{
 use super::peripherals::adc::{AdcReadError, AdcReadErrors, AdcMiscError};
 use super::peripherals::input::InputError;
 use super::peripherals::output::OutputError;
-use lc3_isa::Word;
+use lc3_isa::{
+    Addr, Word, ACCESS_CONTROL_VIOLATION_EXCEPTION_VECTOR,
+    ILLEGAL_OPCODE_EXCEPTION_VECTOR, PRIVILEGE_MODE_VIOLATION_EXCEPTION_VECTOR,
+};
 
-use core::fmt::Display;
+use core::fmt::{self, Display};
 
 use serde::{Serialize, Deserialize};
 
@@ -37,28 +40,90 @@ pub enum Error {
     OutputError(OutputError),
 
     SystemStackOverflow,
-    ///// TODO: finish
+
+    // Faults; the interpreter normally vectors into the OS for these (and
+    // doesn't report them) unless it's been told to stop on them instead.
+    /// An access to memory that the current privilege mode can't touch (in
+    /// user mode: anything below the user program area or in the I/O region).
+    ///
+    /// `insn` is `None` when the fault happened while fetching the instruction
+    /// (in which case `addr` is `pc`).
+    AccessControlViolation { pc: Addr, insn: Option<Word>, addr: Addr },
+    /// An instruction with the reserved opcode (`1101`).
+    IllegalOpcode { pc: Addr, insn: Word },
+    /// A privileged instruction (i.e. `RTI`) in user mode.
+    PrivilegeModeViolation { pc: Addr, insn: Word },
+}
+
+impl Error {
+    /// The address of the instruction that caused the error, for faults.
+    pub fn faulting_pc(&self) -> Option<Addr> {
+        use Error::*;
+
+        match self {
+            AccessControlViolation { pc, .. } |
+            IllegalOpcode { pc, .. } |
+            PrivilegeModeViolation { pc, .. } => Some(*pc),
+            _ => None,
+        }
+    }
+}
+
+// Lists the pins in a set of state mismatches: `G0 (Output), G3 (Disabled)`.
+fn mismatches<P: Display, S: Display>(
+    f: &mut fmt::Formatter<'_>,
+    list: impl Iterator<Item = (P, S)>,
+) -> fmt::Result {
+    for (idx, (pin, state)) in list.enumerate() {
+        if idx != 0 { write!(f, ", ")?; }
+        write!(f, "{} ({} mode)", pin, state)?;
+    }
+
+    Ok(())
 }
 
 impl Display for Error {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Error::*;
 
         match self {
             InvalidGpioWrite(err) =>
                 write!(f, "Attempted to write to {} when in {} mode", (err.0).0, (err.0).1),
-            InvalidGpioWrites(_) => todo!(),
+            InvalidGpioWrites(errs) => {
+                write!(f, "Attempted to write to GPIO pins in the wrong mode: ")?;
+                mismatches(f, (errs.0).0.iter().flatten().copied())
+            },
             InvalidGpioRead(err) =>
                 write!(f, "Attempted to read from {} when in {} mode", (err.0).0, (err.0).1),
-            InvalidGpioReads(_) => todo!(),
-            GpioMiscError(_) => todo!(),
+            InvalidGpioReads(errs) => {
+                write!(f, "Attempted to read from GPIO pins in the wrong mode: ")?;
+                mismatches(f, (errs.0).0.iter().flatten().copied())
+            },
+            GpioMiscError(_) => write!(f, "Unsupported GPIO configuration"),
             InvalidAdcRead(err) =>
                 write!(f, "Attempted to read from {} when in {} mode", (err.0).0, (err.0).1),
-            InvalidAdcReads(_) => todo!(),
-            AdcMiscError(_) => todo!(),
+            InvalidAdcReads(errs) => {
+                write!(f, "Attempted to read from ADC pins in the wrong mode: ")?;
+                mismatches(f, (errs.0).0.iter().flatten().copied())
+            },
+            AdcMiscError(_) => write!(f, "Unsupported ADC configuration"),
             OutputError(e) => write!(f, "{}", e),
             InputError(e) => write!(f, "{}", e),
             SystemStackOverflow => write!(f, "Overflowed system stack"),
+            AccessControlViolation { pc, insn: Some(insn), addr } => write!(f,
+                "Access control violation: the instruction at {:#06x} ({:#06x}) accessed {:#06x}",
+                pc, insn, addr,
+            ),
+            AccessControlViolation { pc, insn: None, .. } => write!(f,
+                "Access control violation: tried to fetch an instruction from {:#06x}", pc,
+            ),
+            IllegalOpcode { pc, insn } => write!(f,
+                "Illegal opcode: the instruction at {:#06x} ({:#06x}) uses the reserved opcode", pc, insn,
+            ),
+            PrivilegeModeViolation { pc, insn } => write!(f,
+                "Privilege mode violation: the instruction at {:#06x} ({:#06x}) can't be run in user mode",
+                pc, insn,
+            ),
         }
     }
 }
@@ -135,6 +200,18 @@ impl From<Error> for ErrorHandlingStrategy {
             InputError(_) => Silent,        // TODO: what to actually do here?
             OutputError(_) => Silent,       // TODO: and here?
             SystemStackOverflow => Silent,
+            AccessControlViolation { .. } => FireException {
+                interrupt_vector_table_number: ACCESS_CONTROL_VIOLATION_EXCEPTION_VECTOR,
+                payload: None,
+            },
+            IllegalOpcode { .. } => FireException {
+                interrupt_vector_table_number: ILLEGAL_OPCODE_EXCEPTION_VECTOR,
+                payload: None,
+            },
+            PrivilegeModeViolation { .. } => FireException {
+                interrupt_vector_table_number: PRIVILEGE_MODE_VIOLATION_EXCEPTION_VECTOR,
+                payload: None,
+            },
         }
     }
 }
@@ -5,7 +5,9 @@ use crate::peripheral_trait;
 use lc3_macros::DisplayUsingDebug;
 
 use core::convert::TryFrom;
+use core::num::NonZeroU16;
 use core::ops::{Deref, Index, IndexMut};
+use core::sync::atomic::AtomicBool;
 
 use serde::{Deserialize, Serialize};
 // TODO: Add Errors
@@ -31,6 +33,60 @@ pub enum AdcState {
     Disabled,
 }
 
+/// How conversions happen for an [enabled](AdcState::Enabled) pin.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(DisplayUsingDebug)]
+pub enum AdcMode {
+    /// Every [read](Adc::read) performs a conversion.
+    OneShot,
+    /// The peripheral converts on its own, once every
+    /// [sample period](Adc::get_sample_period); reads return the result of the
+    /// most recent conversion.
+    Continuous,
+    //
+    // 0 -> One Shot
+    // 1 -> Continuous
+}
+
+impl Default for AdcMode {
+    fn default() -> Self {
+        AdcMode::OneShot
+    }
+}
+
+/// The event (if any) that makes a pin in [continuous](AdcMode::Continuous)
+/// mode fire an interrupt.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(DisplayUsingDebug)]
+pub enum AdcInterrupt {
+    Disabled,
+    /// Fires after every conversion.
+    ConversionComplete,
+    /// Fires when a conversion lands on the other side of the threshold than
+    /// the conversion before it did (in either direction). Readings that are
+    /// greater than or equal to the threshold count as being above it.
+    ThresholdCrossing(u8),
+    //
+    // 00 -> Disabled
+    // 01 -> Conversion Complete
+    // 10 -> Threshold Crossing (threshold in the upper byte)
+}
+
+impl Default for AdcInterrupt {
+    fn default() -> Self {
+        AdcInterrupt::Disabled
+    }
+}
+
+/// Time between conversions in [continuous](AdcMode::Continuous) mode, in
+/// milliseconds.
+pub type AdcSamplePeriod = NonZeroU16;
+
+pub const ADC_DEFAULT_SAMPLE_PERIOD: AdcSamplePeriod = match NonZeroU16::new(10) {
+    Some(p) => p,
+    None => panic!(),
+};
+
 impl From<AdcPin> for usize {
     fn from(pin: AdcPin) -> usize {
         use AdcPin::*;
@@ -80,7 +136,27 @@ impl<T> IndexMut<AdcPin> for AdcPinArr<T> {
 peripheral_trait! {adc,
 
 /// Adc access for the interpreter.
-pub trait Adc: Default {
+///
+/// ### Modes and Interrupts
+/// Pins start out in [one shot](AdcMode::OneShot) mode where every read is a
+/// conversion. Pins in [continuous](AdcMode::Continuous) mode are sampled by the
+/// peripheral once every [sample period](Adc::get_sample_period) (shared by all
+/// the pins) and reads just return the latest sample.
+///
+/// Interrupts are only raised by conversions the peripheral performs on its own
+/// (i.e. in continuous mode); reads never cause interrupts. Which conversions
+/// cause an interrupt is chosen per-pin with [`set_interrupt`](Adc::set_interrupt).
+///
+/// Interrupt flags work the same way they do for [`Gpio`](crate::peripherals::Gpio):
+/// a set of flags must be [registered](Adc::register_interrupt_flags) before
+/// [`interrupt_occurred`](Adc::interrupt_occurred) and
+/// [`reset_interrupt_flag`](Adc::reset_interrupt_flag) are used. Modes and
+/// interrupt settings should be retained across pin state changes.
+///
+/// The default implementations of the mode, sample period and interrupt
+/// functions describe an ADC that only supports one shot conversions; they
+/// return an [`AdcMiscError`] for anything else.
+pub trait Adc<'a>: Default {
     fn set_state(&mut self, pin: AdcPin, state: AdcState) -> Result<(), AdcMiscError>;
     fn get_state(&self, pin: AdcPin) -> AdcState;
     #[inline]
@@ -107,6 +183,58 @@ pub trait Adc: Default {
         readings
     }
 
+    #[inline]
+    fn set_mode(&mut self, _pin: AdcPin, mode: AdcMode) -> Result<(), AdcMiscError> {
+        match mode {
+            AdcMode::OneShot => Ok(()),
+            AdcMode::Continuous => Err(AdcMiscError),
+        }
+    }
+    #[inline]
+    fn get_mode(&self, _pin: AdcPin) -> AdcMode {
+        AdcMode::OneShot
+    }
+    #[inline]
+    fn get_modes(&self) -> AdcPinArr<AdcMode> {
+        let mut modes = AdcPinArr([AdcMode::OneShot; AdcPin::NUM_PINS]);
+
+        ADC_PINS
+            .iter()
+            .for_each(|a| modes[*a] = self.get_mode(*a));
+
+        modes
+    }
+
+    #[inline]
+    fn set_sample_period(&mut self, _period: AdcSamplePeriod) -> Result<(), AdcMiscError> {
+        Err(AdcMiscError)
+    }
+    #[inline]
+    fn get_sample_period(&self) -> AdcSamplePeriod {
+        ADC_DEFAULT_SAMPLE_PERIOD
+    }
+
+    #[inline]
+    fn set_interrupt(&mut self, _pin: AdcPin, interrupt: AdcInterrupt) -> Result<(), AdcMiscError> {
+        match interrupt {
+            AdcInterrupt::Disabled => Ok(()),
+            _ => Err(AdcMiscError),
+        }
+    }
+    #[inline]
+    fn get_interrupt(&self, _pin: AdcPin) -> AdcInterrupt {
+        AdcInterrupt::Disabled
+    }
+
+    fn register_interrupt_flags(&mut self, flags: &'a AdcPinArr<AtomicBool>);
+    fn interrupt_occurred(&self, pin: AdcPin) -> bool;
+    fn reset_interrupt_flag(&mut self, pin: AdcPin);
+    #[inline]
+    fn interrupts_enabled(&self, pin: AdcPin) -> bool {
+        matches!(self.get_state(pin), AdcState::Enabled)
+            && matches!(self.get_mode(pin), AdcMode::Continuous)
+            && !matches!(self.get_interrupt(pin), AdcInterrupt::Disabled)
+    }
 }}
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -146,7 +274,7 @@ impl TryFrom<AdcPinArr<Result<u8, AdcReadError>>> for AdcReadErrors {
 // TODO: roll this into the macro
 using_std! {
     use std::sync::{Arc, RwLock};
-    impl<A: Adc> Adc for Arc<RwLock<A>> {
+    impl<'a, A: Adc<'a>> Adc<'a> for Arc<RwLock<A>> {
         fn set_state(&mut self, pin: AdcPin, state: AdcState) -> Result<(), AdcMiscError> {
             RwLock::write(self).unwrap().set_state(pin, state)
         }
@@ -158,5 +286,45 @@ using_std! {
         fn read(&self, pin: AdcPin) -> Result<u8, AdcReadError> {
             RwLock::read(self).unwrap().read(pin)
         }
+
+        fn set_mode(&mut self, pin: AdcPin, mode: AdcMode) -> Result<(), AdcMiscError> {
+            RwLock::write(self).unwrap().set_mode(pin, mode)
+        }
+
+        fn get_mode(&self, pin: AdcPin) -> AdcMode {
+            RwLock::read(self).unwrap().get_mode(pin)
+        }
+
+        fn set_sample_period(&mut self, period: AdcSamplePeriod) -> Result<(), AdcMiscError> {
+            RwLock::write(self).unwrap().set_sample_period(period)
+        }
+
+        fn get_sample_period(&self) -> AdcSamplePeriod {
+            RwLock::read(self).unwrap().get_sample_period()
+        }
+
+        fn set_interrupt(&mut self, pin: AdcPin, interrupt: AdcInterrupt) -> Result<(), AdcMiscError> {
+            RwLock::write(self).unwrap().set_interrupt(pin, interrupt)
+        }
+
+        fn get_interrupt(&self, pin: AdcPin) -> AdcInterrupt {
+            RwLock::read(self).unwrap().get_interrupt(pin)
+        }
+
+        fn register_interrupt_flags(&mut self, flags: &'a AdcPinArr<AtomicBool>) {
+            RwLock::write(self).unwrap().register_interrupt_flags(flags)
+        }
+
+        fn interrupt_occurred(&self, pin: AdcPin) -> bool {
+            RwLock::read(self).unwrap().interrupt_occurred(pin)
+        }
+
+        fn reset_interrupt_flag(&mut self, pin: AdcPin) {
+            RwLock::write(self).unwrap().reset_interrupt_flag(pin)
+        }
+
+        fn interrupts_enabled(&self, pin: AdcPin) -> bool {
+            RwLock::read(self).unwrap().interrupts_enabled(pin)
+        }
     }
 }
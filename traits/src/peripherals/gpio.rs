@@ -69,6 +69,54 @@ pub enum GpioState {
     Disabled,
 }
 
+/// The condition under which a pin in [`Interrupt`](GpioState::Interrupt) mode
+/// fires an interrupt.
+///
+/// Edge triggers fire once per transition; level triggers keep firing for as
+/// long as the pin is at the given level (i.e. the ISR is expected to do
+/// something about the source of the interrupt before it returns).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(DisplayUsingDebug)]
+pub enum GpioTrigger {
+    RisingEdge,
+    FallingEdge,
+    BothEdges,
+    HighLevel,
+    LowLevel,
+    //
+    // 000 -> Rising Edge
+    // 001 -> Falling Edge
+    // 010 -> Both Edges
+    // 011 -> High Level
+    // 100 -> Low Level
+}
+
+impl Default for GpioTrigger {
+    fn default() -> Self {
+        GpioTrigger::RisingEdge
+    }
+}
+
+/// The bias applied to a pin in [`Input`](GpioState::Input) or
+/// [`Interrupt`](GpioState::Interrupt) mode when nothing is driving it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(DisplayUsingDebug)]
+pub enum GpioPull {
+    Floating,
+    PullUp,
+    PullDown,
+    //
+    // 00 -> Floating
+    // 01 -> Pull Up
+    // 10 -> Pull Down
+}
+
+impl Default for GpioPull {
+    fn default() -> Self {
+        GpioPull::Floating
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct GpioPinArr<T>(pub [T; GpioPin::NUM_PINS]);
 
@@ -143,6 +191,8 @@ peripheral_trait! {gpio,
 ///
 /// Implementations of this trait must provide digital read, digital write, and rising
 /// edge trigger interrupt functionality for 8 GPIO pins which we'll call G0 - G7.
+/// Implementations can optionally support other [interrupt triggers](GpioTrigger)
+/// and [pull-up/pull-down](GpioPull) configuration as well.
 ///
 /// Additionally, implementors of this trait must also provide an implementation of
 /// [`Default`](core::default::Default). Implementors are also free (and encouraged!) to
@@ -196,6 +246,26 @@ peripheral_trait! {gpio,
 /// rising edge *if the pin is in [interrupt](GpioState::Interrupt) mode* (not just if
 /// a handler function has been provided).
 ///
+/// ### Triggers and Pulls
+/// By default pins in [interrupt](GpioState::Interrupt) mode fire on
+/// [rising edges](GpioTrigger::RisingEdge). Implementations that can do more than this
+/// should override [`set_trigger`](Gpio::set_trigger) and
+/// [`get_trigger`](Gpio::get_trigger); the default implementations only accept
+/// [`GpioTrigger::RisingEdge`] and return a [`GpioMiscError`] for everything else.
+///
+/// Like the handler functions, triggers are per-pin and should be retained across
+/// pin state changes.
+///
+/// Level triggers ([`HighLevel`](GpioTrigger::HighLevel) and
+/// [`LowLevel`](GpioTrigger::LowLevel)) should report an interrupt (from
+/// [`interrupt_occurred`](Gpio::interrupt_occurred)) for as long as the pin is at
+/// the given level, even after the interrupt flag is reset.
+///
+/// [`set_pull`](Gpio::set_pull) and [`get_pull`](Gpio::get_pull) work the same way:
+/// the defaults only support [`GpioPull::Floating`]. The pull setting only has an
+/// observable effect on pins that are inputs (or interrupts) and that aren't being
+/// driven externally.
+///
 /// ### Default Function Implementations
 /// The trait provides naïve default implementations of
 /// [`get_states`](Gpio::get_states), [`read_all`](Gpio::read_all), and
@@ -270,6 +340,50 @@ pub trait Gpio<'a>: Default {
     fn interrupts_enabled(&self, pin: GpioPin) -> bool {
         matches!(self.get_state(pin), GpioState::Interrupt)
     }
+
+    #[inline]
+    fn set_trigger(&mut self, _pin: GpioPin, trigger: GpioTrigger) -> Result<(), GpioMiscError> {
+        match trigger {
+            GpioTrigger::RisingEdge => Ok(()),
+            _ => Err(GpioMiscError),
+        }
+    }
+    #[inline]
+    fn get_trigger(&self, _pin: GpioPin) -> GpioTrigger {
+        GpioTrigger::RisingEdge
+    }
+    #[inline]
+    fn get_triggers(&self) -> GpioPinArr<GpioTrigger> {
+        let mut triggers = GpioPinArr([GpioTrigger::RisingEdge; GpioPin::NUM_PINS]);
+
+        GPIO_PINS
+            .iter()
+            .for_each(|g| triggers[*g] = self.get_trigger(*g));
+
+        triggers
+    }
+
+    #[inline]
+    fn set_pull(&mut self, _pin: GpioPin, pull: GpioPull) -> Result<(), GpioMiscError> {
+        match pull {
+            GpioPull::Floating => Ok(()),
+            _ => Err(GpioMiscError),
+        }
+    }
+    #[inline]
+    fn get_pull(&self, _pin: GpioPin) -> GpioPull {
+        GpioPull::Floating
+    }
+    #[inline]
+    fn get_pulls(&self) -> GpioPinArr<GpioPull> {
+        let mut pulls = GpioPinArr([GpioPull::Floating; GpioPin::NUM_PINS]);
+
+        GPIO_PINS
+            .iter()
+            .for_each(|g| pulls[*g] = self.get_pull(*g));
+
+        pulls
+    }
 }}
 
 impl TryFrom<GpioPinArr<Result<bool, GpioReadError>>> for GpioReadErrors {
@@ -362,5 +476,21 @@ using_std! {
         fn interrupts_enabled(&self, pin: GpioPin) -> bool {
             RwLock::read(self).unwrap().interrupts_enabled(pin)
         }
+
+        fn set_trigger(&mut self, pin: GpioPin, trigger: GpioTrigger) -> Result<(), GpioMiscError> {
+            RwLock::write(self).unwrap().set_trigger(pin, trigger)
+        }
+
+        fn get_trigger(&self, pin: GpioPin) -> GpioTrigger {
+            RwLock::read(self).unwrap().get_trigger(pin)
+        }
+
+        fn set_pull(&mut self, pin: GpioPin, pull: GpioPull) -> Result<(), GpioMiscError> {
+            RwLock::write(self).unwrap().set_pull(pin, pull)
+        }
+
+        fn get_pull(&self, pin: GpioPin) -> GpioPull {
+            RwLock::read(self).unwrap().get_pull(pin)
+        }
     }
 }
@@ -34,7 +34,7 @@ use core::marker::PhantomData;
 // }
 
 pub trait Peripherals<'int>:
-    Gpio<'int> + Adc + Pwm + Timers<'int> + Clock + Input<'int> + Output<'int>
+    Gpio<'int> + Adc<'int> + Pwm<'int> + Timers<'int> + Clock + Input<'int> + Output<'int>
 {
     fn init(&mut self);
 }
@@ -42,8 +42,8 @@ pub trait Peripherals<'int>:
 pub struct PeripheralSet<'int, G, A, P, T, C, I, O/*, GW, AW, PW, TW, CW, IW, OW*/>
 where
     G: Gpio<'int>,
-    A: Adc,
-    P: Pwm,
+    A: Adc<'int>,
+    P: Pwm<'int>,
     T: Timers<'int>,
     C: Clock,
     I: Input<'int>,
@@ -79,8 +79,8 @@ where
 impl<'p, G, A, P, T, C, I, O> Default for PeripheralSet<'p, G, A, P, T, C, I, O/*, G, A, P, T, C, I, O*/>
 where
     G: Gpio<'p>,
-    A: Adc,
-    P: Pwm,
+    A: Adc<'p>,
+    P: Pwm<'p>,
     T: Timers<'p>,
     C: Clock,
     I: Input<'p>,
@@ -103,8 +103,8 @@ where
 impl<'p, G, A, P, T, C, I, O/*, GW, AW, PW, TW, CW, IW, OW*/> PeripheralSet<'p, G, A, P, T, C, I, O/*, GW, AW, PW, TW, CW, IW, OW*/>
 where
     G: Gpio<'p>,
-    A: Adc,
-    P: Pwm,
+    A: Adc<'p>,
+    P: Pwm<'p>,
     T: Timers<'p>,
     C: Clock,
     I: Input<'p>,
@@ -239,8 +239,8 @@ macro_rules! peripheral_set_impl {
         where
             $($lifetime: 'p,)?
             G: $crate::peripherals::gpio::Gpio<'p>,
-            A: $crate::peripherals::adc::Adc,
-            P: $crate::peripherals::pwm::Pwm,
+            A: $crate::peripherals::adc::Adc<'p>,
+            P: $crate::peripherals::pwm::Pwm<'p>,
             T: $crate::peripherals::timers::Timers<'p>,
             C: $crate::peripherals::clock::Clock,
             I: $crate::peripherals::input::Input<'p>,
@@ -360,8 +360,8 @@ macro_rules! func_sig {
 impl<'p, G, A, P, T, C, I, O> Peripherals<'p> for PeripheralSet<'p, G, A, P, T, C, I, O/*, G, A, P, T, C, I, O*/>
 where
     G: Gpio<'p>,
-    A: Adc,
-    P: Pwm,
+    A: Adc<'p>,
+    P: Pwm<'p>,
     T: Timers<'p>,
     C: Clock,
     I: Input<'p>,
@@ -375,8 +375,8 @@ use crate::control::{Snapshot, SnapshotError};
 impl<'p, G, A, P, T, C, I, O> Snapshot for PeripheralSet<'p, G, A, P, T, C, I, O>
 where
     G: Snapshot + Gpio<'p>,
-    A: Snapshot + Adc,
-    P: Snapshot + Pwm,
+    A: Snapshot + Adc<'p>,
+    P: Snapshot + Pwm<'p>,
     T: Snapshot + Timers<'p>,
     C: Snapshot + Clock,
     I: Snapshot + Input<'p>,
@@ -4,8 +4,9 @@ use crate::peripheral_trait;
 
 use lc3_macros::DisplayUsingDebug;
 
-use core::num::NonZeroU8;
+use core::num::NonZeroU32;
 use core::ops::{Deref, Index, IndexMut};
+use core::sync::atomic::AtomicBool;
 
 use serde::{Deserialize, Serialize};
 
@@ -34,9 +35,51 @@ pub const PWM_PINS: PwmPinArr<PwmPin> = {
     PwmPinArr([P0, P1])
 }; // TODO: save us, derive macro
 
+/// The length of one cycle of a PWM wave.
+///
+/// Periods are kept in microseconds but can only be made and read through
+/// functions that name their unit (i.e. [`PwmPeriod::from_millis`] and
+/// [`PwmPeriod::from_micros`]).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PwmPeriod(NonZeroU32);
+
+impl PwmPeriod {
+    /// The longest period there is: 255 ms.
+    pub const MAX: PwmPeriod = PwmPeriod(match NonZeroU32::new(255_000) {
+        Some(p) => p,
+        None => panic!(),
+    });
+
+    /// `None` if `micros` is 0 or is longer than [`PwmPeriod::MAX`].
+    pub const fn from_micros(micros: u32) -> Option<Self> {
+        if micros > Self::MAX.as_micros() {
+            return None;
+        }
+
+        match NonZeroU32::new(micros) {
+            Some(p) => Some(Self(p)),
+            None => None,
+        }
+    }
+
+    /// `None` if `millis` is 0 or is longer than [`PwmPeriod::MAX`].
+    pub const fn from_millis(millis: u16) -> Option<Self> {
+        Self::from_micros(millis as u32 * 1000)
+    }
+
+    pub const fn as_micros(self) -> u32 {
+        self.0.get()
+    }
+
+    /// Rounded to the nearest millisecond.
+    pub const fn as_millis(self) -> u32 {
+        (self.as_micros() + 500) / 1000
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PwmState {
-    Enabled(NonZeroU8),
+    Enabled(PwmPeriod),
     Disabled,
 }
 
@@ -72,10 +115,47 @@ impl<T> IndexMut<PwmPin> for PwmPinArr<T> {
     }
 }
 
-pub type PwmDutyCycle = u8;
+/// The fraction of each period a PWM pin spends high, out of
+/// [`MAX_DUTY_CYCLE`] (i.e. 0 is always low and [`MAX_DUTY_CYCLE`] is always
+/// high).
+pub type PwmDutyCycle = u16;
+pub const MAX_DUTY_CYCLE: PwmDutyCycle = PwmDutyCycle::max_value();
+
+/// Everything there is to know about how a PWM pin is set up.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PwmConfig {
+    pub state: PwmState,
+    pub duty_cycle: PwmDutyCycle,
+    /// Whether the pin fires an interrupt at the start of every period.
+    pub rollover_interrupt: bool,
+}
+
+impl Default for PwmConfig {
+    fn default() -> Self {
+        Self {
+            state: PwmState::Disabled,
+            duty_cycle: 0,
+            rollover_interrupt: false,
+        }
+    }
+}
 
 peripheral_trait! {pwm,
-pub trait Pwm: Default {
+/// PWM access for the interpreter.
+///
+/// ### Interrupts
+/// Each pin can be set to fire an interrupt whenever its wave rolls over into
+/// a new period (i.e. on every rising edge) with
+/// [`set_rollover_interrupt`](Pwm::set_rollover_interrupt). Rollover interrupts
+/// are only raised while the pin is [enabled](PwmState::Enabled).
+///
+/// Interrupt flags work the same way they do for the
+/// [`Timers`](crate::peripherals::Timers): a set of flags must be
+/// [registered](Pwm::register_interrupt_flags) before
+/// [`interrupt_occurred`](Pwm::interrupt_occurred) and
+/// [`reset_interrupt_flag`](Pwm::reset_interrupt_flag) are used. Duty cycles
+/// and interrupt settings should be retained across pin state changes.
+pub trait Pwm<'a>: Default {
     fn set_state(&mut self, pin: PwmPin, state: PwmState);
     fn get_state(&self, pin: PwmPin) -> PwmState;
     #[inline]
@@ -93,7 +173,7 @@ pub trait Pwm: Default {
     fn get_duty_cycle(&self, pin: PwmPin) -> PwmDutyCycle;
     #[inline]
     fn get_duty_cycles(&self) -> PwmPinArr<PwmDutyCycle> {
-        let mut duty_cycles = PwmPinArr([0u8; PwmPin::NUM_PINS]);
+        let mut duty_cycles = PwmPinArr([0; PwmPin::NUM_PINS]);
 
         PWM_PINS
             .iter()
@@ -101,6 +181,42 @@ pub trait Pwm: Default {
 
         duty_cycles
     }
+
+    fn set_rollover_interrupt(&mut self, pin: PwmPin, enabled: bool);
+    fn get_rollover_interrupt(&self, pin: PwmPin) -> bool;
+
+    #[inline]
+    fn set_config(&mut self, pin: PwmPin, config: PwmConfig) {
+        self.set_duty_cycle(pin, config.duty_cycle);
+        self.set_rollover_interrupt(pin, config.rollover_interrupt);
+        self.set_state(pin, config.state);
+    }
+    #[inline]
+    fn get_config(&self, pin: PwmPin) -> PwmConfig {
+        PwmConfig {
+            state: self.get_state(pin),
+            duty_cycle: self.get_duty_cycle(pin),
+            rollover_interrupt: self.get_rollover_interrupt(pin),
+        }
+    }
+    #[inline]
+    fn get_configs(&self) -> PwmPinArr<PwmConfig> {
+        let mut configs = PwmPinArr([PwmConfig::default(); PwmPin::NUM_PINS]);
+
+        PWM_PINS
+            .iter()
+            .for_each(|p| configs[*p] = self.get_config(*p));
+
+        configs
+    }
+
+    fn register_interrupt_flags(&mut self, flags: &'a PwmPinArr<AtomicBool>);
+    fn interrupt_occurred(&self, pin: PwmPin) -> bool;
+    fn reset_interrupt_flag(&mut self, pin: PwmPin);
+    #[inline]
+    fn interrupts_enabled(&self, pin: PwmPin) -> bool {
+        matches!(self.get_state(pin), PwmState::Enabled(_)) && self.get_rollover_interrupt(pin)
+    }
 }}
 
 
@@ -110,7 +226,7 @@ using_std! {
 
     // This is adequate if your `Pwm` impl is _already_ `Sync`. If it's not,
     // you'll want the Mutex blanket impl below.
-    impl<P: Pwm> Pwm for Arc<RwLock<P>> {
+    impl<'a, P: Pwm<'a>> Pwm<'a> for Arc<RwLock<P>> {
         fn set_state(&mut self, pin: PwmPin, state: PwmState) {
             RwLock::write(self).unwrap().set_state(pin, state)
         }
@@ -126,9 +242,33 @@ using_std! {
         fn get_duty_cycle(&self, pin: PwmPin) -> PwmDutyCycle {
             RwLock::read(self).unwrap().get_duty_cycle(pin)
         }
+
+        fn set_rollover_interrupt(&mut self, pin: PwmPin, enabled: bool) {
+            RwLock::write(self).unwrap().set_rollover_interrupt(pin, enabled)
+        }
+
+        fn get_rollover_interrupt(&self, pin: PwmPin) -> bool {
+            RwLock::read(self).unwrap().get_rollover_interrupt(pin)
+        }
+
+        fn register_interrupt_flags(&mut self, flags: &'a PwmPinArr<AtomicBool>) {
+            RwLock::write(self).unwrap().register_interrupt_flags(flags)
+        }
+
+        fn interrupt_occurred(&self, pin: PwmPin) -> bool {
+            RwLock::read(self).unwrap().interrupt_occurred(pin)
+        }
+
+        fn reset_interrupt_flag(&mut self, pin: PwmPin) {
+            RwLock::write(self).unwrap().reset_interrupt_flag(pin)
+        }
+
+        fn interrupts_enabled(&self, pin: PwmPin) -> bool {
+            RwLock::read(self).unwrap().interrupts_enabled(pin)
+        }
     }
 
-    impl<P: Pwm> Pwm for Arc<Mutex<P>> {
+    impl<'a, P: Pwm<'a>> Pwm<'a> for Arc<Mutex<P>> {
         fn set_state(&mut self, pin: PwmPin, state: PwmState) {
             Mutex::lock(self).unwrap().set_state(pin, state);
         }
@@ -144,5 +284,29 @@ using_std! {
         fn get_duty_cycle(&self, pin: PwmPin) -> PwmDutyCycle {
             Mutex::lock(self).unwrap().get_duty_cycle(pin)
         }
+
+        fn set_rollover_interrupt(&mut self, pin: PwmPin, enabled: bool) {
+            Mutex::lock(self).unwrap().set_rollover_interrupt(pin, enabled)
+        }
+
+        fn get_rollover_interrupt(&self, pin: PwmPin) -> bool {
+            Mutex::lock(self).unwrap().get_rollover_interrupt(pin)
+        }
+
+        fn register_interrupt_flags(&mut self, flags: &'a PwmPinArr<AtomicBool>) {
+            Mutex::lock(self).unwrap().register_interrupt_flags(flags)
+        }
+
+        fn interrupt_occurred(&self, pin: PwmPin) -> bool {
+            Mutex::lock(self).unwrap().interrupt_occurred(pin)
+        }
+
+        fn reset_interrupt_flag(&mut self, pin: PwmPin) {
+            Mutex::lock(self).unwrap().reset_interrupt_flag(pin)
+        }
+
+        fn interrupts_enabled(&self, pin: PwmPin) -> bool {
+            Mutex::lock(self).unwrap().interrupts_enabled(pin)
+        }
     }
 }
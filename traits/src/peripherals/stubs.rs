@@ -36,25 +36,36 @@ impl<'a> Gpio<'a> for GpioStub {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct AdcStub;
 
-use super::adc::{AdcPin, AdcState, AdcReadError, AdcMiscError};
-impl Adc for AdcStub {
+use super::adc::{AdcPin, AdcPinArr, AdcState, AdcReadError, AdcMiscError};
+impl<'a> Adc<'a> for AdcStub {
     fn set_state(&mut self, _pin: AdcPin, _: AdcState) -> Result<(), AdcMiscError> { Err(AdcMiscError) }
     fn get_state(&self, _pin: AdcPin) -> AdcState { AdcState::Disabled }
 
     fn read(&self, pin: AdcPin) -> Result<u8, AdcReadError> { Err(AdcReadError((pin, AdcState::Disabled)))}
+
+    fn register_interrupt_flags(&mut self, _flags: &'a AdcPinArr<AtomicBool>) {}
+    fn interrupt_occurred(&self, _pin: AdcPin) -> bool { false }
+    fn reset_interrupt_flag(&mut self, _pin: AdcPin) { }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct PwmStub;
 
-use super::pwm::{PwmPin, PwmState, PwmDutyCycle};
-impl Pwm for PwmStub {
+use super::pwm::{PwmPin, PwmPinArr, PwmState, PwmDutyCycle};
+impl<'a> Pwm<'a> for PwmStub {
     fn set_state(&mut self, _pin: PwmPin, _state: PwmState) { }
     fn get_state(&self, _pin: PwmPin) -> PwmState { PwmState::Disabled }
 
     fn set_duty_cycle(&mut self, _pin: PwmPin, _duty: PwmDutyCycle) { }
 
     fn get_duty_cycle(&self, _pin: PwmPin) -> PwmDutyCycle { 0 }
+
+    fn set_rollover_interrupt(&mut self, _pin: PwmPin, _enabled: bool) { }
+    fn get_rollover_interrupt(&self, _pin: PwmPin) -> bool { false }
+
+    fn register_interrupt_flags(&mut self, _flags: &'a PwmPinArr<AtomicBool>) {}
+    fn interrupt_occurred(&self, _pin: PwmPin) -> bool { false }
+    fn reset_interrupt_flag(&mut self, _pin: PwmPin) { }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
@@ -1,6 +1,7 @@
 //! [`Timers` trait](Timers) and related types.
 
 use crate::peripheral_trait;
+use super::gpio::GpioPin;
 
 use lc3_isa::Word;
 use lc3_macros::DisplayUsingDebug;
@@ -14,10 +15,10 @@ use serde::{Deserialize, Serialize};
 #[rustfmt::skip]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[derive(DisplayUsingDebug)]
-pub enum TimerId { T0, T1, }
+pub enum TimerId { T0, T1, T2, T3, }
 
 impl TimerId {
-    pub const NUM_TIMERS: usize = 2;
+    pub const NUM_TIMERS: usize = 4;
 }
 
 impl From<TimerId> for usize {
@@ -26,13 +27,15 @@ impl From<TimerId> for usize {
         match timer {
             T0 => 0,
             T1 => 1,
+            T2 => 2,
+            T3 => 3,
         }
     }
 }
 
 pub const TIMERS: TimerArr<TimerId> = {
     use TimerId::*;
-    TimerArr([T0, T1])
+    TimerArr([T0, T1, T2, T3])
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -71,6 +74,7 @@ impl<T> IndexMut<TimerId> for TimerArr<T> {
 pub enum TimerMode {
     Repeated,
     SingleShot,
+    FreeRunning,
 }
 
 pub type Period = NonZeroU16;
@@ -82,6 +86,35 @@ pub enum TimerState {
     WithPeriod(Period)
 }
 
+/// The edges of a [GPIO pin](GpioPin) that cause a timer to latch its count.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CaptureEdge {
+    Rising,
+    Falling,
+    Both,
+}
+
+/// The [GPIO pin](GpioPin) and [edge(s)](CaptureEdge) a timer captures on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CaptureSource {
+    pub pin: GpioPin,
+    pub edge: CaptureEdge,
+}
+
+impl CaptureEdge {
+    /// Whether going from `previous` to `current` is an edge of this kind.
+    #[inline]
+    pub fn matches(&self, previous: bool, current: bool) -> bool {
+        use CaptureEdge::*;
+
+        match self {
+            Rising => !previous && current,
+            Falling => previous && !current,
+            Both => previous != current,
+        }
+    }
+}
+
 peripheral_trait! {timers,
 /// A [Timer peripheral](Timers) for an LC-3 simulator.
 ///
@@ -95,7 +128,7 @@ peripheral_trait! {timers,
 ///
 /// # Definition
 ///
-/// Each timer can be set to one of two [`modes`](TimerMode) of operation and
+/// Each timer can be set to one of three [`modes`](TimerMode) of operation and
 /// two main [`states`](TimerState) representing either the time period the
 /// timer waits or the lack thereof.
 ///
@@ -128,7 +161,8 @@ peripheral_trait! {timers,
 ///
 /// The [last part of the interrupt interface](Timers::interrupts_enabled) shows
 /// whether interrupts are enabled for a given timer. If a timer is not in a
-/// disabled state (and flags are registered), then its interrupts are enabled.
+/// disabled state and is not [`FreeRunning`] (and flags are registered), then
+/// its interrupts are enabled.
 /// This method is used to show the simulator when it is necessary to check
 /// whether interrupts occur on this peripheral. Note this function has a
 /// default implementation that does almost literally the above; it returns
@@ -151,7 +185,7 @@ peripheral_trait! {timers,
 /// when changing a timer's mode at the middle or close to the end of its
 /// period.
 ///
-/// There are two modes:
+/// There are three modes:
 /// - [`SingleShot`]: after being set to this mode, when the timer is set to
 ///   [a state with a period](TimerState::WithPeriod), it must show that an
 ///   interrupt has occurred as soon as that period of time has elapsed from
@@ -164,6 +198,11 @@ peripheral_trait! {timers,
 ///    + This is perhaps subtle: the count for a timer in [`Repeated`] starts
 ///      again **as soon as the count runs out** and not after the interrupt
 ///      that was raised is actually processed.
+/// - [`FreeRunning`]: after being set to this mode, when the timer is set to a
+///   [state with a period](TimerState::WithPeriod), it counts up from zero
+///   once every millisecond, wrapping back to zero when the count reaches the
+///   period. A timer in this mode **never** causes interrupts; it is meant to
+///   be [read](Timers::get_count) instead.
 ///
 /// Put simply, when set to [`SingleShot`], a timer will cause **one** (1)
 /// interrupt after a specified length of time; when set to [`Repeated`], it
@@ -198,6 +237,33 @@ peripheral_trait! {timers,
 /// [periodically](TimerMode::Repeated) as described in the section on
 /// [Modes](#modes).
 ///
+/// ## Counts
+///
+/// Every timer keeps a count of the milliseconds that have elapsed since its
+/// period was last started, which can be [read](Timers::get_count) at any
+/// time. For [`Repeated`] and [`FreeRunning`] timers the count wraps back to
+/// zero each time the period elapses; for [`SingleShot`] timers it stops at
+/// the period. [`Disabled`] timers have a count of zero.
+///
+/// ## Input Capture
+///
+/// Each timer can optionally be given a [capture source](CaptureSource): a
+/// [GPIO pin](GpioPin) and the [edge(s)](CaptureEdge) of that pin to watch.
+/// The levels of the pin are [fed to the timer](Timers::capture_input) (by the
+/// simulator) and whenever a matching edge is seen, the timer latches its
+/// current count. The latched count can be [read](Timers::get_capture) until
+/// the next matching edge overwrites it.
+///
+/// Setting a timer's capture source (even to the current source) clears the
+/// latched count to zero and forgets the last level seen, so the first level
+/// fed to the timer afterwards never counts as an edge. Timers start with no
+/// capture source.
+///
+/// Capturing is independent of the timer's mode and state; a [`Disabled`]
+/// timer simply captures zeros. Pairing input capture with a [`FreeRunning`]
+/// timer and capturing on [both edges](CaptureEdge::Both) makes it possible
+/// to measure the width of pulses on a pin.
+///
 /// # Reasoning
 ///
 /// We provide the [`Timers`](Timers) peripheral to enable LC-3 users to
@@ -215,15 +281,15 @@ peripheral_trait! {timers,
 /// complete and usable set of peripherals and helps us get closer to providing
 /// a minimal but realistic pedagogical computer.
 ///
-/// One notable way we differ from many (most?) real hardware timer peripherals
-/// is that we offer no way to read the running 'count' of a timer. This is
-/// intentional (for the sake of simplicity). We do appreciate that there are
-/// use cases that require this (i.e. measuring the time between events or the
-/// duration of an event) and for those situations we offer the
-/// [`Clock` peripheral](super::Clock).
+/// Like many real hardware timer peripherals, we also allow the running
+/// 'count' of a timer to be read and latched by external events. This covers
+/// use cases like measuring the time between events or the duration of an
+/// event with more precision than polling the [`Clock` peripheral](super::Clock)
+/// allows.
 ///
 /// [`SingleShot`]: TimerMode::SingleShot
 /// [`Repeated`]: TimerMode::Repeated
+/// [`FreeRunning`]: TimerMode::FreeRunning
 /// [`Disabled`]: TimerState::Disabled
 /// [`Clock`]: super::Clock
 ///
@@ -254,12 +320,31 @@ pub trait Timers<'a>: Default {
         states
     }
 
+    // Counting and input capture are optional; the defaults below are for
+    // implementations that don't support them: counts read as zero and
+    // capture sources are ignored.
+    #[inline]
+    fn get_count(&self, _timer: TimerId) -> Word { 0 }
+
+    #[inline]
+    fn set_capture_source(&mut self, _timer: TimerId, _source: Option<CaptureSource>) { }
+    #[inline]
+    fn get_capture_source(&self, _timer: TimerId) -> Option<CaptureSource> { None }
+    #[inline]
+    fn has_capture_sources(&self) -> bool {
+        TIMERS.iter().any(|t| self.get_capture_source(*t).is_some())
+    }
+    #[inline]
+    fn capture_input(&mut self, _timer: TimerId, _level: bool) { }
+    #[inline]
+    fn get_capture(&self, _timer: TimerId) -> Word { 0 }
+
     fn register_interrupt_flags(&mut self, flags: &'a TimerArr<AtomicBool>);
     fn interrupt_occurred(&self, timer: TimerId) -> bool;
     fn reset_interrupt_flag(&mut self, timer: TimerId);
     #[inline]
     fn interrupts_enabled(&self, timer: TimerId) -> bool {
-        matches!(self.get_state(timer), TimerState::WithPeriod(_)) ||
+        (matches!(self.get_state(timer), TimerState::WithPeriod(_)) && self.get_mode(timer) != TimerMode::FreeRunning) ||
         (self.get_state(timer) == TimerState::Disabled && self.interrupt_occurred(timer))
     }
 }}
@@ -287,6 +372,30 @@ using_std! {
             RwLock::read(self).unwrap().get_state(timer)
         }
 
+        fn get_count(&self, timer: TimerId) -> Word {
+            RwLock::read(self).unwrap().get_count(timer)
+        }
+
+        fn set_capture_source(&mut self, timer: TimerId, source: Option<CaptureSource>) {
+            RwLock::write(self).unwrap().set_capture_source(timer, source)
+        }
+
+        fn get_capture_source(&self, timer: TimerId) -> Option<CaptureSource> {
+            RwLock::read(self).unwrap().get_capture_source(timer)
+        }
+
+        fn has_capture_sources(&self) -> bool {
+            RwLock::read(self).unwrap().has_capture_sources()
+        }
+
+        fn capture_input(&mut self, timer: TimerId, level: bool) {
+            RwLock::write(self).unwrap().capture_input(timer, level)
+        }
+
+        fn get_capture(&self, timer: TimerId) -> Word {
+            RwLock::read(self).unwrap().get_capture(timer)
+        }
+
         fn register_interrupt_flags(&mut self, flags: &'a TimerArr<AtomicBool>) {
             RwLock::write(self).unwrap().register_interrupt_flags(flags)
         }
@@ -321,6 +430,30 @@ using_std! {
             Mutex::lock(self).unwrap().get_state(timer)
         }
 
+        fn get_count(&self, timer: TimerId) -> Word {
+            Mutex::lock(self).unwrap().get_count(timer)
+        }
+
+        fn set_capture_source(&mut self, timer: TimerId, source: Option<CaptureSource>) {
+            Mutex::lock(self).unwrap().set_capture_source(timer, source)
+        }
+
+        fn get_capture_source(&self, timer: TimerId) -> Option<CaptureSource> {
+            Mutex::lock(self).unwrap().get_capture_source(timer)
+        }
+
+        fn has_capture_sources(&self) -> bool {
+            Mutex::lock(self).unwrap().has_capture_sources()
+        }
+
+        fn capture_input(&mut self, timer: TimerId, level: bool) {
+            Mutex::lock(self).unwrap().capture_input(timer, level)
+        }
+
+        fn get_capture(&self, timer: TimerId) -> Word {
+            Mutex::lock(self).unwrap().get_capture(timer)
+        }
+
         fn register_interrupt_flags(&mut self, flags: &'a TimerArr<AtomicBool>) {
             Mutex::lock(self).unwrap().register_interrupt_flags(flags)
         }